@@ -0,0 +1,207 @@
+//! Additive synthesis.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use core::marker::PhantomData;
+use numeric_array::typenum::*;
+
+/// Sine partial of an additive synthesizer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Partial<F: Real> {
+    /// Frequency as a ratio to the fundamental frequency.
+    pub ratio: F,
+    /// Amplitude of the partial.
+    pub amplitude: F,
+}
+
+impl<F: Real> Partial<F> {
+    /// Partial at frequency `ratio` times the fundamental with amplitude `amplitude`.
+    pub fn new(ratio: F, amplitude: F) -> Self {
+        Self { ratio, amplitude }
+    }
+}
+
+/// Setting of `Additive`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdditiveSetting<F: Real> {
+    /// Set partial (index, partial).
+    Partial(usize, Partial<F>),
+    /// Set amplitude of partial (index, amplitude).
+    Amplitude(usize, F),
+    /// Set inharmonicity coefficient.
+    Inharmonicity(F),
+}
+
+impl<F: Real> Default for AdditiveSetting<F> {
+    fn default() -> Self {
+        AdditiveSetting::Inharmonicity(F::zero())
+    }
+}
+
+/// Additive synthesizer made of sine partials. Partials can be changed,
+/// added and removed at runtime. With inharmonicity coefficient `B`, partial
+/// frequencies are stretched as in stiff strings: a partial with ratio `r` sounds at
+/// `r * sqrt(1 + B * r * r)` times the fundamental.
+/// Partials above the Nyquist frequency are silent.
+/// Setting: `AdditiveSetting`.
+/// - Input 0: fundamental frequency in Hz
+/// - Output 0: sum of partials
+#[derive(Clone)]
+pub struct Additive<T: Float, F: Real> {
+    partials: Vec<Partial<F>>,
+    /// Phases of the partials in 0...1.
+    phase: Vec<F>,
+    inharmonicity: F,
+    sample_duration: F,
+    hash: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Float, F: Real> Additive<T, F> {
+    pub fn new(sample_rate: f64, partials: &[Partial<F>]) -> Self {
+        let mut node = Self {
+            partials: partials.to_vec(),
+            phase: vec![F::zero(); partials.len()],
+            inharmonicity: F::zero(),
+            sample_duration: F::zero(),
+            hash: 0,
+            _marker: PhantomData,
+        };
+        node.reset();
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Number of partials.
+    #[inline]
+    pub fn partials(&self) -> usize {
+        self.partials.len()
+    }
+
+    /// Partial `i`.
+    #[inline]
+    pub fn partial(&self, i: usize) -> &Partial<F> {
+        &self.partials[i]
+    }
+
+    /// Set partial `i`. The phase of the partial is retained.
+    #[inline]
+    pub fn set_partial(&mut self, i: usize, partial: Partial<F>) {
+        self.partials[i] = partial;
+    }
+
+    /// Set amplitude of partial `i`.
+    #[inline]
+    pub fn set_amplitude(&mut self, i: usize, amplitude: F) {
+        self.partials[i].amplitude = amplitude;
+    }
+
+    /// Inharmonicity coefficient.
+    #[inline]
+    pub fn inharmonicity(&self) -> F {
+        self.inharmonicity
+    }
+
+    /// Set inharmonicity coefficient (0 = harmonic, positive values stretch partials).
+    #[inline]
+    pub fn set_inharmonicity(&mut self, inharmonicity: F) {
+        self.inharmonicity = inharmonicity;
+    }
+
+    /// Add a partial after the existing partials.
+    /// - Allocates: partial storage.
+    pub fn push_partial(&mut self, partial: Partial<F>) {
+        self.partials.push(partial);
+        self.phase
+            .push(F::from_f64(rnd(self.hash as i64 + self.phase.len() as i64)));
+    }
+
+    /// Remove partial `i`.
+    pub fn remove_partial(&mut self, i: usize) -> Partial<F> {
+        self.phase.remove(i);
+        self.partials.remove(i)
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for Additive<T, F> {
+    const ID: u64 = 111;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = AdditiveSetting<F>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            AdditiveSetting::Partial(i, partial) => self.set_partial(i, partial),
+            AdditiveSetting::Amplitude(i, amplitude) => self.set_amplitude(i, amplitude),
+            AdditiveSetting::Inharmonicity(inharmonicity) => self.set_inharmonicity(inharmonicity),
+        }
+    }
+
+    fn reset(&mut self) {
+        for (i, phase) in self.phase.iter_mut().enumerate() {
+            *phase = F::from_f64(rnd(self.hash as i64 + i as i64));
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = convert(1.0 / sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let delta: F = convert::<T, F>(input[0]) * self.sample_duration;
+        let nyquist = F::from_f64(0.5);
+        let mut output = F::zero();
+        for (partial, phase) in self.partials.iter().zip(self.phase.iter_mut()) {
+            let ratio = partial.ratio * sqrt(F::one() + self.inharmonicity * partial.ratio * partial.ratio);
+            let increment = delta * ratio;
+            *phase += increment;
+            *phase -= floor(*phase);
+            if abs(increment) < nyquist {
+                output += partial.amplitude * sin(*phase * F::from_f64(TAU));
+            }
+        }
+        [convert(output)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "inharmonicity",
+            ParameterUnit::Unitless,
+            0.0,
+            0.01,
+            0.0,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        assert!(index == 0);
+        self.inharmonicity.to_f64()
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        assert!(index == 0);
+        self.set_inharmonicity(F::from_f64(value));
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
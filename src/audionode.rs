@@ -5,9 +5,12 @@ use super::combinator::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
+#[cfg(not(feature = "std"))]
+use alloc::format;
+use core::marker::PhantomData;
 use num_complex::Complex64;
 use numeric_array::typenum::*;
-use std::marker::PhantomData;
+use tinyvec::TinyVec;
 
 /// Type-level integer.
 pub trait Size<T>: numeric_array::ArrayLength<T> + Sync + Send + Clone {}
@@ -34,6 +37,9 @@ Order of type arguments in nodes:
 pub trait AudioNode: Clone + Sync + Send {
     /// Unique ID for hashing.
     const ID: u64;
+    /// Whether the outputs depend only on the current inputs, with no internal state.
+    /// Stateless nodes need not be processed when skipping (see `skip`).
+    const STATELESS: bool = false;
     /// Sample type for input and output.
     type Sample: Float;
     /// Input arity.
@@ -102,7 +108,7 @@ pub trait AudioNode: Clone + Sync + Send {
         input: &Frame<Self::Sample, Self::Inputs>,
     ) -> Frame<Self::Sample, Self::Outputs>;
 
-    /// Process up to 64 (`MAX_BUFFER_SIZE`) samples.
+    /// Process up to `MAX_BUFFER_SIZE` (64 by default) samples.
     /// The number of input and output buffers must match the number of inputs and outputs, respectively.
     /// All input and output buffers must be at least as large as `size`.
     /// If `size` is zero then this is a no-op, which is permitted.
@@ -139,6 +145,45 @@ pub trait AudioNode: Clone + Sync + Send {
         }
     }
 
+    /// Process up to `MAX_BUFFER_SIZE` (64 by default) samples in place.
+    /// The node must have an equal number of inputs and outputs.
+    /// The buffers contain the input on entry and hold the output on return.
+    /// Serial combinators use this to avoid copying through intermediate buffers.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut node = pass() >> mul(2.0) >> add(1.0);
+    /// let mut buffer = Buffer::with_channels(1);
+    /// buffer.mut_at(0)[0] = 1.0;
+    /// node.process_inplace(1, buffer.self_mut());
+    /// assert_eq!(buffer.at(0)[0], 3.0);
+    /// ```
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        // The default implementation is a fallback that copies the input aside and calls into `process`.
+        process_inplace_fallback(self, size, buffers);
+    }
+
+    /// Advance the node by `samples` samples as if processing a constant `input`,
+    /// discarding the output. There is no limit on `samples`.
+    /// Oscillators, envelopes, delays and wave players jump their state directly.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut node = sine_hz(440.0);
+    /// let mut node2 = node.clone();
+    /// node.skip(100, &Frame::default());
+    /// for _ in 0..100 {
+    ///     node2.get_mono();
+    /// }
+    /// assert!((node.get_mono() - node2.get_mono()).abs() < 1.0e-6);
+    /// ```
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        // The default implementation is a fallback that processes blocks of constant input.
+        skip_fallback(self, samples, input);
+    }
+
     /// Set node pseudorandom phase hash.
     /// This is called from `ping` (only). It should not be called by users.
     /// The node is allowed to reset itself here.
@@ -184,8 +229,76 @@ pub trait AudioNode: Clone + Sync + Send {
         // The default implementation does nothing.
     }
 
+    /// Describe the structure of this node for debugging.
+    /// Nodes that contain other nodes should override this to include them as children.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut node = dc(440.0) >> sine();
+    /// let info = node.describe();
+    /// assert_eq!(info.name, "Pipe");
+    /// assert_eq!(info.children.len(), 2);
+    /// println!("{}", info);
+    /// ```
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self)
+    }
+
+    /// Describe the runtime parameters of this node, including those of contained nodes.
+    /// Parameters are addressed by their index in this list.
+    /// Nodes with parameters should override all the parameter methods.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut node = saw() >> lowpass_hz(1000.0, 1.0) >> pan(0.0);
+    /// let parameters = node.parameters();
+    /// assert_eq!(parameters.len(), 3);
+    /// assert_eq!(parameters[0].name, "cutoff");
+    /// assert_eq!(parameters[0].unit, ParameterUnit::Hz);
+    /// node.set_parameter(2, -0.5);
+    /// assert_eq!(node.parameter(2), -0.5);
+    /// ```
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        Vec::new()
+    }
+
+    /// Number of runtime parameters, including those of contained nodes.
+    fn parameter_count(&self) -> usize {
+        0
+    }
+
+    /// Current value of parameter `index`.
+    #[allow(unused_variables)]
+    fn parameter(&self, index: usize) -> f64 {
+        panic!("Parameter index out of range.")
+    }
+
+    /// Set parameter `index` to `value`.
+    #[allow(unused_variables)]
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        panic!("Parameter index out of range.")
+    }
+
     // End of interface. There is no need to override the following.
 
+    /// Index of the first parameter named `name`, if any.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut node = sine_hz(110.0) >> pan(0.0);
+    /// let index = node.parameter_index("pan").unwrap();
+    /// node.set_parameter(index, 1.0);
+    /// assert_eq!(node.parameter(index), 1.0);
+    /// ```
+    fn parameter_index(&self, name: &str) -> Option<usize> {
+        self.parameters()
+            .iter()
+            .position(|parameter| parameter.name == name)
+    }
+
     /// Number of inputs.
     ///
     /// ### Example
@@ -356,6 +469,265 @@ pub trait AudioNode: Clone + Sync + Send {
     }
 }
 
+/// Structural description of a node and the nodes it contains.
+/// Returned from `describe`.
+#[derive(Clone, Debug, Default)]
+pub struct NodeInfo {
+    /// Type name of the node without module path or type arguments.
+    pub name: String,
+    /// Type ID of the node.
+    pub id: u64,
+    /// Number of inputs.
+    pub inputs: usize,
+    /// Number of outputs.
+    pub outputs: usize,
+    /// Latency in samples, if any.
+    pub latency: Option<f64>,
+    /// Descriptions of contained nodes.
+    pub children: Vec<NodeInfo>,
+}
+
+impl NodeInfo {
+    /// Create a description without children.
+    pub fn new(name: &str, id: u64, inputs: usize, outputs: usize, latency: Option<f64>) -> Self {
+        Self {
+            name: String::from(name),
+            id,
+            inputs,
+            outputs,
+            latency,
+            children: Vec::new(),
+        }
+    }
+
+    /// Describe `node` without looking inside it.
+    pub fn from_node<X: AudioNode>(node: &mut X) -> Self {
+        Self::new(
+            short_type_name::<X>(),
+            X::ID,
+            node.inputs(),
+            node.outputs(),
+            node.latency(),
+        )
+    }
+
+    /// Add `children` to the description.
+    pub fn with_children(mut self, children: Vec<NodeInfo>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Total number of nodes in the tree, including this one.
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(|x| x.size()).sum::<usize>()
+    }
+
+    fn write_indented(&self, f: &mut core::fmt::Formatter<'_>, depth: usize) -> core::fmt::Result {
+        write!(
+            f,
+            "{:indent$}{} #{} ({} -> {}",
+            "",
+            self.name,
+            self.id,
+            self.inputs,
+            self.outputs,
+            indent = depth * 2
+        )?;
+        match self.latency {
+            Some(latency) => writeln!(f, ", latency {:.1})", latency)?,
+            None => writeln!(f, ")")?,
+        }
+        for child in self.children.iter() {
+            child.write_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Display for NodeInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+/// Unit of a parameter value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParameterUnit {
+    /// Plain number.
+    #[default]
+    Unitless,
+    /// Frequency in Hz.
+    Hz,
+    /// Time in seconds.
+    Seconds,
+    /// Pitch interval in cents.
+    Cents,
+    /// Linear amplitude gain.
+    Amplitude,
+    /// Level in decibels.
+    Decibels,
+}
+
+/// Description of a runtime parameter. Returned from `parameters`.
+/// Unbounded parameters have infinite limits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterInfo {
+    /// Name of the parameter.
+    pub name: String,
+    /// Unit of the parameter.
+    pub unit: ParameterUnit,
+    /// Minimum value.
+    pub min: f64,
+    /// Maximum value.
+    pub max: f64,
+    /// Default value.
+    pub default: f64,
+}
+
+impl ParameterInfo {
+    /// Create a parameter description.
+    pub fn new(name: &str, unit: ParameterUnit, min: f64, max: f64, default: f64) -> Self {
+        Self {
+            name: String::from(name),
+            unit,
+            min,
+            max,
+            default,
+        }
+    }
+}
+
+/// Value of parameter `index` of the pair `x`, `y`. Parameters of `x` come first.
+pub(crate) fn pair_parameter<X: AudioNode, Y: AudioNode>(x: &X, y: &Y, index: usize) -> f64 {
+    let count = x.parameter_count();
+    if index < count {
+        x.parameter(index)
+    } else {
+        y.parameter(index - count)
+    }
+}
+
+/// Set parameter `index` of the pair `x`, `y`. Parameters of `x` come first.
+pub(crate) fn set_pair_parameter<X: AudioNode, Y: AudioNode>(
+    x: &mut X,
+    y: &mut Y,
+    index: usize,
+    value: f64,
+) {
+    let count = x.parameter_count();
+    if index < count {
+        x.set_parameter(index, value);
+    } else {
+        y.set_parameter(index - count, value);
+    }
+}
+
+/// Value of parameter `index` of the nodes `x` in order.
+pub(crate) fn array_parameter<X: AudioNode>(x: &[X], index: usize) -> f64 {
+    let mut index = index;
+    for node in x {
+        let count = node.parameter_count();
+        if index < count {
+            return node.parameter(index);
+        }
+        index -= count;
+    }
+    panic!("Parameter index out of range.")
+}
+
+/// Set parameter `index` of the nodes `x` in order.
+pub(crate) fn set_array_parameter<X: AudioNode>(x: &mut [X], index: usize, value: f64) {
+    let mut index = index;
+    for node in x {
+        let count = node.parameter_count();
+        if index < count {
+            node.set_parameter(index, value);
+            return;
+        }
+        index -= count;
+    }
+    panic!("Parameter index out of range.")
+}
+
+/// Maximum number of channels `process_inplace_fallback` copies aside on the stack.
+pub(crate) const INPLACE_CHANNELS: usize = 8;
+
+/// Process `node` in place by copying the input aside and calling `process`.
+/// Wide nodes are processed one sample at a time instead.
+pub(crate) fn process_inplace_fallback<X: AudioNode>(
+    node: &mut X,
+    size: usize,
+    buffers: &mut [&mut [X::Sample]],
+) {
+    assert!(node.inputs() == node.outputs());
+    debug_assert!(size <= MAX_BUFFER_SIZE);
+    debug_assert!(buffers.len() == node.inputs());
+    debug_assert!(buffers.iter().all(|x| x.len() >= size));
+    let channels = buffers.len();
+    if channels <= INPLACE_CHANNELS {
+        let mut scratch = [[X::Sample::zero(); MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+        for (x, y) in scratch.iter_mut().zip(buffers.iter()) {
+            x[..size].copy_from_slice(&y[..size]);
+        }
+        let input: [&[X::Sample]; INPLACE_CHANNELS] = core::array::from_fn(|i| &scratch[i][..]);
+        node.process(size, &input[..channels], buffers);
+    } else {
+        for i in 0..size {
+            let result = node.tick(&Frame::generate(|j| buffers[j][i]));
+            for (x, y) in buffers.iter_mut().zip(result.iter()) {
+                (*x)[i] = *y;
+            }
+        }
+    }
+}
+
+/// Advance `node` by `samples` samples of constant `input` by calling `process` in blocks.
+/// Wide nodes are ticked one sample at a time instead.
+pub(crate) fn skip_fallback<X: AudioNode>(
+    node: &mut X,
+    samples: usize,
+    input: &Frame<X::Sample, X::Inputs>,
+) {
+    if X::Inputs::USIZE <= INPLACE_CHANNELS && X::Outputs::USIZE <= INPLACE_CHANNELS {
+        let mut input_scratch = [[X::Sample::zero(); MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+        let mut output_scratch = [[X::Sample::zero(); MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+        for (x, y) in input_scratch.iter_mut().zip(input.iter()) {
+            x.fill(*y);
+        }
+        let input_ref: [&[X::Sample]; INPLACE_CHANNELS] =
+            core::array::from_fn(|i| &input_scratch[i][..]);
+        let mut output_ref: TinyVec<[&mut [X::Sample]; INPLACE_CHANNELS]> = output_scratch
+            .iter_mut()
+            .take(X::Outputs::USIZE)
+            .map(|x| &mut x[..])
+            .collect();
+        let mut samples = samples;
+        while samples > 0 {
+            let length = min(samples, MAX_BUFFER_SIZE);
+            node.process(length, &input_ref[..X::Inputs::USIZE], &mut output_ref);
+            samples -= length;
+        }
+    } else {
+        for _ in 0..samples {
+            node.tick(input);
+        }
+    }
+}
+
+/// Reborrow mutable buffers as input buffers.
+#[inline]
+pub(crate) fn shared_buffers<'a, T>(buffers: &'a [&mut [T]]) -> &'a [&'a [T]] {
+    // Safety: `&mut [T]` and `&[T]` have the same layout, and the result is a shared borrow.
+    unsafe { &*(buffers as *const [&mut [T]] as *const [&[T]]) }
+}
+
+/// Type name of `T` without module path or type arguments.
+pub fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = core::any::type_name::<T>();
+    let name = &name[..name.find('<').unwrap_or(name.len())];
+    name.rsplit("::").next().unwrap_or(name)
+}
+
 /// Pass through inputs unchanged.
 #[derive(Default, Clone)]
 pub struct MultiPass<N, T> {
@@ -370,6 +742,7 @@ impl<N: Size<T>, T: Float> MultiPass<N, T> {
 
 impl<N: Size<T>, T: Float> AudioNode for MultiPass<N, T> {
     const ID: u64 = 0;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = N;
     type Outputs = N;
@@ -392,6 +765,9 @@ impl<N: Size<T>, T: Float> AudioNode for MultiPass<N, T> {
             output[i][..size].clone_from_slice(&input[i][..size]);
         }
     }
+    fn process_inplace(&mut self, _size: usize, _buffers: &mut [&mut [Self::Sample]]) {
+        // Nothing to do.
+    }
     fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
         input.clone()
     }
@@ -413,6 +789,7 @@ impl<T: Float> Pass<T> {
 // because it helps a little with type inference.
 impl<T: Float> AudioNode for Pass<T> {
     const ID: u64 = 48;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = U1;
     type Outputs = U1;
@@ -433,6 +810,9 @@ impl<T: Float> AudioNode for Pass<T> {
     ) {
         output[0][..size].clone_from_slice(&input[0][..size]);
     }
+    fn process_inplace(&mut self, _size: usize, _buffers: &mut [&mut [Self::Sample]]) {
+        // Nothing to do.
+    }
     fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
         input.clone()
     }
@@ -452,6 +832,7 @@ impl<N: Size<T>, T: Float> Sink<N, T> {
 
 impl<N: Size<T>, T: Float> AudioNode for Sink<N, T> {
     const ID: u64 = 1;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = N;
     type Outputs = U0;
@@ -503,6 +884,7 @@ impl<N: Size<T>, T: Float> Constant<N, T> {
 
 impl<N: Size<T>, T: Float> AudioNode for Constant<N, T> {
     const ID: u64 = 2;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = U0;
     type Outputs = N;
@@ -521,6 +903,8 @@ impl<N: Size<T>, T: Float> AudioNode for Constant<N, T> {
         self.output.clone()
     }
 
+    fn skip(&mut self, _samples: usize, _input: &Frame<Self::Sample, Self::Inputs>) {}
+
     fn process(
         &mut self,
         size: usize,
@@ -532,6 +916,37 @@ impl<N: Size<T>, T: Float> AudioNode for Constant<N, T> {
         }
     }
 
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        (0..N::USIZE)
+            .map(|i| {
+                let name = if N::USIZE == 1 {
+                    String::from("value")
+                } else {
+                    format!("value {}", i)
+                };
+                ParameterInfo::new(
+                    &name,
+                    ParameterUnit::Unitless,
+                    f64::NEG_INFINITY,
+                    f64::INFINITY,
+                    0.0,
+                )
+            })
+            .collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        N::USIZE
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.output[index].to_f64()
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.output[index] = T::from_f64(value);
+    }
+
     fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
         let mut output = new_signal_frame(self.outputs());
         for i in 0..N::USIZE {
@@ -568,6 +983,7 @@ where
     T: Float,
 {
     const ID: u64 = 40;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = U1;
     type Outputs = N;
@@ -624,6 +1040,7 @@ where
     T: Float,
 {
     const ID: u64 = 38;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = M;
     type Outputs = numeric_array::typenum::Prod<M, N>;
@@ -676,6 +1093,7 @@ where
     T: Float,
 {
     const ID: u64 = 41;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = N;
     type Outputs = U1;
@@ -746,6 +1164,7 @@ where
     T: Float,
 {
     const ID: u64 = 39;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = numeric_array::typenum::Prod<M, N>;
     type Outputs = M;
@@ -982,6 +1401,7 @@ where
     <X::Inputs as Add<Y::Inputs>>::Output: Size<T>,
 {
     const ID: u64 = 3;
+    const STATELESS: bool = X::STATELESS && Y::STATELESS;
     type Sample = T;
     type Inputs = Sum<X::Inputs, Y::Inputs>;
     type Outputs = X::Outputs;
@@ -1051,10 +1471,32 @@ where
     }
 
     fn allocate(&mut self) {
-        self.buffer.resize(self.outputs());
+        self.buffer.allocate(self.outputs());
         self.x.allocate();
         self.y.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
 }
 
 /// Provides unary operator implementations to the `Unop` node.
@@ -1232,6 +1674,7 @@ where
     X::Outputs: Size<T>,
 {
     const ID: u64 = 4;
+    const STATELESS: bool = X::STATELESS;
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = X::Outputs;
@@ -1270,6 +1713,14 @@ where
         }
     }
 
+    #[inline]
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        self.x.process_inplace(size, buffers);
+        for i in 0..self.outputs() {
+            self.u.assign(size, buffers[i]);
+        }
+    }
+
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.x.ping(probe, hash.hash(Self::ID))
     }
@@ -1285,6 +1736,26 @@ where
     fn allocate(&mut self) {
         self.x.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
 }
 
 /// Map any number of channels.
@@ -1321,6 +1792,7 @@ where
     O::Size: Size<T>,
 {
     const ID: u64 = 5;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = I;
     type Outputs = O::Size;
@@ -1430,6 +1902,7 @@ where
     Y::Outputs: Size<T>,
 {
     const ID: u64 = 6;
+    const STATELESS: bool = X::STATELESS && Y::STATELESS;
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = Y::Outputs;
@@ -1460,6 +1933,27 @@ where
         self.y.tick(&self.x.tick(input))
     }
 
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        // If the first node is stateless and outputs constants, we can skip the second node, too.
+        // Stateful nodes such as delays may route constants that they output only later.
+        if X::STATELESS {
+            let mut signal = new_signal_frame(self.inputs());
+            for (x, y) in signal.iter_mut().zip(input.iter()) {
+                *x = Signal::Value(y.to_f64());
+            }
+            let signal = self.x.route(&signal, 0.0);
+            if (0..X::Outputs::USIZE).all(|i| matches!(signal[i], Signal::Value(_))) {
+                let input_y = Frame::generate(|i| match signal[i] {
+                    Signal::Value(value) => convert(value),
+                    _ => T::zero(),
+                });
+                self.y.skip(samples, &input_y);
+                return;
+            }
+        }
+        skip_fallback(self, samples, input);
+    }
+
     fn process(
         &mut self,
         size: usize,
@@ -1471,6 +1965,20 @@ where
         self.y.process(size, self.buffer.self_ref(), output);
     }
 
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        if self.x.inputs() == self.x.outputs() {
+            self.x.process_inplace(size, buffers);
+            self.y.process_inplace(size, buffers);
+        } else {
+            self.x.process(
+                size,
+                shared_buffers(buffers),
+                self.buffer.get_mut(self.x.outputs()),
+            );
+            self.y.process(size, self.buffer.self_ref(), buffers);
+        }
+    }
+
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
     }
@@ -1480,10 +1988,32 @@ where
     }
 
     fn allocate(&mut self) {
-        self.buffer.resize(self.x.outputs());
+        self.buffer.allocate(self.x.outputs());
         self.x.allocate();
         self.y.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
 }
 
 /// Stack `X` and `Y` in parallel.
@@ -1555,6 +2085,7 @@ where
     <X::Outputs as Add<Y::Outputs>>::Output: Size<T>,
 {
     const ID: u64 = 7;
+    const STATELESS: bool = X::STATELESS && Y::STATELESS;
     type Sample = T;
     type Inputs = Sum<X::Inputs, Y::Inputs>;
     type Outputs = Sum<X::Outputs, Y::Outputs>;
@@ -1594,6 +2125,12 @@ where
             }
         })
     }
+
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        self.x.skip(samples, input[..X::Inputs::USIZE].into());
+        self.y.skip(samples, input[X::Inputs::USIZE..].into());
+    }
+
     fn process(
         &mut self,
         size: usize,
@@ -1611,6 +2148,15 @@ where
             &mut output[X::Outputs::USIZE..],
         );
     }
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        if X::Inputs::USIZE == X::Outputs::USIZE {
+            let (buffers_x, buffers_y) = buffers.split_at_mut(X::Inputs::USIZE);
+            self.x.process_inplace(size, buffers_x);
+            self.y.process_inplace(size, buffers_y);
+        } else {
+            process_inplace_fallback(self, size, buffers);
+        }
+    }
 
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
@@ -1632,6 +2178,28 @@ where
         self.x.allocate();
         self.y.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
 }
 
 /// Send the same input to `X` and `Y`. Concatenate outputs.
@@ -1699,6 +2267,7 @@ where
     <X::Outputs as Add<Y::Outputs>>::Output: Size<T>,
 {
     const ID: u64 = 8;
+    const STATELESS: bool = X::STATELESS && Y::STATELESS;
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = Sum<X::Outputs, Y::Outputs>;
@@ -1736,6 +2305,12 @@ where
             }
         })
     }
+
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        self.x.skip(samples, input);
+        self.y.skip(samples, input);
+    }
+
     fn process(
         &mut self,
         size: usize,
@@ -1764,6 +2339,28 @@ where
         self.x.allocate();
         self.y.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
 }
 
 /// Mix together `X` and `Y` sourcing from the same inputs.
@@ -1836,6 +2433,7 @@ where
     Y::Outputs: Size<T>,
 {
     const ID: u64 = 10;
+    const STATELESS: bool = X::STATELESS && Y::STATELESS;
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = X::Outputs;
@@ -1868,6 +2466,11 @@ where
         output_x + output_y
     }
 
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        self.x.skip(samples, input);
+        self.y.skip(samples, input);
+    }
+
     fn process(
         &mut self,
         size: usize,
@@ -1901,50 +2504,602 @@ where
     }
 
     fn allocate(&mut self) {
-        self.buffer.resize(self.outputs());
+        self.buffer.allocate(self.outputs());
         self.x.allocate();
         self.y.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
 }
 
-/// Pass through inputs without matching outputs.
-/// Adjusts output arity to match input arity, adapting a filter to a pipeline.
+/// Mix together `X` and `Y` sourcing from the same inputs.
+/// The branch with less latency is delayed to match the other,
+/// rounded to the nearest sample, so the branches stay phase-aligned.
 #[derive(Clone)]
-pub struct Thru<X: AudioNode> {
+pub struct Compensate<T, X, Y>
+where
+    T: Float,
+{
+    _marker: PhantomData<T>,
     x: X,
-    buffer: Buffer<X::Sample>,
+    y: Y,
+    buffer: Buffer<T>,
+    /// Whether the delay is applied to `X` (true) or `Y` (false).
+    delay_x: bool,
+    /// Compensating delay in samples.
+    length: usize,
+    /// Delay lines, one for each output channel.
+    line: Vec<Vec<T>>,
+    index: usize,
+    sample_rate: f64,
 }
 
-impl<X: AudioNode> Thru<X> {
-    pub fn new(x: X) -> Self {
-        let mut node = Thru {
+impl<T, X, Y> Compensate<T, X, Y>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    Y: AudioNode<Sample = T, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+    Y::Inputs: Size<T>,
+    Y::Outputs: Size<T>,
+{
+    pub fn new(x: X, y: Y) -> Self {
+        let mut node = Compensate {
+            _marker: PhantomData,
             x,
+            y,
             buffer: Buffer::new(),
+            delay_x: false,
+            length: 0,
+            line: Vec::new(),
+            index: 0,
+            sample_rate: DEFAULT_SR,
         };
         let hash = node.ping(true, AttoHash::new(Self::ID));
         node.ping(false, hash);
+        node.measure();
         node
     }
-}
-
-impl<X: AudioNode> AudioNode for Thru<X> {
-    const ID: u64 = 12;
-    type Sample = X::Sample;
-    type Inputs = X::Inputs;
-    type Outputs = X::Inputs;
-    type Setting = X::Setting;
 
-    fn set(&mut self, setting: Self::Setting) {
-        self.x.set(setting);
+    /// Compensating delay in samples.
+    #[inline]
+    pub fn compensation(&self) -> usize {
+        self.length
+    }
+
+    /// Measure latencies of the branches and resize delay lines to match.
+    fn measure(&mut self) {
+        let latency_x = self.x.latency().unwrap_or(0.0);
+        let latency_y = self.y.latency().unwrap_or(0.0);
+        self.delay_x = latency_x < latency_y;
+        self.length = round(abs(latency_x - latency_y)) as usize;
+        self.line.resize(self.outputs(), Vec::new());
+        for line in self.line.iter_mut() {
+            line.clear();
+            line.resize(self.length, T::zero());
+        }
+        self.index = 0;
     }
+}
 
-    fn reset(&mut self) {
-        self.x.reset();
+/// Delay `size` samples of `signal` in place with delay lines `line` of `length` samples,
+/// starting at `index`.
+#[inline]
+fn delay_in_place<T: Float>(
+    line: &mut [Vec<T>],
+    length: usize,
+    index: usize,
+    size: usize,
+    signal: &mut [&mut [T]],
+) {
+    for (channel, line) in line.iter_mut().enumerate() {
+        let mut i = index;
+        for x in signal[channel][..size].iter_mut() {
+            core::mem::swap(&mut line[i], x);
+            i += 1;
+            if i == length {
+                i = 0;
+            }
+        }
     }
+}
 
-    fn set_sample_rate(&mut self, sample_rate: f64) {
-        self.x.set_sample_rate(sample_rate);
-    }
+impl<T, X, Y> AudioNode for Compensate<T, X, Y>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    Y: AudioNode<Sample = T, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+    Y::Inputs: Size<T>,
+    Y::Outputs: Size<T>,
+{
+    const ID: u64 = 87;
+    type Sample = T;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = Side<X::Setting, Y::Setting>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            Side::Left(left) => self.x.set(left),
+            Side::Right(right) => self.y.set(right),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.y.reset();
+        for line in self.line.iter_mut() {
+            line.fill(T::zero());
+        }
+        self.index = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.x.set_sample_rate(sample_rate);
+        self.y.set_sample_rate(sample_rate);
+        self.measure();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut output_x = self.x.tick(input);
+        let mut output_y = self.y.tick(input);
+        if self.length > 0 {
+            let delayed = if self.delay_x {
+                &mut output_x
+            } else {
+                &mut output_y
+            };
+            for (x, line) in delayed.iter_mut().zip(self.line.iter_mut()) {
+                core::mem::swap(&mut line[self.index], x);
+            }
+            self.index += 1;
+            if self.index == self.length {
+                self.index = 0;
+            }
+        }
+        output_x + output_y
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+        self.y
+            .process(size, input, self.buffer.get_mut(self.outputs()));
+        if self.length > 0 {
+            if self.delay_x {
+                delay_in_place(&mut self.line, self.length, self.index, size, output);
+            } else {
+                delay_in_place(
+                    &mut self.line,
+                    self.length,
+                    self.index,
+                    size,
+                    self.buffer.self_mut(),
+                );
+            }
+            self.index = (self.index + size) % self.length;
+        }
+        for channel in 0..self.outputs() {
+            for (o, i) in output[channel][..size]
+                .iter_mut()
+                .zip(self.buffer.at(channel)[..size].iter())
+            {
+                *o += *i;
+            }
+        }
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut signal_x = self.x.route(input, frequency);
+        let mut signal_y = self.y.route(input, frequency);
+        let length = self.length as f64;
+        let phase = -TAU * frequency * length / self.sample_rate;
+        let delayed = if self.delay_x {
+            &mut signal_x
+        } else {
+            &mut signal_y
+        };
+        for i in 0..Self::Outputs::USIZE {
+            delayed[i] = delayed[i].filter(length, |r| r * Complex64::from_polar(1.0, phase));
+        }
+        for i in 0..Self::Outputs::USIZE {
+            signal_x[i] = signal_x[i].combine_linear(signal_y[i], 0.0, |x, y| x + y, |x, y| x + y);
+        }
+        signal_x
+    }
+
+    fn allocate(&mut self) {
+        self.buffer.allocate(self.outputs());
+        self.x.allocate();
+        self.y.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
+}
+
+/// Crossfade time in seconds of the `Wet` wrapper.
+const WET_FADE: f64 = 0.01;
+
+/// Setting of a `Wet` wrapper.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WetSetting {
+    /// Set wet amount in 0...1.
+    Amount(f64),
+    /// Bypass (true) or engage (false) the wrapped node.
+    Bypass(bool),
+}
+
+impl Default for WetSetting {
+    fn default() -> Self {
+        WetSetting::Amount(1.0)
+    }
+}
+
+/// Mix the output of `X` with its dry input by a wet amount, with optional bypass.
+/// Changes of the wet amount and bypass state are crossfaded over 10 ms, which avoids clicks.
+/// The dry path is delayed by the latency of `X`, rounded to the nearest sample,
+/// so the paths stay phase-aligned. `X` keeps processing while bypassed,
+/// so its state is current when it is engaged again.
+/// Setting: `WetSetting`.
+#[derive(Clone)]
+pub struct Wet<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    _marker: PhantomData<N>,
+    x: X,
+    buffer: Buffer<T>,
+    /// Wet amount in 0...1.
+    amount: f64,
+    /// Whether the wrapped node is bypassed.
+    bypassed: bool,
+    /// Current wet gain, moving towards the target.
+    gain: f64,
+    /// Maximum change of wet gain per sample.
+    step: f64,
+    /// Dry path delay in samples.
+    length: usize,
+    /// Dry delay lines, one for each channel.
+    line: Vec<Vec<T>>,
+    index: usize,
+    sample_rate: f64,
+}
+
+impl<N, T, X> Wet<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    /// Create a wrapper with wet `amount` in 0...1. If `bypassed`, it starts in bypass.
+    pub fn new(x: X, amount: f64, bypassed: bool) -> Self {
+        let amount = clamp01(amount);
+        let mut node = Wet {
+            _marker: PhantomData,
+            x,
+            buffer: Buffer::new(),
+            amount,
+            bypassed,
+            gain: 0.0,
+            step: 0.0,
+            length: 0,
+            line: Vec::new(),
+            index: 0,
+            sample_rate: DEFAULT_SR,
+        };
+        node.gain = node.target();
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Wet amount in 0...1.
+    #[inline]
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    /// Set wet amount in 0...1.
+    #[inline]
+    pub fn set_amount(&mut self, amount: f64) {
+        self.amount = clamp01(amount);
+    }
+
+    /// Whether the wrapped node is bypassed.
+    #[inline]
+    pub fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+
+    /// Bypass (true) or engage (false) the wrapped node.
+    #[inline]
+    pub fn set_bypass(&mut self, bypassed: bool) {
+        self.bypassed = bypassed;
+    }
+
+    /// Dry path delay in samples.
+    #[inline]
+    pub fn compensation(&self) -> usize {
+        self.length
+    }
+
+    /// Target wet gain.
+    #[inline]
+    fn target(&self) -> f64 {
+        if self.bypassed {
+            0.0
+        } else {
+            self.amount
+        }
+    }
+
+    /// Move wet gain one sample towards the target.
+    #[inline]
+    fn advance(&mut self) -> T {
+        let target = self.target();
+        self.gain += clamp(-self.step, self.step, target - self.gain);
+        T::from_f64(self.gain)
+    }
+
+    /// Measure latency of the wrapped node and resize dry delay lines to match.
+    fn measure(&mut self) {
+        self.length = round(self.x.latency().unwrap_or(0.0)) as usize;
+        self.line.resize(self.inputs(), Vec::new());
+        for line in self.line.iter_mut() {
+            line.clear();
+            line.resize(self.length, T::zero());
+        }
+        self.index = 0;
+    }
+}
+
+impl<N, T, X> AudioNode for Wet<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    const ID: u64 = 161;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = WetSetting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            WetSetting::Amount(amount) => self.set_amount(amount),
+            WetSetting::Bypass(bypassed) => self.set_bypass(bypassed),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        for line in self.line.iter_mut() {
+            line.fill(T::zero());
+        }
+        self.index = 0;
+        self.gain = self.target();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.step = 1.0 / (WET_FADE * sample_rate);
+        self.x.set_sample_rate(sample_rate);
+        self.measure();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let wet = self.x.tick(input);
+        let mut dry = input.clone();
+        if self.length > 0 {
+            for (x, line) in dry.iter_mut().zip(self.line.iter_mut()) {
+                core::mem::swap(&mut line[self.index], x);
+            }
+            self.index += 1;
+            if self.index == self.length {
+                self.index = 0;
+            }
+        }
+        let gain = self.advance();
+        Frame::generate(|i| dry[i] + (wet[i] - dry[i]) * gain)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+        let channels = self.inputs();
+        let dry = self.buffer.get_mut(channels);
+        for channel in 0..channels {
+            dry[channel][..size].copy_from_slice(&input[channel][..size]);
+        }
+        if self.length > 0 {
+            delay_in_place(&mut self.line, self.length, self.index, size, dry);
+            self.index = (self.index + size) % self.length;
+        }
+        for i in 0..size {
+            let gain = self.advance();
+            for channel in 0..channels {
+                let dry = self.buffer.at(channel)[i];
+                output[channel][i] = dry + (output[channel][i] - dry) * gain;
+            }
+        }
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut signal = self.x.route(input, frequency);
+        let gain = self.target();
+        if gain == 1.0 {
+            return signal;
+        }
+        let length = self.length as f64;
+        let phase = -TAU * frequency * length / self.sample_rate;
+        for i in 0..N::USIZE {
+            let dry = input[i]
+                .filter(length, |r| r * Complex64::from_polar(1.0, phase))
+                .scale(1.0 - gain);
+            signal[i] = if gain == 0.0 {
+                dry
+            } else {
+                signal[i]
+                    .scale(gain)
+                    .combine_linear(dry, 0.0, |x, y| x + y, |x, y| x + y)
+            };
+        }
+        signal
+    }
+
+    fn allocate(&mut self) {
+        self.buffer.allocate(self.inputs());
+        self.x.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.push(ParameterInfo::new(
+            "wet",
+            ParameterUnit::Unitless,
+            0.0,
+            1.0,
+            1.0,
+        ));
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + 1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        if index < self.x.parameter_count() {
+            self.x.parameter(index)
+        } else {
+            self.amount
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        if index < self.x.parameter_count() {
+            self.x.set_parameter(index, value);
+        } else {
+            self.set_amount(value);
+        }
+    }
+}
+
+/// Pass through inputs without matching outputs.
+/// Adjusts output arity to match input arity, adapting a filter to a pipeline.
+#[derive(Clone)]
+pub struct Thru<X: AudioNode> {
+    x: X,
+    buffer: Buffer<X::Sample>,
+}
+
+impl<X: AudioNode> Thru<X> {
+    pub fn new(x: X) -> Self {
+        let mut node = Thru {
+            x,
+            buffer: Buffer::new(),
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+}
+
+impl<X: AudioNode> AudioNode for Thru<X> {
+    const ID: u64 = 12;
+    const STATELESS: bool = X::STATELESS;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Inputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+    }
 
     #[inline]
     fn tick(
@@ -2000,10 +3155,30 @@ impl<X: AudioNode> AudioNode for Thru<X> {
 
     fn allocate(&mut self) {
         if X::Inputs::USIZE < X::Outputs::USIZE {
-            self.buffer.resize(X::Outputs::USIZE);
+            self.buffer.allocate(X::Outputs::USIZE);
         }
         self.x.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
 }
 
 /// Mix together a bunch of similar nodes sourcing from the same inputs.
@@ -2065,6 +3240,7 @@ where
     X::Outputs: Size<T>,
 {
     const ID: u64 = 28;
+    const STATELESS: bool = X::STATELESS;
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = X::Outputs;
@@ -2143,11 +3319,32 @@ where
     }
 
     fn allocate(&mut self) {
-        self.buffer.resize(X::Outputs::USIZE);
+        self.buffer.allocate(X::Outputs::USIZE);
         for x in &mut self.x {
             x.allocate();
         }
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.iter().flat_map(|x| x.parameters()).collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.iter().map(|x| x.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        array_parameter(&self.x, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_array_parameter(&mut self.x, index, value);
+    }
 }
 
 /// Stack a bunch of similar nodes in parallel.
@@ -2213,6 +3410,7 @@ where
     <X::Outputs as Mul<N>>::Output: Size<T>,
 {
     const ID: u64 = 30;
+    const STATELESS: bool = X::STATELESS;
     type Sample = T;
     type Inputs = Prod<X::Inputs, N>;
     type Outputs = Prod<X::Outputs, N>;
@@ -2267,6 +3465,15 @@ where
             out_channel = next_out_channel;
         }
     }
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        if X::Inputs::USIZE == X::Outputs::USIZE && X::Inputs::USIZE > 0 {
+            for (x, buffers) in self.x.iter_mut().zip(buffers.chunks_mut(X::Inputs::USIZE)) {
+                x.process_inplace(size, buffers);
+            }
+        } else {
+            process_inplace_fallback(self, size, buffers);
+        }
+    }
     #[inline]
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         let mut hash = hash.hash(Self::ID);
@@ -2298,6 +3505,27 @@ where
             x.allocate();
         }
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.iter().flat_map(|x| x.parameters()).collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.iter().map(|x| x.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        array_parameter(&self.x, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_array_parameter(&mut self.x, index, value);
+    }
 }
 
 /// Combine outputs of a bunch of similar nodes with a binary operation.
@@ -2455,11 +3683,32 @@ where
     }
 
     fn allocate(&mut self) {
-        self.buffer.resize(X::Outputs::USIZE);
+        self.buffer.allocate(X::Outputs::USIZE);
         for x in &mut self.x {
             x.allocate();
         }
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.iter().flat_map(|x| x.parameters()).collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.iter().map(|x| x.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        array_parameter(&self.x, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_array_parameter(&mut self.x, index, value);
+    }
 }
 
 /// Branch into a bunch of similar nodes in parallel.
@@ -2522,6 +3771,7 @@ where
     <X::Outputs as Mul<N>>::Output: Size<T>,
 {
     const ID: u64 = 33;
+    const STATELESS: bool = X::STATELESS;
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = Prod<X::Outputs, N>;
@@ -2596,6 +3846,27 @@ where
             x.allocate();
         }
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.iter().flat_map(|x| x.parameters()).collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.iter().map(|x| x.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        array_parameter(&self.x, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_array_parameter(&mut self.x, index, value);
+    }
 }
 
 /// Chain together a bunch of similar nodes.
@@ -2719,6 +3990,12 @@ where
         }
     }
 
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        for x in self.x.iter_mut() {
+            x.process_inplace(size, buffers);
+        }
+    }
+
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
         let mut hash = hash.hash(Self::ID);
         for x in self.x.iter_mut() {
@@ -2736,12 +4013,33 @@ where
     }
 
     fn allocate(&mut self) {
-        self.buffer_a.resize(X::Outputs::USIZE);
-        self.buffer_b.resize(X::Outputs::USIZE);
+        self.buffer_a.allocate(X::Outputs::USIZE);
+        self.buffer_b.allocate(X::Outputs::USIZE);
         for x in &mut self.x {
             x.allocate();
         }
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.iter().flat_map(|x| x.parameters()).collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.iter().map(|x| x.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        array_parameter(&self.x, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_array_parameter(&mut self.x, index, value);
+    }
 }
 
 /// Reverse channel order.
@@ -2758,6 +4056,7 @@ impl<N: Size<T>, T: Float> Reverse<N, T> {
 
 impl<N: Size<T>, T: Float> AudioNode for Reverse<N, T> {
     const ID: u64 = 45;
+    const STATELESS: bool = true;
     type Sample = T;
     type Inputs = N;
     type Outputs = N;
@@ -2785,6 +4084,98 @@ impl<N: Size<T>, T: Float> AudioNode for Reverse<N, T> {
     }
 }
 
+/// Rearrange `N` input channels into `M` output channels.
+/// Each output copies one input channel or is silent.
+/// Any input can be copied to any number of outputs. The mapping can be changed at runtime.
+/// Setting: (output, source), where source `None` silences the output.
+#[derive(Clone)]
+pub struct Remap<N, M, T> {
+    _marker: PhantomData<(N, M, T)>,
+    /// Source input for each output.
+    source: Vec<Option<usize>>,
+}
+
+impl<N: Size<T>, M: Size<T>, T: Float> Remap<N, M, T> {
+    /// Create remapping where output `i` copies input `mapping[i]`.
+    /// The mapping must have `M` entries.
+    pub fn new(mapping: &[usize]) -> Self {
+        assert_eq!(mapping.len(), M::USIZE);
+        let mut node = Self {
+            _marker: PhantomData,
+            source: vec![None; M::USIZE],
+        };
+        for (output, input) in mapping.iter().enumerate() {
+            node.set_source(output, Some(*input));
+        }
+        node
+    }
+
+    /// Source input of `output`, or `None` if the output is silent.
+    #[inline]
+    pub fn source(&self, output: usize) -> Option<usize> {
+        self.source[output]
+    }
+
+    /// Set `output` to copy input `source`. Source `None` silences the output.
+    #[inline]
+    pub fn set_source(&mut self, output: usize, source: Option<usize>) {
+        assert!(output < M::USIZE);
+        if let Some(input) = source {
+            assert!(input < N::USIZE);
+        }
+        self.source[output] = source;
+    }
+}
+
+impl<N: Size<T>, M: Size<T>, T: Float> AudioNode for Remap<N, M, T> {
+    const ID: u64 = 163;
+    const STATELESS: bool = true;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = M;
+    type Setting = (usize, Option<usize>);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_source(setting.0, setting.1);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        Frame::generate(|i| match self.source[i] {
+            Some(source) => input[source],
+            None => T::zero(),
+        })
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for (i, source) in self.source.iter().enumerate() {
+            match source {
+                Some(source) => output[i][..size].clone_from_slice(&input[*source][..size]),
+                None => output[i][..size].fill(T::zero()),
+            }
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(M::USIZE);
+        for (signal, source) in output.iter_mut().zip(self.source.iter()) {
+            *signal = match source {
+                Some(source) => input[*source],
+                None => Signal::Value(0.0),
+            };
+        }
+        output
+    }
+}
+
 /// `N`-channel impulse. First sample on each channel is one, the rest are zero.
 #[derive(Default, Clone)]
 pub struct Impulse<N, T> {
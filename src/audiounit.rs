@@ -6,12 +6,13 @@ use super::combinator::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
+use core::fmt::Write;
+use core::marker::PhantomData;
 use duplicate::duplicate_item;
 use dyn_clone::DynClone;
 use num_complex::Complex64;
 use rsor::Slice;
-use std::fmt::Write;
-use std::marker::PhantomData;
+use tinyvec::TinyVec;
 
 /// An audio processor with an object safe interface.
 /// Once constructed, it has a fixed number of inputs and outputs.
@@ -35,12 +36,151 @@ pub trait AudioUnit48: Send + Sync + DynClone {
     /// The length of `input` and `output` must be equal to `inputs` and `outputs`, respectively.
     fn tick(&mut self, input: &[f48], output: &mut [f48]);
 
-    /// Process up to 64 (MAX_BUFFER_SIZE) samples.
+    /// Process up to MAX_BUFFER_SIZE (64 by default) samples.
     /// Buffers are supplied as slices. All buffers must have room for at least `size` samples.
     /// If `size` is zero then this is a no-op, which is permitted.
     /// The number of input and output buffers must be equal to `inputs` and `outputs`, respectively.
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]);
 
+    /// Process up to MAX_BUFFER_SIZE (64 by default) samples in place.
+    /// The unit must have an equal number of inputs and outputs.
+    /// The buffers contain the input on entry and hold the output on return.
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [f48]]) {
+        // The default implementation is a fallback that copies the input aside and calls into `process`.
+        assert!(self.inputs() == self.outputs());
+        debug_assert!(size <= MAX_BUFFER_SIZE);
+        debug_assert!(buffers.len() == self.inputs());
+        let channels = buffers.len();
+        if channels <= INPLACE_CHANNELS {
+            let mut scratch = [[0.0; MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+            for (x, y) in scratch.iter_mut().zip(buffers.iter()) {
+                x[..size].copy_from_slice(&y[..size]);
+            }
+            let input: [&[f48]; INPLACE_CHANNELS] = core::array::from_fn(|i| &scratch[i][..]);
+            self.process(size, &input[..channels], buffers);
+        } else {
+            let mut input = TinyVec::<[f48; 64]>::new();
+            let mut output = TinyVec::<[f48; 64]>::new();
+            input.resize(channels, 0.0);
+            output.resize(channels, 0.0);
+            for i in 0..size {
+                for (x, y) in input.iter_mut().zip(buffers.iter()) {
+                    *x = y[i];
+                }
+                self.tick(&input, &mut output);
+                for (x, y) in buffers.iter_mut().zip(output.iter()) {
+                    x[i] = *y;
+                }
+            }
+        }
+    }
+
+    /// Advance the unit by `samples` samples as if processing zero input, discarding the output.
+    /// There is no limit on `samples`. Units built from oscillators, envelopes, delays
+    /// and wave players jump their state directly.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut unit: Box<dyn AudioUnit64> = Box::new(lfo(|t| t));
+    /// unit.skip(44100);
+    /// assert!((unit.get_mono() - 1.0).abs() < 1.0e-3);
+    /// ```
+    fn skip(&mut self, samples: usize) {
+        // The default implementation is a fallback that processes blocks of zero input.
+        let inputs = self.inputs();
+        let outputs = self.outputs();
+        if inputs <= INPLACE_CHANNELS && outputs <= INPLACE_CHANNELS {
+            let input_scratch = [[0.0; MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+            let mut output_scratch = [[0.0; MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+            let input_ref: [&[f48]; INPLACE_CHANNELS] =
+                core::array::from_fn(|i| &input_scratch[i][..]);
+            let mut output_ref: TinyVec<[&mut [f48]; INPLACE_CHANNELS]> = output_scratch
+                .iter_mut()
+                .take(outputs)
+                .map(|x| &mut x[..])
+                .collect();
+            let mut samples = samples;
+            while samples > 0 {
+                let length = min(samples, MAX_BUFFER_SIZE);
+                self.process(length, &input_ref[..inputs], &mut output_ref);
+                samples -= length;
+            }
+        } else {
+            let mut input = TinyVec::<[f48; 64]>::new();
+            let mut output = TinyVec::<[f48; 64]>::new();
+            input.resize(inputs, 0.0);
+            output.resize(outputs, 0.0);
+            for _ in 0..samples {
+                self.tick(&input, &mut output);
+            }
+        }
+    }
+
+    /// Process `size` frames of interleaved `f32` samples, as supplied by audio devices and plugin hosts.
+    /// There is no limit on `size`: processing is done in blocks of up to MAX_BUFFER_SIZE (64 by default) samples.
+    /// Frame `i` of `input` occupies `input[i * inputs..(i + 1) * inputs]`,
+    /// and frame `i` of `output` occupies `output[i * outputs..(i + 1) * outputs]`.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut unit = mul((2.0, 3.0));
+    /// let mut output = [0.0f32; 4];
+    /// unit.process_interleaved(2, &[1.0, 1.0, 2.0, 2.0], &mut output);
+    /// assert_eq!(output, [2.0, 3.0, 4.0, 6.0]);
+    /// ```
+    #[allow(clippy::unnecessary_cast)]
+    fn process_interleaved(&mut self, size: usize, input: &[f32], output: &mut [f32]) {
+        let inputs = self.inputs();
+        let outputs = self.outputs();
+        assert!(input.len() >= size * inputs);
+        assert!(output.len() >= size * outputs);
+        if inputs <= INPLACE_CHANNELS && outputs <= INPLACE_CHANNELS {
+            let mut input_scratch = [[0.0; MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+            let mut output_scratch = [[0.0; MAX_BUFFER_SIZE]; INPLACE_CHANNELS];
+            let mut start = 0;
+            while start < size {
+                let length = min(size - start, MAX_BUFFER_SIZE);
+                for i in 0..length {
+                    let frame = &input[(start + i) * inputs..(start + i + 1) * inputs];
+                    for (channel, x) in frame.iter().enumerate() {
+                        input_scratch[channel][i] = *x as f48;
+                    }
+                }
+                let input_ref: [&[f48]; INPLACE_CHANNELS] =
+                    core::array::from_fn(|i| &input_scratch[i][..]);
+                let mut output_ref: TinyVec<[&mut [f48]; INPLACE_CHANNELS]> = output_scratch
+                    .iter_mut()
+                    .take(outputs)
+                    .map(|x| &mut x[..])
+                    .collect();
+                self.process(length, &input_ref[..inputs], &mut output_ref);
+                for i in 0..length {
+                    let frame = &mut output[(start + i) * outputs..(start + i + 1) * outputs];
+                    for (channel, y) in frame.iter_mut().enumerate() {
+                        *y = output_scratch[channel][i] as f32;
+                    }
+                }
+                start += length;
+            }
+        } else {
+            let mut input_frame = TinyVec::<[f48; 64]>::new();
+            let mut output_frame = TinyVec::<[f48; 64]>::new();
+            input_frame.resize(inputs, 0.0);
+            output_frame.resize(outputs, 0.0);
+            for i in 0..size {
+                for (x, y) in input_frame.iter_mut().zip(input[i * inputs..].iter()) {
+                    *x = *y as f48;
+                }
+                self.tick(&input_frame, &mut output_frame);
+                for (x, y) in output[i * outputs..].iter_mut().zip(output_frame.iter()) {
+                    *x = *y as f32;
+                }
+            }
+        }
+    }
+
     /// Number of inputs to this unit.
     /// Equals size of the input argument in `tick` and `process`.
     /// This should be fixed after construction.
@@ -85,8 +225,67 @@ pub trait AudioUnit48: Send + Sync + DynClone {
     /// The default implementation does nothing.
     fn allocate(&mut self) {}
 
+    /// Describe the structure of this unit for debugging.
+    /// Units that contain other units should override this to include them as children.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut unit: Box<dyn AudioUnit64> = Box::new(noise() >> lowpass_hz(1000.0, 1.0));
+    /// assert_eq!(unit.describe().size(), 3);
+    /// ```
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+    }
+
+    /// Describe the runtime parameters of this unit, including those of contained units.
+    /// Parameters are addressed by their index in this list.
+    /// Units with parameters should override all the parameter methods.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut unit: Box<dyn AudioUnit64> = Box::new(noise() >> lowpass_hz(1000.0, 1.0));
+    /// assert_eq!(unit.parameters()[1].name, "q");
+    /// unit.set_parameter(0, 2000.0);
+    /// assert_eq!(unit.parameter(0), 2000.0);
+    /// ```
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        Vec::new()
+    }
+
+    /// Number of runtime parameters, including those of contained units.
+    fn parameter_count(&self) -> usize {
+        0
+    }
+
+    /// Current value of parameter `index`.
+    #[allow(unused_variables)]
+    fn parameter(&self, index: usize) -> f64 {
+        panic!("Parameter index out of range.")
+    }
+
+    /// Set parameter `index` to `value`.
+    #[allow(unused_variables)]
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        panic!("Parameter index out of range.")
+    }
+
     // End of interface. There is no need to override the following.
 
+    /// Index of the first parameter named `name`, if any.
+    fn parameter_index(&self, name: &str) -> Option<usize> {
+        self.parameters()
+            .iter()
+            .position(|parameter| parameter.name == name)
+    }
+
     /// Evaluate frequency response of `output` at `frequency` Hz.
     /// Any linear response can be composed.
     /// Return `None` if there is no response or it could not be calculated.
@@ -350,6 +549,8 @@ pub trait AudioUnit48: Send + Sync + DynClone {
         )
         .unwrap();
         writeln!(&mut string, "Footprint      : {} bytes", self.footprint()).unwrap();
+        writeln!(&mut string, "Structure      :").unwrap();
+        write!(&mut string, "{}", self.describe()).unwrap();
 
         string
     }
@@ -389,6 +590,13 @@ where
         self.0.process(size, input, output);
     }
     #[inline]
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [f48]]) {
+        self.0.process_inplace(size, buffers);
+    }
+    fn skip(&mut self, samples: usize) {
+        self.0.skip(samples, &Frame::default());
+    }
+    #[inline]
     fn inputs(&self) -> usize {
         self.0.inputs()
     }
@@ -410,15 +618,33 @@ where
         self.0.route(input, frequency)
     }
     fn footprint(&self) -> usize {
-        std::mem::size_of::<X>()
+        core::mem::size_of::<X>()
     }
     fn allocate(&mut self) {
         self.0.allocate();
     }
+    fn describe(&mut self) -> NodeInfo {
+        self.0.describe()
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.0.parameters()
+    }
+    fn parameter_count(&self) -> usize {
+        self.0.parameter_count()
+    }
+    fn parameter(&self, index: usize) -> f64 {
+        self.0.parameter(index)
+    }
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.0.set_parameter(index, value);
+    }
 }
 
 /// A big block adapter.
 /// The adapter enables calls to `process` with arbitrary buffer sizes.
+/// With a fixed block size, calls of any size are accumulated into blocks
+/// of exactly that size for the source, at the cost of one block of latency.
 #[duplicate_item(
     f48       BigBlockAdapter48       AudioUnit48;
     [ f64 ]   [ BigBlockAdapter64 ]   [ AudioUnit64 ];
@@ -430,6 +656,10 @@ pub struct BigBlockAdapter48 {
     output: Vec<Vec<f48>>,
     input_slice: Slice<[f48]>,
     output_slice: Slice<[f48]>,
+    /// Fixed block size, or zero for none.
+    block: usize,
+    /// Position in the current block when the block size is fixed.
+    index: usize,
 }
 
 #[duplicate_item(
@@ -445,6 +675,8 @@ impl Clone for BigBlockAdapter48 {
             output: self.output.clone(),
             input_slice: Slice::new(),
             output_slice: Slice::new(),
+            block: self.block,
+            index: self.index,
         }
     }
 }
@@ -465,6 +697,57 @@ impl BigBlockAdapter48 {
             output,
             input_slice: Slice::new(),
             output_slice: Slice::new(),
+            block: 0,
+            index: 0,
+        }
+    }
+
+    /// Create a new big block adapter that processes the source
+    /// in blocks of exactly `block_size` samples (1 <= `block_size` <= `MAX_BUFFER_SIZE`),
+    /// whatever the sizes of calls made to the adapter.
+    /// This adds `block_size` samples of latency.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut adapter = BigBlockAdapter64::with_block_size(Box::new(pass()), 32);
+    /// assert_eq!(adapter.latency(), Some(32.0));
+    /// assert_eq!(adapter.filter_mono(1.0), 0.0);
+    /// ```
+    pub fn with_block_size(source: Box<dyn AudioUnit48>, block_size: usize) -> Self {
+        assert!(block_size >= 1 && block_size <= MAX_BUFFER_SIZE);
+        let input = vec![vec![0.0; MAX_BUFFER_SIZE]; source.inputs()];
+        let output = vec![vec![0.0; MAX_BUFFER_SIZE]; source.outputs()];
+        Self {
+            source,
+            input,
+            output,
+            input_slice: Slice::new(),
+            output_slice: Slice::new(),
+            block: block_size,
+            index: 0,
+        }
+    }
+
+    /// Fixed block size in samples, if any.
+    pub fn block_size(&self) -> Option<usize> {
+        if self.block > 0 {
+            Some(self.block)
+        } else {
+            None
+        }
+    }
+
+    /// Process the source if the current block is full.
+    #[inline]
+    fn process_block(&mut self) {
+        if self.index == self.block {
+            self.source.process(
+                self.block,
+                self.input_slice.from_refs(&self.input),
+                self.output_slice.from_muts(&mut self.output),
+            );
+            self.index = 0;
         }
     }
 }
@@ -477,15 +760,46 @@ impl BigBlockAdapter48 {
 impl AudioUnit48 for BigBlockAdapter48 {
     fn reset(&mut self) {
         self.source.reset();
+        if self.block > 0 {
+            for buffer in self.input.iter_mut().chain(self.output.iter_mut()) {
+                buffer.fill(0.0);
+            }
+            self.index = 0;
+        }
     }
     fn set_sample_rate(&mut self, sample_rate: f64) {
         self.source.set_sample_rate(sample_rate);
     }
     fn tick(&mut self, input: &[f48], output: &mut [f48]) {
-        self.source.tick(input, output);
+        if self.block > 0 {
+            for (channel, x) in input.iter().enumerate() {
+                self.input[channel][self.index] = *x;
+            }
+            for (channel, y) in output.iter_mut().enumerate() {
+                *y = self.output[channel][self.index];
+            }
+            self.index += 1;
+            self.process_block();
+        } else {
+            self.source.tick(input, output);
+        }
     }
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
-        if size > MAX_BUFFER_SIZE {
+        if self.block > 0 {
+            let mut i = 0;
+            while i < size {
+                let n = min(size - i, self.block - self.index);
+                for (channel, x) in input.iter().enumerate() {
+                    self.input[channel][self.index..self.index + n].copy_from_slice(&x[i..i + n]);
+                }
+                for (channel, y) in output.iter_mut().enumerate() {
+                    y[i..i + n].copy_from_slice(&self.output[channel][self.index..self.index + n]);
+                }
+                self.index += n;
+                i += n;
+                self.process_block();
+            }
+        } else if size > MAX_BUFFER_SIZE {
             for input_buffer in self.input.iter_mut() {
                 input_buffer.resize(MAX_BUFFER_SIZE, 0.0);
             }
@@ -529,7 +843,13 @@ impl AudioUnit48 for BigBlockAdapter48 {
         self.source.ping(probe, hash)
     }
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
-        self.source.route(input, frequency)
+        let mut output = self.source.route(input, frequency);
+        if self.block > 0 {
+            for i in 0..self.outputs() {
+                output[i] = output[i].delay(self.block as f64);
+            }
+        }
+        output
     }
     fn footprint(&self) -> usize {
         self.source.footprint()
@@ -543,6 +863,29 @@ impl AudioUnit48 for BigBlockAdapter48 {
         }
         self.source.allocate();
     }
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+        .with_children(vec![self.source.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.source.parameters()
+    }
+    fn parameter_count(&self) -> usize {
+        self.source.parameter_count()
+    }
+    fn parameter(&self, index: usize) -> f64 {
+        self.source.parameter(index)
+    }
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.source.set_parameter(index, value);
+    }
 }
 
 /// Block rate adapter converts processing calls to maximum length block processing.
@@ -640,9 +983,32 @@ impl AudioUnit48 for BlockRateAdapter48 {
         self.unit.footprint()
     }
     fn allocate(&mut self) {
-        self.buffer.resize(self.channels);
+        self.buffer.allocate(self.channels);
         self.unit.allocate();
     }
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+        .with_children(vec![self.unit.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.unit.parameters()
+    }
+    fn parameter_count(&self) -> usize {
+        self.unit.parameter_count()
+    }
+    fn parameter(&self, index: usize) -> f64 {
+        self.unit.parameter(index)
+    }
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.unit.set_parameter(index, value);
+    }
 }
 
 /// Converts an AudioUnit into an AudioNode.
@@ -723,4 +1089,24 @@ impl<I: Size<f48>, O: Size<f48>> AudioNode for Node48<I, O> {
     fn allocate(&mut self) {
         self.unit.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.unit.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.unit.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.unit.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.unit.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.unit.set_parameter(index, value);
+    }
 }
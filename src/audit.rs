@@ -0,0 +1,128 @@
+//! Allocation audit for the real-time audio path.
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+use std::alloc::{GlobalAlloc, Layout, System};
+
+thread_local! {
+    /// Nesting depth of allocation guards on this thread.
+    static AUDIT_DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// Number of allocations made inside guarded scopes on this thread.
+    static AUDIT_COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Record an allocation if the current thread is inside a guarded scope.
+#[inline]
+fn audit_allocation() {
+    // The thread locals may be unavailable while the thread is being torn down.
+    let _ = AUDIT_DEPTH.try_with(|depth| {
+        if depth.get() > 0 {
+            let _ = AUDIT_COUNT.try_with(|count| count.set(count.get() + 1));
+        }
+    });
+}
+
+/// Number of allocations made inside `AllocGuard` scopes on the current thread so far.
+/// Allocations are seen only when `AuditAllocator` is the global allocator.
+pub fn audited_allocations() -> usize {
+    AUDIT_COUNT.with(|count| count.get())
+}
+
+/// Global allocator that counts allocations made inside `AllocGuard` scopes.
+/// Memory is obtained from the system allocator.
+/// Install it in the application or test binary to enable auditing:
+///
+/// ```
+/// use fundsp::audit::*;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: AuditAllocator = AuditAllocator;
+///
+/// let guard = AllocGuard::new();
+/// let x = 1.0 + 2.0;
+/// drop(guard);
+/// assert_eq!(x, 3.0);
+/// ```
+pub struct AuditAllocator;
+
+// Safety: all requests are forwarded to the system allocator unchanged.
+unsafe impl GlobalAlloc for AuditAllocator {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        audit_allocation();
+        System.alloc(layout)
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        audit_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        audit_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Scoped guard that panics when dropped if memory was allocated
+/// on the current thread while it was alive. Guards may be nested.
+/// The check is active only when `AuditAllocator` is the global allocator.
+/// Panicking inside the allocator is not allowed, so allocations are reported
+/// when the guard goes out of scope.
+///
+/// With the `alloc_audit` feature, backends of `Net64`, `Net32`, `Sequencer64`
+/// and `Sequencer32` guard each `tick` and `process` call.
+///
+/// ### Example
+/// ```
+/// use fundsp::audit::*;
+/// let guard = AllocGuard::new();
+/// let x = [0.0f32; 64].iter().sum::<f32>();
+/// drop(guard);
+/// assert_eq!(x, 0.0);
+/// ```
+pub struct AllocGuard {
+    /// Allocation count when the guard was created.
+    count: usize,
+    /// The guard refers to thread local state, so it must not leave the thread.
+    _marker: PhantomData<*const ()>,
+}
+
+impl AllocGuard {
+    /// Start auditing allocations on the current thread.
+    #[inline]
+    pub fn new() -> Self {
+        AUDIT_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Self {
+            count: audited_allocations(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Default for AllocGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for AllocGuard {
+    #[inline]
+    fn drop(&mut self) {
+        AUDIT_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        let allocations = audited_allocations() - self.count;
+        if allocations > 0 && !std::thread::panicking() {
+            panic!(
+                "AllocGuard: {} allocation(s) in the real-time audio path.",
+                allocations
+            );
+        }
+    }
+}
@@ -8,6 +8,8 @@ use rsor::Slice;
 pub struct Buffer<T: Float> {
     buffer: Vec<Vec<T>>,
     slice: Slice<[T]>,
+    /// Whether the buffer has been preallocated. Preallocated buffers cannot grow.
+    allocated: bool,
 }
 
 impl<T: Float> Default for Buffer<T> {
@@ -30,6 +32,7 @@ impl<T: Float> Buffer<T> {
         Buffer::<T> {
             buffer: Vec::new(),
             slice: Slice::new(),
+            allocated: false,
         }
     }
 
@@ -38,6 +41,7 @@ impl<T: Float> Buffer<T> {
         let mut buffer = Buffer::<T> {
             buffer: Vec::new(),
             slice: Slice::new(),
+            allocated: false,
         };
         buffer.resize(channels);
         buffer
@@ -50,12 +54,20 @@ impl<T: Float> Buffer<T> {
     }
 
     /// Set the number of `channels`.
+    /// Panics if the buffer has been preallocated with `allocate`
+    /// and would need to grow.
     #[inline]
     pub fn resize(&mut self, channels: usize) {
         if self.buffer.len() != channels {
             if self.buffer.len() > channels {
                 self.buffer.truncate(channels);
             } else {
+                assert!(
+                    !self.allocated,
+                    "Buffer cannot grow after allocate: {} channels requested, {} allocated.",
+                    channels,
+                    self.buffer.len()
+                );
                 self.slice.fill(|mut v| {
                     v.reserve_exact(channels);
                     v
@@ -69,6 +81,22 @@ impl<T: Float> Buffer<T> {
         }
     }
 
+    /// Preallocate the buffer with the given number of `channels`.
+    /// After this the buffer never allocates: an attempt to grow it panics.
+    pub fn allocate(&mut self, channels: usize) {
+        self.allocated = false;
+        self.resize(channels);
+        // Make sure the slice has room for all the channels.
+        self.slice.from_refs(&self.buffer);
+        self.allocated = true;
+    }
+
+    /// Whether the buffer has been preallocated with `allocate`.
+    #[inline]
+    pub fn is_allocated(&self) -> bool {
+        self.allocated
+    }
+
     /// Get reference to a slice of slices with the given number of `channels`.
     /// The buffer is resized if necessary.
     #[inline]
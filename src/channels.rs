@@ -0,0 +1,542 @@
+//! Units with a channel count chosen at runtime, for adapting to host channel layouts.
+
+use super::audionode::*;
+use super::audiounit::*;
+use super::dynamics::*;
+use super::math::*;
+use super::mixer::*;
+use super::pan::*;
+use super::shared::*;
+use super::signal::*;
+use super::*;
+use duplicate::duplicate_item;
+
+#[duplicate_item(
+    f48       MultiGain48;
+    [ f64 ]   [ MultiGain64 ];
+    [ f32 ]   [ MultiGain32 ];
+)]
+/// Smoothed gain in decibels applied equally to a runtime number of channels.
+/// Gains at or below `MUTE_DB` mute the signal.
+/// - Inputs: `channels` signals
+/// - Outputs: `channels` signals with gain applied
+#[derive(Clone)]
+pub struct MultiGain48 {
+    channels: usize,
+    gain: Gain<f48>,
+}
+
+#[duplicate_item(
+    f48       MultiGain48;
+    [ f64 ]   [ MultiGain64 ];
+    [ f32 ]   [ MultiGain32 ];
+)]
+impl MultiGain48 {
+    /// Create gain of `db` decibels for `channels` channels.
+    /// Gain changes are smoothed with a time constant of 5 ms.
+    pub fn new(channels: usize, db: f64) -> Self {
+        Self {
+            channels,
+            gain: Gain::new(db, f64::NEG_INFINITY, f64::INFINITY, 0.005, "gain"),
+        }
+    }
+
+    /// Target gain in dB.
+    #[inline]
+    pub fn db(&self) -> f64 {
+        self.gain.db()
+    }
+
+    /// Set target gain in dB. The gain changes smoothly.
+    #[inline]
+    pub fn set_db(&mut self, db: f64) {
+        self.gain.set_db(db);
+    }
+}
+
+#[duplicate_item(
+    f48       MultiGain48       AudioUnit48;
+    [ f64 ]   [ MultiGain64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ MultiGain32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for MultiGain48 {
+    fn reset(&mut self) {
+        self.gain.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.gain.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        let gain = self.gain.filter_mono(1.0);
+        for (o, x) in output[..self.channels].iter_mut().zip(input.iter()) {
+            *o = *x * gain;
+        }
+    }
+
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        let ones = [1.0; MAX_BUFFER_SIZE];
+        let mut gain = [0.0; MAX_BUFFER_SIZE];
+        self.gain
+            .process(size, &[&ones[..size]], &mut [&mut gain[..size]]);
+        for (channel, x) in output[..self.channels].iter_mut().zip(input.iter()) {
+            for ((o, x), g) in channel[..size]
+                .iter_mut()
+                .zip(x[..size].iter())
+                .zip(gain[..size].iter())
+            {
+                *o = *x * *g;
+            }
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        self.channels
+    }
+
+    fn outputs(&self) -> usize {
+        self.channels
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for (o, x) in output.iter_mut().zip(input.iter()) {
+            let mut signal = new_signal_frame(1);
+            signal[0] = *x;
+            *o = self.gain.route(&signal, frequency)[0];
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 164;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.gain.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.gain.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.gain.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.gain.set_parameter(index, value);
+    }
+}
+
+#[duplicate_item(
+    f48       MultiMixer48;
+    [ f64 ]   [ MultiMixer64 ];
+    [ f32 ]   [ MultiMixer32 ];
+)]
+/// Matrix mixer with a runtime number of inputs and outputs, for example,
+/// for downmixing a 5.1 host layout to stereo. Each output is a weighted sum
+/// of the inputs. Gain changes are smoothed.
+/// Initially, input `i` is routed to output `i` with unity gain.
+/// - Inputs: `inputs` signals
+/// - Outputs: `outputs` mixes
+#[derive(Clone)]
+pub struct MultiMixer48 {
+    inputs: usize,
+    outputs: usize,
+    /// Target gains in output major order.
+    gains: Vec<f48>,
+    /// Smoothed gains in output major order.
+    current: Vec<f48>,
+    /// Smoothing time in seconds.
+    smoothing: f64,
+    /// Smoothing coefficient per sample.
+    coeff: f48,
+}
+
+#[duplicate_item(
+    f48       MultiMixer48;
+    [ f64 ]   [ MultiMixer64 ];
+    [ f32 ]   [ MultiMixer32 ];
+)]
+impl MultiMixer48 {
+    /// Create mixer from `inputs` (`inputs` > 0) to `outputs` channels.
+    /// Gain changes are smoothed with a time constant of `smoothing` seconds.
+    pub fn new(inputs: usize, outputs: usize, smoothing: f64) -> Self {
+        assert!(inputs > 0);
+        let gains: Vec<f48> = (0..outputs * inputs)
+            .map(|k| if k / inputs == k % inputs { 1.0 } else { 0.0 })
+            .collect();
+        let mut mixer = Self {
+            inputs,
+            outputs,
+            current: gains.clone(),
+            gains,
+            smoothing,
+            coeff: 1.0,
+        };
+        mixer.set_sample_rate(DEFAULT_SR);
+        mixer
+    }
+
+    /// Gain from `input` to `output`.
+    #[inline]
+    pub fn gain(&self, output: usize, input: usize) -> f48 {
+        self.gains[output * self.inputs + input]
+    }
+
+    /// Set gain from `input` to `output`. The gain changes smoothly.
+    #[inline]
+    pub fn set_gain(&mut self, output: usize, input: usize, gain: f48) {
+        assert!(output < self.outputs && input < self.inputs);
+        self.gains[output * self.inputs + input] = gain;
+    }
+
+    /// Set all gains at once from `gains` in output major order. Gains change smoothly.
+    pub fn set_gains(&mut self, gains: &[f48]) {
+        self.gains.copy_from_slice(gains);
+    }
+}
+
+#[duplicate_item(
+    f48       MultiMixer48       AudioUnit48;
+    [ f64 ]   [ MultiMixer64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ MultiMixer32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for MultiMixer48 {
+    fn reset(&mut self) {
+        self.current.copy_from_slice(&self.gains);
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.coeff = if self.smoothing > 0.0 {
+            (1.0 - exp(-1.0 / (self.smoothing * sample_rate))) as f48
+        } else {
+            1.0
+        };
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        for (current, gain) in self.current.iter_mut().zip(self.gains.iter()) {
+            *current += (*gain - *current) * self.coeff;
+        }
+        for (o, row) in output[..self.outputs]
+            .iter_mut()
+            .zip(self.current.chunks_exact(self.inputs))
+        {
+            *o = row
+                .iter()
+                .zip(input.iter())
+                .fold(0.0, |sum, (gain, x)| sum + *gain * *x);
+        }
+    }
+
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        for i in 0..size {
+            for (current, gain) in self.current.iter_mut().zip(self.gains.iter()) {
+                *current += (*gain - *current) * self.coeff;
+            }
+            for (channel, row) in output[..self.outputs]
+                .iter_mut()
+                .zip(self.current.chunks_exact(self.inputs))
+            {
+                channel[i] = row
+                    .iter()
+                    .zip(input.iter())
+                    .fold(0.0, |sum, (gain, x)| sum + *gain * x[i]);
+            }
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs);
+        for (o, signal) in output.iter_mut().enumerate() {
+            let mut sum = Signal::Value(0.0);
+            for i in 0..self.inputs {
+                let gain = self.gain(o, i) as f64;
+                if gain != 0.0 {
+                    sum = sum.combine_linear(input[i].scale(gain), 0.0, |x, y| x + y, |x, y| x + y);
+                }
+            }
+            *signal = sum;
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 165;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
+
+#[duplicate_item(
+    f48       MultiPan48;
+    [ f64 ]   [ MultiPan64 ];
+    [ f32 ]   [ MultiPan32 ];
+)]
+/// Equal power panner from mono to a runtime number of channels.
+/// The output channels are treated as a row of speakers from left to right:
+/// pan value -1 is the first channel and 1 is the last channel.
+/// With two channels, this is the same as `pan`. With one channel, the input passes through.
+/// - Input 0: mono signal
+/// - Outputs: `channels` panned signals
+#[derive(Clone)]
+pub struct MultiPan48 {
+    channels: usize,
+    value: f48,
+    weights: Vec<f48>,
+}
+
+#[duplicate_item(
+    f48       MultiPan48;
+    [ f64 ]   [ MultiPan64 ];
+    [ f32 ]   [ MultiPan32 ];
+)]
+impl MultiPan48 {
+    /// Create panner to `channels` (`channels` > 0) outputs with pan `value` in -1...1.
+    pub fn new(channels: usize, value: f48) -> Self {
+        assert!(channels > 0);
+        let mut pan = Self {
+            channels,
+            value: 0.0,
+            weights: vec![0.0; channels],
+        };
+        pan.set_pan(value);
+        pan
+    }
+
+    /// Pan value in -1...1.
+    #[inline]
+    pub fn pan(&self) -> f48 {
+        self.value
+    }
+
+    /// Set pan value in -1...1.
+    pub fn set_pan(&mut self, value: f48) {
+        self.value = clamp11(value);
+        self.weights.fill(0.0);
+        if self.channels == 1 {
+            self.weights[0] = 1.0;
+            return;
+        }
+        let position = (self.value + 1.0) * 0.5 * (self.channels - 1) as f48;
+        let i = min(floor(position) as usize, self.channels - 2);
+        let (left, right) = pan_weights(clamp11((position - i as f48) * 2.0 - 1.0));
+        self.weights[i] = left;
+        self.weights[i + 1] = right;
+    }
+}
+
+#[duplicate_item(
+    f48       MultiPan48       AudioUnit48;
+    [ f64 ]   [ MultiPan64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ MultiPan32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for MultiPan48 {
+    fn reset(&mut self) {}
+
+    fn set_sample_rate(&mut self, _sample_rate: f64) {}
+
+    #[inline]
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        for (o, weight) in output[..self.channels].iter_mut().zip(self.weights.iter()) {
+            *o = input[0] * *weight;
+        }
+    }
+
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        for (channel, weight) in output[..self.channels].iter_mut().zip(self.weights.iter()) {
+            for (o, x) in channel[..size].iter_mut().zip(input[0][..size].iter()) {
+                *o = *x * *weight;
+            }
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    fn outputs(&self) -> usize {
+        self.channels
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for (o, weight) in output.iter_mut().zip(self.weights.iter()) {
+            *o = input[0].scale(*weight as f64);
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 166;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "pan",
+            ParameterUnit::Unitless,
+            -1.0,
+            1.0,
+            0.0,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.value as f64,
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_pan(value as f48),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       MultiMeter48;
+    [ f64 ]   [ MultiMeter64 ];
+    [ f32 ]   [ MultiMeter32 ];
+)]
+/// Pass through a runtime number of channels and meter each of them.
+/// Levels are placed in shared variables once per block.
+/// - Inputs: `channels` signals
+/// - Outputs: `channels` signals, unchanged
+#[derive(Clone)]
+pub struct MultiMeter48 {
+    meter: Meter,
+    states: Vec<MeterState<f48>>,
+    levels: Vec<Shared<f48>>,
+}
+
+#[duplicate_item(
+    f48       MultiMeter48;
+    [ f64 ]   [ MultiMeter64 ];
+    [ f32 ]   [ MultiMeter32 ];
+)]
+impl MultiMeter48 {
+    /// Create meter for `channels` channels with metering mode `meter`.
+    pub fn new(channels: usize, meter: Meter) -> Self {
+        Self {
+            meter,
+            states: vec![MeterState::new(meter); channels],
+            levels: (0..channels).map(|_| Shared::new(0.0)).collect(),
+        }
+    }
+
+    /// Shared level of each channel.
+    pub fn levels(&self) -> &[Shared<f48>] {
+        &self.levels
+    }
+
+    /// Current level of `channel`.
+    #[inline]
+    pub fn level(&self, channel: usize) -> f48 {
+        self.levels[channel].value()
+    }
+}
+
+#[duplicate_item(
+    f48       MultiMeter48       AudioUnit48;
+    [ f64 ]   [ MultiMeter64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ MultiMeter32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for MultiMeter48 {
+    fn reset(&mut self) {
+        for state in self.states.iter_mut() {
+            state.reset(self.meter);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        for state in self.states.iter_mut() {
+            state.set_sample_rate(self.meter, sample_rate);
+        }
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        for (channel, state) in self.states.iter_mut().enumerate() {
+            state.tick(self.meter, input[channel]);
+            self.levels[channel].set(state.level(self.meter));
+            output[channel] = input[channel];
+        }
+    }
+
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        if size == 0 {
+            return;
+        }
+        for (channel, state) in self.states.iter_mut().enumerate() {
+            if self.meter.latest_only() {
+                state.tick(self.meter, input[channel][size - 1]);
+            } else {
+                for x in input[channel][..size].iter() {
+                    state.tick(self.meter, *x);
+                }
+            }
+            // For efficiency, store the value only once per block.
+            self.levels[channel].set(state.level(self.meter));
+            output[channel][..size].clone_from_slice(&input[channel][..size]);
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        self.states.len()
+    }
+
+    fn outputs(&self) -> usize {
+        self.states.len()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 167;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
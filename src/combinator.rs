@@ -215,6 +215,14 @@ impl<X: AudioNode> An<X> {
         self.0.process(size, input, output);
     }
     #[inline]
+    pub fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [X::Sample]]) {
+        self.0.process_inplace(size, buffers);
+    }
+    #[inline]
+    pub fn skip(&mut self, samples: usize, input: &Frame<X::Sample, X::Inputs>) {
+        self.0.skip(samples, input);
+    }
+    #[inline]
     pub fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         self.0.route(input, frequency)
     }
@@ -235,6 +243,10 @@ impl<X: AudioNode> An<X> {
         self.0.ping(probe, hash)
     }
     #[inline]
+    pub fn describe(&mut self) -> NodeInfo {
+        self.0.describe()
+    }
+    #[inline]
     pub fn get_mono(&mut self) -> X::Sample {
         self.0.get_mono()
     }
@@ -253,7 +265,7 @@ impl<X: AudioNode> An<X> {
 }
 
 /// `-` unary operator: Negates node outputs. Any node can be negated.
-impl<X> std::ops::Neg for An<X>
+impl<X> core::ops::Neg for An<X>
 where
     X: AudioNode,
     X::Outputs: Size<X::Sample>,
@@ -267,7 +279,7 @@ where
 
 /// `!` unary operator: The thru operator makes output arity match input arity
 /// and passes through missing outputs.
-impl<X> std::ops::Not for An<X>
+impl<X> core::ops::Not for An<X>
 where
     X: AudioNode,
 {
@@ -280,7 +292,7 @@ where
 
 /// `+` binary operator: Sums outputs of two nodes with disjoint inputs.
 /// The nodes must have the same number of outputs.
-impl<X, Y> std::ops::Add<An<Y>> for An<X>
+impl<X, Y> core::ops::Add<An<Y>> for An<X>
 where
     X: AudioNode,
     Y: AudioNode<Sample = X::Sample, Outputs = X::Outputs>,
@@ -302,7 +314,7 @@ where
     [ f64 ];
     [ f32 ];
 )]
-impl<X> std::ops::Add<f48> for An<X>
+impl<X> core::ops::Add<f48> for An<X>
 where
     X: AudioNode<Sample = f48>,
     X::Inputs: Size<f48>,
@@ -322,7 +334,7 @@ where
     [ f64 ];
     [ f32 ];
 )]
-impl<X> std::ops::Add<An<X>> for f48
+impl<X> core::ops::Add<An<X>> for f48
 where
     X: AudioNode<Sample = f48>,
     X::Inputs: Size<f48>,
@@ -337,7 +349,7 @@ where
 
 /// `-` binary operator: The difference of outputs of two nodes with disjoint inputs.
 /// The nodes must have the same number of outputs.
-impl<X, Y> std::ops::Sub<An<Y>> for An<X>
+impl<X, Y> core::ops::Sub<An<Y>> for An<X>
 where
     X: AudioNode,
     Y: AudioNode<Sample = X::Sample, Outputs = X::Outputs>,
@@ -359,7 +371,7 @@ where
     [ f64 ];
     [ f32 ];
 )]
-impl<X> std::ops::Sub<f48> for An<X>
+impl<X> core::ops::Sub<f48> for An<X>
 where
     X: AudioNode<Sample = f48>,
     X::Inputs: Size<f48>,
@@ -379,7 +391,7 @@ where
     [ f64 ];
     [ f32 ];
 )]
-impl<X> std::ops::Sub<An<X>> for f48
+impl<X> core::ops::Sub<An<X>> for f48
 where
     X: AudioNode<Sample = f48>,
     X::Inputs: Size<f48> + Add<U0>,
@@ -399,7 +411,7 @@ where
 
 /// `*` binary operator: Multiplies outputs of two nodes with disjoint inputs.
 /// The nodes must have the same number of outputs.
-impl<X, Y> std::ops::Mul<An<Y>> for An<X>
+impl<X, Y> core::ops::Mul<An<Y>> for An<X>
 where
     X: AudioNode,
     Y: AudioNode<Sample = X::Sample, Outputs = X::Outputs>,
@@ -421,7 +433,7 @@ where
     [ f64 ];
     [ f32 ];
 )]
-impl<X> std::ops::Mul<f48> for An<X>
+impl<X> core::ops::Mul<f48> for An<X>
 where
     X: AudioNode<Sample = f48>,
     X::Inputs: Size<f48>,
@@ -441,7 +453,7 @@ where
     [ f64 ];
     [ f32 ];
 )]
-impl<X> std::ops::Mul<An<X>> for f48
+impl<X> core::ops::Mul<An<X>> for f48
 where
     X: AudioNode<Sample = f48>,
     X::Inputs: Size<f48>,
@@ -456,7 +468,7 @@ where
 
 /// `>>` binary operator: The pipe operator pipes outputs of left node to inputs of right node.
 /// Number of outputs on the left side and number of inputs on the right side must match.
-impl<T, X, Y> std::ops::Shr<An<Y>> for An<X>
+impl<T, X, Y> core::ops::Shr<An<Y>> for An<X>
 where
     T: Float,
     X: AudioNode<Sample = T>,
@@ -473,7 +485,7 @@ where
 }
 
 /// `&` binary operator: The bus operator mixes together units with similar connectivity that share inputs and outputs.
-impl<T, X, Y> std::ops::BitAnd<An<Y>> for An<X>
+impl<T, X, Y> core::ops::BitAnd<An<Y>> for An<X>
 where
     T: Float,
     X: AudioNode<Sample = T>,
@@ -489,7 +501,7 @@ where
 }
 
 /// `^` binary operator: The branch operator sources two nodes from the same inputs and concatenates their outputs.
-impl<T, X, Y> std::ops::BitXor<An<Y>> for An<X>
+impl<T, X, Y> core::ops::BitXor<An<Y>> for An<X>
 where
     T: Float,
     X: AudioNode<Sample = T>,
@@ -507,7 +519,7 @@ where
 }
 
 /// `|` binary operator: The stack operator stacks inputs and outputs of two nodes running in parallel.
-impl<T, X, Y> std::ops::BitOr<An<Y>> for An<X>
+impl<T, X, Y> core::ops::BitOr<An<Y>> for An<X>
 where
     T: Float,
     X: AudioNode<Sample = T>,
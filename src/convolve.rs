@@ -0,0 +1,598 @@
+//! FFT convolution.
+
+use super::audionode::*;
+use super::math::*;
+#[cfg(feature = "files")]
+use super::read::*;
+use super::signal::*;
+use super::wave::*;
+use super::*;
+use core::marker::PhantomData;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+
+/// Transform `impulse` into the spectra of `partitions` of `block` samples each for
+/// partitioned convolution. The normalizing term of the inverse transform is folded in.
+fn transform_impulse(
+    forward: &Arc<dyn RealToComplex<f64>>,
+    block: usize,
+    impulse: &[f64],
+    partitions: &mut [Vec<Complex64>],
+    buffer: &mut [f64],
+    scratch: &mut [Complex64],
+) {
+    assert!(impulse.len() <= partitions.len() * block);
+    let z = 1.0 / (2 * block) as f64;
+    for (p, partition) in partitions.iter_mut().enumerate() {
+        buffer.fill(0.0);
+        for (x, y) in buffer[..block]
+            .iter_mut()
+            .zip(impulse.iter().skip(p * block))
+        {
+            *x = *y * z;
+        }
+        forward
+            .process_with_scratch(buffer, partition, scratch)
+            .expect("Internal error");
+    }
+}
+
+/// Frequency domain delay line for uniformly partitioned overlap-save convolution.
+/// Holds the spectra of recent input blocks, which can be convolved
+/// with any impulse response that has been split into the same number of partitions.
+#[derive(Clone)]
+struct SpectrumLine {
+    /// Block length in samples. A power of two.
+    block: usize,
+    /// Spectra of recent input blocks, used as a ring buffer.
+    spectra: Vec<Vec<Complex64>>,
+    /// Index of the most recent input spectrum.
+    index: usize,
+    /// Previous and current input blocks.
+    input: Vec<f64>,
+    /// Frequency domain accumulator.
+    accumulator: Vec<Complex64>,
+    /// Time domain work buffer of two blocks.
+    buffer: Vec<f64>,
+    /// Forward transform.
+    forward: Arc<dyn RealToComplex<f64>>,
+    /// Inverse transform.
+    inverse: Arc<dyn ComplexToReal<f64>>,
+    /// Temporary vector for FFT.
+    scratch: Vec<Complex64>,
+}
+
+impl SpectrumLine {
+    /// Create delay line of `partitions` blocks of `block` samples.
+    fn new(block: usize, partitions: usize) -> Self {
+        assert!(block.is_power_of_two() && partitions > 0);
+        let mut planner = RealFftPlanner::<f64>::new();
+        let forward = planner.plan_fft_forward(2 * block);
+        let inverse = planner.plan_fft_inverse(2 * block);
+        let scratch =
+            vec![Complex64::default(); max(forward.get_scratch_len(), inverse.get_scratch_len())];
+        let bins = block + 1;
+        Self {
+            block,
+            spectra: vec![vec![Complex64::default(); bins]; partitions],
+            index: 0,
+            input: vec![0.0; 2 * block],
+            accumulator: vec![Complex64::default(); bins],
+            buffer: vec![0.0; 2 * block],
+            forward,
+            inverse,
+            scratch,
+        }
+    }
+
+    /// Number of partitions.
+    #[inline]
+    fn partitions(&self) -> usize {
+        self.spectra.len()
+    }
+
+    /// Transform `impulse` into `partitions`.
+    fn transform(&mut self, impulse: &[f64], partitions: &mut [Vec<Complex64>]) {
+        transform_impulse(
+            &self.forward,
+            self.block,
+            impulse,
+            partitions,
+            &mut self.buffer,
+            &mut self.scratch,
+        );
+    }
+
+    fn reset(&mut self) {
+        for spectrum in self.spectra.iter_mut() {
+            spectrum.fill(Complex64::default());
+        }
+        self.input.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Write sample `i` of the current input block.
+    #[inline]
+    fn write(&mut self, i: usize, x: f64) {
+        self.input[self.block + i] = x;
+    }
+
+    /// Transform the current input block into the delay line.
+    fn push(&mut self) {
+        let count = self.spectra.len();
+        self.index = (self.index + 1) % count;
+        self.buffer.copy_from_slice(&self.input);
+        self.forward
+            .process_with_scratch(
+                &mut self.buffer,
+                &mut self.spectra[self.index],
+                &mut self.scratch,
+            )
+            .expect("Internal error");
+        self.input.copy_within(self.block.., 0);
+    }
+
+    /// Convolve the delay line with impulse response `partitions` into the `output` block.
+    fn convolve(&mut self, partitions: &[Vec<Complex64>], output: &mut [f64]) {
+        let count = self.spectra.len();
+        self.accumulator.fill(Complex64::default());
+        for (p, partition) in partitions.iter().enumerate() {
+            let spectrum = &self.spectra[(self.index + count - p) % count];
+            for (a, (x, h)) in self
+                .accumulator
+                .iter_mut()
+                .zip(spectrum.iter().zip(partition.iter()))
+            {
+                *a += x * h;
+            }
+        }
+        // The DC and Nyquist bins of a real signal have no imaginary parts.
+        self.accumulator[0].im = 0.0;
+        self.accumulator[self.block].im = 0.0;
+        self.inverse
+            .process_with_scratch(&mut self.accumulator, &mut self.buffer, &mut self.scratch)
+            .expect("Internal error");
+        output.copy_from_slice(&self.buffer[self.block..]);
+    }
+}
+
+/// Uniformly partitioned overlap-save convolution of a mono signal with an impulse response.
+/// The impulse response is split into partitions of one block each. Each block of input
+/// is transformed once and multiplied with all partitions in the frequency domain.
+/// The latency is equal to the block length.
+#[derive(Clone)]
+pub struct Convolver {
+    /// Spectra of impulse response partitions.
+    partitions: Vec<Vec<Complex64>>,
+    /// Spectra of recent input blocks.
+    line: SpectrumLine,
+    /// Output block.
+    output: Vec<f64>,
+    /// Index into the current block.
+    index: usize,
+}
+
+impl Convolver {
+    /// Create convolver with block length `block` (a power of two) for the `impulse` response.
+    /// The impulse response can later be replaced with another one that is not longer.
+    /// - Allocates: everything.
+    pub fn new(block: usize, impulse: &[f64]) -> Self {
+        let count = max(1, impulse.len().div_ceil(block));
+        let mut line = SpectrumLine::new(block, count);
+        let mut partitions = vec![vec![Complex64::default(); block + 1]; count];
+        line.transform(impulse, &mut partitions);
+        Self {
+            partitions,
+            line,
+            output: vec![0.0; block],
+            index: 0,
+        }
+    }
+
+    /// Block length in samples. This is also the latency.
+    #[inline]
+    pub fn block_length(&self) -> usize {
+        self.line.block
+    }
+
+    /// Maximum impulse response length in samples.
+    #[inline]
+    pub fn length(&self) -> usize {
+        self.line.partitions() * self.line.block
+    }
+
+    /// Replace the impulse response. The length of `impulse` may not exceed `length()`;
+    /// shorter responses are padded with zeros. Does not allocate.
+    pub fn set_impulse(&mut self, impulse: &[f64]) {
+        self.line.transform(impulse, &mut self.partitions);
+    }
+
+    /// Reset the input history.
+    pub fn reset(&mut self) {
+        self.line.reset();
+        self.output.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Process one sample.
+    #[inline]
+    pub fn filter(&mut self, x: f64) -> f64 {
+        let y = self.output[self.index];
+        self.line.write(self.index, x);
+        self.index += 1;
+        if self.index == self.line.block {
+            self.index = 0;
+            self.line.push();
+            self.line.convolve(&self.partitions, &mut self.output);
+        }
+        y
+    }
+}
+
+/// Linear phase FIR filter implemented with FFT convolution.
+/// Suitable for long filters, for example, from `fir_design`.
+/// The latency is the block length plus `(taps - 1) / 2` samples.
+/// Setting: coefficients.
+/// - Input 0: signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct LinearPhase<T: Float> {
+    _marker: PhantomData<T>,
+    /// Filter coefficients.
+    coefficients: Vec<f64>,
+    convolver: Convolver,
+    sample_rate: f64,
+}
+
+impl<T: Float> LinearPhase<T> {
+    /// Create filter from symmetric coefficients `b`.
+    /// The block length is the number of taps rounded up to a power of two, between 16 and 512.
+    /// - Allocates: everything.
+    pub fn new(b: &[T]) -> Self {
+        assert!(!b.is_empty());
+        let coefficients: Vec<f64> = b.iter().map(|x| x.to_f64()).collect();
+        let block = b.len().next_power_of_two().clamp(16, 512);
+        let convolver = Convolver::new(block, &coefficients);
+        Self {
+            _marker: PhantomData,
+            coefficients,
+            convolver,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Number of taps.
+    #[inline]
+    pub fn taps(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Block length of the convolution in samples.
+    #[inline]
+    pub fn block_length(&self) -> usize {
+        self.convolver.block_length()
+    }
+
+    /// Total latency in samples.
+    #[inline]
+    pub fn latency_samples(&self) -> f64 {
+        self.block_length() as f64 + (self.taps() - 1) as f64 * 0.5
+    }
+
+    /// Replace coefficients. The number of taps may not change,
+    /// as the latency depends on it. Does not allocate.
+    pub fn set_coefficients(&mut self, b: &[T]) {
+        assert!(b.len() == self.coefficients.len());
+        for (x, y) in self.coefficients.iter_mut().zip(b.iter()) {
+            *x = y.to_f64();
+        }
+        self.convolver.set_impulse(&self.coefficients);
+    }
+}
+
+impl<T: Float> AudioNode for LinearPhase<T> {
+    const ID: u64 = 156;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = Vec<T>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_coefficients(&setting);
+    }
+
+    fn reset(&mut self) {
+        self.convolver.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [T::from_f64(self.convolver.filter(input[0].to_f64()))].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let omega = -TAU * frequency / self.sample_rate;
+        let block = self.block_length();
+        output[0] = input[0].filter(self.latency_samples(), |r| {
+            let mut response = Complex64::default();
+            for (k, b) in self.coefficients.iter().enumerate() {
+                response += Complex64::from_polar(*b, omega * (block + k) as f64);
+            }
+            r * response
+        });
+        output
+    }
+}
+
+/// Library of impulse responses, for example, guitar cabinets or rooms, for `ir_switch` nodes.
+/// Impulse responses are transformed for partitioned convolution when they are added,
+/// so switching between them at runtime does not allocate.
+/// The library is shared between nodes in an `Arc`.
+#[derive(Clone)]
+pub struct ImpulseLibrary {
+    /// Block length in samples. A power of two.
+    block: usize,
+    /// Impulse responses.
+    impulses: Vec<Vec<f64>>,
+    /// Partition spectra of each impulse response.
+    partitions: Vec<Vec<Vec<Complex64>>>,
+}
+
+impl ImpulseLibrary {
+    /// Create empty library with block length `block` (a power of two).
+    /// The block length is the latency of convolution.
+    pub fn new(block: usize) -> Self {
+        assert!(block.is_power_of_two());
+        Self {
+            block,
+            impulses: Vec::new(),
+            partitions: Vec::new(),
+        }
+    }
+
+    /// Block length in samples.
+    #[inline]
+    pub fn block_length(&self) -> usize {
+        self.block
+    }
+
+    /// Number of impulse responses.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.impulses.len()
+    }
+
+    /// Whether the library is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.impulses.is_empty()
+    }
+
+    /// Impulse response `i`.
+    #[inline]
+    pub fn impulse(&self, i: usize) -> &[f64] {
+        &self.impulses[i]
+    }
+
+    /// Maximum number of partitions in any impulse response.
+    fn max_partitions(&self) -> usize {
+        self.partitions.iter().map(|x| x.len()).max().unwrap_or(1)
+    }
+
+    /// Add `impulse` response to the library. Returns its index.
+    pub fn add(&mut self, impulse: &[f64]) -> usize {
+        let count = max(1, impulse.len().div_ceil(self.block));
+        let mut planner = RealFftPlanner::<f64>::new();
+        let forward = planner.plan_fft_forward(2 * self.block);
+        let mut partitions = vec![vec![Complex64::default(); self.block + 1]; count];
+        let mut buffer = vec![0.0; 2 * self.block];
+        let mut scratch = vec![Complex64::default(); forward.get_scratch_len()];
+        transform_impulse(
+            &forward,
+            self.block,
+            impulse,
+            &mut partitions,
+            &mut buffer,
+            &mut scratch,
+        );
+        self.impulses.push(impulse.to_vec());
+        self.partitions.push(partitions);
+        self.impulses.len() - 1
+    }
+
+    /// Add `channel` of `wave` to the library. Returns its index.
+    /// The sample rate of the wave should match the processing sample rate.
+    pub fn add_wave(&mut self, wave: &Wave64, channel: usize) -> usize {
+        self.add(wave.channel(channel))
+    }
+
+    /// Load the first channel of an audio file from `path` into the library.
+    /// Returns its index.
+    /// The sample rate of the file should match the processing sample rate.
+    #[cfg(feature = "files")]
+    pub fn load<P: AsRef<std::path::Path>>(&mut self, path: P) -> WaveResult<usize> {
+        let wave = Wave64::load(path)?;
+        Ok(self.add_wave(&wave, 0))
+    }
+}
+
+/// Convolver that switches between the impulse responses of an `ImpulseLibrary`
+/// with a linear crossfade. Switches take effect at the next block boundary.
+/// A switch requested during a crossfade starts when the crossfade finishes.
+/// The latency is the block length of the library.
+/// Setting: impulse response index.
+/// - Input 0: signal
+/// - Output 0: convolved signal
+#[derive(Clone)]
+pub struct IrSwitch<T: Float> {
+    _marker: PhantomData<T>,
+    library: Arc<ImpulseLibrary>,
+    /// Spectra of recent input blocks.
+    line: SpectrumLine,
+    /// Output block of the current impulse response.
+    output: Vec<f64>,
+    /// Output block of the previous impulse response during a crossfade.
+    fade_output: Vec<f64>,
+    /// Index into the current block.
+    index: usize,
+    /// Current impulse response.
+    current: usize,
+    /// Impulse response that is being faded out.
+    previous: usize,
+    /// Requested impulse response.
+    pending: usize,
+    /// Crossfade time in seconds.
+    fade_time: f64,
+    /// Crossfade length in samples.
+    fade_length: usize,
+    /// Position in the crossfade in samples. Equal to `fade_length` when not fading.
+    fade_position: usize,
+    sample_rate: f64,
+}
+
+impl<T: Float> IrSwitch<T> {
+    /// Create convolver for the non-empty `library`, starting from impulse response `index`,
+    /// with a crossfade of `fade_time` seconds.
+    /// - Allocates: everything.
+    pub fn new(library: &Arc<ImpulseLibrary>, index: usize, fade_time: f64) -> Self {
+        assert!(index < library.len() && fade_time >= 0.0);
+        let block = library.block_length();
+        let line = SpectrumLine::new(block, library.max_partitions());
+        let mut node = Self {
+            _marker: PhantomData,
+            library: library.clone(),
+            line,
+            output: vec![0.0; block],
+            fade_output: vec![0.0; block],
+            index: 0,
+            current: index,
+            previous: index,
+            pending: index,
+            fade_time,
+            fade_length: 0,
+            fade_position: 0,
+            sample_rate: DEFAULT_SR,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// The impulse response library.
+    #[inline]
+    pub fn library(&self) -> &Arc<ImpulseLibrary> {
+        &self.library
+    }
+
+    /// Index of the current impulse response. A requested switch may still be pending.
+    #[inline]
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Switch to impulse response `index` with a crossfade.
+    #[inline]
+    pub fn set_index(&mut self, index: usize) {
+        assert!(index < self.library.len());
+        self.pending = index;
+    }
+
+    /// Whether a crossfade is in progress.
+    #[inline]
+    pub fn is_fading(&self) -> bool {
+        self.fade_position < self.fade_length
+    }
+
+    /// Set crossfade time in seconds.
+    pub fn set_fade_time(&mut self, fade_time: f64) {
+        assert!(fade_time >= 0.0);
+        self.fade_time = fade_time;
+        self.fade_length = round(fade_time * self.sample_rate) as usize;
+        self.fade_position = self.fade_length;
+    }
+}
+
+impl<T: Float> AudioNode for IrSwitch<T> {
+    const ID: u64 = 158;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = usize;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_index(setting);
+    }
+
+    fn reset(&mut self) {
+        self.line.reset();
+        self.output.fill(0.0);
+        self.fade_output.fill(0.0);
+        self.index = 0;
+        self.current = self.pending;
+        self.previous = self.pending;
+        self.fade_position = self.fade_length;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.set_fade_time(self.fade_time);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let i = self.index;
+        let y = if self.is_fading() {
+            self.fade_position += 1;
+            let w = self.fade_position as f64 / self.fade_length as f64;
+            lerp(self.fade_output[i], self.output[i], w)
+        } else {
+            self.output[i]
+        };
+        self.line.write(i, input[0].to_f64());
+        self.index += 1;
+        if self.index == self.line.block {
+            self.index = 0;
+            if !self.is_fading() && self.pending != self.current {
+                self.previous = self.current;
+                self.current = self.pending;
+                self.fade_position = 0;
+            }
+            self.line.push();
+            self.line
+                .convolve(&self.library.partitions[self.current], &mut self.output);
+            if self.is_fading() {
+                self.line.convolve(
+                    &self.library.partitions[self.previous],
+                    &mut self.fade_output,
+                );
+            }
+        }
+        [T::from_f64(y)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let omega = -TAU * frequency / self.sample_rate;
+        let block = self.line.block;
+        output[0] = input[0].filter(block as f64, |r| {
+            let mut response = Complex64::default();
+            for (k, h) in self.library.impulse(self.pending).iter().enumerate() {
+                response += Complex64::from_polar(*h, omega * (block + k) as f64);
+            }
+            r * response
+        });
+        output
+    }
+}
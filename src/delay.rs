@@ -6,7 +6,7 @@ use super::signal::*;
 use super::*;
 use num_complex::Complex64;
 use numeric_array::typenum::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Single sample delay with `N` channels.
 /// - Input(s): input signal.
@@ -125,6 +125,18 @@ impl<T: Float> AudioNode for Delay<T> {
         [output].into()
     }
 
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        let length = self.buffer.len();
+        if samples >= length {
+            self.buffer.fill(input[0]);
+        } else {
+            for j in 0..samples {
+                self.buffer[(self.i + j) % length] = input[0];
+            }
+        }
+        self.i = (self.i + samples) % length;
+    }
+
     fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
         let mut output = new_signal_frame(self.outputs());
         output[0] = input[0].filter(self.buffer.len() as f64, |r| {
@@ -443,3 +455,379 @@ where
         output
     }
 }
+
+/// Interpolation method of a fractional delay.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Nearest sample. Cheapest; delay changes are quantized to whole samples.
+    Nearest,
+    /// Linear interpolation. Attenuates high frequencies at fractional delays.
+    Linear,
+    /// Cubic spline interpolation.
+    #[default]
+    Cubic,
+    /// First order allpass interpolation. Flat magnitude response,
+    /// best for static or slowly changing delays.
+    Allpass,
+    /// Hann windowed sinc interpolation with 8 taps. Highest quality.
+    Sinc,
+}
+
+impl Interpolation {
+    /// Minimum delay in samples supported by the method.
+    pub fn min_samples(&self) -> f64 {
+        match self {
+            Interpolation::Cubic => 1.0,
+            Interpolation::Sinc => 3.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Half the number of taps in windowed sinc interpolation.
+const SINC_RADIUS: i64 = 4;
+
+/// Variable delay line with selectable interpolation.
+/// The delay can be modulation safe: then abrupt changes of delay time
+/// crossfade between read positions instead of jumping, which avoids clicks.
+/// Changes of more than one sample per sample count as abrupt.
+/// Setting: interpolation method.
+/// - Allocates: the delay line.
+/// - Input 0: input
+/// - Input 1: delay amount in seconds
+/// - Output 0: delayed input
+#[derive(Clone)]
+pub struct FracDelay<T: Float> {
+    buffer: Vec<T>,
+    i: usize,
+    sample_rate: f64,
+    interpolation: Interpolation,
+    min_delay: f64,
+    max_delay: f64,
+    /// Crossfade time in seconds for abrupt delay changes, or zero.
+    crossfade: f64,
+    /// Current delay in samples.
+    delay: f64,
+    /// Delay in samples being faded out.
+    old_delay: f64,
+    /// Crossfade position in 0...1. One when not crossfading.
+    fade: f64,
+    /// Previous output of the allpass interpolator.
+    allpass_y: T,
+}
+
+impl<T: Float> FracDelay<T> {
+    /// Create a fractional delay line. Minimum and maximum delays are specified in seconds.
+    /// Abrupt delay changes are crossfaded over `crossfade` seconds. Zero disables crossfading.
+    pub fn new(interpolation: Interpolation, min_delay: f64, max_delay: f64, crossfade: f64) -> Self {
+        assert!(min_delay >= 0.0);
+        assert!(min_delay <= max_delay);
+        let mut node = Self {
+            buffer: vec![],
+            i: 0,
+            sample_rate: 0.0,
+            interpolation,
+            min_delay,
+            max_delay,
+            crossfade,
+            delay: 0.0,
+            old_delay: 0.0,
+            fade: 1.0,
+            allpass_y: T::zero(),
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Interpolation method.
+    #[inline]
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Set interpolation method.
+    #[inline]
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+        self.allpass_y = T::zero();
+    }
+
+    /// Sample `j` samples back from the newest sample.
+    #[inline]
+    fn at(&self, j: i64) -> T {
+        let mask = self.buffer.len() - 1;
+        self.buffer[(self.i as i64 - j) as usize & mask]
+    }
+
+    /// Read the delay line `delay` samples back. The allpass interpolator
+    /// is stateful and is updated only if `update` is set.
+    /// The read position is kept in double precision for long delays,
+    /// while interpolation is done in the sample type.
+    #[inline]
+    fn read(&mut self, delay: f64, update: bool) -> T {
+        let d = floor(delay);
+        let f = delay - d;
+        let d = d as i64;
+        match self.interpolation {
+            Interpolation::Nearest => self.at(round(delay) as i64),
+            Interpolation::Linear => lerp(self.at(d), self.at(d + 1), T::from_f64(f)),
+            Interpolation::Cubic => spline(
+                self.at(d - 1),
+                self.at(d),
+                self.at(d + 1),
+                self.at(d + 2),
+                T::from_f64(f),
+            ),
+            Interpolation::Allpass => {
+                // Keep the fractional part in 0.5...1.5 when possible for a well behaved pole.
+                let (d, f) = if f < 0.5 && d > 0 { (d - 1, f + 1.0) } else { (d, f) };
+                let eta = T::from_f64((1.0 - f) / (1.0 + f));
+                let x = self.at(d);
+                let y = eta * (x - self.allpass_y) + self.at(d + 1);
+                if update {
+                    self.allpass_y = y;
+                }
+                y
+            }
+            Interpolation::Sinc => {
+                if f == 0.0 {
+                    return self.at(d);
+                }
+                // sin(PI * (j - f)) alternates in sign with integer j.
+                let sin_f = sin(PI * f);
+                let mut output = T::zero();
+                let mut weight_sum = T::zero();
+                for j in 1 - SINC_RADIUS..=SINC_RADIUS {
+                    let x = j as f64 - f;
+                    let sign = if j & 1 == 0 { -1.0 } else { 1.0 };
+                    let sinc = sign * sin_f / (PI * x);
+                    let window = 0.5 + 0.5 * cos(PI * x / SINC_RADIUS as f64);
+                    let weight = T::from_f64(sinc * window);
+                    output += weight * self.at(d + j);
+                    weight_sum += weight;
+                }
+                // Normalize for unity gain at DC.
+                output / weight_sum
+            }
+        }
+    }
+}
+
+impl<T: Float> AudioNode for FracDelay<T> {
+    const ID: u64 = 115;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = Interpolation;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_interpolation(setting);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.buffer.fill(T::zero());
+        self.delay = self.min_delay * self.sample_rate;
+        self.fade = 1.0;
+        self.allpass_y = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            let buffer_length = ceil(self.max_delay * sample_rate) as usize + 8;
+            self.sample_rate = sample_rate;
+            self.buffer.resize(buffer_length.next_power_of_two(), T::zero());
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.i = (self.i + 1) & (self.buffer.len() - 1);
+        self.buffer[self.i] = input[0];
+        let delay = max(
+            self.interpolation.min_samples(),
+            clamp(self.min_delay, self.max_delay, input[1].to_f64()) * self.sample_rate,
+        );
+        if self.crossfade > 0.0 && abs(delay - self.delay) > 1.0 && self.fade >= 1.0 {
+            self.old_delay = self.delay;
+            self.fade = 0.0;
+        }
+        self.delay = delay;
+        let output = if self.fade < 1.0 {
+            let old = self.read(self.old_delay, false);
+            let new = self.read(self.delay, true);
+            self.fade = min(1.0, self.fade + 1.0 / (self.crossfade * self.sample_rate));
+            lerp(old, new, T::from_f64(smooth3(self.fade)))
+        } else {
+            self.read(self.delay, true)
+        };
+        [output].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(self.min_delay * self.sample_rate);
+        output
+    }
+}
+
+/// Comb filter in feedforward (`y = x + g * x[n - d]`)
+/// or feedback (`y = x + g * y[n - d]`) form, with a cubic interpolated delay.
+/// With `N` = `U1`, the delay and gain are fixed.
+/// With `N` = `U3`, they are read from the inputs.
+/// Setting: (delay in seconds, gain), for fixed combs.
+/// - Allocates: the delay line.
+/// - Input 0: input
+/// - Input 1 (optional): frequency in Hz (the reciprocal of the delay)
+/// - Input 2 (optional): gain
+/// - Output 0: filtered input
+#[derive(Clone)]
+pub struct Comb<T: Float, N: Size<T>> {
+    buffer: Vec<T>,
+    i: usize,
+    sample_rate: f64,
+    /// Whether this is a feedback comb.
+    feedback: bool,
+    /// Maximum delay in seconds.
+    max_delay: f64,
+    /// Fixed delay in seconds.
+    delay: f64,
+    /// Fixed gain.
+    gain: f64,
+    _marker: PhantomData<N>,
+}
+
+impl<T: Float, N: Size<T>> Comb<T, N> {
+    /// Create comb filter with room for `max_delay` seconds of delay.
+    /// If `feedback` is set, this is a feedback comb, otherwise a feedforward comb.
+    /// Fixed combs have a delay of `delay` seconds and a gain of `gain`.
+    pub fn new(feedback: bool, max_delay: f64, delay: f64, gain: f64) -> Self {
+        assert!(max_delay > 0.0);
+        let mut node = Self {
+            buffer: vec![],
+            i: 0,
+            sample_rate: 0.0,
+            feedback,
+            max_delay,
+            delay,
+            gain,
+            _marker: PhantomData,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Fixed delay in seconds.
+    #[inline]
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+
+    /// Set fixed delay in seconds. Delays beyond the delay line are clamped.
+    #[inline]
+    pub fn set_delay(&mut self, delay: f64) {
+        self.delay = delay;
+    }
+
+    /// Fixed gain.
+    #[inline]
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    /// Set fixed gain. Feedback combs are stable for gains within -1...1.
+    #[inline]
+    pub fn set_gain(&mut self, gain: f64) {
+        self.gain = gain;
+    }
+
+    /// Sample `j` (`j` >= 1) samples back.
+    #[inline]
+    fn at(&self, j: i64) -> T {
+        let mask = self.buffer.len() - 1;
+        self.buffer[(self.i as i64 + 1 - j) as usize & mask]
+    }
+
+    /// Delay of `delay` seconds in samples, clamped to the delay line.
+    #[inline]
+    fn samples(&self, delay: f64) -> f64 {
+        clamp(2.0, self.max_delay * self.sample_rate, delay * self.sample_rate)
+    }
+
+    /// Read the delay line `delay` seconds back.
+    #[inline]
+    fn read(&self, delay: f64) -> T {
+        let delay = self.samples(delay);
+        let d = floor(delay);
+        let f = T::from_f64(delay - d);
+        let d = d as i64;
+        spline(self.at(d - 1), self.at(d), self.at(d + 1), self.at(d + 2), f)
+    }
+}
+
+impl<T: Float, N: Size<T>> AudioNode for Comb<T, N> {
+    const ID: u64 = 136;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = U1;
+    type Setting = (f64, f64);
+
+    fn set(&mut self, (delay, gain): Self::Setting) {
+        self.set_delay(delay);
+        self.set_gain(gain);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.buffer.fill(T::zero());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            let buffer_length = ceil(self.max_delay * sample_rate) as usize + 4;
+            self.sample_rate = sample_rate;
+            self.buffer.resize(buffer_length.next_power_of_two(), T::zero());
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let (delay, gain) = if N::USIZE > 1 {
+            (1.0 / input[1].to_f64(), input[2])
+        } else {
+            (self.delay, T::from_f64(self.gain))
+        };
+        let x = input[0];
+        let y = x + gain * self.read(delay);
+        self.i = (self.i + 1) & (self.buffer.len() - 1);
+        self.buffer[self.i] = if self.feedback { y } else { x };
+        [y].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        if N::USIZE > 1 {
+            output[0] = input[0].distort(0.0);
+        } else {
+            let delay = self.samples(self.delay);
+            let z = Complex64::from_polar(self.gain, -TAU * delay * frequency / self.sample_rate);
+            let feedback = self.feedback;
+            output[0] = input[0].filter(0.0, |r| {
+                if feedback {
+                    r / (1.0 - z)
+                } else {
+                    r * (1.0 + z)
+                }
+            });
+        }
+        output
+    }
+}
@@ -0,0 +1,270 @@
+//! Denormal protection.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use core::marker::PhantomData;
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+/// Flush-to-zero (FTZ) and denormals-are-zero (DAZ) bits of the SSE control register.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse"
+))]
+const FLUSH_BITS: u64 = 0x8040;
+
+/// Flush-to-zero (FZ) bit of the floating point control register.
+#[cfg(target_arch = "aarch64")]
+const FLUSH_BITS: u64 = 1 << 24;
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse"
+))]
+#[inline]
+fn get_control() -> u64 {
+    let mut csr: u32 = 0;
+    // Safety: storing the control register has no side effects.
+    unsafe {
+        core::arch::asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack, preserves_flags));
+    }
+    csr as u64
+}
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse"
+))]
+#[inline]
+fn set_control(control: u64) {
+    let csr = control as u32;
+    // Safety: we only ever toggle the flush bits of a previously read value.
+    unsafe {
+        core::arch::asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack, preserves_flags, readonly));
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+fn get_control() -> u64 {
+    let fpcr: u64;
+    // Safety: reading the control register has no side effects.
+    unsafe {
+        core::arch::asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags));
+    }
+    fpcr
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+fn set_control(control: u64) {
+    // Safety: we only ever toggle the flush bit of a previously read value.
+    unsafe {
+        core::arch::asm!("msr fpcr, {}", in(reg) control, options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// Whether denormals can be flushed to zero on this platform.
+pub const DENORMAL_FLUSH: bool = cfg!(any(
+    all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse"
+    ),
+    target_arch = "aarch64"
+));
+
+/// Whether denormals are being flushed to zero on the current thread.
+#[inline]
+pub fn is_flushing() -> bool {
+    #[cfg(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "sse"
+        ),
+        target_arch = "aarch64"
+    ))]
+    {
+        get_control() & FLUSH_BITS == FLUSH_BITS
+    }
+    #[cfg(not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "sse"
+        ),
+        target_arch = "aarch64"
+    )))]
+    {
+        false
+    }
+}
+
+/// Scoped guard that flushes denormal floats to zero on the current thread.
+/// The previous floating point mode is restored when the guard is dropped.
+/// Processing denormals is very slow on x86 in particular,
+/// so recursive structures such as reverb tails and filter states
+/// can cause CPU spikes as they decay toward zero.
+/// On platforms without flush-to-zero support this is a no-op (see `DENORMAL_FLUSH`).
+///
+/// ### Example
+/// ```
+/// use fundsp::denormal::*;
+/// let guard = DenormalGuard::new();
+/// let x = core::hint::black_box(f32::MIN_POSITIVE);
+/// if DENORMAL_FLUSH {
+///     assert_eq!(x * 0.5, 0.0);
+/// }
+/// drop(guard);
+/// ```
+pub struct DenormalGuard {
+    /// Control register value to restore, if it was changed.
+    #[allow(dead_code)]
+    restore: Option<u64>,
+    /// The floating point mode is thread local, so the guard must not leave the thread.
+    _marker: PhantomData<*const ()>,
+}
+
+impl DenormalGuard {
+    /// Start flushing denormals to zero on the current thread.
+    #[inline]
+    pub fn new() -> Self {
+        #[cfg(any(
+            all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "sse"
+            ),
+            target_arch = "aarch64"
+        ))]
+        {
+            let control = get_control();
+            if control & FLUSH_BITS != FLUSH_BITS {
+                set_control(control | FLUSH_BITS);
+                return Self {
+                    restore: Some(control),
+                    _marker: PhantomData,
+                };
+            }
+        }
+        Self {
+            restore: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Default for DenormalGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DenormalGuard {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(any(
+            all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "sse"
+            ),
+            target_arch = "aarch64"
+        ))]
+        if let Some(control) = self.restore {
+            set_control(control);
+        }
+    }
+}
+
+/// Process the contained node with denormals flushed to zero.
+/// The floating point mode is set once per block in `process` and once per sample in `tick`.
+/// If the caller holds a `DenormalGuard` around a block of ticks,
+/// the mode is already set and `tick` only reads the control register.
+#[derive(Clone)]
+pub struct FlushDenormals<X> {
+    x: X,
+}
+
+impl<X: AudioNode> FlushDenormals<X> {
+    pub fn new(x: X) -> Self {
+        let mut node = FlushDenormals { x };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+}
+
+impl<X: AudioNode> AudioNode for FlushDenormals<X> {
+    const ID: u64 = 88;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let _guard = DenormalGuard::new();
+        self.x.tick(input)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        let _guard = DenormalGuard::new();
+        self.x.process(size, input, output);
+    }
+
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        let _guard = DenormalGuard::new();
+        self.x.process_inplace(size, buffers);
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
+}
@@ -0,0 +1,736 @@
+//! Real-time audio input and output via `cpal`.
+
+use super::audiounit::*;
+use super::buffer::*;
+use super::*;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use duplicate::duplicate_item;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Size of the preallocated sample conversion buffer in frames.
+/// Device callbacks larger than this allocate.
+const SCRATCH_FRAMES: usize = 8192;
+
+/// Device configuration for `run_output`, `run_input` and `run_duplex`.
+/// Fields left at `None` use the device defaults.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceConfig {
+    /// Name of the device. `None` selects the default device of the default host.
+    pub device: Option<String>,
+    /// Preferred sample rate in Hz. The closest supported rate is chosen.
+    pub sample_rate: Option<u32>,
+    /// Preferred number of device channels.
+    pub channels: Option<u16>,
+    /// Preferred device buffer size in frames.
+    pub buffer_size: Option<u32>,
+}
+
+impl DeviceConfig {
+    /// Default configuration: default device with its default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select device by name.
+    pub fn with_device(mut self, name: &str) -> Self {
+        self.device = Some(name.to_string());
+        self
+    }
+
+    /// Request sample rate in Hz.
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// Request number of device channels.
+    pub fn with_channels(mut self, channels: u16) -> Self {
+        self.channels = Some(channels);
+        self
+    }
+
+    /// Request device buffer size in frames.
+    pub fn with_buffer_size(mut self, buffer_size: u32) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+}
+
+/// Errors from opening and controlling audio devices.
+#[derive(Debug)]
+pub enum DeviceError {
+    /// No device was found (with the requested name).
+    NoDevice,
+    /// The device supports no configuration in a sample format we can use.
+    UnsupportedConfig,
+    /// Input and output devices could not agree on a sample rate (input rate, output rate).
+    SampleRateMismatch(u32, u32),
+    /// Device enumeration failed.
+    Devices(cpal::DevicesError),
+    /// Querying a device name failed.
+    Name(cpal::DeviceNameError),
+    /// Querying the default configuration failed.
+    DefaultConfig(cpal::DefaultStreamConfigError),
+    /// Querying supported configurations failed.
+    SupportedConfigs(cpal::SupportedStreamConfigsError),
+    /// Building the stream failed.
+    Build(cpal::BuildStreamError),
+    /// Starting the stream failed.
+    Play(cpal::PlayStreamError),
+    /// Pausing the stream failed.
+    Pause(cpal::PauseStreamError),
+}
+
+impl std::fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceError::NoDevice => write!(f, "audio device not found"),
+            DeviceError::UnsupportedConfig => write!(f, "no supported device configuration"),
+            DeviceError::SampleRateMismatch(input, output) => write!(
+                f,
+                "input sample rate {} Hz does not match output sample rate {} Hz",
+                input, output
+            ),
+            DeviceError::Devices(error) => write!(f, "{}", error),
+            DeviceError::Name(error) => write!(f, "{}", error),
+            DeviceError::DefaultConfig(error) => write!(f, "{}", error),
+            DeviceError::SupportedConfigs(error) => write!(f, "{}", error),
+            DeviceError::Build(error) => write!(f, "{}", error),
+            DeviceError::Play(error) => write!(f, "{}", error),
+            DeviceError::Pause(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for DeviceError {}
+
+#[duplicate_item(
+    Error                                 Variant;
+    [ cpal::DevicesError ]                [ Devices ];
+    [ cpal::DeviceNameError ]             [ Name ];
+    [ cpal::DefaultStreamConfigError ]    [ DefaultConfig ];
+    [ cpal::SupportedStreamConfigsError ] [ SupportedConfigs ];
+    [ cpal::BuildStreamError ]            [ Build ];
+    [ cpal::PlayStreamError ]             [ Play ];
+    [ cpal::PauseStreamError ]            [ Pause ];
+)]
+impl From<Error> for DeviceError {
+    fn from(error: Error) -> Self {
+        DeviceError::Variant(error)
+    }
+}
+
+/// Handle to a running input or output stream. Dropping the handle stops the stream.
+pub struct DeviceHandle {
+    stream: cpal::Stream,
+    sample_rate: f64,
+    channels: usize,
+}
+
+impl DeviceHandle {
+    /// Negotiated sample rate in Hz. The unit is processed at this rate.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Number of device channels.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Resume a paused stream.
+    pub fn play(&self) -> Result<(), DeviceError> {
+        self.stream.play()?;
+        Ok(())
+    }
+
+    /// Pause the stream. Processing is suspended until `play` is called.
+    pub fn pause(&self) -> Result<(), DeviceError> {
+        self.stream.pause()?;
+        Ok(())
+    }
+
+    /// Stop the stream and release the device.
+    pub fn stop(self) {}
+}
+
+/// State shared between the input and output callbacks of a duplex stream.
+/// Input frames are carried to the output callback in a lock-free
+/// single producer, single consumer ring buffer of interleaved `f32` samples.
+struct DuplexState {
+    data: Vec<AtomicU32>,
+    channels: usize,
+    /// Total number of samples written.
+    written: AtomicUsize,
+    /// Total number of samples read.
+    read: AtomicUsize,
+    /// Number of times input ran late.
+    underruns: AtomicUsize,
+    /// Number of input frames dropped because input ran ahead.
+    overruns: AtomicUsize,
+    /// Latency from capture to the input callback in seconds, as bits.
+    input_latency: AtomicU64,
+    /// Latency from the output callback to playback in seconds, as bits.
+    output_latency: AtomicU64,
+}
+
+impl DuplexState {
+    fn new(channels: usize, frames: usize) -> Self {
+        let channels = std::cmp::max(channels, 1);
+        let size = (frames * channels).next_power_of_two();
+        Self {
+            data: (0..size).map(|_| AtomicU32::new(0)).collect(),
+            channels,
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            underruns: AtomicUsize::new(0),
+            overruns: AtomicUsize::new(0),
+            input_latency: AtomicU64::new(0),
+            output_latency: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of frames buffered.
+    fn buffered(&self) -> usize {
+        let read = self.read.load(Ordering::Acquire);
+        (self.written.load(Ordering::Acquire) - read) / self.channels
+    }
+
+    /// Write interleaved samples. Frames that do not fit are dropped.
+    fn write(&self, samples: &[f32]) {
+        let written = self.written.load(Ordering::Relaxed);
+        let free = self.data.len() - (written - self.read.load(Ordering::Acquire));
+        let n = std::cmp::min(samples.len(), free / self.channels * self.channels);
+        let mask = self.data.len() - 1;
+        for (i, x) in samples[..n].iter().enumerate() {
+            self.data[(written + i) & mask].store(x.to_bits(), Ordering::Relaxed);
+        }
+        self.written.store(written + n, Ordering::Release);
+        if n < samples.len() {
+            self.overruns
+                .fetch_add((samples.len() - n) / self.channels, Ordering::Relaxed);
+        }
+    }
+
+    /// Read a frame into `frame`. Returns false if the buffer is empty.
+    fn read_frame(&self, frame: &mut [f32]) -> bool {
+        let read = self.read.load(Ordering::Relaxed);
+        if self.written.load(Ordering::Acquire) - read < self.channels {
+            return false;
+        }
+        let mask = self.data.len() - 1;
+        for (i, x) in frame.iter_mut().enumerate() {
+            *x = f32::from_bits(self.data[(read + i) & mask].load(Ordering::Relaxed));
+        }
+        self.read.store(read + self.channels, Ordering::Release);
+        true
+    }
+
+    /// Drop the `frames` oldest frames.
+    fn skip(&self, frames: usize) {
+        self.read
+            .fetch_add(frames * self.channels, Ordering::Release);
+        self.overruns.fetch_add(frames, Ordering::Relaxed);
+    }
+}
+
+/// Handle to a running duplex stream. Dropping the handle stops both streams.
+pub struct DuplexHandle {
+    input: cpal::Stream,
+    output: cpal::Stream,
+    state: Arc<DuplexState>,
+    sample_rate: f64,
+    input_channels: usize,
+    output_channels: usize,
+    /// Latency of the unit in samples.
+    unit_latency: f64,
+}
+
+impl DuplexHandle {
+    /// Negotiated sample rate in Hz. The unit is processed at this rate.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Number of input device channels.
+    pub fn input_channels(&self) -> usize {
+        self.input_channels
+    }
+
+    /// Number of output device channels.
+    pub fn output_channels(&self) -> usize {
+        self.output_channels
+    }
+
+    /// Number of input frames presently buffered between the input and output callbacks.
+    pub fn buffered(&self) -> usize {
+        self.state.buffered()
+    }
+
+    /// Current estimate of total latency from capture to playback in seconds.
+    /// It is the sum of device latencies reported by the host,
+    /// buffering between the callbacks and the latency of the unit.
+    pub fn latency(&self) -> f64 {
+        f64::from_bits(self.state.input_latency.load(Ordering::Relaxed))
+            + f64::from_bits(self.state.output_latency.load(Ordering::Relaxed))
+            + (self.state.buffered() as f64 + self.unit_latency) / self.sample_rate
+    }
+
+    /// Number of times input ran late. Each time, the unit receives silence
+    /// until the buffer has been refilled.
+    pub fn underruns(&self) -> usize {
+        self.state.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Number of input frames dropped because input ran ahead of output.
+    pub fn overruns(&self) -> usize {
+        self.state.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Resume paused streams.
+    pub fn play(&self) -> Result<(), DeviceError> {
+        self.input.play()?;
+        self.output.play()?;
+        Ok(())
+    }
+
+    /// Pause both streams. Processing is suspended until `play` is called.
+    pub fn pause(&self) -> Result<(), DeviceError> {
+        self.output.pause()?;
+        self.input.pause()?;
+        Ok(())
+    }
+
+    /// Stop both streams and release the devices.
+    pub fn stop(self) {}
+}
+
+/// Preference order of sample formats. Returns `None` for formats we cannot use.
+fn format_rank(format: SampleFormat, default: SampleFormat) -> Option<usize> {
+    match format {
+        SampleFormat::F32 | SampleFormat::I16 | SampleFormat::U16 if format == default => Some(0),
+        SampleFormat::F32 => Some(1),
+        SampleFormat::I16 => Some(2),
+        SampleFormat::U16 => Some(3),
+        _ => None,
+    }
+}
+
+/// Source channel for channel `index` when there are `channels` source channels.
+/// A mono source is sent to all channels. Otherwise, channel `i` maps to channel `i`
+/// and the remaining channels are silent.
+fn map_channel(channels: usize, index: usize) -> Option<usize> {
+    if channels == 1 {
+        Some(0)
+    } else if index < channels {
+        Some(index)
+    } else {
+        None
+    }
+}
+
+/// Find the input or output device named in `config`, or the default device.
+fn find_device(config: &DeviceConfig, input: bool) -> Result<cpal::Device, DeviceError> {
+    let host = cpal::default_host();
+    match &config.device {
+        Some(name) => {
+            let devices: Vec<cpal::Device> = if input {
+                host.input_devices()?.collect()
+            } else {
+                host.output_devices()?.collect()
+            };
+            for device in devices {
+                if device.name()? == *name {
+                    return Ok(device);
+                }
+            }
+            Err(DeviceError::NoDevice)
+        }
+        None => {
+            let device = if input {
+                host.default_input_device()
+            } else {
+                host.default_output_device()
+            };
+            device.ok_or(DeviceError::NoDevice)
+        }
+    }
+}
+
+/// Negotiate an input or output stream configuration. The requested channel count
+/// is preferred first, then the sample rate closest to the requested rate, then the sample format.
+fn negotiate(
+    device: &cpal::Device,
+    config: &DeviceConfig,
+    input: bool,
+) -> Result<(cpal::StreamConfig, SampleFormat), DeviceError> {
+    let default = if input {
+        device.default_input_config()?
+    } else {
+        device.default_output_config()?
+    };
+    let sample_rate = config.sample_rate.unwrap_or(default.sample_rate().0);
+    let channels = config.channels.unwrap_or(default.channels());
+    let supported = if format_rank(default.sample_format(), default.sample_format()).is_some()
+        && sample_rate == default.sample_rate().0
+        && channels == default.channels()
+    {
+        default
+    } else {
+        let ranges: Vec<cpal::SupportedStreamConfigRange> = if input {
+            device.supported_input_configs()?.collect()
+        } else {
+            device.supported_output_configs()?.collect()
+        };
+        let mut best: Option<((bool, u32, usize), cpal::SupportedStreamConfig)> = None;
+        for range in ranges {
+            let Some(rank) = format_rank(range.sample_format(), default.sample_format()) else {
+                continue;
+            };
+            let rate = sample_rate.clamp(range.min_sample_rate().0, range.max_sample_rate().0);
+            let score = (
+                range.channels() != channels,
+                rate.abs_diff(sample_rate),
+                rank,
+            );
+            if !matches!(&best, Some((best_score, _)) if *best_score <= score) {
+                best = Some((score, range.with_sample_rate(cpal::SampleRate(rate))));
+            }
+        }
+        best.map(|(_, config)| config)
+            .ok_or(DeviceError::UnsupportedConfig)?
+    };
+    let format = supported.sample_format();
+    let mut stream_config: cpal::StreamConfig = supported.into();
+    if let Some(size) = config.buffer_size {
+        stream_config.buffer_size = cpal::BufferSize::Fixed(size);
+    }
+    Ok((stream_config, format))
+}
+
+/// Build an output stream writing samples of type `T`. The callback fills interleaved
+/// `f32` samples and receives the output latency in seconds reported by the host.
+fn build_output<T: SizedSample + FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut callback: impl FnMut(&mut [f32], f64) + Send + 'static,
+) -> Result<cpal::Stream, DeviceError> {
+    let mut scratch = vec![0.0; SCRATCH_FRAMES * config.channels as usize];
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], info: &cpal::OutputCallbackInfo| {
+            let timestamp = info.timestamp();
+            let latency = timestamp
+                .playback
+                .duration_since(&timestamp.callback)
+                .map_or(0.0, |duration| duration.as_secs_f64());
+            if scratch.len() < data.len() {
+                scratch.resize(data.len(), 0.0);
+            }
+            callback(&mut scratch[..data.len()], latency);
+            for (y, x) in data.iter_mut().zip(scratch.iter()) {
+                *y = T::from_sample(*x);
+            }
+        },
+        |error| eprintln!("an error occurred on output stream: {}", error),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// Build an input stream reading samples of type `T`. The callback receives interleaved
+/// `f32` samples and the input latency in seconds reported by the host.
+fn build_input<T: SizedSample>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut callback: impl FnMut(&[f32], f64) + Send + 'static,
+) -> Result<cpal::Stream, DeviceError>
+where
+    f32: FromSample<T>,
+{
+    let mut scratch = Vec::with_capacity(SCRATCH_FRAMES * config.channels as usize);
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], info: &cpal::InputCallbackInfo| {
+            let timestamp = info.timestamp();
+            let latency = timestamp
+                .callback
+                .duration_since(&timestamp.capture)
+                .map_or(0.0, |duration| duration.as_secs_f64());
+            scratch.clear();
+            scratch.extend(data.iter().map(|x| x.to_sample::<f32>()));
+            callback(&scratch, latency);
+        },
+        |error| eprintln!("an error occurred on input stream: {}", error),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// Build an output stream in sample format `f32`, `i16` or `u16`.
+fn open_output(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    format: SampleFormat,
+    callback: impl FnMut(&mut [f32], f64) + Send + 'static,
+) -> Result<cpal::Stream, DeviceError> {
+    match format {
+        SampleFormat::F32 => build_output::<f32>(device, config, callback),
+        SampleFormat::I16 => build_output::<i16>(device, config, callback),
+        SampleFormat::U16 => build_output::<u16>(device, config, callback),
+        _ => Err(DeviceError::UnsupportedConfig),
+    }
+}
+
+/// Build an input stream in sample format `f32`, `i16` or `u16`.
+fn open_input(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    format: SampleFormat,
+    callback: impl FnMut(&[f32], f64) + Send + 'static,
+) -> Result<cpal::Stream, DeviceError> {
+    match format {
+        SampleFormat::F32 => build_input::<f32>(device, config, callback),
+        SampleFormat::I16 => build_input::<i16>(device, config, callback),
+        SampleFormat::U16 => build_input::<u16>(device, config, callback),
+        _ => Err(DeviceError::UnsupportedConfig),
+    }
+}
+
+#[allow(clippy::unnecessary_cast)]
+#[duplicate_item(
+    f48       interleave48       deinterleave48;
+    [ f64 ]   [ interleave64 ]   [ deinterleave64 ];
+    [ f32 ]   [ interleave32 ]   [ deinterleave32 ];
+)]
+/// Write `outputs` channels from `buffer` into an interleaved block of device samples.
+fn interleave48(buffer: &Buffer<f48>, outputs: usize, block: &mut [f32], channels: usize) {
+    for (i, frame) in block.chunks_mut(channels).enumerate() {
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            *sample = match map_channel(outputs, channel) {
+                Some(output) => buffer.at(output)[i] as f32,
+                None => 0.0,
+            };
+        }
+    }
+}
+
+#[allow(clippy::unnecessary_cast)]
+#[duplicate_item(
+    f48       interleave48       deinterleave48;
+    [ f64 ]   [ interleave64 ]   [ deinterleave64 ];
+    [ f32 ]   [ interleave32 ]   [ deinterleave32 ];
+)]
+/// Read an interleaved block of device samples into `inputs` channels of `buffer`.
+fn deinterleave48(block: &[f32], channels: usize, buffer: &mut Buffer<f48>, inputs: usize) {
+    for input in 0..inputs {
+        let data = buffer.mut_at(input);
+        match map_channel(channels, input) {
+            Some(channel) => {
+                for (i, frame) in block.chunks(channels).enumerate() {
+                    data[i] = frame[channel] as f48;
+                }
+            }
+            None => data[..block.len() / channels].fill(0.0),
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       AudioUnit48       run_output48       interleave48;
+    [ f64 ]   [ AudioUnit64 ]   [ run_output ]     [ interleave64 ];
+    [ f32 ]   [ AudioUnit32 ]   [ run_output32 ]   [ interleave32 ];
+)]
+/// Play `unit` on an output device. The unit must have no inputs.
+/// The device configuration is negotiated from `config` and
+/// the sample rate of the unit is set to the negotiated rate.
+/// Sample formats `f32`, `i16` and `u16` are supported.
+/// A mono unit is sent to all device channels. Otherwise, unit output `i`
+/// is sent to device channel `i` and any remaining device channels are silent.
+/// Playback starts immediately and continues until the returned handle is dropped.
+pub fn run_output48(
+    mut unit: Box<dyn AudioUnit48>,
+    config: DeviceConfig,
+) -> Result<DeviceHandle, DeviceError> {
+    assert_eq!(unit.inputs(), 0);
+    let device = find_device(&config, false)?;
+    let (stream_config, format) = negotiate(&device, &config, false)?;
+    let sample_rate = stream_config.sample_rate.0 as f64;
+    let channels = stream_config.channels as usize;
+    unit.set_sample_rate(sample_rate);
+    unit.allocate();
+    let outputs = unit.outputs();
+    let mut buffer = Buffer::<f48>::with_channels(outputs);
+    let stream = open_output(&device, &stream_config, format, move |data, _latency| {
+        for block in data.chunks_mut(channels * MAX_BUFFER_SIZE) {
+            unit.process(block.len() / channels, &[], buffer.self_mut());
+            interleave48(&buffer, outputs, block, channels);
+        }
+    })?;
+    stream.play()?;
+    Ok(DeviceHandle {
+        stream,
+        sample_rate,
+        channels,
+    })
+}
+
+#[duplicate_item(
+    f48       AudioUnit48       run_input48       deinterleave48;
+    [ f64 ]   [ AudioUnit64 ]   [ run_input ]     [ deinterleave64 ];
+    [ f32 ]   [ AudioUnit32 ]   [ run_input32 ]   [ deinterleave32 ];
+)]
+/// Feed input device channels into the inputs of `unit`, which is processed
+/// in the input callback. Outputs of the unit are discarded, so it is typically
+/// an analysis or recording graph communicating via shared variables or snoops.
+/// The device configuration is negotiated from `config` and
+/// the sample rate of the unit is set to the negotiated rate.
+/// Sample formats `f32`, `i16` and `u16` are supported.
+/// A mono device is sent to all unit inputs. Otherwise, device channel `i`
+/// is sent to unit input `i` and any remaining unit inputs are silent.
+/// Capture starts immediately and continues until the returned handle is dropped.
+pub fn run_input48(
+    mut unit: Box<dyn AudioUnit48>,
+    config: DeviceConfig,
+) -> Result<DeviceHandle, DeviceError> {
+    let device = find_device(&config, true)?;
+    let (stream_config, format) = negotiate(&device, &config, true)?;
+    let sample_rate = stream_config.sample_rate.0 as f64;
+    let channels = stream_config.channels as usize;
+    unit.set_sample_rate(sample_rate);
+    unit.allocate();
+    let inputs = unit.inputs();
+    let mut input = Buffer::<f48>::with_channels(inputs);
+    let mut output = Buffer::<f48>::with_channels(unit.outputs());
+    let stream = open_input(&device, &stream_config, format, move |data, _latency| {
+        for block in data.chunks(channels * MAX_BUFFER_SIZE) {
+            deinterleave48(block, channels, &mut input, inputs);
+            unit.process(block.len() / channels, input.self_ref(), output.self_mut());
+        }
+    })?;
+    stream.play()?;
+    Ok(DeviceHandle {
+        stream,
+        sample_rate,
+        channels,
+    })
+}
+
+#[duplicate_item(
+    f48       AudioUnit48       run_duplex48       interleave48       deinterleave48;
+    [ f64 ]   [ AudioUnit64 ]   [ run_duplex ]     [ interleave64 ]   [ deinterleave64 ];
+    [ f32 ]   [ AudioUnit32 ]   [ run_duplex32 ]   [ interleave32 ]   [ deinterleave32 ];
+)]
+/// Process live input with `unit` and play the result. Input device channels
+/// are sent to unit inputs and unit outputs to output device channels;
+/// mono signals are sent to all channels and otherwise channel `i` maps to channel `i`.
+/// The output device configuration is negotiated from `output_config` first;
+/// the input device is then opened at the same sample rate.
+/// Input and output callbacks are reconciled with a ring buffer that holds
+/// `latency` seconds of input (plus one output callback) before processing starts.
+/// If input runs late, the unit receives silence until the buffer has refilled;
+/// if input runs ahead, the oldest input is dropped.
+/// Streams start immediately and continue until the returned handle is dropped.
+pub fn run_duplex48(
+    mut unit: Box<dyn AudioUnit48>,
+    input_config: DeviceConfig,
+    output_config: DeviceConfig,
+    latency: f64,
+) -> Result<DuplexHandle, DeviceError> {
+    let output_device = find_device(&output_config, false)?;
+    let (output_stream_config, output_format) = negotiate(&output_device, &output_config, false)?;
+    let rate = output_stream_config.sample_rate.0;
+    let input_config = DeviceConfig {
+        sample_rate: Some(rate),
+        ..input_config
+    };
+    let input_device = find_device(&input_config, true)?;
+    let (input_stream_config, input_format) = negotiate(&input_device, &input_config, true)?;
+    if input_stream_config.sample_rate.0 != rate {
+        return Err(DeviceError::SampleRateMismatch(
+            input_stream_config.sample_rate.0,
+            rate,
+        ));
+    }
+    let sample_rate = rate as f64;
+    let input_channels = input_stream_config.channels as usize;
+    let output_channels = output_stream_config.channels as usize;
+    unit.set_sample_rate(sample_rate);
+    unit.allocate();
+    let unit_latency = unit.latency().unwrap_or(0.0);
+    let prefill = (latency * sample_rate).round() as usize;
+    let state = Arc::new(DuplexState::new(
+        input_channels,
+        2 * (prefill + SCRATCH_FRAMES),
+    ));
+
+    let input_state = state.clone();
+    let input_stream = open_input(
+        &input_device,
+        &input_stream_config,
+        input_format,
+        move |data, latency| {
+            input_state
+                .input_latency
+                .store(latency.to_bits(), Ordering::Relaxed);
+            input_state.write(data);
+        },
+    )?;
+
+    let output_state = state.clone();
+    let inputs = unit.inputs();
+    let outputs = unit.outputs();
+    let mut input = Buffer::<f48>::with_channels(inputs);
+    let mut output = Buffer::<f48>::with_channels(outputs);
+    let mut block_in = vec![0.0; MAX_BUFFER_SIZE * input_channels];
+    let mut primed = false;
+    let output_stream = open_output(
+        &output_device,
+        &output_stream_config,
+        output_format,
+        move |data, latency| {
+            let state = &output_state;
+            state
+                .output_latency
+                .store(latency.to_bits(), Ordering::Relaxed);
+            let frames = data.len() / output_channels;
+            let buffered = state.buffered();
+            if !primed && buffered >= prefill + frames {
+                primed = true;
+            }
+            if primed && buffered > 2 * (prefill + frames) {
+                state.skip(buffered - prefill - frames);
+            }
+            for block in data.chunks_mut(output_channels * MAX_BUFFER_SIZE) {
+                let size = block.len() / output_channels;
+                let block_in = &mut block_in[..size * input_channels];
+                for frame in block_in.chunks_mut(input_channels) {
+                    if !(primed && state.read_frame(frame)) {
+                        if primed {
+                            state.underruns.fetch_add(1, Ordering::Relaxed);
+                            primed = false;
+                        }
+                        frame.fill(0.0);
+                    }
+                }
+                deinterleave48(block_in, input_channels, &mut input, inputs);
+                unit.process(size, input.self_ref(), output.self_mut());
+                interleave48(&output, outputs, block, output_channels);
+            }
+        },
+    )?;
+
+    input_stream.play()?;
+    output_stream.play()?;
+    Ok(DuplexHandle {
+        input: input_stream,
+        output: output_stream,
+        state,
+        sample_rate,
+        input_channels,
+        output_channels,
+        unit_latency,
+    })
+}
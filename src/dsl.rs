@@ -0,0 +1,567 @@
+//! Runtime patch language that builds networks from expressions.
+//!
+//! Expressions use the same opcodes and operators as Rust code:
+//! `saw_hz(110.0) >> lowpass_hz(800.0, 1.0) * 0.3`.
+//! Operator precedence follows Rust. Opcode arguments are numbers
+//! or constant arithmetic expressions. A number standing alone in a graph
+//! expression becomes a constant generator, as with `dc`.
+
+use super::audiounit::*;
+use super::math::*;
+use super::net::*;
+use duplicate::duplicate_item;
+use std::collections::HashMap;
+
+/// Errors from parsing patch expressions. Positions are byte offsets into the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatchError {
+    /// Unexpected character at the position.
+    Character(usize),
+    /// Unexpected token at the position, or end of input.
+    Token(usize),
+    /// Unknown opcode at the position.
+    Opcode(String, usize),
+    /// Wrong number of arguments to an opcode (opcode, expected, found).
+    Arguments(String, usize, usize),
+    /// An opcode argument at the position is not a number.
+    Number(usize),
+    /// The operator at the position cannot be applied to units.
+    Operator(usize),
+    /// The operands of the operator at the position have mismatched channels.
+    Channels(usize),
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchError::Character(position) => {
+                write!(f, "unexpected character at position {}", position)
+            }
+            PatchError::Token(position) => write!(f, "unexpected token at position {}", position),
+            PatchError::Opcode(name, position) => {
+                write!(f, "unknown opcode `{}` at position {}", name, position)
+            }
+            PatchError::Arguments(name, expected, found) => write!(
+                f,
+                "opcode `{}` takes {} arguments but {} were given",
+                name, expected, found
+            ),
+            PatchError::Number(position) => {
+                write!(f, "argument at position {} is not a number", position)
+            }
+            PatchError::Operator(position) => write!(
+                f,
+                "operator at position {} cannot be applied to units",
+                position
+            ),
+            PatchError::Channels(position) => write!(
+                f,
+                "mismatched channels for operator at position {}",
+                position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Lexical tokens.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Pipe,
+    Stack,
+    Bus,
+    Branch,
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Thru,
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+/// Split `source` into tokens and their positions.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, PatchError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if c.is_ascii_digit() || (c == b'.' && i + 1 < bytes.len() && bytes[i + 1].is_ascii_digit())
+        {
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+                let mut j = i + 1;
+                if j < bytes.len() && (bytes[j] == b'+' || bytes[j] == b'-') {
+                    j += 1;
+                }
+                if j < bytes.len() && bytes[j].is_ascii_digit() {
+                    i = j;
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            match source[start..i].parse::<f64>() {
+                Ok(x) => tokens.push((Token::Number(x), start)),
+                Err(_) => return Err(PatchError::Character(start)),
+            }
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push((Token::Name(source[start..i].to_string()), start));
+            continue;
+        }
+        let token = match c {
+            b'>' if i + 1 < bytes.len() && bytes[i + 1] == b'>' => {
+                i += 1;
+                Token::Pipe
+            }
+            b'|' => Token::Stack,
+            b'&' => Token::Bus,
+            b'^' => Token::Branch,
+            b'+' => Token::Plus,
+            b'-' => Token::Minus,
+            b'*' => Token::Times,
+            b'/' => Token::Divide,
+            b'!' => Token::Thru,
+            b'(' => Token::Open,
+            b')' => Token::Close,
+            b',' => Token::Comma,
+            _ => return Err(PatchError::Character(start)),
+        };
+        i += 1;
+        tokens.push((token, start));
+    }
+    tokens.push((Token::End, source.len()));
+    Ok(tokens)
+}
+
+#[duplicate_item(
+    f48       Net48       Opcode48;
+    [ f64 ]   [ Net64 ]   [ Opcode64 ];
+    [ f32 ]   [ Net32 ]   [ Opcode32 ];
+)]
+/// Opcode constructor. Takes the arguments and returns the unit.
+pub type Opcode48 = Box<dyn Fn(&[f48]) -> Net48 + Send + Sync>;
+
+#[duplicate_item(
+    f48       Net48       Value48;
+    [ f64 ]   [ Net64 ]   [ Value64 ];
+    [ f32 ]   [ Net32 ]   [ Value32 ];
+)]
+/// Value of a subexpression.
+enum Value48 {
+    Number(f48),
+    Unit(Net48),
+}
+
+#[duplicate_item(
+    f48       Net48       Opcode48       Patch48;
+    [ f64 ]   [ Net64 ]   [ Opcode64 ]   [ Patch64 ];
+    [ f32 ]   [ Net32 ]   [ Opcode32 ]   [ Patch32 ];
+)]
+/// Patch language parser. Builds networks from expressions at runtime,
+/// resolving opcodes by name from a table that can be extended.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let patch = Patch64::new();
+/// let net = patch.parse("saw_hz(110.0) >> lowpass_hz(800.0, 1.0) * 0.3").unwrap();
+/// assert!(net.inputs() == 0 && net.outputs() == 1);
+/// assert!(patch.parse("saw_hz(110.0) >> pan(0.0) >> pass()").is_err());
+/// ```
+pub struct Patch48 {
+    opcodes: HashMap<String, (usize, Opcode48)>,
+}
+
+#[duplicate_item(
+    f48       Net48       Opcode48       Patch48;
+    [ f64 ]   [ Net64 ]   [ Opcode64 ]   [ Patch64 ];
+    [ f32 ]   [ Net32 ]   [ Opcode32 ]   [ Patch32 ];
+)]
+impl Default for Patch48 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[duplicate_item(
+    f48       Net48       Opcode48       Patch48       Value48       Parser48       hacker48;
+    [ f64 ]   [ Net64 ]   [ Opcode64 ]   [ Patch64 ]   [ Value64 ]   [ Parser64 ]   [ hacker ];
+    [ f32 ]   [ Net32 ]   [ Opcode32 ]   [ Patch32 ]   [ Value32 ]   [ Parser32 ]   [ hacker32 ];
+)]
+#[allow(clippy::unnecessary_cast)]
+impl Patch48 {
+    /// Create parser with the built-in opcodes.
+    pub fn new() -> Self {
+        use super::hacker48::*;
+        let mut patch = Self {
+            opcodes: HashMap::new(),
+        };
+        macro_rules! opcode {
+            ($name:ident, $arity:expr, |$a:ident| $unit:expr) => {
+                patch.add_opcode(stringify!($name), $arity, |$a| Net48::wrap(Box::new($unit)));
+            };
+        }
+        // Generators.
+        opcode!(zero, 0, |_a| zero());
+        opcode!(dc, 1, |a| dc(a[0]));
+        opcode!(sine, 0, |_a| sine());
+        opcode!(sine_hz, 1, |a| sine_hz(a[0]));
+        opcode!(saw, 0, |_a| saw());
+        opcode!(saw_hz, 1, |a| saw_hz(a[0]));
+        opcode!(square, 0, |_a| square());
+        opcode!(square_hz, 1, |a| square_hz(a[0]));
+        opcode!(triangle, 0, |_a| triangle());
+        opcode!(triangle_hz, 1, |a| triangle_hz(a[0]));
+        opcode!(organ, 0, |_a| organ());
+        opcode!(organ_hz, 1, |a| organ_hz(a[0]));
+        opcode!(soft_saw, 0, |_a| soft_saw());
+        opcode!(soft_saw_hz, 1, |a| soft_saw_hz(a[0]));
+        opcode!(hammond, 0, |_a| hammond());
+        opcode!(hammond_hz, 1, |a| hammond_hz(a[0]));
+        opcode!(pulse, 0, |_a| pulse());
+        opcode!(noise, 0, |_a| noise());
+        opcode!(white, 0, |_a| white());
+        opcode!(pink, 0, |_a| pink());
+        opcode!(brown, 0, |_a| brown());
+        opcode!(mls, 0, |_a| mls());
+        opcode!(pluck, 3, |a| pluck(a[0], a[1], a[2]));
+        // Filters.
+        opcode!(lowpass, 0, |_a| lowpass());
+        opcode!(lowpass_hz, 2, |a| lowpass_hz(a[0], a[1]));
+        opcode!(highpass, 0, |_a| highpass());
+        opcode!(highpass_hz, 2, |a| highpass_hz(a[0], a[1]));
+        opcode!(bandpass, 0, |_a| bandpass());
+        opcode!(bandpass_hz, 2, |a| bandpass_hz(a[0], a[1]));
+        opcode!(notch, 0, |_a| notch());
+        opcode!(notch_hz, 2, |a| notch_hz(a[0], a[1]));
+        opcode!(peak, 0, |_a| peak());
+        opcode!(peak_hz, 2, |a| peak_hz(a[0], a[1]));
+        opcode!(allpass, 0, |_a| allpass());
+        opcode!(allpass_hz, 2, |a| allpass_hz(a[0], a[1]));
+        opcode!(bell_hz, 3, |a| bell_hz(a[0], a[1], a[2]));
+        opcode!(lowshelf_hz, 3, |a| lowshelf_hz(a[0], a[1], a[2]));
+        opcode!(highshelf_hz, 3, |a| highshelf_hz(a[0], a[1], a[2]));
+        opcode!(moog, 0, |_a| moog());
+        opcode!(moog_hz, 2, |a| moog_hz(a[0], a[1]));
+        opcode!(resonator, 0, |_a| resonator());
+        opcode!(resonator_hz, 2, |a| resonator_hz(a[0], a[1]));
+        opcode!(butterpass, 0, |_a| butterpass());
+        opcode!(butterpass_hz, 1, |a| butterpass_hz(a[0]));
+        opcode!(lowpole, 0, |_a| lowpole());
+        opcode!(lowpole_hz, 1, |a| lowpole_hz(a[0]));
+        opcode!(highpole, 0, |_a| highpole());
+        opcode!(highpole_hz, 1, |a| highpole_hz(a[0]));
+        opcode!(dcblock, 0, |_a| dcblock());
+        opcode!(dcblock_hz, 1, |a| dcblock_hz(a[0]));
+        opcode!(pinkpass, 0, |_a| pinkpass());
+        // Effects and utilities.
+        opcode!(pass, 0, |_a| pass());
+        opcode!(sink, 0, |_a| sink());
+        opcode!(add, 1, |a| add(a[0]));
+        opcode!(sub, 1, |a| sub(a[0]));
+        opcode!(mul, 1, |a| mul(a[0]));
+        opcode!(delay, 1, |a| delay(a[0]));
+        opcode!(clip, 0, |_a| clip());
+        opcode!(clip_to, 2, |a| clip_to(a[0], a[1]));
+        opcode!(declick, 0, |_a| declick());
+        opcode!(follow, 1, |a| follow(a[0]));
+        opcode!(limiter, 2, |a| limiter((a[0], a[1])));
+        opcode!(pan, 1, |a| pan(a[0]));
+        opcode!(chorus, 4, |a| chorus(a[0] as i64, a[1], a[2], a[3]));
+        opcode!(reverb_stereo, 3, |a| reverb_stereo(
+            a[0] as f64,
+            a[1] as f64,
+            a[2] as f64
+        ));
+        patch.add_opcode("split", 1, |a| {
+            let n = max(1.0, round(a[0] as f64)) as usize;
+            let mut net = Net48::new(1, n);
+            for i in 0..n {
+                net.pass_through(0, i);
+            }
+            net
+        });
+        patch.add_opcode("join", 1, |a| {
+            let n = max(1.0, round(a[0] as f64)) as usize;
+            let mut net = Net48::wrap(Box::new(pass()));
+            for _ in 1..n {
+                net = net + Net48::wrap(Box::new(pass()));
+            }
+            net * (1.0 / n as f48)
+        });
+        patch
+    }
+
+    /// Add or replace opcode `name` taking `arity` numeric arguments.
+    pub fn add_opcode(
+        &mut self,
+        name: &str,
+        arity: usize,
+        opcode: impl Fn(&[f48]) -> Net48 + Send + Sync + 'static,
+    ) {
+        self.opcodes
+            .insert(name.to_string(), (arity, Box::new(opcode)));
+    }
+
+    /// Whether opcode `name` is known.
+    pub fn has_opcode(&self, name: &str) -> bool {
+        self.opcodes.contains_key(name)
+    }
+
+    /// Build a network from expression `source`.
+    pub fn parse(&self, source: &str) -> Result<Net48, PatchError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser48 {
+            patch: self,
+            tokens,
+            index: 0,
+        };
+        let value = parser.stack()?;
+        match parser.peek() {
+            (Token::End, _) => Ok(Parser48::unit(value)),
+            (_, position) => Err(PatchError::Token(position)),
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       Net48       Patch48       Value48       Parser48;
+    [ f64 ]   [ Net64 ]   [ Patch64 ]   [ Value64 ]   [ Parser64 ];
+    [ f32 ]   [ Net32 ]   [ Patch32 ]   [ Value32 ]   [ Parser32 ];
+)]
+/// Recursive descent parser state.
+struct Parser48<'a> {
+    patch: &'a Patch48,
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+}
+
+#[duplicate_item(
+    f48       Net48       Patch48       Value48       Parser48       hacker48;
+    [ f64 ]   [ Net64 ]   [ Patch64 ]   [ Value64 ]   [ Parser64 ]   [ hacker ];
+    [ f32 ]   [ Net32 ]   [ Patch32 ]   [ Value32 ]   [ Parser32 ]   [ hacker32 ];
+)]
+impl<'a> Parser48<'a> {
+    /// Current token and its position.
+    fn peek(&self) -> (Token, usize) {
+        self.tokens[self.index].clone()
+    }
+
+    /// Advance to the next token.
+    fn next(&mut self) {
+        if self.index + 1 < self.tokens.len() {
+            self.index += 1;
+        }
+    }
+
+    /// Convert a value into a unit. Numbers become constant generators.
+    fn unit(value: Value48) -> Net48 {
+        match value {
+            Value48::Number(x) => Net48::wrap(Box::new(super::hacker48::dc(x))),
+            Value48::Unit(net) => net,
+        }
+    }
+
+    /// Parse a left associative chain of binary operators at one precedence level.
+    fn binary(
+        &mut self,
+        operators: &[Token],
+        operand: fn(&mut Self) -> Result<Value48, PatchError>,
+        apply: fn(Token, usize, Value48, Value48) -> Result<Value48, PatchError>,
+    ) -> Result<Value48, PatchError> {
+        let mut value = operand(self)?;
+        loop {
+            let (token, position) = self.peek();
+            if !operators.contains(&token) {
+                return Ok(value);
+            }
+            self.next();
+            let right = operand(self)?;
+            value = apply(token, position, value, right)?;
+        }
+    }
+
+    /// Apply a network operator to two values.
+    fn network(
+        token: Token,
+        position: usize,
+        x: Value48,
+        y: Value48,
+    ) -> Result<Value48, PatchError> {
+        let x = Self::unit(x);
+        let y = Self::unit(y);
+        let fits = match token {
+            Token::Pipe => x.outputs() == y.inputs(),
+            Token::Bus => x.inputs() == y.inputs() && x.outputs() == y.outputs(),
+            Token::Branch => x.inputs() == y.inputs(),
+            _ => true,
+        };
+        if !fits {
+            return Err(PatchError::Channels(position));
+        }
+        Ok(Value48::Unit(match token {
+            Token::Pipe => x >> y,
+            Token::Bus => x & y,
+            Token::Branch => x ^ y,
+            _ => x | y,
+        }))
+    }
+
+    /// Apply an arithmetic operator to two values.
+    fn arithmetic(
+        token: Token,
+        position: usize,
+        x: Value48,
+        y: Value48,
+    ) -> Result<Value48, PatchError> {
+        match (x, y) {
+            (Value48::Number(x), Value48::Number(y)) => Ok(Value48::Number(match token {
+                Token::Plus => x + y,
+                Token::Minus => x - y,
+                Token::Times => x * y,
+                _ => x / y,
+            })),
+            (Value48::Unit(x), Value48::Number(y)) => match token {
+                Token::Plus => Ok(Value48::Unit(x + y)),
+                Token::Minus => Ok(Value48::Unit(x - y)),
+                Token::Times => Ok(Value48::Unit(x * y)),
+                _ => Err(PatchError::Operator(position)),
+            },
+            (Value48::Number(x), Value48::Unit(y)) => match token {
+                Token::Plus => Ok(Value48::Unit(x + y)),
+                Token::Minus => Ok(Value48::Unit(x - y)),
+                Token::Times => Ok(Value48::Unit(x * y)),
+                _ => Err(PatchError::Operator(position)),
+            },
+            (Value48::Unit(x), Value48::Unit(y)) => {
+                if token == Token::Divide {
+                    return Err(PatchError::Operator(position));
+                }
+                if x.outputs() != y.outputs() {
+                    return Err(PatchError::Channels(position));
+                }
+                Ok(Value48::Unit(match token {
+                    Token::Plus => x + y,
+                    Token::Minus => x - y,
+                    _ => x * y,
+                }))
+            }
+        }
+    }
+
+    fn stack(&mut self) -> Result<Value48, PatchError> {
+        self.binary(&[Token::Stack], Self::branch, Self::network)
+    }
+
+    fn branch(&mut self) -> Result<Value48, PatchError> {
+        self.binary(&[Token::Branch], Self::bus, Self::network)
+    }
+
+    fn bus(&mut self) -> Result<Value48, PatchError> {
+        self.binary(&[Token::Bus], Self::pipe, Self::network)
+    }
+
+    fn pipe(&mut self) -> Result<Value48, PatchError> {
+        self.binary(&[Token::Pipe], Self::sum, Self::network)
+    }
+
+    fn sum(&mut self) -> Result<Value48, PatchError> {
+        self.binary(&[Token::Plus, Token::Minus], Self::product, Self::arithmetic)
+    }
+
+    fn product(&mut self) -> Result<Value48, PatchError> {
+        self.binary(&[Token::Times, Token::Divide], Self::unary, Self::arithmetic)
+    }
+
+    fn unary(&mut self) -> Result<Value48, PatchError> {
+        match self.peek() {
+            (Token::Minus, _) => {
+                self.next();
+                Ok(match self.unary()? {
+                    Value48::Number(x) => Value48::Number(-x),
+                    Value48::Unit(net) => Value48::Unit(-net),
+                })
+            }
+            (Token::Thru, _) => {
+                self.next();
+                let net = Self::unit(self.unary()?);
+                Ok(Value48::Unit(!net))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn primary(&mut self) -> Result<Value48, PatchError> {
+        let (token, position) = self.peek();
+        self.next();
+        match token {
+            Token::Number(x) => Ok(Value48::Number(x as f48)),
+            Token::Open => {
+                let value = self.stack()?;
+                match self.peek() {
+                    (Token::Close, _) => {
+                        self.next();
+                        Ok(value)
+                    }
+                    (_, position) => Err(PatchError::Token(position)),
+                }
+            }
+            Token::Name(name) => {
+                let patch = self.patch;
+                let (arity, opcode) = match patch.opcodes.get(&name) {
+                    Some(entry) => entry,
+                    None => return Err(PatchError::Opcode(name, position)),
+                };
+                match self.peek() {
+                    (Token::Open, _) => self.next(),
+                    (_, position) => return Err(PatchError::Token(position)),
+                }
+                let mut arguments = Vec::new();
+                if self.peek().0 == Token::Close {
+                    self.next();
+                } else {
+                    loop {
+                        let (_, position) = self.peek();
+                        match self.stack()? {
+                            Value48::Number(x) => arguments.push(x),
+                            Value48::Unit(_) => return Err(PatchError::Number(position)),
+                        }
+                        match self.peek() {
+                            (Token::Comma, _) => self.next(),
+                            (Token::Close, _) => {
+                                self.next();
+                                break;
+                            }
+                            (_, position) => return Err(PatchError::Token(position)),
+                        }
+                    }
+                }
+                if arguments.len() != *arity {
+                    return Err(PatchError::Arguments(name, *arity, arguments.len()));
+                }
+                Ok(Value48::Unit(opcode(&arguments)))
+            }
+            _ => Err(PatchError::Token(position)),
+        }
+    }
+}
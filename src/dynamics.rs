@@ -2,13 +2,16 @@
 
 use super::audionode::*;
 use super::combinator::*;
+use super::filter::*;
 use super::follow::*;
 use super::math::*;
 use super::shared::*;
 use super::signal::*;
+use super::wave::*;
 use super::*;
 use numeric_array::typenum::*;
-use std::sync::Arc;
+use alloc::sync::Arc;
+use duplicate::duplicate_item;
 
 /// Binary operation for the monoidal reducer.
 pub trait Monoidal<T>: Clone {
@@ -17,7 +20,7 @@ pub trait Monoidal<T>: Clone {
 
 #[derive(Default, Clone)]
 pub struct Amplitude<T: Num> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
 }
 
 impl<T: Num> Amplitude<T> {
@@ -35,7 +38,7 @@ impl<T: Num> Monoidal<T> for Amplitude<T> {
 
 #[derive(Default, Clone)]
 pub struct Maximum<T: Num> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
 }
 
 impl<T: Num> Maximum<T> {
@@ -51,6 +54,24 @@ impl<T: Num> Monoidal<T> for Maximum<T> {
     }
 }
 
+#[derive(Default, Clone)]
+pub struct Sum<T: Num> {
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Num> Sum<T> {
+    pub fn new() -> Self {
+        Sum::default()
+    }
+}
+
+impl<T: Num> Monoidal<T> for Sum<T> {
+    #[inline]
+    fn binop(&self, x: T, y: T) -> T {
+        x + y
+    }
+}
+
 /// Hierarchic reducer for a monoid.
 #[derive(Clone)]
 pub struct ReduceBuffer<T, B>
@@ -250,13 +271,1124 @@ where
     }
 }
 
+/// Half the number of taps of the true peak interpolation filter.
+const TRUE_PEAK_RADIUS: usize = 6;
+
+/// Look-ahead limiter with true peak detection. Peaks between samples are estimated
+/// by 4x oversampling. Gain reduction is the lowest gain required within the look-ahead
+/// window, released with a program-dependent release time, and smoothed over the window.
+/// Release is fast after brief peaks and slows down under sustained limiting.
+/// Current gain reduction in dB is written to a shared variable.
+/// Setting: ceiling amplitude.
+/// - Allocates: look-ahead buffers.
+/// - Inputs: signal
+/// - Outputs: limited signal
+#[derive(Clone)]
+pub struct TruePeakLimiter<T: Real + Atomic, N: Size<T>> {
+    ceiling: T,
+    lookahead: f64,
+    release: f64,
+    sample_rate: f64,
+    /// Look-ahead in samples.
+    length: usize,
+    /// Input ring buffer. Length is a power of two.
+    buffer: Vec<Frame<T, N>>,
+    /// Write index of the input buffer.
+    index: usize,
+    /// Peak excess over the ceiling in the look-ahead window.
+    peaks: ReduceBuffer<T, Maximum<T>>,
+    /// Released excess in the look-ahead window, for smoothing.
+    excess: ReduceBuffer<T, Sum<T>>,
+    /// Index into the look-ahead window.
+    slot: usize,
+    /// Released excess.
+    envelope: T,
+    /// Recent proportion of time spent limiting, in 0...1.
+    activity: T,
+    /// Release coefficients after brief peaks and during sustained limiting.
+    release_fast: T,
+    release_slow: T,
+    activity_coeff: T,
+    /// Interpolation filters for the three intermediate phases.
+    coefficients: [[T; 2 * TRUE_PEAK_RADIUS]; 3],
+    reduction: Shared<T>,
+}
+
+impl<T: Real + Atomic, N: Size<T>> TruePeakLimiter<T, N> {
+    /// Create true peak limiter. Output true peaks are limited to `ceiling` amplitude.
+    /// `lookahead` and `release` are in seconds. Current gain reduction in dB
+    /// is written to `reduction`.
+    pub fn new(
+        sample_rate: f64,
+        ceiling: T,
+        lookahead: f64,
+        release: f64,
+        reduction: &Shared<T>,
+    ) -> Self {
+        // Hann windowed sinc interpolators at phases 1/4, 2/4 and 3/4.
+        let coefficients = core::array::from_fn(|k| {
+            let phase = (k + 1) as f64 / 4.0;
+            let weight = |j: usize| {
+                let x = j as f64 + 1.0 - TRUE_PEAK_RADIUS as f64 - phase;
+                let window = 0.5 + 0.5 * cos(PI * x / TRUE_PEAK_RADIUS as f64);
+                sin(PI * x) / (PI * x) * window
+            };
+            let total: f64 = (0..2 * TRUE_PEAK_RADIUS).map(weight).sum();
+            core::array::from_fn(|j| T::from_f64(weight(j) / total))
+        });
+        let mut node = Self {
+            ceiling,
+            lookahead,
+            release,
+            sample_rate: 0.0,
+            length: 1,
+            buffer: vec![],
+            index: 0,
+            peaks: ReduceBuffer::new(1, Maximum::new()),
+            excess: ReduceBuffer::new(1, Sum::new()),
+            slot: 0,
+            envelope: T::zero(),
+            activity: T::zero(),
+            release_fast: T::zero(),
+            release_slow: T::zero(),
+            activity_coeff: T::zero(),
+            coefficients,
+            reduction: reduction.clone(),
+        };
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Ceiling amplitude.
+    #[inline]
+    pub fn ceiling(&self) -> T {
+        self.ceiling
+    }
+
+    /// Set ceiling amplitude.
+    #[inline]
+    pub fn set_ceiling(&mut self, ceiling: T) {
+        self.ceiling = ceiling;
+    }
+
+    /// Total latency in samples.
+    #[inline]
+    pub fn latency_samples(&self) -> usize {
+        self.length - 1 + TRUE_PEAK_RADIUS
+    }
+
+    /// Input sample of `channel` at `i` samples back from the newest.
+    #[inline]
+    fn at(&self, channel: usize, i: usize) -> T {
+        self.buffer[self.index.wrapping_sub(i) & (self.buffer.len() - 1)][channel]
+    }
+
+    /// Estimate the true peak between the samples `TRUE_PEAK_RADIUS`
+    /// and `TRUE_PEAK_RADIUS - 1` samples back.
+    #[inline]
+    fn true_peak(&self) -> T {
+        let mut peak = T::zero();
+        for channel in 0..N::USIZE {
+            peak = max(peak, abs(self.at(channel, TRUE_PEAK_RADIUS)));
+            for coefficients in self.coefficients.iter() {
+                let mut value = T::zero();
+                for (j, c) in coefficients.iter().enumerate() {
+                    value += *c * self.at(channel, 2 * TRUE_PEAK_RADIUS - 1 - j);
+                }
+                peak = max(peak, abs(value));
+            }
+        }
+        peak
+    }
+}
+
+impl<T: Real + Atomic, N: Size<T>> AudioNode for TruePeakLimiter<T, N> {
+    const ID: u64 = 120;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_ceiling(setting);
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(Frame::default());
+        self.index = 0;
+        self.peaks.clear();
+        self.excess.clear();
+        self.slot = 0;
+        self.envelope = T::zero();
+        self.activity = T::zero();
+        self.reduction.set_value(T::zero());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.length = max(1, round(self.lookahead * sample_rate) as usize);
+            let buffer_length = (self.length + 2 * TRUE_PEAK_RADIUS).next_power_of_two();
+            self.buffer.resize(buffer_length, Frame::default());
+            self.peaks = ReduceBuffer::new(self.length, Maximum::new());
+            self.excess = ReduceBuffer::new(self.length, Sum::new());
+            let coeff = |time: f64| T::from_f64(1.0 - exp(-1.0 / (max(1.0e-6, time) * sample_rate)));
+            self.release_fast = coeff(self.release * 0.25);
+            self.release_slow = coeff(self.release);
+            self.activity_coeff = coeff(0.5);
+        }
+        self.reset();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.index = (self.index + 1) & (self.buffer.len() - 1);
+        self.buffer[self.index] = input.clone();
+        let excess = max(T::zero(), self.true_peak() / self.ceiling - T::one());
+        self.peaks.set(self.slot, excess);
+        let required = self.peaks.total();
+        let limiting = if required > T::zero() { T::one() } else { T::zero() };
+        self.activity += (limiting - self.activity) * self.activity_coeff;
+        if required >= self.envelope {
+            self.envelope = required;
+        } else {
+            let coeff = lerp(self.release_fast, self.release_slow, self.activity);
+            self.envelope += (required - self.envelope) * coeff;
+        }
+        self.excess.set(self.slot, self.envelope);
+        self.slot += 1;
+        if self.slot == self.length {
+            self.slot = 0;
+        }
+        // The moving average of the released excess is never less than
+        // the excess required by the output sample.
+        let divisor = T::one() + self.excess.total() / T::from_f64(self.length as f64);
+        self.reduction.set_value(amp_db(divisor));
+        let output = self.buffer[self.index.wrapping_sub(self.latency_samples()) & (self.buffer.len() - 1)].clone();
+        output * Frame::splat(T::one() / divisor)
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..N::USIZE {
+            output[i] = input[i].delay(self.latency_samples() as f64);
+        }
+        output
+    }
+}
+
+/// Sidechain ducker. Attenuates the input signal while the level of the key signal
+/// is above a threshold. The level is the rectified key smoothed with the attack and release times.
+/// Gain reduction starts at the threshold and is full 6 dB above it.
+/// Setting: (threshold, amount).
+/// - Input 0: signal
+/// - Input 1: key
+/// - Output 0: ducked signal
+#[derive(Clone)]
+pub struct Ducker<T: Real> {
+    /// Key level threshold amplitude.
+    threshold: T,
+    /// Gain reduction in 0...1 at full ducking.
+    amount: T,
+    follower: AFollow<T, T, (T, T)>,
+}
+
+impl<T: Real> Ducker<T> {
+    /// Create ducker. The key level `threshold` is an amplitude and `amount`
+    /// is the gain reduction in 0...1 at full ducking. `attack` and `release`
+    /// are halfway response times of the key level in seconds.
+    pub fn new(sample_rate: f64, threshold: T, amount: T, attack: T, release: T) -> Self {
+        Self {
+            threshold,
+            amount,
+            follower: AFollow::new(sample_rate, (attack, release)),
+        }
+    }
+
+    /// Key level threshold amplitude.
+    #[inline]
+    pub fn threshold(&self) -> T {
+        self.threshold
+    }
+
+    /// Set key level threshold amplitude.
+    #[inline]
+    pub fn set_threshold(&mut self, threshold: T) {
+        self.threshold = threshold;
+    }
+
+    /// Gain reduction in 0...1 at full ducking.
+    #[inline]
+    pub fn amount(&self) -> T {
+        self.amount
+    }
+
+    /// Set gain reduction in 0...1 at full ducking.
+    #[inline]
+    pub fn set_amount(&mut self, amount: T) {
+        self.amount = amount;
+    }
+
+    /// Current gain applied to the signal.
+    #[inline]
+    pub fn gain(&self) -> T {
+        let level = self.follower.value();
+        if level <= self.threshold {
+            return T::one();
+        }
+        let depth = clamp01(log2(level / self.threshold));
+        T::one() - self.amount * smooth3(depth)
+    }
+}
+
+impl<T: Real> AudioNode for Ducker<T> {
+    const ID: u64 = 119;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (threshold, amount) = setting;
+        self.set_threshold(threshold);
+        self.set_amount(amount);
+    }
+
+    fn reset(&mut self) {
+        self.follower.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.follower.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.follower.filter_mono(abs(input[1]));
+        [input[0] * self.gain()].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![
+            ParameterInfo::new("threshold", ParameterUnit::Unitless, 0.0, 1.0, 0.1),
+            ParameterInfo::new("amount", ParameterUnit::Unitless, 0.0, 1.0, 0.5),
+        ]
+    }
+
+    fn parameter_count(&self) -> usize {
+        2
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.threshold.to_f64(),
+            1 => self.amount.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_threshold(T::from_f64(value)),
+            1 => self.set_amount(T::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
+
+/// Transient shaper. Splits the signal into attack and sustain portions
+/// by comparing a fast and a slow envelope follower, and applies independent gains to them.
+/// The signal is in its attack portion when the fast envelope exceeds the slow one
+/// and in its sustain portion when the fast envelope decays below the slow one.
+/// Full gain is reached when the envelopes differ by an octave (6 dB).
+/// Setting: (attack gain, sustain gain).
+/// - Input 0: signal
+/// - Output 0: shaped signal
+#[derive(Clone)]
+pub struct TransientShaper<T: Real> {
+    /// Gain amplitude applied to attacks.
+    attack_gain: T,
+    /// Gain amplitude applied to sustain.
+    sustain_gain: T,
+    fast: AFollow<T, T, (T, T)>,
+    slow: AFollow<T, T, (T, T)>,
+}
+
+impl<T: Real> TransientShaper<T> {
+    /// Create transient shaper. `attack_gain` and `sustain_gain` are amplitudes:
+    /// for example, 2.0 boosts by 6 dB and 0.5 attenuates by 6 dB.
+    pub fn new(sample_rate: f64, attack_gain: T, sustain_gain: T) -> Self {
+        Self {
+            attack_gain,
+            sustain_gain,
+            fast: AFollow::new(sample_rate, (T::from_f64(0.0005), T::from_f64(0.03))),
+            slow: AFollow::new(sample_rate, (T::from_f64(0.02), T::from_f64(0.2))),
+        }
+    }
+
+    /// Gain amplitude applied to attacks.
+    #[inline]
+    pub fn attack_gain(&self) -> T {
+        self.attack_gain
+    }
+
+    /// Set gain amplitude applied to attacks.
+    #[inline]
+    pub fn set_attack_gain(&mut self, attack_gain: T) {
+        self.attack_gain = attack_gain;
+    }
+
+    /// Gain amplitude applied to sustain.
+    #[inline]
+    pub fn sustain_gain(&self) -> T {
+        self.sustain_gain
+    }
+
+    /// Set gain amplitude applied to sustain.
+    #[inline]
+    pub fn set_sustain_gain(&mut self, sustain_gain: T) {
+        self.sustain_gain = sustain_gain;
+    }
+
+    /// Current gain applied to the signal.
+    #[inline]
+    pub fn gain(&self) -> T {
+        let floor = T::from_f64(1.0e-6);
+        let fast = self.fast.value() + floor;
+        let slow = self.slow.value() + floor;
+        let difference = log2(fast / slow);
+        if difference >= T::zero() {
+            pow(self.attack_gain, clamp01(difference))
+        } else {
+            pow(self.sustain_gain, clamp01(-difference))
+        }
+    }
+}
+
+impl<T: Real> AudioNode for TransientShaper<T> {
+    const ID: u64 = 137;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (attack_gain, sustain_gain) = setting;
+        self.set_attack_gain(attack_gain);
+        self.set_sustain_gain(sustain_gain);
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.fast.set_sample_rate(sample_rate);
+        self.slow.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let level = abs(input[0]);
+        self.fast.filter_mono(level);
+        self.slow.filter_mono(level);
+        [input[0] * self.gain()].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![
+            ParameterInfo::new("attack gain", ParameterUnit::Unitless, 0.0, 4.0, 1.0),
+            ParameterInfo::new("sustain gain", ParameterUnit::Unitless, 0.0, 4.0, 1.0),
+        ]
+    }
+
+    fn parameter_count(&self) -> usize {
+        2
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.attack_gain.to_f64(),
+            1 => self.sustain_gain.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_attack_gain(T::from_f64(value)),
+            1 => self.set_sustain_gain(T::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
+
+/// Onset strength function. Measures the rise of a fast envelope follower
+/// over a slow one as the binary logarithm of their ratio.
+#[derive(Clone)]
+struct OnsetStrength<T: Real> {
+    fast: AFollow<T, T, (T, T)>,
+    slow: AFollow<T, T, (T, T)>,
+}
+
+impl<T: Real> OnsetStrength<T> {
+    fn new(sample_rate: f64) -> Self {
+        Self {
+            fast: AFollow::new(sample_rate, (T::from_f64(0.0005), T::from_f64(0.03))),
+            slow: AFollow::new(sample_rate, (T::from_f64(0.02), T::from_f64(0.2))),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.fast.set_sample_rate(sample_rate);
+        self.slow.set_sample_rate(sample_rate);
+    }
+
+    /// Process one sample. Returns the onset strength, which is positive when the level rises.
+    #[inline]
+    fn filter(&mut self, x: T) -> T {
+        let level = abs(x);
+        self.fast.filter_mono(level);
+        self.slow.filter_mono(level);
+        // Ignore activity below -60 dB.
+        let floor = T::from_f64(1.0e-3);
+        log2((self.fast.value() + floor) / (self.slow.value() + floor))
+    }
+}
+
+/// Onset detector. Compares a fast and a slow envelope follower and outputs an impulse
+/// when the fast envelope rises above the slow one by more than the detection threshold.
+/// Sensitivity in 0...1 sets the threshold: 0 detects only strong attacks,
+/// 1 detects subtle ones. After an onset, the detector re-arms once the envelopes
+/// settle and at least 50 ms have passed.
+/// Setting: sensitivity.
+/// - Input 0: signal
+/// - Output 0: impulse (1.0 on onset samples, 0.0 otherwise)
+#[derive(Clone)]
+pub struct Onset<T: Real> {
+    /// Sensitivity in 0...1.
+    sensitivity: T,
+    strength: OnsetStrength<T>,
+    /// Whether the detector is ready to report the next onset.
+    armed: bool,
+    /// Samples remaining until the detector may re-arm.
+    hold: usize,
+    sample_rate: f64,
+}
+
+impl<T: Real> Onset<T> {
+    /// Create onset detector with `sensitivity` in 0...1.
+    pub fn new(sample_rate: f64, sensitivity: T) -> Self {
+        Self {
+            sensitivity: clamp01(sensitivity),
+            strength: OnsetStrength::new(sample_rate),
+            armed: true,
+            hold: 0,
+            sample_rate,
+        }
+    }
+
+    /// Sensitivity in 0...1.
+    #[inline]
+    pub fn sensitivity(&self) -> T {
+        self.sensitivity
+    }
+
+    /// Set sensitivity in 0...1.
+    #[inline]
+    pub fn set_sensitivity(&mut self, sensitivity: T) {
+        self.sensitivity = clamp01(sensitivity);
+    }
+
+    /// Detection threshold as the binary logarithm of the fast to slow envelope ratio.
+    #[inline]
+    fn threshold(&self) -> T {
+        lerp(T::from_f64(3.0), T::from_f64(0.25), self.sensitivity)
+    }
+}
+
+impl<T: Real> AudioNode for Onset<T> {
+    const ID: u64 = 150;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_sensitivity(setting);
+    }
+
+    fn reset(&mut self) {
+        self.strength.reset();
+        self.armed = true;
+        self.hold = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.strength.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let difference = self.strength.filter(input[0]);
+        let threshold = self.threshold();
+        if self.hold > 0 {
+            self.hold -= 1;
+        }
+        if self.armed {
+            if difference >= threshold {
+                self.armed = false;
+                self.hold = (0.05 * self.sample_rate) as usize;
+                return [T::one()].into();
+            }
+        } else if self.hold == 0 && difference < threshold * T::from_f64(0.5) {
+            self.armed = true;
+        }
+        [T::zero()].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "sensitivity",
+            ParameterUnit::Unitless,
+            0.0,
+            1.0,
+            0.5,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.sensitivity.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_sensitivity(T::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
+
+/// Tempo estimator. Accumulates an onset strength curve at a frame rate of about 200 Hz
+/// and estimates the tempo from the autocorrelation of the curve.
+/// Used by `Wave64::detect_bpm` and the `tempo_tracker` opcode.
+#[derive(Clone)]
+pub struct TempoEstimator {
+    strength: OnsetStrength<f64>,
+    /// Minimum tempo in BPM.
+    min_bpm: f64,
+    /// Maximum tempo in BPM.
+    max_bpm: f64,
+    /// Length of the analysis window in seconds.
+    window: f64,
+    /// Number of samples per onset curve frame.
+    hop: usize,
+    /// Samples accumulated into the current frame.
+    counter: usize,
+    /// Peak onset strength in the current frame.
+    frame: f64,
+    /// Ring buffer of onset curve frames.
+    curve: Vec<f64>,
+    /// Next write index into the ring buffer.
+    index: usize,
+    /// Number of frames written, up to the length of the ring buffer.
+    frames: usize,
+    sample_rate: f64,
+}
+
+impl TempoEstimator {
+    /// Create tempo estimator for tempos between `min_bpm` and `max_bpm`
+    /// (`min_bpm` < `max_bpm`) that analyzes the latest `window` seconds of audio.
+    pub fn new(sample_rate: f64, min_bpm: f64, max_bpm: f64, window: f64) -> Self {
+        assert!(min_bpm > 0.0 && min_bpm < max_bpm);
+        let mut estimator = Self {
+            strength: OnsetStrength::new(sample_rate),
+            min_bpm,
+            max_bpm,
+            window,
+            hop: 1,
+            counter: 0,
+            frame: 0.0,
+            curve: Vec::new(),
+            index: 0,
+            frames: 0,
+            sample_rate,
+        };
+        estimator.set_sample_rate(sample_rate);
+        estimator
+    }
+
+    /// Minimum tempo in BPM.
+    #[inline]
+    pub fn min_bpm(&self) -> f64 {
+        self.min_bpm
+    }
+
+    /// Maximum tempo in BPM.
+    #[inline]
+    pub fn max_bpm(&self) -> f64 {
+        self.max_bpm
+    }
+
+    /// Frame rate of the onset curve in Hz.
+    #[inline]
+    pub fn frame_rate(&self) -> f64 {
+        self.sample_rate / self.hop as f64
+    }
+
+    /// Set sample rate. Clears the onset curve.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.strength.set_sample_rate(sample_rate);
+        self.hop = max(1.0, round(sample_rate / 200.0)) as usize;
+        let length = max(1.0, ceil(self.window * self.frame_rate())) as usize;
+        self.curve = vec![0.0; length];
+        self.reset();
+    }
+
+    /// Clear the onset curve.
+    pub fn reset(&mut self) {
+        self.strength.reset();
+        self.counter = 0;
+        self.frame = 0.0;
+        self.curve.fill(0.0);
+        self.index = 0;
+        self.frames = 0;
+    }
+
+    /// Process one sample of audio.
+    #[inline]
+    pub fn push(&mut self, x: f64) {
+        self.frame = max(self.frame, self.strength.filter(x));
+        self.counter += 1;
+        if self.counter == self.hop {
+            self.curve[self.index] = self.frame;
+            self.index = (self.index + 1) % self.curve.len();
+            self.frames = min(self.frames + 1, self.curve.len());
+            self.counter = 0;
+            self.frame = 0.0;
+        }
+    }
+
+    /// Estimate the tempo in BPM. Returns `None` if not enough audio has been analyzed
+    /// to cover two beats at the minimum tempo, or if there is no periodicity in the onsets.
+    pub fn bpm(&self) -> Option<f64> {
+        let rate = self.frame_rate();
+        let min_lag = floor(60.0 * rate / self.max_bpm) as usize;
+        let max_lag = ceil(60.0 * rate / self.min_bpm) as usize;
+        if min_lag < 2 || self.frames < 2 * max_lag + 2 {
+            return None;
+        }
+        // Unwrap the ring buffer in chronological order and remove the mean.
+        let length = self.curve.len();
+        let start = if self.frames < length { 0 } else { self.index };
+        let mut curve: Vec<f64> = (0..self.frames)
+            .map(|i| self.curve[(start + i) % length])
+            .collect();
+        let mean = curve.iter().sum::<f64>() / self.frames as f64;
+        curve.iter_mut().for_each(|x| *x -= mean);
+        // The autocorrelation is left unnormalized, which favors the shorter of two
+        // candidate lags of equal periodicity and so avoids halving the tempo.
+        let correlation: Vec<f64> = (min_lag - 1..=max_lag + 1)
+            .map(|lag| {
+                (0..self.frames - lag)
+                    .map(|i| curve[i] * curve[i + lag])
+                    .sum()
+            })
+            .collect();
+        let mut best = None;
+        let mut best_value = 0.0;
+        for j in 1..correlation.len() - 1 {
+            let value = correlation[j];
+            if value > best_value && value >= correlation[j - 1] && value >= correlation[j + 1] {
+                best = Some(j);
+                best_value = value;
+            }
+        }
+        let j = best?;
+        // Refine the lag with parabolic interpolation.
+        let (a, b, c) = (correlation[j - 1], correlation[j], correlation[j + 1]);
+        let denominator = a - 2.0 * b + c;
+        let offset = if denominator < 0.0 {
+            0.5 * (a - c) / denominator
+        } else {
+            0.0
+        };
+        let lag = (min_lag - 1 + j) as f64 + offset;
+        Some(clamp(self.min_bpm, self.max_bpm, 60.0 * rate / lag))
+    }
+}
+
+/// Streaming tempo tracker. Estimates the tempo of the input from the autocorrelation
+/// of its onset strength curve over the latest `window` seconds.
+/// The estimate is updated twice per second.
+/// - Input 0: signal
+/// - Output 0: tempo estimate in BPM, or zero until an estimate is available
+#[derive(Clone)]
+pub struct TempoTracker<T: Real> {
+    estimator: TempoEstimator,
+    /// Samples remaining until the next estimate.
+    countdown: usize,
+    /// Latest estimate in BPM.
+    bpm: T,
+}
+
+impl<T: Real> TempoTracker<T> {
+    /// Create tempo tracker for tempos between `min_bpm` and `max_bpm`
+    /// that analyzes the latest `window` seconds of audio.
+    pub fn new(sample_rate: f64, min_bpm: f64, max_bpm: f64, window: f64) -> Self {
+        let estimator = TempoEstimator::new(sample_rate, min_bpm, max_bpm, window);
+        Self {
+            estimator,
+            countdown: Self::interval(sample_rate),
+            bpm: T::zero(),
+        }
+    }
+
+    /// Latest tempo estimate in BPM, or zero if no estimate is available.
+    #[inline]
+    pub fn bpm(&self) -> T {
+        self.bpm
+    }
+
+    /// Number of samples between estimates.
+    #[inline]
+    fn interval(sample_rate: f64) -> usize {
+        max(1.0, round(sample_rate * 0.5)) as usize
+    }
+}
+
+impl<T: Real> AudioNode for TempoTracker<T> {
+    const ID: u64 = 151;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.estimator.reset();
+        self.countdown = Self::interval(self.estimator.sample_rate);
+        self.bpm = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.estimator.set_sample_rate(sample_rate);
+        self.countdown = Self::interval(sample_rate);
+        self.bpm = T::zero();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.estimator.push(input[0].to_f64());
+        self.countdown -= 1;
+        if self.countdown == 0 {
+            self.countdown = Self::interval(self.estimator.sample_rate);
+            if let Some(bpm) = self.estimator.bpm() {
+                self.bpm = T::from_f64(bpm);
+            }
+        }
+        [self.bpm].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+#[allow(clippy::unnecessary_cast)]
+#[duplicate_item(
+    f48       Wave48;
+    [ f64 ]   [ Wave64 ];
+    [ f32 ]   [ Wave32 ];
+)]
+impl Wave48 {
+    /// Detect the tempo of the wave in BPM in the range 60...180 BPM
+    /// from the autocorrelation of its onset strength curve.
+    /// Channels are mixed down before analysis.
+    /// Returns `None` if the wave is shorter than about two seconds
+    /// or if no periodicity is found.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut pulse = lfo(|t: f64| if (t * 2.0) % 1.0 < 0.05 { 1.0 } else { 0.0 }) * sine_hz(440.0);
+    /// let wave = Wave64::render(44100.0, 8.0, &mut pulse);
+    /// assert!((wave.detect_bpm().unwrap() - 120.0).abs() < 1.0);
+    /// ```
+    pub fn detect_bpm(&self) -> Option<f64> {
+        let mut estimator = TempoEstimator::new(self.sample_rate(), 60.0, 180.0, self.duration());
+        let z = 1.0 / max(1, self.channels()) as f64;
+        for i in 0..self.len() {
+            let mut x = 0.0;
+            for channel in 0..self.channels() {
+                x += self.at(channel, i) as f64;
+            }
+            estimator.push(x * z);
+        }
+        estimator.bpm()
+    }
+
+    /// Repair clicks and crackles in all channels, for example, transfer artifacts of
+    /// vinyl records, with `sensitivity` in 0...1 (see `ClickRepair`).
+    /// Returns the number of clicks repaired.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(440.0) * 0.5));
+    /// wave.set(0, 10000, 1.0);
+    /// assert_eq!(wave.repair_clicks(0.5), 1);
+    /// ```
+    pub fn repair_clicks(&mut self, sensitivity: f64) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut repairs = 0;
+        for channel in 0..self.channels() {
+            let mut node = ClickRepair::<f64>::new(self.sample_rate(), sensitivity);
+            for i in 0..self.len() + CLICK_LATENCY {
+                // Flush the repairer by extrapolating the end linearly,
+                // which the predictor does not mistake for a click.
+                let x = if i < self.len() {
+                    self.at(channel, i) as f64
+                } else {
+                    let last = self.at(channel, self.len() - 1) as f64;
+                    let slope = if self.len() > 1 {
+                        last - self.at(channel, self.len() - 2) as f64
+                    } else {
+                        0.0
+                    };
+                    last + slope * (i + 1 - self.len()) as f64
+                };
+                let y = node.filter_mono(x);
+                if i >= CLICK_LATENCY {
+                    self.set(channel, i - CLICK_LATENCY, y as f48);
+                }
+            }
+            repairs += node.repairs();
+        }
+        repairs
+    }
+}
+
+/// De-esser. Splits the signal into a low band and a complementary high band
+/// at the crossover frequency and compresses only the high band, where sibilance lives.
+/// The detector follows the level of the high band; above the threshold,
+/// the high band is compressed by the ratio. The bands sum back to the input
+/// when no compression takes place.
+/// Setting: (frequency, threshold, ratio).
+/// - Input 0: signal
+/// - Output 0: de-essed signal
+#[derive(Clone)]
+pub struct DeEsser<T: Real> {
+    /// Crossover frequency in Hz.
+    frequency: T,
+    /// Detector threshold amplitude.
+    threshold: T,
+    /// Compression ratio (`ratio` >= 1).
+    ratio: T,
+    lowpass: Biquad<T, T>,
+    follower: AFollow<T, T, (T, T)>,
+    sample_rate: f64,
+}
+
+impl<T: Real> DeEsser<T> {
+    /// Create de-esser. The high band starts at `frequency` Hz.
+    /// `threshold` is an amplitude and `ratio` (`ratio` >= 1) is the compression ratio.
+    pub fn new(sample_rate: f64, frequency: T, threshold: T, ratio: T) -> Self {
+        let mut node = Self {
+            frequency,
+            threshold,
+            ratio,
+            lowpass: Biquad::new(),
+            follower: AFollow::new(sample_rate, (T::from_f64(0.001), T::from_f64(0.05))),
+            sample_rate,
+        };
+        node.set_frequency(frequency);
+        node
+    }
+
+    /// Crossover frequency in Hz.
+    #[inline]
+    pub fn frequency(&self) -> T {
+        self.frequency
+    }
+
+    /// Set crossover frequency in Hz.
+    #[inline]
+    pub fn set_frequency(&mut self, frequency: T) {
+        self.frequency = frequency;
+        self.lowpass.set_coefs(BiquadCoefs::butter_lowpass(
+            T::from_f64(self.sample_rate),
+            frequency,
+        ));
+    }
+
+    /// Detector threshold amplitude.
+    #[inline]
+    pub fn threshold(&self) -> T {
+        self.threshold
+    }
+
+    /// Set detector threshold amplitude.
+    #[inline]
+    pub fn set_threshold(&mut self, threshold: T) {
+        self.threshold = threshold;
+    }
+
+    /// Compression ratio.
+    #[inline]
+    pub fn ratio(&self) -> T {
+        self.ratio
+    }
+
+    /// Set compression ratio (`ratio` >= 1).
+    #[inline]
+    pub fn set_ratio(&mut self, ratio: T) {
+        self.ratio = ratio;
+    }
+
+    /// Current gain applied to the high band.
+    #[inline]
+    pub fn gain(&self) -> T {
+        let level = self.follower.value();
+        if level <= self.threshold {
+            return T::one();
+        }
+        pow(level / self.threshold, T::one() / self.ratio - T::one())
+    }
+}
+
+impl<T: Real> AudioNode for DeEsser<T> {
+    const ID: u64 = 138;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (T, T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (frequency, threshold, ratio) = setting;
+        self.set_frequency(frequency);
+        self.set_threshold(threshold);
+        self.set_ratio(ratio);
+    }
+
+    fn reset(&mut self) {
+        self.lowpass.reset();
+        self.follower.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.lowpass.set_sample_rate(sample_rate);
+        self.follower.set_sample_rate(sample_rate);
+        self.set_frequency(self.frequency);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let low = self.lowpass.tick(input)[0];
+        let high = input[0] - low;
+        self.follower.filter_mono(abs(high));
+        [low + high * self.gain()].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![
+            ParameterInfo::new("frequency", ParameterUnit::Hz, 2000.0, 12000.0, 5000.0),
+            ParameterInfo::new("threshold", ParameterUnit::Unitless, 0.0, 1.0, 0.1),
+            ParameterInfo::new("ratio", ParameterUnit::Unitless, 1.0, 20.0, 4.0),
+        ]
+    }
+
+    fn parameter_count(&self) -> usize {
+        3
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.frequency.to_f64(),
+            1 => self.threshold.to_f64(),
+            2 => self.ratio.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_frequency(T::from_f64(value)),
+            1 => self.set_threshold(T::from_f64(value)),
+            2 => self.set_ratio(T::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
+
 /// Transient filter. Multiply the signal with a fade-in curve.
 /// After fade-in, pass signal through.
 /// - Input 0: input signal
 /// - Output 0: filtered signal
 #[derive(Default, Clone)]
 pub struct Declick<T: Float, F: Real> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
     t: F,
     duration: F,
     sample_duration: F,
@@ -337,6 +1469,217 @@ impl<T: Float, F: Real> AudioNode for Declick<T, F> {
     }
 }
 
+/// Latency of the click repairer in samples.
+const CLICK_LATENCY: usize = 64;
+
+/// Longest repairable click in samples. Longer disturbances are treated as genuine transients.
+const CLICK_MAX_LENGTH: u64 = 32;
+
+/// Click and crackle repairer. Detects clicks as outliers in the second order prediction error
+/// of the signal and replaces them with cubic Hermite interpolation between the intact
+/// samples on either side. Sensitivity in 0...1 sets the detection threshold relative to
+/// the recent level of the prediction error: 0 repairs only strong clicks,
+/// 1 repairs subtle ones. The latency is 64 samples.
+/// Setting: sensitivity.
+/// - Input 0: signal
+/// - Output 0: repaired signal
+#[derive(Clone)]
+pub struct ClickRepair<T: Real> {
+    /// Sensitivity in 0...1.
+    sensitivity: T,
+    /// Ring buffer of recent samples.
+    buffer: Vec<T>,
+    /// Number of samples processed.
+    samples: u64,
+    /// Mean square prediction error.
+    error: T,
+    /// Attack coefficient of the prediction error follower.
+    attack: T,
+    /// Release coefficient of the prediction error follower.
+    release: T,
+    /// First and last detected sample of the click in progress.
+    click: Option<(u64, u64)>,
+    /// Number of clicks repaired.
+    repairs: u64,
+    sample_rate: f64,
+}
+
+impl<T: Real> ClickRepair<T> {
+    /// Create click repairer with `sensitivity` in 0...1.
+    pub fn new(sample_rate: f64, sensitivity: T) -> Self {
+        let mut node = Self {
+            sensitivity: clamp01(sensitivity),
+            buffer: vec![T::zero(); CLICK_LATENCY * 2],
+            samples: 0,
+            error: T::zero(),
+            attack: T::zero(),
+            release: T::zero(),
+            click: None,
+            repairs: 0,
+            sample_rate,
+        };
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Sensitivity in 0...1.
+    #[inline]
+    pub fn sensitivity(&self) -> T {
+        self.sensitivity
+    }
+
+    /// Set sensitivity in 0...1.
+    #[inline]
+    pub fn set_sensitivity(&mut self, sensitivity: T) {
+        self.sensitivity = clamp01(sensitivity);
+    }
+
+    /// Number of clicks repaired since the last reset.
+    #[inline]
+    pub fn repairs(&self) -> u64 {
+        self.repairs
+    }
+
+    /// Sample `i` from the ring buffer.
+    #[inline]
+    fn at(&self, i: u64) -> T {
+        self.buffer[i as usize & (CLICK_LATENCY * 2 - 1)]
+    }
+
+    /// Replace samples `start..=end` with a cubic Hermite curve that matches
+    /// the values and slopes of the intact samples on either side.
+    fn repair(&mut self, start: u64, end: u64) {
+        let length = T::from_f64((end - start + 2) as f64);
+        let y0 = self.at(start - 1);
+        let y1 = self.at(end + 1);
+        let m0 = (y0 - self.at(start - 2)) * length;
+        let m1 = (self.at(end + 2) - y1) * length;
+        for i in start..=end {
+            let t = T::from_f64((i - start + 1) as f64) / length;
+            let t2 = t * t;
+            let t3 = t2 * t;
+            let two = T::new(2);
+            let three = T::new(3);
+            let value = (two * t3 - three * t2 + T::one()) * y0
+                + (t3 - two * t2 + t) * m0
+                + (three * t2 - two * t3) * y1
+                + (t3 - t2) * m1;
+            self.buffer[i as usize & (CLICK_LATENCY * 2 - 1)] = value;
+        }
+        self.repairs += 1;
+    }
+}
+
+impl<T: Real> AudioNode for ClickRepair<T> {
+    const ID: u64 = 152;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_sensitivity(setting);
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(T::zero());
+        self.samples = 0;
+        self.error = T::zero();
+        self.click = None;
+        self.repairs = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.attack = T::from_f64(1.0 - exp(-1.0 / (0.001 * sample_rate)));
+        self.release = T::from_f64(1.0 - exp(-1.0 / (0.02 * sample_rate)));
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let i = self.samples;
+        self.buffer[i as usize & (CLICK_LATENCY * 2 - 1)] = input[0];
+        if i >= 2 {
+            let error = input[0] - (T::new(2) * self.at(i - 1) - self.at(i - 2));
+            let power = error * error;
+            if i == 2 {
+                // Start from the first prediction error so the onset is not taken for a click.
+                self.error = power;
+            }
+            let ratio = lerp(T::from_f64(16.0), T::from_f64(4.0), self.sensitivity);
+            // Prediction errors below -60 dB are never treated as clicks.
+            let threshold = ratio * sqrt(self.error) + T::from_f64(1.0e-3);
+            if abs(error) > threshold {
+                self.click = match self.click {
+                    Some((start, _)) => Some((start, i)),
+                    None => Some((i, i)),
+                };
+            }
+            // Detected samples enter the error level only up to the threshold,
+            // so clicks cannot mask themselves but sustained transients are let through.
+            let power = min(power, threshold * threshold);
+            let coefficient = if power > self.error {
+                self.attack
+            } else {
+                self.release
+            };
+            self.error += (power - self.error) * coefficient;
+        }
+        if let Some((start, end)) = self.click {
+            if i >= end + 2 {
+                if end - start < CLICK_MAX_LENGTH {
+                    self.repair(start, end);
+                }
+                self.click = None;
+            }
+        }
+        self.samples += 1;
+        if i >= CLICK_LATENCY as u64 {
+            [self.at(i - CLICK_LATENCY as u64)].into()
+        } else {
+            [T::zero()].into()
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        // We pretend that the click repairer does not alter the frequency response.
+        output[0] = input[0].delay(CLICK_LATENCY as f64);
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "sensitivity",
+            ParameterUnit::Unitless,
+            0.0,
+            1.0,
+            0.5,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.sensitivity.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_sensitivity(T::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
+
 /// Metering modes.
 #[derive(Copy, Clone)]
 pub enum Meter {
@@ -3,9 +3,11 @@
 use super::audionode::*;
 use super::combinator::*;
 use super::math::*;
+use super::signal::*;
+use super::transport::*;
 use super::*;
 use numeric_array::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Sample a time varying function.
 /// The return type can be scalar or tuple.
@@ -144,6 +146,19 @@ where
         output
     }
 
+    fn skip(&mut self, samples: usize, _input: &Frame<Self::Sample, Self::Inputs>) {
+        self.t += self.sample_duration * F::from_f64(samples as f64);
+        if self.t >= self.t_1 {
+            // Step over whole segments to keep the jitter sequence intact.
+            while self.t >= self.t_1 {
+                self.next_segment();
+            }
+        } else {
+            let samples = T::from_f64(samples as f64);
+            self.value = Frame::generate(|i| self.value[i] + self.value_d[i] * samples);
+        }
+    }
+
     fn process(
         &mut self,
         size: usize,
@@ -365,3 +380,409 @@ where
         self.t_hash = hash;
     }
 }
+
+/// Shape segment position `u` in 0...1 with `curve`.
+/// Zero curve is linear, positive curves start slow and negative curves start fast.
+#[inline]
+fn curve_shape(u: f64, curve: f64) -> f64 {
+    if abs(curve) < 1.0e-6 {
+        u
+    } else {
+        (env_exp(curve * u) - 1.0) / (env_exp(curve) - 1.0)
+    }
+}
+
+/// Setting of a breakpoint envelope.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BreakpointSetting {
+    /// Restart the envelope as if the gate had risen.
+    #[default]
+    Retrigger,
+    /// Set breakpoint with an index to (time, value, curve).
+    /// Times of breakpoints must stay in non-decreasing order.
+    Point(usize, f64, f64, f64),
+    /// Loop between breakpoints with start and end indices while the gate is open.
+    Loop(usize, usize),
+    /// Remove loop.
+    Unloop,
+}
+
+/// Multi-segment envelope with breakpoints (time, value, curve).
+/// Times are in seconds from the trigger. The curve of a breakpoint shapes the segment
+/// that ends at it: zero is linear, positive curves start slow and negative curves start fast.
+/// A rising gate triggers the envelope, gliding from the current value.
+/// While the gate stays open, the envelope loops between the loop breakpoints,
+/// jumping from the end back to the start, or holds at the loop breakpoint
+/// if the loop has zero length. When the gate closes,
+/// the envelope continues from the current value after the loop end.
+/// Setting: `BreakpointSetting`.
+/// - Input 0: gate
+/// - Output 0: envelope
+#[derive(Clone)]
+pub struct Breakpoints<T: Float> {
+    /// Breakpoints as (time, value, curve).
+    points: Vec<(f64, f64, f64)>,
+    /// Optional loop with (start, end) breakpoint indices.
+    loop_points: Option<(usize, usize)>,
+    /// Current time since trigger in seconds.
+    time: f64,
+    /// Index of the breakpoint at the end of the current segment.
+    index: usize,
+    /// Value at start of current segment.
+    from: f64,
+    /// Time at start of current segment.
+    from_time: f64,
+    /// Whether the gate is open.
+    gate: bool,
+    /// Whether we are holding at a sustain breakpoint.
+    holding: bool,
+    sample_duration: f64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Float> Breakpoints<T> {
+    /// Create envelope from breakpoints (time, value, curve) in non-decreasing time order.
+    pub fn new(points: &[(f64, f64, f64)]) -> Self {
+        assert!(!points.is_empty());
+        assert!(points.windows(2).all(|w| w[0].0 <= w[1].0));
+        let mut node = Self {
+            points: points.to_vec(),
+            loop_points: None,
+            time: 0.0,
+            index: 0,
+            from: 0.0,
+            from_time: 0.0,
+            gate: false,
+            holding: false,
+            sample_duration: 1.0 / DEFAULT_SR,
+            _marker: PhantomData,
+        };
+        node.reset();
+        node
+    }
+
+    /// Loop between breakpoints `start` and `end` (`start` <= `end`) while the gate is open.
+    /// If `start` equals `end`, the envelope holds at the breakpoint as a sustain stage.
+    pub fn set_loop(&mut self, start: usize, end: usize) {
+        assert!(start <= end && end < self.points.len());
+        self.loop_points = Some((start, end));
+    }
+
+    /// Remove loop.
+    pub fn unloop(&mut self) {
+        self.loop_points = None;
+        self.holding = false;
+    }
+
+    /// Breakpoints as (time, value, curve).
+    pub fn points(&self) -> &[(f64, f64, f64)] {
+        &self.points
+    }
+
+    /// Current value of the envelope.
+    #[inline]
+    fn value(&self) -> f64 {
+        if self.index >= self.points.len() {
+            return self.from;
+        }
+        let (time, value, curve) = self.points[self.index];
+        let span = time - self.from_time;
+        let u = if span > 0.0 {
+            clamp01((self.time - self.from_time) / span)
+        } else {
+            1.0
+        };
+        self.from + (value - self.from) * curve_shape(u, curve)
+    }
+
+    /// Restart from time zero, gliding from the current value.
+    fn trigger(&mut self) {
+        self.from = self.value();
+        self.time = 0.0;
+        self.from_time = 0.0;
+        self.holding = false;
+        // Skip a breakpoint at time zero to glide into the next one.
+        self.index = if self.points[0].0 <= 0.0 && self.points.len() > 1 {
+            1
+        } else {
+            0
+        };
+    }
+
+    /// Leave the loop: continue from the current value after the loop end.
+    fn release(&mut self) {
+        self.holding = false;
+        if let Some((_, end)) = self.loop_points {
+            if self.index <= end {
+                self.from = self.value();
+                self.time = self.points[end].0;
+                self.from_time = self.time;
+                self.index = end + 1;
+            }
+        }
+    }
+
+    /// Advance time by one sample.
+    #[inline]
+    fn advance(&mut self) {
+        if self.holding || self.index >= self.points.len() {
+            return;
+        }
+        self.time += self.sample_duration;
+        while self.index < self.points.len() && self.time >= self.points[self.index].0 {
+            let (time, value, _) = self.points[self.index];
+            self.from = value;
+            match self.loop_points {
+                Some((start, end)) if self.gate && self.index == end => {
+                    let length = time - self.points[start].0;
+                    if length <= 0.0 {
+                        self.holding = true;
+                        self.time = time;
+                        self.from_time = time;
+                        self.index = end + 1;
+                        return;
+                    }
+                    self.time -= length;
+                    self.from = self.points[start].1;
+                    self.from_time = self.points[start].0;
+                    self.index = start + 1;
+                }
+                _ => {
+                    self.from_time = time;
+                    self.index += 1;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Float> AudioNode for Breakpoints<T> {
+    const ID: u64 = 131;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = BreakpointSetting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            BreakpointSetting::Retrigger => self.trigger(),
+            BreakpointSetting::Point(i, time, value, curve) => {
+                if i < self.points.len() {
+                    self.points[i] = (time, value, curve);
+                }
+            }
+            BreakpointSetting::Loop(start, end) => self.set_loop(start, end),
+            BreakpointSetting::Unloop => self.unloop(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.time = 0.0;
+        self.from = self.points[0].1;
+        self.from_time = 0.0;
+        self.index = self.points.len();
+        self.gate = false;
+        self.holding = false;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let gate = input[0] > T::zero();
+        if gate && !self.gate {
+            self.gate = true;
+            self.trigger();
+        } else if !gate && self.gate {
+            self.gate = false;
+            self.release();
+        }
+        let value = self.value();
+        self.advance();
+        [convert(value)].into()
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
+/// Setting of an LFO.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LfoSetting {
+    /// Restart the cycle from the phase offset.
+    #[default]
+    Retrigger,
+    /// Set waveform.
+    Shape(LfoShape),
+    /// Set rate in Hz.
+    Rate(f64),
+    /// Set phase offset in 0...1.
+    Phase(f64),
+    /// Set whether output is unipolar (0...1) instead of bipolar (-1...1).
+    Unipolar(bool),
+}
+
+/// LFO with selectable waveform, phase offset and retrigger.
+/// Random waveforms draw a new value for each cycle.
+/// Setting: `LfoSetting`.
+/// - Input 0: retrigger. A rising edge restarts the cycle from the phase offset.
+/// - Output 0: LFO in -1...1, or 0...1 if unipolar.
+#[derive(Clone)]
+pub struct LfoWave<T: Float> {
+    shape: LfoShape,
+    rate: f64,
+    /// Phase offset in 0...1.
+    offset: f64,
+    unipolar: bool,
+    phase: f64,
+    random: LfoRandom,
+    /// Whether the retrigger input is high.
+    trigger: bool,
+    sample_duration: f64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Float> LfoWave<T> {
+    /// Create LFO with waveform `shape` at `rate` Hz.
+    pub fn new(shape: LfoShape, rate: f64) -> Self {
+        Self {
+            shape,
+            rate,
+            offset: 0.0,
+            unipolar: false,
+            phase: 0.0,
+            random: LfoRandom::new(0),
+            trigger: false,
+            sample_duration: 1.0 / DEFAULT_SR,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Waveform.
+    #[inline]
+    pub fn shape(&self) -> LfoShape {
+        self.shape
+    }
+
+    /// Set waveform.
+    #[inline]
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+
+    /// Rate in Hz.
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Set rate in Hz.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+    }
+
+    /// Phase offset in 0...1.
+    #[inline]
+    pub fn phase_offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Set phase offset in 0...1. The offset applies immediately.
+    #[inline]
+    pub fn set_phase_offset(&mut self, offset: f64) {
+        self.offset = offset - floor(offset);
+    }
+
+    /// Whether output is unipolar (0...1) instead of bipolar (-1...1).
+    #[inline]
+    pub fn is_unipolar(&self) -> bool {
+        self.unipolar
+    }
+
+    /// Set whether output is unipolar (0...1) instead of bipolar (-1...1).
+    #[inline]
+    pub fn set_unipolar(&mut self, unipolar: bool) {
+        self.unipolar = unipolar;
+    }
+
+    /// Restart the cycle from the phase offset.
+    #[inline]
+    pub fn retrigger(&mut self) {
+        self.phase = 0.0;
+        self.random.advance();
+    }
+}
+
+impl<T: Float> AudioNode for LfoWave<T> {
+    const ID: u64 = 132;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = LfoSetting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            LfoSetting::Retrigger => self.retrigger(),
+            LfoSetting::Shape(shape) => self.set_shape(shape),
+            LfoSetting::Rate(rate) => self.set_rate(rate),
+            LfoSetting::Phase(offset) => self.set_phase_offset(offset),
+            LfoSetting::Unipolar(unipolar) => self.set_unipolar(unipolar),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.trigger = false;
+        self.random.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let trigger = input[0] > T::zero();
+        if trigger && !self.trigger {
+            self.retrigger();
+        }
+        self.trigger = trigger;
+        let phase = self.phase + self.offset;
+        let value = self
+            .shape
+            .at_cycle(phase - floor(phase), self.random.current, self.random.next);
+        self.phase += self.rate * self.sample_duration;
+        if self.phase >= 1.0 {
+            self.phase -= floor(self.phase);
+            self.random.advance();
+        }
+        let value = if self.unipolar {
+            0.5 * value + 0.5
+        } else {
+            value
+        };
+        [convert(value)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.random.set_hash(hash);
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
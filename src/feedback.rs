@@ -7,7 +7,10 @@ use super::math::*;
 use super::signal::*;
 use super::*;
 use duplicate::duplicate_item;
-use std::marker::PhantomData;
+use num_complex::Complex64;
+use core::marker::PhantomData;
+use core::ops::Add;
+use numeric_array::typenum::*;
 
 /// Diffusive Hadamard feedback matrix. The number of channels must be a power of two.
 #[derive(Default, Clone)]
@@ -57,6 +60,37 @@ impl<N: Size<T>, T: Float> FrameUnop<N, T> for FrameHadamard<N, T> {
     }
 }
 
+/// Linear transfer matrix of feedback operator `u` in the row-major format of `transfer_matrix`.
+fn unop_matrix<N: Size<T>, T: Float, U: FrameUnop<N, T>>(u: &U) -> Vec<Complex64> {
+    let n = N::USIZE;
+    // Constant offsets do not contribute to the response.
+    let offset = u.unop(&Frame::default());
+    let mut matrix = vec![Complex64::new(0.0, 0.0); n * n];
+    for j in 0..n {
+        let mut unit = Frame::<T, N>::default();
+        unit[j] = T::one();
+        let column = u.unop(&unit);
+        for i in 0..n {
+            matrix[i * n + j] = Complex64::new((column[i] - offset[i]).to_f64(), 0.0);
+        }
+    }
+    matrix
+}
+
+/// Multiply square matrices `x` and `y` given in row-major order.
+fn matrix_product(x: &[Complex64], y: &[Complex64]) -> Vec<Complex64> {
+    let n = (x.len() as f64).sqrt() as usize;
+    let mut product = vec![Complex64::new(0.0, 0.0); n * n];
+    for i in 0..n {
+        for j in 0..n {
+            for k in 0..n {
+                product[i * n + j] += x[i * n + k] * y[k * n + j];
+            }
+        }
+    }
+    product
+}
+
 /// Mix back output of contained node to its input.
 /// The contained node must have an equal number of inputs and outputs.
 #[derive(Clone)]
@@ -75,6 +109,8 @@ where
     // Feedback operator.
     #[allow(dead_code)]
     feedback: U,
+    // Current sample rate.
+    sample_rate: f64,
 }
 
 impl<N, T, X, U> Feedback<N, T, X, U>
@@ -91,6 +127,7 @@ where
             x,
             value: Frame::default(),
             feedback,
+            sample_rate: DEFAULT_SR,
         };
         let hash = node.ping(true, AttoHash::new(Self::ID));
         node.ping(false, hash);
@@ -119,6 +156,7 @@ where
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
         self.x.set_sample_rate(sample_rate);
     }
 
@@ -132,8 +170,17 @@ where
         output
     }
 
-    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
-        Routing::Arbitrary(0.0).propagate(input, self.outputs())
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let forward_output = self.x.route(input, frequency);
+        // The feedback path contains a delay of one sample.
+        let delay = Complex64::from_polar(1.0, -TAU * frequency / self.sample_rate);
+        let back: Vec<Complex64> = unop_matrix(&self.feedback)
+            .iter()
+            .map(|&r| r * delay)
+            .collect();
+        transfer_matrix(N::USIZE, N::USIZE, |frame| self.x.route(frame, frequency))
+            .and_then(|forward| feedback_response(input, &forward_output, &forward, &back))
+            .unwrap_or_else(|| Routing::Arbitrary(0.0).propagate(input, self.outputs()))
     }
 
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
@@ -143,6 +190,26 @@ where
     fn allocate(&mut self) {
         self.x.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
 }
 
 /// Mix back output of contained node `X` to its input, with extra feedback processing `Y`.
@@ -168,6 +235,8 @@ where
     /// Feedback operator.
     #[allow(dead_code)]
     feedback: U,
+    /// Current sample rate.
+    sample_rate: f64,
 }
 
 impl<N, T, X, Y, U> Feedback2<N, T, X, Y, U>
@@ -192,6 +261,7 @@ where
             y,
             value: Frame::default(),
             feedback,
+            sample_rate: DEFAULT_SR,
         };
         let hash = node.ping(true, AttoHash::new(Self::ID));
         node.ping(false, hash);
@@ -224,6 +294,7 @@ where
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
         self.x.set_sample_rate(sample_rate);
         self.y.set_sample_rate(sample_rate);
     }
@@ -238,8 +309,19 @@ where
         output
     }
 
-    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
-        Routing::Arbitrary(0.0).propagate(input, self.outputs())
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let forward_output = self.x.route(input, frequency);
+        // The feedback path contains a delay of one sample.
+        let delay = Complex64::from_polar(1.0, -TAU * frequency / self.sample_rate);
+        let back = transfer_matrix(N::USIZE, N::USIZE, |frame| self.y.route(frame, frequency))
+            .map(|y| matrix_product(&unop_matrix(&self.feedback), &y))
+            .map(|back| back.iter().map(|&r| r * delay).collect::<Vec<_>>());
+        transfer_matrix(N::USIZE, N::USIZE, |frame| self.x.route(frame, frequency))
+            .zip(back)
+            .and_then(|(forward, back)| {
+                feedback_response(input, &forward_output, &forward, &back)
+            })
+            .unwrap_or_else(|| Routing::Arbitrary(0.0).propagate(input, self.outputs()))
     }
 
     fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
@@ -249,6 +331,261 @@ where
     fn allocate(&mut self) {
         self.x.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
+}
+
+/// Stability guard applied to the feedback signal of `FeedbackLoop`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LoopGuard {
+    /// No guard. The loop may blow up if the loop gain exceeds one.
+    #[default]
+    None,
+    /// Soft clip each feedback channel to the given level with `tanh`.
+    Clip(f64),
+    /// Limit level of the feedback signal, measured as RMS over all channels, to the given level.
+    Energy(f64),
+}
+
+/// Time constant of the energy guard level detector in seconds.
+const GUARD_TIME: f64 = 0.01;
+
+/// Feedback loop with a feedback gain input, a stability guard and an integrated loop delay.
+/// The latency of the contained node counts toward the loop delay, and
+/// at least one sample of delay is always inserted, so the loop works
+/// around latency introducing nodes such as oversamplers.
+/// Setting: guard.
+/// - Inputs: from the contained node, followed by feedback gain
+/// - Outputs: from the contained node
+#[derive(Clone)]
+pub struct FeedbackLoop<N, T, X>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+{
+    x: X,
+    guard: LoopGuard,
+    /// Requested loop delay in seconds.
+    delay: f64,
+    /// Delay line length in samples.
+    samples: usize,
+    /// Delayed output frames, power-of-two sized.
+    line: Vec<Frame<T, N>>,
+    /// Current write index into the delay line.
+    index: usize,
+    /// Smoothed mean square of the feedback signal for the energy guard.
+    energy: f64,
+    /// Energy detector coefficient per sample.
+    energy_coeff: f64,
+    /// Current sample rate.
+    sample_rate: f64,
+}
+
+impl<N, T, X> FeedbackLoop<N, T, X>
+where
+    N: Size<T> + Add<U1>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    <N as Add<U1>>::Output: Size<T>,
+{
+    /// Create feedback loop around `x` with total loop `delay` in seconds
+    /// and feedback `guard`. Delays shorter than the latency of `x` plus one sample
+    /// are lengthened to that minimum.
+    pub fn new(delay: f64, guard: LoopGuard, x: X) -> Self {
+        let mut node = Self {
+            x,
+            guard,
+            delay,
+            samples: 1,
+            line: Vec::new(),
+            index: 0,
+            energy: 0.0,
+            energy_coeff: 0.0,
+            sample_rate: 0.0,
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Stability guard.
+    #[inline]
+    pub fn guard(&self) -> LoopGuard {
+        self.guard
+    }
+
+    /// Set stability guard.
+    #[inline]
+    pub fn set_guard(&mut self, guard: LoopGuard) {
+        self.guard = guard;
+    }
+
+    /// Total loop delay in samples, including the latency of the contained node.
+    pub fn loop_samples(&mut self) -> usize {
+        self.samples + self.node_latency()
+    }
+
+    /// Latency of the contained node in whole samples.
+    fn node_latency(&mut self) -> usize {
+        max(0.0, round(self.x.latency().unwrap_or_default())) as usize
+    }
+
+    /// Access the contained node.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Access the contained node.
+    #[inline]
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+}
+
+impl<N, T, X> AudioNode for FeedbackLoop<N, T, X>
+where
+    N: Size<T> + Add<U1>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    <N as Add<U1>>::Output: Size<T>,
+{
+    const ID: u64 = 123;
+    type Sample = T;
+    type Inputs = <N as Add<U1>>::Output;
+    type Outputs = N;
+    type Setting = LoopGuard;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_guard(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.line.fill(Frame::default());
+        self.index = 0;
+        self.energy = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.x.set_sample_rate(sample_rate);
+            let total = round(self.delay * sample_rate) as usize;
+            self.samples = max(1, total.saturating_sub(self.node_latency()));
+            self.line = vec![Frame::default(); self.samples.next_power_of_two()];
+            self.energy_coeff = 1.0 - exp(-1.0 / (GUARD_TIME * sample_rate));
+            self.index = 0;
+            self.energy = 0.0;
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mask = self.line.len() - 1;
+        let gain = input[N::USIZE];
+        let mut value = self.line[(self.index + mask + 1 - self.samples) & mask].clone()
+            * Frame::splat(gain);
+        match self.guard {
+            LoopGuard::None => (),
+            LoopGuard::Clip(level) => {
+                value =
+                    Frame::generate(|i| T::from_f64(level * tanh(value[i].to_f64() / level)));
+            }
+            LoopGuard::Energy(level) => {
+                let square =
+                    value.iter().map(|x| squared(x.to_f64())).sum::<f64>() / N::USIZE as f64;
+                self.energy += (square - self.energy) * self.energy_coeff;
+                // React to peaks immediately so the loop cannot run away within the detector time.
+                self.energy = max(self.energy, square);
+                let limit = squared(level);
+                if self.energy > limit {
+                    value *= Frame::splat(T::from_f64(sqrt(limit / self.energy)));
+                }
+            }
+        }
+        let output = self.x.tick(&Frame::generate(|i| input[i] + value[i]));
+        self.line[self.index] = output.clone();
+        self.index = (self.index + 1) & mask;
+        output
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let inner = copy_signal_frame(input, 0, N::USIZE);
+        let forward_output = self.x.route(&inner, frequency);
+        // The response is linear only for a constant gain and with the guard inactive.
+        let gain = match (input[N::USIZE], self.guard) {
+            (Signal::Value(gain), LoopGuard::None) => Some(gain),
+            _ => None,
+        };
+        let delay = Complex64::from_polar(
+            1.0,
+            -TAU * frequency * self.samples as f64 / self.sample_rate,
+        );
+        let n = N::USIZE;
+        gain.and_then(|gain| {
+            let mut back = vec![Complex64::new(0.0, 0.0); n * n];
+            for i in 0..n {
+                back[i * n + i] = delay * gain;
+            }
+            transfer_matrix(n, n, |frame| self.x.route(frame, frequency))
+                .and_then(|forward| feedback_response(&inner, &forward_output, &forward, &back))
+        })
+        .unwrap_or_else(|| Routing::Arbitrary(0.0).propagate(&inner, self.outputs()))
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
 }
 
 #[duplicate_item(
@@ -411,8 +748,21 @@ impl AudioUnit48 for Feedback48 {
         self.feedback.len()
     }
 
-    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
-        Routing::Arbitrary(0.0).propagate(input, self.outputs())
+    #[allow(clippy::unnecessary_cast)]
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let channels = self.channels;
+        let forward_output = self.x.route(input, frequency);
+        let delay = Complex64::from_polar(
+            1.0,
+            -TAU * frequency * self.samples as f64 / self.sample_rate as f64,
+        );
+        let mut back = vec![Complex64::new(0.0, 0.0); channels * channels];
+        for i in 0..channels {
+            back[i * channels + i] = delay;
+        }
+        transfer_matrix(channels, channels, |frame| self.x.route(frame, frequency))
+            .and_then(|forward| feedback_response(input, &forward_output, &forward, &back))
+            .unwrap_or_else(|| Routing::Arbitrary(0.0).propagate(input, self.outputs()))
     }
 
     fn get_id(&self) -> u64 {
@@ -425,10 +775,37 @@ impl AudioUnit48 for Feedback48 {
     }
 
     fn footprint(&self) -> usize {
-        std::mem::size_of::<Self>()
+        core::mem::size_of::<Self>()
     }
 
     fn allocate(&mut self) {
         self.x.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+        .with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
 }
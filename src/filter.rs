@@ -47,6 +47,21 @@ impl<F: Real> BiquadCoefs<F> {
         Self { a1, a2, b0, b1, b2 }
     }
 
+    /// Returns settings for a bandpass filter with unity gain at the center frequency.
+    /// The center frequency is given in Hz.
+    pub fn bandpass(sample_rate: F, center: F, q: F) -> Self {
+        let c = F::from_f64;
+        let omega: F = c(TAU) * center / sample_rate;
+        let alpha: F = sin(omega) / (c(2.0) * q);
+        let a0r: F = c(1.0) / (c(1.0) + alpha);
+        let a1: F = c(-2.0) * cos(omega) * a0r;
+        let a2: F = (c(1.0) - alpha) * a0r;
+        let b0: F = alpha * a0r;
+        let b1: F = c(0.0);
+        let b2: F = -b0;
+        Self { a1, a2, b0, b1, b2 }
+    }
+
     /// Arbitrary biquad.
     pub fn arbitrary(a1: F, a2: F, b0: F, b1: F, b2: F) -> Self {
         Self { a1, a2, b0, b1, b2 }
@@ -71,7 +86,7 @@ impl<F: Real> BiquadCoefs<F> {
 /// - Output 0: filtered signal.
 #[derive(Default, Clone)]
 pub struct Biquad<T, F> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
     coefs: BiquadCoefs<F>,
     x1: F,
     x2: F,
@@ -162,7 +177,7 @@ impl<T: Float, F: Real> AudioNode for Biquad<T, F> {
 /// - Output 0: filtered signal
 #[derive(Clone)]
 pub struct ButterLowpass<T: Float, F: Real, N: Size<T>> {
-    _marker: std::marker::PhantomData<N>,
+    _marker: core::marker::PhantomData<N>,
     biquad: Biquad<T, F>,
     sample_rate: F,
     cutoff: F,
@@ -172,7 +187,7 @@ impl<T: Float, F: Real, N: Size<T>> ButterLowpass<T, F, N> {
     /// Create new Butterworth lowpass filter with initial `cutoff` frequency in Hz.
     pub fn new(cutoff: F) -> Self {
         let mut node = ButterLowpass {
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
             biquad: Biquad::new(),
             sample_rate: F::from_f64(DEFAULT_SR),
             cutoff: F::zero(),
@@ -245,7 +260,7 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for ButterLowpass<T, F, N> {
 /// - Output 0: filtered signal
 #[derive(Clone)]
 pub struct Resonator<T: Float, F: Real, N: Size<T>> {
-    _marker: std::marker::PhantomData<N>,
+    _marker: core::marker::PhantomData<N>,
     biquad: Biquad<T, F>,
     sample_rate: F,
     center: F,
@@ -256,7 +271,7 @@ impl<T: Float, F: Real, N: Size<T>> Resonator<T, F, N> {
     /// Create new resonator bandpass. Initial `center` frequency and `bandwidth` are specified in Hz.
     pub fn new(center: F, bandwidth: F) -> Self {
         let mut node = Resonator {
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
             biquad: Biquad::new(),
             sample_rate: F::from_f64(DEFAULT_SR),
             center,
@@ -332,7 +347,7 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Resonator<T, F, N> {
 /// - Output 0: filtered signal
 #[derive(Default, Clone)]
 pub struct Lowpole<T: Float, F: Real, N: Size<T>> {
-    _marker: std::marker::PhantomData<(T, N)>,
+    _marker: core::marker::PhantomData<(T, N)>,
     value: F,
     coeff: F,
     cutoff: F,
@@ -343,7 +358,7 @@ impl<T: Float, F: Real, N: Size<T>> Lowpole<T, F, N> {
     /// Create new lowpass filter. Cutoff frequency is specified in Hz.
     pub fn new(cutoff: F) -> Self {
         let mut node = Lowpole {
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
             value: F::zero(),
             coeff: F::zero(),
             cutoff,
@@ -415,7 +430,7 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Lowpole<T, F, N> {
 /// - Output 0: zero centered signal
 #[derive(Default, Clone)]
 pub struct DCBlock<T: Float, F: Real> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
     x1: F,
     y1: F,
     cutoff: F,
@@ -500,7 +515,7 @@ pub struct Pinkpass<T: Float, F: Float> {
     b4: F,
     b5: F,
     b6: F,
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
     sample_rate: F,
 }
 
@@ -586,7 +601,7 @@ impl<T: Float, F: Float> AudioNode for Pinkpass<T, F> {
 /// - Output 0: filtered signal
 #[derive(Default, Clone)]
 pub struct Allpole<T: Float, F: Float, N: Size<T>> {
-    _marker: std::marker::PhantomData<(T, N)>,
+    _marker: core::marker::PhantomData<(T, N)>,
     eta: F,
     x1: F,
     y1: F,
@@ -598,7 +613,7 @@ impl<T: Float, F: Float, N: Size<T>> Allpole<T, F, N> {
     pub fn new(delay: F) -> Self {
         assert!(delay > F::zero());
         let mut node = Allpole {
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
             eta: F::zero(),
             x1: F::zero(),
             y1: F::zero(),
@@ -661,6 +676,108 @@ impl<T: Float, F: Float, N: Size<T>> AudioNode for Allpole<T, F, N> {
     }
 }
 
+/// Phase rotator: a chain of identical 1st order allpass filters tuned to the same frequency.
+/// Each stage rotates the phase by 90 degrees at the tuning frequency.
+/// Used in broadcast processing to make asymmetric waveforms, such as voice, more symmetric
+/// before limiting, which reduces peak levels without changing the sound.
+/// Setting: frequency.
+/// - Input 0: input signal
+/// - Output 0: phase rotated signal
+#[derive(Clone)]
+pub struct PhaseRotator<T: Float, F: Real> {
+    _marker: core::marker::PhantomData<T>,
+    /// Allpass coefficient.
+    eta: F,
+    /// Previous input and output of each stage.
+    state: Vec<(F, F)>,
+    frequency: F,
+    sample_rate: F,
+}
+
+impl<T: Float, F: Real> PhaseRotator<T, F> {
+    /// Create new phase rotator with `stages` stages (`stages` > 0) tuned to `frequency` Hz.
+    pub fn new(stages: usize, frequency: F) -> Self {
+        assert!(stages > 0);
+        let mut node = PhaseRotator {
+            _marker: core::marker::PhantomData,
+            eta: F::zero(),
+            state: vec![(F::zero(), F::zero()); stages],
+            frequency,
+            sample_rate: convert(DEFAULT_SR),
+        };
+        node.set_frequency(frequency);
+        node
+    }
+
+    /// Number of allpass stages.
+    #[inline]
+    pub fn stages(&self) -> usize {
+        self.state.len()
+    }
+
+    /// Tuning frequency in Hz.
+    #[inline]
+    pub fn frequency(&self) -> F {
+        self.frequency
+    }
+
+    /// Set tuning frequency in Hz.
+    #[inline]
+    pub fn set_frequency(&mut self, frequency: F) {
+        self.frequency = frequency;
+        let t = tan(F::from_f64(PI) * frequency / self.sample_rate);
+        self.eta = (t - F::one()) / (t + F::one());
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for PhaseRotator<T, F> {
+    const ID: u64 = 157;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = F;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_frequency(setting);
+    }
+
+    fn reset(&mut self) {
+        for state in self.state.iter_mut() {
+            *state = (F::zero(), F::zero());
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = convert(sample_rate);
+        self.set_frequency(self.frequency);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut x: F = convert(input[0]);
+        for (x1, y1) in self.state.iter_mut() {
+            let y = self.eta * (x - *y1) + *x1;
+            *x1 = x;
+            *y1 = y;
+            x = y;
+        }
+        [convert(x)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            let eta = self.eta.to_f64();
+            let z1 = Complex64::from_polar(1.0, -frequency * TAU / self.sample_rate.to_f64());
+            r * ((eta + z1) / (1.0 + eta * z1)).powu(self.stages() as u32)
+        });
+        output
+    }
+}
+
 /// One-pole, one-zero highpass filter.
 /// Setting: cutoff.
 /// The number of inputs is `N`, either `U1` or `U2`.
@@ -669,7 +786,7 @@ impl<T: Float, F: Float, N: Size<T>> AudioNode for Allpole<T, F, N> {
 /// - Output 0: filtered signal
 #[derive(Default, Clone)]
 pub struct Highpole<T: Float, F: Real, N: Size<T>> {
-    _marker: std::marker::PhantomData<(T, N)>,
+    _marker: core::marker::PhantomData<(T, N)>,
     x1: F,
     y1: F,
     coeff: F,
@@ -681,7 +798,7 @@ impl<T: Float, F: Real, N: Size<T>> Highpole<T, F, N> {
     /// Create new highpass filter. Initial `cutoff` frequency is specified in Hz.
     pub fn new(cutoff: F) -> Self {
         let mut node = Highpole {
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
             x1: F::zero(),
             y1: F::zero(),
             coeff: F::zero(),
@@ -0,0 +1,491 @@
+//! IIR filter design: Butterworth, Chebyshev and elliptic filters of arbitrary order.
+
+// For more information on elliptic filter design, see
+// Sophocles J. Orfanidis, "Lecture Notes on Elliptic Filter Design" (2006).
+
+use super::audionode::*;
+use super::filter::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+
+/// Analog filter prototypes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FilterPrototype {
+    /// Butterworth filter with a maximally flat passband.
+    /// The cutoff frequency is the -3 dB point.
+    Butterworth,
+    /// Chebyshev type I filter with passband ripple in dB (> 0).
+    /// The cutoff frequency is the edge of the passband, where the response leaves the ripple band.
+    Chebyshev1(f64),
+    /// Chebyshev type II filter with stopband attenuation in dB (> 0).
+    /// The cutoff frequency is the edge of the stopband, where the attenuation is first reached.
+    Chebyshev2(f64),
+    /// Elliptic (Cauer) filter with passband ripple and stopband attenuation in dB
+    /// (0 < ripple < attenuation). Gives the steepest transition for a given order.
+    /// The cutoff frequency is the edge of the passband, where the response leaves the ripple band.
+    Elliptic(f64, f64),
+}
+
+/// Filter pass types for filter design.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FilterPass {
+    /// Lowpass filter.
+    #[default]
+    Lowpass,
+    /// Highpass filter.
+    Highpass,
+}
+
+/// Second or first order section of an analog prototype filter normalized to a cutoff of 1 rad/s.
+#[derive(Copy, Clone, Debug)]
+struct AnalogSection {
+    /// Pole in the upper half of the s-plane. The conjugate pole is implied
+    /// unless the section is first order, in which case the pole is real.
+    pole: Complex64,
+    /// Zero on the imaginary axis, or `None` for zeros at infinity.
+    /// The conjugate zero is implied.
+    zero: Option<Complex64>,
+    /// Whether the section is first order.
+    first_order: bool,
+}
+
+/// Filter design of arbitrary order as a cascade of biquad sections.
+/// The analog prototype is computed once; the digital sections
+/// for any cutoff frequency are then obtained with the bilinear transform
+/// without allocating, which allows retuning in real time.
+#[derive(Clone, Debug)]
+pub struct FilterDesign {
+    prototype: FilterPrototype,
+    pass: FilterPass,
+    order: usize,
+    /// Analog sections of the prototype.
+    sections: Vec<AnalogSection>,
+    /// Prototype gain at DC.
+    gain: f64,
+}
+
+impl FilterDesign {
+    /// Design a filter of the given `order` (`order` > 0).
+    /// - Allocates: analog prototype.
+    pub fn new(prototype: FilterPrototype, pass: FilterPass, order: usize) -> Self {
+        assert!(order > 0);
+        let (poles, zeros, gain) = match prototype {
+            FilterPrototype::Butterworth => butterworth_prototype(order),
+            FilterPrototype::Chebyshev1(ripple) => chebyshev1_prototype(order, ripple),
+            FilterPrototype::Chebyshev2(attenuation) => chebyshev2_prototype(order, attenuation),
+            FilterPrototype::Elliptic(ripple, attenuation) => {
+                elliptic_prototype(order, ripple, attenuation)
+            }
+        };
+        Self {
+            prototype,
+            pass,
+            order,
+            sections: pair_sections(poles, zeros),
+            gain,
+        }
+    }
+
+    /// Analog prototype of the design.
+    #[inline]
+    pub fn prototype(&self) -> FilterPrototype {
+        self.prototype
+    }
+
+    /// Pass type of the design.
+    #[inline]
+    pub fn pass(&self) -> FilterPass {
+        self.pass
+    }
+
+    /// Filter order.
+    #[inline]
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Number of biquad sections, which is `(order + 1) / 2`.
+    #[inline]
+    pub fn sections(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Coefficients of biquad section `i` for the `cutoff` frequency in Hz
+    /// (below the Nyquist frequency) at `sample_rate`. Does not allocate.
+    pub fn section<F: Real>(&self, i: usize, sample_rate: f64, cutoff: f64) -> BiquadCoefs<F> {
+        let section = &self.sections[i];
+        // Prewarp the cutoff for the bilinear transform.
+        let c = tan(PI * cutoff / sample_rate);
+        let one = Complex64::new(1.0, 0.0);
+        let (pole, zero, reference) = match self.pass {
+            FilterPass::Lowpass => (
+                (one + section.pole * c) / (one - section.pole * c),
+                section.zero.map_or(-one, |z| (one + z * c) / (one - z * c)),
+                1.0,
+            ),
+            FilterPass::Highpass => (
+                (section.pole + c) / (section.pole - c),
+                section.zero.map_or(one, |z| (z + c) / (z - c)),
+                -1.0,
+            ),
+        };
+        let (a1, a2, b1, b2) = if section.first_order {
+            (-pole.re, 0.0, -zero.re, 0.0)
+        } else {
+            (
+                -2.0 * pole.re,
+                pole.norm_sqr(),
+                -2.0 * zero.re,
+                zero.norm_sqr(),
+            )
+        };
+        // Normalize the section to unit gain in the passband reference
+        // (DC for lowpass and Nyquist for highpass) and apply prototype gain to the first section.
+        let mut g = (1.0 + a1 * reference + a2) / (1.0 + b1 * reference + b2);
+        if i == 0 {
+            g *= self.gain;
+        }
+        let f = F::from_f64;
+        BiquadCoefs::arbitrary(f(a1), f(a2), f(g), f(g * b1), f(g * b2))
+    }
+
+    /// Coefficients of all biquad sections for the `cutoff` frequency in Hz
+    /// (below the Nyquist frequency) at `sample_rate`.
+    /// - Allocates: the vector of coefficients.
+    pub fn biquads<F: Real>(&self, sample_rate: f64, cutoff: f64) -> Vec<BiquadCoefs<F>> {
+        (0..self.sections())
+            .map(|i| self.section(i, sample_rate, cutoff))
+            .collect()
+    }
+}
+
+/// Butterworth prototype poles, zeros and DC gain.
+fn butterworth_prototype(order: usize) -> (Vec<Complex64>, Vec<Complex64>, f64) {
+    let poles = (0..order)
+        .map(|k| {
+            let theta = PI * (2 * k + 1) as f64 / (2 * order) as f64;
+            Complex64::new(-sin(theta), cos(theta))
+        })
+        .collect();
+    (poles, Vec::new(), 1.0)
+}
+
+/// Chebyshev type I prototype poles, zeros and DC gain with passband `ripple` in dB.
+fn chebyshev1_prototype(order: usize, ripple: f64) -> (Vec<Complex64>, Vec<Complex64>, f64) {
+    assert!(ripple > 0.0);
+    let epsilon = sqrt(exp10(0.1 * ripple) - 1.0);
+    let mu = (1.0 / epsilon).asinh() / order as f64;
+    let poles = (0..order)
+        .map(|k| {
+            let theta = PI * (2 * k + 1) as f64 / (2 * order) as f64;
+            Complex64::new(-mu.sinh() * sin(theta), mu.cosh() * cos(theta))
+        })
+        .collect();
+    // Even orders start from the bottom of the ripple band.
+    let gain = if order & 1 == 0 {
+        1.0 / sqrt(1.0 + epsilon * epsilon)
+    } else {
+        1.0
+    };
+    (poles, Vec::new(), gain)
+}
+
+/// Chebyshev type II prototype poles, zeros and DC gain with stopband `attenuation` in dB.
+fn chebyshev2_prototype(order: usize, attenuation: f64) -> (Vec<Complex64>, Vec<Complex64>, f64) {
+    assert!(attenuation > 0.0);
+    let delta = 1.0 / sqrt(exp10(0.1 * attenuation) - 1.0);
+    let mu = (1.0 / delta).asinh() / order as f64;
+    let mut poles = Vec::with_capacity(order);
+    let mut zeros = Vec::with_capacity(order);
+    for k in 0..order {
+        let theta = PI * (2 * k + 1) as f64 / (2 * order) as f64;
+        let p = Complex64::new(-mu.sinh() * sin(theta), mu.cosh() * cos(theta));
+        poles.push(p.inv());
+        // The middle zero of odd orders is at infinity.
+        if 2 * k + 1 != order {
+            zeros.push(Complex64::new(0.0, 1.0 / cos(theta)));
+        }
+    }
+    (poles, zeros, 1.0)
+}
+
+/// Elliptic prototype poles, zeros and DC gain with passband `ripple`
+/// and stopband `attenuation` in dB.
+fn elliptic_prototype(
+    order: usize,
+    ripple: f64,
+    attenuation: f64,
+) -> (Vec<Complex64>, Vec<Complex64>, f64) {
+    assert!(ripple > 0.0 && ripple < attenuation);
+    let ep = sqrt(exp10(0.1 * ripple) - 1.0);
+    let es = sqrt(exp10(0.1 * attenuation) - 1.0);
+    let k1 = ep / es;
+    let k = elliptic_degree(order, k1);
+    let j = Complex64::new(0.0, 1.0);
+    let v0 = (-j * asne(j / ep, k1) / order as f64).re;
+    let mut poles = Vec::with_capacity(order);
+    let mut zeros = Vec::with_capacity(order);
+    for i in 1..=order / 2 {
+        let u = Complex64::new((2 * i - 1) as f64 / order as f64, 0.0);
+        let zeta = cde(u, k);
+        let zero = j / (zeta * k);
+        zeros.push(zero);
+        zeros.push(zero.conj());
+        let pole = j * cde(u - j * v0, k);
+        poles.push(pole);
+        poles.push(pole.conj());
+    }
+    if order & 1 == 1 {
+        poles.push(Complex64::new((j * sne(j * v0, k)).re, 0.0));
+    }
+    let gain = if order & 1 == 0 {
+        1.0 / sqrt(1.0 + ep * ep)
+    } else {
+        1.0
+    };
+    (poles, zeros, gain)
+}
+
+/// Descending Landen sequence of elliptic moduli starting from modulus `k`.
+fn landen(k: f64) -> Vec<f64> {
+    let mut v = Vec::new();
+    let mut k = k;
+    while v.len() < 32 {
+        let kp = sqrt(max(0.0, 1.0 - k * k));
+        k = (1.0 - kp) / (1.0 + kp);
+        v.push(k);
+        if k < 1.0e-15 {
+            break;
+        }
+    }
+    v
+}
+
+/// Complete elliptic integrals of the first kind `K(k)` and `K'(k) = K(sqrt(1 - k^2))`.
+fn elliptic_k(k: f64) -> (f64, f64) {
+    let integral = |k: f64| PI * 0.5 * landen(k).iter().fold(1.0, |x, v| x * (1.0 + v));
+    (integral(k), integral(sqrt(max(0.0, 1.0 - k * k))))
+}
+
+/// Jacobi elliptic function `cd(u * K, k)` for complex `u`.
+fn cde(u: Complex64, k: f64) -> Complex64 {
+    let mut w = (u * (PI * 0.5)).cos();
+    for v in landen(k).iter().rev() {
+        w = w * (1.0 + v) / (w * w * *v + 1.0);
+    }
+    w
+}
+
+/// Jacobi elliptic function `sn(u * K, k)` for complex `u`.
+fn sne(u: Complex64, k: f64) -> Complex64 {
+    let mut w = (u * (PI * 0.5)).sin();
+    for v in landen(k).iter().rev() {
+        w = w * (1.0 + v) / (w * w * *v + 1.0);
+    }
+    w
+}
+
+/// Inverse of `cde`: returns `u` such that `cd(u * K, k) = w`.
+fn acde(w: Complex64, k: f64) -> Complex64 {
+    let v = landen(k);
+    let mut w = w;
+    let mut previous = k;
+    for vn in v.iter() {
+        let one = Complex64::new(1.0, 0.0);
+        w = w / (one + (one - w * w * (previous * previous)).sqrt()) * (2.0 / (1.0 + vn));
+        previous = *vn;
+    }
+    let u = w.acos() * (2.0 / PI);
+    let (kk, kp) = elliptic_k(k);
+    let r = kp / kk;
+    // Reduce to the fundamental period rectangle.
+    let symmetric_remainder = |x: f64, y: f64| x - y * round(x / y);
+    Complex64::new(
+        symmetric_remainder(u.re, 4.0),
+        symmetric_remainder(u.im, 2.0 * r),
+    )
+}
+
+/// Inverse of `sne`: returns `u` such that `sn(u * K, k) = w`.
+fn asne(w: Complex64, k: f64) -> Complex64 {
+    Complex64::new(1.0, 0.0) - acde(w, k)
+}
+
+/// Solve the degree equation for the elliptic modulus of an elliptic filter
+/// of the given `order` and discrimination modulus `k1`.
+fn elliptic_degree(order: usize, k1: f64) -> f64 {
+    let k1p = sqrt(1.0 - k1 * k1);
+    let mut kp = pow(k1p, order as f64);
+    for i in 1..=order / 2 {
+        let u = Complex64::new((2 * i - 1) as f64 / order as f64, 0.0);
+        kp *= pow(sne(u, k1p).re, 4.0);
+    }
+    sqrt(1.0 - kp * kp)
+}
+
+/// Pair prototype poles and zeros into sections. Complex poles are paired with their conjugates
+/// and each pole pair is matched with the nearest remaining zero pair.
+fn pair_sections(poles: Vec<Complex64>, zeros: Vec<Complex64>) -> Vec<AnalogSection> {
+    let epsilon = 1.0e-9;
+    let mut pairs: Vec<Complex64> = poles.iter().filter(|p| p.im > epsilon).copied().collect();
+    // Process poles closest to the imaginary axis first so they get the nearest zeros.
+    pairs.sort_by(|x, y| (-x.re / x.norm()).total_cmp(&(-y.re / y.norm())));
+    let mut zeros: Vec<Complex64> = zeros.iter().filter(|z| z.im > epsilon).copied().collect();
+    let mut sections = Vec::new();
+    for pole in pairs {
+        let nearest = (0..zeros.len()).min_by(|&i, &j| {
+            (zeros[i] - pole)
+                .norm()
+                .total_cmp(&(zeros[j] - pole).norm())
+        });
+        let zero = nearest.map(|i| zeros.swap_remove(i));
+        sections.push(AnalogSection {
+            pole,
+            zero,
+            first_order: false,
+        });
+    }
+    for pole in poles.iter().filter(|p| p.im.abs() <= epsilon) {
+        sections.push(AnalogSection {
+            pole: Complex64::new(pole.re, 0.0),
+            zero: None,
+            first_order: true,
+        });
+    }
+    sections
+}
+
+/// Designed IIR filter implemented as a cascade of biquad sections.
+/// Setting: cutoff.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct IirCascade<T: Real> {
+    design: FilterDesign,
+    cutoff: T,
+    biquads: Vec<Biquad<T, T>>,
+    sample_rate: f64,
+}
+
+impl<T: Real> IirCascade<T> {
+    /// Create filter from `design` with `cutoff` frequency in Hz.
+    pub fn new(design: FilterDesign, cutoff: T) -> Self {
+        let biquads = vec![Biquad::new(); design.sections()];
+        let mut node = Self {
+            design,
+            cutoff,
+            biquads,
+            sample_rate: DEFAULT_SR,
+        };
+        node.update();
+        node
+    }
+
+    /// The filter design.
+    #[inline]
+    pub fn design(&self) -> &FilterDesign {
+        &self.design
+    }
+
+    /// Cutoff frequency in Hz.
+    #[inline]
+    pub fn cutoff(&self) -> T {
+        self.cutoff
+    }
+
+    /// Set cutoff frequency in Hz. Does not allocate.
+    #[inline]
+    pub fn set_cutoff(&mut self, cutoff: T) {
+        self.cutoff = cutoff;
+        self.update();
+    }
+
+    /// Recompute section coefficients.
+    fn update(&mut self) {
+        for (i, biquad) in self.biquads.iter_mut().enumerate() {
+            biquad.set_coefs(
+                self.design
+                    .section(i, self.sample_rate, self.cutoff.to_f64()),
+            );
+        }
+    }
+}
+
+impl<T: Real> AudioNode for IirCascade<T> {
+    const ID: u64 = 155;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_cutoff(setting);
+    }
+
+    fn reset(&mut self) {
+        for biquad in self.biquads.iter_mut() {
+            biquad.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        for biquad in self.biquads.iter_mut() {
+            biquad.set_sample_rate(sample_rate);
+        }
+        self.update();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut x = input[0];
+        for biquad in self.biquads.iter_mut() {
+            x = biquad.filter_mono(x);
+        }
+        [x].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            self.biquads.iter().fold(r, |r, biquad| {
+                r * biquad.coefs().response(frequency / self.sample_rate)
+            })
+        });
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "cutoff",
+            ParameterUnit::Hz,
+            20.0,
+            20000.0,
+            1000.0,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.cutoff.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_cutoff(T::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
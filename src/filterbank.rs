@@ -0,0 +1,312 @@
+//! Constant-Q filterbank analysis with lock-free readers.
+
+use super::audionode::*;
+use super::filter::*;
+use super::loudness::Levels;
+use super::math::*;
+use super::shared::CONTROL_BLOCK;
+use super::signal::*;
+use super::*;
+use alloc::sync::Arc;
+use core::sync::atomic::AtomicU64;
+use numeric_array::typenum::*;
+
+/// Center frequencies of the 31 third-octave bands from 20 Hz to 20 kHz,
+/// spaced exactly a third of an octave apart around 1 kHz.
+pub fn third_octave_bands() -> Vec<f64> {
+    (-17..=13).map(|k| 1000.0 * exp2(k as f64 / 3.0)).collect()
+}
+
+/// Center frequencies of constant-Q bands with `bands_per_octave` bands per octave
+/// from `min` Hz to `max` Hz, spaced around 1 kHz.
+pub fn constant_q_bands(min: f64, max: f64, bands_per_octave: f64) -> Vec<f64> {
+    let first = ceil(bands_per_octave * log2(min / 1000.0)) as i64;
+    let last = floor(bands_per_octave * log2(max / 1000.0)) as i64;
+    (first..=last)
+        .map(|k| 1000.0 * exp2(k as f64 / bands_per_octave))
+        .collect()
+}
+
+/// Lock-free band level readings shared between a `FilterbankMeter` and any number
+/// of readers, for example, a UI thread. Levels are RMS amplitudes.
+pub struct BandLevels {
+    centers: Vec<f64>,
+    levels: Vec<AtomicU64>,
+}
+
+impl BandLevels {
+    /// Create band level readings for bands centered at `centers` Hz.
+    pub fn new(centers: &[f64]) -> Self {
+        Self {
+            centers: centers.to_vec(),
+            levels: centers.iter().map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Number of bands.
+    #[inline]
+    pub fn bands(&self) -> usize {
+        self.centers.len()
+    }
+
+    /// Center frequency of band `i` in Hz.
+    #[inline]
+    pub fn center(&self, i: usize) -> f64 {
+        self.centers[i]
+    }
+
+    /// RMS amplitude of band `i`.
+    #[inline]
+    pub fn level(&self, i: usize) -> f64 {
+        Levels::load(&self.levels[i])
+    }
+
+    /// RMS amplitudes of all bands.
+    pub fn levels(&self) -> Vec<f64> {
+        (0..self.bands()).map(|i| self.level(i)).collect()
+    }
+}
+
+/// Filterbank of 4th order constant-Q bandpass filters with RMS detectors.
+/// Each band is a cascade of two identical 2nd order sections.
+#[derive(Clone)]
+struct Filterbank {
+    centers: Vec<f64>,
+    /// Bandwidth of each band in octaves.
+    bandwidth: f64,
+    /// RMS smoothing timescale in seconds.
+    window: f64,
+    sections: Vec<[Biquad<f64, f64>; 2]>,
+    /// Whether each band is below the Nyquist frequency.
+    active: Vec<bool>,
+    squares: Vec<f64>,
+    /// Per-sample smoothing factor of mean squares.
+    smoothing: f64,
+}
+
+impl Filterbank {
+    fn new(centers: &[f64], bandwidth: f64, window: f64) -> Self {
+        let mut bank = Self {
+            centers: centers.to_vec(),
+            bandwidth,
+            window,
+            sections: vec![[Biquad::new(), Biquad::new()]; centers.len()],
+            active: vec![false; centers.len()],
+            squares: vec![0.0; centers.len()],
+            smoothing: 0.0,
+        };
+        bank.set_sample_rate(DEFAULT_SR);
+        bank
+    }
+
+    /// Bandwidth in octaves from the spacing of the first two bands.
+    /// A single band gets a bandwidth of a third of an octave.
+    fn spacing(centers: &[f64]) -> f64 {
+        if centers.len() >= 2 {
+            abs(log2(centers[1] / centers[0]))
+        } else {
+            1.0 / 3.0
+        }
+    }
+
+    fn reset(&mut self) {
+        for [a, b] in self.sections.iter_mut() {
+            a.reset();
+            b.reset();
+        }
+        self.squares.fill(0.0);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.smoothing = pow(0.5, 1.0 / (self.window * sample_rate));
+        let ratio = exp2(self.bandwidth);
+        // The -3 dB bandwidth of the cascade is that of one section at -1.5 dB.
+        let q = sqrt(ratio) / (ratio - 1.0) * sqrt(SQRT_2 - 1.0);
+        for i in 0..self.centers.len() {
+            self.active[i] = self.centers[i] < 0.45 * sample_rate;
+            if self.active[i] {
+                let coefs = BiquadCoefs::bandpass(sample_rate, self.centers[i], q);
+                self.sections[i][0].set_coefs(coefs);
+                self.sections[i][1].set_coefs(coefs);
+            }
+        }
+        self.reset();
+    }
+
+    #[inline]
+    fn tick(&mut self, x: f64) {
+        let a = self.smoothing;
+        for i in 0..self.centers.len() {
+            if self.active[i] {
+                let y = self.sections[i][0].tick(&[x].into());
+                let y = self.sections[i][1].tick(&y)[0];
+                self.squares[i] = a * self.squares[i] + (1.0 - a) * y * y;
+            }
+        }
+    }
+
+    #[inline]
+    fn level(&self, i: usize) -> f64 {
+        sqrt(self.squares[i])
+    }
+}
+
+/// Filterbank analyzer. Splits the input into `N` constant-Q bands,
+/// for example, third-octave bands, and outputs the RMS level of each band.
+/// Each band is a 4th order bandpass filter designed for the current sample rate.
+/// Bands centered above 45% of the sample rate read zero.
+/// - Input 0: signal
+/// - Output(s): RMS level of each band
+#[derive(Clone)]
+pub struct FilterbankAnalyzer<T: Float, N: Size<T>> {
+    bank: Filterbank,
+    _marker: core::marker::PhantomData<(T, N)>,
+}
+
+impl<T: Float, N: Size<T>> FilterbankAnalyzer<T, N> {
+    /// Create analyzer with `N` bands centered at `centers` Hz.
+    /// The bandwidth of each band equals the spacing of the first two bands.
+    /// Levels are smoothed with a halfway response time of `window` seconds.
+    pub fn new(centers: &[f64], window: f64) -> Self {
+        assert_eq!(centers.len(), N::USIZE);
+        Self {
+            bank: Filterbank::new(centers, Filterbank::spacing(centers), window),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Center frequencies of the bands in Hz.
+    #[inline]
+    pub fn centers(&self) -> &[f64] {
+        &self.bank.centers
+    }
+}
+
+impl<T: Float, N: Size<T>> AudioNode for FilterbankAnalyzer<T, N> {
+    const ID: u64 = 146;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = N;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.bank.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.bank.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.bank.tick(input[0].to_f64());
+        Frame::generate(|i| T::from_f64(self.bank.level(i)))
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..N::USIZE {
+            output[i] = input[0].distort(0.0);
+        }
+        output
+    }
+}
+
+/// Filterbank meter. Passes through its input and publishes the RMS levels
+/// of constant-Q bands to a shared `BandLevels` structure.
+/// Levels are published every `CONTROL_BLOCK` samples when ticked,
+/// or once per call when processed in blocks.
+/// Bands centered above 45% of the sample rate read zero.
+/// - Input 0: signal
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct FilterbankMeter<T: Float> {
+    levels: Arc<BandLevels>,
+    bank: Filterbank,
+    /// Sample index within the current block.
+    index: usize,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Float> FilterbankMeter<T> {
+    /// Create meter publishing to `levels`. The bands are those of `levels`
+    /// and the bandwidth of each band equals the spacing of the first two bands.
+    /// Levels are smoothed with a halfway response time of `window` seconds.
+    pub fn new(levels: &Arc<BandLevels>, window: f64) -> Self {
+        let centers = &levels.centers;
+        Self {
+            levels: levels.clone(),
+            bank: Filterbank::new(centers, Filterbank::spacing(centers), window),
+            index: 0,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Shared band levels.
+    #[inline]
+    pub fn levels(&self) -> &Arc<BandLevels> {
+        &self.levels
+    }
+
+    fn publish(&self) {
+        for i in 0..self.levels.bands() {
+            Levels::store(&self.levels.levels[i], self.bank.level(i));
+        }
+    }
+}
+
+impl<T: Float> AudioNode for FilterbankMeter<T> {
+    const ID: u64 = 147;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.bank.reset();
+        self.index = 0;
+        self.publish();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.bank.set_sample_rate(sample_rate);
+        self.index = 0;
+        self.publish();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.bank.tick(input[0].to_f64());
+        self.index += 1;
+        if self.index == CONTROL_BLOCK {
+            self.index = 0;
+            self.publish();
+        }
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for i in 0..size {
+            self.bank.tick(input[0][i].to_f64());
+        }
+        // For efficiency, publish levels only once per block.
+        self.publish();
+        self.index = 0;
+        output[0][..size].clone_from_slice(&input[0][..size]);
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
@@ -94,3 +94,428 @@ impl<T: Float, N: Size<T>> AudioNode for Fir<T, N> {
         output
     }
 }
+
+/// FIR filter with runtime coefficients. Coefficient `b[k]` is applied to the input
+/// delayed by `k` samples, as in MATLAB and SciPy.
+/// Setting: coefficients. The filter state is kept if the number of coefficients is unchanged.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct FirFilter<T: Float> {
+    /// Filter coefficients.
+    b: Vec<T>,
+    /// Ring buffer of recent inputs.
+    history: Vec<T>,
+    /// Index of the latest input in the ring buffer.
+    index: usize,
+    sample_rate: f64,
+}
+
+impl<T: Float> FirFilter<T> {
+    /// Create FIR filter from coefficients `b`. There must be at least one coefficient.
+    pub fn new(b: &[T]) -> Self {
+        assert!(!b.is_empty());
+        Self {
+            b: b.to_vec(),
+            history: vec![T::zero(); b.len()],
+            index: 0,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Filter coefficients.
+    #[inline]
+    pub fn coefficients(&self) -> &[T] {
+        &self.b
+    }
+
+    /// Set filter coefficients. There must be at least one coefficient.
+    /// The filter state is kept if the number of coefficients is unchanged.
+    pub fn set_coefficients(&mut self, b: &[T]) {
+        assert!(!b.is_empty());
+        if b.len() == self.b.len() {
+            self.b.copy_from_slice(b);
+        } else {
+            self.b = b.to_vec();
+            self.history = vec![T::zero(); b.len()];
+            self.index = 0;
+        }
+    }
+}
+
+impl<T: Float> AudioNode for FirFilter<T> {
+    const ID: u64 = 153;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = Vec<T>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_coefficients(&setting);
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(T::zero());
+        self.index = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.index = if self.index + 1 < self.history.len() {
+            self.index + 1
+        } else {
+            0
+        };
+        self.history[self.index] = input[0];
+        // Walk the ring buffer backwards in time from the latest input.
+        let (newer, older) = self.history.split_at(self.index + 1);
+        let output = self
+            .b
+            .iter()
+            .zip(newer.iter().rev().chain(older.iter().rev()))
+            .fold(T::zero(), |sum, (b, x)| sum + *b * *x);
+        [output].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            r * polynomial_response(&self.b, frequency / self.sample_rate)
+        });
+        output
+    }
+}
+
+/// IIR filter with runtime coefficients in direct form II transposed.
+/// Feedforward coefficients are `b` and feedback coefficients are `a`, as in MATLAB and SciPy:
+/// `a[0] * y[n] = b[0] * x[n] + b[1] * x[n - 1] + ... - a[1] * y[n - 1] - a[2] * y[n - 2] - ...`
+/// Coefficients are normalized by `a[0]`.
+/// High order filters are sensitive to rounding; consider cascading biquads instead.
+/// Setting: (`b`, `a`). The filter state is kept if the filter order is unchanged.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+#[derive(Clone)]
+pub struct IirFilter<T: Float> {
+    /// Normalized feedforward coefficients, padded to the filter order plus one.
+    b: Vec<T>,
+    /// Normalized feedback coefficients, padded to the filter order plus one.
+    a: Vec<T>,
+    /// Filter state.
+    z: Vec<T>,
+    sample_rate: f64,
+}
+
+impl<T: Float> IirFilter<T> {
+    /// Create IIR filter from feedforward coefficients `b` and feedback coefficients `a`.
+    /// There must be at least one coefficient in each and `a[0]` must be nonzero.
+    pub fn new(b: &[T], a: &[T]) -> Self {
+        let mut filter = Self {
+            b: Vec::new(),
+            a: Vec::new(),
+            z: Vec::new(),
+            sample_rate: DEFAULT_SR,
+        };
+        filter.set_coefficients(b, a);
+        filter
+    }
+
+    /// Filter order.
+    #[inline]
+    pub fn order(&self) -> usize {
+        self.z.len()
+    }
+
+    /// Normalized feedforward coefficients, padded to the filter order plus one.
+    #[inline]
+    pub fn b(&self) -> &[T] {
+        &self.b
+    }
+
+    /// Normalized feedback coefficients, padded to the filter order plus one.
+    #[inline]
+    pub fn a(&self) -> &[T] {
+        &self.a
+    }
+
+    /// Set feedforward coefficients `b` and feedback coefficients `a`.
+    /// There must be at least one coefficient in each and `a[0]` must be nonzero.
+    /// The filter state is kept if the filter order is unchanged.
+    pub fn set_coefficients(&mut self, b: &[T], a: &[T]) {
+        assert!(!b.is_empty() && !a.is_empty() && a[0] != T::zero());
+        let length = max(b.len(), a.len());
+        let z = T::one() / a[0];
+        let coefficient = |v: &[T], i: usize| if i < v.len() { v[i] * z } else { T::zero() };
+        if length == self.b.len() {
+            for i in 0..length {
+                self.b[i] = coefficient(b, i);
+                self.a[i] = coefficient(a, i);
+            }
+        } else {
+            self.b = (0..length).map(|i| coefficient(b, i)).collect();
+            self.a = (0..length).map(|i| coefficient(a, i)).collect();
+            self.z = vec![T::zero(); length - 1];
+        }
+    }
+}
+
+impl<T: Float> AudioNode for IirFilter<T> {
+    const ID: u64 = 154;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (Vec<T>, Vec<T>);
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_coefficients(&setting.0, &setting.1);
+    }
+
+    fn reset(&mut self) {
+        self.z.fill(T::zero());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let x = input[0];
+        let order = self.z.len();
+        if order == 0 {
+            return [self.b[0] * x].into();
+        }
+        let y = self.b[0] * x + self.z[0];
+        for i in 0..order {
+            let next = if i + 1 < order {
+                self.z[i + 1]
+            } else {
+                T::zero()
+            };
+            self.z[i] = self.b[i + 1] * x - self.a[i + 1] * y + next;
+        }
+        [y].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].filter(0.0, |r| {
+            let f = frequency / self.sample_rate;
+            r * polynomial_response(&self.b, f) / polynomial_response(&self.a, f)
+        });
+        output
+    }
+}
+
+/// Response of polynomial `c[0] + c[1] * z^-1 + c[2] * z^-2 + ...`
+/// at normalized frequency `f` (in cycles per sample).
+fn polynomial_response<T: Float>(c: &[T], f: f64) -> Complex64 {
+    let z1 = Complex64::from_polar(1.0, -TAU * f);
+    let mut z = Complex64::new(1.0, 0.0);
+    let mut x = Complex64::default();
+    for c in c.iter() {
+        x += z * c.to_f64();
+        z *= z1;
+    }
+    x
+}
+
+/// Window functions for FIR filter design.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum FirWindow {
+    /// Rectangular window (no windowing). Sharpest transition and the most ripple.
+    Rectangular,
+    /// Hann window.
+    Hann,
+    /// Hamming window. This is the default, as in MATLAB and SciPy.
+    #[default]
+    Hamming,
+    /// Blackman window. Wider transition and less ripple than the Hamming window.
+    Blackman,
+    /// Kaiser window with shape parameter beta (beta >= 0).
+    /// Larger values trade a wider transition for less ripple.
+    Kaiser(f64),
+}
+
+impl FirWindow {
+    /// Window value at tap `i` of `taps`.
+    pub fn at(&self, i: usize, taps: usize) -> f64 {
+        if taps < 2 {
+            return 1.0;
+        }
+        let x = i as f64 / (taps - 1) as f64;
+        match self {
+            FirWindow::Rectangular => 1.0,
+            FirWindow::Hann => 0.5 - 0.5 * cos(TAU * x),
+            FirWindow::Hamming => 0.54 - 0.46 * cos(TAU * x),
+            FirWindow::Blackman => 0.42 - 0.5 * cos(TAU * x) + 0.08 * cos(2.0 * TAU * x),
+            FirWindow::Kaiser(beta) => {
+                let y = 2.0 * x - 1.0;
+                bessel_i0(beta * sqrt(max(0.0, 1.0 - y * y))) / bessel_i0(*beta)
+            }
+        }
+    }
+}
+
+/// Modified Bessel function of the first kind of order zero.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let y = x * x * 0.25;
+    let mut k = 1.0;
+    while term > sum * 1.0e-16 {
+        term *= y / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Windowed ideal lowpass impulse response of `taps` taps at normalized cutoff `f`,
+/// scaled to unit gain at DC.
+fn sinc_response(taps: usize, f: f64, window: FirWindow) -> Vec<f64> {
+    let center = (taps - 1) as f64 * 0.5;
+    let mut h: Vec<f64> = (0..taps)
+        .map(|i| {
+            let t = i as f64 - center;
+            let ideal = if t == 0.0 {
+                2.0 * f
+            } else {
+                sin(TAU * f * t) / (PI * t)
+            };
+            ideal * window.at(i, taps)
+        })
+        .collect();
+    let z = 1.0 / h.iter().sum::<f64>();
+    h.iter_mut().for_each(|x| *x *= z);
+    h
+}
+
+/// Scale coefficients `h` to unit gain at normalized frequency `f` and convert them.
+fn normalize_response<T: Float>(h: &[f64], f: f64) -> Vec<T> {
+    let z = 1.0 / polynomial_response(h, f).norm();
+    h.iter().map(|x| T::from_f64(x * z)).collect()
+}
+
+/// Design a linear phase lowpass FIR filter of `taps` taps with the windowed sinc method.
+/// The `cutoff` frequency (in Hz) is where the response is -6 dB.
+/// The response is normalized to unit gain at DC. The latency is `(taps - 1) / 2` samples.
+///
+/// ### Example: Load A 63-Tap Lowpass Filter
+/// ```
+/// use fundsp::hacker::*;
+/// let filter = fir_coefs(&sinc_lowpass(63, 44100.0, 1000.0, FirWindow::Hamming));
+/// ```
+pub fn sinc_lowpass<T: Float>(
+    taps: usize,
+    sample_rate: f64,
+    cutoff: f64,
+    window: FirWindow,
+) -> Vec<T> {
+    assert!(taps > 0);
+    let h = sinc_response(taps, cutoff / sample_rate, window);
+    normalize_response(&h, 0.0)
+}
+
+/// Design a linear phase highpass FIR filter of `taps` taps with the windowed sinc method.
+/// The number of taps must be odd. The `cutoff` frequency (in Hz) is where the response is -6 dB.
+/// The response is normalized to unit gain at the Nyquist frequency.
+/// The latency is `(taps - 1) / 2` samples.
+pub fn sinc_highpass<T: Float>(
+    taps: usize,
+    sample_rate: f64,
+    cutoff: f64,
+    window: FirWindow,
+) -> Vec<T> {
+    assert!(taps & 1 == 1);
+    let mut h = sinc_response(taps, cutoff / sample_rate, window);
+    h.iter_mut().for_each(|x| *x = -*x);
+    h[taps >> 1] += 1.0;
+    normalize_response(&h, 0.5)
+}
+
+/// Design a linear phase bandpass FIR filter of `taps` taps with the windowed sinc method.
+/// The passband extends from `low` to `high` Hz.
+/// The response is normalized to unit gain at the center of the passband.
+/// The latency is `(taps - 1) / 2` samples.
+pub fn sinc_bandpass<T: Float>(
+    taps: usize,
+    sample_rate: f64,
+    low: f64,
+    high: f64,
+    window: FirWindow,
+) -> Vec<T> {
+    assert!(taps > 0 && low < high);
+    let h_high = sinc_response(taps, high / sample_rate, window);
+    let h_low = sinc_response(taps, low / sample_rate, window);
+    let h: Vec<f64> = h_high
+        .iter()
+        .zip(h_low.iter())
+        .map(|(x, y)| x - y)
+        .collect();
+    normalize_response(&h, 0.5 * (low + high) / sample_rate)
+}
+
+/// Design a linear phase bandstop FIR filter of `taps` taps with the windowed sinc method.
+/// The number of taps must be odd. The stopband extends from `low` to `high` Hz.
+/// The response is normalized to unit gain at DC. The latency is `(taps - 1) / 2` samples.
+pub fn sinc_bandstop<T: Float>(
+    taps: usize,
+    sample_rate: f64,
+    low: f64,
+    high: f64,
+    window: FirWindow,
+) -> Vec<T> {
+    assert!(taps & 1 == 1 && low < high);
+    let h_high = sinc_response(taps, high / sample_rate, window);
+    let h_low = sinc_response(taps, low / sample_rate, window);
+    let mut h: Vec<f64> = h_low
+        .iter()
+        .zip(h_high.iter())
+        .map(|(x, y)| x - y)
+        .collect();
+    h[taps >> 1] += 1.0;
+    normalize_response(&h, 0.0)
+}
+
+/// Design a linear phase FIR filter of `taps` taps with an arbitrary `magnitude` response
+/// (amplitude gain as a function of frequency in Hz) by frequency sampling.
+/// The number of taps must be odd. The magnitude is sampled at multiples of `sample_rate / taps` Hz
+/// and the impulse response is tapered with `window`. The latency is `(taps - 1) / 2` samples.
+///
+/// ### Example: Linear Phase Version Of A Bell Filter
+/// ```
+/// use fundsp::hacker::*;
+/// let mut bell = bell_hz(1000.0, 1.0, 2.0);
+/// let b: Vec<f64> = fir_design(255, 44100.0, |f| bell.response(0, f).unwrap().norm(), FirWindow::Hann);
+/// ```
+pub fn fir_design<T: Float>(
+    taps: usize,
+    sample_rate: f64,
+    mut magnitude: impl FnMut(f64) -> f64,
+    window: FirWindow,
+) -> Vec<T> {
+    assert!(taps & 1 == 1);
+    let m = taps >> 1;
+    let gains: Vec<f64> = (0..=m)
+        .map(|k| magnitude(k as f64 * sample_rate / taps as f64))
+        .collect();
+    (0..taps)
+        .map(|n| {
+            let mut sum = gains[0];
+            for (k, gain) in gains.iter().enumerate().skip(1) {
+                sum += 2.0 * gain * cos(TAU * k as f64 * (n as f64 - m as f64) / taps as f64);
+            }
+            T::from_f64(sum / taps as f64 * window.at(n, taps))
+        })
+        .collect()
+}
@@ -37,7 +37,7 @@ pub struct Follow<T: Float, F: Real> {
     /// Halfway response time.
     response_time: F,
     sample_rate: F,
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
 }
 
 impl<T: Float, F: Real> Follow<T, F> {
@@ -146,7 +146,7 @@ pub struct AFollow<T: Float, F: Real, S: ScalarOrPair<Sample = F>> {
     /// Response times.
     time: S,
     sample_rate: F,
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
 }
 
 impl<T: Float, F: Real, S: ScalarOrPair<Sample = F>> AFollow<T, F, S> {
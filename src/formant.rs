@@ -0,0 +1,249 @@
+//! Formant filters.
+
+use super::audionode::*;
+use super::filter::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use num_complex::Complex64;
+use numeric_array::typenum::*;
+
+/// Number of formants per vowel.
+pub const VOWEL_FORMANTS: usize = 5;
+
+/// A vowel described by its formant center frequencies (Hz),
+/// bandwidths (Hz) and amplitudes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vowel {
+    pub frequency: [f64; VOWEL_FORMANTS],
+    pub bandwidth: [f64; VOWEL_FORMANTS],
+    pub amplitude: [f64; VOWEL_FORMANTS],
+}
+
+/// Bass voice formants of the vowels A, E, I, O and U, in morphing order.
+pub const VOWELS: [Vowel; 5] = [
+    Vowel {
+        frequency: [600.0, 1040.0, 2250.0, 2450.0, 2750.0],
+        bandwidth: [60.0, 70.0, 110.0, 120.0, 130.0],
+        amplitude: [1.0, 0.446684, 0.354813, 0.354813, 0.1],
+    },
+    Vowel {
+        frequency: [400.0, 1620.0, 2400.0, 2800.0, 3100.0],
+        bandwidth: [40.0, 80.0, 100.0, 120.0, 120.0],
+        amplitude: [1.0, 0.251189, 0.354813, 0.251189, 0.125893],
+    },
+    Vowel {
+        frequency: [250.0, 1750.0, 2600.0, 3050.0, 3340.0],
+        bandwidth: [60.0, 90.0, 100.0, 120.0, 120.0],
+        amplitude: [1.0, 0.031623, 0.158489, 0.079433, 0.039811],
+    },
+    Vowel {
+        frequency: [400.0, 750.0, 2400.0, 2600.0, 2900.0],
+        bandwidth: [40.0, 80.0, 100.0, 120.0, 120.0],
+        amplitude: [1.0, 0.281838, 0.089125, 0.1, 0.01],
+    },
+    Vowel {
+        frequency: [350.0, 600.0, 2400.0, 2675.0, 2950.0],
+        bandwidth: [40.0, 80.0, 100.0, 120.0, 120.0],
+        amplitude: [1.0, 0.1, 0.025119, 0.039811, 0.015849],
+    },
+];
+
+/// Interpolated vowel at `morph` in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U).
+/// Frequencies and bandwidths are interpolated exponentially, amplitudes linearly.
+pub fn vowel(morph: f64) -> Vowel {
+    let x = clamp01(morph) * (VOWELS.len() - 1) as f64;
+    let i = min(x as usize, VOWELS.len() - 2);
+    let t = x - i as f64;
+    let (a, b) = (&VOWELS[i], &VOWELS[i + 1]);
+    Vowel {
+        frequency: core::array::from_fn(|j| xerp(a.frequency[j], b.frequency[j], t)),
+        bandwidth: core::array::from_fn(|j| xerp(a.bandwidth[j], b.bandwidth[j], t)),
+        amplitude: core::array::from_fn(|j| lerp(a.amplitude[j], b.amplitude[j], t)),
+    }
+}
+
+/// Bank of parallel bandpass filters with individual gains.
+/// Each bandpass filter has unity gain at its center frequency.
+/// - Input 0: audio
+/// - Output 0: sum of filtered audio
+#[derive(Clone)]
+pub struct FormantBank<T: Float, F: Real> {
+    filters: Vec<Biquad<T, F>>,
+    frequency: Vec<f64>,
+    q: Vec<f64>,
+    gain: Vec<F>,
+    sample_rate: f64,
+}
+
+impl<T: Float, F: Real> FormantBank<T, F> {
+    /// Create a formant bank with center frequencies `frequency` (Hz),
+    /// Q values `q` and amplitude gains `gain`, which must all have the same length.
+    pub fn new(frequency: &[f64], q: &[f64], gain: &[f64]) -> Self {
+        assert!(frequency.len() == q.len() && q.len() == gain.len());
+        let mut node = Self {
+            filters: vec![Biquad::new(); frequency.len()],
+            frequency: frequency.to_vec(),
+            q: q.to_vec(),
+            gain: gain.iter().map(|&x| F::from_f64(x)).collect(),
+            sample_rate: DEFAULT_SR,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Number of formants.
+    #[inline]
+    pub fn formants(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Set center frequency (Hz), Q and amplitude gain of formant `i`.
+    pub fn set_formant(&mut self, i: usize, frequency: f64, q: f64, gain: f64) {
+        self.frequency[i] = frequency;
+        self.q[i] = q;
+        self.gain[i] = F::from_f64(gain);
+        self.update(i);
+    }
+
+    fn update(&mut self, i: usize) {
+        let center = min(self.frequency[i], self.sample_rate * 0.45);
+        self.filters[i].set_coefs(BiquadCoefs::bandpass(
+            F::from_f64(self.sample_rate),
+            F::from_f64(center),
+            F::from_f64(self.q[i]),
+        ));
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for FormantBank<T, F> {
+    const ID: u64 = 96;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        for filter in self.filters.iter_mut() {
+            filter.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        for i in 0..self.formants() {
+            self.filters[i].set_sample_rate(sample_rate);
+            self.update(i);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut output = F::zero();
+        for i in 0..self.formants() {
+            let y: F = convert(self.filters[i].tick(input)[0]);
+            output += self.gain[i] * y;
+        }
+        [convert(output)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let mut response = Complex64::new(0.0, 0.0);
+        for i in 0..self.formants() {
+            response += self.filters[i]
+                .coefs()
+                .response(frequency / self.sample_rate)
+                * self.gain[i].to_f64();
+        }
+        output[0] = input[0].filter(0.0, |r| r * response);
+        output
+    }
+}
+
+/// Vowel formant filter. Morphs between the vowels A, E, I, O and U.
+/// Setting: vowel morph in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U).
+/// The number of inputs is `N`, either `U1` or `U2`.
+/// - Input 0: audio
+/// - Input 1 (optional): vowel morph in 0...1
+/// - Output 0: filtered audio
+#[derive(Clone)]
+pub struct Formant<T: Float, F: Real, N: Size<T>> {
+    bank: FormantBank<T, F>,
+    morph: T,
+    _marker: core::marker::PhantomData<N>,
+}
+
+impl<T: Float, F: Real, N: Size<T>> Formant<T, F, N> {
+    pub fn new(morph: T) -> Self {
+        let mut node = Self {
+            bank: FormantBank::new(
+                &[0.0; VOWEL_FORMANTS],
+                &[1.0; VOWEL_FORMANTS],
+                &[0.0; VOWEL_FORMANTS],
+            ),
+            morph,
+            _marker: core::marker::PhantomData,
+        };
+        node.set_morph(morph);
+        node
+    }
+
+    /// Vowel morph in 0...1.
+    #[inline]
+    pub fn morph(&self) -> T {
+        self.morph
+    }
+
+    /// Set vowel morph in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U).
+    pub fn set_morph(&mut self, morph: T) {
+        self.morph = morph;
+        let vowel = vowel(morph.to_f64());
+        for i in 0..VOWEL_FORMANTS {
+            self.bank.set_formant(
+                i,
+                vowel.frequency[i],
+                vowel.frequency[i] / vowel.bandwidth[i],
+                vowel.amplitude[i],
+            );
+        }
+    }
+}
+
+impl<T: Float, F: Real, N: Size<T>> AudioNode for Formant<T, F, N> {
+    const ID: u64 = 97;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, morph: Self::Setting) {
+        self.set_morph(morph);
+    }
+
+    fn reset(&mut self) {
+        self.bank.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.bank.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if N::USIZE > 1 && input[1] != self.morph {
+            self.set_morph(input[1]);
+        }
+        self.bank.tick(Frame::from_slice(&input[0..1]))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.bank.route(input, frequency)
+    }
+}
@@ -0,0 +1,106 @@
+//! NaN and infinity watchdog.
+
+use super::audionode::*;
+use super::signal::*;
+use super::*;
+use core::marker::PhantomData;
+
+/// Samples with an absolute value above this are considered to have blown up.
+pub const GUARD_LIMIT: f64 = 1.0e6;
+
+/// Return true if `x` is finite and no larger in magnitude than `GUARD_LIMIT`.
+#[inline]
+pub fn is_valid_sample<T: Float>(x: T) -> bool {
+    // NaN fails every comparison.
+    x.abs() <= T::from_f64(GUARD_LIMIT)
+}
+
+/// Find the first invalid sample (see `is_valid_sample`) in the first `size` samples of `buffers`.
+/// Returns the channel and index of the sample.
+///
+/// ### Example
+/// ```
+/// use fundsp::guard::*;
+/// let buffers = [vec![0.0, 1.0], vec![2.0, f64::NAN]];
+/// assert_eq!(find_invalid(2, &buffers), Some((1, 1)));
+/// assert_eq!(find_invalid(1, &buffers), None);
+/// ```
+pub fn find_invalid<T: Float, B: AsRef<[T]>>(size: usize, buffers: &[B]) -> Option<(usize, usize)> {
+    for (channel, buffer) in buffers.iter().enumerate() {
+        if let Some(i) = buffer.as_ref()[..size]
+            .iter()
+            .position(|&x| !is_valid_sample(x))
+        {
+            return Some((channel, i));
+        }
+    }
+    None
+}
+
+/// Pass through inputs unchanged, panicking if any of them are NaN, infinite
+/// or larger in magnitude than `GUARD_LIMIT`. This is a debugging aid:
+/// insert guards into a graph to find where invalid values originate.
+/// - Inputs: input signals.
+/// - Outputs: input signals, unchanged.
+#[derive(Default, Clone)]
+pub struct Guard<N, T> {
+    _marker: PhantomData<(N, T)>,
+}
+
+impl<N: Size<T>, T: Float> Guard<N, T> {
+    pub fn new() -> Self {
+        Guard::default()
+    }
+}
+
+impl<N: Size<T>, T: Float> AudioNode for Guard<N, T> {
+    const ID: u64 = 89;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if let Some(channel) = input.iter().position(|&x| !is_valid_sample(x)) {
+            panic!(
+                "Guard: invalid sample {} in channel {}.",
+                input[channel], channel
+            );
+        }
+        input.clone()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for i in 0..self.inputs() {
+            output[i][..size].clone_from_slice(&input[i][..size]);
+        }
+        if let Some((channel, i)) = find_invalid(size, input) {
+            panic!(
+                "Guard: invalid sample {} in channel {}.",
+                input[channel][i], channel
+            );
+        }
+    }
+
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [Self::Sample]]) {
+        if let Some((channel, i)) = find_invalid(size, buffers) {
+            panic!(
+                "Guard: invalid sample {} in channel {}.",
+                buffers[channel][i], channel
+            );
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
@@ -1,47 +1,107 @@
 //! The hacker prelude, a fully 64-bit environment for audio processing.
 
+pub use super::additive::*;
+#[cfg(feature = "std")]
+pub use super::audit::*;
 pub use super::audionode::*;
 pub use super::audiounit::*;
 pub use super::buffer::*;
+pub use super::channels::*;
 pub use super::combinator::*;
+#[cfg(feature = "std")]
+pub use super::convolve::*;
+pub use super::denormal::*;
 pub use super::delay::*;
+#[cfg(feature = "device")]
+pub use super::device::*;
+#[cfg(feature = "dsl")]
+pub use super::dsl::*;
 pub use super::dynamics::*;
 pub use super::envelope::*;
 pub use super::feedback::*;
 pub use super::filter::*;
+pub use super::filter_design::*;
+pub use super::filterbank::*;
 pub use super::fir::*;
 pub use super::follow::*;
+pub use super::formant::*;
+#[cfg(feature = "std")]
 pub use super::gen::*;
+#[cfg(feature = "std")]
 pub use super::granular::*;
+pub use super::guard::*;
+#[cfg(feature = "std")]
+pub use super::layer::*;
+#[cfg(feature = "link")]
+pub use super::link::*;
+pub use super::looper::*;
+pub use super::loudness::*;
 pub use super::math::*;
+pub use super::mixer::*;
 pub use super::moog::*;
+#[cfg(feature = "std")]
+pub use super::music::*;
+#[cfg(feature = "std")]
 pub use super::net::*;
 pub use super::noise::*;
+#[cfg(feature = "osc")]
+pub use super::osc::*;
 pub use super::oscillator::*;
 pub use super::oversample::*;
 pub use super::pan::*;
+#[cfg(feature = "std")]
+pub use super::parallel::*;
+#[cfg(feature = "std")]
+pub use super::pattern::*;
+pub use super::pitch::*;
+#[cfg(feature = "std")]
+pub use super::playlist::*;
+#[cfg(feature = "plugin")]
+pub use super::plugin::*;
+pub use super::poly::*;
+pub use super::preset::*;
+#[cfg(feature = "std")]
 pub use super::realnet::*;
+#[cfg(feature = "std")]
 pub use super::realseq::*;
 pub use super::resample::*;
+#[cfg(feature = "std")]
 pub use super::resynth::*;
 pub use super::rez::*;
+#[cfg(feature = "std")]
 pub use super::sequencer::*;
+#[cfg(feature = "std")]
 pub use super::setting::*;
 pub use super::shape::*;
 pub use super::shared::*;
+pub use super::shift::*;
 pub use super::signal::*;
+#[cfg(feature = "std")]
 pub use super::slot::*;
+#[cfg(feature = "std")]
 pub use super::snoop::*;
+#[cfg(feature = "files")]
+pub use super::stream::*;
+pub use super::stretch::*;
 pub use super::svf::*;
 pub use super::system::*;
+#[cfg(feature = "std")]
+pub use super::testing::*;
+pub use super::transport::*;
+pub use super::unison::*;
+pub use super::vocoder::*;
 pub use super::wave::*;
+#[cfg(feature = "std")]
 pub use super::wavetable::*;
 pub use super::*;
 
 #[cfg(feature = "files")]
 pub use super::read::*;
 
-use std::sync::Arc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
 
 // Combinator environment.
 // We like to define all kinds of useful functions here.
@@ -271,6 +331,35 @@ pub fn multipass<N: Size<f64>>() -> An<MultiPass<N, f64>> {
     An(MultiPass::new())
 }
 
+/// Mono pass-through that panics if the signal is NaN, infinite or larger in magnitude than `GUARD_LIMIT`.
+/// Insert guards into a graph to find where invalid values originate.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Check Filter Output
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = lowpass_hz(1000.0, 1.0) >> guard();
+/// node.filter_mono(1.0);
+/// ```
+pub fn guard() -> An<Guard<U1, f64>> {
+    An(Guard::new())
+}
+
+/// Multichannel pass-through that panics if any signal is NaN, infinite or larger in magnitude than `GUARD_LIMIT`.
+/// - Input(s): signal
+/// - Output(s): signal
+///
+/// ### Example: Stereo Guard
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = multiguard::<U2>();
+/// assert_eq!(node.filter_stereo(1.0, -1.0), (1.0, -1.0));
+/// ```
+pub fn multiguard<N: Size<f64>>() -> An<Guard<N, f64>> {
+    An(Guard::new())
+}
+
 /// Monitor node. Passes through input. Communicates via the shared variable
 /// an aspect of the input signal according to the chosen metering mode.
 /// - Input 0: signal
@@ -286,6 +375,77 @@ pub fn monitor(shared: &Shared<f64>, meter: Meter) -> An<Monitor<f64>> {
     An(Monitor::new(shared, meter))
 }
 
+/// Level meter. Passes through input and publishes peak level, RMS level
+/// over a window of `window` seconds and ITU-R BS.1770 loudness to `levels`,
+/// which can be read lock-free from other threads.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Metering A Sine Wave
+/// ```
+/// use fundsp::hacker::*;
+/// let levels = std::sync::Arc::new(Levels::new());
+/// let mut node = sine_hz(1000.0) >> level_meter(&levels, 0.3);
+/// for _ in 0..44100 {
+///     node.get_mono();
+/// }
+/// assert!((levels.rms() - sqrt(0.5)).abs() < 0.01);
+/// assert!((levels.momentary() + 3.01).abs() < 0.1);
+/// ```
+pub fn level_meter(levels: &Arc<Levels>, window: f64) -> An<LevelMeter<f64, U1>> {
+    An(LevelMeter::new(levels, window))
+}
+
+/// Stereo level meter. Passes through input and publishes peak level, RMS level
+/// over a window of `window` seconds and ITU-R BS.1770 loudness to `levels`,
+/// which can be read lock-free from other threads.
+/// Peak and RMS levels are taken over both channels.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: left signal
+/// - Output 1: right signal
+pub fn level_meter_stereo(levels: &Arc<Levels>, window: f64) -> An<LevelMeter<f64, U2>> {
+    An(LevelMeter::new(levels, window))
+}
+
+/// Filterbank analyzer with `N` constant-Q bands centered at `centers` Hz
+/// (`centers` must contain `N` frequencies). Outputs the RMS level of each band,
+/// smoothed with a halfway response time of `window` seconds.
+/// The bandwidth of each band equals the spacing of the first two bands.
+/// Each band is a 4th order bandpass filter designed for the current sample rate.
+/// Band centers are available from `third_octave_bands` and `constant_q_bands`.
+/// - Input 0: signal
+/// - Output(s): RMS level of each band
+///
+/// ### Example: Third-Octave Band Levels
+/// ```
+/// use fundsp::hacker::*;
+/// let bands = pink() >> filterbank_analyzer::<U31>(&third_octave_bands(), 0.1);
+/// ```
+pub fn filterbank_analyzer<N: Size<f64>>(centers: &[f64], window: f64) -> An<FilterbankAnalyzer<f64, N>> {
+    An(FilterbankAnalyzer::new(centers, window))
+}
+
+/// Filterbank meter. Passes through input and publishes the RMS levels of the bands
+/// of `levels`, smoothed with a halfway response time of `window` seconds.
+/// Levels can be read lock-free from other threads, for example, by a spectrum display.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Octave Band Display
+/// ```
+/// use fundsp::hacker::*;
+/// let levels = std::sync::Arc::new(BandLevels::new(&constant_q_bands(31.0, 16000.0, 1.0)));
+/// let mut node = pink() >> filterbank_meter(&levels, 0.3);
+/// for _ in 0..4410 {
+///     node.get_mono();
+/// }
+/// assert_eq!(levels.bands(), 10);
+/// ```
+pub fn filterbank_meter(levels: &Arc<BandLevels>, window: f64) -> An<FilterbankMeter<f64>> {
+    An(FilterbankMeter::new(levels, window))
+}
+
 /// Meter node.
 /// Outputs a summary of the input according to the chosen metering mode.
 /// - Input 0: signal
@@ -325,6 +485,71 @@ pub fn reverse<N: Size<f64>>() -> An<Reverse<N, f64>> {
     An(Reverse::new())
 }
 
+/// Rearrange `N` channels. Output `i` copies input `mapping[i]`.
+/// The mapping must have `N` entries and can be changed at runtime.
+/// Setting: (output, source), where source `None` silences the output.
+/// - Input(s): signal
+/// - Output(s): rearranged signal
+///
+/// ### Example: Swap Stereo Pairs and Silence A Channel
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = route::<U4>(&[1, 0, 3, 2]);
+/// node.set((3, None));
+/// ```
+pub fn route<N: Size<f64>>(mapping: &[usize]) -> An<Remap<N, N, f64>> {
+    An(Remap::new(mapping))
+}
+
+/// Swap channels `a` and `b` of `N` channels. The rest pass through.
+/// Setting: (output, source), where source `None` silences the output.
+/// - Input(s): signal
+/// - Output(s): signal with channels `a` and `b` swapped
+///
+/// ### Example: Swap Left And Right
+/// ```
+/// use fundsp::hacker::*;
+/// reverb_stereo(20.0, 2.0, 0.5) >> swap_channels(0, 1);
+/// ```
+pub fn swap_channels<N: Size<f64>>(a: usize, b: usize) -> An<Remap<N, N, f64>> {
+    let mut mapping: Vec<usize> = (0..N::USIZE).collect();
+    mapping.swap(a, b);
+    An(Remap::new(&mapping))
+}
+
+/// Extract `M` channels from `N` channels. Output `i` copies input `indices[i]`.
+/// Setting: (output, source), where source `None` silences the output.
+/// - Input(s): signal
+/// - Output(s): extracted channels
+///
+/// ### Example: Front Pair Of A 5.1 Bus
+/// ```
+/// use fundsp::hacker::*;
+/// let node = extract_channels::<U6, U2>(&[0, 1]);
+/// assert_eq!(node.outputs(), 2);
+/// ```
+pub fn extract_channels<N: Size<f64>, M: Size<f64>>(indices: &[usize]) -> An<Remap<N, M, f64>> {
+    An(Remap::new(indices))
+}
+
+/// Additive synthesizer with sine `partials`. Partials can be changed with settings
+/// or, at runtime, added and removed. Partials above the Nyquist frequency are silent.
+/// Setting: `AdditiveSetting` (partial, partial amplitude or inharmonicity).
+/// - Input 0: fundamental frequency in Hz
+/// - Output 0: sum of partials
+///
+/// ### Example: Additive Saw With Stretched Partials
+/// ```
+/// use fundsp::hacker::*;
+/// let partials: Vec<Partial<f64>> = (1..=32).map(|i| Partial::new(i as f64, 1.0 / i as f64)).collect();
+/// let mut synth = additive(&partials);
+/// synth.set(AdditiveSetting::Inharmonicity(0.0005));
+/// let synth = dc(110.0) >> synth;
+/// ```
+pub fn additive(partials: &[Partial<f64>]) -> An<Additive<f64, f64>> {
+    An(Additive::new(DEFAULT_SR, partials))
+}
+
 /// Sine oscillator.
 /// - Input 0: frequency (Hz)
 /// - Output 0: sine wave
@@ -350,6 +575,90 @@ pub fn sine_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, Sine<f64>>> {
     super::prelude::sine_hz(f)
 }
 
+/// Bandlimited sine oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: sine wave
+pub fn sine_sync() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Sine, SyncInput::Sync))
+}
+
+/// Bandlimited saw oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: saw wave
+///
+/// ### Example: Hard Sync Lead
+/// ```
+/// use fundsp::hacker::*;
+/// (lfo(|t| 220.0 + 110.0 * sin_hz(0.5, t)) | saw_hz(110.0)) >> saw_sync();
+/// ```
+pub fn saw_sync() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Saw, SyncInput::Sync))
+}
+
+/// Bandlimited square oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: square wave
+pub fn square_sync() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Square, SyncInput::Sync))
+}
+
+/// Bandlimited triangle oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: triangle wave
+pub fn triangle_sync() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Triangle, SyncInput::Sync))
+}
+
+/// Bandlimited sine oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: sine wave
+///
+/// ### Example: Phase Modulation
+/// ```
+/// use fundsp::hacker::*;
+/// (dc(220.0) | sine_hz(3.0) * 0.2) >> sine_phase();
+/// ```
+pub fn sine_phase() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Sine, SyncInput::Phase))
+}
+
+/// Bandlimited saw oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: saw wave
+pub fn saw_phase() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Saw, SyncInput::Phase))
+}
+
+/// Bandlimited square oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: square wave
+pub fn square_phase() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Square, SyncInput::Phase))
+}
+
+/// Bandlimited triangle oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: triangle wave
+pub fn triangle_phase() -> An<SyncSynth<f64>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Triangle, SyncInput::Phase))
+}
+
 /// Rossler dynamical system oscillator.
 /// - Input 0: frequency. The Rossler oscillator exhibits peaks at multiples of this frequency.
 /// - Output 0: system output
@@ -376,6 +685,32 @@ pub fn lorenz() -> An<Lorenz<f64>> {
     An(Lorenz::new())
 }
 
+/// Rossler dynamical system oscillator at fixed frequency `f` Hz.
+/// Useful as a chaotic LFO at low frequencies.
+/// - Output 0: system output
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// rossler_hz(0.5) * 200.0 + 400.0 >> sine();
+/// ```
+pub fn rossler_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, Rossler<f64>>> {
+    constant(f) >> rossler()
+}
+
+/// Lorenz dynamical system oscillator at fixed frequency `f` Hz.
+/// Useful as a chaotic LFO at low frequencies.
+/// - Output 0: system output
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// lorenz_hz(1.0) * 200.0 + 400.0 >> sine();
+/// ```
+pub fn lorenz_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, Lorenz<f64>>> {
+    constant(f) >> lorenz()
+}
+
 /// Add constant to signal.
 /// - Input(s): signal
 /// - Output(s): signal plus constant
@@ -478,6 +813,22 @@ pub fn allpole_delay(delay: f64) -> An<Allpole<f64, f64, U1>> {
     An(Allpole::new(delay))
 }
 
+/// Phase rotator: chain of `stages` (`stages` > 0) 1st order allpass filters tuned to `frequency` Hz.
+/// Each stage rotates the phase by 90 degrees at the tuning frequency.
+/// Reduces asymmetric peaks, for example, in voice before limiting.
+/// Setting: frequency.
+/// - Input 0: audio
+/// - Output 0: phase rotated audio
+///
+/// ### Example: Broadcast Voice Chain
+/// ```
+/// use fundsp::hacker::*;
+/// phase_rotator(4, 200.0) >> limiter((0.001, 0.1));
+/// ```
+pub fn phase_rotator(stages: usize, frequency: f64) -> An<PhaseRotator<f64, f64>> {
+    super::prelude::phase_rotator(stages, frequency)
+}
+
 /// One-pole, one-zero highpass filter (1st order).
 /// - Input 0: audio
 /// - Input 1: cutoff frequency (Hz)
@@ -508,6 +859,72 @@ pub fn resonator() -> An<Resonator<f64, f64, U3>> {
     An(Resonator::new(440.0, 110.0))
 }
 
+/// Vowel formant filter with fixed vowel morph in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U).
+/// Setting: vowel morph.
+/// - Input 0: audio
+/// - Output 0: filtered audio
+///
+/// ### Example: Singing Saw
+/// ```
+/// use fundsp::hacker::*;
+/// saw_hz(110.0) >> formant(0.25);
+/// ```
+pub fn formant(vowel_morph: f64) -> An<Formant<f64, f64, U1>> {
+    An(Formant::new(vowel_morph))
+}
+
+/// Vowel formant filter with vowel morph input.
+/// - Input 0: audio
+/// - Input 1: vowel morph in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U)
+/// - Output 0: filtered audio
+pub fn formant_morph() -> An<Formant<f64, f64, U2>> {
+    An(Formant::new(0.0))
+}
+
+/// Bank of parallel bandpass filters with center frequencies `frequency` (Hz), Q values `q`
+/// and amplitude gains `gain`. The slices must have the same length.
+/// - Input 0: audio
+/// - Output 0: sum of filtered audio
+pub fn formant_bank(frequency: &[f64], q: &[f64], gain: &[f64]) -> An<FormantBank<f64, f64>> {
+    An(FormantBank::new(frequency, q, gain))
+}
+
+/// Parametric equalizer with `bands` (low shelf, bell and high shelf bands).
+/// Bands can be changed with settings or, at runtime, added and removed.
+/// Setting: (band index, band).
+/// - Input 0: audio
+/// - Output 0: equalized audio
+///
+/// ### Example: Channel Equalizer
+/// ```
+/// use fundsp::hacker::*;
+/// let mut eq = parametric_eq(&[
+///     EqBand::lowshelf(100.0, 0.7, 1.5),
+///     EqBand::bell(1000.0, 1.0, 0.5),
+///     EqBand::highshelf(8000.0, 0.7, 2.0),
+/// ]);
+/// eq.set((1, EqBand::bell(2000.0, 2.0, 0.5)));
+/// assert!(eq.response_db(0, 2000.0).unwrap() < -5.0);
+/// ```
+pub fn parametric_eq(bands: &[EqBand<f64>]) -> An<ParametricEq<f64, f64>> {
+    An(ParametricEq::new(DEFAULT_SR, bands))
+}
+
+/// Channel vocoder with `bands` bands (`bands` > 0), which are spaced logarithmically from 100 Hz to 8 kHz.
+/// The spectral envelope of the modulator is imposed on the carrier.
+/// - Input 0: carrier
+/// - Input 1: modulator
+/// - Output 0: vocoded signal
+///
+/// ### Example: Vocoded Saw
+/// ```
+/// use fundsp::hacker::*;
+/// (saw_hz(110.0) | pink()) >> vocoder(16);
+/// ```
+pub fn vocoder(bands: usize) -> An<Vocoder<f64, f64>> {
+    An(Vocoder::new(bands))
+}
+
 /// Constant-gain bandpass resonator with fixed `center` frequency (Hz) and `bandwidth` (Hz).
 /// - Input 0: audio
 /// - Output 0: filtered audio
@@ -556,6 +973,33 @@ pub fn moog_hz(frequency: f64, q: f64) -> An<Moog<f64, f64, U1>> {
     An(Moog::new(DEFAULT_SR, frequency, q))
 }
 
+/// Zero-delay feedback Moog ladder filter. Stays stable under fast cutoff modulation.
+/// Drive defaults to 1 and can be changed with `MoogZdf::set_drive`.
+/// - Input 0: input signal
+/// - Input 1: cutoff frequency (Hz)
+/// - Input 2: Q in 0...1
+/// - Output 0: filtered signal
+pub fn moog_zdf() -> An<MoogZdf<f64, f64, U3>> {
+    An(MoogZdf::new(DEFAULT_SR, 1000.0, 0.1, 1.0))
+}
+
+/// Zero-delay feedback Moog ladder filter with fixed Q.
+/// - Input 0: input signal
+/// - Input 1: cutoff frequency (Hz)
+/// - Output 0: filtered signal
+pub fn moog_zdf_q(
+    q: f64,
+) -> An<Pipe<f64, Stack<f64, MultiPass<U2, f64>, Constant<U1, f64>>, MoogZdf<f64, f64, U3>>> {
+    (multipass::<U2>() | dc(q)) >> An(MoogZdf::new(DEFAULT_SR, 1000.0, q, 1.0))
+}
+
+/// Zero-delay feedback Moog ladder filter with fixed cutoff frequency and Q.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+pub fn moog_zdf_hz(frequency: f64, q: f64) -> An<MoogZdf<f64, f64, U1>> {
+    An(MoogZdf::new(DEFAULT_SR, frequency, q, 1.0))
+}
+
 /// Morphing filter that morphs between lowpass, peak and highpass modes.
 /// - Input 0: input signal
 /// - Input 1: center frequency (Hz)
@@ -578,6 +1022,33 @@ pub fn morph_hz(
     super::prelude::morph_hz(f, q, morph)
 }
 
+/// State variable filter with simultaneous lowpass, highpass, bandpass and notch outputs.
+/// All four outputs are computed from a single filter core.
+/// - Input 0: audio
+/// - Input 1: cutoff frequency (Hz)
+/// - Input 2: Q
+/// - Output 0: lowpass filtered audio
+/// - Output 1: highpass filtered audio
+/// - Output 2: bandpass filtered audio
+/// - Output 3: notch filtered audio
+pub fn svf_multi() -> An<super::prelude::SvfMulti<f64, f64>> {
+    super::prelude::svf_multi()
+}
+
+/// State variable filter with cutoff frequency `f` Hz and Q value `q`
+/// and simultaneous lowpass, highpass, bandpass and notch outputs.
+/// - Input 0: audio
+/// - Output 0: lowpass filtered audio
+/// - Output 1: highpass filtered audio
+/// - Output 2: bandpass filtered audio
+/// - Output 3: notch filtered audio
+pub fn svf_multi_hz(
+    f: f64,
+    q: f64,
+) -> An<Pipe<f64, Stack<f64, Pass<f64>, Constant<U2, f64>>, super::prelude::SvfMulti<f64, f64>>> {
+    super::prelude::svf_multi_hz(f, q)
+}
+
 /// Control envelope from time-varying function `f(t)` with `t` in seconds.
 /// Spaces samples using pseudorandom jittering.
 /// Synonymous with [`fn@lfo`].
@@ -755,6 +1226,50 @@ where
     An(EnvelopeIn::new(0.002, DEFAULT_SR, f))
 }
 
+/// Multi-segment envelope with breakpoints (time, value, curve) in non-decreasing time order.
+/// Times are in seconds from the trigger. The curve of a breakpoint shapes the segment
+/// that ends at it: zero is linear, positive curves start slow and negative curves start fast.
+/// A rising gate retriggers the envelope, gliding from the current value.
+/// While the gate is open, the envelope loops between breakpoints set with `set_loop`,
+/// or holds at a breakpoint if the loop has zero length.
+/// Setting: `BreakpointSetting`.
+/// - Input 0: gate
+/// - Output 0: envelope
+///
+/// ### Example: DADSR Envelope With A Sustain Stage
+/// ```
+/// use fundsp::hacker::*;
+/// let mut env = envelope_bp(&[
+///     (0.0, 0.0, 0.0),
+///     (0.1, 0.0, 0.0),
+///     (0.11, 1.0, -4.0),
+///     (0.3, 0.6, 4.0),
+///     (0.8, 0.0, 4.0),
+/// ]);
+/// env.set_loop(3, 3);
+/// ```
+pub fn envelope_bp(points: &[(f64, f64, f64)]) -> An<Breakpoints<f64>> {
+    An(Breakpoints::new(points))
+}
+
+/// LFO with waveform `shape` at `rate` Hz. Random waveforms draw a new value for each cycle.
+/// The phase offset and polarity are set with `set_phase_offset` and `set_unipolar`.
+/// Setting: `LfoSetting`.
+/// - Input 0: retrigger. A rising edge restarts the cycle from the phase offset.
+/// - Output 0: LFO in -1...1, or 0...1 if unipolar.
+///
+/// ### Example: Sample And Hold Retriggered By A Gate
+/// ```
+/// use fundsp::hacker::*;
+/// let gate = shared(0.0);
+/// let mut lfo = lfo_wave(LfoShape::SampleHold, 8.0);
+/// lfo.set_unipolar(true);
+/// let node = var(&gate) >> lfo;
+/// ```
+pub fn lfo_wave(shape: LfoShape, rate: f64) -> An<LfoWave<f64>> {
+    An(LfoWave::new(shape, rate))
+}
+
 /// ADSR envelope.
 ///
 /// When a positive value is given by the input, its output increases from 0.0 to 1.0 in the time
@@ -827,6 +1342,73 @@ pub fn white() -> An<Noise<f64>> {
     An(Noise::new())
 }
 
+/// White noise generator with an explicit `seed`.
+/// The seed overrides any seed derived from the graph structure, making renders reproducible.
+/// - Output 0: white noise.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let mut noise1 = white_seed(1);
+/// let mut noise2 = white_seed(1) >> pass();
+/// assert_eq!(noise1.get_mono(), noise2.get_mono());
+/// ```
+pub fn white_seed(seed: u64) -> An<Noise<f64>> {
+    An(Noise::with_seed(seed))
+}
+
+/// Noise generator with an explicit `seed`. Synonym of `white_seed`.
+/// - Output 0: white noise.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let mut noise1 = noise_seed(7);
+/// let mut noise2 = noise_seed(7);
+/// assert_eq!(noise1.get_mono(), noise2.get_mono());
+/// ```
+pub fn noise_seed(seed: u64) -> An<Noise<f64>> {
+    An(Noise::with_seed(seed))
+}
+
+/// Velvet noise generator with `density` impulses per second (`density` > 0).
+/// Velvet noise is a sparse sequence of -1 and 1 valued impulses, one at a random position
+/// in each grid period. It is useful for decorrelation and artificial reverberation.
+/// Setting: density.
+/// - Output 0: velvet noise.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// velvet(2000.0);
+/// ```
+pub fn velvet(density: f64) -> An<Velvet<f64>> {
+    An(Velvet::new(density, None))
+}
+
+/// Velvet noise generator with `density` impulses per second (`density` > 0)
+/// and an explicit `seed`.
+/// Setting: density.
+/// - Output 0: velvet noise.
+pub fn velvet_seed(density: f64, seed: u64) -> An<Velvet<f64>> {
+    An(Velvet::new(density, Some(seed)))
+}
+
+/// Bounded random walk with speed `rate` (`rate` >= 0) and range `range` (`range` > 0).
+/// The output wanders within -range...range, reflecting at the bounds.
+/// After `t` seconds, the standard deviation of the position is about `range * rate * sqrt(t)`.
+/// Setting: (rate, range).
+/// - Output 0: random walk.
+///
+/// ### Example: Wandering Pitch
+/// ```
+/// use fundsp::hacker::*;
+/// random_walk(0.5, 20.0) + 440.0 >> sine();
+/// ```
+pub fn random_walk(rate: f64, range: f64) -> An<RandomWalk<f64>> {
+    An(RandomWalk::new(rate, range))
+}
+
 /// Sample-and-hold component. Sampling frequency `variability` is in 0...1.
 /// - Input 0: signal.
 /// - Input 1: sampling frequency (Hz).
@@ -851,63 +1433,244 @@ pub fn hold_hz(
     (pass() | dc(f)) >> hold(variability)
 }
 
-/// FIR filter.
+/// Sample-and-hold at fixed sampling frequency `f` Hz.
+/// Any input signal can be sampled, including control signals.
 /// - Input 0: signal.
-/// - Output 0: filtered signal.
+/// - Output 0: sampled signal.
 ///
-/// ### Example: 3-Point Lowpass Filter
+/// ### Example: Stepped Random Melody
 /// ```
 /// use fundsp::hacker::*;
-/// fir((0.5, 1.0, 0.5));
+/// lorenz_hz(0.3) >> sample_hold(4.0) * 200.0 + 400.0 >> sine();
 /// ```
-pub fn fir<X: ConstantFrame<Sample = f64>>(weights: X) -> An<Fir<f64, X::Size>> {
-    An(Fir::new(weights))
+pub fn sample_hold(f: f64) -> An<Pipe<f64, Stack<f64, Pass<f64>, Constant<U1, f64>>, Hold<f64>>> {
+    (pass() | dc(f)) >> hold(0.0)
 }
 
-/// Create a 3-point symmetric FIR from desired `gain` (`gain` >= 0) at the Nyquist frequency.
-/// Results in a monotonic low-pass filter when `gain` < 1.
+/// Bitcrusher. Quantizes the signal to `bits` bits (`bits` >= 1).
+/// The number of bits may be fractional.
 /// - Input 0: signal.
-/// - Output 0: filtered signal.
-pub fn fir3(gain: f64) -> Fir<f64, U3> {
-    super::prelude::fir3(gain)
+/// - Output 0: quantized signal.
+///
+/// ### Example: Crushed Noise
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> bitcrush(4.0);
+/// ```
+pub fn bitcrush(bits: f64) -> An<Bitcrush<f64, U1>> {
+    super::prelude::bitcrush(bits)
 }
 
-/// Single sample delay.
+/// Bitcrusher with the number of bits (`bits` >= 1) supplied as input.
+/// The number of bits may be fractional, which allows smooth modulation.
 /// - Input 0: signal.
-/// - Output 0: delayed signal.
+/// - Input 1: number of bits.
+/// - Output 0: quantized signal.
 ///
-/// ### Example: 2-Point Sum Filter
+/// ### Example: Sweeping Bit Depth
 /// ```
 /// use fundsp::hacker::*;
-/// tick() & pass();
+/// (pink() | lfo(|t| 12.0 - 2.0 * t)) >> bitcrusher();
 /// ```
-pub fn tick() -> An<Tick<U1, f64>> {
-    An(Tick::new())
+pub fn bitcrusher() -> An<Bitcrush<f64, U2>> {
+    super::prelude::bitcrusher()
 }
 
-/// Multichannel single sample delay.
-/// - Inputs: signal.
-/// - Outputs: delayed signal.
+/// Sample rate reducer. Samples and holds the signal at `hold_rate` Hz.
+/// - Input 0: signal.
+/// - Output 0: decimated signal.
 ///
-/// ### Example
+/// ### Example: Lo-Fi Noise
 /// ```
 /// use fundsp::hacker::*;
-/// multitick::<U2>();
+/// noise() >> decimate(4000.0);
 /// ```
-pub fn multitick<N: Size<f64>>() -> An<Tick<N, f64>> {
-    An(Tick::new())
+pub fn decimate(
+    hold_rate: f64,
+) -> An<Pipe<f64, Stack<f64, Pass<f64>, Constant<U1, f64>>, Hold<f64>>> {
+    hold_hz(hold_rate, 0.0)
 }
 
-/// Fixed delay of `t` seconds.
-/// Delay time is rounded to the nearest sample. The minimum delay is one sample.
-/// - Allocates: the delay line.
+/// Sample rate reducer with the hold rate supplied as input.
+/// The hold rate can be modulated smoothly.
 /// - Input 0: signal.
-/// - Output 0: delayed signal.
+/// - Input 1: hold rate (Hz).
+/// - Output 0: decimated signal.
 ///
-/// ### Example
+/// ### Example: Sweeping Hold Rate
 /// ```
 /// use fundsp::hacker::*;
-/// delay(1.0);
+/// (noise() | lfo(|t| xerp(8000.0, 1000.0, t * 0.1))) >> decimator();
+/// ```
+pub fn decimator() -> An<Hold<f64>> {
+    hold(0.0)
+}
+
+/// FIR filter.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: 3-Point Lowpass Filter
+/// ```
+/// use fundsp::hacker::*;
+/// fir((0.5, 1.0, 0.5));
+/// ```
+pub fn fir<X: ConstantFrame<Sample = f64>>(weights: X) -> An<Fir<f64, X::Size>> {
+    An(Fir::new(weights))
+}
+
+/// Create a 3-point symmetric FIR from desired `gain` (`gain` >= 0) at the Nyquist frequency.
+/// Results in a monotonic low-pass filter when `gain` < 1.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+pub fn fir3(gain: f64) -> Fir<f64, U3> {
+    super::prelude::fir3(gain)
+}
+
+/// FIR filter with coefficients `b` supplied at runtime, for example, from a filter design tool.
+/// Coefficient `b[k]` is applied to the input delayed by `k` samples, as in MATLAB and SciPy.
+/// Coefficients can be designed with `sinc_lowpass`, `sinc_highpass`, `sinc_bandpass` and `sinc_bandstop`.
+/// Setting: coefficients.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Windowed Sinc Lowpass Filter
+/// ```
+/// use fundsp::hacker::*;
+/// fir_coefs(&sinc_lowpass(63, 44100.0, 1000.0, FirWindow::Hamming));
+/// ```
+pub fn fir_coefs(b: &[f64]) -> An<FirFilter<f64>> {
+    super::prelude::fir_coefs(b)
+}
+
+/// IIR filter with feedforward coefficients `b` and feedback coefficients `a` supplied at runtime,
+/// as in MATLAB and SciPy. Coefficients are normalized by `a[0]`.
+/// Setting: (`b`, `a`).
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: One-Pole Lowpass Filter
+/// ```
+/// use fundsp::hacker::*;
+/// iir(&[0.1], &[1.0, -0.9]);
+/// ```
+pub fn iir(b: &[f64], a: &[f64]) -> An<IirFilter<f64>> {
+    super::prelude::iir(b, a)
+}
+
+/// Lowpass IIR filter of the given `order` (`order` > 0) designed from an analog `prototype`
+/// (Butterworth, Chebyshev I, Chebyshev II or elliptic) and realized as a cascade of biquad sections.
+/// The meaning of the `cutoff` frequency in Hz depends on the prototype; see `FilterPrototype`.
+/// The cutoff can be retuned at runtime without allocating.
+/// Setting: cutoff.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: 8th Order Elliptic Lowpass
+/// ```
+/// use fundsp::hacker::*;
+/// iir_lowpass(FilterPrototype::Elliptic(0.5, 60.0), 8, 1000.0);
+/// ```
+pub fn iir_lowpass(prototype: FilterPrototype, order: usize, cutoff: f64) -> An<IirCascade<f64>> {
+    super::prelude::iir_lowpass(prototype, order, cutoff)
+}
+
+/// Highpass IIR filter of the given `order` (`order` > 0) designed from an analog `prototype`
+/// (Butterworth, Chebyshev I, Chebyshev II or elliptic) and realized as a cascade of biquad sections.
+/// The meaning of the `cutoff` frequency in Hz depends on the prototype; see `FilterPrototype`.
+/// The cutoff can be retuned at runtime without allocating.
+/// Setting: cutoff.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: 8th Order Elliptic Highpass
+/// ```
+/// use fundsp::hacker::*;
+/// iir_highpass(FilterPrototype::Elliptic(0.5, 60.0), 8, 1000.0);
+/// ```
+pub fn iir_highpass(prototype: FilterPrototype, order: usize, cutoff: f64) -> An<IirCascade<f64>> {
+    super::prelude::iir_highpass(prototype, order, cutoff)
+}
+
+/// Linear phase FIR filter with coefficients `b` implemented with FFT convolution,
+/// for example, for mastering equalization. The coefficients should be symmetric,
+/// as from `fir_design` or `sinc_lowpass`. The latency is reported to signal flow analysis,
+/// so dry paths can be aligned with `compensate`. The latency is the number of taps rounded up
+/// to a power of two (between 16 and 512) plus `(taps - 1) / 2` samples.
+/// Setting: coefficients (the number of taps may not change).
+/// - Allocates: everything.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Linear Phase Treble Boost
+/// ```
+/// use fundsp::hacker::*;
+/// let b = fir_design(1023, 44100.0, |f| if f > 8000.0 { 2.0 } else { 1.0 }, FirWindow::Hann);
+/// linear_phase(&b);
+/// ```
+#[cfg(feature = "std")]
+pub fn linear_phase(b: &[f64]) -> An<LinearPhase<f64>> {
+    super::prelude::linear_phase(b)
+}
+
+/// Convolver that switches between impulse responses in `library`, for example, guitar cabinets,
+/// with a crossfade of `fade_time` seconds. Starts from the first impulse response.
+/// The latency is the block length of the library.
+/// Setting: impulse response index.
+/// - Allocates: everything.
+/// - Input 0: signal.
+/// - Output 0: convolved signal.
+///
+/// ### Example: Switch Between Two Cabinets
+/// ```
+/// use fundsp::hacker::*;
+/// let mut library = ImpulseLibrary::new(256);
+/// library.add(&[1.0, 0.5, 0.25]);
+/// library.add(&[0.5, -0.25]);
+/// let mut node = ir_switch(&std::sync::Arc::new(library), 0.05);
+/// node.set(1);
+/// ```
+#[cfg(feature = "std")]
+pub fn ir_switch(library: &Arc<ImpulseLibrary>, fade_time: f64) -> An<IrSwitch<f64>> {
+    super::prelude::ir_switch(library, fade_time)
+}
+
+/// Single sample delay.
+/// - Input 0: signal.
+/// - Output 0: delayed signal.
+///
+/// ### Example: 2-Point Sum Filter
+/// ```
+/// use fundsp::hacker::*;
+/// tick() & pass();
+/// ```
+pub fn tick() -> An<Tick<U1, f64>> {
+    An(Tick::new())
+}
+
+/// Multichannel single sample delay.
+/// - Inputs: signal.
+/// - Outputs: delayed signal.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// multitick::<U2>();
+/// ```
+pub fn multitick<N: Size<f64>>() -> An<Tick<N, f64>> {
+    An(Tick::new())
+}
+
+/// Fixed delay of `t` seconds.
+/// Delay time is rounded to the nearest sample. The minimum delay is one sample.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: delayed signal.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// delay(1.0);
 /// ```
 pub fn delay(t: f64) -> An<Delay<f64>> {
     An(Delay::new(t))
@@ -987,6 +1750,209 @@ where
     An(TapLinear::new(min_delay, max_delay))
 }
 
+/// Variable delay line with selectable `interpolation` (`Interpolation`).
+/// Minimum and maximum delay times are in seconds.
+/// Setting: interpolation method.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: delay time in seconds.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Sinc Interpolated Chorus Voice
+/// ```
+/// use fundsp::hacker::*;
+/// pass() & (pass() | lfo(|t| lerp11(0.01, 0.02, sin_hz(0.5, t)))) >> frac_delay(Interpolation::Sinc, 0.01, 0.02);
+/// ```
+pub fn frac_delay(interpolation: Interpolation, min_delay: f64, max_delay: f64) -> An<FracDelay<f64>> {
+    An(FracDelay::new(interpolation, min_delay, max_delay, 0.0))
+}
+
+/// Modulation safe variable delay line with selectable `interpolation` (`Interpolation`).
+/// Abrupt changes in delay time crossfade over 10 ms instead of clicking.
+/// Minimum and maximum delay times are in seconds.
+/// Setting: interpolation method.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: delay time in seconds.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Stepped Delay Time
+/// ```
+/// use fundsp::hacker::*;
+/// pass() & (pass() | lfo(|t| if t < 1.0 { 0.1 } else { 0.2 })) >> frac_delay_smooth(Interpolation::Cubic, 0.1, 0.2);
+/// ```
+pub fn frac_delay_smooth(
+    interpolation: Interpolation,
+    min_delay: f64,
+    max_delay: f64,
+) -> An<FracDelay<f64>> {
+    An(FracDelay::new(interpolation, min_delay, max_delay, 0.01))
+}
+
+/// Feedforward comb filter `y = x + gain * x[n - d]` with delay `d` of `time` seconds.
+/// The delay is cubic interpolated.
+/// Setting: (delay in seconds, gain).
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Flanger Notches At 1 kHz Intervals
+/// ```
+/// use fundsp::hacker::*;
+/// let node = comb_ff(0.001, -1.0);
+/// ```
+pub fn comb_ff(time: f64, gain: f64) -> An<Comb<f64, U1>> {
+    An(Comb::new(false, time, time, gain))
+}
+
+/// Feedback comb filter `y = x + gain * y[n - d]` with delay `d` of `time` seconds.
+/// The delay is cubic interpolated. The filter is stable for `gain` within -1...1.
+/// Setting: (delay in seconds, gain).
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Metallic Resonance
+/// ```
+/// use fundsp::hacker::*;
+/// let node = comb_fb(0.005, 0.9) * 0.1;
+/// ```
+pub fn comb_fb(time: f64, gain: f64) -> An<Comb<f64, U1>> {
+    An(Comb::new(true, time, time, gain))
+}
+
+/// Feedback comb filter tuned to resonate at `frequency` Hz with feedback `gain` in -1...1.
+/// The delay is cubic interpolated.
+/// Setting: (delay in seconds, gain).
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Karplus-Strong Style Resonator
+/// ```
+/// use fundsp::hacker::*;
+/// let string = noise() * 0.1 >> comb_hz(220.0, 0.99);
+/// ```
+pub fn comb_hz(frequency: f64, gain: f64) -> An<Comb<f64, U1>> {
+    An(Comb::new(true, 1.0 / frequency, 1.0 / frequency, gain))
+}
+
+/// Tunable feedback comb filter resonating at frequencies down to `min_frequency` Hz.
+/// The delay is cubic interpolated.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: frequency in Hz (the reciprocal of the delay).
+/// - Input 2: feedback gain in -1...1.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Swept Resonance
+/// ```
+/// use fundsp::hacker::*;
+/// let node = (noise() * 0.1 | lfo(|t| 200.0 + 100.0 * t) | dc(0.95)) >> comb(50.0);
+/// ```
+pub fn comb(min_frequency: f64) -> An<Comb<f64, U3>> {
+    An(Comb::new(true, 1.0 / min_frequency, 0.0, 0.0))
+}
+
+/// Tempo synced delay of `beats` beats at the tempo of `transport`.
+/// When the tempo changes, delay time glides smoothly to the new value.
+/// Setting: delay in beats.
+/// - Allocates: the delay line, with room for `beats` beats at `MIN_TEMPO`.
+/// - Input 0: signal.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Dotted Eighth Echo
+/// ```
+/// use fundsp::hacker::*;
+/// let transport = Transport::new(120.0);
+/// let echo = pass() & 0.5 * delay_sync(&transport, 0.75);
+/// transport.set_tempo(128.0);
+/// ```
+pub fn delay_sync(transport: &Transport, beats: f64) -> An<DelaySync<f64>> {
+    An(DelaySync::new(transport, beats))
+}
+
+/// Tempo synced LFO with a cycle of `beats` beats at the tempo of `transport`.
+/// Setting: waveform (`LfoShape`).
+/// - Output 0: LFO in -1...1.
+///
+/// ### Example: Four Beat Filter Sweep
+/// ```
+/// use fundsp::hacker::*;
+/// let transport = Transport::new(120.0);
+/// let sweep = lfo_sync(&transport, 4.0, LfoShape::Triangle) * 500.0 + 1000.0;
+/// ```
+pub fn lfo_sync(transport: &Transport, beats: f64, shape: LfoShape) -> An<LfoSync<f64>> {
+    An(LfoSync::new(transport, beats, shape))
+}
+
+/// Auto-panner. Pans a mono signal back and forth at `rate` Hz with LFO waveform `shape`.
+/// Pan `depth` is in 0...1, where 1 sweeps fully from left to right.
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Slow Pad Sweep
+/// ```
+/// use fundsp::hacker::*;
+/// saw_hz(110.0) >> autopan(0.25, 0.8, LfoShape::Sine);
+/// ```
+pub fn autopan(rate: f64, depth: f64, shape: LfoShape) -> An<AutoPan<f64>> {
+    super::prelude::autopan(rate, depth, shape)
+}
+
+/// Tempo synced auto-panner with a cycle of `beats` beats at the tempo of `transport`.
+/// Pan `depth` is in 0...1. Setting: (beats, depth).
+/// - Input 0: signal
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Pan Once Per Bar
+/// ```
+/// use fundsp::hacker::*;
+/// let transport = Transport::new(120.0);
+/// saw_hz(110.0) >> autopan_sync(&transport, 4.0, 1.0, LfoShape::Triangle);
+/// ```
+pub fn autopan_sync(
+    transport: &Transport,
+    beats: f64,
+    depth: f64,
+    shape: LfoShape,
+) -> An<AutoPan<f64>> {
+    super::prelude::autopan_sync(transport, beats, depth, shape)
+}
+
+/// Tremolo. Modulates amplitude with a sine LFO at `rate` Hz.
+/// The gain swings between 1 and 1 - `depth` (`depth` in 0...1).
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: modulated signal
+///
+/// ### Example: Electric Piano Tremolo
+/// ```
+/// use fundsp::hacker::*;
+/// sine_hz(220.0) >> tremolo(5.0, 0.5);
+/// ```
+pub fn tremolo(rate: f64, depth: f64) -> An<Tremolo<f64>> {
+    super::prelude::tremolo(rate, depth)
+}
+
+/// Tempo synced tremolo with a cycle of `beats` beats at the tempo of `transport`.
+/// The gain swings between 1 and 1 - `depth` (`depth` in 0...1). Setting: (beats, depth).
+/// - Input 0: signal
+/// - Output 0: modulated signal
+///
+/// ### Example: Sixteenth Note Tremolo
+/// ```
+/// use fundsp::hacker::*;
+/// let transport = Transport::new(120.0);
+/// sine_hz(220.0) >> tremolo_sync(&transport, 0.25, 1.0);
+/// ```
+pub fn tremolo_sync(transport: &Transport, beats: f64, depth: f64) -> An<Tremolo<f64>> {
+    super::prelude::tremolo_sync(transport, beats, depth)
+}
+
 /// 2x oversample enclosed `node`.
 /// - Inputs and outputs: from `node`.
 ///
@@ -1047,6 +2013,32 @@ where
     An(Feedback::new(node.0, FrameId::new()))
 }
 
+/// Mix output of enclosed circuit `node` back to its input through a loop delay
+/// of `delay` seconds, scaled by a feedback gain input and processed with stability `guard`.
+/// The latency of `node` counts toward the loop delay, and the loop always contains
+/// at least one sample of delay, so `node` may introduce latency.
+/// Setting: guard.
+/// - Input(s): signal, followed by feedback gain.
+/// - Output(s): signal with feedback.
+///
+/// ### Example: Guarded Feedback Delay
+/// ```
+/// use fundsp::hacker::*;
+/// (pass() | dc(1.1)) >> feedback_loop(0.5, LoopGuard::Clip(1.0), lowpass_hz(2000.0, 1.0));
+/// ```
+pub fn feedback_loop<N, X>(
+    delay: f64,
+    guard: LoopGuard,
+    node: An<X>,
+) -> An<FeedbackLoop<N, f64, X>>
+where
+    N: Size<f64> + Add<U1>,
+    X: AudioNode<Sample = f64, Inputs = N, Outputs = N>,
+    <N as Add<U1>>::Output: Size<f64>,
+{
+    An(FeedbackLoop::new(delay, guard, node.0))
+}
+
 /// Mix output of enclosed circuit `node` back to its input
 /// with extra `loopback` feedback loop processing.
 /// Feedback circuits `node` and `loopback` must have an equal number of inputs and outputs.
@@ -1178,6 +2170,18 @@ pub fn declick_s(t: f64) -> An<Declick<f64, f64>> {
     super::prelude::declick_s(t)
 }
 
+/// Click and crackle repairer with `sensitivity` in 0...1. Detects clicks as outliers
+/// in the prediction error of the signal and interpolates over them.
+/// Sensitivity 0 repairs only strong clicks, 1 repairs subtle ones.
+/// Disturbances longer than 32 samples are left alone. The latency is 64 samples.
+/// For offline repair of waves, see `Wave64::repair_clicks`.
+/// Setting: sensitivity.
+/// - Input 0: signal
+/// - Output 0: repaired signal
+pub fn click_repair(sensitivity: f64) -> An<ClickRepair<f64>> {
+    super::prelude::click_repair(sensitivity)
+}
+
 /// Shape signal with a waveshaper function.
 /// - Input 0: input signal
 /// - Output 0: shaped signal
@@ -1198,6 +2202,58 @@ pub fn shape(mode: Shape<f64>) -> An<Shaper<f64>> {
     super::prelude::shape(mode)
 }
 
+/// Shape signal with a lookup table.
+/// The table spans input values -1...1 and is linearly interpolated.
+/// Input values outside the range are clamped. The table must contain at least two values.
+/// - Input 0: input signal
+/// - Output 0: shaped signal
+///
+/// ### Example: Soft Knee
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = shape_table(&[-1.0, -0.9, 0.0, 0.9, 1.0]);
+/// assert_eq!(node.filter_mono(0.25), 0.45);
+/// ```
+pub fn shape_table(table: &[f64]) -> An<ShaperTable<f64>> {
+    super::prelude::shape_table(table)
+}
+
+/// Run waveshaper `node` at 4x oversampling to reduce aliasing
+/// and remove any DC offset introduced by an asymmetric curve.
+/// - Input 0: input signal
+/// - Output 0: shaped signal
+///
+/// ### Example: Clean Tube Saturation
+/// ```
+/// use fundsp::hacker::*;
+/// antialias(shape(Shape::Tube(4.0)));
+/// ```
+pub fn antialias<X>(node: An<X>) -> An<Pipe<f64, Oversampler<f64, Oversampler<f64, X>>, DCBlock<f64, f64>>>
+where
+    X: AudioNode<Sample = f64, Inputs = U1, Outputs = U1>,
+{
+    super::prelude::antialias(node)
+}
+
+/// Saturate signal with an analog-inspired `model` and input gain `drive` (`drive` > 0).
+/// The curve runs at 4x oversampling to reduce aliasing, followed by a DC blocker.
+/// Output gain is compensated automatically so that loudness does not jump with drive.
+/// Setting: `Side::Left(drive)`.
+/// - Input 0: input signal
+/// - Output 0: saturated signal
+///
+/// ### Example: Tape Saturation
+/// ```
+/// use fundsp::hacker::*;
+/// saturate(SaturationModel::Tape, 4.0);
+/// ```
+pub fn saturate(
+    model: SaturationModel,
+    drive: f64,
+) -> An<Pipe<f64, Oversampler<f64, Oversampler<f64, Saturator<f64>>>, DCBlock<f64, f64>>> {
+    super::prelude::saturate(model, drive)
+}
+
 /// Clip signal to -1...1.
 /// - Input 0: input signal
 /// - Output 0: clipped signal
@@ -1275,6 +2331,133 @@ pub fn limiter_stereo<S: ScalarOrPair<Sample = f64>>(time: S) -> An<Limiter<f64,
     An(Limiter::new(DEFAULT_SR, time))
 }
 
+/// True peak look-ahead limiter. Limits true peaks, including peaks between samples,
+/// to `ceiling` amplitude. Look-ahead and release times are in seconds.
+/// Release is program-dependent: faster after brief peaks and slower under sustained limiting.
+/// Current gain reduction in dB is written to `reduction`.
+/// Setting: ceiling amplitude.
+/// - Allocates: look-ahead buffers.
+/// - Input 0: signal
+/// - Output 0: limited signal
+///
+/// ### Example: Limit to -1 dBTP
+/// ```
+/// use fundsp::hacker::*;
+/// let reduction = shared(0.0);
+/// saw() >> limiter_true_peak(db_amp(-1.0), 0.005, 0.1, &reduction);
+/// ```
+pub fn limiter_true_peak(
+    ceiling: f64,
+    lookahead: f64,
+    release: f64,
+    reduction: &Shared<f64>,
+) -> An<TruePeakLimiter<f64, U1>> {
+    An(TruePeakLimiter::new(DEFAULT_SR, ceiling, lookahead, release, reduction))
+}
+
+/// Stereo true peak look-ahead limiter. Limits true peaks, including peaks between samples,
+/// to `ceiling` amplitude. Both channels receive the same gain reduction.
+/// Look-ahead and release times are in seconds.
+/// Release is program-dependent: faster after brief peaks and slower under sustained limiting.
+/// Current gain reduction in dB is written to `reduction`.
+/// Setting: ceiling amplitude.
+/// - Allocates: look-ahead buffers.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: limited left signal
+/// - Output 1: limited right signal
+pub fn limiter_true_peak_stereo(
+    ceiling: f64,
+    lookahead: f64,
+    release: f64,
+    reduction: &Shared<f64>,
+) -> An<TruePeakLimiter<f64, U2>> {
+    An(TruePeakLimiter::new(DEFAULT_SR, ceiling, lookahead, release, reduction))
+}
+
+/// Sidechain ducker. Attenuates the signal by `amount` (in 0...1) while the level of the key
+/// is above amplitude `threshold`. Ducking is gradual up to 6 dB above the threshold.
+/// The key level follows with halfway response times `attack` and `release` seconds.
+/// Setting: (threshold, amount).
+/// - Input 0: signal
+/// - Input 1: key
+/// - Output 0: ducked signal
+///
+/// ### Example: Duck Music Under Dialogue
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() | noise()) >> ducker(0.1, 0.75, 0.01, 0.3);
+/// ```
+pub fn ducker(threshold: f64, amount: f64, attack: f64, release: f64) -> An<Ducker<f64>> {
+    An(Ducker::new(DEFAULT_SR, threshold, amount, attack, release))
+}
+
+/// Transient shaper for drum processing. Splits the signal into attack and sustain portions
+/// using a fast and a slow envelope follower and applies independent gain amplitudes to them.
+/// For example, `transient(2.0, 0.5)` boosts attacks by up to 6 dB and cuts sustain by up to 6 dB.
+/// Setting: (attack gain, sustain gain).
+/// - Input 0: signal
+/// - Output 0: shaped signal
+///
+/// ### Example: Punchier Drums
+/// ```
+/// use fundsp::hacker::*;
+/// noise() >> transient(2.0, 0.5);
+/// ```
+pub fn transient(attack_gain: f64, sustain_gain: f64) -> An<TransientShaper<f64>> {
+    An(TransientShaper::new(DEFAULT_SR, attack_gain, sustain_gain))
+}
+
+/// De-esser. Compresses only the band above `frequency` Hz, where sibilance lives,
+/// when its level is above amplitude `threshold`. The compression ratio is `ratio` (`ratio` >= 1).
+/// The band is split off with a complementary crossover, so the signal passes unchanged
+/// while the band stays below the threshold.
+/// Setting: (frequency, threshold, ratio).
+/// - Input 0: signal
+/// - Output 0: de-essed signal
+///
+/// ### Example: Tame Sibilance
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() * 0.5) >> deesser(5000.0, 0.05, 4.0);
+/// ```
+pub fn deesser(frequency: f64, threshold: f64, ratio: f64) -> An<DeEsser<f64>> {
+    An(DeEsser::new(DEFAULT_SR, frequency, threshold, ratio))
+}
+
+/// Onset detector. Outputs an impulse (1.0) on the sample where an onset is detected
+/// and 0.0 otherwise. Onsets are detected from the rise of a fast envelope follower
+/// over a slow one. `sensitivity` is in 0...1: 0 detects only strong attacks,
+/// 1 detects subtle ones. The impulse output can drive `trigger` and `trigger_fn`.
+/// Setting: sensitivity.
+/// - Input 0: signal
+/// - Output 0: onset impulses
+///
+/// ### Example: Print Onset Times
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() * 0.5) >> onset(0.5) >> trigger_fn(0.5, |event| println!("onset at {} s", event.time));
+/// ```
+pub fn onset(sensitivity: f64) -> An<Onset<f64>> {
+    An(Onset::new(DEFAULT_SR, sensitivity))
+}
+
+/// Streaming tempo tracker for tempos between `min_bpm` and `max_bpm`.
+/// Estimates tempo from the autocorrelation of the onset strength curve
+/// of the latest 8 seconds of input. The estimate is updated twice per second.
+/// - Input 0: signal
+/// - Output 0: tempo estimate in BPM, or zero until an estimate is available
+///
+/// ### Example: Track Tempo Of A Pulse
+/// ```
+/// use fundsp::hacker::*;
+/// let pulse = lfo(|t: f64| if (t * 2.0) % 1.0 < 0.05 { 1.0 } else { 0.0 }) * sine_hz(440.0);
+/// pulse >> tempo_tracker(60.0, 180.0);
+/// ```
+pub fn tempo_tracker(min_bpm: f64, max_bpm: f64) -> An<TempoTracker<f64>> {
+    An(TempoTracker::new(DEFAULT_SR, min_bpm, max_bpm, 8.0))
+}
+
 /// Pinking filter.
 /// - Input 0: input signal
 /// - Output 0: filtered signal
@@ -1297,6 +2480,58 @@ pub fn brown() -> An<
     white() >> lowpole_hz(10.0) * dc(13.7)
 }
 
+/// Pink noise with an explicit `seed`.
+/// - Output 0: pink noise
+pub fn pink_seed(seed: u64) -> An<Pipe<f64, Noise<f64>, Pinkpass<f64, f64>>> {
+    super::prelude::pink_seed(seed)
+}
+
+/// Brown noise with an explicit `seed`.
+/// - Output 0: brown noise
+pub fn brown_seed(
+    seed: u64,
+) -> An<
+    Pipe<f64, Noise<f64>, Binop<f64, FrameMul<U1, f64>, Lowpole<f64, f64, U1>, Constant<U1, f64>>>,
+> {
+    super::prelude::brown_seed(seed)
+}
+
+/// Blue noise. Blue noise rises 3 dB per octave.
+/// - Output 0: blue noise
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// blue();
+/// ```
+pub fn blue() -> An<Pipe<f64, Pipe<f64, Noise<f64>, Pinkpass<f64, f64>>, Fir<f64, U2>>> {
+    super::prelude::blue()
+}
+
+/// Blue noise with an explicit `seed`.
+/// - Output 0: blue noise
+pub fn blue_seed(seed: u64) -> An<Pipe<f64, Pipe<f64, Noise<f64>, Pinkpass<f64, f64>>, Fir<f64, U2>>> {
+    super::prelude::blue_seed(seed)
+}
+
+/// Violet noise. Violet noise rises 6 dB per octave.
+/// - Output 0: violet noise in -1...1
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// violet();
+/// ```
+pub fn violet() -> An<Pipe<f64, Noise<f64>, Fir<f64, U2>>> {
+    super::prelude::violet()
+}
+
+/// Violet noise with an explicit `seed`.
+/// - Output 0: violet noise in -1...1
+pub fn violet_seed(seed: u64) -> An<Pipe<f64, Noise<f64>, Fir<f64, U2>>> {
+    super::prelude::violet_seed(seed)
+}
+
 /// Feedback delay network.
 /// Mix output of enclosed circuit `x` back to its input.
 /// The output is diffused with a Hadamard matrix for feedback.
@@ -1340,6 +2575,194 @@ where
     An(Feedback2::new(x.0, y.0, FrameHadamard::new()))
 }
 
+/// Mix together `x` and `y` sourcing from the same inputs.
+/// The branch with less latency is delayed to match the other,
+/// which keeps, for example, a dry signal aligned with a look-ahead limiter or `resynth`.
+/// Latencies are measured from signal flow analysis.
+/// - Allocates: compensating delay lines.
+/// - Input(s): from `x` and `y`.
+/// - Output(s): sum of `x` and `y`.
+///
+/// ### Example: Parallel Limiting
+/// ```
+/// use fundsp::hacker::*;
+/// compensate(pass(), limiter((0.01, 0.1)));
+/// ```
+pub fn compensate<X, Y>(x: An<X>, y: An<Y>) -> An<Compensate<f64, X, Y>>
+where
+    X: AudioNode<Sample = f64>,
+    Y: AudioNode<Sample = f64, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+    Y::Inputs: Size<f64>,
+    Y::Outputs: Size<f64>,
+{
+    An(Compensate::new(x.0, y.0))
+}
+
+/// Mix the output of `node` with its dry input by wet `amount` in 0...1.
+/// The dry path is delayed by the latency of `node` to keep the paths aligned.
+/// Changes are crossfaded over 10 ms.
+/// Setting: `WetSetting` (wet amount or bypass).
+/// - Allocates: dry delay lines.
+/// - Inputs and outputs: from `node`.
+///
+/// ### Example: Parallel Limiting
+/// ```
+/// use fundsp::hacker::*;
+/// let node = wet(0.5, limiter((0.01, 0.1)));
+/// assert_eq!(node.compensation(), 441);
+/// ```
+pub fn wet<N, X>(amount: f64, node: An<X>) -> An<Wet<N, f64, X>>
+where
+    X: AudioNode<Sample = f64, Inputs = N, Outputs = N>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+    N: Size<f64>,
+{
+    An(Wet::new(node.0, amount, false))
+}
+
+/// Fully wet `node` that can be bypassed with a click-free crossfade.
+/// The dry path is delayed by the latency of `node` to keep the paths aligned.
+/// Setting: `WetSetting` (bypass or wet amount).
+/// - Allocates: dry delay lines.
+/// - Inputs and outputs: from `node`.
+///
+/// ### Example: Bypassed Filter
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = bypass(lowpass_hz(1000.0, 1.0));
+/// node.set(WetSetting::Bypass(true));
+/// ```
+pub fn bypass<N, X>(node: An<X>) -> An<Wet<N, f64, X>>
+where
+    X: AudioNode<Sample = f64, Inputs = N, Outputs = N>,
+    X::Inputs: Size<f64>,
+    X::Outputs: Size<f64>,
+    N: Size<f64>,
+{
+    An(Wet::new(node.0, 1.0, false))
+}
+
+/// Process `x` with denormals flushed to zero.
+/// Recursive structures such as reverbs and filters produce denormals
+/// as their state decays, which can be very slow to process.
+/// Has no effect on platforms without flush-to-zero support.
+/// The mode is set once per block in `process` and once per sample in `tick`;
+/// holding a `DenormalGuard` around a block of ticks makes them cheaper.
+/// - Input(s): from `x`.
+/// - Output(s): from `x`.
+///
+/// ### Example: Reverb Tail
+/// ```
+/// use fundsp::hacker::*;
+/// let node = flush_denormals(reverb_stereo(20.0, 3.0, 0.5));
+/// ```
+pub fn flush_denormals<X: AudioNode<Sample = f64>>(x: An<X>) -> An<FlushDenormals<X>> {
+    An(FlushDenormals::new(x.0))
+}
+/// Equal-power crossfade between nodes `x` and `y`, which receive the same input.
+/// The last input is the crossfade amount in 0...1: 0 selects `x` and 1 selects `y`.
+/// - Inputs: from `x` and `y`, followed by crossfade amount
+/// - Outputs: crossfaded outputs of `x` and `y`
+///
+/// ### Example: Morph Saw to Square
+/// ```
+/// use fundsp::hacker::*;
+/// (dc((110.0, 0.5)) >> xfade(saw(), square())).get_mono();
+/// ```
+pub fn xfade<X, Y>(x: An<X>, y: An<Y>) -> An<XFade<f64, X, Y>>
+where
+    X: AudioNode<Sample = f64>,
+    Y: AudioNode<Sample = f64, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<f64> + Add<U1>,
+    X::Outputs: Size<f64>,
+    <X::Inputs as Add<U1>>::Output: Size<f64>,
+{
+    An(XFade::new(x.0, y.0))
+}
+
+/// Matrix mixer from `N` inputs to `M` outputs. Gain changes are smoothed
+/// with a time constant of `smoothing` seconds. Initially, input `i`
+/// is routed to output `i` with unity gain.
+/// Setting: (output, input, gain).
+/// - Inputs: `N` signals
+/// - Outputs: `M` mixes
+///
+/// ### Example: Route Three Sources to Stereo
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = matrix_mixer::<U3, U2>(0.01);
+/// node.set_gain(0, 2, 0.5);
+/// node.set_gain(1, 2, 0.5);
+/// ```
+pub fn matrix_mixer<N: Size<f64>, M: Size<f64>>(smoothing: f64) -> An<MatrixMixer<N, M, f64>> {
+    An(MatrixMixer::new(smoothing))
+}
+
+/// Gain of `db` decibels. Gain changes are smoothed with a time constant of 5 ms.
+/// Gains at or below `MUTE_DB`, such as `f64::NEG_INFINITY`, mute the signal.
+/// Setting: gain in dB.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+///
+/// ### Example: Fade Out
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = gain_db(-6.0);
+/// node.set(f64::NEG_INFINITY);
+/// ```
+pub fn gain_db(db: f64) -> An<Gain<f64>> {
+    An(Gain::new(
+        db,
+        f64::NEG_INFINITY,
+        f64::INFINITY,
+        0.005,
+        "gain",
+    ))
+}
+
+/// Input trim of `db` decibels, limited to -24...24 dB.
+/// Trim changes are smoothed with a time constant of 5 ms.
+/// Setting: trim in dB.
+/// - Input 0: signal
+/// - Output 0: trimmed signal
+///
+/// ### Example: Trim Before Saturation
+/// ```
+/// use fundsp::hacker::*;
+/// trim_db(6.0) >> shape(Shape::Tanh(1.0));
+/// ```
+pub fn trim_db(db: f64) -> An<Gain<f64>> {
+    An(Gain::new(db, -24.0, 24.0, 0.005, "trim"))
+}
+
+/// Fader that follows `shared` gain in decibels, limited to 12 dB at most.
+/// Gain changes are smoothed with a time constant of `smoothing` seconds.
+/// Gains at or below `MUTE_DB`, such as negative infinity, mute the signal.
+/// Setting: gain in dB, which is also stored in `shared`.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+///
+/// ### Example: Channel Fader
+/// ```
+/// use fundsp::hacker::*;
+/// let level = shared(0.0);
+/// let channel = saw_hz(110.0) >> fader(&level, 0.01);
+/// level.set(-12.0);
+/// ```
+pub fn fader(shared: &Shared<f64>, smoothing: f64) -> An<Gain<f64>> {
+    An(Gain::new_shared(
+        shared,
+        f64::NEG_INFINITY,
+        12.0,
+        smoothing,
+        "fader",
+    ))
+}
+
+
 /// Bus `N` similar nodes from indexed generator `f`.
 /// - Input(s): from `f`.
 /// - Output(s): from `f`.
@@ -1626,6 +3049,7 @@ pub fn reverb2_stereo(
 /// use fundsp::hacker::*;
 /// multipass() & 0.25 * reverb3_stereo(2.0, 0.5, lowpole_hz(8000.0));
 /// ```
+#[cfg(feature = "std")]
 pub fn reverb3_stereo(
     time: f64,
     diffusion: f64,
@@ -1636,6 +3060,31 @@ pub fn reverb3_stereo(
     ))
 }
 
+/// Diffuser: a cascade of `stages` (`stages` > 0) Schroeder allpasses with randomized,
+/// mutually prime delay lengths of at most `time_spread` seconds chosen from `seed`.
+/// Smears transients without coloring the spectrum. A building block for reverbs.
+/// Setting: feedforward coefficient of the allpasses (default 0.5).
+/// - Allocates: delay lines
+/// - Input 0: input signal
+/// - Output 0: diffused signal
+///
+/// ### Example: Diffused Echo
+/// ```
+/// use fundsp::hacker::*;
+/// pass() & feedback(delay(0.25) >> diffuser(4, 0.02, 1) * db_amp(-6.0));
+/// ```
+#[cfg(feature = "std")]
+pub fn diffuser(stages: usize, time_spread: f64, seed: u64) -> An<super::reverb::Diffuser<f64>> {
+    An(super::reverb::Diffuser::new(stages, time_spread, seed))
+}
+
+/// Stereo reverb with a slow fade-in envelope.
+/// `room_size` is in meters (at least 15 meters).
+/// `time` is approximate reverberation time to -60 dB in seconds.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: reverberated left signal
+/// - Output 1: reverberated right signal
 pub fn reverb4_stereo(
     room_size: f64,
     time: f64,
@@ -1711,6 +3160,7 @@ pub fn pluck(frequency: f64, gain_per_second: f64, high_frequency_damping: f64)
 /// - Allocates: global saw wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: saw wave
+#[cfg(feature = "std")]
 pub fn saw() -> An<WaveSynth<'static, f64, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &SAW_TABLE))
 }
@@ -1719,6 +3169,7 @@ pub fn saw() -> An<WaveSynth<'static, f64, U1>> {
 /// - Allocates: global square wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: square wave
+#[cfg(feature = "std")]
 pub fn square() -> An<WaveSynth<'static, f64, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &SQUARE_TABLE))
 }
@@ -1727,6 +3178,7 @@ pub fn square() -> An<WaveSynth<'static, f64, U1>> {
 /// - Allocates: global triangle wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: triangle wave
+#[cfg(feature = "std")]
 pub fn triangle() -> An<WaveSynth<'static, f64, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &TRIANGLE_TABLE))
 }
@@ -1735,6 +3187,7 @@ pub fn triangle() -> An<WaveSynth<'static, f64, U1>> {
 /// - Allocates: global organ wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: organ wave
+#[cfg(feature = "std")]
 pub fn organ() -> An<WaveSynth<'static, f64, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &ORGAN_TABLE))
 }
@@ -1744,6 +3197,7 @@ pub fn organ() -> An<WaveSynth<'static, f64, U1>> {
 /// - Allocates: global soft saw wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: soft saw wave
+#[cfg(feature = "std")]
 pub fn soft_saw() -> An<WaveSynth<'static, f64, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &SOFT_SAW_TABLE))
 }
@@ -1752,6 +3206,7 @@ pub fn soft_saw() -> An<WaveSynth<'static, f64, U1>> {
 /// - Allocates: global Hammond wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: Hammond wave
+#[cfg(feature = "std")]
 pub fn hammond() -> An<WaveSynth<'static, f64, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &HAMMOND_TABLE))
 }
@@ -1759,6 +3214,7 @@ pub fn hammond() -> An<WaveSynth<'static, f64, U1>> {
 /// Fixed saw wavetable oscillator at `f` Hz.
 /// - Allocates: global saw wavetable.
 /// - Output 0: saw wave
+#[cfg(feature = "std")]
 pub fn saw_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64, U1>>> {
     super::prelude::saw_hz(f)
 }
@@ -1766,6 +3222,7 @@ pub fn saw_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64,
 /// Fixed square wavetable oscillator at `f` Hz.
 /// - Allocates: global square wavetable.
 /// - Output 0: square wave
+#[cfg(feature = "std")]
 pub fn square_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64, U1>>> {
     super::prelude::square_hz(f)
 }
@@ -1773,6 +3230,7 @@ pub fn square_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f
 /// Fixed triangle wavetable oscillator at `f` Hz.
 /// - Allocates: global triangle wavetable.
 /// - Output 0: triangle wave
+#[cfg(feature = "std")]
 pub fn triangle_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64, U1>>> {
     super::prelude::triangle_hz(f)
 }
@@ -1780,6 +3238,7 @@ pub fn triangle_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static,
 /// Fixed organ wavetable oscillator at `f` Hz. Emphasizes octave partials.
 /// - Allocates: global organ wavetable.
 /// - Output 0: organ wave
+#[cfg(feature = "std")]
 pub fn organ_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64, U1>>> {
     constant(f) >> organ()
 }
@@ -1788,6 +3247,7 @@ pub fn organ_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f6
 /// Contains all partials, falls off like a triangle wave.
 /// - Allocates: global soft saw wavetable.
 /// - Output 0: soft saw wave
+#[cfg(feature = "std")]
 pub fn soft_saw_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64, U1>>> {
     constant(f) >> soft_saw()
 }
@@ -1795,6 +3255,7 @@ pub fn soft_saw_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static,
 /// Fixed Hammond wavetable oscillator at `f` Hz. Emphasizes first three partials.
 /// - Allocates: global Hammond wavetable.
 /// - Output 0: Hammond wave
+#[cfg(feature = "std")]
 pub fn hammond_hz(f: f64) -> An<Pipe<f64, Constant<U1, f64>, WaveSynth<'static, f64, U1>>> {
     constant(f) >> hammond()
 }
@@ -2108,10 +3569,27 @@ pub fn bandrez_q(
 /// - Input 0: frequency in Hz
 /// - Input 1: pulse duty cycle in 0...1
 /// - Output 0: pulse wave
+#[cfg(feature = "std")]
 pub fn pulse() -> An<super::prelude::PulseWave<f64>> {
     super::prelude::pulse()
 }
 
+/// Pulse wave oscillator with PolyBLEP antialiasing.
+/// Unlike `pulse`, it does not depend on wavetables.
+/// The pulse is 1 for the first part of the cycle (the pulse width) and -1 for the rest.
+/// - Input 0: frequency in Hz
+/// - Input 1: pulse width in 0...1
+/// - Output 0: pulse wave
+///
+/// ### Example: Pulse Width Modulation
+/// ```
+/// use fundsp::hacker::*;
+/// lfo(|t| (110.0, lerp11(0.1, 0.9, sin_hz(0.2, t)))) >> pulse_blep();
+/// ```
+pub fn pulse_blep() -> An<BlepPulse<f64>> {
+    An(BlepPulse::new(DEFAULT_SR))
+}
+
 /// Play back a channel of a Wave64.
 /// Optional loop point is the index to jump to at the end of the wave.
 /// - Output 0: wave
@@ -2200,6 +3678,209 @@ pub fn wave32_at(
     ))
 }
 
+/// Stream audio file at `path` from disk, buffering `prebuffer` seconds ahead on a background thread.
+/// The file plays once at its own sample rate. Monitor buffer level and underruns via `status`.
+/// Supported formats are anything that Symphonia can read.
+/// - Outputs: channels of the file
+///
+/// ### Example: Stream Backing Track
+/// ```no_run
+/// use fundsp::hacker::*;
+/// let track = stream_wav("backing.wav", 2.0).unwrap();
+/// let status = track.status();
+/// assert_eq!(status.underruns(), 0);
+/// ```
+#[cfg(feature = "files")]
+pub fn stream_wav<P: AsRef<std::path::Path>>(path: P, prebuffer: f64) -> WaveResult<WavStream64> {
+    WavStream64::new(path, prebuffer)
+}
+
+/// Play back a channel of a Wave64 at time rate `rate` with pitch preserved,
+/// using WSOLA time stretching. Rates below 1 slow playback down.
+/// Setting: (time rate, pitch ratio).
+/// - Output 0: wave
+///
+/// ### Example: Half Speed
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = std::sync::Arc::new(Wave64::new(1, 44100.0));
+/// let mut player = stretch64(&wave, 0, 0.5);
+/// player.set_pitch(2.0);
+/// ```
+pub fn stretch64(wave: &Arc<Wave64>, channel: usize, rate: f64) -> An<Wave64Stretcher<f64>> {
+    An(Wave64Stretcher::new(DEFAULT_SR, wave, channel, 0.0, rate, 1.0))
+}
+
+/// Play back a channel of a Wave32 at time rate `rate` with pitch preserved,
+/// using WSOLA time stretching. Rates below 1 slow playback down.
+/// Setting: (time rate, pitch ratio).
+/// - Output 0: wave
+///
+/// ### Example: Half Speed
+/// ```
+/// use fundsp::hacker::*;
+/// let wave = std::sync::Arc::new(Wave32::new(1, 44100.0));
+/// let mut player = stretch32(&wave, 0, 0.5);
+/// player.set_pitch(2.0);
+/// ```
+pub fn stretch32(wave: &Arc<Wave32>, channel: usize, rate: f64) -> An<Wave32Stretcher<f64>> {
+    An(Wave32Stretcher::new(DEFAULT_SR, wave, channel, 0.0, rate, 1.0))
+}
+
+#[cfg(feature = "std")]
+/// Record input into a growing Wave64. The input is passed through.
+/// The sample is appended to every channel of the wave.
+/// Recording allocates memory and locks the wave, so it is not real-time safe:
+/// use `capture` in real-time contexts.
+/// - Input 0: signal to record
+/// - Output 0: signal
+///
+/// ### Example: Record Noise
+/// ```
+/// use fundsp::hacker::*;
+/// use std::sync::{Arc, Mutex};
+/// let wave = Arc::new(Mutex::new(Wave64::new(1, 44100.0)));
+/// let mut node = white() >> record64(&wave);
+/// for _ in 0..100 {
+///     node.get_mono();
+/// }
+/// assert_eq!(wave.lock().unwrap().length(), 100);
+/// ```
+pub fn record64(wave: &Arc<Mutex<Wave64>>) -> An<Wave64Recorder<f64>> {
+    An(Wave64Recorder::new(wave))
+}
+
+#[cfg(feature = "std")]
+/// Record input into a growing Wave32. The input is passed through.
+/// The sample is appended to every channel of the wave.
+/// Recording allocates memory and locks the wave, so it is not real-time safe:
+/// use `capture` in real-time contexts.
+/// - Input 0: signal to record
+/// - Output 0: signal
+pub fn record32(wave: &Arc<Mutex<Wave32>>) -> An<Wave32Recorder<f64>> {
+    An(Wave32Recorder::new(wave))
+}
+
+/// Capture input into a preallocated buffer. The input is passed through.
+/// Capture is real-time safe. Once the buffer is full, further samples are dropped
+/// and the overflow flag of the buffer is set.
+/// - Input 0: signal to capture
+/// - Output 0: signal
+///
+/// ### Example: Capture Into Buffer
+/// ```
+/// use fundsp::hacker::*;
+/// let buffer = std::sync::Arc::new(CaptureBuffer::new(10));
+/// let mut node = dc(0.5) >> capture(&buffer);
+/// for _ in 0..11 {
+///     node.get_mono();
+/// }
+/// assert!(buffer.len() == 10 && buffer.overflow());
+/// ```
+pub fn capture(buffer: &Arc<CaptureBuffer>) -> An<Capture<f64>> {
+    An(Capture::new(buffer))
+}
+
+/// Looper with room for a loop of `max_duration` seconds, controlled with settings.
+/// Loop boundaries are crossfaded. The output contains only the loop.
+/// Setting: loop state (`LoopState`).
+/// - Input 0: audio
+/// - Output 0: loop playback
+///
+/// ### Example: Record And Play Back
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = looper(10.0);
+/// node.set(LoopState::Record);
+/// for _ in 0..1000 {
+///     node.filter_mono(1.0);
+/// }
+/// node.set(LoopState::Play);
+/// assert_eq!(node.filter_mono(1.0), 1.0);
+/// ```
+pub fn looper(max_duration: f64) -> An<Looper<f64, U1>> {
+    An(Looper::new(max_duration))
+}
+
+/// Looper with room for a loop of `max_duration` seconds, controlled with a gate.
+/// Each rising edge of the gate steps through the states
+/// record, play, overdub, play, overdub, and so on.
+/// Loop boundaries are crossfaded. The output contains only the loop.
+/// Setting: loop state (`LoopState`).
+/// - Input 0: audio
+/// - Input 1: gate
+/// - Input 2: playback speed (1 = normal speed, negative = reverse)
+/// - Output 0: loop playback
+pub fn looper_gate(max_duration: f64) -> An<Looper<f64, U3>> {
+    An(Looper::new(max_duration))
+}
+
+/// Polyphonic synthesizer with `N` copies of `voice`. Voices are allocated to notes
+/// with a stealing strategy (`VoiceSteal`, default round robin) and summed.
+/// Setting: note event (`PolyEvent`).
+/// - Output 0: sum of voice outputs
+///
+/// The voice has three inputs:
+/// - Input 0: pitch in Hz
+/// - Input 1: gate (1 = note held, 0 = note released)
+/// - Input 2: velocity in 0...1
+///
+/// Voices are clones of `voice`, so they should not share state
+/// through shared variables, as `adsr_live` does.
+///
+/// ### Example: Polyphonic Saw Synth
+/// ```
+/// use fundsp::hacker::*;
+/// // ADSR envelope with a sustain stage at breakpoint 2.
+/// let mut env = envelope_bp(&[(0.0, 0.0, 0.0), (0.01, 1.0, 0.0), (0.21, 0.5, 0.0), (0.51, 0.0, 0.0)]);
+/// env.set_loop(2, 2);
+/// let voice = (saw() | env | pass()) >> map(|x: &Frame<f64, U3>| x[0] * x[1] * x[2]);
+/// let mut synth = poly::<U8, _>(voice);
+/// synth.set_steal(VoiceSteal::Oldest);
+/// synth.note_on(60, midi_hz(60.0), 1.0);
+/// synth.note_on(64, midi_hz(64.0), 0.8);
+/// assert_eq!(synth.held(), 2);
+/// synth.note_off(60);
+/// assert_eq!(synth.held(), 1);
+/// ```
+pub fn poly<N, X>(voice: An<X>) -> An<Poly<N, f64, X>>
+where
+    N: Size<f64>,
+    N: Size<X>,
+    X: AudioNode<Sample = f64, Inputs = U3, Outputs = U1>,
+{
+    assert!(N::USIZE > 0);
+    An(Poly::new(Frame::generate(|_| voice.0.clone())))
+}
+
+/// Unison stack of `N` copies of oscillator `voice`, detuned over a total of `detune` cents
+/// and spread in the stereo field by `spread` in 0...1. Each copy starts at a different pseudorandom phase.
+/// The output is scaled by `1 / sqrt(N)`.
+/// Setting: (detune in cents, stereo spread in 0...1).
+/// - Input 0: frequency in Hz
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// The oscillator has one input (frequency in Hz) and one output.
+///
+/// ### Example: Supersaw
+/// ```
+/// use fundsp::hacker::*;
+/// let mut supersaw = unison::<U7, _>(25.0, 0.8, saw());
+/// supersaw.set_detune(40.0);
+/// let synth = dc(110.0) >> supersaw;
+/// assert_eq!(synth.outputs(), 2);
+/// ```
+pub fn unison<N, X>(detune: f64, spread: f64, voice: An<X>) -> An<Unison<N, f64, X>>
+where
+    N: Size<f64>,
+    N: Size<X>,
+    X: AudioNode<Sample = f64, Inputs = U1, Outputs = U1>,
+{
+    assert!(N::USIZE > 0);
+    An(Unison::new(detune, spread, Frame::generate(|_| voice.0.clone())))
+}
+
 /// Mono chorus, 5 voices. For stereo, stack two of these using different seed values.
 /// `seed`: LFO seed.
 /// `separation`: base voice separation in seconds (for example, 0.015).
@@ -2306,6 +3987,77 @@ where
     An(VarFn::new(shared, f))
 }
 
+/// Outputs the value of the shared variable with block-rate smoothing.
+/// The variable is read every `CONTROL_BLOCK` samples and the output ramps
+/// linearly to the new value over the block.
+///
+/// - Output 0: smoothed value
+///
+/// ### Example: Smoothed Volume Control
+/// ```
+/// use fundsp::hacker::*;
+/// let volume = shared(0.5);
+/// pink() * from_shared(&volume);
+/// ```
+pub fn from_shared(shared: &Shared<f64>) -> An<FromShared<f64>> {
+    An(FromShared::new(shared))
+}
+
+/// Pass through input and store its average over each block
+/// of `CONTROL_BLOCK` samples into the shared variable.
+///
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Publish Envelope Level
+/// ```
+/// use fundsp::hacker::*;
+/// let level = shared(0.0);
+/// pink() >> (pass() ^ follow(0.1) >> to_shared(&level) >> sink());
+/// ```
+pub fn to_shared(shared: &Shared<f64>) -> An<ToShared<f64>> {
+    An(ToShared::new(shared))
+}
+
+/// Control-rate probe. Pass through input and publish its latest value
+/// into the shared variable once per block of `CONTROL_BLOCK` samples,
+/// or once per call when processed in blocks. Cheaper than `monitor` for UI display.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Display Filter Cutoff
+/// ```
+/// use fundsp::hacker::*;
+/// let cutoff = shared(0.0);
+/// let sweep = lfo(|t| xerp(500.0, 5000.0, t)) >> probe(&cutoff);
+/// ```
+pub fn probe(shared: &Shared<f64>) -> An<Probe<f64>> {
+    An(Probe::new(shared))
+}
+
+/// Convert the last input, an audio-rate control, to settings of `node`.
+/// The control is averaged over blocks of `CONTROL_BLOCK` samples
+/// and mapped to a setting with `f`. The setting is applied at the start of the next block.
+/// Setting: from `node`.
+///
+/// - Inputs: from `node`, followed by control
+/// - Outputs: from `node`
+///
+/// ### Example: Sweep Lowpass Cutoff With Settings
+/// ```
+/// use fundsp::hacker::*;
+/// (noise() | lfo(|t| xerp(500.0, 5000.0, t / 10.0))) >> to_setting(lowpole_hz(1000.0), |f| f);
+/// ```
+pub fn to_setting<X, F>(node: An<X>, f: F) -> An<ToSetting<f64, X, F>>
+where
+    X: AudioNode<Sample = f64>,
+    X::Inputs: Add<U1>,
+    <X::Inputs as Add<U1>>::Output: Size<f64>,
+    F: Fn(f64) -> X::Setting + Clone + Send + Sync,
+{
+    An(ToSetting::new(node.0, f))
+}
+
 /// Timer node. A node with no inputs or outputs that maintains
 /// current stream time in a shared variable.
 /// It can be added to any node by stacking.
@@ -2320,16 +4072,107 @@ pub fn timer(shared: &Shared<f64>) -> An<Timer<f64>> {
     An(Timer::new(DEFAULT_SR, shared))
 }
 
+/// Oscilloscope node for sharing triggered snapshots of `length` samples
+/// with a frontend thread. Snapshots begin at a rising edge through the trigger level (0 by default).
+/// Returns (frontend, backend).
+/// Setting: trigger level.
+/// - Input 0: signal to capture.
+/// - Output 0: signal passed through.
+///
+/// ### Example: Stable Sine Wave Display
+/// ```
+/// use fundsp::hacker::*;
+/// let (mut scope, backend) = scope(100);
+/// let mut node = sine_hz(441.0) >> backend;
+/// for _ in 0..1000 {
+///     node.get_mono();
+/// }
+/// assert!(scope.update());
+/// assert!(scope.at(0) > 0.0 && scope.at(0) < 0.1);
+/// ```
+#[cfg(feature = "std")]
+pub fn scope(length: usize) -> (Scope, An<ScopeBackend<f64>>) {
+    let (scope, backend) = Scope::new(length);
+    (scope, An(backend))
+}
+
+/// Sample accurate trigger. Detects rising edges of the input through `threshold`
+/// and outputs an impulse at each edge, which can retrigger envelopes.
+/// Each edge is also sent as a `TriggerEvent` with its sample index and interpolated time
+/// to the frontend, which can be polled from a control thread, for example,
+/// to push sequencer events. Up to 1024 events can be pending.
+/// Returns (frontend, backend).
+/// Setting: threshold.
+/// - Input 0: signal
+/// - Output 0: impulse at rising edges
+///
+/// ### Example: Count Rising Edges
+/// ```
+/// use fundsp::hacker::*;
+/// let (mut triggers, backend) = trigger(0.5);
+/// let mut node = sine_hz(10.0) >> backend;
+/// for _ in 0..44100 {
+///     node.get_mono();
+/// }
+/// assert_eq!(triggers.events().len(), 10);
+/// ```
+#[cfg(feature = "std")]
+pub fn trigger(threshold: f64) -> (Triggers, An<TriggerBackend<f64>>) {
+    let (triggers, backend) = Triggers::new(threshold, 1024);
+    (triggers, An(backend))
+}
+
+/// Sample accurate trigger that calls `f` with a `TriggerEvent` at each rising edge
+/// of the input through `threshold`. The function is called on the audio thread.
+/// Setting: threshold.
+/// - Input 0: signal
+/// - Output 0: impulse at rising edges
+#[cfg(feature = "std")]
+pub fn trigger_fn<F: FnMut(TriggerEvent) + Clone + Send + Sync>(threshold: f64, f: F) -> An<TriggerFn<f64, F>> {
+    An(TriggerFn::new(threshold, f))
+}
+
 /// Snoop node for sharing audio data with a frontend thread.
 /// The latest samples buffer has room for at least `capacity` samples.
 /// Returns (frontend, backend).
 /// - Input 0: signal to snoop.
 /// - Output 0: signal passed through.
+#[cfg(feature = "std")]
 pub fn snoop(capacity: usize) -> (Snoop<f64>, An<SnoopBackend<f64>>) {
     let (snoop, backend) = Snoop::new(capacity);
     (snoop, An(backend))
 }
 
+/// Snapshot meter. Passes through input and writes a summary of it
+/// according to the chosen metering mode to `slot` of a `Snapshot`.
+/// The values are published together by `snapshot_publish`.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let (_snapshot, writer) = Snapshot::new(&["level"]);
+/// snapshot_meter(&writer, 0, Meter::Rms(0.1)) >> snapshot_publish(&writer);
+/// ```
+#[cfg(feature = "std")]
+pub fn snapshot_meter(
+    writer: &SnapshotWriter,
+    slot: usize,
+    meter: Meter,
+) -> An<SnapshotMeter<f64>> {
+    An(SnapshotMeter::new(writer, slot, meter))
+}
+
+/// Snapshot publisher. Passes through input and publishes the values written
+/// to a `Snapshot` once per block. Place it after the nodes that write to the snapshot.
+/// - Input 0: signal
+/// - Output 0: signal
+#[cfg(feature = "std")]
+pub fn snapshot_publish(writer: &SnapshotWriter) -> An<SnapshotPublisher<f64>> {
+    An(SnapshotPublisher::new(writer))
+}
+
 /// Frequency domain resynthesizer.
 /// The number of inputs is `I` and the number of outputs is `O`.
 /// The window length (in samples) must be a power of two and at least four.
@@ -2354,6 +4197,7 @@ pub fn snoop(capacity: usize) -> (Snoop<f64>, An<SnoopBackend<f64>>) {
 ///         }
 ///     });
 /// ```
+#[cfg(feature = "std")]
 pub fn resynth<I, O, F>(window_length: usize, processing: F) -> An<Resynth<I, O, f64, F>>
 where
     I: Size<f64>,
@@ -2363,6 +4207,175 @@ where
     An(Resynth::new(window_length, processing))
 }
 
+/// Hilbert transformer. Outputs the analytic signal of the input: the real part (I)
+/// and the imaginary part (Q), which lags the real part by 90 degrees.
+/// Only the phase difference of the outputs is accurate, not their phase relative to the input.
+/// - Input 0: signal
+/// - Output 0: real part (I)
+/// - Output 1: imaginary part (Q)
+///
+/// ### Example: Envelope Detector
+/// ```
+/// use fundsp::hacker::*;
+/// let envelope = hilbert() >> map(|x: &Frame<f64, U2>| (x[0] * x[0] + x[1] * x[1]).sqrt());
+/// ```
+pub fn hilbert() -> An<Hilbert<f64>> {
+    An(Hilbert::new())
+}
+
+/// Ring modulator. Multiplies two signals after blocking DC (below 10 Hz) from both,
+/// so that neither signal leaks into the output.
+/// - Input 0: signal
+/// - Input 1: modulator
+/// - Output 0: product
+///
+/// ### Example: Ring Modulated Voice
+/// ```
+/// use fundsp::hacker::*;
+/// let robot = (pass() | sine_hz(30.0)) >> ring_mod();
+/// ```
+pub fn ring_mod() -> An<RingMod<f64>> {
+    An(RingMod::new())
+}
+
+/// Single-sideband frequency shifter. Shifts all frequencies of the input by `shift` Hz,
+/// up if positive and down if negative, using a Hilbert transformer.
+/// Unlike pitch shifting, harmonic relations are not preserved.
+/// Setting: shift in Hz.
+/// - Input 0: signal
+/// - Output 0: frequency shifted signal
+///
+/// ### Example: Barberpole Style Shift Feedback
+/// ```
+/// use fundsp::hacker::*;
+/// let shifter = pass() & feedback(delay(0.1) >> freq_shift(5.0) * 0.6);
+/// ```
+pub fn freq_shift(shift: f64) -> An<FreqShift<f64>> {
+    An(FreqShift::new(shift))
+}
+
+/// Pitch shifter with low latency. Shifts input by `semitones`.
+/// Two delay line taps with a crossfade window of 50 ms do the shifting,
+/// which works best for monophonic and noisy material.
+/// Setting: pitch shift in semitones.
+/// - Input 0: input signal
+/// - Output 0: pitch shifted signal
+///
+/// ### Example: Octave Doubler
+/// ```
+/// use fundsp::hacker::*;
+/// let doubler = pass() & pitch_shift(12.0);
+/// ```
+pub fn pitch_shift(semitones: f64) -> An<PitchShift<f64>> {
+    An(PitchShift::new(semitones, 0.05))
+}
+
+/// Spectral pitch shifter. Shifts input by `semitones` using a phase vocoder.
+/// If `preserve_formants` is set, then the spectral envelope stays in place,
+/// which keeps the character of voices and instruments.
+/// The latency is 2048 samples.
+/// - Input 0: input signal
+/// - Output 0: pitch shifted signal
+///
+/// ### Example: Harmonizer
+/// ```
+/// use fundsp::hacker::*;
+/// let harmonizer = pass() & pitch_shift_spectral(4.0, true) & pitch_shift_spectral(7.0, true);
+/// ```
+#[cfg(feature = "std")]
+pub fn pitch_shift_spectral(
+    semitones: f64,
+    preserve_formants: bool,
+) -> An<Resynth<U1, U1, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut shifter = SpectralShift::new(2048, semitones, preserve_formants);
+    An(Resynth::new(2048, move |fft| shifter.process(fft)))
+}
+
+/// Spectral subtraction noise reducer. The noise profile is learned from the quietest
+/// portions of the input, which are assumed to contain only noise, such as pauses in speech.
+/// The `amount` of reduction is in 0...1: the maximum attenuation ranges from 0 dB to 40 dB.
+/// Gains are smoothed over time and frequency to suppress musical noise.
+/// The latency is 2048 samples.
+/// - Input 0: input signal
+/// - Output 0: denoised signal
+///
+/// ### Example: Clean Up A Voice Chat Stream
+/// ```
+/// use fundsp::hacker::*;
+/// pass() >> denoise(0.7);
+/// ```
+#[cfg(feature = "std")]
+pub fn denoise(
+    amount: f64,
+) -> An<Resynth<U1, U1, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut denoiser = SpectralDenoise::new(2048, amount, None);
+    An(Resynth::new(2048, move |fft| denoiser.process(fft)))
+}
+
+/// Spectral subtraction noise reducer with a supplied noise `profile`,
+/// which must have a window length of 2048 samples.
+/// The `amount` of reduction is in 0...1: the maximum attenuation ranges from 0 dB to 40 dB.
+/// The latency is 2048 samples.
+/// - Input 0: input signal
+/// - Output 0: denoised signal
+///
+/// ### Example: Denoise With A Profile Of Room Tone
+/// ```
+/// use fundsp::hacker::*;
+/// let room = Wave64::render(44100.0, 1.0, &mut (pink() * 0.01));
+/// let profile = NoiseProfile::new(2048, room.channel(0));
+/// pass() >> denoise_profile(1.0, &profile);
+/// ```
+#[cfg(feature = "std")]
+pub fn denoise_profile(
+    amount: f64,
+    profile: &NoiseProfile,
+) -> An<Resynth<U1, U1, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut denoiser = SpectralDenoise::new(2048, amount, Some(profile));
+    An(Resynth::new(2048, move |fft| denoiser.process(fft)))
+}
+
+/// Spectral morph between two inputs. Interpolates the magnitude and phase of each FFT bin
+/// from input 0 (`amount` = 0) to input 1 (`amount` = 1).
+/// Phases are interpolated along the shorter arc.
+/// The latency is 2048 samples.
+/// - Input 0: source A
+/// - Input 1: source B
+/// - Output 0: morphed signal
+///
+/// ### Example: Halfway Between A Saw And Noise
+/// ```
+/// use fundsp::hacker::*;
+/// (saw_hz(110.0) | pink()) >> spectral_morph(0.5);
+/// ```
+#[cfg(feature = "std")]
+pub fn spectral_morph(
+    amount: f64,
+) -> An<Resynth<U2, U1, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut morph = SpectralMorph::new(amount, true);
+    An(Resynth::new(2048, move |fft| morph.process(fft)))
+}
+
+/// Spectral magnitude morph between two inputs. Interpolates the magnitude of each FFT bin
+/// from input 0 (`amount` = 0) to input 1 (`amount` = 1) while keeping the phases of input 0.
+/// The latency is 2048 samples.
+/// - Input 0: source A, which supplies phases
+/// - Input 1: source B
+/// - Output 0: morphed signal
+///
+/// ### Example: Imprint Noise Spectrum On A Saw
+/// ```
+/// use fundsp::hacker::*;
+/// (saw_hz(110.0) | pink()) >> spectral_morph_magnitude(0.5);
+/// ```
+#[cfg(feature = "std")]
+pub fn spectral_morph_magnitude(
+    amount: f64,
+) -> An<Resynth<U2, U1, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut morph = SpectralMorph::new(amount, false);
+    An(Resynth::new(2048, move |fft| morph.process(fft)))
+}
+
 /// `N`-channel impulse. The first sample on each channel is one and the rest are zero.
 /// - Output(s): impulse.
 pub fn impulse<N: Size<f64>>() -> An<Impulse<N, f64>> {
@@ -2400,3 +4413,154 @@ pub fn node64<I: Size<f64>, O: Size<f64>>(unit: Box<dyn AudioUnit64>) -> An<Node
 pub fn rotate(angle: f64, gain: f64) -> An<Mixer<U2, U2, f64>> {
     super::prelude::rotate(angle, gain)
 }
+
+/// Mid/side encoder. Converts a stereo signal to mid `(L + R) / 2` and side `(L - R) / 2`.
+/// The inverse is `ms_decode`.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: mid output
+/// - Output 1: side output
+///
+/// ### Example: Process The Side Channel
+/// ```
+/// use fundsp::hacker::*;
+/// ms_encode() >> (pass() | highpass_hz(200.0, 0.7)) >> ms_decode();
+/// ```
+pub fn ms_encode() -> An<Mixer<U2, U2, f64>> {
+    super::prelude::ms_encode()
+}
+
+/// Mid/side decoder. Converts mid and side signals back to left `M + S` and right `M - S`.
+/// The inverse is `ms_encode`.
+/// - Input 0: mid input
+/// - Input 1: side input
+/// - Output 0: left output
+/// - Output 1: right output
+pub fn ms_decode() -> An<Mixer<U2, U2, f64>> {
+    super::prelude::ms_decode()
+}
+
+/// Stereo width control. Scales the side component of a stereo signal by `width` (`width` >= 0).
+/// Width 0 is mono, 1 leaves the signal unchanged and values above 1 widen the image.
+/// Setting: width.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Narrow A Stereo Bus
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() | pink()) >> stereo_width(0.5);
+/// ```
+pub fn stereo_width(width: f64) -> An<StereoWidth<f64>> {
+    super::prelude::stereo_width(width)
+}
+
+/// Stereo rotation by `angle` radians. Setting: angle.
+/// Unlike `rotate`, the angle can be changed with a setting.
+/// A rotation of `PI / 4` converts a stereo signal to scaled mid and side signals.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: rotated left output
+/// - Output 1: rotated right output
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// stereo_rotate(PI / 8.0);
+/// ```
+pub fn stereo_rotate(angle: f64) -> An<StereoRotate<f64>> {
+    super::prelude::stereo_rotate(angle)
+}
+
+/// Stereo correlation meter with a smoothing timescale of 300 ms.
+/// Outputs the correlation of the left and right channels in -1...1:
+/// 1 is mono, 0 is uncorrelated and -1 is out of phase. Silence reads as 0.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: correlation
+///
+/// ### Example: Meter A Stereo Bus
+/// ```
+/// use fundsp::hacker::*;
+/// let phase = shared(0.0);
+/// (pink() | pink()) >> (multipass::<U2>() ^ (correlation() >> monitor(&phase, Meter::Sample) >> sink()));
+/// ```
+pub fn correlation() -> An<Correlation<f64>> {
+    super::prelude::correlation()
+}
+
+/// Stereo correlation meter with smoothing timescale `t` seconds.
+/// The timescale is the time it takes for the reading to move halfway to a new value.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: correlation
+pub fn correlation_s(t: f64) -> An<Correlation<f64>> {
+    super::prelude::correlation_s(t)
+}
+
+/// Center channel canceller for karaoke and practice tools. Outputs the side component
+/// of a stereo signal, left `(L - R) / 2` and right `(R - L) / 2`,
+/// which removes everything panned to the center, such as lead vocals.
+/// The result sums to silence in mono.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Remove Vocals
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() | pink()) >> center_cancel();
+/// ```
+pub fn center_cancel() -> An<Mixer<U2, U2, f64>> {
+    super::prelude::center_cancel()
+}
+
+/// Center channel extractor. Outputs the mid component `(L + R) / 2` of a stereo signal
+/// on both channels. Side content that is not hard panned leaks into the output;
+/// see `center_extract_spectral` for a more selective extractor.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+pub fn center_extract() -> An<Mixer<U2, U2, f64>> {
+    super::prelude::center_extract()
+}
+
+/// Spectral center channel canceller. Removes FFT bins that are identical in both channels
+/// with a soft mask, which keeps partially panned and stereo content intact.
+/// Complements `center_extract_spectral`: the two sum to the input.
+/// The latency is 2048 samples.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Remove Vocals
+/// ```
+/// use fundsp::hacker::*;
+/// (pink() | pink()) >> center_cancel_spectral();
+/// ```
+#[cfg(feature = "std")]
+pub fn center_cancel_spectral(
+) -> An<Resynth<U2, U2, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut center = SpectralCenter::new(false);
+    An(Resynth::new(2048, move |fft| center.process(fft)))
+}
+
+/// Spectral center channel extractor. Keeps FFT bins that are identical in both channels
+/// with a soft mask and outputs them on both channels.
+/// Complements `center_cancel_spectral`: the two sum to the input.
+/// The latency is 2048 samples.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+#[cfg(feature = "std")]
+pub fn center_extract_spectral(
+) -> An<Resynth<U2, U2, f64, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut center = SpectralCenter::new(true);
+    An(Resynth::new(2048, move |fft| center.process(fft)))
+}
@@ -0,0 +1,256 @@
+//! Adaptive music layer mixer (vertical remixing).
+
+use super::audiounit::*;
+use super::math::*;
+use super::sequencer::Fade;
+use super::shared::*;
+use super::signal::*;
+use super::transport::*;
+use super::wave::*;
+use super::*;
+use duplicate::duplicate_item;
+use std::sync::Arc;
+
+/// Default layer crossfade time in seconds.
+const LAYER_FADE_TIME: f64 = 1.0;
+
+#[duplicate_item(
+    f48       Wave48       Layer48;
+    [ f64 ]   [ Wave64 ]   [ Layer64 ];
+    [ f32 ]   [ Wave32 ]   [ Layer32 ];
+)]
+/// A stem of a layer mixer.
+#[derive(Clone)]
+struct Layer48 {
+    wave: Arc<Wave48>,
+    /// Stem is active when intensity is in `low`...`high`.
+    low: f48,
+    high: f48,
+    gain: f48,
+    /// Fade position in 0...1.
+    position: f48,
+    /// Whether the stem is fading in (or is in).
+    active: bool,
+}
+
+#[duplicate_item(
+    f48       Wave48       Layer48       LayerMixer48;
+    [ f64 ]   [ Wave64 ]   [ Layer64 ]   [ LayerMixer64 ];
+    [ f32 ]   [ Wave32 ]   [ Layer32 ]   [ LayerMixer32 ];
+)]
+/// Layer mixer for adaptive music. Loops a number of synchronized stems
+/// and crossfades between sets of them according to an intensity parameter.
+/// Each stem is active in a range of intensities. When a transport is attached,
+/// transitions wait for the next beat grid boundary.
+/// - Outputs: mix of active stems
+#[derive(Clone)]
+pub struct LayerMixer48 {
+    channels: usize,
+    layers: Vec<Layer48>,
+    intensity: Shared<f48>,
+    /// Intensity that determines the currently active stems.
+    current: f48,
+    transport: Option<Transport>,
+    /// Quantization grid in beats.
+    quantize: f64,
+    /// Playback position in beats.
+    beat: f64,
+    /// Whether the playback position is at a grid boundary.
+    on_grid: bool,
+    /// Next sample index in the stems.
+    index: usize,
+    fade: Fade,
+    fade_time: f64,
+    sample_rate: f64,
+}
+
+#[duplicate_item(
+    f48       Wave48       Layer48       LayerMixer48;
+    [ f64 ]   [ Wave64 ]   [ Layer64 ]   [ LayerMixer64 ];
+    [ f32 ]   [ Wave32 ]   [ Layer32 ]   [ LayerMixer32 ];
+)]
+impl LayerMixer48 {
+    /// Create an empty layer mixer with `channels` outputs,
+    /// controlled by the shared variable `intensity`.
+    /// Stems with fewer channels are repeated cyclically across the outputs.
+    pub fn new(channels: usize, intensity: &Shared<f48>) -> Self {
+        Self {
+            channels,
+            layers: Vec::new(),
+            intensity: intensity.clone(),
+            current: intensity.value(),
+            transport: None,
+            quantize: 1.0,
+            beat: 0.0,
+            on_grid: true,
+            index: 0,
+            fade: Fade::Power,
+            fade_time: LAYER_FADE_TIME,
+            sample_rate: DEFAULT_SR,
+        }
+    }
+
+    /// Quantize transitions to a grid of `beats` (`beats` > 0) beats
+    /// at the tempo of `transport`. The grid starts at the beginning of the stems.
+    pub fn with_transport(mut self, transport: &Transport, beats: f64) -> Self {
+        assert!(beats > 0.0);
+        self.transport = Some(transport.clone());
+        self.quantize = beats;
+        self
+    }
+
+    /// Set crossfade curve and crossfade time in seconds.
+    pub fn set_fade(&mut self, fade: Fade, fade_time: f64) {
+        self.fade = fade;
+        self.fade_time = fade_time;
+    }
+
+    /// Add a stem that is active when intensity is in the range `low`...`high`.
+    /// Stems loop and play in sync; returns the index of the stem.
+    pub fn push(&mut self, wave: &Arc<Wave48>, low: f48, high: f48, gain: f48) -> usize {
+        assert!(wave.channels() > 0 && !wave.is_empty());
+        let active = self.current >= low && self.current <= high;
+        self.layers.push(Layer48 {
+            wave: wave.clone(),
+            low,
+            high,
+            gain,
+            position: if active { 1.0 } else { 0.0 },
+            active,
+        });
+        self.layers.len() - 1
+    }
+
+    /// Number of stems.
+    pub fn layers(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Whether stem `layer` is active at the current intensity.
+    /// Changes in intensity become current at the next transition.
+    pub fn is_active(&self, layer: usize) -> bool {
+        self.layers[layer].active
+    }
+
+    /// Intensity parameter.
+    pub fn intensity(&self) -> &Shared<f48> {
+        &self.intensity
+    }
+
+    /// Playback position in beats, if a transport is attached.
+    pub fn beat(&self) -> Option<f64> {
+        self.transport.as_ref().map(|_| self.beat)
+    }
+
+    /// Start a transition if intensity has changed and we are on the grid.
+    #[inline]
+    fn update(&mut self) {
+        let intensity = self.intensity.value();
+        if intensity == self.current {
+            return;
+        }
+        if self.transport.is_some() && !self.on_grid {
+            return;
+        }
+        self.current = intensity;
+        for layer in self.layers.iter_mut() {
+            layer.active = intensity >= layer.low && intensity <= layer.high;
+        }
+    }
+
+    /// Render the next frame.
+    #[inline]
+    #[allow(clippy::unnecessary_cast)]
+    fn next_frame(&mut self, mut store: impl FnMut(usize, f48)) {
+        self.update();
+        let step: f48 = if self.fade_time > 0.0 {
+            (1.0 / (self.fade_time * self.sample_rate)) as f48
+        } else {
+            1.0
+        };
+        for layer in self.layers.iter_mut() {
+            layer.position = if layer.active {
+                min(1.0, layer.position + step)
+            } else {
+                max(0.0, layer.position - step)
+            };
+            if layer.position == 0.0 {
+                continue;
+            }
+            let gain = layer.gain * self.fade.at(layer.position);
+            let index = self.index % layer.wave.len();
+            let wave_channels = layer.wave.channels();
+            for channel in 0..self.channels {
+                store(channel, layer.wave.at(channel % wave_channels, index) * gain);
+            }
+        }
+        self.index += 1;
+        if let Some(transport) = &self.transport {
+            let beat = self.beat + 1.0 / (self.sample_rate * transport.beats_to_seconds(1.0));
+            self.on_grid = floor(beat / self.quantize) != floor(self.beat / self.quantize);
+            self.beat = beat;
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Layer48       LayerMixer48       AudioUnit48;
+    [ f64 ]   [ Wave64 ]   [ Layer64 ]   [ LayerMixer64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Wave32 ]   [ Layer32 ]   [ LayerMixer32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for LayerMixer48 {
+    fn reset(&mut self) {
+        self.index = 0;
+        self.beat = 0.0;
+        self.on_grid = true;
+        self.current = self.intensity.value();
+        for layer in self.layers.iter_mut() {
+            layer.active = self.current >= layer.low && self.current <= layer.high;
+            layer.position = if layer.active { 1.0 } else { 0.0 };
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &[f48], output: &mut [f48]) {
+        output.fill(0.0);
+        self.next_frame(|channel, x| output[channel] += x);
+    }
+
+    fn process(&mut self, size: usize, _input: &[&[f48]], output: &mut [&mut [f48]]) {
+        for channel in output.iter_mut() {
+            channel[..size].fill(0.0);
+        }
+        for i in 0..size {
+            self.next_frame(|channel, x| output[channel][i] += x);
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn outputs(&self) -> usize {
+        self.channels
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..self.outputs() {
+            output[i] = Signal::Latency(0.0);
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 129;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
@@ -1,4 +1,5 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
 //! FunDSP is an audio processing and synthesis library.
 //!
 //! See `README.md` in crate root folder for an overview.
@@ -19,18 +20,35 @@
     clippy::comparison_chain
 )]
 
+#[cfg(feature = "std")]
 #[macro_use]
 pub extern crate lazy_static;
 
-use std::cmp::PartialEq;
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
-use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+extern crate alloc;
+
+// Without `std`, bring in the allocating types that the `std` prelude would provide.
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+use core::cmp::PartialEq;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
 
 /// Default sample rate is 44.1 kHz.
 pub const DEFAULT_SR: f64 = 44_100.0;
 
-/// Maximum buffer size for block processing is 64 samples.
-pub const MAX_BUFFER_SIZE: usize = 64;
+/// Maximum buffer size for block processing. It is 64 samples by default.
+/// Features `block_128`, `block_256` and `block_512` raise it to 128, 256 or 512 samples,
+/// respectively. If several of them are enabled, the largest size applies.
+pub const MAX_BUFFER_SIZE: usize = if cfg!(feature = "block_512") {
+    512
+} else if cfg!(feature = "block_256") {
+    256
+} else if cfg!(feature = "block_128") {
+    128
+} else {
+    64
+};
 
 /// Number abstraction.
 pub trait Num:
@@ -38,7 +56,7 @@ pub trait Num:
     + Default
     + Send
     + Sync
-    + std::fmt::Display
+    + core::fmt::Display
     + Add<Output = Self>
     + Sub<Output = Self>
     + Mul<Output = Self>
@@ -78,8 +96,8 @@ macro_rules! impl_signed_num {
         #[inline(always)] fn from_f32(x: f32) -> Self { x as Self }
         #[inline(always)] fn abs(self) -> Self { <$t>::abs(self) }
         #[inline(always)] fn signum(self) -> Self { <$t>::signum(self) }
-        #[inline(always)] fn min(self, other: Self) -> Self { std::cmp::min(self, other) }
-        #[inline(always)] fn max(self, other: Self) -> Self { std::cmp::max(self, other) }
+        #[inline(always)] fn min(self, other: Self) -> Self { core::cmp::min(self, other) }
+        #[inline(always)] fn max(self, other: Self) -> Self { core::cmp::max(self, other) }
         #[inline(always)] fn pow(self, other: Self) -> Self { <$t>::pow(self, other as u32) }
         #[inline(always)] fn floor(self) -> Self { self }
         #[inline(always)] fn fract(self) -> Self { self }
@@ -100,8 +118,8 @@ macro_rules! impl_unsigned_num {
         #[inline(always)] fn from_f32(x: f32) -> Self { x as Self }
         #[inline(always)] fn abs(self) -> Self { self }
         #[inline(always)] fn signum(self) -> Self { 1 }
-        #[inline(always)] fn min(self, other: Self) -> Self { std::cmp::min(self, other) }
-        #[inline(always)] fn max(self, other: Self) -> Self { std::cmp::max(self, other) }
+        #[inline(always)] fn min(self, other: Self) -> Self { core::cmp::min(self, other) }
+        #[inline(always)] fn max(self, other: Self) -> Self { core::cmp::max(self, other) }
         #[inline(always)] fn pow(self, other: Self) -> Self { <$t>::pow(self, other as u32) }
         #[inline(always)] fn floor(self) -> Self { self }
         #[inline(always)] fn fract(self) -> Self { self }
@@ -112,6 +130,7 @@ macro_rules! impl_unsigned_num {
 }
 impl_unsigned_num! { u8, u16, u32, u64, u128, usize }
 
+#[cfg(feature = "std")]
 macro_rules! impl_float_num {
     ( $($t:ty),* ) => {
     $( impl Num for $t {
@@ -132,6 +151,29 @@ macro_rules! impl_float_num {
     }) *
     }
 }
+
+// Without `std`, the inherent float methods are unavailable; use `libm` instead.
+#[cfg(not(feature = "std"))]
+macro_rules! impl_float_num {
+    ( $($t:ty),* ) => {
+    $( impl Num for $t {
+        #[inline(always)] fn zero() -> Self { 0.0 }
+        #[inline(always)] fn one() -> Self { 1.0 }
+        #[inline(always)] fn new(x: i64) -> Self { x as Self }
+        #[inline(always)] fn from_f64(x: f64) -> Self { x as Self }
+        #[inline(always)] fn from_f32(x: f32) -> Self { x as Self }
+        #[inline(always)] fn abs(self) -> Self { libm::Libm::<$t>::fabs(self) }
+        #[inline(always)] fn signum(self) -> Self { if self.is_nan() { self } else { libm::Libm::<$t>::copysign(1.0, self) } }
+        #[inline(always)] fn min(self, other: Self) -> Self { libm::Libm::<$t>::fmin(self, other) }
+        #[inline(always)] fn max(self, other: Self) -> Self { libm::Libm::<$t>::fmax(self, other) }
+        #[inline(always)] fn pow(self, other: Self) -> Self { libm::Libm::<$t>::pow(self, other) }
+        #[inline(always)] fn floor(self) -> Self { libm::Libm::<$t>::floor(self) }
+        #[inline(always)] fn fract(self) -> Self { self - libm::Libm::<$t>::trunc(self) }
+        #[inline(always)] fn ceil(self) -> Self { libm::Libm::<$t>::ceil(self) }
+        #[inline(always)] fn round(self) -> Self { libm::Libm::<$t>::round(self) }
+    }) *
+    }
+}
 impl_float_num! { f32, f64 }
 
 /// Integer abstraction.
@@ -233,6 +275,7 @@ pub trait Real: Num + Float {
     fn atan(self) -> Self;
 }
 
+#[cfg(feature = "std")]
 macro_rules! impl_real {
     ( $($t:ty),* ) => {
     $( impl Real for $t {
@@ -250,49 +293,127 @@ macro_rules! impl_real {
     }) *
     }
 }
+
+#[cfg(not(feature = "std"))]
+macro_rules! impl_real {
+    ( $($t:ty),* ) => {
+    $( impl Real for $t {
+        #[inline(always)] fn sqrt(self) -> Self { libm::Libm::<$t>::sqrt(self) }
+        #[inline(always)] fn exp(self) -> Self { libm::Libm::<$t>::exp(self) }
+        #[inline(always)] fn exp2(self) -> Self { libm::Libm::<$t>::exp2(self) }
+        #[inline(always)] fn log(self) -> Self { libm::Libm::<$t>::log(self) }
+        #[inline(always)] fn log2(self) -> Self { libm::Libm::<$t>::log2(self) }
+        #[inline(always)] fn log10(self) -> Self { libm::Libm::<$t>::log10(self) }
+        #[inline(always)] fn sin(self) -> Self { libm::Libm::<$t>::sin(self) }
+        #[inline(always)] fn cos(self) -> Self { libm::Libm::<$t>::cos(self) }
+        #[inline(always)] fn tan(self) -> Self { libm::Libm::<$t>::tan(self) }
+        #[inline(always)] fn tanh(self) -> Self { libm::Libm::<$t>::tanh(self) }
+        #[inline(always)] fn atan(self) -> Self { libm::Libm::<$t>::atan(self) }
+    }) *
+    }
+}
 impl_real! { f32, f64 }
 
+// pub mod additive;
 // pub mod adsr;
 pub mod audionode;
 pub mod audiounit;
+#[cfg(feature = "std")]
+pub mod audit;
 pub mod buffer;
+// pub mod channels;
 pub mod combinator;
+// #[cfg(feature = "std")]
+// pub mod convolve;
+pub mod denormal;
+// #[cfg(feature = "device")]
+// pub mod device;
+// #[cfg(feature = "dsl")]
+// pub mod dsl;
 // pub mod delay;
 // pub mod dynamics;
 // pub mod envelope;
 // pub mod feedback;
 // pub mod filter;
+// pub mod filter_design;
+// pub mod filterbank;
 // pub mod fir;
 // pub mod follow;
+// pub mod formant;
+// #[cfg(feature = "std")]
 // pub mod gen;
+// #[cfg(feature = "std")]
 // pub mod granular;
+pub mod guard;
 // pub mod hacker;
 // pub mod hacker32;
+// #[cfg(feature = "std")]
+// pub mod layer;
+// #[cfg(feature = "link")]
+// pub mod link;
+// pub mod looper;
+// pub mod loudness;
 pub mod math;
+// pub mod mixer;
 pub mod moog;
+// #[cfg(feature = "std")]
+// pub mod music;
+// #[cfg(feature = "std")]
 // pub mod net;
 // pub mod noise;
+// #[cfg(feature = "osc")]
+// pub mod osc;
 // pub mod oscillator;
 // pub mod oversample;
 // pub mod pan;
+#[cfg(feature = "std")]
+pub mod parallel;
+// #[cfg(feature = "std")]
+// pub mod pattern;
+// pub mod pitch;
+// #[cfg(feature = "std")]
+// pub mod playlist;
+// #[cfg(feature = "plugin")]
+// pub mod plugin;
+// pub mod poly;
 // pub mod prelude;
+// pub mod preset;
+// #[cfg(feature = "std")]
 // pub mod realnet;
+// #[cfg(feature = "std")]
 // pub mod realseq;
 // pub mod resample;
+// #[cfg(feature = "std")]
 // pub mod resynth;
+// #[cfg(feature = "std")]
 // pub mod reverb;
 // pub mod rez;
+// #[cfg(feature = "std")]
 // pub mod sequencer;
+// #[cfg(feature = "std")]
 // pub mod setting;
 // pub mod shape;
 // pub mod shared;
+// pub mod shift;
 pub mod signal;
+// #[cfg(feature = "std")]
 // pub mod slot;
+// #[cfg(feature = "std")]
 // pub mod snoop;
+// #[cfg(feature = "std")]
 // pub mod sound;
+// #[cfg(feature = "files")]
+// pub mod stream;
+// pub mod stretch;
 // pub mod svf;
 // pub mod system;
+// #[cfg(feature = "std")]
+// pub mod testing;
+// pub mod transport;
+// pub mod unison;
+// pub mod vocoder;
 pub mod wave;
+// #[cfg(feature = "std")]
 // pub mod wavetable;
 
 #[cfg(feature = "files")]
@@ -0,0 +1,141 @@
+//! Ableton Link tempo and phase synchronization.
+
+use super::transport::*;
+use rusty_link::{AblLink, SessionState};
+
+/// Ableton Link session. Synchronizes the tempo of a transport and the grid of sequencers
+/// with other applications on the local network.
+///
+/// Call `update` periodically from a control thread: it adopts the session tempo
+/// into the transport. `sync` also computes a grid offset for sequencers,
+/// so that events pushed with `push_quantized` start on session beats.
+/// Beats are grouped into cycles of `quantum` beats; for example, a quantum of 4
+/// aligns bars of 4/4 time among peers.
+///
+/// ### Example: Start A Loop On The Next Bar Of The Session
+/// ```no_run
+/// use fundsp::hacker::*;
+/// let transport = Transport::new(120.0);
+/// let mut link = LinkSync::new(&transport, 4.0);
+/// let mut sequencer = Sequencer64::new(false, 1);
+/// sequencer.set_grid_offset(link.sync(sequencer.backend_time()));
+/// let bar = transport.beats_to_seconds(link.quantum());
+/// let pad = Box::new(saw_hz(110.0) * 0.1);
+/// sequencer.push_quantized(bar, 8.0 * bar, Fade::Smooth, 0.0, 0.01, pad);
+/// ```
+pub struct LinkSync {
+    link: AblLink,
+    state: SessionState,
+    transport: Transport,
+    quantum: f64,
+    /// Output latency in seconds.
+    latency: f64,
+}
+
+impl LinkSync {
+    /// Join a Link session with the tempo of `transport`
+    /// and a cycle of `quantum` (`quantum` > 0) beats.
+    pub fn new(transport: &Transport, quantum: f64) -> Self {
+        assert!(quantum > 0.0);
+        let link = AblLink::new(transport.tempo());
+        link.enable(true);
+        let mut state = SessionState::new();
+        link.capture_app_session_state(&mut state);
+        Self {
+            link,
+            state,
+            transport: transport.clone(),
+            quantum,
+            latency: 0.0,
+        }
+    }
+
+    /// Enable or disable synchronization. Disabled sessions do not see peers.
+    pub fn enable(&mut self, enable: bool) {
+        self.link.enable(enable);
+    }
+
+    /// Whether synchronization is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.link.is_enabled()
+    }
+
+    /// Number of peers in the session.
+    pub fn peers(&self) -> u64 {
+        self.link.num_peers()
+    }
+
+    /// Cycle length in beats.
+    pub fn quantum(&self) -> f64 {
+        self.quantum
+    }
+
+    /// Set cycle length in beats (`quantum` > 0).
+    pub fn set_quantum(&mut self, quantum: f64) {
+        assert!(quantum > 0.0);
+        self.quantum = quantum;
+    }
+
+    /// Set output latency in seconds: the time from processing a sample to hearing it.
+    /// Beats are placed so that they are heard at the same time as those of peers.
+    pub fn set_latency(&mut self, latency: f64) {
+        self.latency = latency;
+    }
+
+    /// Session time of the sample processed now, in microseconds.
+    fn now(&self) -> i64 {
+        self.link.clock_micros() + (self.latency * 1.0e6) as i64
+    }
+
+    /// Set the tempo of the session and the transport in beats per minute.
+    /// The tempo is sent to all peers.
+    pub fn set_tempo(&mut self, bpm: f64) {
+        self.transport.set_tempo(bpm);
+        let now = self.now();
+        self.link.capture_app_session_state(&mut self.state);
+        self.state.set_tempo(self.transport.tempo(), now);
+        self.link.commit_app_session_state(&self.state);
+    }
+
+    /// Capture the session state and adopt the session tempo into the transport.
+    /// Returns the tempo in beats per minute.
+    pub fn update(&mut self) -> f64 {
+        self.link.capture_app_session_state(&mut self.state);
+        self.transport.set_tempo(self.state.tempo());
+        self.transport.tempo()
+    }
+
+    /// Current beat of the session timeline as of the last update.
+    pub fn beat(&self) -> f64 {
+        self.state.beat_at_time(self.now(), self.quantum)
+    }
+
+    /// Current phase within the cycle, in beats from 0 to `quantum`, as of the last update.
+    pub fn phase(&self) -> f64 {
+        self.state.phase_at_time(self.now(), self.quantum)
+    }
+
+    /// Time in seconds until the start of the next cycle, as of the last update.
+    /// A cycle that starts now is 0 seconds away.
+    pub fn time_to_cycle(&self) -> f64 {
+        let now = self.now();
+        let phase = self.state.phase_at_time(now, self.quantum);
+        if phase <= 0.0 {
+            return 0.0;
+        }
+        let beat = self.state.beat_at_time(now, self.quantum);
+        let start = self.state.time_at_beat(beat - phase + self.quantum, self.quantum);
+        (start - now).max(0) as f64 * 1.0e-6
+    }
+
+    /// Update, then return the grid offset in seconds that aligns the quantization grid
+    /// of a sequencer currently at `time` seconds with the session cycle.
+    /// Set it with `set_grid_offset`, so that quantized events on a grid
+    /// that divides the cycle start on session beats.
+    /// Call this periodically, for example, once per block in the control thread.
+    pub fn sync(&mut self, time: f64) -> f64 {
+        self.update();
+        let cycle = self.transport.beats_to_seconds(self.quantum);
+        (time + self.time_to_cycle()).rem_euclid(cycle)
+    }
+}
@@ -0,0 +1,277 @@
+//! Looper with overdub and crossfaded loop boundaries.
+
+use super::audionode::*;
+use super::math::*;
+use super::*;
+use numeric_array::typenum::*;
+
+/// State of a looper. Used also as the setting of `Looper`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoopState {
+    /// Playback is stopped. The loop is retained.
+    #[default]
+    Stop,
+    /// Record a new loop, replacing any existing loop.
+    /// The loop length is fixed when recording ends.
+    Record,
+    /// Play back the loop.
+    Play,
+    /// Play back the loop while adding input to it.
+    Overdub,
+    /// Erase the loop and stop. When set, the looper moves to the `Stop` state.
+    Clear,
+}
+
+/// Looper. Records a loop from the input and plays it back, optionally overdubbing.
+/// When recording ends, recording continues briefly past the loop end
+/// and the extra material is crossfaded into the start of the loop, which makes
+/// loop boundaries seamless. Playback speed is variable; recording is always at normal speed.
+/// The output contains only the loop, not the input.
+/// Setting: loop state.
+/// The number of inputs is `N`, either `U1`, `U2` or `U3`.
+/// - Input 0: audio
+/// - Input 1 (optional): gate. Each rising edge steps through the states
+///   record, play, overdub, play, overdub, and so on.
+/// - Input 2 (optional): playback speed (1 = normal speed, negative = reverse)
+/// - Output 0: loop playback
+#[derive(Clone)]
+pub struct Looper<T: Float, N: Size<T>> {
+    /// Loop buffer, with room for the crossfade tail after the loop.
+    buffer: Vec<T>,
+    /// Maximum loop duration in seconds.
+    max_duration: f64,
+    /// Crossfade duration in seconds.
+    crossfade: f64,
+    /// Crossfade length in samples.
+    fade: usize,
+    /// Length of the loop in samples. Zero if there is no loop.
+    length: usize,
+    /// Number of samples of crossfade tail still to be recorded.
+    tail: usize,
+    /// Playback position in samples.
+    position: f64,
+    /// Playback speed.
+    speed: T,
+    state: LoopState,
+    gate: T,
+    sample_rate: f64,
+    _marker: core::marker::PhantomData<N>,
+}
+
+impl<T: Float, N: Size<T>> Looper<T, N> {
+    /// Create looper with room for a loop of `max_duration` seconds.
+    pub fn new(max_duration: f64) -> Self {
+        let mut node = Self {
+            buffer: Vec::new(),
+            max_duration,
+            crossfade: 0.01,
+            fade: 0,
+            length: 0,
+            tail: 0,
+            position: 0.0,
+            speed: T::one(),
+            state: LoopState::Stop,
+            gate: T::zero(),
+            sample_rate: DEFAULT_SR,
+            _marker: core::marker::PhantomData,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Current state.
+    #[inline]
+    pub fn state(&self) -> LoopState {
+        self.state
+    }
+
+    /// Length of the loop in seconds. Zero if there is no loop.
+    #[inline]
+    pub fn loop_duration(&self) -> f64 {
+        self.length as f64 / self.sample_rate
+    }
+
+    /// Playback speed (1 = normal speed).
+    #[inline]
+    pub fn speed(&self) -> T {
+        self.speed
+    }
+
+    /// Set playback speed (1 = normal speed, negative = reverse).
+    /// If the speed is supplied as an input, the input overrides this.
+    #[inline]
+    pub fn set_speed(&mut self, speed: T) {
+        self.speed = speed;
+    }
+
+    /// Crossfade duration at the loop boundary in seconds.
+    #[inline]
+    pub fn crossfade(&self) -> f64 {
+        self.crossfade
+    }
+
+    /// Set crossfade duration at the loop boundary in seconds (default is 0.01).
+    /// Changing the crossfade duration clears the loop.
+    pub fn set_crossfade(&mut self, crossfade: f64) {
+        self.crossfade = crossfade;
+        self.set_sample_rate(self.sample_rate);
+    }
+
+    /// Change state.
+    pub fn set_state(&mut self, state: LoopState) {
+        match state {
+            LoopState::Record => {
+                self.length = 0;
+                self.tail = 0;
+                self.position = 0.0;
+                self.state = LoopState::Record;
+            }
+            LoopState::Play | LoopState::Overdub => {
+                if self.state == LoopState::Record {
+                    self.close_loop();
+                }
+                self.state = if self.length > 0 {
+                    state
+                } else {
+                    LoopState::Stop
+                };
+            }
+            LoopState::Stop => {
+                if self.state == LoopState::Record {
+                    self.close_loop();
+                }
+                self.state = LoopState::Stop;
+            }
+            LoopState::Clear => {
+                self.length = 0;
+                self.tail = 0;
+                self.position = 0.0;
+                self.state = LoopState::Stop;
+            }
+        }
+    }
+
+    /// End recording and fix the loop length.
+    fn close_loop(&mut self) {
+        self.length = self.position as usize;
+        self.fade = min(self.fade_samples(), self.length / 2);
+        self.tail = self.fade;
+        self.position = 0.0;
+    }
+
+    /// Crossfade length in samples for the current sample rate.
+    fn fade_samples(&self) -> usize {
+        (self.crossfade * self.sample_rate).round() as usize
+    }
+
+    /// Read loop sample `i` with the crossfade applied.
+    #[inline]
+    fn at(&self, i: usize) -> T {
+        if i < self.fade {
+            let t = T::from_f64((i as f64 + 0.5) / self.fade as f64);
+            lerp(self.buffer[self.length + i], self.buffer[i], t)
+        } else {
+            self.buffer[i]
+        }
+    }
+
+    /// Advance gate and respond to rising edges.
+    #[inline]
+    fn gate(&mut self, gate: T) {
+        let half = T::from_f64(0.5);
+        if self.gate <= half && gate > half {
+            let next = match self.state {
+                LoopState::Record => LoopState::Play,
+                LoopState::Play => LoopState::Overdub,
+                LoopState::Overdub => LoopState::Play,
+                _ => {
+                    if self.length > 0 {
+                        LoopState::Play
+                    } else {
+                        LoopState::Record
+                    }
+                }
+            };
+            self.set_state(next);
+        }
+        self.gate = gate;
+    }
+}
+
+impl<T: Float, N: Size<T>> AudioNode for Looper<T, N> {
+    const ID: u64 = 103;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = U1;
+    type Setting = LoopState;
+
+    fn set(&mut self, state: Self::Setting) {
+        self.set_state(state);
+    }
+
+    fn reset(&mut self) {
+        self.set_state(LoopState::Clear);
+        self.gate = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        let capacity = (self.max_duration * sample_rate).ceil() as usize + self.fade_samples();
+        if self.buffer.len() != capacity {
+            self.buffer = vec![T::zero(); capacity];
+            self.set_state(LoopState::Clear);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if N::USIZE > 1 {
+            self.gate(input[1]);
+        }
+        if N::USIZE > 2 {
+            self.speed = input[2];
+        }
+        let x = input[0];
+        if self.state == LoopState::Record {
+            let i = self.position as usize;
+            self.buffer[i] = x;
+            self.position += 1.0;
+            if i + 1 + self.fade_samples() >= self.buffer.len() {
+                // The buffer is full.
+                self.set_state(LoopState::Play);
+            }
+            return [T::zero()].into();
+        }
+        if self.tail > 0 {
+            // Record the crossfade tail past the loop end.
+            let i = self.length + self.fade - self.tail;
+            self.buffer[i] = x;
+            self.tail -= 1;
+        }
+        if self.length == 0 || self.state == LoopState::Stop {
+            return [T::zero()].into();
+        }
+        let length = self.length as f64;
+        let i0 = self.position as usize;
+        let w = T::from_f64(self.position - i0 as f64);
+        let i1 = if i0 + 1 < self.length { i0 + 1 } else { 0 };
+        let output = lerp(self.at(i0), self.at(i1), w);
+        if self.state == LoopState::Overdub {
+            let value = self.buffer[i0] + x;
+            self.buffer[i0] = value;
+            if i0 < self.fade {
+                // Keep the crossfade tail in sync with the overdubbed start.
+                self.buffer[self.length + i0] += x;
+            }
+        }
+        self.position = (self.position + self.speed.to_f64()).rem_euclid(length);
+        if self.position >= length {
+            // Guard against rounding.
+            self.position = 0.0;
+        }
+        [output].into()
+    }
+}
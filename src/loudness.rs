@@ -0,0 +1,480 @@
+//! Level and loudness metering with lock-free readers.
+
+use super::audionode::*;
+use super::filter::*;
+use super::math::*;
+use super::signal::*;
+use super::wave::*;
+use super::*;
+use core::sync::atomic::{AtomicU64, Ordering};
+use alloc::sync::Arc;
+use duplicate::duplicate_item;
+
+/// Loudness of a mean square `energy` of K-weighted audio in LUFS (ITU-R BS.1770).
+#[inline]
+pub fn energy_lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * log10(energy)
+}
+
+/// Mean square energy of K-weighted audio with loudness `lufs` (ITU-R BS.1770).
+#[inline]
+pub fn lufs_energy(lufs: f64) -> f64 {
+    pow(10.0, (lufs + 0.691) / 10.0)
+}
+
+/// First stage of the ITU-R BS.1770 K-weighting filter, a high shelf.
+pub fn k_shelf(sample_rate: f64) -> BiquadCoefs<f64> {
+    let f0 = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = tan(PI * f0 / sample_rate);
+    let vh = pow(10.0, gain_db / 20.0);
+    let vb = pow(vh, 0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    BiquadCoefs::arbitrary(
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+    )
+}
+
+/// Second stage of the ITU-R BS.1770 K-weighting filter, a highpass.
+pub fn k_highpass(sample_rate: f64) -> BiquadCoefs<f64> {
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = tan(PI * f0 / sample_rate);
+    let a0 = 1.0 + k / q + k * k;
+    BiquadCoefs::arbitrary(
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+        1.0,
+        -2.0,
+        1.0,
+    )
+}
+
+/// Lock-free meter readings shared between a `LevelMeter` and any number of readers,
+/// for example, a UI thread. Loudness values are in LUFS and other levels are amplitudes.
+/// Silence reads as negative infinity LUFS.
+pub struct Levels {
+    peak: AtomicU64,
+    rms: AtomicU64,
+    momentary: AtomicU64,
+    short_term: AtomicU64,
+    integrated: AtomicU64,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Levels {
+    /// Create new meter readings.
+    pub fn new() -> Self {
+        let silence = f64::NEG_INFINITY.to_bits();
+        Self {
+            peak: AtomicU64::new(0),
+            rms: AtomicU64::new(0),
+            momentary: AtomicU64::new(silence),
+            short_term: AtomicU64::new(silence),
+            integrated: AtomicU64::new(silence),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn load(value: &AtomicU64) -> f64 {
+        f64::from_bits(value.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub(crate) fn store(value: &AtomicU64, x: f64) {
+        value.store(x.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Peak amplitude with release.
+    #[inline]
+    pub fn peak(&self) -> f64 {
+        Self::load(&self.peak)
+    }
+
+    /// RMS amplitude over the metering window.
+    #[inline]
+    pub fn rms(&self) -> f64 {
+        Self::load(&self.rms)
+    }
+
+    /// Momentary loudness over the last 400 ms in LUFS.
+    #[inline]
+    pub fn momentary(&self) -> f64 {
+        Self::load(&self.momentary)
+    }
+
+    /// Short-term loudness over the last 3 seconds in LUFS.
+    #[inline]
+    pub fn short_term(&self) -> f64 {
+        Self::load(&self.short_term)
+    }
+
+    /// Gated integrated loudness since the last reset in LUFS.
+    #[inline]
+    pub fn integrated(&self) -> f64 {
+        Self::load(&self.integrated)
+    }
+}
+
+/// Integrated loudness histogram lower bound in LUFS. This is also the absolute gate.
+const HISTOGRAM_MIN: f64 = -70.0;
+/// Integrated loudness histogram bins per LU.
+const HISTOGRAM_RESOLUTION: f64 = 10.0;
+/// Number of integrated loudness histogram bins, covering -70...+10 LUFS.
+const HISTOGRAM_BINS: usize = 800;
+/// Number of 100 ms blocks in the short-term loudness window.
+const SHORT_TERM_BLOCKS: usize = 30;
+/// Number of 100 ms blocks in the momentary loudness window.
+const MOMENTARY_BLOCKS: usize = 4;
+
+/// Level and loudness meter. Passes through input and publishes peak level,
+/// RMS level and ITU-R BS.1770 loudness (momentary, short-term and integrated)
+/// to a shared `Levels` structure. Processing is real-time safe.
+/// Peak and RMS levels are taken over all channels. For loudness, all channels
+/// are weighted equally, which matches BS.1770 for mono and stereo.
+/// The number of channels is `N`.
+/// - Input(s): signal
+/// - Output(s): signal
+#[derive(Clone)]
+pub struct LevelMeter<T: Float, N: Size<T>> {
+    levels: Arc<Levels>,
+    /// RMS window in seconds.
+    window: f64,
+    sample_rate: f64,
+    /// Per-sample peak release factor.
+    release: f64,
+    peak: f64,
+    /// Ring buffer of squared samples for the RMS window.
+    squares: Vec<f64>,
+    square_index: usize,
+    square_sum: f64,
+    /// K-weighting filter stages for each channel.
+    shelf: Vec<Biquad<f64, f64>>,
+    highpass: Vec<Biquad<f64, f64>>,
+    /// Length of a 100 ms loudness block in samples.
+    block_length: usize,
+    /// Position in the current loudness block.
+    block_position: usize,
+    /// K-weighted energy accumulated in the current block.
+    block_energy: f64,
+    /// Mean energies of recent blocks.
+    blocks: [f64; SHORT_TERM_BLOCKS],
+    /// Number of blocks processed so far.
+    block_count: usize,
+    /// Number of gating blocks in each histogram bin.
+    histogram_count: Vec<u64>,
+    /// Total energy of gating blocks in each histogram bin.
+    histogram_energy: Vec<f64>,
+    _marker: core::marker::PhantomData<(T, N)>,
+}
+
+impl<T: Float, N: Size<T>> LevelMeter<T, N> {
+    /// Create level meter publishing to `levels`. The RMS window is `window` seconds.
+    /// Peak level is released by half in `window` seconds.
+    pub fn new(levels: &Arc<Levels>, window: f64) -> Self {
+        let mut node = Self {
+            levels: levels.clone(),
+            window,
+            sample_rate: DEFAULT_SR,
+            release: 0.0,
+            peak: 0.0,
+            squares: Vec::new(),
+            square_index: 0,
+            square_sum: 0.0,
+            shelf: vec![Biquad::new(); N::USIZE],
+            highpass: vec![Biquad::new(); N::USIZE],
+            block_length: 1,
+            block_position: 0,
+            block_energy: 0.0,
+            blocks: [0.0; SHORT_TERM_BLOCKS],
+            block_count: 0,
+            histogram_count: vec![0; HISTOGRAM_BINS],
+            histogram_energy: vec![0.0; HISTOGRAM_BINS],
+            _marker: core::marker::PhantomData,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Shared meter readings.
+    #[inline]
+    pub fn levels(&self) -> &Arc<Levels> {
+        &self.levels
+    }
+
+    /// Mean energy of the last `n` blocks.
+    fn recent_energy(&self, n: usize) -> f64 {
+        let n = min(n, self.block_count);
+        if n == 0 {
+            return 0.0;
+        }
+        let mut energy = 0.0;
+        for i in 0..n {
+            energy += self.blocks[(self.block_count - 1 - i) % SHORT_TERM_BLOCKS];
+        }
+        energy / n as f64
+    }
+
+    /// Gated integrated loudness from the histogram.
+    fn integrated(&self) -> f64 {
+        // Absolute gate: the histogram contains only blocks above -70 LUFS.
+        let mut count = 0;
+        let mut energy = 0.0;
+        for i in 0..HISTOGRAM_BINS {
+            count += self.histogram_count[i];
+            energy += self.histogram_energy[i];
+        }
+        if count == 0 {
+            return f64::NEG_INFINITY;
+        }
+        // Relative gate is 10 LU below the loudness of blocks above the absolute gate.
+        let gate = energy_lufs(energy / count as f64) - 10.0;
+        let first = max(0.0, ((gate - HISTOGRAM_MIN) * HISTOGRAM_RESOLUTION).floor()) as usize;
+        let mut count = 0;
+        let mut energy = 0.0;
+        for i in first..HISTOGRAM_BINS {
+            count += self.histogram_count[i];
+            energy += self.histogram_energy[i];
+        }
+        if count == 0 {
+            return f64::NEG_INFINITY;
+        }
+        energy_lufs(energy / count as f64)
+    }
+
+    /// Finish a 100 ms loudness block.
+    fn end_block(&mut self) {
+        self.blocks[self.block_count % SHORT_TERM_BLOCKS] =
+            self.block_energy / self.block_length as f64;
+        self.block_count += 1;
+        self.block_energy = 0.0;
+        self.block_position = 0;
+        if self.block_count >= MOMENTARY_BLOCKS {
+            // Gating blocks are 400 ms long with 75% overlap.
+            let momentary = self.recent_energy(MOMENTARY_BLOCKS);
+            let lufs = energy_lufs(momentary);
+            if lufs > HISTOGRAM_MIN {
+                let bin = min(
+                    HISTOGRAM_BINS - 1,
+                    ((lufs - HISTOGRAM_MIN) * HISTOGRAM_RESOLUTION) as usize,
+                );
+                self.histogram_count[bin] += 1;
+                self.histogram_energy[bin] += momentary;
+            }
+        }
+        Levels::store(
+            &self.levels.momentary,
+            energy_lufs(self.recent_energy(MOMENTARY_BLOCKS)),
+        );
+        Levels::store(
+            &self.levels.short_term,
+            energy_lufs(self.recent_energy(SHORT_TERM_BLOCKS)),
+        );
+        Levels::store(&self.levels.integrated, self.integrated());
+    }
+
+    /// Process one frame without publishing peak and RMS levels.
+    #[inline]
+    fn meter(&mut self, input: &Frame<T, N>) {
+        let mut peak = 0.0;
+        let mut square = 0.0;
+        let mut energy = 0.0;
+        for i in 0..N::USIZE {
+            let x = input[i].to_f64();
+            peak = max(peak, abs(x));
+            square += x * x;
+            let y = self.shelf[i].tick(&[x].into())[0];
+            let z = self.highpass[i].tick(&[y].into())[0];
+            energy += z * z;
+        }
+        self.peak = max(peak, self.peak * self.release);
+        let square = square / N::USIZE as f64;
+        self.square_sum += square - self.squares[self.square_index];
+        self.squares[self.square_index] = square;
+        self.square_index += 1;
+        if self.square_index == self.squares.len() {
+            self.square_index = 0;
+            // Recompute the sum to prevent rounding errors from accumulating.
+            self.square_sum = self.squares.iter().sum();
+        }
+        self.block_energy += energy;
+        self.block_position += 1;
+        if self.block_position == self.block_length {
+            self.end_block();
+        }
+    }
+
+    /// Publish peak and RMS levels.
+    #[inline]
+    fn publish(&self) {
+        Levels::store(&self.levels.peak, self.peak);
+        Levels::store(
+            &self.levels.rms,
+            sqrt(max(0.0, self.square_sum) / self.squares.len() as f64),
+        );
+    }
+}
+
+impl<T: Float, N: Size<T>> AudioNode for LevelMeter<T, N> {
+    const ID: u64 = 104;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = N;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.peak = 0.0;
+        self.squares.fill(0.0);
+        self.square_index = 0;
+        self.square_sum = 0.0;
+        for i in 0..N::USIZE {
+            self.shelf[i].reset();
+            self.highpass[i].reset();
+        }
+        self.block_position = 0;
+        self.block_energy = 0.0;
+        self.blocks = [0.0; SHORT_TERM_BLOCKS];
+        self.block_count = 0;
+        self.histogram_count.fill(0);
+        self.histogram_energy.fill(0.0);
+        let silence = f64::NEG_INFINITY;
+        Levels::store(&self.levels.peak, 0.0);
+        Levels::store(&self.levels.rms, 0.0);
+        Levels::store(&self.levels.momentary, silence);
+        Levels::store(&self.levels.short_term, silence);
+        Levels::store(&self.levels.integrated, silence);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.release = pow(0.5, 1.0 / (self.window * sample_rate));
+        self.squares = vec![0.0; max(1, (self.window * sample_rate).round() as usize)];
+        self.block_length = max(1, (0.1 * sample_rate).round() as usize);
+        for i in 0..N::USIZE {
+            self.shelf[i].set_coefs(k_shelf(sample_rate));
+            self.highpass[i].set_coefs(k_highpass(sample_rate));
+        }
+        self.reset();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.meter(input);
+        self.publish();
+        input.clone()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for i in 0..size {
+            let frame = Frame::generate(|channel| input[channel][i]);
+            self.meter(&frame);
+        }
+        // For efficiency, publish levels only once per block.
+        self.publish();
+        for channel in 0..N::USIZE {
+            output[channel][..size].clone_from_slice(&input[channel][..size]);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
+
+#[allow(clippy::unnecessary_cast)]
+#[duplicate_item(
+    f48       Wave48;
+    [ f64 ]   [ Wave64 ];
+    [ f32 ]   [ Wave32 ];
+)]
+impl Wave48 {
+    /// Integrated loudness of the wave in LUFS according to ITU-R BS.1770,
+    /// with absolute and relative gating. All channels are weighted equally,
+    /// which matches the standard for mono and stereo.
+    /// Returns negative infinity if the wave is silent or shorter than 400 ms.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(48000.0, 5.0, &mut (sine_hz(1000.0) * db_amp(-20.0)));
+    /// assert!((wave.measure_lufs() + 23.0).abs() < 0.1);
+    /// ```
+    pub fn measure_lufs(&self) -> f64 {
+        let sample_rate = self.sample_rate();
+        let block_length = max(1, round(0.1 * sample_rate) as usize);
+        let blocks = self.len() / block_length;
+        // K-weighted energy of 100 ms blocks summed over channels.
+        let mut energy = vec![0.0; blocks];
+        for channel in 0..self.channels() {
+            let mut shelf = Biquad::<f64, f64>::new();
+            shelf.set_coefs(k_shelf(sample_rate));
+            let mut highpass = Biquad::<f64, f64>::new();
+            highpass.set_coefs(k_highpass(sample_rate));
+            for i in 0..blocks * block_length {
+                let x = self.at(channel, i).to_f64();
+                let z = highpass.filter_mono(shelf.filter_mono(x));
+                energy[i / block_length] += z * z;
+            }
+        }
+        // Gating blocks are 400 ms long with 75% overlap.
+        let gating: Vec<f64> = (3..blocks)
+            .map(|i| energy[i - 3..=i].iter().sum::<f64>() / (4 * block_length) as f64)
+            .filter(|&x| energy_lufs(x) > -70.0)
+            .collect();
+        if gating.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+        // Relative gate is 10 LU below the loudness of blocks above the absolute gate.
+        let gate = energy_lufs(gating.iter().sum::<f64>() / gating.len() as f64) - 10.0;
+        let (count, total) = gating
+            .iter()
+            .filter(|&&x| energy_lufs(x) > gate)
+            .fold((0, 0.0), |(count, total), x| (count + 1, total + x));
+        if count == 0 {
+            return f64::NEG_INFINITY;
+        }
+        energy_lufs(total / count as f64)
+    }
+
+    /// Scales the wave to an integrated loudness of `target` LUFS (ITU-R BS.1770),
+    /// for example, -14 or -23 LUFS. Does nothing if the loudness cannot be measured.
+    /// Peaks are not limited, so amplifying a wave may take it outside -1...1.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::render(44100.0, 5.0, &mut (pink() * 0.1));
+    /// wave.normalize_lufs(-23.0);
+    /// assert!((wave.measure_lufs() + 23.0).abs() < 0.01);
+    /// ```
+    pub fn normalize_lufs(&mut self, target: f64) {
+        let lufs = self.measure_lufs();
+        if !lufs.is_finite() {
+            return;
+        }
+        let z = db_amp(target - lufs) as f48;
+        for channel in 0..self.channels() {
+            for i in 0..self.len() {
+                self.set(channel, i, self.at(channel, i) * z);
+            }
+        }
+    }
+}
@@ -113,15 +113,189 @@ pub fn atan<T: Real>(x: T) -> T {
 }
 
 /// sqrt(2)
-pub const SQRT_2: f64 = std::f64::consts::SQRT_2;
+pub const SQRT_2: f64 = core::f64::consts::SQRT_2;
 /// pi
-pub const PI: f64 = std::f64::consts::PI;
+pub const PI: f64 = core::f64::consts::PI;
 /// tau = 2 * pi
-pub const TAU: f64 = std::f64::consts::TAU;
+pub const TAU: f64 = core::f64::consts::TAU;
 /// log(2)
-pub const LN_2: f64 = std::f64::consts::LN_2;
+pub const LN_2: f64 = core::f64::consts::LN_2;
 /// log(10)
-pub const LN_10: f64 = std::f64::consts::LN_10;
+pub const LN_10: f64 = core::f64::consts::LN_10;
+
+/// Fast approximate sine function. Uses a minimax polynomial of degree 9
+/// after range reduction. The maximum absolute error is 1.3e-8,
+/// plus rounding error of the sample type, for arguments of moderate size.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!((sin_approx(1.0) - sin(1.0)).abs() < 1.0e-7);
+/// ```
+#[inline]
+pub fn sin_approx<T: Float>(x: T) -> T {
+    let pi = T::from_f64(PI);
+    let half_pi = T::from_f64(PI * 0.5);
+    // Reduce to -pi...pi and then fold to -pi/2...pi/2.
+    let r = x - T::from_f64(TAU) * round(x * T::from_f64(1.0 / TAU));
+    let r = if r > half_pi {
+        pi - r
+    } else if r < -half_pi {
+        -pi - r
+    } else {
+        r
+    };
+    let r2 = r * r;
+    r * (T::from_f64(0.999999999158245)
+        + r2 * (T::from_f64(-0.16666662483617917)
+            + r2 * (T::from_f64(0.008333130778218397)
+                + r2 * (T::from_f64(-0.00019813423871460514)
+                    + r2 * T::from_f64(2.612538035837135e-6)))))
+}
+
+/// Fast approximate cosine function. The maximum absolute error is 1.3e-8,
+/// plus rounding error of the sample type, for arguments of moderate size.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!((cos_approx(1.0) - cos(1.0)).abs() < 1.0e-7);
+/// ```
+#[inline]
+pub fn cos_approx<T: Float>(x: T) -> T {
+    sin_approx(x + T::from_f64(PI * 0.5))
+}
+
+/// Fast approximate power of 2 function. Computed in single precision
+/// by scaling a polynomial of degree 5 with exponent bits.
+/// The maximum relative error is 1.1e-7, plus single precision rounding error.
+/// The argument is clamped to -126...127.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!((exp2_approx(10.5) / exp2(10.5) - 1.0).abs() < 1.0e-6);
+/// ```
+#[inline]
+pub fn exp2_approx<T: Float>(x: T) -> T {
+    let x = x.to_f32().clamp(-126.0, 127.0);
+    let i = floor(x);
+    let f = x - i;
+    let p = 0.9999999
+        + f * (0.69315475
+            + f * (0.24013971 + f * (0.055866245 + f * (0.008942829 + f * 0.0018964611))));
+    let scale = f32::from_bits(((i as i32 + 127) as u32) << 23);
+    T::from_f32(p * scale)
+}
+
+/// Fast approximate exponential function.
+/// The maximum relative error is 1.1e-7, plus single precision rounding error.
+/// The argument is clamped to -87.3...88.0.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!((exp_approx(2.0) / exp(2.0) - 1.0).abs() < 1.0e-6);
+/// ```
+#[inline]
+pub fn exp_approx<T: Float>(x: T) -> T {
+    exp2_approx(x * T::from_f64(core::f64::consts::LOG2_E))
+}
+
+/// Fast approximate binary logarithm. Computed in single precision
+/// from exponent bits and a polynomial of degree 7 of the mantissa.
+/// The maximum absolute error is 4e-7, plus single precision rounding error,
+/// for normal positive arguments.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!((log2_approx(10.0) - log2(10.0)).abs() < 1.0e-6);
+/// ```
+#[inline]
+pub fn log2_approx<T: Float>(x: T) -> T {
+    let bits = x.to_f32().to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let t = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000) - 1.0;
+    let p = t
+        * (1.4426678
+            + t * (-0.72058547
+                + t * (0.47355342
+                    + t * (-0.325902 + t * (0.19429432 + t * (-0.07955773 + t * 0.015529918))))));
+    T::from_f32(exponent as f32 + p)
+}
+
+/// Fast approximate hyperbolic tangent. Uses a rational approximation
+/// from the continued fraction of `tanh`. The maximum absolute error is 1e-4.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// assert!((tanh_approx(0.5) - tanh(0.5)).abs() < 1.0e-4);
+/// assert!(tanh_approx(10.0) == 1.0);
+/// ```
+#[inline]
+pub fn tanh_approx<T: Float>(x: T) -> T {
+    // The approximation reaches one before 5, so clamping here avoids overflow.
+    let x = clamp(T::new(-5), T::new(5), x);
+    let x2 = x * x;
+    let y = x * (T::new(135135) + x2 * (T::new(17325) + x2 * (T::new(378) + x2)))
+        / (T::new(135135) + x2 * (T::new(62370) + x2 * (T::new(3150) + x2 * T::new(28))));
+    clamp11(y)
+}
+
+/// Sine for oscillators. Approximated with `sin_approx` if the `fast_math` feature is enabled.
+#[inline]
+pub fn osc_sin<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast_math")]
+    {
+        sin_approx(x)
+    }
+    #[cfg(not(feature = "fast_math"))]
+    {
+        sin(x)
+    }
+}
+
+/// Cosine for oscillators. Approximated with `cos_approx` if the `fast_math` feature is enabled.
+#[inline]
+pub fn osc_cos<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast_math")]
+    {
+        cos_approx(x)
+    }
+    #[cfg(not(feature = "fast_math"))]
+    {
+        cos(x)
+    }
+}
+
+/// Exponential for envelopes. Approximated with `exp_approx` if the `fast_math` feature is enabled.
+#[inline]
+pub fn env_exp<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast_math")]
+    {
+        exp_approx(x)
+    }
+    #[cfg(not(feature = "fast_math"))]
+    {
+        exp(x)
+    }
+}
+
+/// Hyperbolic tangent for shapers.
+/// Approximated with `tanh_approx` if the `fast_math` feature is enabled.
+#[inline]
+pub fn shape_tanh<T: Real>(x: T) -> T {
+    #[cfg(feature = "fast_math")]
+    {
+        tanh_approx(x)
+    }
+    #[cfg(not(feature = "fast_math"))]
+    {
+        tanh(x)
+    }
+}
 
 /// Clamps `x` between `x0` and `x1`.
 #[inline]
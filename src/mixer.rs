@@ -0,0 +1,522 @@
+//! Crossfading and mixing.
+
+use super::audionode::*;
+use super::buffer::*;
+use super::math::*;
+use super::shared::*;
+use super::signal::*;
+use super::*;
+use core::marker::PhantomData;
+use core::ops::Add;
+use numeric_array::typenum::*;
+
+/// Equal-power gains `(a, b)` for crossfade amount `mix` in 0...1.
+#[inline]
+pub fn equal_power<T: Float>(mix: T) -> (T, T) {
+    let angle = clamp01(mix.to_f64()) * PI * 0.5;
+    (T::from_f64(cos(angle)), T::from_f64(sin(angle)))
+}
+
+/// Equal-power crossfade between two nodes with the same inputs and outputs.
+/// Both nodes receive the same input. The extra input is the crossfade amount
+/// in 0...1, where 0 selects the first node and 1 selects the second.
+/// - Inputs: from the nodes, followed by crossfade amount
+/// - Outputs: crossfaded outputs of the nodes
+#[derive(Clone)]
+pub struct XFade<T, X, Y>
+where
+    T: Float,
+{
+    _marker: PhantomData<T>,
+    x: X,
+    y: Y,
+    buffer: Buffer<T>,
+}
+
+impl<T, X, Y> XFade<T, X, Y>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    Y: AudioNode<Sample = T, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<T> + Add<U1>,
+    X::Outputs: Size<T>,
+    <X::Inputs as Add<U1>>::Output: Size<T>,
+{
+    pub fn new(x: X, y: Y) -> Self {
+        let mut node = XFade {
+            _marker: PhantomData,
+            x,
+            y,
+            buffer: Buffer::new(),
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Access the first node.
+    #[inline]
+    pub fn left_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Access the first node.
+    #[inline]
+    pub fn left(&self) -> &X {
+        &self.x
+    }
+
+    /// Access the second node.
+    #[inline]
+    pub fn right_mut(&mut self) -> &mut Y {
+        &mut self.y
+    }
+
+    /// Access the second node.
+    #[inline]
+    pub fn right(&self) -> &Y {
+        &self.y
+    }
+}
+
+impl<T, X, Y> AudioNode for XFade<T, X, Y>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    Y: AudioNode<Sample = T, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<T> + Add<U1>,
+    X::Outputs: Size<T>,
+    <X::Inputs as Add<U1>>::Output: Size<T>,
+{
+    const ID: u64 = 121;
+    type Sample = T;
+    type Inputs = <X::Inputs as Add<U1>>::Output;
+    type Outputs = X::Outputs;
+    type Setting = Side<X::Setting, Y::Setting>;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            Side::Left(left) => self.x.set(left),
+            Side::Right(right) => self.y.set(right),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.y.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+        self.y.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let inner = Frame::generate(|i| input[i]);
+        let (a, b) = equal_power(input[X::Inputs::USIZE]);
+        let output_x = self.x.tick(&inner);
+        let output_y = self.y.tick(&inner);
+        output_x * Frame::splat(a) + output_y * Frame::splat(b)
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        let inputs = X::Inputs::USIZE;
+        self.x.process(size, &input[..inputs], output);
+        self.y
+            .process(size, &input[..inputs], self.buffer.get_mut(self.outputs()));
+        for channel in 0..self.outputs() {
+            for ((o, y), mix) in output[channel][..size]
+                .iter_mut()
+                .zip(self.buffer.at(channel)[..size].iter())
+                .zip(input[inputs][..size].iter())
+            {
+                let (a, b) = equal_power(*mix);
+                *o = *o * a + *y * b;
+            }
+        }
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.y.ping(probe, self.x.ping(probe, hash.hash(Self::ID)))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let inner = copy_signal_frame(input, 0, X::Inputs::USIZE);
+        let mut signal_x = self.x.route(&inner, frequency);
+        let signal_y = self.y.route(&inner, frequency);
+        for i in 0..Self::Outputs::USIZE {
+            signal_x[i] = signal_x[i].combine_nonlinear(signal_y[i], 0.0);
+        }
+        signal_x
+    }
+
+    fn allocate(&mut self) {
+        self.buffer.allocate(self.outputs());
+        self.x.allocate();
+        self.y.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe(), self.y.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = self.x.parameters();
+        parameters.extend(self.y.parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count() + self.y.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        pair_parameter(&self.x, &self.y, index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        set_pair_parameter(&mut self.x, &mut self.y, index, value);
+    }
+}
+
+/// Matrix mixer with `N` inputs and `M` outputs. Each output is a weighted sum
+/// of the inputs. Gains can be changed at runtime; changes are smoothed.
+/// Initially, input `i` is routed to output `i` with unity gain.
+/// Setting: (output, input, gain).
+/// - Inputs: `N` signals
+/// - Outputs: `M` mixes
+#[derive(Clone)]
+pub struct MatrixMixer<N: Size<T>, M: Size<T>, T: Float> {
+    /// Target gains in output major order.
+    gains: Vec<T>,
+    /// Smoothed gains in output major order.
+    current: Vec<T>,
+    /// Smoothing time in seconds.
+    smoothing: f64,
+    /// Smoothing coefficient per sample.
+    coeff: T,
+    _marker: PhantomData<(N, M)>,
+}
+
+impl<N: Size<T>, M: Size<T>, T: Float> MatrixMixer<N, M, T> {
+    /// Create matrix mixer. Gain changes are smoothed with a time constant of `smoothing` seconds.
+    pub fn new(smoothing: f64) -> Self {
+        let gains: Vec<T> = (0..M::USIZE * N::USIZE)
+            .map(|k| {
+                if k / N::USIZE == k % N::USIZE {
+                    T::one()
+                } else {
+                    T::zero()
+                }
+            })
+            .collect();
+        let mut node = Self {
+            current: gains.clone(),
+            gains,
+            smoothing,
+            coeff: T::one(),
+            _marker: PhantomData,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Gain from `input` to `output`.
+    #[inline]
+    pub fn gain(&self, output: usize, input: usize) -> T {
+        self.gains[output * N::USIZE + input]
+    }
+
+    /// Set gain from `input` to `output`. The gain changes smoothly.
+    #[inline]
+    pub fn set_gain(&mut self, output: usize, input: usize, gain: T) {
+        assert!(output < M::USIZE && input < N::USIZE);
+        self.gains[output * N::USIZE + input] = gain;
+    }
+
+    /// Set all gains at once from `gains` in output major order. Gains change smoothly.
+    pub fn set_gains(&mut self, gains: &[T]) {
+        self.gains.copy_from_slice(gains);
+    }
+}
+
+impl<N: Size<T>, M: Size<T>, T: Float> AudioNode for MatrixMixer<N, M, T> {
+    const ID: u64 = 122;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = M;
+    type Setting = (usize, usize, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (output, input, gain) = setting;
+        self.set_gain(output, input, gain);
+    }
+
+    fn reset(&mut self) {
+        self.current.copy_from_slice(&self.gains);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.coeff = if self.smoothing > 0.0 {
+            T::from_f64(1.0 - exp(-1.0 / (self.smoothing * sample_rate)))
+        } else {
+            T::one()
+        };
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        for (current, gain) in self.current.iter_mut().zip(self.gains.iter()) {
+            *current += (*gain - *current) * self.coeff;
+        }
+        Frame::generate(|o| {
+            let row = &self.current[o * N::USIZE..(o + 1) * N::USIZE];
+            row.iter()
+                .zip(input.iter())
+                .fold(T::zero(), |sum, (gain, x)| sum + *gain * *x)
+        })
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for (o, signal) in output.iter_mut().enumerate() {
+            let mut sum = Signal::Value(0.0);
+            for i in 0..N::USIZE {
+                let gain = self.gain(o, i).to_f64();
+                if gain != 0.0 {
+                    sum = sum.combine_linear(input[i].scale(gain), 0.0, |x, y| x + y, |x, y| x + y);
+                }
+            }
+            *signal = sum;
+        }
+        output
+    }
+}
+
+/// Gains at or below this level in dB are treated as negative infinity, that is, silence.
+pub const MUTE_DB: f64 = -100.0;
+
+/// Convert level `db` in decibels to amplitude. Levels at or below `MUTE_DB` map to zero.
+#[inline]
+pub fn db_gain(db: f64) -> f64 {
+    if db <= MUTE_DB {
+        0.0
+    } else {
+        db_amp(db)
+    }
+}
+
+/// Smoothed gain in decibels. The gain is either set directly (`gain_db`, `trim_db`)
+/// or read from a shared variable (`fader`). Gain changes are smoothed in the linear domain,
+/// so a mute at `-inf` dB fades out and then outputs exact zeros.
+/// Setting: gain in dB.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+#[derive(Clone)]
+pub struct Gain<T: Atomic> {
+    /// Target gain in dB.
+    db: f64,
+    /// Gain range in dB.
+    min_db: f64,
+    max_db: f64,
+    /// Name of the gain parameter.
+    name: &'static str,
+    /// Shared variable that overrides the target gain in dB, if any.
+    shared: Option<Shared<T>>,
+    /// Target linear gain.
+    target: T,
+    /// Smoothed linear gain.
+    gain: T,
+    /// Smoothing time in seconds.
+    smoothing: f64,
+    /// Smoothing coefficient per sample.
+    coeff: T,
+}
+
+impl<T: Atomic> Gain<T> {
+    /// Create gain of `db` decibels, limited to `min_db`...`max_db`.
+    /// Changes are smoothed with a time constant of `smoothing` seconds.
+    /// The parameter is called `name`.
+    pub fn new(db: f64, min_db: f64, max_db: f64, smoothing: f64, name: &'static str) -> Self {
+        let db = clamp(min_db, max_db, db);
+        let target = T::from_f64(db_gain(db));
+        let mut node = Self {
+            db,
+            min_db,
+            max_db,
+            name,
+            shared: None,
+            target,
+            gain: target,
+            smoothing,
+            coeff: T::one(),
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Create gain that follows `shared` gain in dB, limited to `min_db`...`max_db`.
+    /// Changes are smoothed with a time constant of `smoothing` seconds.
+    /// The parameter is called `name`.
+    pub fn new_shared(
+        shared: &Shared<T>,
+        min_db: f64,
+        max_db: f64,
+        smoothing: f64,
+        name: &'static str,
+    ) -> Self {
+        let mut node = Self::new(shared.value().to_f64(), min_db, max_db, smoothing, name);
+        node.shared = Some(shared.clone());
+        node
+    }
+
+    /// Target gain in dB.
+    #[inline]
+    pub fn db(&self) -> f64 {
+        self.db
+    }
+
+    /// Set target gain in dB. The gain changes smoothly.
+    /// If the gain follows a shared variable, the variable is set.
+    #[inline]
+    pub fn set_db(&mut self, db: f64) {
+        if let Some(shared) = &self.shared {
+            shared.set(T::from_f64(db));
+        }
+        self.retarget(db);
+    }
+
+    /// Current smoothed linear gain.
+    #[inline]
+    pub fn gain(&self) -> T {
+        self.gain
+    }
+
+    /// Set target gain without touching the shared variable.
+    #[inline]
+    fn retarget(&mut self, db: f64) {
+        let db = clamp(self.min_db, self.max_db, db);
+        if db != self.db {
+            self.db = db;
+            self.target = T::from_f64(db_gain(db));
+        }
+    }
+
+    /// Read the shared gain, if any.
+    #[inline]
+    fn update(&mut self) {
+        if let Some(shared) = &self.shared {
+            let db = shared.value().to_f64();
+            self.retarget(db);
+        }
+    }
+
+    /// Advance smoothed gain by one sample.
+    #[inline]
+    fn advance(&mut self) -> T {
+        self.gain += (self.target - self.gain) * self.coeff;
+        if abs(self.target - self.gain) <= T::from_f64(1.0e-6) {
+            self.gain = self.target;
+        }
+        self.gain
+    }
+}
+
+impl<T: Atomic> AudioNode for Gain<T> {
+    const ID: u64 = 162;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_db(setting.to_f64());
+    }
+
+    fn reset(&mut self) {
+        self.update();
+        self.gain = self.target;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.coeff = if self.smoothing > 0.0 {
+            T::from_f64(1.0 - exp(-1.0 / (self.smoothing * sample_rate)))
+        } else {
+            T::one()
+        };
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.update();
+        [input[0] * self.advance()].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.update();
+        if self.gain == self.target {
+            let gain = self.gain;
+            for (o, x) in output[0][..size].iter_mut().zip(input[0][..size].iter()) {
+                *o = *x * gain;
+            }
+        } else {
+            for (o, x) in output[0][..size].iter_mut().zip(input[0][..size].iter()) {
+                *o = *x * self.advance();
+            }
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].scale(self.target.to_f64());
+        output
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            self.name,
+            ParameterUnit::Decibels,
+            max(MUTE_DB, self.min_db),
+            self.max_db,
+            0.0,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.db,
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_db(value),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+}
@@ -4,6 +4,7 @@ use super::audionode::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
+use num_complex::Complex64;
 use numeric_array::*;
 
 /// Moog resonant lowpass filter.
@@ -15,7 +16,7 @@ use numeric_array::*;
 /// - Output 0: filtered signal
 #[derive(Default, Clone)]
 pub struct Moog<T: Float, F: Real, N: Size<T>> {
-    _marker: std::marker::PhantomData<(T, N)>,
+    _marker: core::marker::PhantomData<(T, N)>,
     q: F,
     cutoff: F,
     sample_rate: F,
@@ -114,3 +115,132 @@ impl<T: Float, F: Real, N: Size<T>> AudioNode for Moog<T, F, N> {
         output
     }
 }
+
+/// Zero-delay feedback Moog ladder filter built from topology-preserving
+/// transform one-pole stages. The feedback loop is solved exactly each sample,
+/// which keeps tuning accurate and the filter stable under fast cutoff modulation.
+/// Passband gain is compensated as resonance is increased.
+/// The input to the ladder is saturated; `drive` sets the gain into the saturator.
+/// Setting: (cutoff, Q).
+/// The number of inputs is `N`, either `U1` or `U3`.
+/// - Input 0: input signal
+/// - Input 1 (optional): cutoff frequency (Hz)
+/// - Input 2 (optional): Q in 0...1 (self-oscillation starts at 1)
+/// - Output 0: filtered signal
+#[derive(Default, Clone)]
+pub struct MoogZdf<T: Float, F: Real, N: Size<T>> {
+    _marker: core::marker::PhantomData<(T, N)>,
+    q: F,
+    cutoff: F,
+    drive: F,
+    sample_rate: F,
+    /// One-pole stage gain g / (1 + g).
+    g: F,
+    /// Feedback amount.
+    k: F,
+    s: [F; 4],
+}
+
+impl<T: Float, F: Real, N: Size<T>> MoogZdf<T, F, N> {
+    pub fn new(sample_rate: f64, cutoff: F, q: F, drive: F) -> Self {
+        let mut node = Self {
+            sample_rate: convert(sample_rate),
+            drive,
+            ..Self::default()
+        };
+        node.set_cutoff_q(cutoff, q);
+        node
+    }
+
+    /// Set cutoff frequency (in Hz) and Q.
+    /// This has no effect if the filter has cutoff and Q inputs.
+    #[inline]
+    pub fn set_cutoff_q(&mut self, cutoff: F, q: F) {
+        self.cutoff = cutoff;
+        self.q = q;
+        let cutoff = clamp(F::zero(), self.sample_rate * F::from_f64(0.49), cutoff);
+        let g = tan(F::from_f64(PI) * cutoff / self.sample_rate);
+        self.g = g / (F::one() + g);
+        self.k = F::new(4) * max(F::zero(), q);
+    }
+
+    /// Set input drive. Values above 1 add saturation.
+    #[inline]
+    pub fn set_drive(&mut self, drive: F) {
+        self.drive = drive;
+    }
+
+    /// Input drive.
+    #[inline]
+    pub fn drive(&self) -> F {
+        self.drive
+    }
+}
+
+impl<T: Float, F: Real, N: Size<T>> AudioNode for MoogZdf<T, F, N> {
+    const ID: u64 = 95;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = typenum::U1;
+    type Setting = (F, F);
+
+    fn set(&mut self, (cutoff, q): Self::Setting) {
+        self.set_cutoff_q(cutoff, q);
+    }
+
+    fn reset(&mut self) {
+        self.s = [F::zero(); 4];
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = convert(sample_rate);
+        self.set_cutoff_q(self.cutoff, self.q);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if N::USIZE > 1 {
+            let cutoff = convert(input[1]);
+            let q = convert(input[2]);
+            if cutoff != self.cutoff || q != self.q {
+                self.set_cutoff_q(cutoff, q);
+            }
+        }
+        let g = self.g;
+        let g2 = g * g;
+        // For small signals, the ladder output is g^4 * drive * u + sigma,
+        // where u is the ladder input and sigma is the contribution of the stage states.
+        let a = F::one() - g;
+        let sigma = a * (g2 * g * self.s[0] + g2 * self.s[1] + g * self.s[2] + self.s[3]);
+        let x: F = convert(input[0]);
+        let u =
+            (x * (F::one() + self.k) - self.k * sigma) / (F::one() + self.k * self.drive * g2 * g2);
+        let mut y = tanh(u * self.drive);
+        for s in self.s.iter_mut() {
+            let v = (y - *s) * g;
+            y = v + *s;
+            *s = y + v;
+        }
+        [convert(y)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        // Small signal response.
+        let g = tan(
+            PI * min(self.cutoff.to_f64(), self.sample_rate.to_f64() * 0.49)
+                / self.sample_rate.to_f64(),
+        );
+        let k = self.k.to_f64();
+        let drive = self.drive.to_f64();
+        let z = Complex64::from_polar(1.0, -frequency * TAU / self.sample_rate.to_f64());
+        let stage = g * (1.0 + z) / ((1.0 + g) + (g - 1.0) * z);
+        let ladder = stage * stage * stage * stage;
+        let response = ladder * drive * (1.0 + k) / (1.0 + k * drive * ladder);
+        output[0] = input[0].filter(0.0, |r| r * response);
+        output
+    }
+}
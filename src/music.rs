@@ -0,0 +1,519 @@
+//! Horizontal re-sequencing of music sections with a state machine of transitions.
+
+use super::audiounit::*;
+use super::math::*;
+use super::sequencer::Fade;
+use super::signal::*;
+use super::wave::*;
+use super::*;
+use duplicate::duplicate_item;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
+
+/// Maximum number of sections fading out simultaneously.
+const MUSIC_TAILS: usize = 8;
+
+/// Value of the shared section index when nothing is playing.
+const NO_SECTION: usize = usize::MAX;
+
+/// Where a section may be left for another.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Boundary {
+    /// Leave at the next bar line of the section.
+    Bar,
+    /// Leave at the end of the section.
+    #[default]
+    End,
+}
+
+/// A transition rule between two sections.
+#[derive(Clone)]
+struct Rule {
+    from: usize,
+    to: usize,
+    boundary: Boundary,
+    /// Optional transition section played in between.
+    via: Option<usize>,
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ];
+)]
+/// A music section.
+#[derive(Clone)]
+struct Section48 {
+    wave: Arc<Wave48>,
+    /// Bar length in samples, or zero if the section has no bar lines.
+    bar: f64,
+    /// Section that follows when this one ends, if any.
+    next: Option<usize>,
+}
+
+#[derive(Clone, Default)]
+enum MusicMessage {
+    #[default]
+    Nothing,
+    /// Start playing a section immediately.
+    Start(usize),
+    /// Move to a section following the transition rules.
+    Goto(usize),
+    /// Fade out over the time (in seconds) and stop.
+    Stop(f64),
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48       MusicGraph48       MusicGraphBackend48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ]   [ MusicGraph64 ]   [ MusicGraphBackend64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ]   [ MusicGraph32 ]   [ MusicGraphBackend32 ];
+)]
+/// Frontend for a music graph. Named sections (intro, loops, transitions, outro)
+/// are connected by rules that say where and how one section may be left for another.
+/// The backend makes the transitions with sample accuracy at bar lines or section ends,
+/// so they land on the beat regardless of when the frontend asks for them.
+pub struct MusicGraph48 {
+    channels: usize,
+    sections: Vec<Section48>,
+    names: HashMap<String, usize>,
+    rules: Vec<Rule>,
+    fade: Fade,
+    fade_time: f64,
+    /// Index of the section playing in the backend.
+    current: Arc<AtomicUsize>,
+    sender: Option<Sender<MusicMessage>>,
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48       MusicGraph48       MusicGraphBackend48       MusicVoice48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ]   [ MusicGraph64 ]   [ MusicGraphBackend64 ]   [ MusicVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ]   [ MusicGraph32 ]   [ MusicGraphBackend32 ]   [ MusicVoice32 ];
+)]
+impl MusicGraph48 {
+    /// Create an empty music graph with `channels` outputs.
+    /// Waves with fewer channels are repeated cyclically across the outputs.
+    pub fn new(channels: usize) -> Self {
+        Self {
+            channels,
+            sections: Vec::new(),
+            names: HashMap::new(),
+            rules: Vec::new(),
+            fade: Fade::Smooth,
+            fade_time: 0.01,
+            current: Arc::new(AtomicUsize::new(NO_SECTION)),
+            sender: None,
+        }
+    }
+
+    /// Number of outputs.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Add section `name` playing `wave`. Bar lines are `bar` seconds apart;
+    /// zero `bar` means the section can only be left at its end.
+    /// For bars in beats, convert with `Transport::beats_to_seconds`.
+    /// The section loops until another is requested (see `set_next`).
+    /// Returns the index of the section.
+    pub fn add_section(&mut self, name: &str, wave: &Arc<Wave48>, bar: f64) -> usize {
+        assert!(!self.has_backend());
+        assert!(wave.channels() > 0 && !wave.is_empty());
+        assert!(!self.names.contains_key(name), "Duplicate section name.");
+        let index = self.sections.len();
+        self.sections.push(Section48 {
+            wave: wave.clone(),
+            bar: max(0.0, bar * wave.sample_rate()),
+            next: Some(index),
+        });
+        self.names.insert(name.to_string(), index);
+        index
+    }
+
+    /// Set the section that plays when `section` ends and nothing else has been requested.
+    /// `None` stops playback at the end of the section, as for an outro.
+    pub fn set_next(&mut self, section: &str, next: Option<&str>) {
+        assert!(!self.has_backend());
+        let next = next.map(|name| self.index(name));
+        let section = self.index(section);
+        self.sections[section].next = next;
+    }
+
+    /// Add a rule: when `to` is requested while `from` is playing,
+    /// leave `from` at the next `boundary`, optionally playing the transition section `via` first.
+    /// Without a rule, sections are left at their end.
+    pub fn add_rule(&mut self, from: &str, to: &str, boundary: Boundary, via: Option<&str>) {
+        assert!(!self.has_backend());
+        let rule = Rule {
+            from: self.index(from),
+            to: self.index(to),
+            boundary,
+            via: via.map(|name| self.index(name)),
+        };
+        self.rules.retain(|x| x.from != rule.from || x.to != rule.to);
+        self.rules.push(rule);
+    }
+
+    /// Set the fade curve and the fade out time in seconds of sections that are left at a bar line.
+    /// The next section always starts at full level on the boundary. The default is 10 ms.
+    pub fn set_fade(&mut self, fade: Fade, fade_time: f64) {
+        assert!(!self.has_backend());
+        self.fade = fade;
+        self.fade_time = fade_time;
+    }
+
+    /// Index of section `name`. Panics if there is no such section.
+    pub fn index(&self, name: &str) -> usize {
+        match self.names.get(name) {
+            Some(index) => *index,
+            None => panic!("Unknown section name."),
+        }
+    }
+
+    /// Name of the section playing in the backend, if any.
+    pub fn section(&self) -> Option<&str> {
+        let current = self.current.load(Ordering::Relaxed);
+        self.names
+            .iter()
+            .find(|(_, index)| **index == current)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Create the backend, which is an audio unit that plays the graph.
+    /// Sections and rules can no longer be changed after this.
+    /// This can be called only once for a graph.
+    pub fn backend(&mut self) -> MusicGraphBackend48 {
+        assert!(!self.has_backend());
+        let (sender, receiver) = channel(1024);
+        self.sender = Some(sender);
+        MusicGraphBackend48 {
+            channels: self.channels,
+            sections: self.sections.clone(),
+            rules: self.rules.clone(),
+            fade: self.fade.clone(),
+            fade_time: self.fade_time,
+            sample_rate: DEFAULT_SR,
+            voice: None,
+            tails: Vec::with_capacity(MUSIC_TAILS),
+            target: None,
+            current: self.current.clone(),
+            receiver,
+        }
+    }
+
+    /// Returns whether this graph has a backend.
+    pub fn has_backend(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Send `message` to the backend.
+    fn send(&mut self, message: MusicMessage) {
+        if let Some(sender) = &self.sender {
+            if sender.try_send(message).is_ok() {}
+        }
+    }
+
+    /// Start playing section `name` immediately, fading out anything playing.
+    pub fn start(&mut self, name: &str) {
+        let index = self.index(name);
+        self.send(MusicMessage::Start(index));
+    }
+
+    /// Move to section `name` following the transition rules.
+    /// A later request replaces an earlier one that has not been reached yet.
+    pub fn goto(&mut self, name: &str) {
+        let index = self.index(name);
+        self.send(MusicMessage::Goto(index));
+    }
+
+    /// Fade out over `fade_time` seconds and stop.
+    pub fn stop(&mut self, fade_time: f64) {
+        self.send(MusicMessage::Stop(fade_time));
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       MusicVoice48;
+    [ f64 ]   [ Wave64 ]   [ MusicVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ MusicVoice32 ];
+)]
+/// A sounding section.
+#[derive(Clone)]
+struct MusicVoice48 {
+    section: usize,
+    wave: Arc<Wave48>,
+    /// Next sample index.
+    index: usize,
+    /// Position of the next bar line in samples.
+    next_bar: f64,
+    /// Fade out length in samples and samples left, if fading out.
+    fade_out: Option<(usize, usize)>,
+}
+
+#[duplicate_item(
+    f48       Wave48       MusicVoice48;
+    [ f64 ]   [ Wave64 ]   [ MusicVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ MusicVoice32 ];
+)]
+impl MusicVoice48 {
+    /// Gain at the current index.
+    #[inline]
+    fn gain(&self, fade: &Fade) -> f48 {
+        match self.fade_out {
+            Some((length, left)) => fade.at(left as f48 / (length + 1) as f48),
+            None => 1.0,
+        }
+    }
+
+    /// Whether the voice has stopped sounding.
+    #[inline]
+    fn is_done(&self) -> bool {
+        self.index >= self.wave.len() || matches!(self.fade_out, Some((_, 0)))
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48       MusicGraphBackend48       MusicVoice48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ]   [ MusicGraphBackend64 ]   [ MusicVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ]   [ MusicGraphBackend32 ]   [ MusicVoice32 ];
+)]
+/// Backend for a music graph.
+/// - Outputs: music
+pub struct MusicGraphBackend48 {
+    channels: usize,
+    sections: Vec<Section48>,
+    rules: Vec<Rule>,
+    fade: Fade,
+    fade_time: f64,
+    sample_rate: f64,
+    /// Section playing at full level.
+    voice: Option<MusicVoice48>,
+    /// Sections fading out.
+    tails: Vec<MusicVoice48>,
+    /// Requested section, if not reached yet.
+    target: Option<usize>,
+    current: Arc<AtomicUsize>,
+    receiver: Receiver<MusicMessage>,
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48       MusicGraphBackend48       MusicVoice48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ]   [ MusicGraphBackend64 ]   [ MusicVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ]   [ MusicGraphBackend32 ]   [ MusicVoice32 ];
+)]
+impl Clone for MusicGraphBackend48 {
+    fn clone(&self) -> Self {
+        // Backends cannot be cloned effectively. Allocate a dummy channel.
+        let (_sender, receiver) = channel(1);
+        Self {
+            channels: self.channels,
+            sections: self.sections.clone(),
+            rules: self.rules.clone(),
+            fade: self.fade.clone(),
+            fade_time: self.fade_time,
+            sample_rate: self.sample_rate,
+            voice: self.voice.clone(),
+            tails: self.tails.clone(),
+            target: self.target,
+            current: Arc::new(AtomicUsize::new(self.current.load(Ordering::Relaxed))),
+            receiver,
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48       MusicGraphBackend48       MusicVoice48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ]   [ MusicGraphBackend64 ]   [ MusicVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ]   [ MusicGraphBackend32 ]   [ MusicVoice32 ];
+)]
+impl MusicGraphBackend48 {
+    /// Handle messages from the frontend.
+    fn handle_messages(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                MusicMessage::Start(section) => {
+                    self.target = None;
+                    let length = self.fade_length(self.fade_time);
+                    self.switch(Some(section), length);
+                }
+                MusicMessage::Goto(section) => {
+                    self.target = Some(section);
+                }
+                MusicMessage::Stop(fade_time) => {
+                    self.target = None;
+                    let length = self.fade_length(fade_time);
+                    self.switch(None, length);
+                }
+                MusicMessage::Nothing => (),
+            }
+        }
+    }
+
+    /// Fade time in samples.
+    fn fade_length(&self, fade_time: f64) -> usize {
+        round(max(0.0, fade_time) * self.sample_rate) as usize
+    }
+
+    /// Move the playing section to the tails, fading it out over `length` samples,
+    /// and start `section`, if any, from the beginning.
+    fn switch(&mut self, section: Option<usize>, length: usize) {
+        if let Some(mut voice) = self.voice.take() {
+            if length > 0 && voice.index < voice.wave.len() {
+                if self.tails.len() == MUSIC_TAILS {
+                    self.tails.remove(0);
+                }
+                voice.fade_out = Some((length, length));
+                self.tails.push(voice);
+            }
+        }
+        self.voice = section.map(|section| MusicVoice48 {
+            section,
+            wave: self.sections[section].wave.clone(),
+            index: 0,
+            next_bar: self.sections[section].bar,
+            fade_out: None,
+        });
+        self.current
+            .store(section.unwrap_or(NO_SECTION), Ordering::Relaxed);
+    }
+
+    /// Make any transition that falls on the current sample.
+    fn update(&mut self) {
+        let (section, at_end, at_bar) = match &mut self.voice {
+            Some(voice) => {
+                let at_end = voice.index >= voice.wave.len();
+                let at_bar = voice.next_bar > 0.0 && voice.index as f64 >= voice.next_bar - 0.5;
+                if at_bar {
+                    voice.next_bar += self.sections[voice.section].bar;
+                }
+                (voice.section, at_end, at_bar)
+            }
+            None => return,
+        };
+        if let Some(target) = self.target {
+            if target == section {
+                self.target = None;
+            } else {
+                let rule = self
+                    .rules
+                    .iter()
+                    .find(|rule| rule.from == section && rule.to == target);
+                let (boundary, via) = match rule {
+                    Some(rule) => (rule.boundary, rule.via),
+                    None => (Boundary::End, None),
+                };
+                if at_end || (at_bar && boundary == Boundary::Bar) {
+                    let next = via.unwrap_or(target);
+                    if next == target {
+                        self.target = None;
+                    }
+                    let length = if at_end {
+                        0
+                    } else {
+                        self.fade_length(self.fade_time)
+                    };
+                    self.switch(Some(next), length);
+                    return;
+                }
+            }
+        }
+        if at_end {
+            let next = self.sections[section].next;
+            self.switch(next, 0);
+        }
+    }
+
+    /// Render the next frame.
+    #[inline]
+    fn next_frame(&mut self, mut store: impl FnMut(usize, f48)) {
+        self.update();
+        let channels = self.channels;
+        let fade = &self.fade;
+        let mut play = |voice: &mut MusicVoice48| {
+            let gain = voice.gain(fade);
+            let wave_channels = voice.wave.channels();
+            for channel in 0..channels {
+                store(channel, voice.wave.at(channel % wave_channels, voice.index) * gain);
+            }
+            voice.index += 1;
+            if let Some((_, left)) = &mut voice.fade_out {
+                *left -= 1;
+            }
+        };
+        if let Some(voice) = &mut self.voice {
+            play(voice);
+        }
+        for voice in self.tails.iter_mut() {
+            if !voice.is_done() {
+                play(voice);
+            }
+        }
+        self.tails.retain(|voice| !voice.is_done());
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Section48       MusicGraphBackend48       MusicVoice48       AudioUnit48;
+    [ f64 ]   [ Wave64 ]   [ Section64 ]   [ MusicGraphBackend64 ]   [ MusicVoice64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Wave32 ]   [ Section32 ]   [ MusicGraphBackend32 ]   [ MusicVoice32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for MusicGraphBackend48 {
+    /// Stop playing. Pending requests are cleared.
+    fn reset(&mut self) {
+        self.voice = None;
+        self.tails.clear();
+        self.target = None;
+        self.current.store(NO_SECTION, Ordering::Relaxed);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &[f48], output: &mut [f48]) {
+        self.handle_messages();
+        output.fill(0.0);
+        self.next_frame(|channel, x| output[channel] += x);
+    }
+
+    fn process(&mut self, size: usize, _input: &[&[f48]], output: &mut [&mut [f48]]) {
+        self.handle_messages();
+        for channel in output.iter_mut() {
+            channel[..size].fill(0.0);
+        }
+        for i in 0..size {
+            self.next_frame(|channel, x| output[channel][i] += x);
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn outputs(&self) -> usize {
+        self.channels
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..self.outputs() {
+            output[i] = Signal::Latency(0.0);
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 130;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    fn allocate(&mut self) {
+        self.tails.reserve(MUSIC_TAILS);
+    }
+}
@@ -4,6 +4,9 @@ use super::audionode::*;
 use super::audiounit::*;
 use super::buffer::*;
 use super::combinator::*;
+use super::denormal::*;
+#[cfg(feature = "debug")]
+use super::guard::*;
 use super::math::*;
 use super::realnet::*;
 use super::signal::*;
@@ -154,6 +157,35 @@ impl Vertex48 {
     pub fn allocate(&mut self) {
         self.unit.allocate();
     }
+
+    /// Panic if the first `size` samples of the output buffers contain invalid values.
+    #[cfg(feature = "debug")]
+    pub fn check_output(&mut self, size: usize) {
+        if let Some((channel, i)) = find_invalid(size, self.output.vec()) {
+            let value = self.output.at(channel)[i];
+            self.report_invalid(channel, value);
+        }
+    }
+
+    /// Panic if the tick output contains invalid values.
+    #[cfg(feature = "debug")]
+    pub fn check_tick_output(&mut self) {
+        if let Some(channel) = self.tick_output.iter().position(|&x| !is_valid_sample(x)) {
+            let value = self.tick_output[channel];
+            self.report_invalid(channel, value);
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    fn report_invalid(&mut self, channel: usize, value: f48) {
+        panic!(
+            "Net: node {:?} ({}) produced invalid sample {} in output {}.",
+            self.id,
+            self.unit.describe().name,
+            value,
+            channel
+        );
+    }
 }
 
 #[duplicate_item(
@@ -926,6 +958,11 @@ impl Net48 {
         NetBackend48::new(sender_b, receiver_a, net)
     }
 
+    /// Current sample rate of the network in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
     /// Returns whether this network has a backend.
     pub fn has_backend(&self) -> bool {
         self.front.is_some()
@@ -1040,6 +1077,8 @@ impl AudioUnit48 for Net48 {
             vertex
                 .unit
                 .tick(&vertex.tick_input, &mut vertex.tick_output);
+            #[cfg(feature = "debug")]
+            vertex.check_tick_output();
         }
 
         // Then we set the global outputs.
@@ -1053,6 +1092,7 @@ impl AudioUnit48 for Net48 {
     }
 
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        let _guard = DenormalGuard::new();
         if !self.is_ordered() {
             self.determine_order();
         }
@@ -1094,6 +1134,8 @@ impl AudioUnit48 for Net48 {
                         .process(size, (*ptr).self_ref(), vertex.output.self_mut());
                 }
             }
+            #[cfg(feature = "debug")]
+            self.vertex[node_index].check_output(size);
         }
 
         // Then we set the global outputs.
@@ -1107,6 +1149,34 @@ impl AudioUnit48 for Net48 {
         }
     }
 
+    fn process_inplace(&mut self, size: usize, buffers: &mut [&mut [f48]]) {
+        assert!(self.inputs() == self.outputs());
+        // Copy the input aside into the global input buffers, which are otherwise unused in `process`.
+        let mut input = core::mem::take(&mut self.input);
+        for (x, y) in input.vec_mut().iter_mut().zip(buffers.iter()) {
+            x[..size].copy_from_slice(&y[..size]);
+        }
+        self.process(size, input.self_ref(), buffers);
+        self.input = input;
+    }
+
+    fn skip(&mut self, samples: usize) {
+        // Process blocks of zero input through the global buffers, which are otherwise unused in `process`.
+        let mut input = core::mem::take(&mut self.input);
+        let mut output = core::mem::take(&mut self.output);
+        for x in input.vec_mut().iter_mut() {
+            x.fill(0.0);
+        }
+        let mut samples = samples;
+        while samples > 0 {
+            let length = min(samples, MAX_BUFFER_SIZE);
+            self.process(length, input.self_ref(), output.self_mut());
+            samples -= length;
+        }
+        self.input = input;
+        self.output = output;
+    }
+
     fn get_id(&self) -> u64 {
         ID
     }
@@ -1165,6 +1235,60 @@ impl AudioUnit48 for Net48 {
             vertex.allocate();
         }
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self
+            .vertex
+            .iter_mut()
+            .map(|vertex| vertex.unit.describe())
+            .collect();
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+        .with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.vertex
+            .iter()
+            .flat_map(|x| x.unit.parameters())
+            .collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.vertex.iter().map(|x| x.unit.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        let mut index = index;
+        for x in self.vertex.iter() {
+            let count = x.unit.parameter_count();
+            if index < count {
+                return x.unit.parameter(index);
+            }
+            index -= count;
+        }
+        panic!("Parameter index out of range.")
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        let mut index = index;
+        for x in self.vertex.iter_mut() {
+            let count = x.unit.parameter_count();
+            if index < count {
+                x.unit.set_parameter(index, value);
+                // The changed unit replaces its backend version on commit.
+                x.changed = self.revision;
+                return;
+            }
+            index -= count;
+        }
+        panic!("Parameter index out of range.")
+    }
 }
 
 #[duplicate_item(
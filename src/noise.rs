@@ -98,7 +98,7 @@ impl MlsState {
 /// - Output 0: noise.
 #[derive(Clone)]
 pub struct Mls<T> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
     mls: MlsState,
     hash: u64,
 }
@@ -106,7 +106,7 @@ pub struct Mls<T> {
 impl<T: Float> Mls<T> {
     pub fn new(mls: MlsState) -> Self {
         Self {
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
             mls,
             hash: 0,
         }
@@ -147,18 +147,31 @@ impl<T: Float> AudioNode for Mls<T> {
 }
 
 /// White noise component.
+/// The noise is seeded from the structure of the graph unless an explicit seed is given.
 /// - Output 0: noise.
 #[derive(Default, Clone)]
 pub struct Noise<T> {
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
     state: u64,
     hash: u64,
+    seed: Option<u64>,
 }
 
 impl<T: Float> Noise<T> {
     pub fn new() -> Self {
         Noise::default()
     }
+
+    /// Create white noise component with an explicit `seed`.
+    /// The seed overrides any seed derived from the graph structure.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut node = Self {
+            seed: Some(seed),
+            ..Self::default()
+        };
+        node.set_hash(0);
+        node
+    }
 }
 
 impl<T: Float> AudioNode for Noise<T> {
@@ -196,6 +209,238 @@ impl<T: Float> AudioNode for Noise<T> {
         }
     }
 
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = match self.seed {
+            Some(seed) => funutd::hash::hash64g(seed),
+            None => hash,
+        };
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
+/// Velvet noise component. Velvet noise is a sparse sequence of -1 and 1 valued impulses.
+/// There is one impulse at a random position in each grid period of 1 / density seconds.
+/// It sounds smoother than white noise and is cheap to convolve with,
+/// which makes it useful for decorrelation and artificial reverberation.
+/// The noise is seeded from the structure of the graph unless an explicit seed is given.
+/// Setting: density in impulses per second.
+/// - Output 0: noise.
+#[derive(Clone)]
+pub struct Velvet<T> {
+    rnd: Rnd,
+    hash: u64,
+    seed: Option<u64>,
+    density: T,
+    sample_rate: f64,
+    /// Grid period in samples.
+    period: f64,
+    /// Position within the current grid period in samples.
+    phase: f64,
+    /// Position of the impulse within the current grid period in samples.
+    impulse: f64,
+    /// Sign of the impulse.
+    sign: T,
+    /// Whether the impulse of the current grid period has been emitted.
+    emitted: bool,
+}
+
+impl<T: Float> Velvet<T> {
+    /// Create velvet noise component with `density` impulses per second (`density` > 0).
+    pub fn new(density: T, seed: Option<u64>) -> Self {
+        let mut node = Self {
+            rnd: Rnd::new(),
+            hash: 0,
+            seed,
+            density,
+            sample_rate: DEFAULT_SR,
+            period: 1.0,
+            phase: 0.0,
+            impulse: 0.0,
+            sign: T::one(),
+            emitted: false,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node.set_hash(0);
+        node
+    }
+    /// Density in impulses per second.
+    #[inline]
+    pub fn density(&self) -> T {
+        self.density
+    }
+    /// Set density in impulses per second (`density` > 0).
+    #[inline]
+    pub fn set_density(&mut self, density: T) {
+        self.density = density;
+        self.period = max(1.0, self.sample_rate / density.to_f64());
+    }
+    /// Start a new grid period.
+    #[inline]
+    fn next_period(&mut self) {
+        self.impulse = self.rnd.f64() * self.period;
+        self.sign = if self.rnd.bool(0.5) {
+            T::one()
+        } else {
+            -T::one()
+        };
+        self.emitted = false;
+    }
+}
+
+impl<T: Float> AudioNode for Velvet<T> {
+    const ID: u64 = 99;
+    type Sample = T;
+    type Inputs = typenum::U0;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, density: T) {
+        self.set_density(density);
+    }
+
+    fn reset(&mut self) {
+        self.rnd = Rnd::from_u64(self.hash);
+        self.phase = 0.0;
+        self.next_period();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.set_density(self.density);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut value = T::zero();
+        if !self.emitted && self.impulse < self.phase + 1.0 {
+            value = self.sign;
+            self.emitted = true;
+        }
+        self.phase += 1.0;
+        if self.phase >= self.period {
+            self.phase -= self.period;
+            self.next_period();
+        }
+        [value].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = match self.seed {
+            Some(seed) => funutd::hash::hash64g(seed),
+            None => hash,
+        };
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
+/// Bounded random walk. The output wanders within -range...range, reflecting at the bounds.
+/// After `t` seconds, the standard deviation of the position is about `range * rate * sqrt(t)`,
+/// independent of sample rate. The walk is seeded from the structure of the graph.
+/// Setting: (rate, range).
+/// - Output 0: random walk.
+#[derive(Clone)]
+pub struct RandomWalk<T> {
+    rnd: Rnd,
+    hash: u64,
+    rate: T,
+    range: T,
+    sample_rate: f64,
+    /// Maximum step per sample.
+    step: f64,
+    /// Current position.
+    x: f64,
+}
+
+impl<T: Float> RandomWalk<T> {
+    /// Create random walk with speed `rate` (`rate` >= 0)
+    /// in units of `range` (`range` > 0) per square root second.
+    pub fn new(rate: T, range: T) -> Self {
+        let mut node = Self {
+            rnd: Rnd::new(),
+            hash: 0,
+            rate,
+            range,
+            sample_rate: DEFAULT_SR,
+            step: 0.0,
+            x: 0.0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node.set_hash(0);
+        node
+    }
+    /// Speed of the walk in units of range per square root second.
+    #[inline]
+    pub fn rate(&self) -> T {
+        self.rate
+    }
+    /// Range of the walk. The output is in -range...range.
+    #[inline]
+    pub fn range(&self) -> T {
+        self.range
+    }
+    /// Set speed (`rate` >= 0) and range (`range` > 0) of the walk.
+    pub fn set_walk(&mut self, rate: T, range: T) {
+        self.rate = rate;
+        self.range = range;
+        // A uniform step in -a...a has standard deviation a / sqrt(3).
+        self.step = sqrt(3.0) * range.to_f64() * rate.to_f64() / sqrt(self.sample_rate);
+        self.x = clamp(-range.to_f64(), range.to_f64(), self.x);
+    }
+}
+
+impl<T: Float> AudioNode for RandomWalk<T> {
+    const ID: u64 = 100;
+    type Sample = T;
+    type Inputs = typenum::U0;
+    type Outputs = typenum::U1;
+    type Setting = (T, T);
+
+    fn set(&mut self, (rate, range): Self::Setting) {
+        self.set_walk(rate, range);
+    }
+
+    fn reset(&mut self) {
+        self.rnd = Rnd::from_u64(self.hash);
+        self.x = (self.rnd.f64() * 2.0 - 1.0) * self.range.to_f64();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.set_walk(self.rate, self.range);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let range = self.range.to_f64();
+        self.x += (self.rnd.f64() * 2.0 - 1.0) * self.step;
+        if self.x > range {
+            self.x = 2.0 * range - self.x;
+        } else if self.x < -range {
+            self.x = -2.0 * range - self.x;
+        }
+        // Guard against steps larger than the whole range.
+        self.x = clamp(-range, range, self.x);
+        [T::from_f64(self.x)].into()
+    }
+
     fn set_hash(&mut self, hash: u64) {
         self.hash = hash;
         self.reset();
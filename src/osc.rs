@@ -0,0 +1,462 @@
+//! Open Sound Control (OSC 1.0) integration: message decoding and address pattern routing
+//! to shared parameters and sequencer events.
+
+use super::audiounit::*;
+use super::sequencer::*;
+use super::shared::*;
+use duplicate::duplicate_item;
+use std::net::UdpSocket;
+
+/// Errors from decoding OSC packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OscError {
+    /// The packet ended in the middle of an element.
+    Truncated,
+    /// A string is not valid UTF-8 or is not terminated.
+    String,
+    /// The address does not start with a slash.
+    Address,
+    /// The type tag string is missing or malformed.
+    TypeTags,
+    /// The argument type tag is not supported.
+    UnknownType(char),
+}
+
+impl std::fmt::Display for OscError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OscError::Truncated => write!(f, "truncated OSC packet"),
+            OscError::String => write!(f, "malformed OSC string"),
+            OscError::Address => write!(f, "malformed OSC address"),
+            OscError::TypeTags => write!(f, "malformed OSC type tags"),
+            OscError::UnknownType(tag) => write!(f, "unsupported OSC type tag '{}'", tag),
+        }
+    }
+}
+
+impl std::error::Error for OscError {}
+
+/// OSC message argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    /// 32-bit integer (`i`).
+    Int(i32),
+    /// 32-bit float (`f`).
+    Float(f32),
+    /// 64-bit integer (`h`).
+    Long(i64),
+    /// 64-bit float (`d`).
+    Double(f64),
+    /// String (`s`).
+    String(String),
+    /// Blob (`b`).
+    Blob(Vec<u8>),
+    /// Boolean (`T` or `F`).
+    Bool(bool),
+    /// Nil (`N`).
+    Nil,
+}
+
+impl OscArg {
+    /// The argument as a number, if it is numeric or boolean.
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            OscArg::Int(x) => Some(*x as f64),
+            OscArg::Float(x) => Some(*x as f64),
+            OscArg::Long(x) => Some(*x as f64),
+            OscArg::Double(x) => Some(*x),
+            OscArg::Bool(x) => Some(if *x { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+}
+
+/// OSC message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    /// Address pattern of the message.
+    pub address: String,
+    /// Arguments.
+    pub args: Vec<OscArg>,
+}
+
+/// Append OSC string `s` with terminator and padding.
+fn encode_string(s: &str, output: &mut Vec<u8>) {
+    output.extend_from_slice(s.as_bytes());
+    output.push(0);
+    while output.len() % 4 != 0 {
+        output.push(0);
+    }
+}
+
+/// Read `n` bytes at `position`.
+fn read_bytes<'a>(data: &'a [u8], position: &mut usize, n: usize) -> Result<&'a [u8], OscError> {
+    if data.len() < *position + n {
+        return Err(OscError::Truncated);
+    }
+    let bytes = &data[*position..*position + n];
+    *position += n;
+    Ok(bytes)
+}
+
+/// Read a 4-byte word at `position`.
+fn read_word(data: &[u8], position: &mut usize) -> Result<[u8; 4], OscError> {
+    let bytes = read_bytes(data, position, 4)?;
+    Ok([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Read an 8-byte word at `position`.
+fn read_long(data: &[u8], position: &mut usize) -> Result<[u8; 8], OscError> {
+    let mut word = [0; 8];
+    word.copy_from_slice(read_bytes(data, position, 8)?);
+    Ok(word)
+}
+
+/// Read a padded OSC string at `position`.
+fn read_string(data: &[u8], position: &mut usize) -> Result<String, OscError> {
+    let rest = &data[(*position).min(data.len())..];
+    let length = match rest.iter().position(|x| *x == 0) {
+        Some(length) => length,
+        None => return Err(OscError::String),
+    };
+    let s = match std::str::from_utf8(&rest[..length]) {
+        Ok(s) => s.to_string(),
+        Err(_) => return Err(OscError::String),
+    };
+    let padded = (length + 4) & !3;
+    read_bytes(data, position, padded)?;
+    Ok(s)
+}
+
+impl OscMessage {
+    /// Create message with `address` and arguments `args`.
+    pub fn new(address: &str, args: Vec<OscArg>) -> Self {
+        Self {
+            address: address.to_string(),
+            args,
+        }
+    }
+
+    /// Argument `i` as a number, if it exists and is numeric.
+    pub fn number(&self, i: usize) -> Option<f64> {
+        self.args.get(i).and_then(|arg| arg.number())
+    }
+
+    /// Encode the message as an OSC packet.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        encode_string(&self.address, &mut output);
+        let mut tags = String::from(",");
+        for arg in self.args.iter() {
+            tags.push(match arg {
+                OscArg::Int(_) => 'i',
+                OscArg::Float(_) => 'f',
+                OscArg::Long(_) => 'h',
+                OscArg::Double(_) => 'd',
+                OscArg::String(_) => 's',
+                OscArg::Blob(_) => 'b',
+                OscArg::Bool(true) => 'T',
+                OscArg::Bool(false) => 'F',
+                OscArg::Nil => 'N',
+            });
+        }
+        encode_string(&tags, &mut output);
+        for arg in self.args.iter() {
+            match arg {
+                OscArg::Int(x) => output.extend_from_slice(&x.to_be_bytes()),
+                OscArg::Float(x) => output.extend_from_slice(&x.to_be_bytes()),
+                OscArg::Long(x) => output.extend_from_slice(&x.to_be_bytes()),
+                OscArg::Double(x) => output.extend_from_slice(&x.to_be_bytes()),
+                OscArg::String(x) => encode_string(x, &mut output),
+                OscArg::Blob(x) => {
+                    output.extend_from_slice(&(x.len() as i32).to_be_bytes());
+                    output.extend_from_slice(x);
+                    while output.len() % 4 != 0 {
+                        output.push(0);
+                    }
+                }
+                _ => (),
+            }
+        }
+        output
+    }
+
+    /// Decode an OSC packet. Messages in bundles are returned in order;
+    /// bundle time tags are ignored and all messages are treated as immediate.
+    pub fn decode(packet: &[u8]) -> Result<Vec<OscMessage>, OscError> {
+        let mut messages = Vec::new();
+        Self::decode_into(packet, &mut messages)?;
+        Ok(messages)
+    }
+
+    /// Decode a packet, appending its messages to `messages`.
+    fn decode_into(packet: &[u8], messages: &mut Vec<OscMessage>) -> Result<(), OscError> {
+        let mut position = 0;
+        let address = read_string(packet, &mut position)?;
+        if address == "#bundle" {
+            // Skip the time tag.
+            read_long(packet, &mut position)?;
+            while position < packet.len() {
+                let size = i32::from_be_bytes(read_word(packet, &mut position)?);
+                if size < 0 {
+                    return Err(OscError::Truncated);
+                }
+                let element = read_bytes(packet, &mut position, size as usize)?;
+                Self::decode_into(element, messages)?;
+            }
+            return Ok(());
+        }
+        if !address.starts_with('/') {
+            return Err(OscError::Address);
+        }
+        let mut args = Vec::new();
+        if position < packet.len() {
+            let tags = read_string(packet, &mut position)?;
+            if !tags.starts_with(',') {
+                return Err(OscError::TypeTags);
+            }
+            for tag in tags.chars().skip(1) {
+                args.push(match tag {
+                    'i' => OscArg::Int(i32::from_be_bytes(read_word(packet, &mut position)?)),
+                    'f' => OscArg::Float(f32::from_be_bytes(read_word(packet, &mut position)?)),
+                    'h' => OscArg::Long(i64::from_be_bytes(read_long(packet, &mut position)?)),
+                    'd' => OscArg::Double(f64::from_be_bytes(read_long(packet, &mut position)?)),
+                    's' | 'S' => OscArg::String(read_string(packet, &mut position)?),
+                    'b' => {
+                        let size = i32::from_be_bytes(read_word(packet, &mut position)?);
+                        if size < 0 {
+                            return Err(OscError::Truncated);
+                        }
+                        let size = size as usize;
+                        let blob = read_bytes(packet, &mut position, size)?.to_vec();
+                        read_bytes(packet, &mut position, (4 - size % 4) % 4)?;
+                        OscArg::Blob(blob)
+                    }
+                    'T' => OscArg::Bool(true),
+                    'F' => OscArg::Bool(false),
+                    'N' => OscArg::Nil,
+                    _ => return Err(OscError::UnknownType(tag)),
+                });
+            }
+        }
+        messages.push(OscMessage { address, args });
+        Ok(())
+    }
+}
+
+/// Match one part of an OSC address pattern against one part of an address.
+fn match_part(pattern: &[u8], address: &[u8]) -> bool {
+    match pattern.first() {
+        None => address.is_empty(),
+        Some(b'*') => (0..=address.len()).any(|i| match_part(&pattern[1..], &address[i..])),
+        Some(b'?') => !address.is_empty() && match_part(&pattern[1..], &address[1..]),
+        Some(b'[') => {
+            let end = match pattern.iter().position(|x| *x == b']') {
+                Some(end) => end,
+                None => return false,
+            };
+            if address.is_empty() {
+                return false;
+            }
+            let mut set = &pattern[1..end];
+            let negate = set.first() == Some(&b'!');
+            if negate {
+                set = &set[1..];
+            }
+            let c = address[0];
+            let mut found = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == b'-' {
+                    found |= c >= set[i] && c <= set[i + 2];
+                    i += 3;
+                } else {
+                    found |= c == set[i];
+                    i += 1;
+                }
+            }
+            found != negate && match_part(&pattern[end + 1..], &address[1..])
+        }
+        Some(b'{') => {
+            let end = match pattern.iter().position(|x| *x == b'}') {
+                Some(end) => end,
+                None => return false,
+            };
+            pattern[1..end].split(|x| *x == b',').any(|choice| {
+                address.starts_with(choice)
+                    && match_part(&pattern[end + 1..], &address[choice.len()..])
+            })
+        }
+        Some(c) => address.first() == Some(c) && match_part(&pattern[1..], &address[1..]),
+    }
+}
+
+/// Match OSC address `pattern` against `address`. Patterns support
+/// `?`, `*`, character sets like `[a-c]` or `[!0-9]` and alternatives like `{left,right}`.
+///
+/// ### Example
+/// ```
+/// use fundsp::osc::*;
+/// assert!(osc_match("/mixer/*/gain", "/mixer/3/gain"));
+/// assert!(osc_match("/{left,right}/pan", "/right/pan"));
+/// assert!(!osc_match("/fader[1-4]", "/fader5"));
+/// ```
+pub fn osc_match(pattern: &str, address: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let address: Vec<&str> = address.split('/').collect();
+    pattern.len() == address.len()
+        && pattern
+            .iter()
+            .zip(address.iter())
+            .all(|(p, a)| match_part(p.as_bytes(), a.as_bytes()))
+}
+
+/// Handler of OSC messages.
+type OscMethod<C> = Box<dyn FnMut(&OscMessage, &mut C) + Send>;
+
+/// Routes OSC messages to methods by address. The address patterns of incoming
+/// messages are matched against the addresses of methods, and every matching method is invoked
+/// with the message and a context. The context type `C` is decided by the user;
+/// with a sequencer context, messages can push events (see `add_event`).
+///
+/// ### Example: Control Cutoff From A Fader
+/// ```
+/// use fundsp::hacker::*;
+/// let cutoff = shared(1000.0);
+/// let mut router = OscRouter::<()>::new();
+/// router.add_shared_range("/filter/cutoff", &cutoff, 100.0, 10000.0);
+/// let packet = OscMessage::new("/filter/*", vec![OscArg::Float(0.5)]).encode();
+/// router.handle(&packet, &mut ()).unwrap();
+/// assert_eq!(cutoff.value(), 5050.0);
+/// ```
+pub struct OscRouter<C> {
+    methods: Vec<(String, OscMethod<C>)>,
+}
+
+impl<C> Default for OscRouter<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> OscRouter<C> {
+    /// Create router with no methods.
+    pub fn new() -> Self {
+        Self {
+            methods: Vec::new(),
+        }
+    }
+
+    /// Add `method` at `address`, which must start with a slash.
+    pub fn add_method(
+        &mut self,
+        address: &str,
+        method: impl FnMut(&OscMessage, &mut C) + Send + 'static,
+    ) {
+        assert!(address.starts_with('/'));
+        self.methods.push((address.to_string(), Box::new(method)));
+    }
+
+    /// Set `shared` from the first argument of messages to `address`.
+    /// Messages without a numeric first argument are ignored.
+    pub fn add_shared<T: Atomic + 'static>(&mut self, address: &str, shared: &Shared<T>) {
+        let shared = shared.clone();
+        self.add_method(address, move |message, _| {
+            if let Some(x) = message.number(0) {
+                shared.set_value(T::from_f64(x));
+            }
+        });
+    }
+
+    /// Set `shared` from the first argument of messages to `address`,
+    /// mapping the range 0...1 linearly to `min`...`max`, as from a fader.
+    /// Arguments outside 0...1 are clamped.
+    pub fn add_shared_range<T: Atomic + 'static>(
+        &mut self,
+        address: &str,
+        shared: &Shared<T>,
+        min: f64,
+        max: f64,
+    ) {
+        let shared = shared.clone();
+        self.add_method(address, move |message, _| {
+            if let Some(x) = message.number(0) {
+                let x = x.clamp(0.0, 1.0);
+                shared.set_value(T::from_f64(min + (max - min) * x));
+            }
+        });
+    }
+
+    /// Number of methods.
+    pub fn methods(&self) -> usize {
+        self.methods.len()
+    }
+
+    /// Invoke all methods whose address matches the address pattern of `message`.
+    /// Returns the number of methods invoked.
+    pub fn dispatch(&mut self, message: &OscMessage, context: &mut C) -> usize {
+        let mut count = 0;
+        for (address, method) in self.methods.iter_mut() {
+            if osc_match(&message.address, address) {
+                method(message, context);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Decode `packet` and dispatch its messages. Returns the number of methods invoked.
+    pub fn handle(&mut self, packet: &[u8], context: &mut C) -> Result<usize, OscError> {
+        let messages = OscMessage::decode(packet)?;
+        Ok(messages
+            .iter()
+            .map(|message| self.dispatch(message, context))
+            .sum())
+    }
+
+    /// Handle all packets waiting in `socket`, which should be in nonblocking mode.
+    /// Malformed packets are skipped. Returns the number of methods invoked.
+    /// Call this periodically from a control thread.
+    pub fn receive(&mut self, socket: &UdpSocket, context: &mut C) -> std::io::Result<usize> {
+        let mut buffer = [0u8; 65536];
+        let mut count = 0;
+        loop {
+            match socket.recv(&mut buffer) {
+                Ok(size) => {
+                    if let Ok(n) = self.handle(&buffer[..size], context) {
+                        count += n;
+                    }
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => return Ok(count),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       Sequencer48       AudioUnit48;
+    [ f64 ]   [ Sequencer64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Sequencer32 ]   [ AudioUnit32 ];
+)]
+impl OscRouter<Sequencer48> {
+    /// Push an event into the sequencer context for messages to `address`.
+    /// The unit is made by `make` from the message; if it returns `None`, nothing is pushed.
+    /// The event starts as soon as possible and lasts `duration` seconds,
+    /// with `fade_in` and `fade_out` times in seconds.
+    pub fn add_event(
+        &mut self,
+        address: &str,
+        duration: f48,
+        fade: Fade,
+        fade_in: f48,
+        fade_out: f48,
+        mut make: impl FnMut(&OscMessage) -> Option<Box<dyn AudioUnit48>> + Send + 'static,
+    ) {
+        self.add_method(address, move |message, sequencer: &mut Sequencer48| {
+            if let Some(unit) = make(message) {
+                sequencer.push_relative(0.0, duration, fade.clone(), fade_in, fade_out, unit);
+            }
+        });
+    }
+}
@@ -8,7 +8,7 @@ use super::signal::*;
 use super::*;
 use funutd::Rnd;
 use numeric_array::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Sine oscillator.
 /// - Input 0: frequency in Hz.
@@ -71,7 +71,13 @@ impl<T: Real> AudioNode for Sine<T> {
         while self.phase > T::one() {
             self.phase -= T::one();
         }
-        [sin(self.phase * T::from_f64(TAU))].into()
+        [osc_sin(self.phase * T::from_f64(TAU))].into()
+    }
+
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        let phase = self.phase.to_f64()
+            + input[0].to_f64() * self.sample_duration.to_f64() * samples as f64;
+        self.phase = T::from_f64(phase - floor(phase));
     }
 
     fn process(
@@ -82,7 +88,7 @@ impl<T: Real> AudioNode for Sine<T> {
     ) {
         for i in 0..size {
             self.phase += input[0][i] * self.sample_duration;
-            output[0][i] = sin(self.phase * T::from_f64(TAU));
+            output[0][i] = osc_sin(self.phase * T::from_f64(TAU));
         }
         self.phase -= self.phase.floor();
     }
@@ -99,6 +105,337 @@ impl<T: Real> AudioNode for Sine<T> {
     }
 }
 
+/// Waveform of `SyncSynth`. Waveforms start at zero and rise from phase zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Saw,
+    Square,
+    Triangle,
+}
+
+impl Waveform {
+    /// Naive waveform value at phase `p` in 0...1.
+    #[inline]
+    fn value<T: Real>(&self, p: T) -> T {
+        match self {
+            Waveform::Sine => osc_sin(p * T::from_f64(TAU)),
+            Waveform::Saw => {
+                if p < T::from_f64(0.5) {
+                    T::new(2) * p
+                } else {
+                    T::new(2) * p - T::new(2)
+                }
+            }
+            Waveform::Square => {
+                if p < T::from_f64(0.5) {
+                    T::one()
+                } else {
+                    -T::one()
+                }
+            }
+            Waveform::Triangle => {
+                if p < T::from_f64(0.25) {
+                    T::new(4) * p
+                } else if p < T::from_f64(0.75) {
+                    T::new(2) - T::new(4) * p
+                } else {
+                    T::new(4) * p - T::new(4)
+                }
+            }
+        }
+    }
+
+    /// Derivative of the naive waveform with respect to phase at `p` in 0...1.
+    #[inline]
+    fn slope<T: Real>(&self, p: T) -> T {
+        match self {
+            Waveform::Sine => T::from_f64(TAU) * osc_cos(p * T::from_f64(TAU)),
+            Waveform::Saw => T::new(2),
+            Waveform::Square => T::zero(),
+            Waveform::Triangle => {
+                if p < T::from_f64(0.25) || p >= T::from_f64(0.75) {
+                    T::new(4)
+                } else {
+                    T::new(-4)
+                }
+            }
+        }
+    }
+
+    /// Discontinuities of the waveform as (phase, value jump, slope jump) when moving forward.
+    fn edges(&self) -> &'static [(f64, f64, f64)] {
+        match self {
+            Waveform::Sine => &[],
+            Waveform::Saw => &[(0.5, -2.0, 0.0)],
+            Waveform::Square => &[(0.0, 2.0, 0.0), (0.5, -2.0, 0.0)],
+            Waveform::Triangle => &[(0.25, 0.0, -8.0), (0.75, 0.0, 8.0)],
+        }
+    }
+}
+
+/// Function of the second input of `SyncSynth`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncInput {
+    /// Hard sync: a rising zero crossing resets the phase to zero.
+    Sync,
+    /// Phase offset in 0...1 added to the oscillator phase.
+    Phase,
+}
+
+/// Oscillator with a hard sync or phase input. Discontinuities in the waveform
+/// and its slope, including those caused by sync, are corrected with polynomial
+/// bandlimited steps and ramps (PolyBLEP and PolyBLAMP) at a latency of one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal or phase offset in 0...1 (`SyncInput`)
+/// - Output 0: audio
+#[derive(Clone)]
+pub struct SyncSynth<T: Real> {
+    waveform: Waveform,
+    mode: SyncInput,
+    /// Oscillator phase in 0...1.
+    phase: T,
+    /// Phase including phase offset from the previous sample.
+    offset_phase: T,
+    /// Previous value of the sync input.
+    sync: T,
+    /// Output of the previous sample with corrections accumulated so far.
+    pending: T,
+    sample_duration: T,
+    hash: u64,
+}
+
+impl<T: Real> SyncSynth<T> {
+    /// Create oscillator with the given waveform and function of the second input.
+    pub fn new(sample_rate: f64, waveform: Waveform, mode: SyncInput) -> Self {
+        let mut node = Self {
+            waveform,
+            mode,
+            phase: T::zero(),
+            offset_phase: T::zero(),
+            sync: T::zero(),
+            pending: T::zero(),
+            sample_duration: T::zero(),
+            hash: 0,
+        };
+        node.reset();
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Waveform of the oscillator.
+    #[inline]
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    /// Function of the second input.
+    #[inline]
+    pub fn mode(&self) -> SyncInput {
+        self.mode
+    }
+
+    /// Add step and ramp corrections for a discontinuity that happened `t` samples
+    /// (0...1) before the current sample. Returns the correction to the current sample.
+    #[inline]
+    fn blep(&mut self, t: T, step: T, ramp: T) -> T {
+        let half = T::from_f64(0.5);
+        let sixth = T::from_f64(1.0 / 6.0);
+        let u = T::one() - t;
+        self.pending += step * t * t * half + ramp * t * t * t * sixth;
+        -step * u * u * half + ramp * u * u * u * sixth
+    }
+
+    /// Correct waveform discontinuities crossed when the phase moves from `start` to `end`,
+    /// which spans sample times `t0` to `t1` (0...1) before the current sample.
+    /// Returns the correction to the current sample.
+    fn edges(&mut self, start: T, end: T, t0: T, t1: T) -> T {
+        let mut correction = T::zero();
+        let span = end - start;
+        if span == T::zero() {
+            return correction;
+        }
+        for &(position, step, ramp) in self.waveform.edges() {
+            let position = T::from_f64(position);
+            let (step, ramp) = (T::from_f64(step), T::from_f64(ramp) * abs(span / (t0 - t1)));
+            // Edges are crossed at `position + k` in the half-open interval from `start` to `end`.
+            let (k0, k1, step) = if span > T::zero() {
+                (floor(start - position) + T::one(), floor(end - position), step)
+            } else {
+                (ceil(end - position), ceil(start - position) - T::one(), -step)
+            };
+            let mut k = k0;
+            while k <= k1 {
+                let t = t0 + (t1 - t0) * (position + k - start) / span;
+                correction += self.blep(t, step, ramp);
+                k += T::one();
+            }
+        }
+        correction
+    }
+}
+
+impl<T: Real> AudioNode for SyncSynth<T> {
+    const ID: u64 = 109;
+    type Sample = T;
+    type Inputs = typenum::U2;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.phase = T::from_f64(rnd(self.hash as i64));
+        self.offset_phase = self.phase;
+        self.sync = T::zero();
+        self.pending = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = convert(1.0 / sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let delta = input[0] * self.sample_duration;
+        let mut correction = T::zero();
+        let value = match self.mode {
+            SyncInput::Sync => {
+                let start = self.phase;
+                let end = start + delta;
+                let mut phase = end;
+                if self.sync <= T::zero() && input[1] > T::zero() {
+                    // Locate the zero crossing of the sync input between samples.
+                    let x = self.sync / (self.sync - input[1]);
+                    let t = T::one() - x;
+                    let reset = start + delta * x;
+                    correction += self.edges(start, reset, T::one(), t);
+                    let reset = reset - floor(reset);
+                    let step = self.waveform.value(T::zero()) - self.waveform.value(reset);
+                    let ramp = (self.waveform.slope(T::zero()) - self.waveform.slope(reset)) * delta;
+                    correction += self.blep(t, step, ramp);
+                    phase = delta * t;
+                    correction += self.edges(T::zero(), phase, t, T::zero());
+                } else {
+                    correction += self.edges(start, end, T::one(), T::zero());
+                }
+                self.sync = input[1];
+                self.phase = phase - floor(phase);
+                self.waveform.value(self.phase)
+            }
+            SyncInput::Phase => {
+                let phase = self.phase + delta;
+                self.phase = phase - floor(phase);
+                let start = self.offset_phase;
+                let end = self.phase + input[1];
+                // Unwrap the phase along the path closest to the frequency increment.
+                let end = end - round(end - start - delta);
+                correction += self.edges(start, end, T::one(), T::zero());
+                self.offset_phase = end - floor(end);
+                self.waveform.value(self.offset_phase)
+            }
+        };
+        let output = self.pending;
+        self.pending = value + correction;
+        [output].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(1.0);
+        output
+    }
+}
+
+/// PolyBLEP residual for a unit step at phase zero, for phase `t` in 0...1
+/// and phase increment `dt` per sample.
+#[inline]
+fn poly_blep<T: Real>(t: T, dt: T) -> T {
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - T::one()
+    } else if t > T::one() - dt {
+        let t = (t - T::one()) / dt;
+        t * t + t + t + T::one()
+    } else {
+        T::zero()
+    }
+}
+
+/// Pulse oscillator with pulse width input. Edges are antialiased with PolyBLEP.
+/// The pulse is 1 for the first part of the cycle and -1 for the rest.
+/// - Input 0: frequency in Hz
+/// - Input 1: pulse width in 0...1
+/// - Output 0: pulse wave
+#[derive(Default, Clone)]
+pub struct BlepPulse<T: Real> {
+    phase: T,
+    sample_duration: T,
+    hash: u64,
+}
+
+impl<T: Real> BlepPulse<T> {
+    /// Create pulse oscillator.
+    pub fn new(sample_rate: f64) -> Self {
+        let mut node = Self::default();
+        node.reset();
+        node.set_sample_rate(sample_rate);
+        node
+    }
+}
+
+impl<T: Real> AudioNode for BlepPulse<T> {
+    const ID: u64 = 110;
+    type Sample = T;
+    type Inputs = typenum::U2;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.phase = T::from_f64(rnd(self.hash as i64));
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = convert(1.0 / sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let dt = min(abs(input[0] * self.sample_duration), T::from_f64(0.5));
+        self.phase += input[0] * self.sample_duration;
+        self.phase -= floor(self.phase);
+        let width = clamp01(input[1]);
+        let naive = if self.phase < width {
+            T::one()
+        } else {
+            -T::one()
+        };
+        let fall = self.phase - width;
+        let fall = fall - floor(fall);
+        [naive + (poly_blep(self.phase, dt) - poly_blep(fall, dt))].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
 /// Discrete summation formula. Returns sum, of `i` in `0..n`, of `r ** i * sin(f + i * d)`.
 fn dsf<T: Real>(f: T, d: T, r: T, n: T) -> T {
     // Note: beware of division by zero, which results when `r` = 1 and `d` = 0.
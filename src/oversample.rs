@@ -244,7 +244,11 @@ where
     type Sample = T;
     type Inputs = X::Inputs;
     type Outputs = X::Outputs;
-    type Setting = ();
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
 
     fn reset(&mut self) {
         self.x.reset();
@@ -298,4 +302,24 @@ where
     fn allocate(&mut self) {
         self.x.allocate();
     }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
 }
@@ -2,14 +2,16 @@
 
 use super::audionode::*;
 use super::math::*;
+#[cfg(feature = "std")]
+use super::resynth::*;
 use super::signal::*;
 use super::*;
 use numeric_array::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Return equal power pan weights for pan value in -1...1.
 #[inline]
-fn pan_weights<T: Real>(value: T) -> (T, T) {
+pub(crate) fn pan_weights<T: Real>(value: T) -> (T, T) {
     let angle = (clamp11(value) + T::one()) * T::from_f64(PI * 0.25);
     (cos(angle), sin(angle))
 }
@@ -23,6 +25,7 @@ fn pan_weights<T: Real>(value: T) -> (T, T) {
 #[derive(Clone)]
 pub struct Panner<T: Real, N: Size<T>> {
     _marker: PhantomData<(T, N)>,
+    value: T,
     left_weight: T,
     right_weight: T,
 }
@@ -32,12 +35,14 @@ impl<T: Real, N: Size<T>> Panner<T, N> {
         let (left_weight, right_weight) = pan_weights(value);
         Self {
             _marker: PhantomData,
+            value,
             left_weight,
             right_weight,
         }
     }
     #[inline]
     pub fn set_pan(&mut self, value: T) {
+        self.value = value;
         let (left_weight, right_weight) = pan_weights(value);
         self.left_weight = left_weight;
         self.right_weight = right_weight;
@@ -87,6 +92,39 @@ impl<T: Real, N: Size<T>> AudioNode for Panner<T, N> {
         }
     }
 
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        // With a pan input, the pan value is not a parameter.
+        if N::USIZE == 1 {
+            vec![ParameterInfo::new(
+                "pan",
+                ParameterUnit::Unitless,
+                -1.0,
+                1.0,
+                0.0,
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn parameter_count(&self) -> usize {
+        if N::USIZE == 1 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        assert!(index < self.parameter_count());
+        self.value.to_f64()
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        assert!(index < self.parameter_count());
+        self.set_pan(T::from_f64(value));
+    }
+
     fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
         let mut output = new_signal_frame(self.outputs());
         // Pretend the pan value is constant.
@@ -96,6 +134,259 @@ impl<T: Real, N: Size<T>> AudioNode for Panner<T, N> {
     }
 }
 
+/// Stereo width control. Scales the side (difference) component of a stereo signal.
+/// Width 0 is mono, 1 leaves the signal unchanged and values above 1 widen the image.
+/// Setting: width.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+#[derive(Clone)]
+pub struct StereoWidth<T: Real> {
+    width: T,
+}
+
+impl<T: Real> StereoWidth<T> {
+    /// Create stereo width control with `width` (`width` >= 0).
+    pub fn new(width: T) -> Self {
+        Self { width }
+    }
+
+    /// Stereo width.
+    #[inline]
+    pub fn width(&self) -> T {
+        self.width
+    }
+
+    /// Set stereo width (`width` >= 0).
+    #[inline]
+    pub fn set_width(&mut self, width: T) {
+        self.width = width;
+    }
+}
+
+impl<T: Real> AudioNode for StereoWidth<T> {
+    const ID: u64 = 140;
+    type Sample = T;
+    type Inputs = typenum::U2;
+    type Outputs = typenum::U2;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_width(setting);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let half = T::from_f64(0.5);
+        let mid = (input[0] + input[1]) * half;
+        let side = (input[0] - input[1]) * half * self.width;
+        [mid + side, mid - side].into()
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "width",
+            ParameterUnit::Unitless,
+            0.0,
+            2.0,
+            1.0,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        assert!(index < self.parameter_count());
+        self.width.to_f64()
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        assert!(index < self.parameter_count());
+        self.set_width(T::from_f64(value));
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let a = (1.0 + self.width.to_f64()) * 0.5;
+        let b = (1.0 - self.width.to_f64()) * 0.5;
+        output[0] = input[0].scale(a).combine_linear(
+            input[1].scale(b),
+            0.0,
+            |x, y| x + y,
+            |x, y| x + y,
+        );
+        output[1] = input[0].scale(b).combine_linear(
+            input[1].scale(a),
+            0.0,
+            |x, y| x + y,
+            |x, y| x + y,
+        );
+        output
+    }
+}
+
+/// Stereo rotation. Rotates the stereo signal, viewed as a vector (left, right), by an angle.
+/// Rotations maintain the L2 norm of the signal.
+/// Setting: angle in radians.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: rotated left output
+/// - Output 1: rotated right output
+#[derive(Clone)]
+pub struct StereoRotate<T: Real> {
+    angle: T,
+    cos: T,
+    sin: T,
+}
+
+impl<T: Real> StereoRotate<T> {
+    /// Create stereo rotation by `angle` radians.
+    pub fn new(angle: T) -> Self {
+        Self {
+            angle,
+            cos: cos(angle),
+            sin: sin(angle),
+        }
+    }
+
+    /// Rotation angle in radians.
+    #[inline]
+    pub fn angle(&self) -> T {
+        self.angle
+    }
+
+    /// Set rotation angle in radians.
+    #[inline]
+    pub fn set_angle(&mut self, angle: T) {
+        self.angle = angle;
+        self.cos = cos(angle);
+        self.sin = sin(angle);
+    }
+}
+
+impl<T: Real> AudioNode for StereoRotate<T> {
+    const ID: u64 = 141;
+    type Sample = T;
+    type Inputs = typenum::U2;
+    type Outputs = typenum::U2;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_angle(setting);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [
+            self.cos * input[0] - self.sin * input[1],
+            self.sin * input[0] + self.cos * input[1],
+        ]
+        .into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let (c, s) = (self.cos.to_f64(), self.sin.to_f64());
+        output[0] = input[0].scale(c).combine_linear(
+            input[1].scale(-s),
+            0.0,
+            |x, y| x + y,
+            |x, y| x + y,
+        );
+        output[1] = input[0].scale(s).combine_linear(
+            input[1].scale(c),
+            0.0,
+            |x, y| x + y,
+            |x, y| x + y,
+        );
+        output
+    }
+}
+
+/// Stereo correlation meter. Outputs the correlation of the left and right channels
+/// in -1...1: 1 is mono, 0 is uncorrelated and -1 is out of phase.
+/// Silence reads as 0.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: correlation
+#[derive(Clone)]
+pub struct Correlation<T: Real> {
+    /// Smoothing timescale in seconds.
+    timescale: f64,
+    /// Per-sample smoothing factor.
+    smoothing: T,
+    product: T,
+    left: T,
+    right: T,
+}
+
+impl<T: Real> Correlation<T> {
+    /// Create correlation meter. Smoothing `timescale` is the halfway response time in seconds.
+    pub fn new(sample_rate: f64, timescale: f64) -> Self {
+        let mut node = Self {
+            timescale,
+            smoothing: T::zero(),
+            product: T::zero(),
+            left: T::zero(),
+            right: T::zero(),
+        };
+        node.set_sample_rate(sample_rate);
+        node
+    }
+}
+
+impl<T: Real> AudioNode for Correlation<T> {
+    const ID: u64 = 142;
+    type Sample = T;
+    type Inputs = typenum::U2;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.product = T::zero();
+        self.left = T::zero();
+        self.right = T::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.smoothing = T::from_f64(pow(0.5, 1.0 / (self.timescale * sample_rate)));
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let (x, y) = (input[0], input[1]);
+        let a = self.smoothing;
+        let b = T::one() - a;
+        self.product = a * self.product + b * x * y;
+        self.left = a * self.left + b * x * x;
+        self.right = a * self.right + b * y * y;
+        let power = sqrt(self.left * self.right);
+        if power > T::from_f64(1.0e-12) {
+            [clamp11(self.product / power)].into()
+        } else {
+            [T::zero()].into()
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0).combine_nonlinear(input[1].distort(0.0), 0.0);
+        output
+    }
+}
+
 /// Mixing matrix with `M` input channels and `N` output channels.
 #[derive(Clone)]
 pub struct Mixer<M, N, T>
@@ -160,3 +451,49 @@ where
         output
     }
 }
+
+/// Spectral center channel processor for use with `resynth`.
+/// Compares the left and right channels (channels 0 and 1) of each FFT bin.
+/// Bins that are identical in both channels belong to the center, while bins that differ
+/// in level or phase belong to the sides. The center is either extracted
+/// or removed with a soft mask, and extraction and removal sum to the input.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct SpectralCenter {
+    /// Whether to extract (true) or remove (false) the center.
+    extract: bool,
+}
+
+#[cfg(feature = "std")]
+impl SpectralCenter {
+    /// Create center channel processor that extracts the center if `extract` is set
+    /// and removes it otherwise.
+    pub fn new(extract: bool) -> Self {
+        Self { extract }
+    }
+
+    /// Process one window.
+    pub fn process(&mut self, fft: &mut FftWindow) {
+        for i in 0..fft.bins() {
+            let left = fft.at(0, i);
+            let right = fft.at(1, i);
+            let energy = left.norm_sqr() + right.norm_sqr();
+            // Similarity is 1 when the channels are equal and 0 or less
+            // when they are hard panned or out of phase.
+            let similarity = if energy > 0.0 {
+                2.0 * (left * right.conj()).re / energy
+            } else {
+                0.0
+            };
+            let mask = squared(squared(clamp01(similarity)));
+            let center = (left + right) * (0.5 * mask);
+            if self.extract {
+                fft.set(0, i, center);
+                fft.set(1, i, center);
+            } else {
+                fft.set(0, i, left - center);
+                fft.set(1, i, right - center);
+            }
+        }
+    }
+}
@@ -0,0 +1,551 @@
+//! Multithreaded block processing of parallel branches.
+
+use super::audionode::*;
+use super::audiounit::*;
+use super::buffer::*;
+use super::denormal::*;
+use super::math::*;
+use super::signal::*;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use duplicate::duplicate_item;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How long an idle worker polls for new work before parking.
+/// This spans the gap between blocks at common block sizes and sample rates,
+/// so workers stay awake while audio is running and only park when it stops.
+const WORKER_SPIN_TIME: Duration = Duration::from_millis(5);
+
+/// Job processing task `i`.
+type Job = dyn Fn(usize) + Sync;
+
+/// State shared between the calling thread and the workers.
+struct PoolState {
+    /// Current job. Valid while the job is open and until all workers have left it.
+    job: UnsafeCell<Option<*const Job>>,
+    /// Number of tasks in the current job.
+    tasks: AtomicUsize,
+    /// Next task to take.
+    next: AtomicUsize,
+    /// Whether workers may join the current job.
+    open: AtomicBool,
+    /// Number of workers that have joined the current job and not left it yet.
+    joined: AtomicUsize,
+    /// Set when a task panics in a worker.
+    panicked: AtomicBool,
+    /// Whether the calling thread flushes denormals to zero. Workers follow suit.
+    flush: AtomicBool,
+    /// Incremented to publish a new job.
+    generation: AtomicUsize,
+    /// Set when the pool is dropped.
+    quit: AtomicBool,
+}
+
+// Safety: the job is written only while it is closed and no worker has joined it,
+// and published to them with sequentially consistent ordering.
+unsafe impl Send for PoolState {}
+unsafe impl Sync for PoolState {}
+
+impl PoolState {
+    /// Take and run tasks of the current job until there are none left.
+    /// Safety: the job must be valid.
+    #[inline]
+    unsafe fn work(&self) {
+        if let Some(job) = *self.job.get() {
+            let tasks = self.tasks.load(Ordering::Relaxed);
+            loop {
+                let i = self.next.fetch_add(1, Ordering::Relaxed);
+                if i >= tasks {
+                    break;
+                }
+                (*job)(i);
+            }
+        }
+    }
+}
+
+/// Pool of worker threads for processing the tasks of a job in parallel.
+/// The calling thread takes part in the work. Synchronization is lock-free:
+/// idle workers spin for a few milliseconds, then park until woken up for the next job.
+/// The calling thread never waits for workers that have not started on a job:
+/// it processes their share serially, so a descheduled worker cannot stall a block.
+/// It only waits for workers to finish the task they are on.
+/// A panic in a task is propagated to the calling thread.
+pub struct WorkerPool {
+    state: Arc<PoolState>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    /// Create a pool with the given number of worker `threads`.
+    /// With zero threads, all tasks are run on the calling thread.
+    pub fn new(threads: usize) -> Self {
+        let state = Arc::new(PoolState {
+            job: UnsafeCell::new(None),
+            tasks: AtomicUsize::new(0),
+            next: AtomicUsize::new(0),
+            open: AtomicBool::new(false),
+            joined: AtomicUsize::new(0),
+            panicked: AtomicBool::new(false),
+            flush: AtomicBool::new(false),
+            generation: AtomicUsize::new(0),
+            quit: AtomicBool::new(false),
+        });
+        let workers = (0..threads)
+            .map(|_| {
+                let state = state.clone();
+                std::thread::Builder::new()
+                    .name(String::from("fundsp worker"))
+                    .spawn(move || Self::worker(&state))
+                    .expect("WorkerPool: cannot spawn worker thread.")
+            })
+            .collect();
+        Self { state, workers }
+    }
+
+    /// Number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Worker thread main loop.
+    fn worker(state: &PoolState) {
+        let mut seen = 0;
+        loop {
+            let start = Instant::now();
+            let mut spins = 0usize;
+            loop {
+                let generation = state.generation.load(Ordering::Acquire);
+                if generation != seen {
+                    seen = generation;
+                    break;
+                }
+                // Check the clock only now and then, as it is slower than spinning.
+                spins = spins.wrapping_add(1);
+                if spins & 63 != 0 || start.elapsed() < WORKER_SPIN_TIME {
+                    core::hint::spin_loop();
+                } else {
+                    std::thread::park();
+                }
+            }
+            if state.quit.load(Ordering::Acquire) {
+                break;
+            }
+            // Join the job, unless the calling thread has already closed it.
+            state.joined.fetch_add(1, Ordering::SeqCst);
+            if state.open.load(Ordering::SeqCst) {
+                let _guard = if state.flush.load(Ordering::Relaxed) {
+                    Some(DenormalGuard::new())
+                } else {
+                    None
+                };
+                // Safety: the job stays valid until we leave it.
+                let result = catch_unwind(AssertUnwindSafe(|| unsafe { state.work() }));
+                if result.is_err() {
+                    state.panicked.store(true, Ordering::Relaxed);
+                }
+            }
+            // Leave the job. This is always done, even if a task panicked.
+            state.joined.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Run `job` for task indices `0..tasks`, each exactly once, and wait until all are done.
+    /// Tasks are distributed between the workers and the calling thread.
+    /// If a task panics, the panic is propagated after the other tasks are done.
+    pub fn run(&mut self, tasks: usize, job: &(dyn Fn(usize) + Sync)) {
+        if self.workers.is_empty() || tasks <= 1 {
+            for i in 0..tasks {
+                job(i);
+            }
+            return;
+        }
+        // Safety: the job is closed and no worker has joined it, so nobody else is accessing it.
+        // The job is cleared before we return.
+        unsafe {
+            let job: *const Job = core::mem::transmute(job as *const (dyn Fn(usize) + Sync));
+            *self.state.job.get() = Some(job);
+        }
+        self.state.tasks.store(tasks, Ordering::Relaxed);
+        self.state.next.store(0, Ordering::Relaxed);
+        self.state.panicked.store(false, Ordering::Relaxed);
+        self.state.flush.store(is_flushing(), Ordering::Relaxed);
+        self.state.open.store(true, Ordering::SeqCst);
+        self.state.generation.fetch_add(1, Ordering::Release);
+        for worker in self.workers.iter() {
+            worker.thread().unpark();
+        }
+        // Safety: the job is valid for the duration of this call.
+        let result = catch_unwind(AssertUnwindSafe(|| unsafe { self.state.work() }));
+        // All tasks have been taken. Close the job: workers that have not joined yet
+        // will not join anymore, so we wait only for those still finishing a task.
+        self.state.open.store(false, Ordering::SeqCst);
+        while self.state.joined.load(Ordering::SeqCst) > 0 {
+            core::hint::spin_loop();
+        }
+        // Safety: no worker is in the job anymore.
+        unsafe {
+            *self.state.job.get() = None;
+        }
+        if let Err(payload) = result {
+            resume_unwind(payload);
+        }
+        if self.state.panicked.load(Ordering::Relaxed) {
+            panic!("WorkerPool: a task panicked in a worker thread.");
+        }
+    }
+}
+
+/// Cloning a pool spawns a new set of worker threads.
+impl Clone for WorkerPool {
+    fn clone(&self) -> Self {
+        Self::new(self.threads())
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.state.quit.store(true, Ordering::Release);
+        self.state.generation.fetch_add(1, Ordering::Release);
+        for worker in self.workers.drain(..) {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+}
+
+/// How the branches of a `Parallel64` or `Parallel32` are connected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParallelMode {
+    /// Inputs and outputs of the branches are stacked (like `stack`).
+    Stack,
+    /// The branches receive the same input and their outputs are stacked (like `branch`).
+    Branch,
+    /// The branches receive the same input and their outputs are summed (like `bus`).
+    Bus,
+}
+
+/// Shareable pointer to the branches.
+#[derive(Clone, Copy)]
+struct ArmPtr<T>(*mut T);
+
+// Safety: every branch is accessed by exactly one thread at a time.
+unsafe impl<T> Send for ArmPtr<T> {}
+unsafe impl<T> Sync for ArmPtr<T> {}
+
+impl<T> ArmPtr<T> {
+    #[inline]
+    fn get(&self) -> *mut T {
+        self.0
+    }
+}
+
+/// Branch of a parallel unit with its output buffer.
+#[duplicate_item(
+    f48       Arm48       AudioUnit48;
+    [ f64 ]   [ Arm64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Arm32 ]   [ AudioUnit32 ];
+)]
+#[derive(Clone)]
+struct Arm48 {
+    unit: Box<dyn AudioUnit48>,
+    /// First input channel of the branch (in stack mode).
+    input: usize,
+    output: Buffer<f48>,
+}
+
+/// Process independent branches in parallel on a worker thread pool.
+/// The branches are processed concurrently block by block and combined
+/// according to `ParallelMode`. Single sample processing is done on the calling thread.
+/// Parallel processing pays off when the branches are heavy, such as the voices
+/// of a large polyphonic patch. Worker threads run at normal priority.
+/// Each clone has a worker pool of its own: cloning spawns new threads.
+///
+/// ### Example
+/// ```
+/// use fundsp::hacker::*;
+/// let voices: Vec<Box<dyn AudioUnit64>> = (1..=4)
+///     .map(|i| Box::new(saw_hz(110.0 * i as f64) >> lowpass_hz(1000.0, 1.0)) as Box<dyn AudioUnit64>)
+///     .collect();
+/// let mut synth = Parallel64::new(ParallelMode::Bus, voices, 2);
+/// assert_eq!(synth.inputs(), 0);
+/// assert_eq!(synth.outputs(), 1);
+/// let mut output = Buffer::<f64>::with_channels(1);
+/// synth.process(MAX_BUFFER_SIZE, &[], output.self_mut());
+/// ```
+#[duplicate_item(
+    f48       Parallel48       Arm48       AudioUnit48;
+    [ f64 ]   [ Parallel64 ]   [ Arm64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Parallel32 ]   [ Arm32 ]   [ AudioUnit32 ];
+)]
+#[derive(Clone)]
+pub struct Parallel48 {
+    mode: ParallelMode,
+    arms: Vec<Arm48>,
+    inputs: usize,
+    outputs: usize,
+    pool: WorkerPool,
+    tick_buffer: Vec<f48>,
+}
+
+#[duplicate_item(
+    f48       Parallel48       Arm48       AudioUnit48;
+    [ f64 ]   [ Parallel64 ]   [ Arm64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Parallel32 ]   [ Arm32 ]   [ AudioUnit32 ];
+)]
+impl Parallel48 {
+    /// Create a parallel unit from `units`, processed with the given number of worker `threads`
+    /// in addition to the calling thread. In `Branch` and `Bus` modes the units
+    /// must have the same number of inputs; in `Bus` mode also the same number of outputs.
+    pub fn new(mode: ParallelMode, units: Vec<Box<dyn AudioUnit48>>, threads: usize) -> Self {
+        assert!(!units.is_empty());
+        let inputs = match mode {
+            ParallelMode::Stack => units.iter().map(|unit| unit.inputs()).sum(),
+            _ => units[0].inputs(),
+        };
+        let outputs = match mode {
+            ParallelMode::Bus => units[0].outputs(),
+            _ => units.iter().map(|unit| unit.outputs()).sum(),
+        };
+        let mut input = 0;
+        let mut arms = Vec::with_capacity(units.len());
+        let mut max_outputs = 0;
+        for unit in units {
+            if mode != ParallelMode::Stack {
+                assert_eq!(unit.inputs(), inputs);
+            }
+            if mode == ParallelMode::Bus {
+                assert_eq!(unit.outputs(), outputs);
+            }
+            max_outputs = max(max_outputs, unit.outputs());
+            let arm = Arm48 {
+                input,
+                output: Buffer::with_channels(unit.outputs()),
+                unit,
+            };
+            if mode == ParallelMode::Stack {
+                input += arm.unit.inputs();
+            }
+            arms.push(arm);
+        }
+        Self {
+            mode,
+            arms,
+            inputs,
+            outputs,
+            pool: WorkerPool::new(threads),
+            tick_buffer: vec![0.0; max_outputs],
+        }
+    }
+
+    /// Connection mode.
+    pub fn mode(&self) -> ParallelMode {
+        self.mode
+    }
+
+    /// Number of branches.
+    pub fn branches(&self) -> usize {
+        self.arms.len()
+    }
+
+    /// Number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.pool.threads()
+    }
+}
+
+#[duplicate_item(
+    f48       Parallel48       Arm48       AudioUnit48;
+    [ f64 ]   [ Parallel64 ]   [ Arm64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Parallel32 ]   [ Arm32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for Parallel48 {
+    fn reset(&mut self) {
+        for arm in self.arms.iter_mut() {
+            arm.unit.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        for arm in self.arms.iter_mut() {
+            arm.unit.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        let mut channel = 0;
+        if self.mode == ParallelMode::Bus {
+            output.fill(0.0);
+        }
+        for arm in self.arms.iter_mut() {
+            let arm_input = match self.mode {
+                ParallelMode::Stack => &input[arm.input..arm.input + arm.unit.inputs()],
+                _ => input,
+            };
+            let outputs = arm.unit.outputs();
+            let tick_output = &mut self.tick_buffer[..outputs];
+            arm.unit.tick(arm_input, tick_output);
+            match self.mode {
+                ParallelMode::Bus => {
+                    for (y, x) in output.iter_mut().zip(tick_output.iter()) {
+                        *y += *x;
+                    }
+                }
+                _ => {
+                    output[channel..channel + outputs].copy_from_slice(tick_output);
+                    channel += outputs;
+                }
+            }
+        }
+    }
+
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        let mode = self.mode;
+        let arms = ArmPtr(self.arms.as_mut_ptr());
+        let job = move |i: usize| {
+            // Safety: the pool runs each task exactly once.
+            let arm = unsafe { &mut *arms.get().add(i) };
+            let arm_input = match mode {
+                ParallelMode::Stack => &input[arm.input..arm.input + arm.unit.inputs()],
+                _ => input,
+            };
+            let outputs = arm.unit.outputs();
+            arm.unit
+                .process(size, arm_input, arm.output.get_mut(outputs));
+        };
+        self.pool.run(self.arms.len(), &job);
+        let mut channel = 0;
+        for (i, arm) in self.arms.iter().enumerate() {
+            for j in 0..arm.unit.outputs() {
+                let x = &arm.output.at(j)[..size];
+                match self.mode {
+                    ParallelMode::Bus => {
+                        if i == 0 {
+                            output[j][..size].copy_from_slice(x);
+                        } else {
+                            for (y, x) in output[j][..size].iter_mut().zip(x.iter()) {
+                                *y += *x;
+                            }
+                        }
+                    }
+                    _ => {
+                        output[channel][..size].copy_from_slice(x);
+                        channel += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 106;
+        ID
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        let mut hash = hash.hash(self.get_id());
+        for arm in self.arms.iter_mut() {
+            hash = arm.unit.ping(probe, hash);
+        }
+        hash
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs);
+        let mut channel = 0;
+        for (i, arm) in self.arms.iter_mut().enumerate() {
+            let arm_input = match self.mode {
+                ParallelMode::Stack => copy_signal_frame(input, arm.input, arm.unit.inputs()),
+                _ => input.clone(),
+            };
+            let arm_output = arm.unit.route(&arm_input, frequency);
+            for j in 0..arm.unit.outputs() {
+                match self.mode {
+                    ParallelMode::Bus => {
+                        output[j] = if i == 0 {
+                            arm_output[j]
+                        } else {
+                            output[j].combine_linear(arm_output[j], 0.0, |x, y| x + y, |x, y| x + y)
+                        };
+                    }
+                    _ => {
+                        output[channel] = arm_output[j];
+                        channel += 1;
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    fn allocate(&mut self) {
+        for arm in self.arms.iter_mut() {
+            arm.output.allocate(arm.unit.outputs());
+            arm.unit.allocate();
+        }
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self
+            .arms
+            .iter_mut()
+            .map(|arm| arm.unit.describe())
+            .collect();
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+        .with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.arms.iter().flat_map(|x| x.unit.parameters()).collect()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.arms.iter().map(|x| x.unit.parameter_count()).sum()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        let mut index = index;
+        for x in self.arms.iter() {
+            let count = x.unit.parameter_count();
+            if index < count {
+                return x.unit.parameter(index);
+            }
+            index -= count;
+        }
+        panic!("Parameter index out of range.")
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        let mut index = index;
+        for x in self.arms.iter_mut() {
+            let count = x.unit.parameter_count();
+            if index < count {
+                x.unit.set_parameter(index, value);
+                return;
+            }
+            index -= count;
+        }
+        panic!("Parameter index out of range.")
+    }
+}
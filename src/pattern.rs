@@ -0,0 +1,250 @@
+//! Pattern generators that schedule sequencer events.
+
+use super::audiounit::*;
+use super::sequencer::*;
+use duplicate::duplicate_item;
+use funutd::Rnd;
+
+/// Targets that pattern generators can schedule events into.
+pub trait Schedule {
+    /// Type of units scheduled.
+    type Unit;
+
+    /// Schedule `unit` to play for `duration` seconds starting at `start_time` seconds,
+    /// relative to the current time. Returns the ID of the event.
+    fn schedule(&mut self, start_time: f64, duration: f64, unit: Self::Unit) -> EventId;
+}
+
+#[allow(clippy::unnecessary_cast)]
+#[duplicate_item(
+    f48       AudioUnit48       Sequencer48;
+    [ f64 ]   [ AudioUnit64 ]   [ Sequencer64 ];
+    [ f32 ]   [ AudioUnit32 ]   [ Sequencer32 ];
+)]
+impl Schedule for Sequencer48 {
+    type Unit = Box<dyn AudioUnit48>;
+
+    fn schedule(&mut self, start_time: f64, duration: f64, unit: Self::Unit) -> EventId {
+        self.push_relative(
+            start_time as f48,
+            (start_time + duration) as f48,
+            Fade::Smooth,
+            0.0,
+            0.0,
+            unit,
+        )
+    }
+}
+
+/// Step pattern with a trigger probability for each step.
+/// Steps with probability 1 always trigger and steps with probability 0 are rests.
+///
+/// ### Example: Euclidean Kick Drum
+/// ```
+/// use fundsp::hacker::*;
+/// let mut sequencer = Sequencer64::new(false, 1);
+/// let kick = Pattern::euclid(3, 8, 0);
+/// kick.schedule(&mut sequencer, 0.0, 0.125, 4, 0, |_step| {
+///     Box::new(sine_hz(60.0) * envelope(|t| exp(-t * 20.0)))
+/// });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pattern {
+    probability: Vec<f64>,
+}
+
+impl Pattern {
+    /// Create a step grid from per-step trigger probabilities in 0...1.
+    pub fn new(probability: &[f64]) -> Self {
+        Self {
+            probability: probability.to_vec(),
+        }
+    }
+
+    /// Euclidean rhythm that distributes `pulses` triggers as evenly as possible
+    /// over `steps` steps (`pulses` <= `steps`), rotated left by `rotation` steps.
+    pub fn euclid(pulses: usize, steps: usize, rotation: usize) -> Self {
+        assert!(pulses <= steps);
+        Self {
+            probability: (0..steps)
+                .map(|i| {
+                    if (i + rotation) * pulses % steps < pulses {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of steps in the pattern.
+    #[inline]
+    pub fn steps(&self) -> usize {
+        self.probability.len()
+    }
+
+    /// Trigger probability of step `i`.
+    #[inline]
+    pub fn probability(&self, i: usize) -> f64 {
+        self.probability[i]
+    }
+
+    /// Set trigger probability of step `i` in 0...1.
+    #[inline]
+    pub fn set_probability(&mut self, i: usize, probability: f64) {
+        self.probability[i] = probability;
+    }
+
+    /// Roll the pattern for `repeats` repetitions using random `seed`.
+    /// Returns the absolute step numbers that trigger.
+    /// The same seed always results in the same steps.
+    pub fn generate(&self, repeats: usize, seed: u64) -> Vec<usize> {
+        let mut rnd = Rnd::from_u64(seed);
+        (0..repeats * self.steps())
+            .filter(|&i| rnd.f64() < self.probability[i % self.steps()])
+            .collect()
+    }
+
+    /// Schedule `repeats` repetitions of the pattern into `sequencer`
+    /// starting `start_time` seconds from now, with steps lasting `step_duration` seconds.
+    /// Trigger probabilities are rolled with random `seed`.
+    /// The `factory` is called with the step index within the pattern
+    /// and returns the unit to play for that step.
+    /// Returns the IDs of the scheduled events.
+    pub fn schedule<S: Schedule>(
+        &self,
+        sequencer: &mut S,
+        start_time: f64,
+        step_duration: f64,
+        repeats: usize,
+        seed: u64,
+        mut factory: impl FnMut(usize) -> S::Unit,
+    ) -> Vec<EventId> {
+        self.generate(repeats, seed)
+            .into_iter()
+            .map(|i| {
+                sequencer.schedule(
+                    start_time + i as f64 * step_duration,
+                    step_duration,
+                    factory(i % self.steps()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Markov chain over a number of states.
+/// Each step moves to the next state with probabilities given by the transition weights
+/// of the current state.
+///
+/// ### Example: Random Melody
+/// ```
+/// use fundsp::hacker::*;
+/// let mut sequencer = Sequencer64::new(false, 1);
+/// let notes = [60.0, 62.0, 64.0];
+/// let chain = Markov::new(vec![vec![0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0], vec![2.0, 1.0, 0.0]]);
+/// chain.schedule(&mut sequencer, 0.0, 0.25, 0, 16, 1, |state| {
+///     Box::new(sine_hz(midi_hz(notes[state])) * 0.5)
+/// });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Markov {
+    /// Cumulative transition weights, normalized to 1, for each state.
+    cumulative: Vec<Vec<f64>>,
+}
+
+impl Markov {
+    /// Create Markov chain from a square matrix of non-negative transition weights.
+    /// Row `i` contains the relative weights of moving from state `i` to each state.
+    /// Each row must have a positive sum.
+    pub fn new(transition: Vec<Vec<f64>>) -> Self {
+        let states = transition.len();
+        let cumulative = transition
+            .into_iter()
+            .map(|row| {
+                assert!(row.len() == states);
+                let total: f64 = row.iter().sum();
+                assert!(total > 0.0);
+                let mut sum = 0.0;
+                row.iter()
+                    .map(|&weight| {
+                        sum += weight;
+                        sum / total
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { cumulative }
+    }
+
+    /// Number of states.
+    #[inline]
+    pub fn states(&self) -> usize {
+        self.cumulative.len()
+    }
+
+    /// Probability of moving from state `from` to state `to`.
+    pub fn probability(&self, from: usize, to: usize) -> f64 {
+        let row = &self.cumulative[from];
+        if to == 0 {
+            row[0]
+        } else {
+            row[to] - row[to - 1]
+        }
+    }
+
+    /// Choose the next state after `state` using `rnd`.
+    pub fn next(&self, state: usize, rnd: &mut Rnd) -> usize {
+        let x = rnd.f64();
+        let row = &self.cumulative[state];
+        row.iter()
+            .position(|&c| x < c)
+            .unwrap_or(self.states() - 1)
+    }
+
+    /// Generate a sequence of `length` states beginning with state `start`
+    /// using random `seed`. The same seed always results in the same sequence.
+    pub fn generate(&self, start: usize, length: usize, seed: u64) -> Vec<usize> {
+        let mut rnd = Rnd::from_u64(seed);
+        let mut state = start;
+        (0..length)
+            .map(|i| {
+                if i > 0 {
+                    state = self.next(state, &mut rnd);
+                }
+                state
+            })
+            .collect()
+    }
+
+    /// Schedule a sequence of `length` steps into `sequencer`, beginning with state `start`,
+    /// starting `start_time` seconds from now, with steps lasting `step_duration` seconds.
+    /// Transitions are rolled with random `seed`.
+    /// The `factory` is called with the state of each step
+    /// and returns the unit to play for that step.
+    /// Returns the IDs of the scheduled events.
+    #[allow(clippy::too_many_arguments)]
+    pub fn schedule<S: Schedule>(
+        &self,
+        sequencer: &mut S,
+        start_time: f64,
+        step_duration: f64,
+        start: usize,
+        length: usize,
+        seed: u64,
+        mut factory: impl FnMut(usize) -> S::Unit,
+    ) -> Vec<EventId> {
+        self.generate(start, length, seed)
+            .into_iter()
+            .enumerate()
+            .map(|(i, state)| {
+                sequencer.schedule(
+                    start_time + i as f64 * step_duration,
+                    step_duration,
+                    factory(state),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,262 @@
+//! Pitch shifting.
+
+use super::audionode::*;
+use super::math::*;
+#[cfg(feature = "std")]
+use super::resynth::*;
+use super::*;
+#[cfg(feature = "std")]
+use num_complex::Complex32;
+use numeric_array::typenum::*;
+
+/// Delay line pitch shifter. Two taps sweep through a delay line
+/// at a rate that transposes the input and crossfade so that
+/// one tap is silent whenever it jumps back.
+/// Latency is half the window length on average.
+/// Setting: pitch shift in semitones.
+/// - Input 0: input signal
+/// - Output 0: pitch shifted signal
+#[derive(Clone)]
+pub struct PitchShift<T: Float> {
+    /// Delay line. Length is a power of two.
+    buffer: Vec<T>,
+    /// Write index.
+    i: usize,
+    /// Pitch shift in semitones.
+    semitones: T,
+    /// Frequency ratio.
+    ratio: f64,
+    /// Phase of the first tap in 0...1.
+    phase: f64,
+    /// Window length in seconds.
+    window: f64,
+    /// Window length in samples.
+    length: f64,
+    sample_rate: f64,
+}
+
+impl<T: Float> PitchShift<T> {
+    /// Create pitch shifter. Shift is `semitones` and the crossfade window is `window` seconds.
+    /// - Allocates: the delay line.
+    pub fn new(semitones: T, window: f64) -> Self {
+        let mut node = Self {
+            buffer: Vec::new(),
+            i: 0,
+            semitones,
+            ratio: semitone_ratio(semitones.to_f64()),
+            phase: 0.0,
+            window,
+            length: 0.0,
+            sample_rate: 0.0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Pitch shift in semitones.
+    #[inline]
+    pub fn semitones(&self) -> T {
+        self.semitones
+    }
+
+    /// Set pitch shift in semitones.
+    #[inline]
+    pub fn set_semitones(&mut self, semitones: T) {
+        self.semitones = semitones;
+        self.ratio = semitone_ratio(semitones.to_f64());
+    }
+
+    /// Read the delay line `delay` samples back with linear interpolation.
+    #[inline]
+    fn read(&self, delay: f64) -> f64 {
+        let mask = self.buffer.len() - 1;
+        let d = floor(delay);
+        let j = (self.i.wrapping_sub(d as usize)) & mask;
+        let a = self.buffer[j].to_f64();
+        let b = self.buffer[j.wrapping_sub(1) & mask].to_f64();
+        a + (b - a) * (delay - d)
+    }
+}
+
+impl<T: Float> AudioNode for PitchShift<T> {
+    const ID: u64 = 114;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_semitones(setting);
+    }
+
+    fn reset(&mut self) {
+        self.i = 0;
+        self.phase = 0.0;
+        self.buffer.fill(T::zero());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            self.length = max(4.0, round(self.window * sample_rate));
+            let buffer_length = (self.length as usize + 4).next_power_of_two();
+            self.buffer.resize(buffer_length, T::zero());
+            self.reset();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.buffer[self.i] = input[0];
+        let mut output = 0.0;
+        for offset in [0.0, 0.5] {
+            let phase = self.phase + offset;
+            let phase = phase - floor(phase);
+            // The squared sine windows of the taps sum to one.
+            let window = squared(sin(phase * PI));
+            output += window * self.read(phase * self.length);
+        }
+        self.phase += (1.0 - self.ratio) / self.length;
+        self.phase -= floor(self.phase);
+        self.i = (self.i + 1) & (self.buffer.len() - 1);
+        [convert(output)].into()
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "semitones",
+            ParameterUnit::Unitless,
+            -24.0,
+            24.0,
+            0.0,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        assert!(index == 0);
+        self.semitones.to_f64()
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        assert!(index == 0);
+        self.set_semitones(T::from_f64(value));
+    }
+}
+
+/// Phase vocoder pitch shifter for use with `resynth`.
+/// Estimates the true frequency of each FFT bin from phase differences between
+/// windows and moves bins to transposed frequencies. Optionally, the spectral envelope
+/// is preserved, which keeps formants in place.
+/// Processes channel 0 of the window, which must be the window that
+/// the shifter was created for.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct SpectralShift {
+    /// Frequency ratio.
+    ratio: f32,
+    /// Whether to preserve formants.
+    formants: bool,
+    /// Analysis phases of the previous window.
+    last_phase: Vec<f32>,
+    /// Accumulated synthesis phases.
+    sum_phase: Vec<f32>,
+    /// Analysis magnitudes.
+    magnitude: Vec<f32>,
+    /// Analysis frequencies in bins.
+    frequency: Vec<f32>,
+    /// Synthesis magnitudes.
+    shift_magnitude: Vec<f32>,
+    /// Synthesis frequencies in bins.
+    shift_frequency: Vec<f32>,
+    /// Spectral envelope.
+    envelope: Vec<f32>,
+}
+
+#[cfg(feature = "std")]
+impl SpectralShift {
+    /// Create spectral pitch shifter for `resynth` windows of `window_length` samples.
+    /// Shift is `semitones` and spectral envelope is kept in place if `preserve_formants` is set.
+    /// - Allocates: all needed buffers.
+    pub fn new(window_length: usize, semitones: f64, preserve_formants: bool) -> Self {
+        let bins = (window_length >> 1) + 1;
+        Self {
+            ratio: semitone_ratio(semitones) as f32,
+            formants: preserve_formants,
+            last_phase: vec![0.0; bins],
+            sum_phase: vec![0.0; bins],
+            magnitude: vec![0.0; bins],
+            frequency: vec![0.0; bins],
+            shift_magnitude: vec![0.0; bins],
+            shift_frequency: vec![0.0; bins],
+            envelope: vec![0.0; bins],
+        }
+    }
+
+    /// Estimate spectral envelope with a moving average of magnitudes.
+    fn estimate_envelope(&mut self) {
+        let bins = self.magnitude.len();
+        let radius = max(1, bins >> 6);
+        let mut sum: f32 = self.magnitude[..min(radius, bins)].iter().sum();
+        for k in 0..bins {
+            if k + radius < bins {
+                sum += self.magnitude[k + radius];
+            }
+            if k > radius {
+                sum -= self.magnitude[k - radius - 1];
+            }
+            let count = min(k + radius + 1, bins) - k.saturating_sub(radius);
+            self.envelope[k] = sum / count as f32;
+        }
+    }
+
+    /// Process one window.
+    pub fn process(&mut self, fft: &mut FftWindow) {
+        let length = fft.length() as f32;
+        let hop = length / 4.0;
+        let bins = fft.bins();
+        let tau = TAU as f32;
+        for k in 0..bins {
+            let (magnitude, phase) = fft.at(0, k).to_polar();
+            let expected = tau * k as f32 * hop / length;
+            let delta = phase - self.last_phase[k] - expected;
+            let delta = delta - tau * round(delta / tau);
+            self.last_phase[k] = phase;
+            self.magnitude[k] = magnitude;
+            self.frequency[k] = k as f32 + delta * length / (tau * hop);
+        }
+        if self.formants {
+            self.estimate_envelope();
+        }
+        self.shift_magnitude.fill(0.0);
+        self.shift_frequency.fill(0.0);
+        for k in 0..bins {
+            let j = round(k as f32 * self.ratio) as usize;
+            if j >= bins {
+                break;
+            }
+            let mut magnitude = self.magnitude[k];
+            if self.formants {
+                magnitude *= self.envelope[j] / (self.envelope[k] + 1.0e-9);
+            }
+            self.shift_magnitude[j] += magnitude;
+            self.shift_frequency[j] = self.frequency[k] * self.ratio;
+        }
+        for j in 0..bins {
+            self.sum_phase[j] =
+                (self.sum_phase[j] + tau * self.shift_frequency[j] * hop / length) % tau;
+            let mut value = Complex32::from_polar(self.shift_magnitude[j], self.sum_phase[j]);
+            // The DC and Nyquist bins of a real signal are real.
+            if j == 0 || j == bins - 1 {
+                value.im = 0.0;
+            }
+            fft.set(0, j, value);
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! Gapless playback of a queue of wave segments that can be controlled in real time.
+
+use super::audiounit::*;
+use super::math::*;
+use super::sequencer::Fade;
+use super::signal::*;
+use super::wave::*;
+use super::*;
+use duplicate::duplicate_item;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
+
+/// Maximum number of segments waiting in a playlist queue.
+const PLAYLIST_CAPACITY: usize = 1024;
+
+/// Maximum number of segments sounding simultaneously.
+const PLAYLIST_VOICES: usize = 8;
+
+#[duplicate_item(
+    f48       Wave48       Segment48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ];
+)]
+/// A playlist segment.
+#[derive(Clone)]
+pub struct Segment48 {
+    /// Wave to play, at its own sample rate.
+    pub wave: Arc<Wave48>,
+    /// Amplitude gain.
+    pub gain: f48,
+    /// Crossfade curve.
+    pub fade: Fade,
+    /// Crossfade time in seconds with the end of the previous segment. Zero is gapless.
+    pub crossfade: f48,
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ];
+)]
+#[derive(Clone, Default)]
+enum PlaylistMessage48 {
+    #[default]
+    Nothing,
+    /// Append segment to the queue.
+    Enqueue(Segment48),
+    /// Fade out playing segments over the time (in seconds) and start the next one.
+    Skip(f48),
+    /// Fade out playing segments over the time (in seconds) and empty the queue.
+    Clear(f48),
+    /// Return a wave for deallocation.
+    #[allow(dead_code)]
+    Return(Arc<Wave48>),
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48       Playlist48       PlaylistBackend48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ]   [ Playlist64 ]   [ PlaylistBackend64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ]   [ Playlist32 ]   [ PlaylistBackend32 ];
+)]
+/// Frontend for a playlist that plays wave segments back to back.
+pub struct Playlist48 {
+    channels: usize,
+    receiver: Receiver<PlaylistMessage48>,
+    sender: Sender<PlaylistMessage48>,
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48       Playlist48       PlaylistBackend48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ]   [ Playlist64 ]   [ PlaylistBackend64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ]   [ Playlist32 ]   [ PlaylistBackend32 ];
+)]
+impl Playlist48 {
+    /// Create a new, empty playlist with `channels` outputs.
+    /// Waves with fewer channels are repeated cyclically across the outputs.
+    /// Returns (frontend, backend) pair.
+    pub fn new(channels: usize) -> (Playlist48, PlaylistBackend48) {
+        let (sender_a, receiver_a) = channel(PLAYLIST_CAPACITY);
+        let (sender_b, receiver_b) = channel(PLAYLIST_CAPACITY);
+        let playlist = Playlist48 {
+            channels,
+            receiver: receiver_a,
+            sender: sender_b,
+        };
+        let backend = PlaylistBackend48 {
+            channels,
+            sample_rate: DEFAULT_SR,
+            queue: VecDeque::with_capacity(PLAYLIST_CAPACITY),
+            voices: Vec::with_capacity(PLAYLIST_VOICES),
+            skip: None,
+            receiver: receiver_b,
+            sender: sender_a,
+        };
+        (playlist, backend)
+    }
+
+    /// Number of outputs.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Deallocate waves that were sent back and send `message` to the backend.
+    fn send(&mut self, message: PlaylistMessage48) {
+        while self.receiver.try_recv().is_ok() {}
+        if self.sender.try_send(message).is_ok() {}
+    }
+
+    /// Append `wave` to the queue with amplitude `gain`.
+    /// The segment is crossfaded with the end of the previous segment
+    /// over `crossfade` seconds using `fade` curves; zero crossfade time is gapless.
+    pub fn enqueue(&mut self, wave: &Arc<Wave48>, gain: f48, fade: Fade, crossfade: f48) {
+        assert!(wave.channels() > 0);
+        self.send(PlaylistMessage48::Enqueue(Segment48 {
+            wave: wave.clone(),
+            gain,
+            fade,
+            crossfade,
+        }));
+    }
+
+    /// Fade out the playing segment over `fade_time` seconds
+    /// while the next segment in the queue, if any, fades in.
+    pub fn skip(&mut self, fade_time: f48) {
+        self.send(PlaylistMessage48::Skip(fade_time));
+    }
+
+    /// Fade out the playing segment over `fade_time` seconds and empty the queue.
+    pub fn clear(&mut self, fade_time: f48) {
+        self.send(PlaylistMessage48::Clear(fade_time));
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       PlaylistVoice48;
+    [ f64 ]   [ Wave64 ]   [ PlaylistVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ PlaylistVoice32 ];
+)]
+/// A sounding segment.
+#[derive(Clone)]
+struct PlaylistVoice48 {
+    wave: Arc<Wave48>,
+    gain: f48,
+    /// Next sample index.
+    index: usize,
+    fade_in: Fade,
+    /// Fade in length in samples.
+    fade_in_length: usize,
+    fade_out: Fade,
+    /// Index where fade out starts, if fading out.
+    fade_out_start: Option<usize>,
+    /// Fade out length in samples.
+    fade_out_length: usize,
+}
+
+#[duplicate_item(
+    f48       Wave48       PlaylistVoice48;
+    [ f64 ]   [ Wave64 ]   [ PlaylistVoice64 ];
+    [ f32 ]   [ Wave32 ]   [ PlaylistVoice32 ];
+)]
+impl PlaylistVoice48 {
+    /// Number of samples left to play.
+    fn remaining(&self) -> usize {
+        let end = match self.fade_out_start {
+            Some(start) => min(self.wave.len(), start + self.fade_out_length),
+            None => self.wave.len(),
+        };
+        end.saturating_sub(self.index)
+    }
+
+    /// Start fading out now over `length` samples, unless already fading out sooner.
+    fn fade_out(&mut self, fade: &Fade, length: usize) {
+        if self.fade_out_start.is_none() || length < self.remaining() {
+            self.fade_out = fade.clone();
+            self.fade_out_start = Some(self.index);
+            self.fade_out_length = length;
+        }
+    }
+
+    /// Gain at the current index.
+    #[inline]
+    fn current_gain(&self) -> f48 {
+        let mut gain = self.gain;
+        if self.index < self.fade_in_length {
+            gain *= self
+                .fade_in
+                .at(self.index as f48 / self.fade_in_length as f48);
+        }
+        if let Some(start) = self.fade_out_start {
+            let phase = (self.index - start + 1) as f48 / (self.fade_out_length + 1) as f48;
+            gain *= self.fade_out.at(1.0 - phase);
+        }
+        gain
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48       PlaylistVoice48       PlaylistBackend48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ]   [ PlaylistVoice64 ]   [ PlaylistBackend64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ]   [ PlaylistVoice32 ]   [ PlaylistBackend32 ];
+)]
+/// Backend for a playlist. Plays queued segments back to back.
+/// - Outputs: playlist
+pub struct PlaylistBackend48 {
+    channels: usize,
+    sample_rate: f64,
+    queue: VecDeque<Segment48>,
+    voices: Vec<PlaylistVoice48>,
+    /// Pending skip or clear fade time in seconds.
+    skip: Option<f48>,
+    receiver: Receiver<PlaylistMessage48>,
+    sender: Sender<PlaylistMessage48>,
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48       PlaylistVoice48       PlaylistBackend48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ]   [ PlaylistVoice64 ]   [ PlaylistBackend64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ]   [ PlaylistVoice32 ]   [ PlaylistBackend32 ];
+)]
+impl Clone for PlaylistBackend48 {
+    fn clone(&self) -> Self {
+        // Backends cannot be cloned effectively. Allocate a dummy channel.
+        let (sender, receiver) = channel(1);
+        Self {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            queue: self.queue.clone(),
+            voices: self.voices.clone(),
+            skip: self.skip,
+            receiver,
+            sender,
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48       PlaylistVoice48       PlaylistBackend48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ]   [ PlaylistVoice64 ]   [ PlaylistBackend64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ]   [ PlaylistVoice32 ]   [ PlaylistBackend32 ];
+)]
+impl PlaylistBackend48 {
+    /// Handle messages from the frontend.
+    fn handle_messages(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                PlaylistMessage48::Enqueue(segment) => {
+                    if self.queue.len() < PLAYLIST_CAPACITY {
+                        self.queue.push_back(segment);
+                    } else {
+                        self.give_back(segment.wave);
+                    }
+                }
+                PlaylistMessage48::Skip(fade_time) => {
+                    self.skip = Some(fade_time);
+                }
+                PlaylistMessage48::Clear(fade_time) => {
+                    while let Some(segment) = self.queue.pop_front() {
+                        self.give_back(segment.wave);
+                    }
+                    self.skip = Some(fade_time);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Send a wave back to the frontend for deallocation.
+    #[inline]
+    fn give_back(&mut self, wave: Arc<Wave48>) {
+        if self.sender.try_send(PlaylistMessage48::Return(wave)).is_ok() {}
+    }
+
+    /// Start the next segment from the queue.
+    fn start_next(&mut self, fade_in_length: usize) {
+        if let Some(segment) = self.queue.pop_front() {
+            if self.voices.len() == PLAYLIST_VOICES {
+                let voice = self.voices.remove(0);
+                self.give_back(voice.wave);
+            }
+            self.voices.push(PlaylistVoice48 {
+                wave: segment.wave,
+                gain: segment.gain,
+                index: 0,
+                fade_in: segment.fade.clone(),
+                fade_in_length,
+                fade_out: segment.fade,
+                fade_out_start: None,
+                fade_out_length: 0,
+            });
+        }
+    }
+
+    /// Retire finished segments and start new ones as needed.
+    #[allow(clippy::unnecessary_cast)]
+    fn update(&mut self) {
+        let mut i = 0;
+        while i < self.voices.len() {
+            if self.voices[i].remaining() == 0 {
+                let voice = self.voices.remove(i);
+                self.give_back(voice.wave);
+            } else {
+                i += 1;
+            }
+        }
+        if let Some(fade_time) = self.skip.take() {
+            let length = max(1, round(fade_time as f64 * self.sample_rate) as usize);
+            let fade = match self.queue.front() {
+                Some(segment) => segment.fade.clone(),
+                None => Fade::Smooth,
+            };
+            for voice in self.voices.iter_mut() {
+                voice.fade_out(&fade, length);
+            }
+            if self.voices.is_empty() {
+                self.start_next(0);
+            } else {
+                self.start_next(length);
+            }
+            return;
+        }
+        if let Some(segment) = self.queue.front() {
+            let crossfade = round(segment.crossfade as f64 * self.sample_rate) as usize;
+            let fade = segment.fade.clone();
+            match self.voices.last_mut() {
+                None => self.start_next(0),
+                Some(voice) if voice.fade_out_start.is_some() => {
+                    // Crossfade with a segment that is being skipped.
+                    let length = max(1, voice.remaining());
+                    self.start_next(length);
+                }
+                Some(voice) if voice.remaining() <= crossfade => {
+                    let length = voice.remaining();
+                    voice.fade_out(&fade, length);
+                    self.start_next(length);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Render the next frame.
+    #[inline]
+    fn next_frame(&mut self, mut store: impl FnMut(usize, f48)) {
+        self.update();
+        for channel in 0..self.channels {
+            store(channel, 0.0);
+        }
+        for voice in self.voices.iter_mut() {
+            if voice.remaining() == 0 {
+                continue;
+            }
+            let gain = voice.current_gain();
+            let wave_channels = voice.wave.channels();
+            for channel in 0..self.channels {
+                let x = voice.wave.at(channel % wave_channels, voice.index) * gain;
+                store(channel, x);
+            }
+            voice.index += 1;
+        }
+    }
+
+    /// Add the next frame to a block at index `i`.
+    #[inline]
+    fn next_frame_at(&mut self, i: usize, output: &mut [&mut [f48]]) {
+        for channel in output.iter_mut() {
+            channel[i] = 0.0;
+        }
+        self.next_frame(|channel, x| output[channel][i] += x);
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Segment48       PlaylistMessage48       PlaylistVoice48       PlaylistBackend48       AudioUnit48;
+    [ f64 ]   [ Wave64 ]   [ Segment64 ]   [ PlaylistMessage64 ]   [ PlaylistVoice64 ]   [ PlaylistBackend64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ Wave32 ]   [ Segment32 ]   [ PlaylistMessage32 ]   [ PlaylistVoice32 ]   [ PlaylistBackend32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for PlaylistBackend48 {
+    /// Stop playing segments. The queue is retained.
+    fn reset(&mut self) {
+        while let Some(voice) = self.voices.pop() {
+            self.give_back(voice.wave);
+        }
+        self.skip = None;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &[f48], output: &mut [f48]) {
+        self.handle_messages();
+        output.fill(0.0);
+        self.next_frame(|channel, x| output[channel] += x);
+    }
+
+    fn process(&mut self, size: usize, _input: &[&[f48]], output: &mut [&mut [f48]]) {
+        self.handle_messages();
+        for i in 0..size {
+            self.next_frame_at(i, output);
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn outputs(&self) -> usize {
+        self.channels
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..self.outputs() {
+            output[i] = Signal::Latency(0.0);
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 128;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+
+    fn allocate(&mut self) {
+        self.queue.reserve(PLAYLIST_CAPACITY);
+        self.voices.reserve(PLAYLIST_VOICES);
+    }
+}
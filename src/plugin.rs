@@ -0,0 +1,324 @@
+//! Parameter bridge for embedding graphs in audio plugins.
+
+use super::audionode::*;
+use super::audiounit::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use duplicate::duplicate_item;
+use rsor::Slice;
+pub use thingbuf::mpsc::blocking::Sender;
+use thingbuf::mpsc::blocking::{channel, Receiver};
+
+/// Parameter automation event.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Automation {
+    /// Offset in samples from the start of the next processed block.
+    pub offset: usize,
+    /// Parameter index.
+    pub index: usize,
+    /// Plain (not normalized) parameter value.
+    pub value: f64,
+}
+
+impl Automation {
+    /// Set parameter `index` to `value` at `offset` samples from the start of the next block.
+    pub fn new(offset: usize, index: usize, value: f64) -> Self {
+        Self {
+            offset,
+            index,
+            value,
+        }
+    }
+}
+
+/// Plugin parameter. Plugin frameworks identify parameters by stable string IDs
+/// and automate them with normalized values in 0...1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginParameter {
+    /// Unique identifier. It is the parameter name, suffixed with an
+    /// occurrence number if the name appears earlier in the graph, for example, `cutoff 2`.
+    pub id: String,
+    /// Parameter description.
+    pub info: ParameterInfo,
+}
+
+impl PluginParameter {
+    /// Create plugin parameters from a list of parameters.
+    pub fn list(parameters: Vec<ParameterInfo>) -> Vec<PluginParameter> {
+        let mut list: Vec<PluginParameter> = Vec::with_capacity(parameters.len());
+        for info in parameters {
+            let occurrences = list
+                .iter()
+                .filter(|parameter| parameter.info.name == info.name)
+                .count();
+            let id = if occurrences == 0 {
+                info.name.clone()
+            } else {
+                format!("{} {}", info.name, occurrences + 1)
+            };
+            list.push(PluginParameter { id, info });
+        }
+        list
+    }
+
+    /// Whether the parameter is mapped logarithmically to the normalized range.
+    /// Frequencies and times with a positive finite range are logarithmic.
+    pub fn is_logarithmic(&self) -> bool {
+        matches!(self.info.unit, ParameterUnit::Hz | ParameterUnit::Seconds)
+            && self.info.min > 0.0
+            && self.info.max.is_finite()
+    }
+
+    /// Convert plain `value` to a normalized value in 0...1.
+    pub fn normalize(&self, value: f64) -> f64 {
+        let (min, max) = (self.info.min, self.info.max);
+        if !min.is_finite() || !max.is_finite() || max <= min {
+            return 0.0;
+        }
+        let value = clamp(min, max, value);
+        if self.is_logarithmic() {
+            log(value / min) / log(max / min)
+        } else {
+            (value - min) / (max - min)
+        }
+    }
+
+    /// Convert `normalized` value in 0...1 to a plain value.
+    pub fn denormalize(&self, normalized: f64) -> f64 {
+        let (min, max) = (self.info.min, self.info.max);
+        if !min.is_finite() || !max.is_finite() || max <= min {
+            return self.info.default;
+        }
+        let normalized = clamp01(normalized);
+        if self.is_logarithmic() {
+            min * pow(max / min, normalized)
+        } else {
+            lerp(min, max, normalized)
+        }
+    }
+}
+
+/// Plugin unit. Wraps a unit and applies parameter automation sample accurately.
+/// Blocks are split at automation offsets; `process` accepts blocks of any size.
+/// Automation can be sent from other threads via the sender returned from `new`
+/// or queued from the processing thread with `automate`.
+#[duplicate_item(
+    f48       PluginUnit48       AudioUnit48;
+    [ f64 ]   [ PluginUnit64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ PluginUnit32 ]   [ AudioUnit32 ];
+)]
+pub struct PluginUnit48 {
+    unit: Box<dyn AudioUnit48>,
+    receiver: Receiver<Automation>,
+    /// Pending automation in order of offset.
+    events: Vec<Automation>,
+    input: Vec<Vec<f48>>,
+    output: Vec<Vec<f48>>,
+    input_slice: Slice<[f48]>,
+    output_slice: Slice<[f48]>,
+}
+
+#[duplicate_item(
+    f48       PluginUnit48       AudioUnit48;
+    [ f64 ]   [ PluginUnit64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ PluginUnit32 ]   [ AudioUnit32 ];
+)]
+impl Clone for PluginUnit48 {
+    fn clone(&self) -> Self {
+        // Receiver cannot be cloned, so instantiate a dummy channel.
+        let (_sender, receiver) = channel(1);
+        Self {
+            unit: self.unit.clone(),
+            receiver,
+            events: self.events.clone(),
+            input: self.input.clone(),
+            output: self.output.clone(),
+            input_slice: Slice::new(),
+            output_slice: Slice::new(),
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       PluginUnit48       AudioUnit48;
+    [ f64 ]   [ PluginUnit64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ PluginUnit32 ]   [ AudioUnit32 ];
+)]
+impl PluginUnit48 {
+    /// Number of automation events that can be pending without allocation.
+    pub const CAPACITY: usize = 256;
+
+    /// Create plugin unit. Returns pair `(sender, unit)`
+    /// where automation can be sent through `sender`.
+    pub fn new(unit: Box<dyn AudioUnit48>) -> (Sender<Automation>, Self) {
+        let (sender, receiver) = channel(Self::CAPACITY);
+        let input = vec![Vec::new(); unit.inputs()];
+        let output = vec![Vec::new(); unit.outputs()];
+        let node = Self {
+            unit,
+            receiver,
+            events: Vec::with_capacity(Self::CAPACITY),
+            input,
+            output,
+            input_slice: Slice::new(),
+            output_slice: Slice::new(),
+        };
+        (sender, node)
+    }
+
+    /// Plugin parameters of the contained unit.
+    pub fn plugin_parameters(&self) -> Vec<PluginParameter> {
+        PluginParameter::list(self.unit.parameters())
+    }
+
+    /// Index of the plugin parameter with identifier `id`, if any.
+    pub fn plugin_parameter_index(&self, id: &str) -> Option<usize> {
+        self.plugin_parameters()
+            .iter()
+            .position(|parameter| parameter.id == id)
+    }
+
+    /// Queue automation. Events at the same offset are applied in the order they were queued.
+    /// Allocates if there are more than `CAPACITY` pending events.
+    pub fn automate(&mut self, automation: Automation) {
+        let position = self
+            .events
+            .partition_point(|event| event.offset <= automation.offset);
+        self.events.insert(position, automation);
+    }
+
+    /// Access the contained unit.
+    pub fn unit(&self) -> &dyn AudioUnit48 {
+        &*self.unit
+    }
+
+    /// Access the contained unit mutably.
+    pub fn unit_mut(&mut self) -> &mut dyn AudioUnit48 {
+        &mut *self.unit
+    }
+
+    /// Move automation sent from other threads to the pending queue.
+    fn receive_automation(&mut self) {
+        while let Result::Ok(automation) = self.receiver.try_recv() {
+            self.automate(automation);
+        }
+    }
+
+    /// Apply pending events due at `offset`. Returns the offset of the next event, if any.
+    fn apply_automation(&mut self, offset: usize) -> Option<usize> {
+        let due = self.events.partition_point(|event| event.offset <= offset);
+        for event in &self.events[..due] {
+            self.unit.set_parameter(event.index, event.value);
+        }
+        self.events.drain(..due);
+        self.events.first().map(|event| event.offset)
+    }
+
+    /// Make pending event offsets relative to the next block after `size` samples.
+    fn advance_automation(&mut self, size: usize) {
+        for event in self.events.iter_mut() {
+            event.offset -= size;
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       PluginUnit48       AudioUnit48;
+    [ f64 ]   [ PluginUnit64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ PluginUnit32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for PluginUnit48 {
+    fn reset(&mut self) {
+        self.unit.reset();
+    }
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.unit.set_sample_rate(sample_rate);
+    }
+    fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        self.receive_automation();
+        self.apply_automation(0);
+        self.unit.tick(input, output);
+        self.advance_automation(1);
+    }
+    fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        self.receive_automation();
+        let mut next = self.apply_automation(0);
+        if size <= MAX_BUFFER_SIZE && next.is_none_or(|offset| offset >= size) {
+            self.unit.process(size, input, output);
+        } else {
+            for buffer in self.input.iter_mut().chain(self.output.iter_mut()) {
+                buffer.resize(MAX_BUFFER_SIZE, 0.0);
+            }
+            let mut i = 0;
+            while i < size {
+                let end = min(size, i + MAX_BUFFER_SIZE);
+                let end = next.map_or(end, |offset| min(end, offset));
+                let n = end - i;
+                for (buffer, input) in self.input.iter_mut().zip(input.iter()) {
+                    buffer[..n].copy_from_slice(&input[i..end]);
+                }
+                self.unit.process(
+                    n,
+                    self.input_slice.from_refs(&self.input),
+                    self.output_slice.from_muts(&mut self.output),
+                );
+                for (buffer, output) in self.output.iter().zip(output.iter_mut()) {
+                    output[i..end].copy_from_slice(&buffer[..n]);
+                }
+                i = end;
+                if i < size {
+                    next = self.apply_automation(i);
+                }
+            }
+        }
+        self.advance_automation(size);
+    }
+    fn inputs(&self) -> usize {
+        self.unit.inputs()
+    }
+    fn outputs(&self) -> usize {
+        self.unit.outputs()
+    }
+    fn get_id(&self) -> u64 {
+        self.unit.get_id()
+    }
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.unit.ping(probe, hash)
+    }
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.unit.route(input, frequency)
+    }
+    fn footprint(&self) -> usize {
+        self.unit.footprint()
+    }
+    fn allocate(&mut self) {
+        for buffer in self.input.iter_mut().chain(self.output.iter_mut()) {
+            buffer.resize(MAX_BUFFER_SIZE, 0.0);
+        }
+        self.unit.allocate();
+    }
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::new(
+            short_type_name::<Self>(),
+            self.get_id(),
+            self.inputs(),
+            self.outputs(),
+            self.latency(),
+        )
+        .with_children(vec![self.unit.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.unit.parameters()
+    }
+    fn parameter_count(&self) -> usize {
+        self.unit.parameter_count()
+    }
+    fn parameter(&self, index: usize) -> f64 {
+        self.unit.parameter(index)
+    }
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.unit.set_parameter(index, value);
+    }
+}
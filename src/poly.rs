@@ -0,0 +1,357 @@
+//! Polyphonic voice management.
+
+use super::audionode::*;
+use super::buffer::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+
+/// Voice stealing strategy of `Poly`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoiceSteal {
+    /// Cycle through the voices in order.
+    #[default]
+    RoundRobin,
+    /// Take the voice released the longest time ago, or if all voices are held,
+    /// the voice whose note started the longest time ago.
+    Oldest,
+    /// Take the quietest released voice, or if all voices are held, the quietest voice.
+    Quietest,
+}
+
+/// Note event for `Poly`. Used also as the setting of `Poly`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PolyEvent {
+    /// Release all held notes.
+    #[default]
+    AllOff,
+    /// Start a note. The key identifies the note, for example, a MIDI note number.
+    /// Pitch is in Hz and velocity is in 0...1.
+    On { key: i64, pitch: f64, velocity: f64 },
+    /// Release the note with the given key.
+    Off { key: i64 },
+}
+
+/// State of a single voice.
+#[derive(Clone, Default)]
+struct Voice<T: Float> {
+    /// Key of the held note, if any.
+    key: Option<i64>,
+    pitch: T,
+    gate: T,
+    velocity: T,
+    /// If set, the gate is held low for one sample to retrigger the voice.
+    retrigger: bool,
+    /// Note on or note off time, whichever is later, as an event count.
+    time: u64,
+    /// Peak level of the voice output.
+    level: T,
+}
+
+/// Polyphonic synthesizer. Contains `N` copies of a voice with pitch, gate and velocity inputs
+/// and manages note-on and note-off events with a voice stealing strategy (`VoiceSteal`).
+/// The outputs of the voices are summed.
+/// Setting: `PolyEvent`.
+/// - Output 0: sum of voice outputs
+///
+/// The voice has three inputs:
+/// - Input 0: pitch in Hz
+/// - Input 1: gate (1 = note held, 0 = note released)
+/// - Input 2: velocity in 0...1
+#[derive(Clone)]
+pub struct Poly<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = U3, Outputs = U1>,
+{
+    x: Frame<X, N>,
+    voice: Vec<Voice<T>>,
+    steal: VoiceSteal,
+    /// Next voice in round robin order.
+    next: usize,
+    /// Event counter.
+    time: u64,
+    /// Per sample decay of voice levels.
+    decay: T,
+    input: Buffer<T>,
+    output: Buffer<T>,
+}
+
+impl<N, T, X> Poly<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = U3, Outputs = U1>,
+{
+    /// Create polyphonic synthesizer from voices.
+    pub fn new(x: Frame<X, N>) -> Self {
+        let mut node = Self {
+            x,
+            voice: vec![Voice::default(); N::USIZE],
+            steal: VoiceSteal::default(),
+            next: 0,
+            time: 0,
+            decay: T::zero(),
+            input: Buffer::with_channels(3),
+            output: Buffer::with_channels(1),
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Voice stealing strategy.
+    #[inline]
+    pub fn steal(&self) -> VoiceSteal {
+        self.steal
+    }
+
+    /// Set voice stealing strategy.
+    #[inline]
+    pub fn set_steal(&mut self, steal: VoiceSteal) {
+        self.steal = steal;
+    }
+
+    /// Number of voices holding a note.
+    pub fn held(&self) -> usize {
+        self.voice.iter().filter(|voice| voice.key.is_some()).count()
+    }
+
+    /// Access a contained voice.
+    #[inline]
+    pub fn node_mut(&mut self, index: usize) -> &mut X {
+        &mut self.x[index]
+    }
+
+    /// Access a contained voice.
+    #[inline]
+    pub fn node(&self, index: usize) -> &X {
+        &self.x[index]
+    }
+
+    /// Choose a voice for a new note.
+    fn allocate_voice(&mut self) -> usize {
+        match self.steal {
+            VoiceSteal::RoundRobin => {
+                let i = self.next;
+                self.next = (self.next + 1) % N::USIZE;
+                i
+            }
+            VoiceSteal::Oldest => {
+                let free = (0..N::USIZE)
+                    .filter(|&i| self.voice[i].key.is_none())
+                    .min_by_key(|&i| self.voice[i].time);
+                free.unwrap_or_else(|| {
+                    (0..N::USIZE)
+                        .min_by_key(|&i| self.voice[i].time)
+                        .unwrap_or(0)
+                })
+            }
+            VoiceSteal::Quietest => {
+                let quietest = |voices: &mut dyn Iterator<Item = usize>| {
+                    voices.fold(None, |best: Option<usize>, i| match best {
+                        Some(j) if self.voice[j].level <= self.voice[i].level => Some(j),
+                        _ => Some(i),
+                    })
+                };
+                let free = quietest(&mut (0..N::USIZE).filter(|&i| self.voice[i].key.is_none()));
+                free.or_else(|| quietest(&mut (0..N::USIZE))).unwrap_or(0)
+            }
+        }
+    }
+
+    /// Start a note. The key identifies the note for `note_off`.
+    /// Pitch is in Hz and velocity is in 0...1.
+    /// A note with a held key retriggers its voice.
+    /// Returns the index of the voice playing the note.
+    pub fn note_on(&mut self, key: i64, pitch: T, velocity: T) -> usize {
+        let i = match self.voice.iter().position(|voice| voice.key == Some(key)) {
+            Some(i) => i,
+            None => self.allocate_voice(),
+        };
+        self.time += 1;
+        let voice = &mut self.voice[i];
+        voice.retrigger = voice.gate > T::zero();
+        voice.key = Some(key);
+        voice.pitch = pitch;
+        voice.gate = T::one();
+        voice.velocity = velocity;
+        voice.time = self.time;
+        i
+    }
+
+    /// Release the note with the given key. Returns the index of the voice playing it, if any.
+    pub fn note_off(&mut self, key: i64) -> Option<usize> {
+        let i = self.voice.iter().position(|voice| voice.key == Some(key))?;
+        self.time += 1;
+        let voice = &mut self.voice[i];
+        voice.key = None;
+        voice.gate = T::zero();
+        voice.retrigger = false;
+        voice.time = self.time;
+        Some(i)
+    }
+
+    /// Release all held notes.
+    pub fn all_notes_off(&mut self) {
+        for voice in self.voice.iter_mut() {
+            if voice.key.is_some() {
+                self.time += 1;
+                voice.key = None;
+                voice.gate = T::zero();
+                voice.retrigger = false;
+                voice.time = self.time;
+            }
+        }
+    }
+}
+
+impl<N, T, X> AudioNode for Poly<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = U3, Outputs = U1>,
+{
+    const ID: u64 = 107;
+    type Sample = T;
+    type Inputs = U0;
+    type Outputs = U1;
+    type Setting = PolyEvent;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            PolyEvent::AllOff => self.all_notes_off(),
+            PolyEvent::On {
+                key,
+                pitch,
+                velocity,
+            } => {
+                self.note_on(key, T::from_f64(pitch), T::from_f64(velocity));
+            }
+            PolyEvent::Off { key } => {
+                self.note_off(key);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x.iter_mut().for_each(|node| node.reset());
+        self.voice.iter_mut().for_each(|voice| *voice = Voice::default());
+        self.next = 0;
+        self.time = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        // Voice levels decay with a time constant of 50 ms.
+        self.decay = T::from_f64(exp(-1.0 / (0.05 * sample_rate)));
+        self.x
+            .iter_mut()
+            .for_each(|node| node.set_sample_rate(sample_rate));
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut output = T::zero();
+        for (x, voice) in self.x.iter_mut().zip(self.voice.iter_mut()) {
+            let gate = if voice.retrigger {
+                voice.retrigger = false;
+                T::zero()
+            } else {
+                voice.gate
+            };
+            let y = x.tick(&[voice.pitch, gate, voice.velocity].into())[0];
+            voice.level = max(y.abs(), voice.level * self.decay);
+            output += y;
+        }
+        [output].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        _input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        output[0][..size].fill(T::zero());
+        for (x, voice) in self.x.iter_mut().zip(self.voice.iter_mut()) {
+            let input = self.input.get_mut(3);
+            input[0][..size].fill(voice.pitch);
+            input[1][..size].fill(voice.gate);
+            input[2][..size].fill(voice.velocity);
+            if voice.retrigger && size > 0 {
+                voice.retrigger = false;
+                input[1][0] = T::zero();
+            }
+            x.process(size, self.input.self_ref(), self.output.get_mut(1));
+            let mut level = voice.level;
+            for (o, y) in output[0][..size]
+                .iter_mut()
+                .zip(self.output.at(0)[..size].iter())
+            {
+                level = max(y.abs(), level * self.decay);
+                *o += *y;
+            }
+            voice.level = level;
+        }
+    }
+
+    #[inline]
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        let mut hash = hash.hash(Self::ID);
+        for x in &mut self.x {
+            hash = x.ping(probe, hash);
+        }
+        hash
+    }
+
+    fn route(&mut self, _input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let input = new_signal_frame(3);
+        let mut output = self.x[0].route(&input, frequency);
+        for i in 1..N::USIZE {
+            let output_i = self.x[i].route(&input, frequency);
+            output[0] = output[0].combine_linear(output_i[0], 0.0, |x, y| x + y, |x, y| x + y);
+        }
+        output
+    }
+
+    fn allocate(&mut self) {
+        self.input.allocate(3);
+        self.output.allocate(1);
+        for x in &mut self.x {
+            x.allocate();
+        }
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        // All voices share the parameters of the first voice.
+        self.x[0].parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x[0].parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x[0].parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        for x in self.x.iter_mut() {
+            x.set_parameter(index, value);
+        }
+    }
+}
@@ -1,47 +1,107 @@
 //! Generic prelude.
 
+pub use super::additive::*;
+#[cfg(feature = "std")]
+pub use super::audit::*;
 pub use super::audionode::*;
 pub use super::audiounit::*;
 pub use super::buffer::*;
+pub use super::channels::*;
 pub use super::combinator::*;
+#[cfg(feature = "std")]
+pub use super::convolve::*;
+pub use super::denormal::*;
 pub use super::delay::*;
+#[cfg(feature = "device")]
+pub use super::device::*;
+#[cfg(feature = "dsl")]
+pub use super::dsl::*;
 pub use super::dynamics::*;
 pub use super::envelope::*;
 pub use super::feedback::*;
 pub use super::filter::*;
+pub use super::filter_design::*;
+pub use super::filterbank::*;
 pub use super::fir::*;
 pub use super::follow::*;
+pub use super::formant::*;
+#[cfg(feature = "std")]
 pub use super::gen::*;
+#[cfg(feature = "std")]
 pub use super::granular::*;
+pub use super::guard::*;
+#[cfg(feature = "std")]
+pub use super::layer::*;
+#[cfg(feature = "link")]
+pub use super::link::*;
+pub use super::looper::*;
+pub use super::loudness::*;
 pub use super::math::*;
+pub use super::mixer::*;
 pub use super::moog::*;
+#[cfg(feature = "std")]
+pub use super::music::*;
+#[cfg(feature = "std")]
 pub use super::net::*;
 pub use super::noise::*;
+#[cfg(feature = "osc")]
+pub use super::osc::*;
 pub use super::oscillator::*;
 pub use super::oversample::*;
 pub use super::pan::*;
+#[cfg(feature = "std")]
+pub use super::parallel::*;
+#[cfg(feature = "std")]
+pub use super::pattern::*;
+pub use super::pitch::*;
+#[cfg(feature = "std")]
+pub use super::playlist::*;
+#[cfg(feature = "plugin")]
+pub use super::plugin::*;
+pub use super::poly::*;
+pub use super::preset::*;
+#[cfg(feature = "std")]
 pub use super::realnet::*;
+#[cfg(feature = "std")]
 pub use super::realseq::*;
 pub use super::resample::*;
+#[cfg(feature = "std")]
 pub use super::resynth::*;
 pub use super::rez::*;
+#[cfg(feature = "std")]
 pub use super::sequencer::*;
+#[cfg(feature = "std")]
 pub use super::setting::*;
 pub use super::shape::*;
 pub use super::shared::*;
+pub use super::shift::*;
 pub use super::signal::*;
+#[cfg(feature = "std")]
 pub use super::slot::*;
+#[cfg(feature = "std")]
 pub use super::snoop::*;
+#[cfg(feature = "files")]
+pub use super::stream::*;
+pub use super::stretch::*;
 pub use super::svf::*;
 pub use super::system::*;
+#[cfg(feature = "std")]
+pub use super::testing::*;
+pub use super::transport::*;
+pub use super::unison::*;
+pub use super::vocoder::*;
 pub use super::wave::*;
+#[cfg(feature = "std")]
 pub use super::wavetable::*;
 pub use super::*;
 
 #[cfg(feature = "files")]
 pub use super::read::*;
 
-use std::sync::Arc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
 
 // Combinator environment.
 // We like to define all kinds of useful functions here.
@@ -271,6 +331,35 @@ pub fn multipass<N: Size<T>, T: Float>() -> An<MultiPass<N, T>> {
     An(MultiPass::new())
 }
 
+/// Mono pass-through that panics if the signal is NaN, infinite or larger in magnitude than `GUARD_LIMIT`.
+/// Insert guards into a graph to find where invalid values originate.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Check Filter Output
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = lowpass_hz::<f64, f64>(1000.0, 1.0) >> guard();
+/// node.filter_mono(1.0);
+/// ```
+pub fn guard<T: Float>() -> An<Guard<U1, T>> {
+    An(Guard::new())
+}
+
+/// Multichannel pass-through that panics if any signal is NaN, infinite or larger in magnitude than `GUARD_LIMIT`.
+/// - Input(s): signal
+/// - Output(s): signal
+///
+/// ### Example: Stereo Guard
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = multiguard::<U2, f64>();
+/// assert_eq!(node.filter_stereo(1.0, -1.0), (1.0, -1.0));
+/// ```
+pub fn multiguard<N: Size<T>, T: Float>() -> An<Guard<N, T>> {
+    An(Guard::new())
+}
+
 /// Monitor node. Passes through input. Communicates via the shared variable
 /// an aspect of the input signal according to the chosen metering mode.
 /// - Input 0: signal
@@ -286,6 +375,80 @@ pub fn monitor<T: Real + Atomic>(shared: &Shared<T>, meter: Meter) -> An<Monitor
     An(Monitor::new(shared, meter))
 }
 
+/// Level meter. Passes through input and publishes peak level, RMS level
+/// over a window of `window` seconds and ITU-R BS.1770 loudness to `levels`,
+/// which can be read lock-free from other threads.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Metering A Sine Wave
+/// ```
+/// use fundsp::prelude::*;
+/// let levels = std::sync::Arc::new(Levels::new());
+/// let mut node = sine_hz::<f64>(1000.0) >> level_meter::<f64>(&levels, 0.3);
+/// for _ in 0..44100 {
+///     node.get_mono();
+/// }
+/// assert!((levels.rms() - sqrt(0.5)).abs() < 0.01);
+/// assert!((levels.momentary() + 3.01).abs() < 0.1);
+/// ```
+pub fn level_meter<T: Float>(levels: &Arc<Levels>, window: f64) -> An<LevelMeter<T, U1>> {
+    An(LevelMeter::new(levels, window))
+}
+
+/// Stereo level meter. Passes through input and publishes peak level, RMS level
+/// over a window of `window` seconds and ITU-R BS.1770 loudness to `levels`,
+/// which can be read lock-free from other threads.
+/// Peak and RMS levels are taken over both channels.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: left signal
+/// - Output 1: right signal
+pub fn level_meter_stereo<T: Float>(levels: &Arc<Levels>, window: f64) -> An<LevelMeter<T, U2>> {
+    An(LevelMeter::new(levels, window))
+}
+
+/// Filterbank analyzer with `N` constant-Q bands centered at `centers` Hz
+/// (`centers` must contain `N` frequencies). Outputs the RMS level of each band,
+/// smoothed with a halfway response time of `window` seconds.
+/// The bandwidth of each band equals the spacing of the first two bands.
+/// Each band is a 4th order bandpass filter designed for the current sample rate.
+/// Band centers are available from `third_octave_bands` and `constant_q_bands`.
+/// - Input 0: signal
+/// - Output(s): RMS level of each band
+///
+/// ### Example: Third-Octave Band Levels
+/// ```
+/// use fundsp::prelude::*;
+/// let bands = filterbank_analyzer::<U31, f64>(&third_octave_bands(), 0.1);
+/// ```
+pub fn filterbank_analyzer<N: Size<T>, T: Float>(
+    centers: &[f64],
+    window: f64,
+) -> An<FilterbankAnalyzer<T, N>> {
+    An(FilterbankAnalyzer::new(centers, window))
+}
+
+/// Filterbank meter. Passes through input and publishes the RMS levels of the bands
+/// of `levels`, smoothed with a halfway response time of `window` seconds.
+/// Levels can be read lock-free from other threads, for example, by a spectrum display.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Octave Band Display
+/// ```
+/// use fundsp::prelude::*;
+/// let levels = std::sync::Arc::new(BandLevels::new(&constant_q_bands(31.0, 16000.0, 1.0)));
+/// let mut node = pink::<f64, f64>() >> filterbank_meter(&levels, 0.3);
+/// for _ in 0..4410 {
+///     node.get_mono();
+/// }
+/// assert_eq!(levels.bands(), 10);
+/// ```
+pub fn filterbank_meter<T: Float>(levels: &Arc<BandLevels>, window: f64) -> An<FilterbankMeter<T>> {
+    An(FilterbankMeter::new(levels, window))
+}
+
 /// Meter node.
 /// Outputs a summary of the input according to the chosen metering mode.
 /// - Input 0: signal
@@ -325,6 +488,71 @@ pub fn reverse<N: Size<T>, T: Float>() -> An<Reverse<N, T>> {
     An(Reverse::new())
 }
 
+/// Rearrange `N` channels. Output `i` copies input `mapping[i]`.
+/// The mapping must have `N` entries and can be changed at runtime.
+/// Setting: (output, source), where source `None` silences the output.
+/// - Input(s): signal
+/// - Output(s): rearranged signal
+///
+/// ### Example: Swap Stereo Pairs and Silence A Channel
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = route::<U4, f64>(&[1, 0, 3, 2]);
+/// node.set((3, None));
+/// ```
+pub fn route<N: Size<T>, T: Float>(mapping: &[usize]) -> An<Remap<N, N, T>> {
+    An(Remap::new(mapping))
+}
+
+/// Swap channels `a` and `b` of `N` channels. The rest pass through.
+/// Setting: (output, source), where source `None` silences the output.
+/// - Input(s): signal
+/// - Output(s): signal with channels `a` and `b` swapped
+///
+/// ### Example: Swap Left And Right
+/// ```
+/// use fundsp::prelude::*;
+/// reverb_stereo::<f32>(20.0, 2.0, 0.5) >> swap_channels(0, 1);
+/// ```
+pub fn swap_channels<N: Size<T>, T: Float>(a: usize, b: usize) -> An<Remap<N, N, T>> {
+    let mut mapping: Vec<usize> = (0..N::USIZE).collect();
+    mapping.swap(a, b);
+    An(Remap::new(&mapping))
+}
+
+/// Extract `M` channels from `N` channels. Output `i` copies input `indices[i]`.
+/// Setting: (output, source), where source `None` silences the output.
+/// - Input(s): signal
+/// - Output(s): extracted channels
+///
+/// ### Example: Front Pair Of A 5.1 Bus
+/// ```
+/// use fundsp::prelude::*;
+/// let node = extract_channels::<U6, U2, f64>(&[0, 1]);
+/// assert_eq!(node.outputs(), 2);
+/// ```
+pub fn extract_channels<N: Size<T>, M: Size<T>, T: Float>(indices: &[usize]) -> An<Remap<N, M, T>> {
+    An(Remap::new(indices))
+}
+
+/// Additive synthesizer with sine `partials`. Partials can be changed with settings
+/// or, at runtime, added and removed. Partials above the Nyquist frequency are silent.
+/// Setting: `AdditiveSetting` (partial, partial amplitude or inharmonicity).
+/// - Input 0: fundamental frequency in Hz
+/// - Output 0: sum of partials
+///
+/// ### Example: Additive Saw With Stretched Partials
+/// ```
+/// use fundsp::prelude::*;
+/// let partials: Vec<Partial<f64>> = (1..=32).map(|i| Partial::new(i as f64, 1.0 / i as f64)).collect();
+/// let mut synth = additive::<f64, f64>(&partials);
+/// synth.set(AdditiveSetting::Inharmonicity(0.0005));
+/// let synth = dc(110.0) >> synth;
+/// ```
+pub fn additive<T: Float, F: Real>(partials: &[Partial<F>]) -> An<Additive<T, F>> {
+    An(Additive::new(DEFAULT_SR, partials))
+}
+
 /// Sine oscillator.
 /// - Input 0: frequency (Hz)
 /// - Output 0: sine wave
@@ -350,6 +578,90 @@ pub fn sine_hz<T: Real>(f: T) -> An<Pipe<T, Constant<U1, T>, Sine<T>>> {
     constant(f) >> sine()
 }
 
+/// Bandlimited sine oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: sine wave
+pub fn sine_sync<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Sine, SyncInput::Sync))
+}
+
+/// Bandlimited saw oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: saw wave
+///
+/// ### Example: Hard Sync Lead
+/// ```
+/// use fundsp::prelude::*;
+/// (lfo(|t| 220.0 + 110.0 * sin_hz(0.5, t)) | saw_hz::<f64>(110.0)) >> saw_sync();
+/// ```
+pub fn saw_sync<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Saw, SyncInput::Sync))
+}
+
+/// Bandlimited square oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: square wave
+pub fn square_sync<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Square, SyncInput::Sync))
+}
+
+/// Bandlimited triangle oscillator with hard sync.
+/// A rising zero crossing in the sync signal resets the phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: sync signal
+/// - Output 0: triangle wave
+pub fn triangle_sync<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Triangle, SyncInput::Sync))
+}
+
+/// Bandlimited sine oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: sine wave
+///
+/// ### Example: Phase Modulation
+/// ```
+/// use fundsp::prelude::*;
+/// (dc(220.0) | sine_hz::<f64>(3.0) * 0.2) >> sine_phase();
+/// ```
+pub fn sine_phase<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Sine, SyncInput::Phase))
+}
+
+/// Bandlimited saw oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: saw wave
+pub fn saw_phase<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Saw, SyncInput::Phase))
+}
+
+/// Bandlimited square oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: square wave
+pub fn square_phase<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Square, SyncInput::Phase))
+}
+
+/// Bandlimited triangle oscillator with a phase input.
+/// The phase offset is added to the oscillator phase. Latency is one sample.
+/// - Input 0: frequency in Hz
+/// - Input 1: phase offset in 0...1
+/// - Output 0: triangle wave
+pub fn triangle_phase<T: Real>() -> An<SyncSynth<T>> {
+    An(SyncSynth::new(DEFAULT_SR, Waveform::Triangle, SyncInput::Phase))
+}
+
 /// Rossler dynamical system oscillator.
 /// - Input 0: frequency. The Rossler oscillator exhibits peaks at multiples of this frequency.
 /// - Output 0: system output
@@ -376,6 +688,32 @@ pub fn lorenz<T: Float>() -> An<Lorenz<T>> {
     An(Lorenz::new())
 }
 
+/// Rossler dynamical system oscillator at fixed frequency `f` Hz.
+/// Useful as a chaotic LFO at low frequencies.
+/// - Output 0: system output
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// rossler_hz::<f64>(0.5) * 200.0 + 400.0 >> sine::<f64>();
+/// ```
+pub fn rossler_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, Rossler<T>>> {
+    constant(f) >> rossler()
+}
+
+/// Lorenz dynamical system oscillator at fixed frequency `f` Hz.
+/// Useful as a chaotic LFO at low frequencies.
+/// - Output 0: system output
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// lorenz_hz::<f64>(1.0) * 200.0 + 400.0 >> sine::<f64>();
+/// ```
+pub fn lorenz_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, Lorenz<T>>> {
+    constant(f) >> lorenz()
+}
+
 /// Add constant to signal.
 /// - Input(s): signal
 /// - Output(s): signal plus constant
@@ -499,6 +837,22 @@ pub fn allpole_delay<T: Float, F: Float>(delay: F) -> An<Allpole<T, F, U1>> {
     An(Allpole::new(delay))
 }
 
+/// Phase rotator: chain of `stages` (`stages` > 0) 1st order allpass filters tuned to `frequency` Hz.
+/// Each stage rotates the phase by 90 degrees at the tuning frequency.
+/// Reduces asymmetric peaks, for example, in voice before limiting.
+/// Setting: frequency.
+/// - Input 0: audio
+/// - Output 0: phase rotated audio
+///
+/// ### Example: Broadcast Voice Chain
+/// ```
+/// use fundsp::prelude::*;
+/// phase_rotator::<f64, f64>(4, 200.0) >> limiter((0.001, 0.1));
+/// ```
+pub fn phase_rotator<T: Float, F: Real>(stages: usize, frequency: F) -> An<PhaseRotator<T, F>> {
+    An(PhaseRotator::new(stages, frequency))
+}
+
 /// One-pole, one-zero highpass filter (1st order).
 /// - Input 0: audio
 /// - Input 1: cutoff frequency (Hz)
@@ -529,6 +883,72 @@ pub fn resonator<T: Float, F: Real>() -> An<Resonator<T, F, U3>> {
     An(Resonator::new(F::new(440), F::new(110)))
 }
 
+/// Vowel formant filter with fixed vowel morph in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U).
+/// Setting: vowel morph.
+/// - Input 0: audio
+/// - Output 0: filtered audio
+///
+/// ### Example: Singing Saw
+/// ```
+/// use fundsp::prelude::*;
+/// saw_hz(110.0) >> formant::<f64, f64>(0.25);
+/// ```
+pub fn formant<T: Float, F: Real>(vowel_morph: T) -> An<Formant<T, F, U1>> {
+    An(Formant::new(vowel_morph))
+}
+
+/// Vowel formant filter with vowel morph input.
+/// - Input 0: audio
+/// - Input 1: vowel morph in 0...1 (0 = A, 0.25 = E, 0.5 = I, 0.75 = O, 1 = U)
+/// - Output 0: filtered audio
+pub fn formant_morph<T: Float, F: Real>() -> An<Formant<T, F, U2>> {
+    An(Formant::new(T::zero()))
+}
+
+/// Bank of parallel bandpass filters with center frequencies `frequency` (Hz), Q values `q`
+/// and amplitude gains `gain`. The slices must have the same length.
+/// - Input 0: audio
+/// - Output 0: sum of filtered audio
+pub fn formant_bank<T: Float, F: Real>(frequency: &[f64], q: &[f64], gain: &[f64]) -> An<FormantBank<T, F>> {
+    An(FormantBank::new(frequency, q, gain))
+}
+
+/// Parametric equalizer with `bands` (low shelf, bell and high shelf bands).
+/// Bands can be changed with settings or, at runtime, added and removed.
+/// Setting: (band index, band).
+/// - Input 0: audio
+/// - Output 0: equalized audio
+///
+/// ### Example: Channel Equalizer
+/// ```
+/// use fundsp::prelude::*;
+/// let mut eq = parametric_eq::<f64, f64>(&[
+///     EqBand::lowshelf(100.0, 0.7, 1.5),
+///     EqBand::bell(1000.0, 1.0, 0.5),
+///     EqBand::highshelf(8000.0, 0.7, 2.0),
+/// ]);
+/// eq.set((1, EqBand::bell(2000.0, 2.0, 0.5)));
+/// assert!(eq.response_db(0, 2000.0).unwrap() < -5.0);
+/// ```
+pub fn parametric_eq<T: Float, F: Real>(bands: &[EqBand<F>]) -> An<ParametricEq<T, F>> {
+    An(ParametricEq::new(DEFAULT_SR, bands))
+}
+
+/// Channel vocoder with `bands` bands (`bands` > 0), which are spaced logarithmically from 100 Hz to 8 kHz.
+/// The spectral envelope of the modulator is imposed on the carrier.
+/// - Input 0: carrier
+/// - Input 1: modulator
+/// - Output 0: vocoded signal
+///
+/// ### Example: Vocoded Saw
+/// ```
+/// use fundsp::prelude::*;
+/// (saw_hz::<f64>(110.0) | pink::<f64, f64>()) >> vocoder::<f64, f64>(16);
+/// ```
+pub fn vocoder<T: Float, F: Real>(bands: usize) -> An<Vocoder<T, F>> {
+    An(Vocoder::new(bands))
+}
+
 /// Constant-gain bandpass resonator with fixed `center` frequency (Hz) and `bandwidth` (Hz).
 /// - Input 0: audio
 /// - Output 0: filtered audio
@@ -581,6 +1001,53 @@ pub fn moog_hz<T: Float, F: Real>(frequency: F, q: F) -> An<Moog<T, F, U1>> {
     An(Moog::new(convert(DEFAULT_SR), frequency, q))
 }
 
+/// Zero-delay feedback Moog ladder filter. Stays stable under fast cutoff modulation.
+/// Drive defaults to 1 and can be changed with `MoogZdf::set_drive`.
+/// - Input 0: input signal
+/// - Input 1: cutoff frequency (Hz)
+/// - Input 2: Q in 0...1
+/// - Output 0: filtered signal
+///
+/// ### Example: Overdriven Ladder Sweep
+/// ```
+/// use fundsp::prelude::*;
+/// let mut filter = moog_zdf::<f64, f64>();
+/// filter.set_drive(4.0);
+/// let mut node = (saw_hz(110.0) | lfo(|t: f64| (xerp11(200.0, 5000.0, sin_hz(8.0, t)), 0.8))) >> filter;
+/// assert!(node.get_mono().abs() <= 1.0);
+/// ```
+pub fn moog_zdf<T: Float, F: Real>() -> An<MoogZdf<T, F, U3>> {
+    An(MoogZdf::new(
+        convert(DEFAULT_SR),
+        F::new(1000),
+        F::from_f64(0.1),
+        F::one(),
+    ))
+}
+
+/// Zero-delay feedback Moog ladder filter with fixed Q.
+/// - Input 0: input signal
+/// - Input 1: cutoff frequency (Hz)
+/// - Output 0: filtered signal
+pub fn moog_zdf_q<T: Float, F: Real>(
+    q: T,
+) -> An<Pipe<T, Stack<T, MultiPass<U2, T>, Constant<U1, T>>, MoogZdf<T, F, U3>>> {
+    (multipass::<U2, T>() | dc(q))
+        >> An(MoogZdf::new(
+            convert(DEFAULT_SR),
+            F::new(1000),
+            convert(q),
+            F::one(),
+        ))
+}
+
+/// Zero-delay feedback Moog ladder filter with fixed cutoff frequency and Q.
+/// - Input 0: input signal
+/// - Output 0: filtered signal
+pub fn moog_zdf_hz<T: Float, F: Real>(frequency: F, q: F) -> An<MoogZdf<T, F, U1>> {
+    An(MoogZdf::new(convert(DEFAULT_SR), frequency, q, F::one()))
+}
+
 /// Control envelope from time-varying function `f(t)` with `t` in seconds.
 /// Spaces samples using pseudorandom jittering.
 /// Synonymous with [`fn@lfo`].
@@ -784,6 +1251,50 @@ where
     An(EnvelopeIn::new(F::from_f64(0.002), DEFAULT_SR, f))
 }
 
+/// Multi-segment envelope with breakpoints (time, value, curve) in non-decreasing time order.
+/// Times are in seconds from the trigger. The curve of a breakpoint shapes the segment
+/// that ends at it: zero is linear, positive curves start slow and negative curves start fast.
+/// A rising gate retriggers the envelope, gliding from the current value.
+/// While the gate is open, the envelope loops between breakpoints set with `set_loop`,
+/// or holds at a breakpoint if the loop has zero length.
+/// Setting: `BreakpointSetting`.
+/// - Input 0: gate
+/// - Output 0: envelope
+///
+/// ### Example: DADSR Envelope With A Sustain Stage
+/// ```
+/// use fundsp::prelude::*;
+/// let mut env = envelope_bp::<f64>(&[
+///     (0.0, 0.0, 0.0),
+///     (0.1, 0.0, 0.0),
+///     (0.11, 1.0, -4.0),
+///     (0.3, 0.6, 4.0),
+///     (0.8, 0.0, 4.0),
+/// ]);
+/// env.set_loop(3, 3);
+/// ```
+pub fn envelope_bp<T: Float>(points: &[(f64, f64, f64)]) -> An<Breakpoints<T>> {
+    An(Breakpoints::new(points))
+}
+
+/// LFO with waveform `shape` at `rate` Hz. Random waveforms draw a new value for each cycle.
+/// The phase offset and polarity are set with `set_phase_offset` and `set_unipolar`.
+/// Setting: `LfoSetting`.
+/// - Input 0: retrigger. A rising edge restarts the cycle from the phase offset.
+/// - Output 0: LFO in -1...1, or 0...1 if unipolar.
+///
+/// ### Example: Sample And Hold Retriggered By A Gate
+/// ```
+/// use fundsp::prelude::*;
+/// let gate = shared(0.0);
+/// let mut lfo = lfo_wave::<f64>(LfoShape::SampleHold, 8.0);
+/// lfo.set_unipolar(true);
+/// let node = var(&gate) >> lfo;
+/// ```
+pub fn lfo_wave<T: Float>(shape: LfoShape, rate: f64) -> An<LfoWave<T>> {
+    An(LfoWave::new(shape, rate))
+}
+
 /// ADSR envelope.
 ///
 /// When a positive value is given by the input, its output increases from 0.0 to 1.0 in the time
@@ -859,6 +1370,73 @@ pub fn white<T: Float>() -> An<Noise<T>> {
     An(Noise::new())
 }
 
+/// White noise generator with an explicit `seed`.
+/// The seed overrides any seed derived from the graph structure, making renders reproducible.
+/// - Output 0: white noise.
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// let mut noise1 = white_seed::<f64>(1);
+/// let mut noise2 = white_seed::<f64>(1) >> pass();
+/// assert_eq!(noise1.get_mono(), noise2.get_mono());
+/// ```
+pub fn white_seed<T: Float>(seed: u64) -> An<Noise<T>> {
+    An(Noise::with_seed(seed))
+}
+
+/// Noise generator with an explicit `seed`. Synonym of `white_seed`.
+/// - Output 0: white noise.
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// let mut noise1 = noise_seed::<f64>(7);
+/// let mut noise2 = noise_seed::<f64>(7);
+/// assert_eq!(noise1.get_mono(), noise2.get_mono());
+/// ```
+pub fn noise_seed<T: Float>(seed: u64) -> An<Noise<T>> {
+    An(Noise::with_seed(seed))
+}
+
+/// Velvet noise generator with `density` impulses per second (`density` > 0).
+/// Velvet noise is a sparse sequence of -1 and 1 valued impulses, one at a random position
+/// in each grid period. It is useful for decorrelation and artificial reverberation.
+/// Setting: density.
+/// - Output 0: velvet noise.
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// velvet::<f32>(2000.0);
+/// ```
+pub fn velvet<T: Float>(density: T) -> An<Velvet<T>> {
+    An(Velvet::new(density, None))
+}
+
+/// Velvet noise generator with `density` impulses per second (`density` > 0)
+/// and an explicit `seed`.
+/// Setting: density.
+/// - Output 0: velvet noise.
+pub fn velvet_seed<T: Float>(density: T, seed: u64) -> An<Velvet<T>> {
+    An(Velvet::new(density, Some(seed)))
+}
+
+/// Bounded random walk with speed `rate` (`rate` >= 0) and range `range` (`range` > 0).
+/// The output wanders within -range...range, reflecting at the bounds.
+/// After `t` seconds, the standard deviation of the position is about `range * rate * sqrt(t)`.
+/// Setting: (rate, range).
+/// - Output 0: random walk.
+///
+/// ### Example: Wandering Pitch
+/// ```
+/// use fundsp::prelude::*;
+/// random_walk::<f64>(0.5, 20.0) + 440.0 >> sine::<f64>();
+/// ```
+pub fn random_walk<T: Float>(rate: T, range: T) -> An<RandomWalk<T>> {
+    An(RandomWalk::new(rate, range))
+}
+
 /// Sample-and-hold component. Sampling frequency `variability` is in 0...1.
 /// - Input 0: signal.
 /// - Input 1: sampling frequency (Hz).
@@ -883,62 +1461,257 @@ pub fn hold_hz<T: Float>(
     (pass() | dc(f)) >> hold(variability)
 }
 
-/// FIR filter.
+/// Sample-and-hold at fixed sampling frequency `f` Hz.
+/// Any input signal can be sampled, including control signals.
 /// - Input 0: signal.
-/// - Output 0: filtered signal.
+/// - Output 0: sampled signal.
 ///
-/// ### Example: 3-Point Lowpass Filter
+/// ### Example: Stepped Random Melody
 /// ```
 /// use fundsp::prelude::*;
-/// fir(Frame::<f64, _>::from([0.5, 1.0, 0.5]));
+/// lorenz_hz::<f64>(0.3) >> sample_hold::<f64>(4.0) * 200.0 + 400.0 >> sine::<f64>();
 /// ```
-pub fn fir<X: ConstantFrame>(weights: X) -> An<Fir<X::Sample, X::Size>> {
-    An(Fir::new(weights))
-}
-
-/// Create a 3-point symmetric FIR from desired `gain` (`gain` >= 0) at the Nyquist frequency.
-/// Results in a monotonic low-pass filter when `gain` < 1.
-/// - Input 0: signal.
-/// - Output 0: filtered signal.
-pub fn fir3<T: Float>(gain: T) -> Fir<T, U3> {
-    let alpha = (gain + T::new(1)) / T::new(2);
-    let beta = (T::new(1) - alpha) / T::new(2);
-    Fir::new((beta, alpha, beta))
+pub fn sample_hold<T: Float>(f: T) -> An<Pipe<T, Stack<T, Pass<T>, Constant<U1, T>>, Hold<T>>> {
+    (pass() | dc(f)) >> hold(T::zero())
 }
 
-/// Single sample delay.
+/// Bitcrusher. Quantizes the signal to `bits` bits (`bits` >= 1).
+/// The number of bits may be fractional.
 /// - Input 0: signal.
-/// - Output 0: delayed signal.
+/// - Output 0: quantized signal.
 ///
-/// ### Example: 2-Point Sum Filter
+/// ### Example: Crushed Noise
 /// ```
 /// use fundsp::prelude::*;
-/// tick::<f64>() & pass();
+/// noise() >> bitcrush::<f64>(4.0);
 /// ```
-pub fn tick<T: Float>() -> An<Tick<U1, T>> {
-    An(Tick::new())
+pub fn bitcrush<T: Real>(bits: T) -> An<Bitcrush<T, U1>> {
+    An(Bitcrush::new(bits))
 }
 
-/// Multichannel single sample delay.
-/// - Inputs: signal.
-/// - Outputs: delayed signal.
+/// Bitcrusher with the number of bits (`bits` >= 1) supplied as input.
+/// The number of bits may be fractional, which allows smooth modulation.
+/// - Input 0: signal.
+/// - Input 1: number of bits.
+/// - Output 0: quantized signal.
 ///
-/// ### Example
+/// ### Example: Sweeping Bit Depth
 /// ```
 /// use fundsp::prelude::*;
-/// multitick::<U2, f32>();
+/// (pink::<f64, f64>() | lfo(|t: f64| 12.0 - 2.0 * t)) >> bitcrusher();
 /// ```
-pub fn multitick<N: Size<T>, T: Float>() -> An<Tick<N, T>> {
-    An(Tick::new())
+pub fn bitcrusher<T: Real>() -> An<Bitcrush<T, U2>> {
+    An(Bitcrush::new(T::new(16)))
 }
 
-/// Fixed delay of `t` seconds.
-/// Delay time is rounded to the nearest sample. The minimum delay is one sample.
-/// - Allocates: the delay line.
+/// Sample rate reducer. Samples and holds the signal at `hold_rate` Hz.
 /// - Input 0: signal.
-/// - Output 0: delayed signal.
+/// - Output 0: decimated signal.
 ///
-/// ### Example
+/// ### Example: Lo-Fi Noise
+/// ```
+/// use fundsp::prelude::*;
+/// noise() >> decimate::<f64>(4000.0);
+/// ```
+pub fn decimate<T: Float>(
+    hold_rate: T,
+) -> An<Pipe<T, Stack<T, Pass<T>, Constant<U1, T>>, Hold<T>>> {
+    hold_hz(hold_rate, T::zero())
+}
+
+/// Sample rate reducer with the hold rate supplied as input.
+/// The hold rate can be modulated smoothly.
+/// - Input 0: signal.
+/// - Input 1: hold rate (Hz).
+/// - Output 0: decimated signal.
+///
+/// ### Example: Sweeping Hold Rate
+/// ```
+/// use fundsp::prelude::*;
+/// (noise::<f64>() | lfo(|t: f64| xerp(8000.0, 1000.0, t * 0.1))) >> decimator();
+/// ```
+pub fn decimator<T: Float>() -> An<Hold<T>> {
+    hold(T::zero())
+}
+
+/// FIR filter.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: 3-Point Lowpass Filter
+/// ```
+/// use fundsp::prelude::*;
+/// fir(Frame::<f64, _>::from([0.5, 1.0, 0.5]));
+/// ```
+pub fn fir<X: ConstantFrame>(weights: X) -> An<Fir<X::Sample, X::Size>> {
+    An(Fir::new(weights))
+}
+
+/// Create a 3-point symmetric FIR from desired `gain` (`gain` >= 0) at the Nyquist frequency.
+/// Results in a monotonic low-pass filter when `gain` < 1.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+pub fn fir3<T: Float>(gain: T) -> Fir<T, U3> {
+    let alpha = (gain + T::new(1)) / T::new(2);
+    let beta = (T::new(1) - alpha) / T::new(2);
+    Fir::new((beta, alpha, beta))
+}
+
+/// FIR filter with coefficients `b` supplied at runtime, for example, from a filter design tool.
+/// Coefficient `b[k]` is applied to the input delayed by `k` samples, as in MATLAB and SciPy.
+/// Coefficients can be designed with `sinc_lowpass`, `sinc_highpass`, `sinc_bandpass` and `sinc_bandstop`.
+/// Setting: coefficients.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Windowed Sinc Lowpass Filter
+/// ```
+/// use fundsp::prelude::*;
+/// fir_coefs::<f64>(&sinc_lowpass(63, 44100.0, 1000.0, FirWindow::Hamming));
+/// ```
+pub fn fir_coefs<T: Float>(b: &[T]) -> An<FirFilter<T>> {
+    An(FirFilter::new(b))
+}
+
+/// IIR filter with feedforward coefficients `b` and feedback coefficients `a` supplied at runtime,
+/// as in MATLAB and SciPy. Coefficients are normalized by `a[0]`.
+/// Setting: (`b`, `a`).
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: One-Pole Lowpass Filter
+/// ```
+/// use fundsp::prelude::*;
+/// iir::<f64>(&[0.1], &[1.0, -0.9]);
+/// ```
+pub fn iir<T: Float>(b: &[T], a: &[T]) -> An<IirFilter<T>> {
+    An(IirFilter::new(b, a))
+}
+
+/// Lowpass IIR filter of the given `order` (`order` > 0) designed from an analog `prototype`
+/// (Butterworth, Chebyshev I, Chebyshev II or elliptic) and realized as a cascade of biquad sections.
+/// The meaning of the `cutoff` frequency in Hz depends on the prototype; see `FilterPrototype`.
+/// The cutoff can be retuned at runtime without allocating.
+/// Setting: cutoff.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: 8th Order Elliptic Lowpass
+/// ```
+/// use fundsp::prelude::*;
+/// iir_lowpass::<f64>(FilterPrototype::Elliptic(0.5, 60.0), 8, 1000.0);
+/// ```
+pub fn iir_lowpass<T: Real>(
+    prototype: FilterPrototype,
+    order: usize,
+    cutoff: T,
+) -> An<IirCascade<T>> {
+    An(IirCascade::new(
+        FilterDesign::new(prototype, FilterPass::Lowpass, order),
+        cutoff,
+    ))
+}
+
+/// Highpass IIR filter of the given `order` (`order` > 0) designed from an analog `prototype`
+/// (Butterworth, Chebyshev I, Chebyshev II or elliptic) and realized as a cascade of biquad sections.
+/// The meaning of the `cutoff` frequency in Hz depends on the prototype; see `FilterPrototype`.
+/// The cutoff can be retuned at runtime without allocating.
+/// Setting: cutoff.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: 8th Order Elliptic Highpass
+/// ```
+/// use fundsp::prelude::*;
+/// iir_highpass::<f64>(FilterPrototype::Elliptic(0.5, 60.0), 8, 1000.0);
+/// ```
+pub fn iir_highpass<T: Real>(
+    prototype: FilterPrototype,
+    order: usize,
+    cutoff: T,
+) -> An<IirCascade<T>> {
+    An(IirCascade::new(
+        FilterDesign::new(prototype, FilterPass::Highpass, order),
+        cutoff,
+    ))
+}
+
+/// Linear phase FIR filter with coefficients `b` implemented with FFT convolution,
+/// for example, for mastering equalization. The coefficients should be symmetric,
+/// as from `fir_design` or `sinc_lowpass`. The latency is reported to signal flow analysis,
+/// so dry paths can be aligned with `compensate`. The latency is the number of taps rounded up
+/// to a power of two (between 16 and 512) plus `(taps - 1) / 2` samples.
+/// Setting: coefficients (the number of taps may not change).
+/// - Allocates: everything.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Linear Phase Treble Boost
+/// ```
+/// use fundsp::prelude::*;
+/// let b = fir_design(1023, 44100.0, |f| if f > 8000.0 { 2.0 } else { 1.0 }, FirWindow::Hann);
+/// linear_phase::<f64>(&b);
+/// ```
+#[cfg(feature = "std")]
+pub fn linear_phase<T: Float>(b: &[T]) -> An<LinearPhase<T>> {
+    An(LinearPhase::new(b))
+}
+
+/// Convolver that switches between impulse responses in `library`, for example, guitar cabinets,
+/// with a crossfade of `fade_time` seconds. Starts from the first impulse response.
+/// The latency is the block length of the library.
+/// Setting: impulse response index.
+/// - Allocates: everything.
+/// - Input 0: signal.
+/// - Output 0: convolved signal.
+///
+/// ### Example: Switch Between Two Cabinets
+/// ```
+/// use fundsp::prelude::*;
+/// let mut library = ImpulseLibrary::new(256);
+/// library.add(&[1.0, 0.5, 0.25]);
+/// library.add(&[0.5, -0.25]);
+/// let mut node = ir_switch::<f64>(&std::sync::Arc::new(library), 0.05);
+/// node.set(1);
+/// ```
+#[cfg(feature = "std")]
+pub fn ir_switch<T: Float>(library: &Arc<ImpulseLibrary>, fade_time: f64) -> An<IrSwitch<T>> {
+    An(IrSwitch::new(library, 0, fade_time))
+}
+
+/// Single sample delay.
+/// - Input 0: signal.
+/// - Output 0: delayed signal.
+///
+/// ### Example: 2-Point Sum Filter
+/// ```
+/// use fundsp::prelude::*;
+/// tick::<f64>() & pass();
+/// ```
+pub fn tick<T: Float>() -> An<Tick<U1, T>> {
+    An(Tick::new())
+}
+
+/// Multichannel single sample delay.
+/// - Inputs: signal.
+/// - Outputs: delayed signal.
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// multitick::<U2, f32>();
+/// ```
+pub fn multitick<N: Size<T>, T: Float>() -> An<Tick<N, T>> {
+    An(Tick::new())
+}
+
+/// Fixed delay of `t` seconds.
+/// Delay time is rounded to the nearest sample. The minimum delay is one sample.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: delayed signal.
+///
+/// ### Example
 /// ```
 /// use fundsp::prelude::*;
 /// delay::<f32>(1.0);
@@ -1023,6 +1796,209 @@ where
     An(TapLinear::new(min_delay, max_delay))
 }
 
+/// Variable delay line with selectable `interpolation` (`Interpolation`).
+/// Minimum and maximum delay times are in seconds.
+/// Setting: interpolation method.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: delay time in seconds.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Sinc Interpolated Chorus Voice
+/// ```
+/// use fundsp::prelude::*;
+/// pass::<f64>() & (pass() | lfo(|t| lerp11(0.01, 0.02, sin_hz(0.5, t)))) >> frac_delay(Interpolation::Sinc, 0.01, 0.02);
+/// ```
+pub fn frac_delay<T: Float>(interpolation: Interpolation, min_delay: f64, max_delay: f64) -> An<FracDelay<T>> {
+    An(FracDelay::new(interpolation, min_delay, max_delay, 0.0))
+}
+
+/// Modulation safe variable delay line with selectable `interpolation` (`Interpolation`).
+/// Abrupt changes in delay time crossfade over 10 ms instead of clicking.
+/// Minimum and maximum delay times are in seconds.
+/// Setting: interpolation method.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: delay time in seconds.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Stepped Delay Time
+/// ```
+/// use fundsp::prelude::*;
+/// pass::<f64>() & (pass() | lfo(|t| if t < 1.0 { 0.1 } else { 0.2 })) >> frac_delay_smooth(Interpolation::Cubic, 0.1, 0.2);
+/// ```
+pub fn frac_delay_smooth<T: Float>(
+    interpolation: Interpolation,
+    min_delay: f64,
+    max_delay: f64,
+) -> An<FracDelay<T>> {
+    An(FracDelay::new(interpolation, min_delay, max_delay, 0.01))
+}
+
+/// Feedforward comb filter `y = x + gain * x[n - d]` with delay `d` of `time` seconds.
+/// The delay is cubic interpolated.
+/// Setting: (delay in seconds, gain).
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Flanger Notches At 1 kHz Intervals
+/// ```
+/// use fundsp::prelude::*;
+/// let node = comb_ff::<f64>(0.001, -1.0);
+/// ```
+pub fn comb_ff<T: Float>(time: f64, gain: f64) -> An<Comb<T, U1>> {
+    An(Comb::new(false, time, time, gain))
+}
+
+/// Feedback comb filter `y = x + gain * y[n - d]` with delay `d` of `time` seconds.
+/// The delay is cubic interpolated. The filter is stable for `gain` within -1...1.
+/// Setting: (delay in seconds, gain).
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Metallic Resonance
+/// ```
+/// use fundsp::prelude::*;
+/// let node = comb_fb::<f64>(0.005, 0.9) * 0.1;
+/// ```
+pub fn comb_fb<T: Float>(time: f64, gain: f64) -> An<Comb<T, U1>> {
+    An(Comb::new(true, time, time, gain))
+}
+
+/// Feedback comb filter tuned to resonate at `frequency` Hz with feedback `gain` in -1...1.
+/// The delay is cubic interpolated.
+/// Setting: (delay in seconds, gain).
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Karplus-Strong Style Resonator
+/// ```
+/// use fundsp::prelude::*;
+/// let string = noise::<f64>() * 0.1 >> comb_hz::<f64>(220.0, 0.99);
+/// ```
+pub fn comb_hz<T: Float>(frequency: f64, gain: f64) -> An<Comb<T, U1>> {
+    An(Comb::new(true, 1.0 / frequency, 1.0 / frequency, gain))
+}
+
+/// Tunable feedback comb filter resonating at frequencies down to `min_frequency` Hz.
+/// The delay is cubic interpolated.
+/// - Allocates: the delay line.
+/// - Input 0: signal.
+/// - Input 1: frequency in Hz (the reciprocal of the delay).
+/// - Input 2: feedback gain in -1...1.
+/// - Output 0: filtered signal.
+///
+/// ### Example: Swept Resonance
+/// ```
+/// use fundsp::prelude::*;
+/// let node = (noise::<f64>() * 0.1 | lfo(|t: f64| 200.0 + 100.0 * t) | dc(0.95)) >> comb::<f64>(50.0);
+/// ```
+pub fn comb<T: Float>(min_frequency: f64) -> An<Comb<T, U3>> {
+    An(Comb::new(true, 1.0 / min_frequency, 0.0, 0.0))
+}
+
+/// Tempo synced delay of `beats` beats at the tempo of `transport`.
+/// When the tempo changes, delay time glides smoothly to the new value.
+/// Setting: delay in beats.
+/// - Allocates: the delay line, with room for `beats` beats at `MIN_TEMPO`.
+/// - Input 0: signal.
+/// - Output 0: delayed signal.
+///
+/// ### Example: Dotted Eighth Echo
+/// ```
+/// use fundsp::prelude::*;
+/// let transport = Transport::new(120.0);
+/// let echo = pass() & 0.5 * delay_sync::<f64>(&transport, 0.75);
+/// transport.set_tempo(128.0);
+/// ```
+pub fn delay_sync<T: Float>(transport: &Transport, beats: f64) -> An<DelaySync<T>> {
+    An(DelaySync::new(transport, beats))
+}
+
+/// Tempo synced LFO with a cycle of `beats` beats at the tempo of `transport`.
+/// Setting: waveform (`LfoShape`).
+/// - Output 0: LFO in -1...1.
+///
+/// ### Example: Four Beat Filter Sweep
+/// ```
+/// use fundsp::prelude::*;
+/// let transport = Transport::new(120.0);
+/// let sweep = lfo_sync::<f64>(&transport, 4.0, LfoShape::Triangle) * 500.0 + 1000.0;
+/// ```
+pub fn lfo_sync<T: Float>(transport: &Transport, beats: f64, shape: LfoShape) -> An<LfoSync<T>> {
+    An(LfoSync::new(transport, beats, shape))
+}
+
+/// Auto-panner. Pans a mono signal back and forth at `rate` Hz with LFO waveform `shape`.
+/// Pan `depth` is in 0...1, where 1 sweeps fully from left to right.
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Slow Pad Sweep
+/// ```
+/// use fundsp::prelude::*;
+/// saw_hz(110.0) >> autopan::<f64>(0.25, 0.8, LfoShape::Sine);
+/// ```
+pub fn autopan<T: Real>(rate: f64, depth: f64, shape: LfoShape) -> An<AutoPan<T>> {
+    An(AutoPan::new(rate, depth, shape))
+}
+
+/// Tempo synced auto-panner with a cycle of `beats` beats at the tempo of `transport`.
+/// Pan `depth` is in 0...1. Setting: (beats, depth).
+/// - Input 0: signal
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Pan Once Per Bar
+/// ```
+/// use fundsp::prelude::*;
+/// let transport = Transport::new(120.0);
+/// saw_hz(110.0) >> autopan_sync::<f64>(&transport, 4.0, 1.0, LfoShape::Triangle);
+/// ```
+pub fn autopan_sync<T: Real>(
+    transport: &Transport,
+    beats: f64,
+    depth: f64,
+    shape: LfoShape,
+) -> An<AutoPan<T>> {
+    An(AutoPan::new_sync(transport, beats, depth, shape))
+}
+
+/// Tremolo. Modulates amplitude with a sine LFO at `rate` Hz.
+/// The gain swings between 1 and 1 - `depth` (`depth` in 0...1).
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: modulated signal
+///
+/// ### Example: Electric Piano Tremolo
+/// ```
+/// use fundsp::prelude::*;
+/// sine_hz(220.0) >> tremolo::<f64>(5.0, 0.5);
+/// ```
+pub fn tremolo<T: Real>(rate: f64, depth: f64) -> An<Tremolo<T>> {
+    An(Tremolo::new(rate, depth))
+}
+
+/// Tempo synced tremolo with a cycle of `beats` beats at the tempo of `transport`.
+/// The gain swings between 1 and 1 - `depth` (`depth` in 0...1). Setting: (beats, depth).
+/// - Input 0: signal
+/// - Output 0: modulated signal
+///
+/// ### Example: Sixteenth Note Tremolo
+/// ```
+/// use fundsp::prelude::*;
+/// let transport = Transport::new(120.0);
+/// sine_hz(220.0) >> tremolo_sync::<f64>(&transport, 0.25, 1.0);
+/// ```
+pub fn tremolo_sync<T: Real>(transport: &Transport, beats: f64, depth: f64) -> An<Tremolo<T>> {
+    An(Tremolo::new_sync(transport, beats, depth))
+}
+
 /// 2x oversample enclosed `node`.
 /// - Inputs and outputs: from `node`.
 ///
@@ -1086,6 +2062,33 @@ where
     An(Feedback::new(node.0, FrameId::new()))
 }
 
+/// Mix output of enclosed circuit `node` back to its input through a loop delay
+/// of `delay` seconds, scaled by a feedback gain input and processed with stability `guard`.
+/// The latency of `node` counts toward the loop delay, and the loop always contains
+/// at least one sample of delay, so `node` may introduce latency.
+/// Setting: guard.
+/// - Input(s): signal, followed by feedback gain.
+/// - Output(s): signal with feedback.
+///
+/// ### Example: Guarded Feedback Delay
+/// ```
+/// use fundsp::prelude::*;
+/// (pass::<f64>() | dc(1.1)) >> feedback_loop(0.5, LoopGuard::Clip(1.0), lowpass_hz::<f64, f64>(2000.0, 1.0));
+/// ```
+pub fn feedback_loop<N, T, X>(
+    delay: f64,
+    guard: LoopGuard,
+    node: An<X>,
+) -> An<FeedbackLoop<N, T, X>>
+where
+    N: Size<T> + Add<U1>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    <N as Add<U1>>::Output: Size<T>,
+{
+    An(FeedbackLoop::new(delay, guard, node.0))
+}
+
 /// Mix output of enclosed circuit `node` back to its input
 /// with extra `loopback` feedback loop processing.
 /// Feedback circuits `node` and `loopback` must have an equal number of inputs and outputs.
@@ -1221,6 +2224,18 @@ pub fn declick_s<T: Float, F: Real>(t: F) -> An<Declick<T, F>> {
     An(Declick::new(DEFAULT_SR, t))
 }
 
+/// Click and crackle repairer with `sensitivity` in 0...1. Detects clicks as outliers
+/// in the prediction error of the signal and interpolates over them.
+/// Sensitivity 0 repairs only strong clicks, 1 repairs subtle ones.
+/// Disturbances longer than 32 samples are left alone. The latency is 64 samples.
+/// For offline repair of waves, see `Wave64::repair_clicks`.
+/// Setting: sensitivity.
+/// - Input 0: signal
+/// - Output 0: repaired signal
+pub fn click_repair<T: Real>(sensitivity: T) -> An<ClickRepair<T>> {
+    An(ClickRepair::new(DEFAULT_SR, sensitivity))
+}
+
 /// Shape signal with a waveshaper function.
 /// - Input 0: input signal
 /// - Output 0: shaped signal
@@ -1241,6 +2256,60 @@ pub fn shape<T: Real>(mode: Shape<T>) -> An<Shaper<T>> {
     An(Shaper::new(mode))
 }
 
+/// Shape signal with a lookup table.
+/// The table spans input values -1...1 and is linearly interpolated.
+/// Input values outside the range are clamped. The table must contain at least two values.
+/// - Input 0: input signal
+/// - Output 0: shaped signal
+///
+/// ### Example: Soft Knee
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = shape_table(&[-1.0, -0.9, 0.0, 0.9, 1.0f64]);
+/// assert_eq!(node.filter_mono(0.25), 0.45);
+/// ```
+pub fn shape_table<T: Float>(table: &[T]) -> An<ShaperTable<T>> {
+    An(ShaperTable::new(table))
+}
+
+/// Run waveshaper `node` at 4x oversampling to reduce aliasing
+/// and remove any DC offset introduced by an asymmetric curve.
+/// - Input 0: input signal
+/// - Output 0: shaped signal
+///
+/// ### Example: Clean Tube Saturation
+/// ```
+/// use fundsp::prelude::*;
+/// antialias::<f64, f64, _>(shape(Shape::Tube(4.0)));
+/// ```
+pub fn antialias<T, F, X>(node: An<X>) -> An<Pipe<T, Oversampler<T, Oversampler<T, X>>, DCBlock<T, F>>>
+where
+    T: Float,
+    F: Real,
+    X: AudioNode<Sample = T, Inputs = U1, Outputs = U1>,
+{
+    oversample(oversample(node)) >> dcblock()
+}
+
+/// Saturate signal with an analog-inspired `model` and input gain `drive` (`drive` > 0).
+/// The curve runs at 4x oversampling to reduce aliasing, followed by a DC blocker.
+/// Output gain is compensated automatically so that loudness does not jump with drive.
+/// Setting: `Side::Left(drive)`.
+/// - Input 0: input signal
+/// - Output 0: saturated signal
+///
+/// ### Example: Tape Saturation
+/// ```
+/// use fundsp::prelude::*;
+/// saturate::<f64>(SaturationModel::Tape, 4.0);
+/// ```
+pub fn saturate<T: Real>(
+    model: SaturationModel,
+    drive: T,
+) -> An<Pipe<T, Oversampler<T, Oversampler<T, Saturator<T>>>, DCBlock<T, T>>> {
+    antialias(An(Saturator::new(model, drive)))
+}
+
 /// Clip signal to -1...1.
 /// - Input 0: input signal
 /// - Output 0: clipped signal
@@ -1318,6 +2387,133 @@ pub fn limiter_stereo<T: Real, S: ScalarOrPair<Sample = T>>(time: S) -> An<Limit
     An(Limiter::new(DEFAULT_SR, time))
 }
 
+/// True peak look-ahead limiter. Limits true peaks, including peaks between samples,
+/// to `ceiling` amplitude. Look-ahead and release times are in seconds.
+/// Release is program-dependent: faster after brief peaks and slower under sustained limiting.
+/// Current gain reduction in dB is written to `reduction`.
+/// Setting: ceiling amplitude.
+/// - Allocates: look-ahead buffers.
+/// - Input 0: signal
+/// - Output 0: limited signal
+///
+/// ### Example: Limit to -1 dBTP
+/// ```
+/// use fundsp::prelude::*;
+/// let reduction = shared::<f32>(0.0);
+/// saw() >> limiter_true_peak(db_amp(-1.0), 0.005, 0.1, &reduction);
+/// ```
+pub fn limiter_true_peak<T: Real + Atomic>(
+    ceiling: T,
+    lookahead: f64,
+    release: f64,
+    reduction: &Shared<T>,
+) -> An<TruePeakLimiter<T, U1>> {
+    An(TruePeakLimiter::new(DEFAULT_SR, ceiling, lookahead, release, reduction))
+}
+
+/// Stereo true peak look-ahead limiter. Limits true peaks, including peaks between samples,
+/// to `ceiling` amplitude. Both channels receive the same gain reduction.
+/// Look-ahead and release times are in seconds.
+/// Release is program-dependent: faster after brief peaks and slower under sustained limiting.
+/// Current gain reduction in dB is written to `reduction`.
+/// Setting: ceiling amplitude.
+/// - Allocates: look-ahead buffers.
+/// - Input 0: left signal
+/// - Input 1: right signal
+/// - Output 0: limited left signal
+/// - Output 1: limited right signal
+pub fn limiter_true_peak_stereo<T: Real + Atomic>(
+    ceiling: T,
+    lookahead: f64,
+    release: f64,
+    reduction: &Shared<T>,
+) -> An<TruePeakLimiter<T, U2>> {
+    An(TruePeakLimiter::new(DEFAULT_SR, ceiling, lookahead, release, reduction))
+}
+
+/// Sidechain ducker. Attenuates the signal by `amount` (in 0...1) while the level of the key
+/// is above amplitude `threshold`. Ducking is gradual up to 6 dB above the threshold.
+/// The key level follows with halfway response times `attack` and `release` seconds.
+/// Setting: (threshold, amount).
+/// - Input 0: signal
+/// - Input 1: key
+/// - Output 0: ducked signal
+///
+/// ### Example: Duck Music Under Dialogue
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f32, f32>() | noise()) >> ducker(0.1, 0.75, 0.01, 0.3);
+/// ```
+pub fn ducker<T: Real>(threshold: T, amount: T, attack: T, release: T) -> An<Ducker<T>> {
+    An(Ducker::new(DEFAULT_SR, threshold, amount, attack, release))
+}
+
+/// Transient shaper for drum processing. Splits the signal into attack and sustain portions
+/// using a fast and a slow envelope follower and applies independent gain amplitudes to them.
+/// For example, `transient(2.0, 0.5)` boosts attacks by up to 6 dB and cuts sustain by up to 6 dB.
+/// Setting: (attack gain, sustain gain).
+/// - Input 0: signal
+/// - Output 0: shaped signal
+///
+/// ### Example: Punchier Drums
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f32, f32>() * 0.5) >> transient(2.0, 0.5);
+/// ```
+pub fn transient<T: Real>(attack_gain: T, sustain_gain: T) -> An<TransientShaper<T>> {
+    An(TransientShaper::new(DEFAULT_SR, attack_gain, sustain_gain))
+}
+
+/// De-esser. Compresses only the band above `frequency` Hz, where sibilance lives,
+/// when its level is above amplitude `threshold`. The compression ratio is `ratio` (`ratio` >= 1).
+/// The band is split off with a complementary crossover, so the signal passes unchanged
+/// while the band stays below the threshold.
+/// Setting: (frequency, threshold, ratio).
+/// - Input 0: signal
+/// - Output 0: de-essed signal
+///
+/// ### Example: Tame Sibilance
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f32, f32>() * 0.5) >> deesser(5000.0, 0.05, 4.0);
+/// ```
+pub fn deesser<T: Real>(frequency: T, threshold: T, ratio: T) -> An<DeEsser<T>> {
+    An(DeEsser::new(DEFAULT_SR, frequency, threshold, ratio))
+}
+
+/// Onset detector. Outputs an impulse (1.0) on the sample where an onset is detected
+/// and 0.0 otherwise. Onsets are detected from the rise of a fast envelope follower
+/// over a slow one. `sensitivity` is in 0...1: 0 detects only strong attacks,
+/// 1 detects subtle ones. The impulse output can drive `trigger` and `trigger_fn`.
+/// Setting: sensitivity.
+/// - Input 0: signal
+/// - Output 0: onset impulses
+///
+/// ### Example: Print Onset Times
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f32, f32>() * 0.5) >> onset(0.5) >> trigger_fn(0.5, |event| println!("onset at {} s", event.time));
+/// ```
+pub fn onset<T: Real>(sensitivity: T) -> An<Onset<T>> {
+    An(Onset::new(DEFAULT_SR, sensitivity))
+}
+
+/// Streaming tempo tracker for tempos between `min_bpm` and `max_bpm`.
+/// Estimates tempo from the autocorrelation of the onset strength curve
+/// of the latest 8 seconds of input. The estimate is updated twice per second.
+/// - Input 0: signal
+/// - Output 0: tempo estimate in BPM, or zero until an estimate is available
+///
+/// ### Example: Track Tempo Of A Pulse
+/// ```
+/// use fundsp::prelude::*;
+/// let pulse = lfo(|t: f64| if (t * 2.0) % 1.0 < 0.05 { 1.0 } else { 0.0 }) * sine_hz::<f64>(440.0);
+/// pulse >> tempo_tracker(60.0, 180.0);
+/// ```
+pub fn tempo_tracker<T: Real>(min_bpm: f64, max_bpm: f64) -> An<TempoTracker<T>> {
+    An(TempoTracker::new(DEFAULT_SR, min_bpm, max_bpm, 8.0))
+}
+
 /// Pinking filter.
 /// - Input 0: input signal
 /// - Output 0: filtered signal
@@ -1339,6 +2535,63 @@ pub fn brown<T: Float, F: Real>(
     white() >> lowpole_hz::<T, F>(T::from_f64(10.0)) * dc(T::from_f64(13.7))
 }
 
+/// Pink noise with an explicit `seed`.
+/// - Output 0: pink noise
+pub fn pink_seed<T: Float, F: Float>(seed: u64) -> An<Pipe<T, Noise<T>, Pinkpass<T, F>>> {
+    white_seed(seed) >> pinkpass::<T, F>()
+}
+
+/// Brown noise with an explicit `seed`.
+/// - Output 0: brown noise
+pub fn brown_seed<T: Float, F: Real>(
+    seed: u64,
+) -> An<Pipe<T, Noise<T>, Binop<T, FrameMul<U1, T>, Lowpole<T, F, U1>, Constant<U1, T>>>> {
+    // Empirical normalization factor.
+    white_seed(seed) >> lowpole_hz::<T, F>(T::from_f64(10.0)) * dc(T::from_f64(13.7))
+}
+
+/// Blue noise. Blue noise rises 3 dB per octave.
+/// - Output 0: blue noise
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// blue::<f64, f64>();
+/// ```
+pub fn blue<T: Float, F: Float>() -> An<Pipe<T, Pipe<T, Noise<T>, Pinkpass<T, F>>, Fir<T, U2>>> {
+    // Empirical normalization factor.
+    let gain = T::from_f64(1.7);
+    pink::<T, F>() >> An(Fir::new((gain, -gain)))
+}
+
+/// Blue noise with an explicit `seed`.
+/// - Output 0: blue noise
+pub fn blue_seed<T: Float, F: Float>(
+    seed: u64,
+) -> An<Pipe<T, Pipe<T, Noise<T>, Pinkpass<T, F>>, Fir<T, U2>>> {
+    // Empirical normalization factor.
+    let gain = T::from_f64(1.7);
+    pink_seed::<T, F>(seed) >> An(Fir::new((gain, -gain)))
+}
+
+/// Violet noise. Violet noise rises 6 dB per octave.
+/// - Output 0: violet noise in -1...1
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// violet::<f32>();
+/// ```
+pub fn violet<T: Float>() -> An<Pipe<T, Noise<T>, Fir<T, U2>>> {
+    white() >> An(Fir::new((T::from_f64(0.5), T::from_f64(-0.5))))
+}
+
+/// Violet noise with an explicit `seed`.
+/// - Output 0: violet noise in -1...1
+pub fn violet_seed<T: Float>(seed: u64) -> An<Pipe<T, Noise<T>, Fir<T, U2>>> {
+    white_seed(seed) >> An(Fir::new((T::from_f64(0.5), T::from_f64(-0.5))))
+}
+
 /// Feedback delay network.
 /// Mix output of enclosed circuit `x` back to its input.
 /// The output is diffused with a Hadamard matrix for feedback.
@@ -1384,6 +2637,199 @@ where
     An(Feedback2::new(x.0, y.0, FrameHadamard::new()))
 }
 
+/// Mix together `x` and `y` sourcing from the same inputs.
+/// The branch with less latency is delayed to match the other,
+/// which keeps, for example, a dry signal aligned with a look-ahead limiter or `resynth`.
+/// Latencies are measured from signal flow analysis.
+/// - Allocates: compensating delay lines.
+/// - Input(s): from `x` and `y`.
+/// - Output(s): sum of `x` and `y`.
+///
+/// ### Example: Parallel Limiting
+/// ```
+/// use fundsp::prelude::*;
+/// let node = compensate(pass::<f64>(), limiter((0.01, 0.1)));
+/// assert_eq!(node.compensation(), 441);
+/// ```
+pub fn compensate<T, X, Y>(x: An<X>, y: An<Y>) -> An<Compensate<T, X, Y>>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    Y: AudioNode<Sample = T, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+    Y::Inputs: Size<T>,
+    Y::Outputs: Size<T>,
+{
+    An(Compensate::new(x.0, y.0))
+}
+
+/// Mix the output of `node` with its dry input by wet `amount` in 0...1.
+/// The dry path is delayed by the latency of `node` to keep the paths aligned.
+/// Changes are crossfaded over 10 ms.
+/// Setting: `WetSetting` (wet amount or bypass).
+/// - Allocates: dry delay lines.
+/// - Inputs and outputs: from `node`.
+///
+/// ### Example: Parallel Limiting
+/// ```
+/// use fundsp::prelude::*;
+/// let node = wet(0.5, limiter::<f64, _>((0.01, 0.1)));
+/// assert_eq!(node.compensation(), 441);
+/// ```
+pub fn wet<N, T, X>(amount: f64, node: An<X>) -> An<Wet<N, T, X>>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    An(Wet::new(node.0, amount, false))
+}
+
+/// Fully wet `node` that can be bypassed with a click-free crossfade.
+/// The dry path is delayed by the latency of `node` to keep the paths aligned.
+/// Setting: `WetSetting` (bypass or wet amount).
+/// - Allocates: dry delay lines.
+/// - Inputs and outputs: from `node`.
+///
+/// ### Example: Bypassed Filter
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = bypass(lowpass_hz::<f32, f32>(1000.0, 1.0));
+/// node.set(WetSetting::Bypass(true));
+/// ```
+pub fn bypass<N, T, X>(node: An<X>) -> An<Wet<N, T, X>>
+where
+    N: Size<T>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = N, Outputs = N>,
+    X::Inputs: Size<T>,
+    X::Outputs: Size<T>,
+{
+    An(Wet::new(node.0, 1.0, false))
+}
+
+/// Process `x` with denormals flushed to zero.
+/// Recursive structures such as reverbs and filters produce denormals
+/// as their state decays, which can be very slow to process.
+/// Has no effect on platforms without flush-to-zero support.
+/// The mode is set once per block in `process` and once per sample in `tick`;
+/// holding a `DenormalGuard` around a block of ticks makes them cheaper.
+/// - Input(s): from `x`.
+/// - Output(s): from `x`.
+///
+/// ### Example: Reverb Tail
+/// ```
+/// use fundsp::prelude::*;
+/// let node = flush_denormals(reverb_stereo::<f64>(20.0, 3.0, 0.5));
+/// ```
+pub fn flush_denormals<X: AudioNode>(x: An<X>) -> An<FlushDenormals<X>> {
+    An(FlushDenormals::new(x.0))
+}
+/// Equal-power crossfade between nodes `x` and `y`, which receive the same input.
+/// The last input is the crossfade amount in 0...1: 0 selects `x` and 1 selects `y`.
+/// - Inputs: from `x` and `y`, followed by crossfade amount
+/// - Outputs: crossfaded outputs of `x` and `y`
+///
+/// ### Example: Morph Saw to Square
+/// ```
+/// use fundsp::prelude::*;
+/// (dc((110.0, 0.5)) >> xfade(saw::<f64>(), square())).get_mono();
+/// ```
+pub fn xfade<T, X, Y>(x: An<X>, y: An<Y>) -> An<XFade<T, X, Y>>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    Y: AudioNode<Sample = T, Inputs = X::Inputs, Outputs = X::Outputs>,
+    X::Inputs: Size<T> + Add<U1>,
+    X::Outputs: Size<T>,
+    <X::Inputs as Add<U1>>::Output: Size<T>,
+{
+    An(XFade::new(x.0, y.0))
+}
+
+/// Matrix mixer from `N` inputs to `M` outputs. Gain changes are smoothed
+/// with a time constant of `smoothing` seconds. Initially, input `i`
+/// is routed to output `i` with unity gain.
+/// Setting: (output, input, gain).
+/// - Inputs: `N` signals
+/// - Outputs: `M` mixes
+///
+/// ### Example: Route Three Sources to Stereo
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = matrix_mixer::<U3, U2, f64>(0.01);
+/// node.set_gain(0, 2, 0.5);
+/// node.set_gain(1, 2, 0.5);
+/// ```
+pub fn matrix_mixer<N: Size<T>, M: Size<T>, T: Float>(smoothing: f64) -> An<MatrixMixer<N, M, T>> {
+    An(MatrixMixer::new(smoothing))
+}
+
+/// Gain of `db` decibels. Gain changes are smoothed with a time constant of 5 ms.
+/// Gains at or below `MUTE_DB`, such as `f64::NEG_INFINITY`, mute the signal.
+/// Setting: gain in dB.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+///
+/// ### Example: Fade Out
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = gain_db::<f64>(-6.0);
+/// node.set(f64::NEG_INFINITY);
+/// ```
+pub fn gain_db<T: Atomic>(db: f64) -> An<Gain<T>> {
+    An(Gain::new(
+        db,
+        f64::NEG_INFINITY,
+        f64::INFINITY,
+        0.005,
+        "gain",
+    ))
+}
+
+/// Input trim of `db` decibels, limited to -24...24 dB.
+/// Trim changes are smoothed with a time constant of 5 ms.
+/// Setting: trim in dB.
+/// - Input 0: signal
+/// - Output 0: trimmed signal
+///
+/// ### Example: Trim Before Saturation
+/// ```
+/// use fundsp::prelude::*;
+/// trim_db::<f32>(6.0) >> shape(Shape::Tanh(1.0));
+/// ```
+pub fn trim_db<T: Atomic>(db: f64) -> An<Gain<T>> {
+    An(Gain::new(db, -24.0, 24.0, 0.005, "trim"))
+}
+
+/// Fader that follows `shared` gain in decibels, limited to 12 dB at most.
+/// Gain changes are smoothed with a time constant of `smoothing` seconds.
+/// Gains at or below `MUTE_DB`, such as negative infinity, mute the signal.
+/// Setting: gain in dB, which is also stored in `shared`.
+/// - Input 0: signal
+/// - Output 0: signal with gain applied
+///
+/// ### Example: Channel Fader
+/// ```
+/// use fundsp::prelude::*;
+/// let level = shared::<f32>(0.0);
+/// let channel = saw_hz(110.0) >> fader(&level, 0.01);
+/// level.set(-12.0);
+/// ```
+pub fn fader<T: Atomic>(shared: &Shared<T>, smoothing: f64) -> An<Gain<T>> {
+    An(Gain::new_shared(
+        shared,
+        f64::NEG_INFINITY,
+        12.0,
+        smoothing,
+        "fader",
+    ))
+}
+
+
 /// Bus `N` similar nodes from indexed generator `f`.
 /// - Input(s): from `f`.
 /// - Output(s): from `f`.
@@ -1817,6 +3263,7 @@ pub fn reverb2_stereo<T: Real>(
 /// use fundsp::prelude::*;
 /// multipass() & 0.25 * reverb3_stereo::<f32>(2.0, 0.5, lowpole_hz::<f32, f32>(8000.0));
 /// ```
+#[cfg(feature = "std")]
 pub fn reverb3_stereo<T: Real>(
     time: f64,
     diffusion: f64,
@@ -1827,6 +3274,24 @@ pub fn reverb3_stereo<T: Real>(
     ))
 }
 
+/// Diffuser: a cascade of `stages` (`stages` > 0) Schroeder allpasses with randomized,
+/// mutually prime delay lengths of at most `time_spread` seconds chosen from `seed`.
+/// Smears transients without coloring the spectrum. A building block for reverbs.
+/// Setting: feedforward coefficient of the allpasses (default 0.5).
+/// - Allocates: delay lines
+/// - Input 0: input signal
+/// - Output 0: diffused signal
+///
+/// ### Example: Diffused Echo
+/// ```
+/// use fundsp::prelude::*;
+/// pass() & feedback(delay(0.25) >> diffuser::<f32>(4, 0.02, 1) * db_amp(-6.0));
+/// ```
+#[cfg(feature = "std")]
+pub fn diffuser<T: Float>(stages: usize, time_spread: f64, seed: u64) -> An<super::reverb::Diffuser<T>> {
+    An(super::reverb::Diffuser::new(stages, time_spread, seed))
+}
+
 /// Stereo reverb with a slow fade-in envelope.
 /// `room_size` is in meters (at least 15 meters).
 /// `time` is approximate reverberation time to -60 dB in seconds.
@@ -1973,6 +3438,7 @@ pub fn pluck<T: Float>(
 /// - Allocates: global saw wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: saw wave
+#[cfg(feature = "std")]
 pub fn saw<T: Float>() -> An<WaveSynth<'static, T, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &SAW_TABLE))
 }
@@ -1981,6 +3447,7 @@ pub fn saw<T: Float>() -> An<WaveSynth<'static, T, U1>> {
 /// - Allocates: global square wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: square wave
+#[cfg(feature = "std")]
 pub fn square<T: Float>() -> An<WaveSynth<'static, T, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &SQUARE_TABLE))
 }
@@ -1989,6 +3456,7 @@ pub fn square<T: Float>() -> An<WaveSynth<'static, T, U1>> {
 /// - Allocates: global triangle wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: triangle wave
+#[cfg(feature = "std")]
 pub fn triangle<T: Float>() -> An<WaveSynth<'static, T, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &TRIANGLE_TABLE))
 }
@@ -1997,6 +3465,7 @@ pub fn triangle<T: Float>() -> An<WaveSynth<'static, T, U1>> {
 /// - Allocates: global organ wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: organ wave
+#[cfg(feature = "std")]
 pub fn organ<T: Float>() -> An<WaveSynth<'static, T, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &ORGAN_TABLE))
 }
@@ -2006,6 +3475,7 @@ pub fn organ<T: Float>() -> An<WaveSynth<'static, T, U1>> {
 /// - Allocates: global soft saw wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: soft saw wave
+#[cfg(feature = "std")]
 pub fn soft_saw<T: Float>() -> An<WaveSynth<'static, T, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &SOFT_SAW_TABLE))
 }
@@ -2014,6 +3484,7 @@ pub fn soft_saw<T: Float>() -> An<WaveSynth<'static, T, U1>> {
 /// - Allocates: global Hammond wavetable.
 /// - Input 0: frequency in Hz
 /// - Output 0: Hammond wave
+#[cfg(feature = "std")]
 pub fn hammond<T: Float>() -> An<WaveSynth<'static, T, U1>> {
     An(WaveSynth::new(DEFAULT_SR, &HAMMOND_TABLE))
 }
@@ -2021,6 +3492,7 @@ pub fn hammond<T: Float>() -> An<WaveSynth<'static, T, U1>> {
 /// Fixed saw wavetable oscillator at `f` Hz.
 /// - Allocates: global saw wavetable.
 /// - Output 0: saw wave
+#[cfg(feature = "std")]
 pub fn saw_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static, T, U1>>> {
     constant(f) >> saw()
 }
@@ -2028,6 +3500,7 @@ pub fn saw_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static,
 /// Fixed square wavetable oscillator at `f` Hz.
 /// - Allocates: global square wavetable.
 /// - Output 0: square wave
+#[cfg(feature = "std")]
 pub fn square_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static, T, U1>>> {
     constant(f) >> square()
 }
@@ -2035,6 +3508,7 @@ pub fn square_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'stati
 /// Fixed triangle wavetable oscillator at `f` Hz.
 /// - Allocates: global triangle wavetable.
 /// - Output 0: triangle wave
+#[cfg(feature = "std")]
 pub fn triangle_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static, T, U1>>> {
     constant(f) >> triangle()
 }
@@ -2042,6 +3516,7 @@ pub fn triangle_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'sta
 /// Fixed organ wavetable oscillator at `f` Hz. Emphasizes octave partials.
 /// - Allocates: global organ wavetable.
 /// - Output 0: organ wave
+#[cfg(feature = "std")]
 pub fn organ_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static, T, U1>>> {
     constant(f) >> organ()
 }
@@ -2050,6 +3525,7 @@ pub fn organ_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static
 /// Contains all partials, falls off like a triangle wave.
 /// - Allocates: global soft saw wavetable.
 /// - Output 0: soft saw wave
+#[cfg(feature = "std")]
 pub fn soft_saw_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static, T, U1>>> {
     constant(f) >> soft_saw()
 }
@@ -2057,6 +3533,7 @@ pub fn soft_saw_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'sta
 /// Fixed Hammond wavetable oscillator at `f` Hz. Emphasizes first three partials.
 /// - Allocates: global Hammond wavetable.
 /// - Output 0: Hammond wave
+#[cfg(feature = "std")]
 pub fn hammond_hz<T: Float>(f: T) -> An<Pipe<T, Constant<U1, T>, WaveSynth<'static, T, U1>>> {
     constant(f) >> hammond()
 }
@@ -2586,6 +4063,7 @@ pub fn bandrez_q<T: Float, F: Real>(
 /// - Input 0: frequency in Hz
 /// - Input 1: pulse duty cycle in 0...1
 /// - Output 0: pulse wave
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub struct PulseWave<T: Float> {
     pulse: An<
@@ -2606,6 +4084,7 @@ pub struct PulseWave<T: Float> {
 }
 
 #[allow(clippy::new_without_default)]
+#[cfg(feature = "std")]
 impl<T: Float> PulseWave<T> {
     pub fn new() -> Self {
         Self {
@@ -2618,6 +4097,7 @@ impl<T: Float> PulseWave<T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T: Float> AudioNode for PulseWave<T> {
     const ID: u64 = 44;
     type Sample = T;
@@ -2660,10 +4140,27 @@ impl<T: Float> AudioNode for PulseWave<T> {
 /// - Input 0: frequency in Hz
 /// - Input 1: pulse duty cycle in 0...1
 /// - Output 0: pulse wave
+#[cfg(feature = "std")]
 pub fn pulse<T: Float>() -> An<PulseWave<T>> {
     An(PulseWave::new())
 }
 
+/// Pulse wave oscillator with PolyBLEP antialiasing.
+/// Unlike `pulse`, it does not depend on wavetables.
+/// The pulse is 1 for the first part of the cycle (the pulse width) and -1 for the rest.
+/// - Input 0: frequency in Hz
+/// - Input 1: pulse width in 0...1
+/// - Output 0: pulse wave
+///
+/// ### Example: Pulse Width Modulation
+/// ```
+/// use fundsp::prelude::*;
+/// lfo(|t: f64| (110.0, lerp11(0.1, 0.9, sin_hz(0.2, t)))) >> pulse_blep::<f64>();
+/// ```
+pub fn pulse_blep<T: Real>() -> An<BlepPulse<T>> {
+    An(BlepPulse::new(DEFAULT_SR))
+}
+
 /// Morphing filter that morphs between lowpass, peak and highpass modes.
 /// - Input 0: input signal
 /// - Input 1: center frequency (Hz)
@@ -2692,6 +4189,40 @@ pub fn morph_hz<T: Real, F: Real>(
         ))
 }
 
+/// State variable filter with simultaneous lowpass, highpass, bandpass and notch outputs.
+/// All four outputs are computed from a single filter core.
+/// - Input 0: audio
+/// - Input 1: cutoff frequency (Hz)
+/// - Input 2: Q
+/// - Output 0: lowpass filtered audio
+/// - Output 1: highpass filtered audio
+/// - Output 2: bandpass filtered audio
+/// - Output 3: notch filtered audio
+///
+/// ### Example: Lowpass To Highpass Crossfade
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = (pass() | dc((1000.0, 1.0))) >> svf_multi::<f64, f64>() >> (mul(0.75) | mul(0.25) | sink() | sink()) >> join::<U2, f64>();
+/// assert_eq!(node.outputs(), 1);
+/// ```
+pub fn svf_multi<T: Float, F: Real>() -> An<SvfMulti<T, F>> {
+    An(SvfMulti::new(DEFAULT_SR, F::new(440), F::one()))
+}
+
+/// State variable filter with cutoff frequency `f` Hz and Q value `q`
+/// and simultaneous lowpass, highpass, bandpass and notch outputs.
+/// - Input 0: audio
+/// - Output 0: lowpass filtered audio
+/// - Output 1: highpass filtered audio
+/// - Output 2: bandpass filtered audio
+/// - Output 3: notch filtered audio
+pub fn svf_multi_hz<T: Float, F: Real>(
+    f: T,
+    q: T,
+) -> An<Pipe<T, Stack<T, Pass<T>, Constant<U2, T>>, SvfMulti<T, F>>> {
+    (pass() | dc((f, q))) >> An(SvfMulti::new(DEFAULT_SR, convert(f), convert(q)))
+}
+
 /// Play back a channel of a Wave64.
 /// Optional loop point is the index to jump to at the end of the wave.
 /// - Output 0: wave
@@ -2780,6 +4311,194 @@ pub fn wave32_at<T: Float>(
     ))
 }
 
+/// Play back a channel of a Wave64 at time rate `rate` with pitch preserved,
+/// using WSOLA time stretching. Rates below 1 slow playback down.
+/// Setting: (time rate, pitch ratio).
+/// - Output 0: wave
+///
+/// ### Example: Half Speed
+/// ```
+/// use fundsp::prelude::*;
+/// let wave = std::sync::Arc::new(Wave64::new(1, 44100.0));
+/// let mut player = stretch64::<f64>(&wave, 0, 0.5);
+/// player.set_pitch(2.0);
+/// ```
+pub fn stretch64<T: Float>(wave: &Arc<Wave64>, channel: usize, rate: f64) -> An<Wave64Stretcher<T>> {
+    An(Wave64Stretcher::new(DEFAULT_SR, wave, channel, 0.0, rate, 1.0))
+}
+
+/// Play back a channel of a Wave32 at time rate `rate` with pitch preserved,
+/// using WSOLA time stretching. Rates below 1 slow playback down.
+/// Setting: (time rate, pitch ratio).
+/// - Output 0: wave
+///
+/// ### Example: Half Speed
+/// ```
+/// use fundsp::prelude::*;
+/// let wave = std::sync::Arc::new(Wave32::new(1, 44100.0));
+/// let mut player = stretch32::<f64>(&wave, 0, 0.5);
+/// player.set_pitch(2.0);
+/// ```
+pub fn stretch32<T: Float>(wave: &Arc<Wave32>, channel: usize, rate: f64) -> An<Wave32Stretcher<T>> {
+    An(Wave32Stretcher::new(DEFAULT_SR, wave, channel, 0.0, rate, 1.0))
+}
+
+#[cfg(feature = "std")]
+/// Record input into a growing Wave64. The input is passed through.
+/// The sample is appended to every channel of the wave.
+/// Recording allocates memory and locks the wave, so it is not real-time safe:
+/// use `capture` in real-time contexts.
+/// - Input 0: signal to record
+/// - Output 0: signal
+///
+/// ### Example: Record Noise
+/// ```
+/// use fundsp::prelude::*;
+/// use std::sync::{Arc, Mutex};
+/// let wave = Arc::new(Mutex::new(Wave64::new(1, 44100.0)));
+/// let mut node = white::<f64>() >> record64::<f64>(&wave);
+/// for _ in 0..100 {
+///     node.get_mono();
+/// }
+/// assert_eq!(wave.lock().unwrap().length(), 100);
+/// ```
+pub fn record64<T: Float>(wave: &Arc<Mutex<Wave64>>) -> An<Wave64Recorder<T>> {
+    An(Wave64Recorder::new(wave))
+}
+
+#[cfg(feature = "std")]
+/// Record input into a growing Wave32. The input is passed through.
+/// The sample is appended to every channel of the wave.
+/// Recording allocates memory and locks the wave, so it is not real-time safe:
+/// use `capture` in real-time contexts.
+/// - Input 0: signal to record
+/// - Output 0: signal
+pub fn record32<T: Float>(wave: &Arc<Mutex<Wave32>>) -> An<Wave32Recorder<T>> {
+    An(Wave32Recorder::new(wave))
+}
+
+/// Capture input into a preallocated buffer. The input is passed through.
+/// Capture is real-time safe. Once the buffer is full, further samples are dropped
+/// and the overflow flag of the buffer is set.
+/// - Input 0: signal to capture
+/// - Output 0: signal
+///
+/// ### Example: Capture Into Buffer
+/// ```
+/// use fundsp::prelude::*;
+/// let buffer = std::sync::Arc::new(CaptureBuffer::new(10));
+/// let mut node = dc(0.5) >> capture::<f64>(&buffer);
+/// for _ in 0..11 {
+///     node.get_mono();
+/// }
+/// assert!(buffer.len() == 10 && buffer.overflow());
+/// ```
+pub fn capture<T: Float>(buffer: &Arc<CaptureBuffer>) -> An<Capture<T>> {
+    An(Capture::new(buffer))
+}
+
+/// Looper with room for a loop of `max_duration` seconds, controlled with settings.
+/// Loop boundaries are crossfaded. The output contains only the loop.
+/// Setting: loop state (`LoopState`).
+/// - Input 0: audio
+/// - Output 0: loop playback
+///
+/// ### Example: Record And Play Back
+/// ```
+/// use fundsp::prelude::*;
+/// let mut node = looper::<f64>(10.0);
+/// node.set(LoopState::Record);
+/// for _ in 0..1000 {
+///     node.filter_mono(1.0);
+/// }
+/// node.set(LoopState::Play);
+/// assert_eq!(node.filter_mono(1.0), 1.0);
+/// ```
+pub fn looper<T: Float>(max_duration: f64) -> An<Looper<T, U1>> {
+    An(Looper::new(max_duration))
+}
+
+/// Looper with room for a loop of `max_duration` seconds, controlled with a gate.
+/// Each rising edge of the gate steps through the states
+/// record, play, overdub, play, overdub, and so on.
+/// Loop boundaries are crossfaded. The output contains only the loop.
+/// Setting: loop state (`LoopState`).
+/// - Input 0: audio
+/// - Input 1: gate
+/// - Input 2: playback speed (1 = normal speed, negative = reverse)
+/// - Output 0: loop playback
+pub fn looper_gate<T: Float>(max_duration: f64) -> An<Looper<T, U3>> {
+    An(Looper::new(max_duration))
+}
+
+/// Polyphonic synthesizer with `N` copies of `voice`. Voices are allocated to notes
+/// with a stealing strategy (`VoiceSteal`, default round robin) and summed.
+/// Setting: note event (`PolyEvent`).
+/// - Output 0: sum of voice outputs
+///
+/// The voice has three inputs:
+/// - Input 0: pitch in Hz
+/// - Input 1: gate (1 = note held, 0 = note released)
+/// - Input 2: velocity in 0...1
+///
+/// Voices are clones of `voice`, so they should not share state
+/// through shared variables, as `adsr_live` does.
+///
+/// ### Example: Polyphonic Saw Synth
+/// ```
+/// use fundsp::prelude::*;
+/// // ADSR envelope with a sustain stage at breakpoint 2.
+/// let mut env = envelope_bp::<f64>(&[(0.0, 0.0, 0.0), (0.01, 1.0, 0.0), (0.21, 0.5, 0.0), (0.51, 0.0, 0.0)]);
+/// env.set_loop(2, 2);
+/// let voice = (saw::<f64>() | env | pass()) >> map(|x: &Frame<f64, U3>| x[0] * x[1] * x[2]);
+/// let mut synth = poly::<U8, f64, _>(voice);
+/// synth.set_steal(VoiceSteal::Oldest);
+/// synth.note_on(60, midi_hz(60.0), 1.0);
+/// synth.note_on(64, midi_hz(64.0), 0.8);
+/// assert_eq!(synth.held(), 2);
+/// synth.note_off(60);
+/// assert_eq!(synth.held(), 1);
+/// ```
+pub fn poly<N, T, X>(voice: An<X>) -> An<Poly<N, T, X>>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Float,
+    X: AudioNode<Sample = T, Inputs = U3, Outputs = U1>,
+{
+    assert!(N::USIZE > 0);
+    An(Poly::new(Frame::generate(|_| voice.0.clone())))
+}
+
+/// Unison stack of `N` copies of oscillator `voice`, detuned over a total of `detune` cents
+/// and spread in the stereo field by `spread` in 0...1. Each copy starts at a different pseudorandom phase.
+/// The output is scaled by `1 / sqrt(N)`.
+/// Setting: (detune in cents, stereo spread in 0...1).
+/// - Input 0: frequency in Hz
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// The oscillator has one input (frequency in Hz) and one output.
+///
+/// ### Example: Supersaw
+/// ```
+/// use fundsp::prelude::*;
+/// let mut supersaw = unison::<U7, f64, _>(25.0, 0.8, saw::<f64>());
+/// supersaw.set_detune(40.0);
+/// let synth = dc(110.0) >> supersaw;
+/// assert_eq!(synth.outputs(), 2);
+/// ```
+pub fn unison<N, T, X>(detune: T, spread: T, voice: An<X>) -> An<Unison<N, T, X>>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Real,
+    X: AudioNode<Sample = T, Inputs = U1, Outputs = U1>,
+{
+    assert!(N::USIZE > 0);
+    An(Unison::new(detune, spread, Frame::generate(|_| voice.0.clone())))
+}
+
 /// Mono chorus, 5 voices. For stereo, stack two of these using different seed values.
 /// `seed`: LFO seed.
 /// `separation`: base voice separation in seconds (for example, 0.015).
@@ -2933,6 +4652,78 @@ where
     An(VarFn::new(shared, f))
 }
 
+/// Outputs the value of the shared variable with block-rate smoothing.
+/// The variable is read every `CONTROL_BLOCK` samples and the output ramps
+/// linearly to the new value over the block.
+///
+/// - Output 0: smoothed value
+///
+/// ### Example: Smoothed Volume Control
+/// ```
+/// use fundsp::prelude::*;
+/// let volume = shared::<f32>(0.5);
+/// pink::<f32, f32>() * from_shared(&volume);
+/// ```
+pub fn from_shared<T: Atomic>(shared: &Shared<T>) -> An<FromShared<T>> {
+    An(FromShared::new(shared))
+}
+
+/// Pass through input and store its average over each block
+/// of `CONTROL_BLOCK` samples into the shared variable.
+///
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Publish Envelope Level
+/// ```
+/// use fundsp::prelude::*;
+/// let level = shared::<f32>(0.0);
+/// pink::<f32, f32>() >> (pass() ^ follow(0.1) >> to_shared(&level) >> sink());
+/// ```
+pub fn to_shared<T: Atomic>(shared: &Shared<T>) -> An<ToShared<T>> {
+    An(ToShared::new(shared))
+}
+
+/// Control-rate probe. Pass through input and publish its latest value
+/// into the shared variable once per block of `CONTROL_BLOCK` samples,
+/// or once per call when processed in blocks. Cheaper than `monitor` for UI display.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example: Display Filter Cutoff
+/// ```
+/// use fundsp::prelude::*;
+/// let cutoff = shared::<f32>(0.0);
+/// let sweep = lfo(|t: f32| xerp(500.0, 5000.0, t)) >> probe(&cutoff);
+/// ```
+pub fn probe<T: Atomic>(shared: &Shared<T>) -> An<Probe<T>> {
+    An(Probe::new(shared))
+}
+
+/// Convert the last input, an audio-rate control, to settings of `node`.
+/// The control is averaged over blocks of `CONTROL_BLOCK` samples
+/// and mapped to a setting with `f`. The setting is applied at the start of the next block.
+/// Setting: from `node`.
+///
+/// - Inputs: from `node`, followed by control
+/// - Outputs: from `node`
+///
+/// ### Example: Sweep Lowpass Cutoff With Settings
+/// ```
+/// use fundsp::prelude::*;
+/// (noise() | lfo(|t: f32| xerp(500.0, 5000.0, t / 10.0))) >> to_setting(lowpole_hz::<f32, f32>(1000.0), |f| f);
+/// ```
+pub fn to_setting<T, X, F>(node: An<X>, f: F) -> An<ToSetting<T, X, F>>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Inputs: Add<U1>,
+    <X::Inputs as Add<U1>>::Output: Size<T>,
+    F: Fn(T) -> X::Setting + Clone + Send + Sync,
+{
+    An(ToSetting::new(node.0, f))
+}
+
 /// Timer node. A node with no inputs or outputs that maintains
 /// current stream time in a shared variable.
 /// It can be added to any node by stacking.
@@ -2947,16 +4738,107 @@ pub fn timer<T: Float + Atomic>(shared: &Shared<T>) -> An<Timer<T>> {
     An(Timer::new(DEFAULT_SR, shared))
 }
 
+/// Oscilloscope node for sharing triggered snapshots of `length` samples
+/// with a frontend thread. Snapshots begin at a rising edge through the trigger level (0 by default).
+/// Returns (frontend, backend).
+/// Setting: trigger level.
+/// - Input 0: signal to capture.
+/// - Output 0: signal passed through.
+///
+/// ### Example: Stable Sine Wave Display
+/// ```
+/// use fundsp::prelude::*;
+/// let (mut scope, backend) = scope::<f64>(100);
+/// let mut node = sine_hz::<f64>(441.0) >> backend;
+/// for _ in 0..1000 {
+///     node.get_mono();
+/// }
+/// assert!(scope.update());
+/// assert!(scope.at(0) > 0.0 && scope.at(0) < 0.1);
+/// ```
+#[cfg(feature = "std")]
+pub fn scope<T: Float>(length: usize) -> (Scope, An<ScopeBackend<T>>) {
+    let (scope, backend) = Scope::new(length);
+    (scope, An(backend))
+}
+
+/// Sample accurate trigger. Detects rising edges of the input through `threshold`
+/// and outputs an impulse at each edge, which can retrigger envelopes.
+/// Each edge is also sent as a `TriggerEvent` with its sample index and interpolated time
+/// to the frontend, which can be polled from a control thread, for example,
+/// to push sequencer events. Up to 1024 events can be pending.
+/// Returns (frontend, backend).
+/// Setting: threshold.
+/// - Input 0: signal
+/// - Output 0: impulse at rising edges
+///
+/// ### Example: Count Rising Edges
+/// ```
+/// use fundsp::prelude::*;
+/// let (mut triggers, backend) = trigger(0.5);
+/// let mut node = sine_hz::<f64>(10.0) >> backend;
+/// for _ in 0..44100 {
+///     node.get_mono();
+/// }
+/// assert_eq!(triggers.events().len(), 10);
+/// ```
+#[cfg(feature = "std")]
+pub fn trigger<T: Float>(threshold: T) -> (Triggers, An<TriggerBackend<T>>) {
+    let (triggers, backend) = Triggers::new(threshold, 1024);
+    (triggers, An(backend))
+}
+
+/// Sample accurate trigger that calls `f` with a `TriggerEvent` at each rising edge
+/// of the input through `threshold`. The function is called on the audio thread.
+/// Setting: threshold.
+/// - Input 0: signal
+/// - Output 0: impulse at rising edges
+#[cfg(feature = "std")]
+pub fn trigger_fn<T: Float, F: FnMut(TriggerEvent) + Clone + Send + Sync>(threshold: T, f: F) -> An<TriggerFn<T, F>> {
+    An(TriggerFn::new(threshold, f))
+}
+
 /// Snoop node for sharing audio data with a frontend thread.
 /// The latest samples buffer has room for at least `capacity` samples.
 /// Returns (frontend, backend).
 /// - Input 0: signal to snoop.
 /// - Output 0: signal passed through.
+#[cfg(feature = "std")]
 pub fn snoop<T: Float>(capacity: usize) -> (Snoop<T>, An<SnoopBackend<T>>) {
     let (snoop, backend) = Snoop::new(capacity);
     (snoop, An(backend))
 }
 
+/// Snapshot meter. Passes through input and writes a summary of it
+/// according to the chosen metering mode to `slot` of a `Snapshot`.
+/// The values are published together by `snapshot_publish`.
+/// - Input 0: signal
+/// - Output 0: signal
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// let (_snapshot, writer) = Snapshot::new(&["level"]);
+/// snapshot_meter::<f32>(&writer, 0, Meter::Rms(0.1)) >> snapshot_publish(&writer);
+/// ```
+#[cfg(feature = "std")]
+pub fn snapshot_meter<T: Real>(
+    writer: &SnapshotWriter,
+    slot: usize,
+    meter: Meter,
+) -> An<SnapshotMeter<T>> {
+    An(SnapshotMeter::new(writer, slot, meter))
+}
+
+/// Snapshot publisher. Passes through input and publishes the values written
+/// to a `Snapshot` once per block. Place it after the nodes that write to the snapshot.
+/// - Input 0: signal
+/// - Output 0: signal
+#[cfg(feature = "std")]
+pub fn snapshot_publish<T: Float>(writer: &SnapshotWriter) -> An<SnapshotPublisher<T>> {
+    An(SnapshotPublisher::new(writer))
+}
+
 /// Frequency domain resynthesizer.
 /// The number of inputs is `I` and the number of outputs is `O`.
 /// The window length (in samples) must be a power of two and at least four.
@@ -2981,6 +4863,7 @@ pub fn snoop<T: Float>(capacity: usize) -> (Snoop<T>, An<SnoopBackend<T>>) {
 ///         }
 ///     });
 /// ```
+#[cfg(feature = "std")]
 pub fn resynth<I, O, T, F>(window_length: usize, processing: F) -> An<Resynth<I, O, T, F>>
 where
     I: Size<T>,
@@ -2991,6 +4874,175 @@ where
     An(Resynth::new(window_length, processing))
 }
 
+/// Hilbert transformer. Outputs the analytic signal of the input: the real part (I)
+/// and the imaginary part (Q), which lags the real part by 90 degrees.
+/// Only the phase difference of the outputs is accurate, not their phase relative to the input.
+/// - Input 0: signal
+/// - Output 0: real part (I)
+/// - Output 1: imaginary part (Q)
+///
+/// ### Example: Envelope Detector
+/// ```
+/// use fundsp::prelude::*;
+/// let envelope = hilbert::<f64>() >> map(|x: &Frame<f64, U2>| (x[0] * x[0] + x[1] * x[1]).sqrt());
+/// ```
+pub fn hilbert<T: Float>() -> An<Hilbert<T>> {
+    An(Hilbert::new())
+}
+
+/// Ring modulator. Multiplies two signals after blocking DC (below 10 Hz) from both,
+/// so that neither signal leaks into the output.
+/// - Input 0: signal
+/// - Input 1: modulator
+/// - Output 0: product
+///
+/// ### Example: Ring Modulated Voice
+/// ```
+/// use fundsp::prelude::*;
+/// let robot = (pass() | sine_hz::<f64>(30.0)) >> ring_mod::<f64>();
+/// ```
+pub fn ring_mod<T: Float>() -> An<RingMod<T>> {
+    An(RingMod::new())
+}
+
+/// Single-sideband frequency shifter. Shifts all frequencies of the input by `shift` Hz,
+/// up if positive and down if negative, using a Hilbert transformer.
+/// Unlike pitch shifting, harmonic relations are not preserved.
+/// Setting: shift in Hz.
+/// - Input 0: signal
+/// - Output 0: frequency shifted signal
+///
+/// ### Example: Barberpole Style Shift Feedback
+/// ```
+/// use fundsp::prelude::*;
+/// let shifter = pass() & feedback(delay::<f64>(0.1) >> freq_shift::<f64>(5.0) * 0.6);
+/// ```
+pub fn freq_shift<T: Float>(shift: f64) -> An<FreqShift<T>> {
+    An(FreqShift::new(shift))
+}
+
+/// Pitch shifter with low latency. Shifts input by `semitones`.
+/// Two delay line taps with a crossfade window of 50 ms do the shifting,
+/// which works best for monophonic and noisy material.
+/// Setting: pitch shift in semitones.
+/// - Input 0: input signal
+/// - Output 0: pitch shifted signal
+///
+/// ### Example: Octave Doubler
+/// ```
+/// use fundsp::prelude::*;
+/// let doubler = pass() & pitch_shift::<f64>(12.0);
+/// ```
+pub fn pitch_shift<T: Float>(semitones: T) -> An<PitchShift<T>> {
+    An(PitchShift::new(semitones, 0.05))
+}
+
+/// Spectral pitch shifter. Shifts input by `semitones` using a phase vocoder.
+/// If `preserve_formants` is set, then the spectral envelope stays in place,
+/// which keeps the character of voices and instruments.
+/// The latency is 2048 samples.
+/// - Input 0: input signal
+/// - Output 0: pitch shifted signal
+///
+/// ### Example: Harmonizer
+/// ```
+/// use fundsp::prelude::*;
+/// let harmonizer = pass() & pitch_shift_spectral::<f64>(4.0, true) & pitch_shift_spectral::<f64>(7.0, true);
+/// ```
+#[cfg(feature = "std")]
+pub fn pitch_shift_spectral<T: Float>(
+    semitones: f64,
+    preserve_formants: bool,
+) -> An<Resynth<U1, U1, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut shifter = SpectralShift::new(2048, semitones, preserve_formants);
+    An(Resynth::new(2048, move |fft| shifter.process(fft)))
+}
+
+/// Spectral subtraction noise reducer. The noise profile is learned from the quietest
+/// portions of the input, which are assumed to contain only noise, such as pauses in speech.
+/// The `amount` of reduction is in 0...1: the maximum attenuation ranges from 0 dB to 40 dB.
+/// Gains are smoothed over time and frequency to suppress musical noise.
+/// The latency is 2048 samples.
+/// - Input 0: input signal
+/// - Output 0: denoised signal
+///
+/// ### Example: Clean Up A Voice Chat Stream
+/// ```
+/// use fundsp::prelude::*;
+/// pass() >> denoise::<f64>(0.7);
+/// ```
+#[cfg(feature = "std")]
+pub fn denoise<T: Float>(
+    amount: f64,
+) -> An<Resynth<U1, U1, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut denoiser = SpectralDenoise::new(2048, amount, None);
+    An(Resynth::new(2048, move |fft| denoiser.process(fft)))
+}
+
+/// Spectral subtraction noise reducer with a supplied noise `profile`,
+/// which must have a window length of 2048 samples.
+/// The `amount` of reduction is in 0...1: the maximum attenuation ranges from 0 dB to 40 dB.
+/// The latency is 2048 samples.
+/// - Input 0: input signal
+/// - Output 0: denoised signal
+///
+/// ### Example: Denoise With A Profile Of Room Tone
+/// ```
+/// use fundsp::prelude::*;
+/// let room = Wave64::render(44100.0, 1.0, &mut (pink::<f64, f64>() * 0.01));
+/// let profile = NoiseProfile::new(2048, room.channel(0));
+/// pass() >> denoise_profile::<f64>(1.0, &profile);
+/// ```
+#[cfg(feature = "std")]
+pub fn denoise_profile<T: Float>(
+    amount: f64,
+    profile: &NoiseProfile,
+) -> An<Resynth<U1, U1, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut denoiser = SpectralDenoise::new(2048, amount, Some(profile));
+    An(Resynth::new(2048, move |fft| denoiser.process(fft)))
+}
+
+/// Spectral morph between two inputs. Interpolates the magnitude and phase of each FFT bin
+/// from input 0 (`amount` = 0) to input 1 (`amount` = 1).
+/// Phases are interpolated along the shorter arc.
+/// The latency is 2048 samples.
+/// - Input 0: source A
+/// - Input 1: source B
+/// - Output 0: morphed signal
+///
+/// ### Example: Halfway Between A Saw And Noise
+/// ```
+/// use fundsp::prelude::*;
+/// (saw_hz::<f64>(110.0) | pink::<f64, f64>()) >> spectral_morph::<f64>(0.5);
+/// ```
+#[cfg(feature = "std")]
+pub fn spectral_morph<T: Float>(
+    amount: f64,
+) -> An<Resynth<U2, U1, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut morph = SpectralMorph::new(amount, true);
+    An(Resynth::new(2048, move |fft| morph.process(fft)))
+}
+
+/// Spectral magnitude morph between two inputs. Interpolates the magnitude of each FFT bin
+/// from input 0 (`amount` = 0) to input 1 (`amount` = 1) while keeping the phases of input 0.
+/// The latency is 2048 samples.
+/// - Input 0: source A, which supplies phases
+/// - Input 1: source B
+/// - Output 0: morphed signal
+///
+/// ### Example: Imprint Noise Spectrum On A Saw
+/// ```
+/// use fundsp::prelude::*;
+/// (saw_hz::<f64>(110.0) | pink::<f64, f64>()) >> spectral_morph_magnitude::<f64>(0.5);
+/// ```
+#[cfg(feature = "std")]
+pub fn spectral_morph_magnitude<T: Float>(
+    amount: f64,
+) -> An<Resynth<U2, U1, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut morph = SpectralMorph::new(amount, false);
+    An(Resynth::new(2048, move |fft| morph.process(fft)))
+}
+
 /// `N`-channel impulse. The first sample on each channel is one and the rest are zero.
 /// - Output(s): impulse.
 pub fn impulse<N: Size<T>, T: Float>() -> An<Impulse<N, T>> {
@@ -3018,3 +5070,160 @@ pub fn rotate<T: Real>(angle: T, gain: T) -> An<Mixer<U2, U2, T>> {
         .into(),
     ))
 }
+
+/// Mid/side encoder. Converts a stereo signal to mid `(L + R) / 2` and side `(L - R) / 2`.
+/// The inverse is `ms_decode`.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: mid output
+/// - Output 1: side output
+///
+/// ### Example: Process The Side Channel
+/// ```
+/// use fundsp::prelude::*;
+/// ms_encode::<f64>() >> (pass::<f64>() | highpass_hz::<f64, f64>(200.0, 0.7)) >> ms_decode::<f64>();
+/// ```
+pub fn ms_encode<T: Real>() -> An<Mixer<U2, U2, T>> {
+    let half = T::from_f64(0.5);
+    An(Mixer::new([[half, half].into(), [half, -half].into()].into()))
+}
+
+/// Mid/side decoder. Converts mid and side signals back to left `M + S` and right `M - S`.
+/// The inverse is `ms_encode`.
+/// - Input 0: mid input
+/// - Input 1: side input
+/// - Output 0: left output
+/// - Output 1: right output
+pub fn ms_decode<T: Real>() -> An<Mixer<U2, U2, T>> {
+    An(Mixer::new(
+        [[T::one(), T::one()].into(), [T::one(), -T::one()].into()].into(),
+    ))
+}
+
+/// Stereo width control. Scales the side component of a stereo signal by `width` (`width` >= 0).
+/// Width 0 is mono, 1 leaves the signal unchanged and values above 1 widen the image.
+/// Setting: width.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Narrow A Stereo Bus
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f64, f64>() | pink::<f64, f64>()) >> stereo_width::<f64>(0.5);
+/// ```
+pub fn stereo_width<T: Real>(width: T) -> An<StereoWidth<T>> {
+    An(StereoWidth::new(width))
+}
+
+/// Stereo rotation by `angle` radians. Setting: angle.
+/// Unlike `rotate`, the angle can be changed with a setting.
+/// A rotation of `PI / 4` converts a stereo signal to scaled mid and side signals.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: rotated left output
+/// - Output 1: rotated right output
+///
+/// ### Example
+/// ```
+/// use fundsp::prelude::*;
+/// stereo_rotate::<f64>(PI / 8.0);
+/// ```
+pub fn stereo_rotate<T: Real>(angle: T) -> An<StereoRotate<T>> {
+    An(StereoRotate::new(angle))
+}
+
+/// Stereo correlation meter with a smoothing timescale of 300 ms.
+/// Outputs the correlation of the left and right channels in -1...1:
+/// 1 is mono, 0 is uncorrelated and -1 is out of phase. Silence reads as 0.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: correlation
+///
+/// ### Example: Meter A Stereo Bus
+/// ```
+/// use fundsp::prelude::*;
+/// let phase = shared(0.0);
+/// (pink::<f64, f64>() | pink::<f64, f64>())
+///     >> ((pass::<f64>() | pass::<f64>()) ^ (correlation::<f64>() >> monitor(&phase, Meter::Sample) >> sink::<f64>()));
+/// ```
+pub fn correlation<T: Real>() -> An<Correlation<T>> {
+    An(Correlation::new(DEFAULT_SR, 0.3))
+}
+
+/// Stereo correlation meter with smoothing timescale `t` seconds.
+/// The timescale is the time it takes for the reading to move halfway to a new value.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: correlation
+pub fn correlation_s<T: Real>(t: f64) -> An<Correlation<T>> {
+    An(Correlation::new(DEFAULT_SR, t))
+}
+
+/// Center channel canceller for karaoke and practice tools. Outputs the side component
+/// of a stereo signal, left `(L - R) / 2` and right `(R - L) / 2`,
+/// which removes everything panned to the center, such as lead vocals.
+/// The result sums to silence in mono.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Remove Vocals
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f64, f64>() | pink::<f64, f64>()) >> center_cancel::<f64>();
+/// ```
+pub fn center_cancel<T: Real>() -> An<Mixer<U2, U2, T>> {
+    let half = T::from_f64(0.5);
+    An(Mixer::new([[half, -half].into(), [-half, half].into()].into()))
+}
+
+/// Center channel extractor. Outputs the mid component `(L + R) / 2` of a stereo signal
+/// on both channels. Side content that is not hard panned leaks into the output;
+/// see `center_extract_spectral` for a more selective extractor.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+pub fn center_extract<T: Real>() -> An<Mixer<U2, U2, T>> {
+    let half = T::from_f64(0.5);
+    An(Mixer::new([[half, half].into(), [half, half].into()].into()))
+}
+
+/// Spectral center channel canceller. Removes FFT bins that are identical in both channels
+/// with a soft mask, which keeps partially panned and stereo content intact.
+/// Complements `center_extract_spectral`: the two sum to the input.
+/// The latency is 2048 samples.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// ### Example: Remove Vocals
+/// ```
+/// use fundsp::prelude::*;
+/// (pink::<f64, f64>() | pink::<f64, f64>()) >> center_cancel_spectral::<f64>();
+/// ```
+#[cfg(feature = "std")]
+pub fn center_cancel_spectral<T: Float>(
+) -> An<Resynth<U2, U2, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut center = SpectralCenter::new(false);
+    An(Resynth::new(2048, move |fft| center.process(fft)))
+}
+
+/// Spectral center channel extractor. Keeps FFT bins that are identical in both channels
+/// with a soft mask and outputs them on both channels.
+/// Complements `center_cancel_spectral`: the two sum to the input.
+/// The latency is 2048 samples.
+/// - Input 0: left input
+/// - Input 1: right input
+/// - Output 0: left output
+/// - Output 1: right output
+#[cfg(feature = "std")]
+pub fn center_extract_spectral<T: Float>(
+) -> An<Resynth<U2, U2, T, impl FnMut(&mut FftWindow) + Clone + Send + Sync>> {
+    let mut center = SpectralCenter::new(true);
+    An(Resynth::new(2048, move |fft| center.process(fft)))
+}
@@ -0,0 +1,329 @@
+//! Preset morphing.
+
+use super::audionode::*;
+use super::combinator::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+
+/// Interpolation curve of a parameter when morphing between presets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MorphCurve {
+    /// Linear interpolation.
+    #[default]
+    Linear,
+    /// Smooth cubic interpolation that eases in and out.
+    Smooth,
+    /// Exponential interpolation, suitable for frequencies and times.
+    /// Falls back to linear interpolation if either value is not positive.
+    Logarithmic,
+    /// Switch from one value to the other halfway, for discrete parameters.
+    Step,
+}
+
+impl MorphCurve {
+    /// Default curve for parameter `info`: logarithmic for frequencies and times
+    /// with a positive range, linear otherwise.
+    pub fn for_parameter(info: &ParameterInfo) -> Self {
+        match info.unit {
+            ParameterUnit::Hz | ParameterUnit::Seconds if info.min > 0.0 => {
+                MorphCurve::Logarithmic
+            }
+            _ => MorphCurve::Linear,
+        }
+    }
+
+    /// Interpolate from `a` to `b` with `t` in 0...1.
+    #[inline]
+    pub fn interpolate(&self, a: f64, b: f64, t: f64) -> f64 {
+        match self {
+            MorphCurve::Linear => lerp(a, b, t),
+            MorphCurve::Smooth => lerp(a, b, smooth3(t)),
+            MorphCurve::Logarithmic => {
+                if a > 0.0 && b > 0.0 {
+                    a * pow(b / a, t)
+                } else {
+                    lerp(a, b, t)
+                }
+            }
+            MorphCurve::Step => {
+                if t < 0.5 {
+                    a
+                } else {
+                    b
+                }
+            }
+        }
+    }
+}
+
+/// Setting of `PresetMorph`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MorphSetting {
+    /// Morph from current parameter values to preset (index) over (time) seconds.
+    Morph(usize, f64),
+    /// Set parameters immediately to a blend of presets (a, b) with amount in 0...1.
+    Blend(usize, usize, f64),
+}
+
+impl Default for MorphSetting {
+    fn default() -> Self {
+        MorphSetting::Morph(0, 0.0)
+    }
+}
+
+/// Equip `node` with preset morphing. Presets are stored with `store` or `push_preset`
+/// and morphed between with `morph` and `blend` or via settings.
+/// Setting: `MorphSetting`.
+/// - Inputs and outputs: from `node`.
+///
+/// ### Example: A/B Morph
+/// ```
+/// use fundsp::hacker::*;
+/// let mut node = preset_morph(saw() >> lowpass_hz(500.0, 1.0));
+/// let a = node.store();
+/// let b = node.push_preset(&[5000.0, 2.0]);
+/// node.blend(a, b, 0.5);
+/// assert!((node.parameter(0) - sqrt(500.0 * 5000.0)).abs() < 1.0e-9);
+/// node.morph(b, 1.0);
+/// assert!(node.is_morphing());
+/// ```
+pub fn preset_morph<X: AudioNode>(node: An<X>) -> An<PresetMorph<X>> {
+    An(PresetMorph::new(node.0))
+}
+
+/// Preset morph. Stores snapshots of the parameter values of the contained node
+/// and interpolates between them over time. Morphing does not allocate.
+/// Parameters are updated once per processing block or sample.
+/// Setting: `MorphSetting`.
+/// - Inputs and outputs: from the contained node.
+#[derive(Clone)]
+pub struct PresetMorph<X: AudioNode> {
+    x: X,
+    /// Parameter values of stored presets.
+    presets: Vec<Vec<f64>>,
+    /// Interpolation curves of parameters.
+    curves: Vec<MorphCurve>,
+    /// Parameter values at the start of the morph.
+    start: Vec<f64>,
+    /// Parameter values at the end of the morph.
+    target: Vec<f64>,
+    /// Time elapsed since the start of the morph in seconds.
+    time: f64,
+    /// Duration of the morph in seconds.
+    duration: f64,
+    /// Whether a morph is in progress.
+    active: bool,
+    sample_duration: f64,
+}
+
+impl<X: AudioNode> PresetMorph<X> {
+    /// Create preset morph for node `x`. There are no presets initially.
+    /// Interpolation curves are chosen based on parameter units.
+    pub fn new(x: X) -> Self {
+        let curves = x.parameters().iter().map(MorphCurve::for_parameter).collect();
+        let count = x.parameter_count();
+        let mut node = Self {
+            x,
+            presets: Vec::new(),
+            curves,
+            start: vec![0.0; count],
+            target: vec![0.0; count],
+            time: 0.0,
+            duration: 0.0,
+            active: false,
+            sample_duration: 1.0 / DEFAULT_SR,
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Access the contained node.
+    #[inline]
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+
+    /// Access the contained node mutably.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Number of stored presets.
+    #[inline]
+    pub fn presets(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Parameter values of preset `i`.
+    #[inline]
+    pub fn preset(&self, i: usize) -> &[f64] {
+        &self.presets[i]
+    }
+
+    /// Current parameter values of the contained node.
+    pub fn snapshot(&self) -> Vec<f64> {
+        (0..self.x.parameter_count())
+            .map(|i| self.x.parameter(i))
+            .collect()
+    }
+
+    /// Store current parameter values as a new preset. Returns the index of the preset.
+    /// - Allocates: preset storage.
+    pub fn store(&mut self) -> usize {
+        let snapshot = self.snapshot();
+        self.presets.push(snapshot);
+        self.presets.len() - 1
+    }
+
+    /// Add a preset with the given parameter values. Returns the index of the preset.
+    /// - Allocates: preset storage.
+    pub fn push_preset(&mut self, values: &[f64]) -> usize {
+        assert_eq!(values.len(), self.x.parameter_count());
+        self.presets.push(values.to_vec());
+        self.presets.len() - 1
+    }
+
+    /// Set parameter values of preset `i`.
+    pub fn set_preset(&mut self, i: usize, values: &[f64]) {
+        self.presets[i].copy_from_slice(values);
+    }
+
+    /// Interpolation curve of parameter `index`.
+    #[inline]
+    pub fn curve(&self, index: usize) -> MorphCurve {
+        self.curves[index]
+    }
+
+    /// Set interpolation curve of parameter `index`.
+    #[inline]
+    pub fn set_curve(&mut self, index: usize, curve: MorphCurve) {
+        self.curves[index] = curve;
+    }
+
+    /// Morph from current parameter values to preset `i` over `time` seconds.
+    pub fn morph(&mut self, i: usize, time: f64) {
+        for (j, start) in self.start.iter_mut().enumerate() {
+            *start = self.x.parameter(j);
+        }
+        self.target.copy_from_slice(&self.presets[i]);
+        self.time = 0.0;
+        self.duration = max(0.0, time);
+        self.active = true;
+        self.update(0.0);
+    }
+
+    /// Set parameters immediately to a blend of presets `a` and `b` with `amount` in 0...1.
+    /// Cancels any morph in progress.
+    pub fn blend(&mut self, a: usize, b: usize, amount: f64) {
+        let amount = clamp01(amount);
+        for (j, curve) in self.curves.iter().enumerate() {
+            let value = curve.interpolate(self.presets[a][j], self.presets[b][j], amount);
+            self.x.set_parameter(j, value);
+        }
+        self.active = false;
+    }
+
+    /// Whether a morph is in progress.
+    #[inline]
+    pub fn is_morphing(&self) -> bool {
+        self.active
+    }
+
+    /// Advance the morph by `elapsed` seconds and apply parameter values.
+    fn update(&mut self, elapsed: f64) {
+        if !self.active {
+            return;
+        }
+        self.time += elapsed;
+        let t = if self.time < self.duration {
+            self.time / self.duration
+        } else {
+            self.active = false;
+            1.0
+        };
+        for (j, curve) in self.curves.iter().enumerate() {
+            let value = curve.interpolate(self.start[j], self.target[j], t);
+            self.x.set_parameter(j, value);
+        }
+    }
+}
+
+impl<X: AudioNode> AudioNode for PresetMorph<X> {
+    const ID: u64 = 112;
+    type Sample = X::Sample;
+    type Inputs = X::Inputs;
+    type Outputs = X::Outputs;
+    type Setting = MorphSetting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        match setting {
+            MorphSetting::Morph(i, time) => self.morph(i, time),
+            MorphSetting::Blend(a, b, amount) => self.blend(a, b, amount),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = 1.0 / sample_rate;
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let output = self.x.tick(input);
+        self.update(self.sample_duration);
+        output
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        self.x.process(size, input, output);
+        self.update(size as f64 * self.sample_duration);
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x.route(input, frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        let child = self.x.describe();
+        NodeInfo::from_node(self).with_children(vec![child])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
+}
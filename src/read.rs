@@ -23,7 +23,8 @@ pub type WaveError = Error;
 )]
 impl Wave48 {
     /// Load first track of audio file from the given path.
-    /// Supported formats are anything that Symphonia can read.
+    /// Supported formats are anything that Symphonia can read,
+    /// including WAV, OGG/Vorbis, FLAC and MP3.
     pub fn load<P: AsRef<Path>>(path: P) -> WaveResult<Wave48> {
         Wave48::load_track(path, None)
     }
@@ -1,12 +1,111 @@
 //! Real-time friendly backend for Net64 and Net32.
 
+#[cfg(feature = "alloc_audit")]
+use super::audit::*;
 use super::audiounit::*;
 use super::math::*;
 use super::net::*;
 use super::signal::*;
+use super::*;
 use duplicate::duplicate_item;
 use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
 
+/// Duration in seconds of the fade out and fade in around a live sample rate change in a backend.
+pub(crate) const RATE_FADE_TIME: f64 = 0.005;
+
+/// Fades backend output out and back in around a live sample rate change,
+/// so that the backend can switch rates at a silent point.
+#[derive(Clone)]
+pub(crate) struct RateFade {
+    /// Current sample rate.
+    sample_rate: f64,
+    /// Requested sample rate, if different from the current one.
+    pending: Option<f64>,
+    /// Current gain in 0...1.
+    gain: f64,
+    /// Gain change per sample.
+    step: f64,
+}
+
+impl RateFade {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            pending: None,
+            gain: 1.0,
+            step: 1.0 / (RATE_FADE_TIME * sample_rate),
+        }
+    }
+
+    /// Current sample rate in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Set sample rate immediately, canceling any fade.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        *self = Self::new(sample_rate);
+    }
+
+    /// Request a change to `sample_rate`. Fading out begins if it differs from the current rate.
+    pub fn request(&mut self, sample_rate: f64) {
+        self.pending = if sample_rate != self.sample_rate {
+            Some(sample_rate)
+        } else {
+            None
+        };
+    }
+
+    /// Returns true, and adopts the requested sample rate, if it is time to switch.
+    pub fn switch(&mut self) -> bool {
+        match self.pending {
+            Some(sample_rate) if self.gain <= 0.0 => {
+                self.sample_rate = sample_rate;
+                self.step = 1.0 / (RATE_FADE_TIME * sample_rate);
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Advance gain by one sample.
+    #[inline]
+    fn next_gain(&mut self) -> f64 {
+        if self.pending.is_some() {
+            self.gain = max(0.0, self.gain - self.step);
+        } else {
+            self.gain = min(1.0, self.gain + self.step);
+        }
+        self.gain
+    }
+
+    /// Apply fade to a frame.
+    #[inline]
+    pub fn apply_frame<T: Float>(&mut self, output: &mut [T]) {
+        if self.pending.is_none() && self.gain >= 1.0 {
+            return;
+        }
+        let gain = T::from_f64(self.next_gain());
+        for x in output.iter_mut() {
+            *x *= gain;
+        }
+    }
+
+    /// Apply fade to a block.
+    pub fn apply<T: Float>(&mut self, size: usize, output: &mut [&mut [T]]) {
+        if self.pending.is_none() && self.gain >= 1.0 {
+            return;
+        }
+        for i in 0..size {
+            let gain = T::from_f64(self.next_gain());
+            for channel in output.iter_mut() {
+                channel[i] *= gain;
+            }
+        }
+    }
+}
+
 #[duplicate_item(
     f48       Net48       NetBackend48       Vertex48       AudioUnit48;
     [ f64 ]   [ Net64 ]   [ NetBackend64 ]   [ Vertex64 ]   [ AudioUnit64 ];
@@ -18,6 +117,10 @@ pub struct NetBackend48 {
     /// For receiving new versions from the frontend.
     receiver: Receiver<Net48>,
     net: Net48,
+    /// Version at a new sample rate waiting for the fade out to finish.
+    pending: Option<Net48>,
+    /// Fade around live sample rate changes.
+    fade: RateFade,
 }
 
 #[duplicate_item(
@@ -33,6 +136,8 @@ impl Clone for NetBackend48 {
             sender,
             receiver,
             net: self.net.clone(),
+            pending: None,
+            fade: self.fade.clone(),
         }
     }
 }
@@ -45,10 +150,13 @@ impl Clone for NetBackend48 {
 impl NetBackend48 {
     /// Create new backend.
     pub fn new(sender: Sender<Net48>, receiver: Receiver<Net48>, net: Net48) -> Self {
+        let fade = RateFade::new(net.sample_rate());
         Self {
             sender,
             receiver,
             net,
+            pending: None,
+            fade,
         }
     }
 
@@ -69,11 +177,28 @@ impl NetBackend48 {
             }
         }
         if let Some(mut net) = latest_net {
-            // Migrate existing nodes to the new network.
-            self.net.migrate(&mut net);
-            std::mem::swap(&mut net, &mut self.net);
-            // Send the previous network back for deallocation.
-            if self.sender.try_send(net).is_ok() {}
+            // Any version waiting for a sample rate change is now obsolete.
+            if let Some(pending) = self.pending.take() {
+                if self.sender.try_send(pending).is_ok() {}
+            }
+            self.fade.request(net.sample_rate());
+            if net.sample_rate() != self.net.sample_rate() {
+                // Fade out before switching to the new sample rate.
+                self.pending = Some(net);
+            } else {
+                // Migrate existing nodes to the new network.
+                self.net.migrate(&mut net);
+                std::mem::swap(&mut net, &mut self.net);
+                // Send the previous network back for deallocation.
+                if self.sender.try_send(net).is_ok() {}
+            }
+        }
+        if self.fade.switch() {
+            if let Some(mut net) = self.pending.take() {
+                self.net.migrate(&mut net);
+                std::mem::swap(&mut net, &mut self.net);
+                if self.sender.try_send(net).is_ok() {}
+            }
         }
     }
 }
@@ -99,17 +224,24 @@ impl AudioUnit48 for NetBackend48 {
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
         self.net.set_sample_rate(sample_rate);
+        self.fade.set_sample_rate(sample_rate);
         self.handle_messages();
     }
 
     fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        #[cfg(feature = "alloc_audit")]
+        let _guard = AllocGuard::new();
         self.handle_messages();
         self.net.tick(input, output);
+        self.fade.apply_frame(output);
     }
 
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        #[cfg(feature = "alloc_audit")]
+        let _guard = AllocGuard::new();
         self.handle_messages();
         self.net.process(size, input, output);
+        self.fade.apply(size, output);
     }
 
     fn get_id(&self) -> u64 {
@@ -1,10 +1,15 @@
 //! Real-time friendly backend for the sequencer unit.
 
+#[cfg(feature = "alloc_audit")]
+use super::audit::*;
 use super::audiounit::*;
 use super::math::*;
+use super::realnet::RateFade;
 use super::sequencer::*;
 use super::signal::*;
 use duplicate::duplicate_item;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
 
 #[duplicate_item(
@@ -21,10 +26,24 @@ pub enum Message48 {
     Push(Event48),
     /// Add new event in relative time.
     PushRelative(Event48),
+    /// Add new event starting at the next multiple of the grid (in seconds).
+    PushQuantized(f48, Event48),
+    /// Add a batch of new events in absolute time.
+    PushBatch(Vec<Event48>),
     /// Edit event.
     Edit(EventId, Edit48),
     /// Edit event in relative time.
     EditRelative(EventId, Edit48),
+    /// Change sample rate (in Hz) with a short fade out and fade in.
+    SetSampleRate(f64),
+    /// Set offset of the grid of quantized events (in seconds).
+    SetGridOffset(f48),
+    /// Set maximum number of sounding events and fade out time (in seconds) of culled events.
+    SetVoiceBudget(usize, f48),
+    /// Set maximum fraction of real time spent processing.
+    SetCpuBudget(Option<f64>),
+    /// Enable or disable virtual voices.
+    SetVirtualVoices(bool),
 }
 
 #[duplicate_item(
@@ -50,6 +69,16 @@ pub struct SequencerBackend48 {
     receiver: Receiver<Message48>,
     /// The backend sequencer.
     sequencer: Sequencer48,
+    /// For notifying the frontend of finished events.
+    finished: Option<Sender<Option<EventId>>>,
+    /// Fade around live sample rate changes.
+    fade: RateFade,
+    /// For publishing the number of samples processed to the frontend.
+    time: Option<Arc<AtomicU64>>,
+    /// Number of samples processed since the last reset, at the current sample rate.
+    samples: u64,
+    /// For sending emptied batches back to the frontend for deallocation.
+    batches: Option<Sender<Vec<Event48>>>,
 }
 
 #[duplicate_item(
@@ -66,6 +95,11 @@ impl Clone for SequencerBackend48 {
             sender: sender_1,
             receiver: receiver_2,
             sequencer: self.sequencer.clone(),
+            finished: None,
+            fade: self.fade.clone(),
+            time: None,
+            samples: self.samples,
+            batches: None,
         }
     }
 }
@@ -82,10 +116,49 @@ impl SequencerBackend48 {
         receiver: Receiver<Message48>,
         sequencer: Sequencer48,
     ) -> Self {
+        let fade = RateFade::new(sequencer.sample_rate());
+        let samples = Self::time_samples(&sequencer);
         Self {
             sender,
             receiver,
             sequencer,
+            finished: None,
+            fade,
+            time: None,
+            samples,
+            batches: None,
+        }
+    }
+
+    /// Notify the frontend of finished events through `finished`.
+    pub fn with_finished(mut self, finished: Sender<Option<EventId>>) -> Self {
+        self.finished = Some(finished);
+        self
+    }
+
+    /// Publish the number of samples processed by the backend to the frontend through `time`.
+    pub fn with_time(mut self, time: Arc<AtomicU64>) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Send emptied batches back to the frontend for deallocation through `batches`.
+    pub fn with_batches(mut self, batches: Sender<Vec<Event48>>) -> Self {
+        self.batches = Some(batches);
+        self
+    }
+
+    /// Current time of `sequencer` in samples.
+    #[allow(clippy::unnecessary_cast)]
+    fn time_samples(sequencer: &Sequencer48) -> u64 {
+        round(sequencer.time() as f64 * sequencer.sample_rate()) as u64
+    }
+
+    /// Publish the number of samples processed to the frontend.
+    #[inline]
+    fn store_time(&mut self) {
+        if let Some(time) = &self.time {
+            time.store(self.samples, Ordering::Relaxed);
         }
     }
 
@@ -99,6 +172,18 @@ impl SequencerBackend48 {
                 Message48::PushRelative(event) => {
                     self.sequencer.push_relative_event(event);
                 }
+                Message48::PushQuantized(grid, event) => {
+                    self.sequencer.push_quantized_event(grid, event);
+                }
+                Message48::PushBatch(mut events) => {
+                    for event in events.drain(..) {
+                        self.sequencer.push_event(event);
+                    }
+                    // The batch channel is as large as the message channel, so there is always room.
+                    if let Some(sender) = &self.batches {
+                        if sender.try_send(events).is_ok() {}
+                    }
+                }
                 Message48::Edit(id, edit) => {
                     self.sequencer.edit(id, edit.end_time, edit.fade_out);
                 }
@@ -106,11 +191,43 @@ impl SequencerBackend48 {
                     self.sequencer
                         .edit_relative(id, edit.end_time, edit.fade_out);
                 }
+                Message48::SetSampleRate(sample_rate) => {
+                    self.fade.request(sample_rate);
+                }
+                Message48::SetGridOffset(offset) => {
+                    self.sequencer.set_grid_offset(offset);
+                }
+                Message48::SetVoiceBudget(voices, fade_out_time) => {
+                    self.sequencer.set_voice_budget(voices, fade_out_time);
+                }
+                Message48::SetCpuBudget(load) => {
+                    self.sequencer.set_cpu_budget(load);
+                }
+                Message48::SetVirtualVoices(enabled) => {
+                    self.sequencer.set_virtual_voices(enabled);
+                }
                 Message48::Null => {}
             }
         }
+        if self.fade.switch() {
+            self.sequencer.set_sample_rate(self.fade.sample_rate());
+            // Count samples at the new rate from the current time on.
+            self.samples = Self::time_samples(&self.sequencer);
+        }
+    }
+
+    /// Notify the frontend of events that have finished.
+    #[inline]
+    fn send_finished(&mut self) {
+        for id in self.sequencer.drain_finished() {
+            if let Some(sender) = &self.finished {
+                if sender.try_send(Some(id)).is_ok() {}
+            }
+        }
     }
 
+    /// Send past events back to the frontend for deallocation.
+    /// The return channel has room for every event we can hold, so no event is dropped here.
     #[inline]
     fn send_back_past(&mut self) {
         while let Some(event) = self.sequencer.get_past_event() {
@@ -135,6 +252,7 @@ impl AudioUnit48 for SequencerBackend48 {
 
     fn reset(&mut self) {
         self.handle_messages();
+        // Send all events back to the frontend. The return channel has room for all of them.
         while let Some(event) = self.sequencer.get_past_event() {
             if self.sender.try_send(Some(event)).is_ok() {}
         }
@@ -145,17 +263,28 @@ impl AudioUnit48 for SequencerBackend48 {
             if self.sender.try_send(Some(event)).is_ok() {}
         }
         self.sequencer.reset();
+        self.samples = 0;
+        self.store_time();
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
         self.handle_messages();
         self.sequencer.set_sample_rate(sample_rate);
+        self.fade.set_sample_rate(sample_rate);
+        self.samples = Self::time_samples(&self.sequencer);
+        self.store_time();
     }
 
     #[inline]
     fn tick(&mut self, input: &[f48], output: &mut [f48]) {
+        #[cfg(feature = "alloc_audit")]
+        let _guard = AllocGuard::new();
         self.handle_messages();
         self.sequencer.tick(input, output);
+        self.fade.apply_frame(output);
+        self.samples += 1;
+        self.store_time();
+        self.send_finished();
         // Tick and process are the only places where events may be pushed to the past vector.
         if !self.sequencer.replay_events() {
             self.send_back_past();
@@ -163,8 +292,14 @@ impl AudioUnit48 for SequencerBackend48 {
     }
 
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
+        #[cfg(feature = "alloc_audit")]
+        let _guard = AllocGuard::new();
         self.handle_messages();
         self.sequencer.process(size, input, output);
+        self.fade.apply(size, output);
+        self.samples += size as u64;
+        self.store_time();
+        self.send_finished();
         // Tick and process are the only places where events may be pushed to the past vector.
         if !self.sequencer.replay_events() {
             self.send_back_past();
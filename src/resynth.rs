@@ -219,6 +219,15 @@ impl FftWindow {
     }
 }
 
+/// Hann window function of `length` samples centered on the middle sample.
+fn hann_window(length: usize) -> Vec<f32> {
+    (0..length)
+        .map(|i| {
+            0.5 + 0.5 * cos((i as i32 - (length >> 1) as i32) as f32 * TAU as f32 / length as f32)
+        })
+        .collect()
+}
+
 /// Frequency domain resynthesizer. Processes windows of input samples with an overlap of four.
 /// Each window is Fourier transformed and then processed into output spectra
 /// by the user supplied processing function.
@@ -284,15 +293,7 @@ where
         let forward = planner.plan_fft_forward(window_length);
         let inverse = planner.plan_fft_inverse(window_length);
 
-        let mut window_function = Vec::with_capacity(window_length);
-
-        for i in 0..window_length {
-            let hann = 0.5
-                + 0.5
-                    * cos((i as i32 - (window_length >> 1) as i32) as f32 * TAU as f32
-                        / window_length as f32);
-            window_function.push(hann);
-        }
+        let window_function = hann_window(window_length);
 
         let window = [
             FftWindow::new(window_length, 0, I::USIZE, O::USIZE),
@@ -382,9 +383,13 @@ where
 
                     for channel in 0..O::USIZE {
                         let (output_fft, output) = self.window[i].inverse_vectors(channel);
-                        self.inverse
+                        // The inverse transform reports nonzero imaginary parts in the DC
+                        // and Nyquist bins as an error but ignores them, which is what we want.
+                        if self
+                            .inverse
                             .process_with_scratch(output_fft, output, &mut self.scratch)
-                            .expect("Internal error");
+                            .is_ok()
+                        {}
                     }
                 }
             }
@@ -396,3 +401,230 @@ where
         Routing::Arbitrary(self.window_length as f64).propagate(input, self.outputs())
     }
 }
+
+/// Noise profile for spectral noise reduction. Contains the average power
+/// of each FFT bin of a noise recording, analyzed with the same windowing as `resynth`.
+#[derive(Clone)]
+pub struct NoiseProfile {
+    /// Window length in samples.
+    window_length: usize,
+    /// Average power of each bin.
+    power: Vec<f32>,
+}
+
+impl NoiseProfile {
+    /// Analyze a noise profile from `samples` with a window length of `window_length` samples.
+    /// The window length must be a power of two and at least four, and must match
+    /// the window length of the denoiser. The `denoise_profile` opcode uses 2048 samples.
+    /// Windows overlap by three quarters. Samples that do not fill a window are ignored.
+    pub fn new<T: Float>(window_length: usize, samples: &[T]) -> Self {
+        assert!(window_length >= 4 && window_length.is_power_of_two());
+        let bins = (window_length >> 1) + 1;
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(window_length);
+        let window_function = hann_window(window_length);
+        let mut input = vec![0.0; window_length];
+        let mut spectrum = vec![Complex32::default(); bins];
+        let mut power = vec![0.0; bins];
+        let mut windows = 0;
+        let mut start = 0;
+        while start + window_length <= samples.len() {
+            for i in 0..window_length {
+                input[i] = samples[start + i].to_f32() * window_function[i];
+            }
+            forward
+                .process(&mut input, &mut spectrum)
+                .expect("Internal error");
+            for (power, x) in power.iter_mut().zip(spectrum.iter()) {
+                *power += x.norm_sqr();
+            }
+            windows += 1;
+            start += window_length >> 2;
+        }
+        if windows > 0 {
+            let z = 1.0 / windows as f32;
+            power.iter_mut().for_each(|x| *x *= z);
+        }
+        Self {
+            window_length,
+            power,
+        }
+    }
+
+    /// Window length in samples.
+    #[inline]
+    pub fn window_length(&self) -> usize {
+        self.window_length
+    }
+
+    /// Number of FFT bins.
+    #[inline]
+    pub fn bins(&self) -> usize {
+        self.power.len()
+    }
+
+    /// Average power of bin `i`.
+    #[inline]
+    pub fn power(&self, i: usize) -> f32 {
+        self.power[i]
+    }
+}
+
+/// Spectral subtraction noise reducer for use with `resynth`. Processes channel 0.
+/// Subtracts the power of a noise profile from each FFT bin.
+/// Gains are smoothed over time and frequency to suppress musical noise.
+/// If no profile is supplied, then the profile is learned from the quietest windows,
+/// which are assumed to contain only noise, such as pauses in speech.
+#[derive(Clone)]
+pub struct SpectralDenoise {
+    /// Minimum gain amplitude.
+    floor: f32,
+    /// Noise power of each bin.
+    noise: Vec<f32>,
+    /// Whether the noise profile is learned from the input.
+    learn: bool,
+    /// Whether a noise profile is available.
+    ready: bool,
+    /// Tracked minimum window energy.
+    minimum: f32,
+    /// Smoothed gain of each bin.
+    gain: Vec<f32>,
+}
+
+impl SpectralDenoise {
+    /// Create noise reducer for `resynth` windows of `window_length` samples.
+    /// The `amount` of reduction is in 0...1: the maximum attenuation of a bin
+    /// ranges from 0 dB to 40 dB. If a `profile` is supplied, then its window length
+    /// must be equal to `window_length`; otherwise, the profile is learned from the input.
+    /// - Allocates: all needed buffers.
+    pub fn new(window_length: usize, amount: f64, profile: Option<&NoiseProfile>) -> Self {
+        let bins = (window_length >> 1) + 1;
+        let noise = match profile {
+            Some(profile) => {
+                assert_eq!(profile.window_length(), window_length);
+                profile.power.clone()
+            }
+            None => vec![0.0; bins],
+        };
+        Self {
+            floor: db_amp(-40.0 * clamp01(amount)) as f32,
+            noise,
+            learn: profile.is_none(),
+            ready: profile.is_some(),
+            minimum: 0.0,
+            gain: vec![1.0; bins],
+        }
+    }
+
+    /// Update the learned noise profile if the window is one of the quietest seen so far.
+    fn learn_profile(&mut self, fft: &FftWindow) {
+        let bins = fft.bins();
+        let energy: f32 = (0..bins).map(|i| fft.at(0, i).norm_sqr()).sum();
+        // Digital silence carries no information about the noise.
+        if energy <= 1.0e-12 {
+            return;
+        }
+        // The tracked minimum rises 3 dB per second so the profile can follow louder noise.
+        let hop = (fft.length() >> 2) as f32;
+        self.minimum *= pow(2.0f32, hop / fft.sample_rate() as f32);
+        if !self.ready || energy < self.minimum {
+            self.minimum = energy;
+        }
+        if energy <= self.minimum * 2.0 {
+            let weight = if self.ready { 0.1 } else { 1.0 };
+            for i in 0..bins {
+                self.noise[i] = lerp(self.noise[i], fft.at(0, i).norm_sqr(), weight);
+            }
+            self.ready = true;
+        }
+    }
+
+    /// Process one window.
+    pub fn process(&mut self, fft: &mut FftWindow) {
+        if self.learn {
+            self.learn_profile(fft);
+        }
+        let bins = fft.bins();
+        for i in 0..bins {
+            let power = fft.at(0, i).norm_sqr();
+            // Over-subtract the noise power by a factor of two.
+            let target = if self.ready && power > 0.0 {
+                sqrt(max(0.0, 1.0 - 2.0 * self.noise[i] / power))
+            } else {
+                1.0
+            };
+            let target = max(self.floor, target);
+            // Open gains immediately but close them gradually.
+            self.gain[i] = if target > self.gain[i] {
+                target
+            } else {
+                lerp(self.gain[i], target, 0.5)
+            };
+        }
+        for i in 0..bins {
+            let previous = self.gain[i.saturating_sub(1)];
+            let next = self.gain[min(i + 1, bins - 1)];
+            let gain = 0.25 * previous + 0.5 * self.gain[i] + 0.25 * next;
+            fft.set(0, i, fft.at(0, i) * gain);
+        }
+    }
+}
+
+/// Spectral morph for use with `resynth`. Interpolates the spectra of channels 0 and 1
+/// into output channel 0. Magnitudes are interpolated linearly. Phases are either
+/// interpolated along the shorter arc or taken from channel 0.
+#[derive(Clone)]
+pub struct SpectralMorph {
+    /// Morph amount in 0...1 from channel 0 to channel 1.
+    amount: f32,
+    /// Whether to interpolate phases (true) or take them from channel 0 (false).
+    morph_phase: bool,
+}
+
+impl SpectralMorph {
+    /// Create spectral morph with `amount` in 0...1 from channel 0 (0) to channel 1 (1).
+    /// If `morph_phase` is set, then phases are interpolated, too;
+    /// otherwise, they are taken from channel 0.
+    pub fn new(amount: f64, morph_phase: bool) -> Self {
+        Self {
+            amount: clamp01(amount) as f32,
+            morph_phase,
+        }
+    }
+
+    /// Morph amount in 0...1.
+    pub fn amount(&self) -> f64 {
+        self.amount as f64
+    }
+
+    /// Set morph amount in 0...1.
+    pub fn set_amount(&mut self, amount: f64) {
+        self.amount = clamp01(amount) as f32;
+    }
+
+    /// Process one window.
+    pub fn process(&mut self, fft: &mut FftWindow) {
+        for i in 0..fft.bins() {
+            let (magnitude_a, phase_a) = fft.at(0, i).to_polar();
+            let (magnitude_b, phase_b) = fft.at(1, i).to_polar();
+            let magnitude = lerp(magnitude_a, magnitude_b, self.amount);
+            let phase = if self.morph_phase {
+                let mut delta = phase_b - phase_a;
+                if delta > PI as f32 {
+                    delta -= TAU as f32;
+                } else if delta < -PI as f32 {
+                    delta += TAU as f32;
+                }
+                phase_a + delta * self.amount
+            } else {
+                phase_a
+            };
+            let mut value = Complex32::from_polar(magnitude, phase);
+            // The inverse transform requires real DC and Nyquist bins.
+            if i == 0 || i == fft.bins() - 1 {
+                value.im = 0.0;
+            }
+            fft.set(0, i, value);
+        }
+    }
+}
@@ -2,6 +2,7 @@
 
 use super::hacker32::*;
 use funutd::dna::*;
+use funutd::Rnd;
 use realfft::*;
 
 /// Generate a reverb unit.
@@ -276,3 +277,158 @@ impl<T: Real, F: AudioNode<Sample = T, Inputs = U1, Outputs = U1>> AudioNode for
         Routing::Arbitrary(0.0).propagate(input, 2)
     }
 }
+
+/// Greatest common divisor.
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Diffuser: a cascade of Schroeder allpasses with randomized, mutually prime delay lengths.
+/// Delay times are spread over 30...100% of the time spread, one stage in each
+/// subrange in random order, and are rounded up to mutually prime lengths in samples.
+/// Setting: feedforward coefficient of the allpasses.
+/// - Input 0: input signal
+/// - Output 0: diffused signal
+#[derive(Clone)]
+pub struct Diffuser<T: Float> {
+    allpass: Vec<Schroeder<T>>,
+    /// Nominal delay times in seconds.
+    times: Vec<f64>,
+    coefficient: T,
+    sample_rate: f64,
+}
+
+impl<T: Float> Diffuser<T> {
+    /// Create diffuser with `stages` allpasses (`stages` > 0). Delay times are at most
+    /// `time_spread` seconds and are chosen pseudorandomly from `seed`.
+    /// - Allocates: the delay lines.
+    pub fn new(stages: usize, time_spread: f64, seed: u64) -> Self {
+        assert!(stages > 0);
+        let mut rnd = Rnd::from_u64(seed);
+        let mut times: Vec<f64> = (0..stages)
+            .map(|i| time_spread * lerp(0.3, 1.0, (i as f64 + rnd.f64()) / stages as f64))
+            .collect();
+        // Shuffle the stages so that delay times do not increase along the cascade.
+        for i in (1..stages).rev() {
+            let j = rnd.u64_in(0, i as u64) as usize;
+            times.swap(i, j);
+        }
+        let coefficient = T::from_f64(0.5);
+        let mut node = Self {
+            allpass: Vec::with_capacity(stages),
+            times,
+            coefficient,
+            sample_rate: 0.0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Delay lengths of the stages in samples at the current sample rate.
+    pub fn lengths(&self) -> Vec<usize> {
+        self.times
+            .iter()
+            .scan(Vec::new(), |lengths: &mut Vec<usize>, time| {
+                let mut length = max(2, round(time * self.sample_rate) as usize);
+                while lengths.iter().any(|&other| gcd(length, other) != 1) {
+                    length += 1;
+                }
+                lengths.push(length);
+                Some(length)
+            })
+            .collect()
+    }
+
+    /// Feedforward coefficient of the allpasses.
+    #[inline]
+    pub fn coefficient(&self) -> T {
+        self.coefficient
+    }
+
+    /// Set feedforward coefficient of the allpasses.
+    /// It should have an absolute value smaller than one to avoid a blowup.
+    pub fn set_coefficient(&mut self, coefficient: T) {
+        self.coefficient = coefficient;
+        for x in self.allpass.iter_mut() {
+            x.set_coefficient(coefficient);
+        }
+    }
+}
+
+impl<T: Float> AudioNode for Diffuser<T> {
+    const ID: u64 = 116;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_coefficient(setting);
+    }
+
+    fn reset(&mut self) {
+        for x in self.allpass.iter_mut() {
+            x.reset();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            // The allpass adds one sample of delay to its delay element.
+            self.allpass = self
+                .lengths()
+                .iter()
+                .map(|&length| {
+                    let mut delay = Delay::new((length - 1) as f64 / sample_rate);
+                    delay.set_sample_rate(sample_rate);
+                    Schroeder::new(self.coefficient, delay)
+                })
+                .collect();
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut value = input[0];
+        for x in self.allpass.iter_mut() {
+            value = x.filter_mono(value);
+        }
+        [value].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary(0.0).propagate(input, 1)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![ParameterInfo::new(
+            "coefficient",
+            ParameterUnit::Unitless,
+            -0.95,
+            0.95,
+            0.5,
+        )]
+    }
+
+    fn parameter_count(&self) -> usize {
+        1
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        assert!(index == 0);
+        self.coefficient.to_f64()
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        assert!(index == 0);
+        self.set_coefficient(T::from_f64(value));
+    }
+}
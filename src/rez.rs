@@ -16,7 +16,7 @@ pub struct Rez<T, F, N> {
     q: F,
     sample_rate: F,
     bandpass: F,
-    _marker: std::marker::PhantomData<(T, N)>,
+    _marker: core::marker::PhantomData<(T, N)>,
 }
 
 impl<T: Float, F: Real, N: Size<T>> Rez<T, F, N> {
@@ -31,7 +31,7 @@ impl<T: Float, F: Real, N: Size<T>> Rez<T, F, N> {
             q,
             sample_rate: convert(DEFAULT_SR),
             bandpass,
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
         };
         node.set_cutoff_q(cutoff, q);
         node
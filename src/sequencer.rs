@@ -5,14 +5,24 @@ use super::buffer::*;
 use super::math::*;
 use super::realseq::*;
 use super::signal::*;
+use super::wave::*;
 use super::*;
 use duplicate::duplicate_item;
 use std::cmp::{Eq, Ord, Ordering};
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicU32, AtomicU64};
+use std::sync::Arc;
 use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
 
+/// Halfway decay time in seconds of the event levels used to pick the quietest events
+/// when the sequencer is over budget.
+const LEVEL_DECAY_TIME: f64 = 0.1;
+
+/// Virtual events catch up at most this many times faster than real time,
+/// spreading the fast-forward over several blocks to bound the cost per block.
+const CATCH_UP_RATE: usize = 8;
+
 /// Fade curves.
 #[derive(Clone, Default)]
 pub enum Fade {
@@ -64,6 +74,21 @@ pub struct Event48 {
     pub fade_in: f48,
     pub fade_out: f48,
     pub id: EventId,
+    /// Priority of the event. When the sequencer is over budget,
+    /// events with the lowest priority are faded out first.
+    pub priority: i32,
+    /// Decaying peak level of the event, measured only when the sequencer has a budget.
+    level: f48,
+    /// Whether the event has been faded out early to stay within budget.
+    culled: bool,
+    /// Time when a culled event becomes virtual, or infinity if it is audible.
+    virtual_time: f48,
+    /// Time when a virtual event was resumed, or negative infinity.
+    resume_time: f48,
+    /// Number of samples the event has spent virtual.
+    virtual_samples: usize,
+    /// Time in seconds the unit is fast-forwarded by when it starts.
+    offset: f48,
 }
 
 #[duplicate_item(
@@ -88,8 +113,43 @@ impl Event48 {
             fade_in,
             fade_out,
             id: EventId::new(),
+            priority: 0,
+            level: 0.0,
+            culled: false,
+            virtual_time: f48::INFINITY,
+            resume_time: -f48::INFINITY,
+            virtual_samples: 0,
+            offset: 0.0,
         }
     }
+
+    /// Fast-forward a freshly reset unit by the start offset of the event.
+    fn skip_offset(&mut self, sample_rate: f48) {
+        if self.offset > 0.0 {
+            self.unit.skip(round(self.offset * sample_rate) as usize);
+        }
+    }
+
+    /// Whether the event is virtual at `time`, that is, it has been culled and keeps time silently.
+    fn is_virtual(&self, time: f48) -> bool {
+        self.virtual_time <= time
+    }
+
+    /// Make the event audible again from the start.
+    fn restore_voice(&mut self) {
+        self.level = 0.0;
+        self.culled = false;
+        self.virtual_time = f48::INFINITY;
+        self.resume_time = -f48::INFINITY;
+        self.virtual_samples = 0;
+    }
+
+    /// Set priority of the event. When the sequencer is over budget,
+    /// events with the lowest priority are faded out first.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 #[duplicate_item(
@@ -282,6 +342,38 @@ pub struct Sequencer48 {
     front: Option<(Sender<Message48>, Receiver<Option<Event48>>)>,
     /// Whether we replay existing events after a call to `reset`.
     replay_events: bool,
+    /// Optional seed for reseeding pushed units.
+    rng_seed: Option<u64>,
+    /// Number of units seeded so far.
+    rng_count: u64,
+    /// Number of events we have room for without allocating.
+    capacity: usize,
+    /// In a frontend, the number of events held by the backend.
+    backend_events: usize,
+    /// IDs of events that have finished playing, not yet retrieved.
+    finished: Vec<EventId>,
+    /// In a frontend, receives IDs of events that have finished playing in the backend.
+    finished_receiver: Option<Receiver<Option<EventId>>>,
+    /// In a frontend, the number of samples processed by the backend.
+    backend_time: Option<Arc<AtomicU64>>,
+    /// In a frontend, receives emptied batches from the backend for deallocation.
+    batch_receiver: Option<Receiver<Vec<Event48>>>,
+    /// Offset of the grid of quantized events in seconds.
+    grid_offset: f48,
+    /// Priority of events pushed from now on.
+    priority: i32,
+    /// Maximum number of sounding events.
+    voice_budget: usize,
+    /// Fade out time in seconds of events culled to stay within budget.
+    budget_fade: f48,
+    /// Maximum fraction of real time spent in `process`, if any.
+    cpu_budget: Option<f64>,
+    /// Maximum number of sounding events adapted to the CPU budget.
+    cpu_voices: usize,
+    /// Per-sample decay of event levels.
+    level_decay: f48,
+    /// Whether culled events become virtual instead of finishing.
+    virtual_voices: bool,
 }
 
 #[duplicate_item(
@@ -309,6 +401,22 @@ impl Clone for Sequencer48 {
             tick_buffer: self.tick_buffer.clone(),
             front: None,
             replay_events: self.replay_events,
+            rng_seed: self.rng_seed,
+            rng_count: self.rng_count,
+            capacity: self.capacity,
+            backend_events: 0,
+            finished: self.finished.clone(),
+            finished_receiver: None,
+            backend_time: None,
+            batch_receiver: None,
+            grid_offset: self.grid_offset,
+            priority: self.priority,
+            voice_budget: self.voice_budget,
+            budget_fade: self.budget_fade,
+            cpu_budget: self.cpu_budget,
+            cpu_voices: self.cpu_voices,
+            level_decay: self.level_decay,
+            virtual_voices: self.virtual_voices,
         }
     }
 }
@@ -325,14 +433,28 @@ impl Sequencer48 {
     /// If `replay_events` is true, then past events will be retained
     /// and played back after a reset.
     /// If false, then all events will be cleared on reset.
+    /// There is room for 16384 events (see `with_capacity`).
     pub fn new(replay_events: bool, outputs: usize) -> Self {
+        Self::with_capacity(replay_events, outputs, 16384)
+    }
+
+    /// Create a new sequencer with room for `capacity` events.
+    /// The sequencer has zero inputs. The number of outputs is decided by the user.
+    /// If `replay_events` is true, then past events will be retained
+    /// and played back after a reset.
+    /// If false, then all events will be cleared on reset.
+    ///
+    /// A backend (see `backend`) never allocates memory: it is given room for `capacity` events
+    /// and the frontend panics if it would have to hold more events than that.
+    /// Without replay, events are counted until they have finished and been returned to the frontend.
+    pub fn with_capacity(replay_events: bool, outputs: usize, capacity: usize) -> Self {
         Self {
-            active: Vec::with_capacity(16384),
-            active_map: HashMap::with_capacity(16384),
+            active: Vec::with_capacity(capacity),
+            active_map: HashMap::with_capacity(capacity),
             active_threshold: -f48::INFINITY,
-            ready: BinaryHeap::with_capacity(16384),
-            past: Vec::with_capacity(16384),
-            edit_map: HashMap::with_capacity(16384),
+            ready: BinaryHeap::with_capacity(capacity),
+            past: Vec::with_capacity(capacity),
+            edit_map: HashMap::with_capacity(capacity),
             outputs,
             time: 0.0,
             sample_rate: DEFAULT_SR as f48,
@@ -341,15 +463,81 @@ impl Sequencer48 {
             tick_buffer: vec![0.0; outputs],
             front: None,
             replay_events,
+            rng_seed: None,
+            rng_count: 0,
+            capacity,
+            backend_events: 0,
+            finished: Vec::with_capacity(capacity),
+            finished_receiver: None,
+            backend_time: None,
+            batch_receiver: None,
+            grid_offset: 0.0,
+            priority: 0,
+            voice_budget: usize::MAX,
+            budget_fade: 0.0,
+            cpu_budget: None,
+            cpu_voices: usize::MAX,
+            level_decay: pow(0.5, 1.0 / (LEVEL_DECAY_TIME * DEFAULT_SR)) as f48,
+            virtual_voices: false,
         }
     }
 
+    /// Number of events the sequencer has room for without allocating.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
     /// Current time in seconds.
     /// This method is not applicable to frontends, which do not process audio.
     pub fn time(&self) -> f48 {
         self.time
     }
 
+    /// Current time of the backend in seconds, as of the last block it processed.
+    /// Events pushed with absolute start times relative to this time
+    /// land where intended instead of at a guessed backend position.
+    /// If there is no backend, this is the same as `time`.
+    pub fn backend_time(&self) -> f48 {
+        match &self.backend_time {
+            Some(_) => (self.backend_samples() as f64 / self.sample_rate as f64) as f48,
+            None => self.time,
+        }
+    }
+
+    /// Number of samples processed by the backend since the last reset,
+    /// as of the last block it processed. After a sample rate change,
+    /// the count continues from the current time at the new rate.
+    /// If there is no backend, this is the current time in samples.
+    pub fn backend_samples(&self) -> u64 {
+        match &self.backend_time {
+            Some(samples) => samples.load(std::sync::atomic::Ordering::Relaxed),
+            None => round(self.time as f64 * self.sample_rate as f64) as u64,
+        }
+    }
+
+    /// Set the seed used to reseed pushed units. Each unit pushed from now on
+    /// gets a pseudorandom seed derived from `seed` and the order of pushes,
+    /// overriding its structural seed. Stochastic events are then reproducible
+    /// across runs and platforms; they are also reseeded on `reset`.
+    /// Units with an explicit seed, such as `white_seed`, keep their seed.
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.rng_seed = Some(seed);
+        self.rng_count = 0;
+    }
+
+    /// Current seed for reseeding pushed units, if any.
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
+    /// Reseed unit if we have a seed. This is an internal method.
+    fn seed_unit(&mut self, unit: &mut dyn AudioUnit48) {
+        if let Some(seed) = self.rng_seed {
+            unit.ping(false, AttoHash::new(seed).hash(self.rng_count));
+            self.rng_count += 1;
+        }
+    }
+
     /// Add an event. All times are specified in seconds.
     /// Fade in and fade out may overlap but may not exceed the duration of the event.
     /// Returns the ID of the event.
@@ -366,6 +554,7 @@ impl Sequencer48 {
         assert_eq!(unit.outputs(), self.outputs);
         let duration = end_time - start_time;
         assert!(fade_in_time <= duration && fade_out_time <= duration);
+        self.seed_unit(&mut *unit);
         // Make sure the sample rate of the unit matches ours.
         unit.set_sample_rate(self.sample_rate as f64);
         unit.allocate();
@@ -376,7 +565,48 @@ impl Sequencer48 {
             fade_ease,
             fade_in_time,
             fade_out_time,
-        );
+        )
+        .with_priority(self.priority);
+        let id = event.id;
+        self.push_event(event);
+        id
+    }
+
+    /// Add an event that plays from `offset` seconds into the unit, for example to join
+    /// an ambience that is already playing. The unit is fast-forwarded silently by `offset`
+    /// with `skip` when the event is pushed. All times are specified in seconds.
+    /// Fade in and fade out may overlap but may not exceed the duration of the event.
+    /// Returns the ID of the event.
+    pub fn push_from_offset(
+        &mut self,
+        start_time: f48,
+        end_time: f48,
+        offset: f48,
+        fade_ease: Fade,
+        fade_in_time: f48,
+        fade_out_time: f48,
+        mut unit: Box<dyn AudioUnit48>,
+    ) -> EventId {
+        assert_eq!(unit.inputs(), 0);
+        assert_eq!(unit.outputs(), self.outputs);
+        assert!(offset >= 0.0);
+        let duration = end_time - start_time;
+        assert!(fade_in_time <= duration && fade_out_time <= duration);
+        self.seed_unit(&mut *unit);
+        // Make sure the sample rate of the unit matches ours.
+        unit.set_sample_rate(self.sample_rate as f64);
+        unit.allocate();
+        let mut event = Event48::new(
+            unit,
+            start_time,
+            end_time,
+            fade_ease,
+            fade_in_time,
+            fade_out_time,
+        )
+        .with_priority(self.priority);
+        event.offset = offset;
+        event.skip_offset(self.sample_rate);
         let id = event.id;
         self.push_event(event);
         id
@@ -386,9 +616,19 @@ impl Sequencer48 {
     pub(crate) fn push_event(&mut self, event: Event48) {
         if let Some((sender, receiver)) = &mut self.front {
             // Deallocate all past events.
-            while receiver.try_recv().is_ok() {}
+            while let Ok(past) = receiver.try_recv() {
+                if past.is_some() {
+                    self.backend_events -= 1;
+                }
+            }
+            assert!(
+                self.backend_events < self.capacity,
+                "Sequencer backend is full: create the sequencer with a larger capacity."
+            );
             // Send the new event over.
-            if sender.try_send(Message48::Push(event)).is_ok() {}
+            if sender.try_send(Message48::Push(event)).is_ok() {
+                self.backend_events += 1;
+            }
         } else if event.start_time < self.active_threshold {
             self.active_map.insert(event.id, self.active.len());
             self.active.push(event);
@@ -414,6 +654,7 @@ impl Sequencer48 {
         assert!(unit.inputs() == 0 && unit.outputs() == self.outputs);
         let duration = end_time - start_time;
         assert!(fade_in_time <= duration && fade_out_time <= duration);
+        self.seed_unit(&mut *unit);
         // Make sure the sample rate of the unit matches ours.
         unit.set_sample_rate(self.sample_rate as f64);
         unit.allocate();
@@ -424,7 +665,8 @@ impl Sequencer48 {
             fade_ease,
             fade_in_time,
             fade_out_time,
-        );
+        )
+        .with_priority(self.priority);
         let id = event.id;
         self.push_relative_event(event);
         id
@@ -434,9 +676,19 @@ impl Sequencer48 {
     pub(crate) fn push_relative_event(&mut self, mut event: Event48) {
         if let Some((sender, receiver)) = &mut self.front {
             // Deallocate all past events.
-            while receiver.try_recv().is_ok() {}
+            while let Ok(past) = receiver.try_recv() {
+                if past.is_some() {
+                    self.backend_events -= 1;
+                }
+            }
+            assert!(
+                self.backend_events < self.capacity,
+                "Sequencer backend is full: create the sequencer with a larger capacity."
+            );
             // Send the new event over.
-            if sender.try_send(Message48::PushRelative(event)).is_ok() {}
+            if sender.try_send(Message48::PushRelative(event)).is_ok() {
+                self.backend_events += 1;
+            }
         } else {
             event.start_time += self.time;
             event.end_time += self.time;
@@ -449,6 +701,281 @@ impl Sequencer48 {
         }
     }
 
+    /// Add an event that starts at the next multiple of `grid` seconds (`grid` > 0) on the sequencer timeline
+    /// and lasts `duration` seconds. In a frontend, the start time is computed in the backend
+    /// from backend time, so the event is launched on the grid regardless of clock skew.
+    /// An event pushed exactly on a grid boundary starts immediately.
+    /// For a grid in beats, convert with `Transport::beats_to_seconds`.
+    /// The grid can be shifted with `set_grid_offset`.
+    /// Fade in and fade out may overlap but may not exceed the duration of the event.
+    /// Returns the ID of the event.
+    pub fn push_quantized(
+        &mut self,
+        grid: f48,
+        duration: f48,
+        fade_ease: Fade,
+        fade_in_time: f48,
+        fade_out_time: f48,
+        mut unit: Box<dyn AudioUnit48>,
+    ) -> EventId {
+        assert!(unit.inputs() == 0 && unit.outputs() == self.outputs);
+        assert!(grid > 0.0);
+        assert!(fade_in_time <= duration && fade_out_time <= duration);
+        self.seed_unit(&mut *unit);
+        // Make sure the sample rate of the unit matches ours.
+        unit.set_sample_rate(self.sample_rate as f64);
+        unit.allocate();
+        let event = Event48::new(unit, 0.0, duration, fade_ease, fade_in_time, fade_out_time)
+            .with_priority(self.priority);
+        let id = event.id;
+        self.push_quantized_event(grid, event);
+        id
+    }
+
+    /// Add quantized event. This is an internal method.
+    pub(crate) fn push_quantized_event(&mut self, grid: f48, mut event: Event48) {
+        if let Some((sender, receiver)) = &mut self.front {
+            // Deallocate all past events.
+            while let Ok(past) = receiver.try_recv() {
+                if past.is_some() {
+                    self.backend_events -= 1;
+                }
+            }
+            assert!(
+                self.backend_events < self.capacity,
+                "Sequencer backend is full: create the sequencer with a larger capacity."
+            );
+            // Send the new event over.
+            if sender
+                .try_send(Message48::PushQuantized(grid, event))
+                .is_ok()
+            {
+                self.backend_events += 1;
+            }
+        } else {
+            // Snap to the next grid boundary, allowing for rounding of the current time.
+            let phase = self.time - self.grid_offset - 0.5 * self.sample_duration;
+            let start_time = ceil(phase / grid) * grid + self.grid_offset;
+            event.start_time += start_time;
+            event.end_time += start_time;
+            if event.start_time < self.active_threshold {
+                self.active_map.insert(event.id, self.active.len());
+                self.active.push(event);
+            } else {
+                self.ready.push(event);
+            }
+        }
+    }
+
+    /// Offset of the grid of quantized events in seconds.
+    pub fn grid_offset(&self) -> f48 {
+        self.grid_offset
+    }
+
+    /// Set offset of the grid of quantized events in seconds: quantized events
+    /// start at `offset` plus a multiple of the grid. The default offset is zero.
+    /// The offset aligns the grid with an external clock (see `LinkSync`).
+    /// In a frontend, the offset is sent to the backend.
+    pub fn set_grid_offset(&mut self, offset: f48) {
+        self.grid_offset = offset;
+        if let Some((sender, _)) = &self.front {
+            if sender.try_send(Message48::SetGridOffset(offset)).is_ok() {}
+        }
+    }
+
+    /// Priority of events pushed from now on.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Set priority of events pushed from now on. The default priority is zero.
+    /// When the sequencer is over budget, events with the lowest priority are faded out first,
+    /// and the quietest of them go first. Events in a batch keep their own priorities.
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    /// Limit the number of sounding events to `voices`. When more events are sounding,
+    /// the lowest priority and quietest events are faded out early over `fade_out_time` seconds.
+    /// Culled events finish as usual. There is no limit by default.
+    /// In a frontend, the budget is sent to the backend.
+    pub fn set_voice_budget(&mut self, voices: usize, fade_out_time: f48) {
+        self.voice_budget = voices;
+        self.budget_fade = fade_out_time;
+        if let Some((sender, _)) = &self.front {
+            if sender
+                .try_send(Message48::SetVoiceBudget(voices, fade_out_time))
+                .is_ok()
+            {}
+        }
+    }
+
+    /// Limit the time spent in `process` to a fraction `load` of real time, if any.
+    /// When a block takes longer, the number of sounding events is reduced by one
+    /// as with `set_voice_budget`, and when a block takes less than half the budget,
+    /// the limit is raised again by one. Processing time is measured in the backend
+    /// if there is one. Culled events fade out over the time set in `set_voice_budget`.
+    pub fn set_cpu_budget(&mut self, load: Option<f64>) {
+        self.cpu_budget = load;
+        self.cpu_voices = usize::MAX;
+        if let Some((sender, _)) = &self.front {
+            if sender.try_send(Message48::SetCpuBudget(load)).is_ok() {}
+        }
+    }
+
+    /// Enable or disable virtual voices. With virtual voices, events culled to stay within budget
+    /// fade out but keep their place on the timeline silently without being processed.
+    /// When the budget frees up, the highest priority, loudest virtual events are fast-forwarded
+    /// to the current time and faded back in. The fast-forward is spread over several blocks
+    /// at up to 8 times real time. Virtual events finish at their end time as usual.
+    /// Virtual voices are disabled by default. In a frontend, the setting is sent to the backend.
+    pub fn set_virtual_voices(&mut self, enabled: bool) {
+        self.virtual_voices = enabled;
+        if let Some((sender, _)) = &self.front {
+            if sender
+                .try_send(Message48::SetVirtualVoices(enabled))
+                .is_ok()
+            {}
+        }
+    }
+
+    /// Number of sounding events that have not been culled.
+    fn voices(&self) -> usize {
+        self.active.iter().filter(|event| !event.culled).count()
+    }
+
+    /// Whether we measure levels and cull events.
+    #[inline]
+    fn has_budget(&self) -> bool {
+        self.voice_budget < usize::MAX || self.cpu_budget.is_some()
+    }
+
+    /// Fade out the lowest priority, quietest events until we are within budget.
+    /// This is done after rendering so new events are measured before they are compared.
+    fn enforce_budget(&mut self) {
+        let limit = min(self.voice_budget, self.cpu_voices);
+        if self.active.len() <= limit {
+            return;
+        }
+        let mut voices = self.voices();
+        while voices > limit {
+            let mut victim: Option<usize> = None;
+            for (i, event) in self.active.iter().enumerate() {
+                if event.culled {
+                    continue;
+                }
+                victim = match victim {
+                    Some(j)
+                        if self.active[j].priority < event.priority
+                            || (self.active[j].priority == event.priority
+                                && self.active[j].level <= event.level) =>
+                    {
+                        Some(j)
+                    }
+                    _ => Some(i),
+                };
+            }
+            let Some(i) = victim else {
+                break;
+            };
+            let end_time = self.time + self.budget_fade;
+            let event = &mut self.active[i];
+            event.culled = true;
+            if self.virtual_voices {
+                event.virtual_time = end_time;
+                event.resume_time = -f48::INFINITY;
+            } else if end_time < event.end_time {
+                event.end_time = end_time;
+                event.fade_out = self.budget_fade;
+            }
+            voices -= 1;
+        }
+    }
+
+    /// Resume the highest priority, loudest virtual events while we are within budget.
+    /// Catch-up is limited to `CATCH_UP_RATE` times the `samples` just processed;
+    /// an event that is still behind stays virtual and continues catching up in the next call.
+    fn resume_virtual(&mut self, samples: usize) {
+        let limit = min(self.voice_budget, self.cpu_voices);
+        let mut voices = self.voices();
+        let mut catch_up = CATCH_UP_RATE * samples;
+        while voices < limit {
+            let mut candidate: Option<usize> = None;
+            for (i, event) in self.active.iter().enumerate() {
+                if !event.is_virtual(self.time) {
+                    continue;
+                }
+                candidate = match candidate {
+                    Some(j)
+                        if self.active[j].priority > event.priority
+                            || (self.active[j].priority == event.priority
+                                && self.active[j].level >= event.level) =>
+                    {
+                        Some(j)
+                    }
+                    _ => Some(i),
+                };
+            }
+            let Some(i) = candidate else {
+                break;
+            };
+            // Fast-forward the unit over the time it spent virtual.
+            let event = &mut self.active[i];
+            let length = min(event.virtual_samples, catch_up);
+            event.unit.skip(length);
+            event.virtual_samples -= length;
+            catch_up -= length;
+            if event.virtual_samples > 0 {
+                break;
+            }
+            event.culled = false;
+            event.virtual_time = f48::INFINITY;
+            event.resume_time = self.time;
+            event.virtual_samples = 0;
+            voices += 1;
+        }
+    }
+
+    /// Add a batch of events in absolute time. In a frontend, the batch is sent to the backend
+    /// as a single message, so events that start at the same time, such as the notes
+    /// of a chord, become active in the same block. Batches are not real-time safe in a frontend.
+    /// Fade in and fade out may overlap but may not exceed the duration of each event.
+    pub fn push_batch(&mut self, mut events: Vec<Event48>) {
+        for event in events.iter_mut() {
+            assert!(event.unit.inputs() == 0 && event.unit.outputs() == self.outputs);
+            let duration = event.end_time - event.start_time;
+            assert!(event.fade_in <= duration && event.fade_out <= duration);
+            self.seed_unit(&mut *event.unit);
+            // Make sure the sample rate of the unit matches ours.
+            event.unit.set_sample_rate(self.sample_rate as f64);
+            event.unit.allocate();
+        }
+        if let Some((sender, receiver)) = &mut self.front {
+            // Deallocate all past events and batches.
+            while let Ok(past) = receiver.try_recv() {
+                if past.is_some() {
+                    self.backend_events -= 1;
+                }
+            }
+            if let Some(batches) = &self.batch_receiver {
+                while batches.try_recv().is_ok() {}
+            }
+            assert!(
+                self.backend_events + events.len() <= self.capacity,
+                "Sequencer backend is full: create the sequencer with a larger capacity."
+            );
+            let length = events.len();
+            // Send the batch over.
+            if sender.try_send(Message48::PushBatch(events)).is_ok() {
+                self.backend_events += length;
+            }
+        } else {
+            for event in events {
+                self.push_event(event);
+            }
+        }
+    }
+
     /// Add an event using start time and duration.
     /// Fade in and fade out may overlap but may not exceed the duration of the event.
     /// Returns the ID of the event.
@@ -479,7 +1006,11 @@ impl Sequencer48 {
     pub fn edit(&mut self, id: EventId, end_time: f48, fade_out_time: f48) {
         if let Some((sender, receiver)) = &mut self.front {
             // Deallocate all past events.
-            while receiver.try_recv().is_ok() {}
+            while let Ok(past) = receiver.try_recv() {
+                if past.is_some() {
+                    self.backend_events -= 1;
+                }
+            }
             // Send the new edit over.
             if sender
                 .try_send(Message48::Edit(
@@ -520,7 +1051,11 @@ impl Sequencer48 {
     pub fn edit_relative(&mut self, id: EventId, end_time: f48, fade_out_time: f48) {
         if let Some((sender, receiver)) = &mut self.front {
             // Deallocate all past events.
-            while receiver.try_recv().is_ok() {}
+            while let Ok(past) = receiver.try_recv() {
+                if past.is_some() {
+                    self.backend_events -= 1;
+                }
+            }
             // Send the new edit over.
             if sender
                 .try_send(Message48::EditRelative(
@@ -583,13 +1118,52 @@ impl Sequencer48 {
     /// This can be called only once for a sequencer.
     pub fn backend(&mut self) -> SequencerBackend48 {
         assert!(!self.has_backend());
-        // Create huge channel buffers to make sure we don't run out of space easily.
+        // The backend starts with our current events.
+        self.backend_events = self.active.len() + self.ready.len() + self.past.len();
+        // The return channel has room for every event the backend can hold,
+        // so the backend never has to drop events on the audio thread.
+        // Each batch takes up a message slot before it is returned,
+        // so the batch channel needs as much room as the message channel.
+        let events = max(1, max(self.capacity, self.backend_events));
         let (sender_a, receiver_a) = channel(16384);
-        let (sender_b, receiver_b) = channel(16384);
+        let (sender_b, receiver_b) = channel(events);
+        let (sender_c, receiver_c) = channel(max(1, self.capacity));
+        let (sender_d, receiver_d) = channel(16384);
         let mut sequencer = self.clone();
         sequencer.allocate();
+        let time = Arc::new(AtomicU64::new(self.backend_samples()));
         self.front = Some((sender_a, receiver_b));
+        self.finished_receiver = Some(receiver_c);
+        self.finished.clear();
+        self.backend_time = Some(time.clone());
+        self.batch_receiver = Some(receiver_d);
         SequencerBackend48::new(sender_b, receiver_a, sequencer)
+            .with_finished(sender_c)
+            .with_time(time)
+            .with_batches(sender_d)
+    }
+
+    /// Retrieve IDs of events that have finished playing since the last call, in order of finishing.
+    /// In a frontend, the IDs are those of events that have finished in the backend.
+    /// Up to `capacity` IDs are retained between calls; any further IDs are dropped.
+    /// This method is not real-time safe in a frontend.
+    pub fn finished_events(&mut self) -> impl Iterator<Item = EventId> + '_ {
+        let receiver = self.finished_receiver.as_ref();
+        let mut finished = self.finished.drain(..);
+        core::iter::from_fn(move || match receiver {
+            Some(receiver) => receiver.try_recv().ok().flatten(),
+            None => finished.next(),
+        })
+    }
+
+    /// Take finished event IDs in order of finishing. This is an internal method.
+    pub(crate) fn drain_finished(&mut self) -> std::vec::Drain<'_, EventId> {
+        self.finished.drain(..)
+    }
+
+    /// Current sample rate of the sequencer in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate as f64
     }
 
     /// Returns whether this sequencer has a backend.
@@ -622,11 +1196,102 @@ impl Sequencer48 {
     }
 }
 
+#[duplicate_item(
+    f48       Event48       Sequencer48       Wave48;
+    [ f64 ]   [ Event64 ]   [ Sequencer64 ]   [ Wave64 ];
+    [ f32 ]   [ Event32 ]   [ Sequencer32 ]   [ Wave32 ];
+)]
+impl Sequencer48 {
+    /// Clone all events held by the sequencer, sorted by start time. This is an internal method.
+    fn clone_events(&self) -> Vec<Event48> {
+        let mut events: Vec<Event48> = self
+            .active
+            .iter()
+            .chain(self.ready.iter())
+            .chain(self.past.iter())
+            .cloned()
+            .collect();
+        for event in events.iter_mut() {
+            // Apply pending edits.
+            if let Some(edit) = self.edit_map.get(&event.id) {
+                event.end_time = edit.end_time;
+                event.fade_out = edit.fade_out;
+            }
+        }
+        events.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+        events
+    }
+
+    /// Render `events` from time zero. This is an internal method.
+    fn render_events(
+        &self,
+        sample_rate: f64,
+        duration: f64,
+        events: impl Iterator<Item = Event48>,
+    ) -> Wave48 {
+        let mut sequencer = Sequencer48::new(true, self.outputs);
+        for event in events {
+            sequencer.push_event(event);
+        }
+        sequencer.reset();
+        Wave48::render(sample_rate, duration, &mut sequencer)
+    }
+
+    /// Render the mix and a separate stem for each event, `duration` seconds from time zero
+    /// at `sample_rate` Hz. All events held by the sequencer are rendered, including past events
+    /// if we replay events. The sequencer itself is left untouched.
+    /// Returns the mix and pairs of event ID and stem, in order of start time.
+    /// The stems sum to the mix. Frontends cannot render stems.
+    pub fn render_stems(&self, sample_rate: f64, duration: f64) -> (Wave48, Vec<(EventId, Wave48)>) {
+        assert!(!self.has_backend());
+        let events = self.clone_events();
+        let mix = self.render_events(sample_rate, duration, events.iter().cloned());
+        let stems = events
+            .into_iter()
+            .map(|event| {
+                let id = event.id;
+                (
+                    id,
+                    self.render_events(sample_rate, duration, core::iter::once(event)),
+                )
+            })
+            .collect();
+        (mix, stems)
+    }
+
+    /// Render the mix and a separate stem for each of `groups` groups of events,
+    /// `duration` seconds from time zero at `sample_rate` Hz.
+    /// Function `group` maps event IDs to groups. Events mapped to `groups` or above are
+    /// rendered only in the mix. The sequencer itself is left untouched.
+    /// Returns the mix and the stems in group order. Frontends cannot render stems.
+    pub fn render_groups(
+        &self,
+        sample_rate: f64,
+        duration: f64,
+        groups: usize,
+        group: impl Fn(EventId) -> usize,
+    ) -> (Wave48, Vec<Wave48>) {
+        assert!(!self.has_backend());
+        let events = self.clone_events();
+        let mix = self.render_events(sample_rate, duration, events.iter().cloned());
+        let stems = (0..groups)
+            .map(|i| {
+                self.render_events(
+                    sample_rate,
+                    duration,
+                    events.iter().filter(|event| group(event.id) == i).cloned(),
+                )
+            })
+            .collect();
+        (mix, stems)
+    }
+}
+
 #[allow(clippy::unnecessary_cast)]
 #[duplicate_item(
-    f48       Event48       AudioUnit48       Sequencer48      fade_in48      fade_out48;
-    [ f64 ]   [ Event64 ]   [ AudioUnit64 ]   [ Sequencer64 ]  [ fade_in64 ]  [ fade_out64 ];
-    [ f32 ]   [ Event32 ]   [ AudioUnit32 ]   [ Sequencer32 ]  [ fade_in32 ]  [ fade_out32 ];
+    f48       Event48       AudioUnit48       Sequencer48      Message48      fade_in48      fade_out48;
+    [ f64 ]   [ Event64 ]   [ AudioUnit64 ]   [ Sequencer64 ]  [ Message64 ]  [ fade_in64 ]  [ fade_out64 ];
+    [ f32 ]   [ Event32 ]   [ AudioUnit32 ]   [ Sequencer32 ]  [ Message32 ]  [ fade_in32 ]  [ fade_out32 ];
 )]
 impl AudioUnit48 for Sequencer48 {
     fn reset(&mut self) {
@@ -639,6 +1304,8 @@ impl AudioUnit48 for Sequencer48 {
             }
             for i in 0..self.active.len() {
                 self.active[i].unit.reset();
+                self.active[i].skip_offset(self.sample_rate);
+                self.active[i].restore_voice();
             }
             while let Some(active) = self.active.pop() {
                 self.ready.push(active);
@@ -651,15 +1318,25 @@ impl AudioUnit48 for Sequencer48 {
             self.edit_map.clear();
             self.active_map.clear();
         }
+        self.finished.clear();
         self.time = 0.0;
         self.active_threshold = -f48::INFINITY;
+        self.cpu_voices = usize::MAX;
     }
 
     fn set_sample_rate(&mut self, sample_rate: f64) {
+        if let Some((sender, _)) = &self.front {
+            // Re-rate the backend with a short fade.
+            if sender
+                .try_send(Message48::SetSampleRate(sample_rate))
+                .is_ok()
+            {}
+        }
         let sample_rate = sample_rate as f48;
         if self.sample_rate != sample_rate {
             self.sample_rate = sample_rate;
             self.sample_duration = 1.0 / sample_rate;
+            self.level_decay = pow(0.5, 1.0 / (LEVEL_DECAY_TIME * sample_rate as f64)) as f48;
             // Move everything to the active queue, then set sample rate and move
             // everything to the ready heap.
             while let Some(ready) = self.ready.pop() {
@@ -670,6 +1347,7 @@ impl AudioUnit48 for Sequencer48 {
             }
             for i in 0..self.active.len() {
                 self.active[i].unit.set_sample_rate(sample_rate as f64);
+                self.active[i].skip_offset(sample_rate);
             }
             while let Some(active) = self.active.pop() {
                 self.ready.push(active);
@@ -689,6 +1367,7 @@ impl AudioUnit48 for Sequencer48 {
         }
         let end_time = self.time + self.sample_duration;
         self.ready_to_active(end_time);
+        let has_budget = self.has_budget();
         let mut i = 0;
         while i < self.active.len() {
             if self.active[i].end_time <= self.time + 0.5 * self.sample_duration {
@@ -697,7 +1376,13 @@ impl AudioUnit48 for Sequencer48 {
                     self.active_map
                         .insert(self.active[self.active.len() - 1].id, i);
                 }
+                if self.finished.len() < self.capacity {
+                    self.finished.push(self.active[i].id);
+                }
                 self.past.push(self.active.swap_remove(i));
+            } else if self.active[i].is_virtual(self.time + 0.5 * self.sample_duration) {
+                self.active[i].virtual_samples += 1;
+                i += 1;
             } else {
                 self.active[i].unit.tick(input, &mut self.tick_buffer);
                 if self.active[i].fade_in > 0.0 {
@@ -742,13 +1427,55 @@ impl AudioUnit48 for Sequencer48 {
                         }
                     }
                 }
+                if self.budget_fade > 0.0
+                    && (self.active[i].virtual_time < f48::INFINITY
+                        || self.active[i].resume_time > -f48::INFINITY)
+                {
+                    // Fade culled events out and resumed events back in.
+                    let fade = if self.active[i].virtual_time < f48::INFINITY {
+                        1.0 - delerp(
+                            self.active[i].virtual_time - self.budget_fade,
+                            self.active[i].virtual_time,
+                            self.time,
+                        )
+                    } else {
+                        delerp(
+                            self.active[i].resume_time,
+                            self.active[i].resume_time + self.budget_fade,
+                            self.time,
+                        )
+                    };
+                    if fade < 1.0 {
+                        let gain = match self.active[i].fade_ease {
+                            Fade::Power => sine_ease(clamp01(fade)),
+                            Fade::Smooth => smooth5(clamp01(fade)),
+                        };
+                        for channel in 0..self.outputs {
+                            self.tick_buffer[channel] *= gain;
+                        }
+                    }
+                }
                 for channel in 0..self.outputs {
                     output[channel] += self.tick_buffer[channel];
                 }
+                if has_budget {
+                    let peak = self
+                        .tick_buffer
+                        .iter()
+                        .fold(0.0, |peak, x| max(peak, abs(*x)));
+                    let level = self.active[i].level * self.level_decay;
+                    self.active[i].level = max(level, peak);
+                }
                 i += 1;
             }
         }
         self.time = end_time;
+        if has_budget {
+            self.enforce_budget();
+            if self.virtual_voices {
+                self.resume_virtual(1);
+            }
+        }
     }
 
     fn process(&mut self, size: usize, input: &[&[f48]], output: &mut [&mut [f48]]) {
@@ -760,6 +1487,8 @@ impl AudioUnit48 for Sequencer48 {
         }
         let end_time = self.time + self.sample_duration * size as f48;
         self.ready_to_active(end_time);
+        let has_budget = self.has_budget();
+        let start = self.cpu_budget.map(|_| std::time::Instant::now());
         let buffer_output = self.buffer.get_mut(self.outputs);
         let mut i = 0;
         while i < self.active.len() {
@@ -769,6 +1498,9 @@ impl AudioUnit48 for Sequencer48 {
                     self.active_map
                         .insert(self.active[self.active.len() - 1].id, i);
                 }
+                if self.finished.len() < self.capacity {
+                    self.finished.push(self.active[i].id);
+                }
                 self.past.push(self.active.swap_remove(i));
             } else {
                 let start_index = if self.active[i].start_time <= self.time {
@@ -781,7 +1513,11 @@ impl AudioUnit48 for Sequencer48 {
                 } else {
                     round((self.active[i].end_time - self.time) * self.sample_rate) as usize
                 };
-                if end_index > start_index {
+                if end_index > start_index
+                    && self.active[i].is_virtual(self.time + 0.5 * self.sample_duration)
+                {
+                    self.active[i].virtual_samples += end_index - start_index;
+                } else if end_index > start_index {
                     self.active[i]
                         .unit
                         .process(end_index - start_index, input, buffer_output);
@@ -807,16 +1543,75 @@ impl AudioUnit48 for Sequencer48 {
                         self.active[i].end_time,
                         buffer_output,
                     );
+                    // Fade culled events out and resumed events back in.
+                    let mut mix_end_index = end_index;
+                    if self.active[i].virtual_time < end_time {
+                        mix_end_index = clamp(
+                            start_index as f48,
+                            end_index as f48,
+                            round((self.active[i].virtual_time - self.time) * self.sample_rate),
+                        ) as usize;
+                    }
+                    if self.active[i].virtual_time < f48::INFINITY {
+                        fade_out48(
+                            self.sample_duration,
+                            self.time,
+                            end_time,
+                            start_index,
+                            mix_end_index,
+                            self.active[i].fade_ease.clone(),
+                            self.budget_fade,
+                            self.active[i].virtual_time,
+                            buffer_output,
+                        );
+                    } else if self.active[i].resume_time > -f48::INFINITY {
+                        fade_in48(
+                            self.sample_duration,
+                            self.time,
+                            end_time,
+                            start_index,
+                            end_index,
+                            self.active[i].fade_ease.clone(),
+                            self.budget_fade,
+                            self.active[i].resume_time,
+                            buffer_output,
+                        );
+                    }
                     for channel in 0..self.outputs {
-                        for j in start_index..end_index {
+                        for j in start_index..mix_end_index {
                             output[channel][j] += buffer_output[channel][j - start_index];
                         }
                     }
+                    if has_budget {
+                        let length = end_index - start_index;
+                        let audible = mix_end_index - start_index;
+                        let peak = buffer_output.iter().fold(0.0, |peak, channel| {
+                            channel[..audible]
+                                .iter()
+                                .fold(peak, |peak, x| max(peak, abs(*x)))
+                        });
+                        let level = self.active[i].level * pow(self.level_decay, length as f48);
+                        self.active[i].level = max(level, peak);
+                    }
                 }
                 i += 1;
             }
         }
+        if let (Some(budget), Some(start)) = (self.cpu_budget, start) {
+            let load = start.elapsed().as_secs_f64() * self.sample_rate as f64 / size as f64;
+            if load > budget {
+                self.cpu_voices = max(1, self.voices().saturating_sub(1));
+            } else if load < 0.5 * budget {
+                self.cpu_voices = self.cpu_voices.saturating_add(1);
+            }
+        }
         self.time = end_time;
+        if has_budget {
+            self.enforce_budget();
+            if self.virtual_voices {
+                self.resume_virtual(size);
+            }
+        }
     }
 
     fn get_id(&self) -> u64 {
@@ -843,4 +1638,29 @@ impl AudioUnit48 for Sequencer48 {
     fn footprint(&self) -> usize {
         std::mem::size_of::<Self>()
     }
+
+    fn allocate(&mut self) {
+        // Make room for `capacity` events in all containers.
+        self.active
+            .reserve(self.capacity.saturating_sub(self.active.len()));
+        self.active_map
+            .reserve(self.capacity.saturating_sub(self.active_map.len()));
+        self.past
+            .reserve(self.capacity.saturating_sub(self.past.len()));
+        self.finished
+            .reserve(self.capacity.saturating_sub(self.finished.len()));
+        self.edit_map
+            .reserve(self.capacity.saturating_sub(self.edit_map.len()));
+        self.buffer.allocate(self.outputs);
+        for event in self.active.iter_mut().chain(self.past.iter_mut()) {
+            event.unit.allocate();
+        }
+        let mut ready = core::mem::take(&mut self.ready).into_vec();
+        for event in ready.iter_mut() {
+            event.unit.allocate();
+        }
+        self.ready = BinaryHeap::from(ready);
+        self.ready
+            .reserve(self.capacity.saturating_sub(self.ready.len()));
+    }
 }
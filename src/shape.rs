@@ -5,7 +5,7 @@ use super::math::*;
 use super::signal::*;
 use super::*;
 use numeric_array::typenum::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Waveshaper from a closure.
 #[derive(Clone)]
@@ -91,6 +91,153 @@ pub enum Shape<T: Real> {
     /// The argument to `tanh` is divided by the RMS level of the signal and multiplied by hardness.
     /// Minimum estimated signal level for adaptive distortion is approximately -60 dB.
     AdaptiveTanh(T, T),
+    /// Cubic soft clipping with configurable hardness.
+    /// The input is multiplied by the hardness value and the curve saturates at -1 and 1.
+    SoftClip(T),
+    /// Triangle wavefolding with configurable hardness.
+    /// The input is multiplied by the hardness value and reflected back into the range -1...1.
+    Fold(T),
+    /// Asymmetric tube style saturation with configurable hardness.
+    /// The input is multiplied by the hardness value. Positive values saturate earlier
+    /// than negative values, which produces even harmonics and a DC offset.
+    Tube(T),
+}
+
+/// Cubic soft clipper. Saturates at -1 and 1.
+#[inline]
+fn soft_clip<T: Real>(x: T) -> T {
+    let x = clamp11(x);
+    x * (T::from_f64(1.5) - T::from_f64(0.5) * x * x)
+}
+
+/// Triangle wavefolder. Reflects `x` back into the range -1...1.
+#[inline]
+fn fold<T: Real>(x: T) -> T {
+    let x = (x + T::one()) * T::from_f64(0.25);
+    let x = x - floor(x);
+    T::one() - abs(T::from_f64(4.0) * x - T::from_f64(2.0))
+}
+
+/// Asymmetric tube style saturator. Maps zero to zero.
+#[inline]
+fn tube<T: Real>(x: T) -> T {
+    let bias = T::from_f64(0.25);
+    shape_tanh(x + bias) - shape_tanh(bias)
+}
+
+/// Analog-inspired saturation models for `Saturator`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SaturationModel {
+    /// Gentle symmetric tape style saturation that produces odd harmonics.
+    #[default]
+    Tape,
+    /// Asymmetric tube style saturation that produces even harmonics.
+    Tube,
+    /// Diode clipper with a sharp knee.
+    Diode,
+}
+
+/// Reference amplitude for automatic gain compensation in `Saturator`.
+const SATURATION_REFERENCE: f64 = 0.25;
+
+/// Apply saturation curve `model` to `x`.
+#[inline]
+fn saturate_curve<T: Real>(model: SaturationModel, x: T) -> T {
+    match model {
+        SaturationModel::Tape => x / sqrt(T::one() + x * x),
+        SaturationModel::Tube => tube(x),
+        SaturationModel::Diode => {
+            let x2 = x * x;
+            x / sqrt(sqrt(T::one() + x2 * x2))
+        }
+    }
+}
+
+/// Saturator with drive and automatic gain compensation.
+/// Gain is compensated so that signals at an amplitude of 0.25
+/// keep their level regardless of drive.
+/// Setting: drive.
+/// - Input 0: input signal
+/// - Output 0: saturated signal
+#[derive(Clone)]
+pub struct Saturator<T: Real> {
+    model: SaturationModel,
+    drive: T,
+    /// Gain compensation factor.
+    trim: T,
+}
+
+impl<T: Real> Saturator<T> {
+    /// Create saturator with `model` and input gain `drive` (`drive` > 0).
+    pub fn new(model: SaturationModel, drive: T) -> Self {
+        let mut node = Self {
+            model,
+            drive,
+            trim: T::one(),
+        };
+        node.set_drive(drive);
+        node
+    }
+
+    /// Saturation model.
+    #[inline]
+    pub fn model(&self) -> SaturationModel {
+        self.model
+    }
+
+    /// Input gain.
+    #[inline]
+    pub fn drive(&self) -> T {
+        self.drive
+    }
+
+    /// Set input gain (`drive` > 0). Output gain is compensated automatically.
+    #[inline]
+    pub fn set_drive(&mut self, drive: T) {
+        self.drive = drive;
+        let reference = T::from_f64(SATURATION_REFERENCE);
+        let level = (saturate_curve(self.model, drive * reference)
+            - saturate_curve(self.model, -drive * reference))
+            * T::from_f64(0.5);
+        self.trim = reference / level;
+    }
+}
+
+impl<T: Real> AudioNode for Saturator<T> {
+    const ID: u64 = 139;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_drive(setting);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [saturate_curve(self.model, input[0] * self.drive) * self.trim].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for (x, y) in output[0][0..size].iter_mut().zip(input[0][0..size].iter()) {
+            *x = saturate_curve(self.model, *y * self.drive) * self.trim;
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
 }
 
 /// Waveshaper with various shaping modes.
@@ -140,7 +287,7 @@ impl<T: Real> AudioNode for Shaper<T> {
         match self.shape {
             Shape::Clip => [clamp11(input)].into(),
             Shape::ClipTo(min, max) => [clamp(min, max, input)].into(),
-            Shape::Tanh(hardness) => [tanh(input * hardness)].into(),
+            Shape::Tanh(hardness) => [shape_tanh(input * hardness)].into(),
             Shape::Atan(hardness) => [atan(input * hardness)].into(),
             Shape::Softsign(hardness) => [softsign(input * hardness)].into(),
             Shape::Crush(levels) => [round(input * levels) / levels].into(),
@@ -152,8 +299,11 @@ impl<T: Real> AudioNode for Shaper<T> {
             Shape::AdaptiveTanh(_timescale, hardness) => {
                 self.state = self.smoothing * self.state
                     + (T::one() - self.smoothing) * (T::from_f32(1.0e-6) + squared(input));
-                [tanh(input * hardness / sqrt(self.state))].into()
+                [shape_tanh(input * hardness / sqrt(self.state))].into()
             }
+            Shape::SoftClip(hardness) => [soft_clip(input * hardness)].into(),
+            Shape::Fold(hardness) => [fold(input * hardness)].into(),
+            Shape::Tube(hardness) => [tube(input * hardness)].into(),
         }
     }
 
@@ -178,7 +328,7 @@ impl<T: Real> AudioNode for Shaper<T> {
             }
             Shape::Tanh(hardness) => {
                 for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
-                    *x = tanh(*y * hardness);
+                    *x = shape_tanh(*y * hardness);
                 }
             }
             Shape::Atan(hardness) => {
@@ -207,7 +357,22 @@ impl<T: Real> AudioNode for Shaper<T> {
                 for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
                     self.state = self.smoothing * self.state
                         + (T::one() - self.smoothing) * (T::from_f32(1.0e-6) + squared(*y));
-                    *x = tanh(*y * hardness / sqrt(self.state));
+                    *x = shape_tanh(*y * hardness / sqrt(self.state));
+                }
+            }
+            Shape::SoftClip(hardness) => {
+                for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
+                    *x = soft_clip(*y * hardness);
+                }
+            }
+            Shape::Fold(hardness) => {
+                for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
+                    *x = fold(*y * hardness);
+                }
+            }
+            Shape::Tube(hardness) => {
+                for (x, y) in output[0..size].iter_mut().zip(input[0..size].iter()) {
+                    *x = tube(*y * hardness);
                 }
             }
         }
@@ -219,3 +384,152 @@ impl<T: Real> AudioNode for Shaper<T> {
         output
     }
 }
+
+/// Waveshaper from a lookup table.
+/// The table spans input values -1...1 and is linearly interpolated.
+/// Input values outside the range are clamped.
+#[derive(Clone)]
+pub struct ShaperTable<T: Float> {
+    table: Vec<T>,
+}
+
+impl<T: Float> ShaperTable<T> {
+    /// Create waveshaper from `table`, which must contain at least two values.
+    pub fn new(table: &[T]) -> Self {
+        assert!(table.len() >= 2);
+        Self {
+            table: table.to_vec(),
+        }
+    }
+
+    /// Look up shaped value for `x`.
+    #[inline]
+    fn shape(&self, x: T) -> T {
+        let last = self.table.len() - 1;
+        let position = (clamp11(x) + T::one()) * T::from_f64(0.5 * last as f64);
+        let i = min(last - 1, position.to_i64() as usize);
+        let t = position - T::new(i as i64);
+        self.table[i] + (self.table[i + 1] - self.table[i]) * t
+    }
+}
+
+impl<T: Float> AudioNode for ShaperTable<T> {
+    const ID: u64 = 90;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        [self.shape(input[0])].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for (x, y) in output[0][0..size].iter_mut().zip(input[0][0..size].iter()) {
+            *x = self.shape(*y);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Bitcrusher. Quantizes the signal to a number of bits.
+/// The number of bits may be fractional, which allows smooth modulation.
+/// Setting: bits.
+/// The number of inputs is `N`, either `U1` or `U2`.
+/// - Input 0: input signal
+/// - Input 1 (optional): number of bits
+/// - Output 0: quantized signal
+#[derive(Clone)]
+pub struct Bitcrush<T: Real, N: Size<T>> {
+    _marker: PhantomData<N>,
+    bits: T,
+    /// Number of quantization levels per unit.
+    levels: T,
+}
+
+impl<T: Real, N: Size<T>> Bitcrush<T, N> {
+    pub fn new(bits: T) -> Self {
+        let mut node = Self {
+            _marker: PhantomData,
+            bits,
+            levels: T::one(),
+        };
+        node.set_bits(bits);
+        node
+    }
+
+    /// Number of bits.
+    #[inline]
+    pub fn bits(&self) -> T {
+        self.bits
+    }
+
+    /// Set number of bits (`bits` >= 1).
+    /// This has no effect if the bitcrusher has a bits input.
+    #[inline]
+    pub fn set_bits(&mut self, bits: T) {
+        self.bits = bits;
+        self.levels = exp2(max(T::one(), bits) - T::one());
+    }
+}
+
+impl<T: Real, N: Size<T>> AudioNode for Bitcrush<T, N> {
+    const ID: u64 = 91;
+    type Sample = T;
+    type Inputs = N;
+    type Outputs = U1;
+    type Setting = T;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_bits(setting);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if N::USIZE > 1 {
+            self.set_bits(input[1]);
+        }
+        [round(input[0] * self.levels) / self.levels].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        if N::USIZE > 1 {
+            for i in 0..size {
+                self.set_bits(input[1][i]);
+                output[0][i] = round(input[0][i] * self.levels) / self.levels;
+            }
+        } else {
+            for (x, y) in output[0][0..size].iter_mut().zip(input[0][0..size].iter()) {
+                *x = round(*y * self.levels) / self.levels;
+            }
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
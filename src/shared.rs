@@ -2,11 +2,15 @@
 
 use super::audionode::*;
 use super::combinator::*;
+use super::math::*;
 use super::*;
+use super::signal::*;
+use core::marker::PhantomData;
+use core::ops::Add;
 use numeric_array::typenum::*;
-use std::sync::atomic::AtomicU32;
-// use std::sync::atomic::AtomicU64;
-use std::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+// use core::sync::atomic::AtomicU64;
+use alloc::sync::Arc;
 
 /// A variable floating point number to use as a control.
 pub trait Atomic: Float {
@@ -26,12 +30,12 @@ impl Atomic for f32 {
 
     #[inline]
     fn store(stored: &Self::Storage, t: Self) {
-        stored.store(t.to_bits(), std::sync::atomic::Ordering::Relaxed);
+        stored.store(t.to_bits(), core::sync::atomic::Ordering::Relaxed);
     }
 
     #[inline]
     fn get_stored(stored: &Self::Storage) -> Self {
-        let u = stored.load(std::sync::atomic::Ordering::Relaxed);
+        let u = stored.load(core::sync::atomic::Ordering::Relaxed);
         f32::from_bits(u)
     }
 }
@@ -45,12 +49,12 @@ impl Atomic for f64 {
 
     #[inline]
     fn store(stored: &Self::Storage, t: Self) {
-        stored.store(t.to_bits(), std::sync::atomic::Ordering::Relaxed);
+        stored.store(t.to_bits(), core::sync::atomic::Ordering::Relaxed);
     }
 
     #[inline]
     fn get_stored(stored: &Self::Storage) -> Self {
-        let u = stored.load(std::sync::atomic::Ordering::Relaxed);
+        let u = stored.load(core::sync::atomic::Ordering::Relaxed);
         f64::from_bits(u)
     }
 }
@@ -296,6 +300,343 @@ impl<T: Atomic + Float> AudioNode for Timer<T> {
     }
 }
 
+/// Length in samples of blocks in block-rate control conversions.
+pub const CONTROL_BLOCK: usize = 64;
+
+/// Outputs the value of a shared variable with block-rate smoothing.
+/// The variable is read once every `CONTROL_BLOCK` samples
+/// and the output ramps linearly to the new value over the block.
+/// - Output 0: smoothed value
+#[derive(Clone)]
+pub struct FromShared<T: Atomic> {
+    shared: Shared<T>,
+    /// Value at the start of the current block.
+    previous: T,
+    /// Value at the end of the current block.
+    target: T,
+    /// Sample index within the current block.
+    index: usize,
+}
+
+impl<T: Atomic> FromShared<T> {
+    /// Create node that reads `shared` with block-rate smoothing.
+    pub fn new(shared: &Shared<T>) -> Self {
+        let value = shared.value();
+        Self {
+            shared: shared.clone(),
+            previous: value,
+            target: value,
+            index: 0,
+        }
+    }
+}
+
+impl<T: Atomic> AudioNode for FromShared<T> {
+    const ID: u64 = 124;
+    type Sample = T;
+    type Inputs = U0;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.previous = self.shared.value();
+        self.target = self.previous;
+        self.index = 0;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.index == 0 {
+            self.previous = self.target;
+            self.target = self.shared.value();
+        }
+        self.index += 1;
+        let t = T::from_f64(self.index as f64 / CONTROL_BLOCK as f64);
+        let value = lerp(self.previous, self.target, t);
+        if self.index == CONTROL_BLOCK {
+            self.index = 0;
+        }
+        [value].into()
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
+/// Passes through its input and stores the average of the input
+/// over each block of `CONTROL_BLOCK` samples into a shared variable.
+/// - Input 0: signal
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct ToShared<T: Atomic> {
+    shared: Shared<T>,
+    /// Sum of input over the current block.
+    sum: f64,
+    /// Sample index within the current block.
+    index: usize,
+}
+
+impl<T: Atomic> ToShared<T> {
+    /// Create node that stores block averages of its input into `shared`.
+    pub fn new(shared: &Shared<T>) -> Self {
+        Self {
+            shared: shared.clone(),
+            sum: 0.0,
+            index: 0,
+        }
+    }
+}
+
+impl<T: Atomic> AudioNode for ToShared<T> {
+    const ID: u64 = 125;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.sum = 0.0;
+        self.index = 0;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.sum += input[0].to_f64();
+        self.index += 1;
+        if self.index == CONTROL_BLOCK {
+            self.shared
+                .set_value(T::from_f64(self.sum / CONTROL_BLOCK as f64));
+            self.sum = 0.0;
+            self.index = 0;
+        }
+        *input
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary(0.0).propagate(input, self.outputs())
+    }
+}
+
+/// Control-rate probe. Passes through its input and publishes the latest input value
+/// into a shared variable once per block: every `CONTROL_BLOCK` samples
+/// when ticked, or once per call when processed in blocks.
+/// Unlike `Monitor`, the shared variable is not written every sample,
+/// so dozens of probes cost almost nothing.
+/// - Input 0: signal
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct Probe<T: Atomic> {
+    shared: Shared<T>,
+    /// Sample index within the current block.
+    index: usize,
+}
+
+impl<T: Atomic> Probe<T> {
+    /// Create probe that publishes its input into `shared`.
+    pub fn new(shared: &Shared<T>) -> Self {
+        Self {
+            shared: shared.clone(),
+            index: 0,
+        }
+    }
+}
+
+impl<T: Atomic> AudioNode for Probe<T> {
+    const ID: u64 = 145;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.index = 0;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.index += 1;
+        if self.index == CONTROL_BLOCK {
+            self.shared.set_value(input[0]);
+            self.index = 0;
+        }
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        if size > 0 {
+            output[0][..size].clone_from_slice(&input[0][..size]);
+            self.shared.set_value(input[0][size - 1]);
+            self.index = 0;
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        Routing::Arbitrary(0.0).propagate(input, self.outputs())
+    }
+}
+
+/// Converts a control input to settings of the contained node.
+/// The control is averaged over blocks of `CONTROL_BLOCK` samples and
+/// mapped to a setting, which is applied at the start of the next block.
+/// The first setting after a reset is applied immediately.
+/// - Inputs: from the contained node, followed by control
+/// - Outputs: from the contained node
+#[derive(Clone)]
+pub struct ToSetting<T, X, F>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    F: Fn(T) -> X::Setting + Clone + Send + Sync,
+{
+    _marker: PhantomData<T>,
+    x: X,
+    f: F,
+    /// Sum of control over the current block.
+    sum: f64,
+    /// Sample index within the current block.
+    index: usize,
+    /// Whether a setting has been applied since the last reset.
+    primed: bool,
+}
+
+impl<T, X, F> ToSetting<T, X, F>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Inputs: Add<U1>,
+    <X::Inputs as Add<U1>>::Output: Size<T>,
+    F: Fn(T) -> X::Setting + Clone + Send + Sync,
+{
+    /// Create adaptor that maps control values to settings of `x` with `f`.
+    pub fn new(x: X, f: F) -> Self {
+        let mut node = Self {
+            _marker: PhantomData,
+            x,
+            f,
+            sum: 0.0,
+            index: 0,
+            primed: false,
+        };
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Access the contained node.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut X {
+        &mut self.x
+    }
+
+    /// Access the contained node.
+    #[inline]
+    pub fn node(&self) -> &X {
+        &self.x
+    }
+}
+
+impl<T, X, F> AudioNode for ToSetting<T, X, F>
+where
+    T: Float,
+    X: AudioNode<Sample = T>,
+    X::Inputs: Add<U1>,
+    <X::Inputs as Add<U1>>::Output: Size<T>,
+    F: Fn(T) -> X::Setting + Clone + Send + Sync,
+{
+    const ID: u64 = 126;
+    type Sample = T;
+    type Inputs = <X::Inputs as Add<U1>>::Output;
+    type Outputs = X::Outputs;
+    type Setting = X::Setting;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.x.set(setting);
+    }
+
+    fn reset(&mut self) {
+        self.x.reset();
+        self.sum = 0.0;
+        self.index = 0;
+        self.primed = false;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let control = input[X::Inputs::USIZE];
+        if !self.primed {
+            self.x.set((self.f)(control));
+            self.primed = true;
+        }
+        self.sum += control.to_f64();
+        self.index += 1;
+        let output = self.x.tick(&Frame::generate(|i| input[i]));
+        if self.index == CONTROL_BLOCK {
+            let average = T::from_f64(self.sum / CONTROL_BLOCK as f64);
+            self.x.set((self.f)(average));
+            self.sum = 0.0;
+            self.index = 0;
+        }
+        output
+    }
+
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        self.x.ping(probe, hash.hash(Self::ID))
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        self.x
+            .route(&copy_signal_frame(input, 0, X::Inputs::USIZE), frequency)
+    }
+
+    fn allocate(&mut self) {
+        self.x.allocate();
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        NodeInfo::from_node(self).with_children(vec![self.x.describe()])
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        self.x.parameters()
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.x.parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        self.x.parameter(index)
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        self.x.set_parameter(index, value);
+    }
+}
+
 /// Atomic wavetable that can be modified on the fly.
 pub struct AtomicTable {
     table: Vec<AtomicU32>,
@@ -374,7 +715,7 @@ pub struct AtomicSynth<T: Float> {
     initial_phase: f32,
     sample_rate: f32,
     sample_duration: f32,
-    _marker: std::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<T>,
 }
 
 impl<T: Float> AtomicSynth<T> {
@@ -385,7 +726,7 @@ impl<T: Float> AtomicSynth<T> {
             initial_phase: 0.0,
             sample_rate: DEFAULT_SR as f32,
             sample_duration: 1.0 / DEFAULT_SR as f32,
-            _marker: std::marker::PhantomData,
+            _marker: core::marker::PhantomData,
         }
     }
 }
@@ -424,3 +765,125 @@ impl<T: Float> AudioNode for AtomicSynth<T> {
         Frame::splat(convert(output))
     }
 }
+
+/// Preallocated atomic buffer for capturing audio in real-time.
+/// Samples are stored as `f32`. Once the buffer is full,
+/// further samples are dropped and the overflow flag is set.
+pub struct CaptureBuffer {
+    data: Vec<AtomicU32>,
+    length: AtomicUsize,
+    overflow: AtomicBool,
+}
+
+impl CaptureBuffer {
+    /// Create new capture buffer with room for `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        let mut data = Vec::with_capacity(capacity);
+        for _ in 0..capacity {
+            data.push(f32::storage(0.0));
+        }
+        Self {
+            data,
+            length: AtomicUsize::new(0),
+            overflow: AtomicBool::new(false),
+        }
+    }
+    /// Maximum number of samples the buffer can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+    /// Number of samples captured so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.length.load(Ordering::Acquire)
+    }
+    /// Whether no samples have been captured.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Whether samples have been dropped because the buffer was full.
+    #[inline]
+    pub fn overflow(&self) -> bool {
+        self.overflow.load(Ordering::Acquire)
+    }
+    /// Read captured sample at index `i`.
+    #[inline]
+    pub fn at(&self, i: usize) -> f32 {
+        f32::get_stored(&self.data[i])
+    }
+    /// Append a sample. Sets the overflow flag if the buffer is full.
+    /// Only one thread should push samples at a time.
+    #[inline]
+    pub fn push(&self, value: f32) {
+        let i = self.length.load(Ordering::Relaxed);
+        if i < self.data.len() {
+            f32::store(&self.data[i], value);
+            self.length.store(i + 1, Ordering::Release);
+        } else {
+            self.overflow.store(true, Ordering::Release);
+        }
+    }
+    /// Empty the buffer and clear the overflow flag.
+    pub fn clear(&self) {
+        self.length.store(0, Ordering::Release);
+        self.overflow.store(false, Ordering::Release);
+    }
+    /// Copy captured samples into a vector.
+    pub fn to_vec(&self) -> Vec<f32> {
+        (0..self.len()).map(|i| self.at(i)).collect()
+    }
+}
+
+/// Capture input into a preallocated buffer. The input is passed through.
+/// Capture is real-time safe: it does not allocate or lock.
+/// - Input 0: signal to capture
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct Capture<T: Float> {
+    buffer: Arc<CaptureBuffer>,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Float> Capture<T> {
+    pub fn new(buffer: &Arc<CaptureBuffer>) -> Self {
+        Self {
+            buffer: buffer.clone(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Float> AudioNode for Capture<T> {
+    const ID: u64 = 102;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.buffer.push(input[0].to_f32());
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        for x in input[0][..size].iter() {
+            self.buffer.push(x.to_f32());
+        }
+        output[0][..size].copy_from_slice(&input[0][..size]);
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
@@ -4,6 +4,9 @@ use super::math::*;
 use num_complex::Complex64;
 use tinyvec::TinyVec;
 
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
 /// Contents of a mono signal. Used in latency and frequency response analysis.
 #[derive(Clone, Copy, Default)]
 pub enum Signal {
@@ -193,3 +196,109 @@ impl Routing {
         output
     }
 }
+
+/// Measure the linear transfer matrix of a system with `inputs` inputs and `outputs` outputs.
+/// Unit responses are routed through `route` one input at a time.
+/// The matrix is returned in row-major order with element `(i, j)` at index `i * inputs + j`
+/// containing the response of output `i` to input `j`.
+/// Returns `None` if any output does not have a linear response.
+pub fn transfer_matrix(
+    inputs: usize,
+    outputs: usize,
+    mut route: impl FnMut(&SignalFrame) -> SignalFrame,
+) -> Option<Vec<Complex64>> {
+    let mut matrix = vec![Complex64::new(0.0, 0.0); inputs * outputs];
+    let mut frame = new_signal_frame(inputs);
+    for j in 0..inputs {
+        for (k, signal) in frame.iter_mut().enumerate() {
+            let unit = if k == j { 1.0 } else { 0.0 };
+            *signal = Signal::Response(Complex64::new(unit, 0.0), 0.0);
+        }
+        let response = route(&frame);
+        for i in 0..outputs {
+            match response[i] {
+                Signal::Response(r, _) => matrix[i * inputs + j] = r,
+                Signal::Value(_) => (),
+                _ => return None,
+            }
+        }
+    }
+    Some(matrix)
+}
+
+/// Compute frequency responses of a feedback loop with `n` channels.
+/// The loop processes the sum of its input and feedback with the forward transfer matrix `forward`.
+/// The output of the loop is processed with the feedback transfer matrix `back` and fed back.
+/// Both matrices are in the row-major format returned by `transfer_matrix`.
+/// Output latencies are taken from `forward_output`, which is `input` routed through the forward path.
+/// Returns `None` if any input does not have a linear response or the loop is singular.
+pub fn feedback_response(
+    input: &SignalFrame,
+    forward_output: &SignalFrame,
+    forward: &[Complex64],
+    back: &[Complex64],
+) -> Option<SignalFrame> {
+    let n = input.len();
+    let zero = Complex64::new(0.0, 0.0);
+    let mut x = vec![zero; n];
+    for i in 0..n {
+        match input[i] {
+            Signal::Response(r, _) => x[i] = r,
+            Signal::Value(_) => (),
+            _ => return None,
+        }
+    }
+    // Solve (I - forward * back) * y = forward * x with Gaussian elimination.
+    let mut a = vec![zero; n * n];
+    let mut y = vec![zero; n];
+    for i in 0..n {
+        for j in 0..n {
+            let mut sum = zero;
+            for k in 0..n {
+                sum += forward[i * n + k] * back[k * n + j];
+            }
+            a[i * n + j] = if i == j { 1.0 - sum } else { -sum };
+            y[i] += forward[i * n + j] * x[j];
+        }
+    }
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&p, &q| {
+            a[p * n + column]
+                .norm()
+                .total_cmp(&a[q * n + column].norm())
+        })?;
+        if a[pivot * n + column].norm() < 1.0e-12 {
+            return None;
+        }
+        for k in 0..n {
+            a.swap(column * n + k, pivot * n + k);
+        }
+        y.swap(column, pivot);
+        for row in column + 1..n {
+            let factor = a[row * n + column] / a[column * n + column];
+            for k in column..n {
+                let subtract = factor * a[column * n + k];
+                a[row * n + k] -= subtract;
+            }
+            let subtract = factor * y[column];
+            y[row] -= subtract;
+        }
+    }
+    for row in (0..n).rev() {
+        let mut sum = y[row];
+        for k in row + 1..n {
+            sum -= a[row * n + k] * y[k];
+        }
+        y[row] = sum / a[row * n + row];
+    }
+    let mut output = new_signal_frame(n);
+    for i in 0..n {
+        output[i] = match forward_output[i] {
+            Signal::Response(_, latency) | Signal::Latency(latency) => {
+                Signal::Response(y[i], latency)
+            }
+            _ => return None,
+        };
+    }
+    Some(output)
+}
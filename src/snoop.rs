@@ -1,9 +1,13 @@
-//! The snoop node shares audio data with a frontend thread.
+//! The snoop, scope and trigger nodes share audio data with a frontend thread.
 
 use super::audionode::*;
+use super::dynamics::*;
+use super::math::*;
 use super::signal::*;
 use super::*;
 use numeric_array::*;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
 use thingbuf::mpsc::blocking::{channel, Receiver, Sender};
 
 /// Buffer for snooped audio data.
@@ -159,3 +163,774 @@ impl<T: Float> AudioNode for SnoopBackend<T> {
         input.clone()
     }
 }
+
+/// Bit set in the middle buffer index when it holds a fresh snapshot.
+const FRESH: usize = 4;
+
+/// Lock-free triple buffer of `f32` snapshots. The writer owns the back buffer
+/// and the reader owns the front buffer. Publishing swaps the back buffer with the middle one,
+/// so the writer never waits and the reader always sees a complete snapshot.
+struct TripleBuffer {
+    buffers: [Vec<AtomicU32>; 3],
+    /// Index of the middle buffer, possibly with the `FRESH` bit set.
+    middle: AtomicUsize,
+    /// Index of the back buffer. It is shared so that clones of the writer agree.
+    back: AtomicUsize,
+    /// Whether a writer is publishing the back buffer right now.
+    publishing: AtomicBool,
+}
+
+impl TripleBuffer {
+    /// Create a triple buffer with snapshots of `length` values.
+    /// The reader starts with buffer 0 and the writer with buffer 2.
+    fn new(length: usize) -> Self {
+        let buffer = || (0..length).map(|_| AtomicU32::new(0)).collect::<Vec<_>>();
+        Self {
+            buffers: [buffer(), buffer(), buffer()],
+            middle: AtomicUsize::new(1),
+            back: AtomicUsize::new(2),
+            publishing: AtomicBool::new(false),
+        }
+    }
+
+    /// Length of snapshots.
+    #[inline]
+    fn len(&self) -> usize {
+        self.buffers[0].len()
+    }
+
+    /// Value `i` of `buffer`.
+    #[inline]
+    fn read(&self, buffer: usize, i: usize) -> f32 {
+        f32::from_bits(self.buffers[buffer][i].load(Ordering::Relaxed))
+    }
+
+    /// Set value `i` of `buffer`.
+    #[inline]
+    fn write(&self, buffer: usize, i: usize, value: f32) {
+        self.buffers[buffer][i].store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Index of the back buffer.
+    #[inline]
+    fn back(&self) -> usize {
+        self.back.load(Ordering::Relaxed)
+    }
+
+    /// Publish the back buffer after `fill` has finished writing it.
+    /// Only one publication runs at a time: if another writer
+    /// is in the middle of one, nothing is published.
+    fn publish(&self, fill: impl FnOnce(usize)) {
+        if self.publishing.swap(true, Ordering::Acquire) {
+            return;
+        }
+        let back = self.back.load(Ordering::Relaxed);
+        fill(back);
+        let next = self.middle.swap(back | FRESH, Ordering::AcqRel) & !FRESH;
+        self.back.store(next, Ordering::Relaxed);
+        self.publishing.store(false, Ordering::Release);
+    }
+
+    /// Receive the latest published buffer in exchange for the `front` buffer, if there is one.
+    #[inline]
+    fn receive(&self, front: usize) -> Option<usize> {
+        if self.middle.load(Ordering::Relaxed) & FRESH == 0 {
+            return None;
+        }
+        Some(self.middle.swap(front, Ordering::AcqRel) & !FRESH)
+    }
+}
+
+/// Receiver for triggered oscilloscope snapshots.
+/// Snapshots are triple-buffered: the backend never waits for the frontend,
+/// and the frontend always reads a complete snapshot.
+pub struct Scope {
+    shared: Arc<TripleBuffer>,
+    /// Index of the buffer we are reading.
+    front: usize,
+    /// Total number of snapshots received so far.
+    total: u64,
+}
+
+impl Scope {
+    /// Create a new scope with snapshots of `length` samples.
+    /// Returns a (frontend, backend) pair.
+    pub fn new<T: Float>(length: usize) -> (Scope, ScopeBackend<T>) {
+        let shared = Arc::new(TripleBuffer::new(length));
+        let scope = Scope {
+            shared: shared.clone(),
+            front: 0,
+            total: 0,
+        };
+        let backend = ScopeBackend {
+            shared,
+            level: T::zero(),
+            previous: T::zero(),
+            recording: false,
+            position: 0,
+            waiting: 0,
+        };
+        (scope, backend)
+    }
+
+    /// Length of snapshots in samples.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    /// Sample at index `i` of the latest snapshot received.
+    /// The snapshot begins at the trigger point.
+    pub fn at(&self, i: usize) -> f32 {
+        self.shared.read(self.front, i)
+    }
+
+    /// Copy the latest snapshot received into a vector.
+    pub fn snapshot(&self) -> Vec<f32> {
+        (0..self.len()).map(|i| self.at(i)).collect()
+    }
+
+    /// Total number of snapshots received so far.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Receive the latest snapshot, if there is a new one. Returns whether there was.
+    /// This should be polled repeatedly.
+    pub fn update(&mut self) -> bool {
+        match self.shared.receive(self.front) {
+            Some(front) => {
+                self.front = front;
+                self.total += 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The scope backend node passes through audio data while capturing snapshots
+/// for the scope frontend. A snapshot begins at a rising edge through the trigger level,
+/// which stabilizes periodic waveforms. If no trigger arrives within a snapshot length,
+/// the scope triggers anyway.
+/// Setting: trigger level.
+/// - Input 0: signal to capture
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct ScopeBackend<T: Float> {
+    shared: Arc<TripleBuffer>,
+    /// Trigger level.
+    level: T,
+    /// Previous input sample.
+    previous: T,
+    /// Whether we are capturing a snapshot.
+    recording: bool,
+    /// Position in the snapshot being captured.
+    position: usize,
+    /// Number of samples we have been waiting for a trigger.
+    waiting: usize,
+}
+
+impl<T: Float> ScopeBackend<T> {
+    /// Trigger level.
+    pub fn level(&self) -> T {
+        self.level
+    }
+
+    /// Set trigger level.
+    pub fn set_level(&mut self, level: T) {
+        self.level = level;
+    }
+
+    #[inline]
+    fn capture(&mut self, x: T) {
+        let length = self.shared.len();
+        if !self.recording {
+            self.waiting += 1;
+            if (self.previous <= self.level && x > self.level) || self.waiting > length {
+                self.recording = true;
+                self.position = 0;
+            }
+        }
+        if self.recording && length > 0 {
+            self.shared
+                .write(self.shared.back(), self.position, x.to_f32());
+            self.position += 1;
+            if self.position == length {
+                self.shared.publish(|_| {});
+                self.recording = false;
+                self.waiting = 0;
+            }
+        }
+        self.previous = x;
+    }
+}
+
+impl<T: Float> AudioNode for ScopeBackend<T> {
+    const ID: u64 = 105;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, level: Self::Setting) {
+        self.set_level(level);
+    }
+
+    fn reset(&mut self) {
+        self.previous = T::zero();
+        self.recording = false;
+        self.position = 0;
+        self.waiting = 0;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.capture(input[0]);
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        output[0][..size].clone_from_slice(&input[0][..size]);
+        for i in 0..size {
+            self.capture(input[0][i]);
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
+
+/// Shared state of a graph snapshot.
+struct SnapshotShared {
+    /// Names of the values.
+    names: Vec<String>,
+    /// Latest values written by the graph, waiting to be published.
+    staging: Vec<AtomicU32>,
+    /// Published snapshots.
+    buffers: TripleBuffer,
+}
+
+/// Receiver for compact snapshots of graph state such as meter levels and parameter values.
+/// The graph writes values into named slots via `SnapshotWriter`
+/// and a publisher node publishes all of them together once per block.
+/// Snapshots are triple-buffered: the audio thread never waits for the frontend,
+/// and the frontend always reads a complete and consistent snapshot without locks.
+///
+/// Snapshots carry values read at UI rate: `snapshot_meter` supports the metering modes
+/// of `meter` and `monitor`. `Scope` publishes through the same triple buffer.
+/// Sample streams are still received through `Snoop`.
+///
+/// ### Example: Metering
+/// ```
+/// use fundsp::hacker::*;
+/// let (mut snapshot, writer) = Snapshot::new(&["peak", "rms"]);
+/// let mut node = sine_hz(110.0)
+///     >> snapshot_meter(&writer, 0, Meter::Peak(0.1))
+///     >> snapshot_meter(&writer, 1, Meter::Rms(0.1))
+///     >> snapshot_publish(&writer);
+/// for _ in 0..44100 {
+///     node.get_mono();
+/// }
+/// assert!(snapshot.update());
+/// let peak = snapshot.value(snapshot.index("peak").unwrap());
+/// assert!(peak > 0.9 && peak <= 1.0);
+/// ```
+pub struct Snapshot {
+    shared: Arc<SnapshotShared>,
+    /// Index of the buffer we are reading.
+    front: usize,
+    /// Total number of snapshots received so far.
+    total: u64,
+}
+
+impl Snapshot {
+    /// Create a new snapshot with a slot for each of the `names`.
+    /// Returns a (frontend, writer) pair.
+    pub fn new(names: &[&str]) -> (Snapshot, SnapshotWriter) {
+        let shared = Arc::new(SnapshotShared {
+            names: names.iter().map(|name| String::from(*name)).collect(),
+            staging: (0..names.len()).map(|_| AtomicU32::new(0)).collect(),
+            buffers: TripleBuffer::new(names.len()),
+        });
+        let snapshot = Snapshot {
+            shared: shared.clone(),
+            front: 0,
+            total: 0,
+        };
+        (snapshot, SnapshotWriter { shared })
+    }
+
+    /// Number of slots in the snapshot.
+    pub fn len(&self) -> usize {
+        self.shared.names.len()
+    }
+
+    /// Whether the snapshot has no slots.
+    pub fn is_empty(&self) -> bool {
+        self.shared.names.is_empty()
+    }
+
+    /// Name of slot `i`.
+    pub fn name(&self, i: usize) -> &str {
+        &self.shared.names[i]
+    }
+
+    /// Index of the slot named `name`, if any.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.shared.names.iter().position(|x| x == name)
+    }
+
+    /// Value of slot `i` in the latest snapshot received.
+    pub fn value(&self, i: usize) -> f32 {
+        self.shared.buffers.read(self.front, i)
+    }
+
+    /// Total number of snapshots received so far.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Receive the latest snapshot, if there is a new one. Returns whether there was.
+    /// This should be polled repeatedly, for example, once per UI frame.
+    pub fn update(&mut self) -> bool {
+        match self.shared.buffers.receive(self.front) {
+            Some(front) => {
+                self.front = front;
+                self.total += 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Writer of graph snapshot values. Writers are cheap to clone.
+/// Values can be written from any thread and they are published
+/// with the next block by the publisher node (see `snapshot_publish`).
+#[derive(Clone)]
+pub struct SnapshotWriter {
+    shared: Arc<SnapshotShared>,
+}
+
+impl SnapshotWriter {
+    /// Number of slots in the snapshot.
+    pub fn len(&self) -> usize {
+        self.shared.names.len()
+    }
+
+    /// Whether the snapshot has no slots.
+    pub fn is_empty(&self) -> bool {
+        self.shared.names.is_empty()
+    }
+
+    /// Write `value` to slot `i`. It will be published with the next block.
+    #[inline]
+    pub fn set(&self, i: usize, value: f32) {
+        self.shared.staging[i].store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Publish all slots. Only one publication runs at a time: if another publisher
+    /// is in the middle of one, nothing is published.
+    fn publish(&self) {
+        let shared = &self.shared;
+        shared.buffers.publish(|back| {
+            for (i, value) in shared.staging.iter().enumerate() {
+                shared
+                    .buffers
+                    .write(back, i, f32::from_bits(value.load(Ordering::Relaxed)));
+            }
+        });
+    }
+}
+
+/// Pass through input unchanged. A summary of the input signal
+/// according to the chosen metering mode is written to a snapshot slot.
+/// The slot is written once per block, or once per sample when ticking.
+/// - Input 0: signal
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct SnapshotMeter<T: Real> {
+    writer: SnapshotWriter,
+    slot: usize,
+    meter: Meter,
+    state: MeterState<T>,
+}
+
+impl<T: Real> SnapshotMeter<T> {
+    /// Create a new snapshot meter writing to `slot` of the snapshot.
+    pub fn new(writer: &SnapshotWriter, slot: usize, meter: Meter) -> Self {
+        assert!(slot < writer.len());
+        Self {
+            writer: writer.clone(),
+            slot,
+            meter,
+            state: MeterState::new(meter),
+        }
+    }
+}
+
+impl<T: Real> AudioNode for SnapshotMeter<T> {
+    const ID: u64 = 159;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.state.reset(self.meter);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.state.set_sample_rate(self.meter, sample_rate);
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.state.tick(self.meter, input[0]);
+        self.writer
+            .set(self.slot, self.state.level(self.meter).to_f32());
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        if size == 0 {
+            return;
+        }
+        if self.meter.latest_only() {
+            self.state.tick(self.meter, input[0][size - 1]);
+        } else {
+            for i in 0..size {
+                self.state.tick(self.meter, input[0][i]);
+            }
+        }
+        self.writer
+            .set(self.slot, self.state.level(self.meter).to_f32());
+        output[0][..size].clone_from_slice(&input[0][..size]);
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
+
+/// Pass through input unchanged while publishing snapshot values
+/// written so far, once per block or once every `MAX_BUFFER_SIZE` samples when ticking.
+/// Place it after the nodes that write to the snapshot.
+/// Clones publish to the same snapshot; a publication that overlaps
+/// with one in progress from another thread is skipped.
+/// - Input 0: signal
+/// - Output 0: signal
+#[derive(Clone)]
+pub struct SnapshotPublisher<T: Float> {
+    writer: SnapshotWriter,
+    /// Number of samples ticked since the last publication.
+    samples: usize,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Float> SnapshotPublisher<T> {
+    /// Create a new publisher for the snapshot.
+    pub fn new(writer: &SnapshotWriter) -> Self {
+        Self {
+            writer: writer.clone(),
+            samples: 0,
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Float> AudioNode for SnapshotPublisher<T> {
+    const ID: u64 = 160;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.samples = 0;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        self.samples += 1;
+        if self.samples >= MAX_BUFFER_SIZE {
+            self.samples = 0;
+            self.writer.publish();
+        }
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        output[0][..size].clone_from_slice(&input[0][..size]);
+        if size > 0 {
+            self.samples = 0;
+            self.writer.publish();
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
+}
+
+/// Rising edge detected by a trigger node.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TriggerEvent {
+    /// Index of the first sample at or above the threshold, counted from the last reset.
+    pub sample: u64,
+    /// Time of the threshold crossing in seconds from the last reset,
+    /// interpolated between samples.
+    pub time: f64,
+}
+
+/// Rising edge detector shared by `TriggerBackend` and `TriggerFn`.
+#[derive(Clone)]
+struct EdgeDetector {
+    threshold: f64,
+    previous: f64,
+    sample: u64,
+    sample_duration: f64,
+}
+
+impl EdgeDetector {
+    fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            previous: 0.0,
+            sample: 0,
+            sample_duration: 1.0 / DEFAULT_SR,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.previous = 0.0;
+        self.sample = 0;
+    }
+
+    /// Process one sample. Returns an event if the input rose through the threshold.
+    #[inline]
+    fn detect(&mut self, x: f64) -> Option<TriggerEvent> {
+        let event = if self.previous < self.threshold && x >= self.threshold {
+            let fraction = (self.threshold - self.previous) / (x - self.previous);
+            Some(TriggerEvent {
+                sample: self.sample,
+                time: max(0.0, self.sample as f64 - 1.0 + fraction) * self.sample_duration,
+            })
+        } else {
+            None
+        };
+        self.previous = x;
+        self.sample += 1;
+        event
+    }
+}
+
+/// Receiver for trigger events.
+pub struct Triggers {
+    receiver: Receiver<TriggerEvent>,
+    total: u64,
+}
+
+impl Triggers {
+    /// Create a trigger with rising edge `threshold` and room for `capacity` pending events.
+    /// Returns a (frontend, backend) pair.
+    pub fn new<T: Float>(threshold: T, capacity: usize) -> (Triggers, TriggerBackend<T>) {
+        let (sender, receiver) = channel(max(1, capacity));
+        let triggers = Triggers { receiver, total: 0 };
+        let backend = TriggerBackend {
+            detector: EdgeDetector::new(threshold.to_f64()),
+            sender,
+            _marker: core::marker::PhantomData,
+        };
+        (triggers, backend)
+    }
+
+    /// Total number of events received so far.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Receive the next event, if available. This should be polled repeatedly.
+    pub fn get(&mut self) -> Option<TriggerEvent> {
+        if let Ok(event) = self.receiver.try_recv() {
+            self.total += 1;
+            Some(event)
+        } else {
+            None
+        }
+    }
+
+    /// Receive all pending events in order.
+    pub fn events(&mut self) -> Vec<TriggerEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = self.get() {
+            events.push(event);
+        }
+        events
+    }
+}
+
+/// The trigger backend node detects rising edges through a threshold with sample accuracy.
+/// It outputs an impulse at each edge, which can retrigger envelopes in the graph,
+/// and sends a `TriggerEvent` to the `Triggers` frontend for each edge.
+/// Events are dropped if the frontend queue is full.
+/// Setting: threshold.
+/// - Input 0: signal
+/// - Output 0: impulse at rising edges
+#[derive(Clone)]
+pub struct TriggerBackend<T: Float> {
+    detector: EdgeDetector,
+    sender: Sender<TriggerEvent>,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Float> TriggerBackend<T> {
+    /// Rising edge threshold.
+    pub fn threshold(&self) -> T {
+        T::from_f64(self.detector.threshold)
+    }
+
+    /// Set rising edge threshold.
+    pub fn set_threshold(&mut self, threshold: T) {
+        self.detector.threshold = threshold.to_f64();
+    }
+}
+
+impl<T: Float> AudioNode for TriggerBackend<T> {
+    const ID: u64 = 148;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, threshold: Self::Setting) {
+        self.set_threshold(threshold);
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.detector.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    #[allow(clippy::needless_if)]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if let Some(event) = self.detector.detect(input[0].to_f64()) {
+            if self.sender.try_send(event).is_ok() {}
+            [T::one()].into()
+        } else {
+            [T::zero()].into()
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Trigger node that calls a function at each rising edge through a threshold.
+/// The function is called on the audio thread with the `TriggerEvent`
+/// and should be real-time safe.
+/// Setting: threshold.
+/// - Input 0: signal
+/// - Output 0: impulse at rising edges
+#[derive(Clone)]
+pub struct TriggerFn<T: Float, F: FnMut(TriggerEvent) + Clone + Send + Sync> {
+    detector: EdgeDetector,
+    f: F,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Float, F: FnMut(TriggerEvent) + Clone + Send + Sync> TriggerFn<T, F> {
+    /// Create trigger with rising edge `threshold` that calls `f` at each edge.
+    pub fn new(threshold: T, f: F) -> Self {
+        Self {
+            detector: EdgeDetector::new(threshold.to_f64()),
+            f,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Rising edge threshold.
+    pub fn threshold(&self) -> T {
+        T::from_f64(self.detector.threshold)
+    }
+
+    /// Set rising edge threshold.
+    pub fn set_threshold(&mut self, threshold: T) {
+        self.detector.threshold = threshold.to_f64();
+    }
+}
+
+impl<T: Float, F: FnMut(TriggerEvent) + Clone + Send + Sync> AudioNode for TriggerFn<T, F> {
+    const ID: u64 = 149;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = T;
+
+    fn set(&mut self, threshold: Self::Setting) {
+        self.set_threshold(threshold);
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.detector.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if let Some(event) = self.detector.detect(input[0].to_f64()) {
+            (self.f)(event);
+            [T::one()].into()
+        } else {
+            [T::zero()].into()
+        }
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
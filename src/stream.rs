@@ -0,0 +1,404 @@
+//! Streaming playback of audio files from disk.
+
+use super::audiounit::*;
+use super::math::*;
+use super::read::*;
+use super::signal::*;
+use duplicate::duplicate_item;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Minimum ring buffer size in frames.
+const MIN_STREAM_FRAMES: usize = 4096;
+
+/// How long the decoder thread sleeps when there is nothing to do.
+const STREAM_SLEEP: Duration = Duration::from_millis(1);
+
+/// Lock-free single producer, single consumer ring buffer of interleaved `f32` samples.
+/// It is shared between a decoder thread and a streaming unit and
+/// doubles as a status handle for the stream.
+pub struct StreamBuffer {
+    data: Vec<AtomicU32>,
+    channels: usize,
+    /// Total number of samples written.
+    written: AtomicUsize,
+    /// Total number of samples read.
+    read: AtomicUsize,
+    /// Number of frames output as silence because the buffer ran empty.
+    underruns: AtomicUsize,
+    /// Whether the decoder has reached the end of the file.
+    finished: AtomicBool,
+    /// Whether the reader has requested a restart from the beginning.
+    restart: AtomicBool,
+    /// Whether the decoder thread should exit.
+    stop: AtomicBool,
+}
+
+impl StreamBuffer {
+    /// Create buffer with room for `frames` frames of `channels` channels.
+    fn new(channels: usize, frames: usize) -> Self {
+        let mut data = Vec::with_capacity(channels * frames);
+        for _ in 0..channels * frames {
+            data.push(AtomicU32::new(0));
+        }
+        Self {
+            data,
+            channels,
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            underruns: AtomicUsize::new(0),
+            finished: AtomicBool::new(false),
+            restart: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+        }
+    }
+
+    /// Number of channels.
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Capacity of the buffer in frames.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.len() / self.channels
+    }
+
+    /// Number of frames buffered and ready for playback.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        (self.written.load(Ordering::Acquire) - self.read.load(Ordering::Acquire)) / self.channels
+    }
+
+    /// Number of frames played as silence because the decoder could not keep up.
+    #[inline]
+    pub fn underruns(&self) -> usize {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Whether the whole file has been decoded into the buffer.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Write as many `samples` as fit. Returns the number of samples written.
+    /// Only the decoder thread writes.
+    fn write(&self, samples: &[f32]) -> usize {
+        let written = self.written.load(Ordering::Relaxed);
+        let space = self.data.len() - (written - self.read.load(Ordering::Acquire));
+        let n = min(space, samples.len());
+        for (i, x) in samples[..n].iter().enumerate() {
+            self.data[(written + i) % self.data.len()].store(x.to_bits(), Ordering::Relaxed);
+        }
+        self.written.store(written + n, Ordering::Release);
+        n
+    }
+
+    /// Read the next frame, passing each channel and sample to `store`.
+    /// Returns false if there is no complete frame. Only the streaming unit reads.
+    #[inline]
+    fn read_frame(&self, mut store: impl FnMut(usize, f32)) -> bool {
+        let read = self.read.load(Ordering::Relaxed);
+        if self.written.load(Ordering::Acquire) - read < self.channels {
+            return false;
+        }
+        for channel in 0..self.channels {
+            let bits = self.data[(read + channel) % self.data.len()].load(Ordering::Relaxed);
+            store(channel, f32::from_bits(bits));
+        }
+        self.read.store(read + self.channels, Ordering::Release);
+        true
+    }
+}
+
+/// Opened audio file ready for decoding.
+struct StreamSource {
+    reader: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    channels: usize,
+    sample_rate: f64,
+}
+
+impl StreamSource {
+    /// Open the first track with a known codec from the file at `path`.
+    fn open(path: &Path) -> WaveResult<Self> {
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+            hint.with_extension(extension);
+        }
+        let file = File::open(path).map_err(Error::IoError)?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?;
+        let reader = probed.format;
+        let track = reader
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or(Error::DecodeError("Could not find track."))?;
+        let channels = track
+            .codec_params
+            .channels
+            .ok_or(Error::DecodeError("Unknown channel count."))?
+            .count();
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .ok_or(Error::DecodeError("Unknown sample rate."))? as f64;
+        let track_id = track.id;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+        Ok(Self {
+            reader,
+            decoder,
+            track_id,
+            channels,
+            sample_rate,
+        })
+    }
+
+    /// Decode the next packet of the track into interleaved `samples`.
+    /// Returns false at the end of the file.
+    fn decode(&mut self, samples: &mut Vec<f32>) -> bool {
+        loop {
+            let packet = match self.reader.next_packet() {
+                Ok(packet) => packet,
+                Err(_) => return false,
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let mut buffer =
+                        SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+                    buffer.copy_interleaved_ref(decoded);
+                    samples.clear();
+                    samples.extend_from_slice(buffer.samples());
+                    return true;
+                }
+                // Skip malformed packets.
+                Err(Error::DecodeError(_)) => continue,
+                Err(_) => return false,
+            }
+        }
+    }
+}
+
+/// Decoder thread body. Fills `buffer` from `source` until told to stop.
+fn stream_decoder(path: PathBuf, mut source: StreamSource, buffer: Arc<StreamBuffer>) {
+    let mut pending: Vec<f32> = Vec::new();
+    let mut offset = 0;
+    while !buffer.stop.load(Ordering::Acquire) {
+        if buffer.restart.load(Ordering::Acquire) {
+            if let Ok(reopened) = StreamSource::open(&path) {
+                source = reopened;
+            }
+            pending.clear();
+            offset = 0;
+            buffer.finished.store(false, Ordering::Release);
+            // Discard buffered audio. The reader is idle while a restart is pending.
+            buffer
+                .written
+                .store(buffer.read.load(Ordering::Acquire), Ordering::Release);
+            buffer.restart.store(false, Ordering::Release);
+        } else if offset < pending.len() {
+            let n = buffer.write(&pending[offset..]);
+            offset += n;
+            if n == 0 {
+                std::thread::sleep(STREAM_SLEEP);
+            }
+        } else if buffer.is_finished() || !source.decode(&mut pending) {
+            buffer.finished.store(true, Ordering::Release);
+            std::thread::sleep(STREAM_SLEEP);
+        } else {
+            offset = 0;
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       WavStream48       AudioUnit48;
+    [ f64 ]   [ WavStream64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ WavStream32 ]   [ AudioUnit32 ];
+)]
+/// Streaming playback of an audio file from disk. The file is decoded on a background thread
+/// into a lock-free ring buffer, so long files need not be loaded into memory.
+/// The file plays once at its own sample rate; the unit does not resample.
+/// A reset restarts playback from the beginning after prebuffering again.
+/// Cloning the unit opens a new, independent stream of the same file.
+/// - Outputs: channels of the file
+pub struct WavStream48 {
+    buffer: Arc<StreamBuffer>,
+    path: PathBuf,
+    /// Prebuffer duration in seconds.
+    prebuffer_time: f64,
+    /// Prebuffer size in frames.
+    prebuffer: usize,
+    /// Sample rate of the file.
+    sample_rate: f64,
+    /// Whether we are waiting for the prebuffer to fill.
+    priming: bool,
+}
+
+#[allow(clippy::unnecessary_cast)]
+#[duplicate_item(
+    f48       WavStream48       AudioUnit48;
+    [ f64 ]   [ WavStream64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ WavStream32 ]   [ AudioUnit32 ];
+)]
+impl WavStream48 {
+    /// Open a stream of the audio file at `path`, buffering `prebuffer` seconds ahead.
+    /// Supported formats are anything that Symphonia can read.
+    /// Blocks until the prebuffer has been filled, so playback can start immediately.
+    pub fn new<P: AsRef<Path>>(path: P, prebuffer: f64) -> WaveResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let source = StreamSource::open(&path)?;
+        let sample_rate = source.sample_rate;
+        let prebuffer_frames = max(1, round(prebuffer * sample_rate) as usize);
+        let buffer = Arc::new(StreamBuffer::new(
+            source.channels,
+            max(MIN_STREAM_FRAMES, prebuffer_frames * 2),
+        ));
+        let thread_buffer = buffer.clone();
+        let thread_path = path.clone();
+        std::thread::spawn(move || stream_decoder(thread_path, source, thread_buffer));
+        while buffer.buffered() < prebuffer_frames && !buffer.is_finished() {
+            std::thread::sleep(STREAM_SLEEP);
+        }
+        Ok(Self {
+            buffer,
+            path,
+            prebuffer_time: prebuffer,
+            prebuffer: prebuffer_frames,
+            sample_rate,
+            priming: false,
+        })
+    }
+
+    /// Status handle of the stream for monitoring buffer level and underruns from any thread.
+    pub fn status(&self) -> Arc<StreamBuffer> {
+        self.buffer.clone()
+    }
+
+    /// Sample rate of the file.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Whether playback has reached the end of the file.
+    pub fn is_finished(&self) -> bool {
+        self.buffer.is_finished() && self.buffer.buffered() == 0
+    }
+
+    /// Output the next frame, passing each channel and sample to `store`.
+    #[inline]
+    fn next_frame(&mut self, mut store: impl FnMut(usize, f48)) {
+        let channels = self.buffer.channels();
+        if self.priming
+            && (self.buffer.restart.load(Ordering::Acquire)
+                || (self.buffer.buffered() < self.prebuffer && !self.buffer.is_finished()))
+        {
+            (0..channels).for_each(|channel| store(channel, 0.0));
+            return;
+        }
+        self.priming = false;
+        if !self.buffer.read_frame(|channel, x| store(channel, x as f48)) {
+            (0..channels).for_each(|channel| store(channel, 0.0));
+            if !self.buffer.is_finished() {
+                self.buffer.underruns.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[duplicate_item(
+    f48       WavStream48       AudioUnit48;
+    [ f64 ]   [ WavStream64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ WavStream32 ]   [ AudioUnit32 ];
+)]
+impl Clone for WavStream48 {
+    fn clone(&self) -> Self {
+        Self::new(&self.path, self.prebuffer_time).expect("Could not reopen audio stream.")
+    }
+}
+
+#[duplicate_item(
+    f48       WavStream48       AudioUnit48;
+    [ f64 ]   [ WavStream64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ WavStream32 ]   [ AudioUnit32 ];
+)]
+impl Drop for WavStream48 {
+    fn drop(&mut self) {
+        self.buffer.stop.store(true, Ordering::Release);
+    }
+}
+
+#[duplicate_item(
+    f48       WavStream48       AudioUnit48;
+    [ f64 ]   [ WavStream64 ]   [ AudioUnit64 ];
+    [ f32 ]   [ WavStream32 ]   [ AudioUnit32 ];
+)]
+impl AudioUnit48 for WavStream48 {
+    fn reset(&mut self) {
+        self.buffer.restart.store(true, Ordering::Release);
+        self.priming = true;
+    }
+
+    fn set_sample_rate(&mut self, _sample_rate: f64) {
+        // The file plays at its own sample rate.
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &[f48], output: &mut [f48]) {
+        self.next_frame(|channel, x| output[channel] = x);
+    }
+
+    fn process(&mut self, size: usize, _input: &[&[f48]], output: &mut [&mut [f48]]) {
+        for i in 0..size {
+            self.next_frame(|channel, x| output[channel][i] = x);
+        }
+    }
+
+    fn inputs(&self) -> usize {
+        0
+    }
+
+    fn outputs(&self) -> usize {
+        self.buffer.channels()
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        for i in 0..self.outputs() {
+            output[i] = Signal::Latency(0.0);
+        }
+        output
+    }
+
+    fn get_id(&self) -> u64 {
+        const ID: u64 = 127;
+        ID
+    }
+
+    fn footprint(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
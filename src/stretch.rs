@@ -0,0 +1,281 @@
+//! Time stretching wave playback.
+
+use super::audionode::*;
+use super::math::*;
+use super::signal::*;
+use super::wave::*;
+use super::*;
+use alloc::sync::Arc;
+use core::marker::PhantomData;
+use duplicate::duplicate_item;
+use numeric_array::typenum::*;
+
+/// Grain length of the stretcher in seconds.
+const GRAIN_DURATION: f64 = 0.04;
+
+/// Decimation of the waveform similarity measure.
+const SIMILARITY_STEP: usize = 4;
+
+/// Step of the coarse grain position search in wave samples.
+const SEARCH_STEP: usize = 4;
+
+/// Play back one channel of a wave at independent time and pitch scales
+/// using WSOLA (waveform similarity overlap-add).
+/// At time rate 1 and pitch 1, the wave is reproduced exactly if its sample rate
+/// matches the output sample rate.
+/// Setting: (time rate, pitch ratio).
+/// - Output 0: wave
+#[duplicate_item(
+    f48       Wave48       Wave48Stretcher;
+    [ f64 ]   [ Wave64 ]   [ Wave64Stretcher ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Stretcher ];
+)]
+#[derive(Clone)]
+pub struct Wave48Stretcher<T: Float> {
+    wave: Arc<Wave48>,
+    channel: usize,
+    /// Playback speed relative to the original duration.
+    rate: f64,
+    /// Pitch ratio.
+    pitch: f64,
+    /// Start position in wave samples.
+    start_point: f64,
+    /// Nominal playback position in wave samples.
+    position: f64,
+    /// Start positions of the newer and older grains in wave samples.
+    grain: [f64; 2],
+    /// Samples since the newer grain started.
+    age: usize,
+    /// Hop size in output samples. Grain length is twice the hop size.
+    hop: usize,
+    /// Ratio of wave sample rate to output sample rate.
+    speed: f64,
+    _marker: PhantomData<T>,
+}
+
+#[duplicate_item(
+    f48       Wave48       Wave48Stretcher;
+    [ f64 ]   [ Wave64 ]   [ Wave64Stretcher ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Stretcher ];
+)]
+impl<T: Float> Wave48Stretcher<T> {
+    /// Create stretcher for `channel` of `wave` starting at `start_point` seconds.
+    pub fn new(
+        sample_rate: f64,
+        wave: &Arc<Wave48>,
+        channel: usize,
+        start_point: f64,
+        rate: f64,
+        pitch: f64,
+    ) -> Self {
+        assert!(channel < wave.channels());
+        let mut node = Self {
+            wave: wave.clone(),
+            channel,
+            rate,
+            pitch,
+            start_point: start_point * wave.sample_rate(),
+            position: 0.0,
+            grain: [0.0; 2],
+            age: 0,
+            hop: 1,
+            speed: 1.0,
+            _marker: PhantomData,
+        };
+        node.set_sample_rate(sample_rate);
+        node
+    }
+
+    /// Time rate. Rates below 1 slow playback down and rates above 1 speed it up.
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Set time rate. Rates below 1 slow playback down and rates above 1 speed it up.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+    }
+
+    /// Pitch ratio.
+    #[inline]
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    /// Set pitch ratio. For example, 2 transposes up one octave.
+    #[inline]
+    pub fn set_pitch(&mut self, pitch: f64) {
+        self.pitch = pitch;
+    }
+
+    /// Current playback position in seconds.
+    #[inline]
+    pub fn position(&self) -> f64 {
+        self.position / self.wave.sample_rate()
+    }
+
+    /// Jump to `position` in seconds.
+    pub fn set_position(&mut self, position: f64) {
+        self.position = position * self.wave.sample_rate();
+        self.start_grains();
+    }
+
+    /// Wave sample at index `i`. Returns zero outside the wave.
+    #[inline]
+    fn sample(&self, i: isize) -> f64 {
+        if i >= 0 && (i as usize) < self.wave.length() {
+            self.wave.at(self.channel, i as usize).to_f64()
+        } else {
+            0.0
+        }
+    }
+
+    /// Read the wave at fractional index `i` with linear interpolation.
+    #[inline]
+    fn read(&self, i: f64) -> f64 {
+        let i0 = floor(i);
+        let j = i0 as isize;
+        lerp(self.sample(j), self.sample(j + 1), i - i0)
+    }
+
+    /// Grain start positions for continuous playback from the nominal position.
+    fn start_grains(&mut self) {
+        let step = self.pitch * self.speed;
+        self.grain = [self.position, self.position - self.hop as f64 * step];
+        self.age = 0;
+    }
+
+    /// Similarity of the grain starting at `start` to the continuation of the newer grain.
+    fn similarity(&self, start: f64) -> f64 {
+        let step = self.pitch * self.speed;
+        let continuation = self.grain[0] + self.hop as f64 * step;
+        let mut correlation = 0.0;
+        let mut energy = 0.0;
+        for k in (0..self.hop).step_by(SIMILARITY_STEP) {
+            let x = self.read(start + k as f64 * step);
+            correlation += x * self.read(continuation + k as f64 * step);
+            energy += x * x;
+        }
+        correlation / sqrt(energy + 1.0e-9)
+    }
+
+    /// Find the start of the next grain near the nominal position whose waveform
+    /// best matches the continuation of the newer grain. The search is done
+    /// first coarsely and then refined around the best match.
+    fn next_grain(&self) -> f64 {
+        let tolerance = (self.hop / 2) as isize;
+        let search = |offsets: &mut dyn Iterator<Item = isize>, mut best: isize| {
+            let mut best_similarity = self.similarity(self.position + best as f64);
+            for offset in offsets {
+                let similarity = self.similarity(self.position + offset as f64);
+                // Prefer offsets closest to the nominal position on ties.
+                if similarity > best_similarity
+                    || (similarity == best_similarity && offset.abs() < best.abs())
+                {
+                    best_similarity = similarity;
+                    best = offset;
+                }
+            }
+            best
+        };
+        let coarse = search(
+            &mut (-tolerance..=tolerance).step_by(SEARCH_STEP),
+            0,
+        );
+        let fine = search(
+            &mut (coarse - SEARCH_STEP as isize + 1..coarse + SEARCH_STEP as isize)
+                .filter(|offset| offset.abs() <= tolerance),
+            coarse,
+        );
+        self.position + fine as f64
+    }
+}
+
+#[duplicate_item(
+    f48       Wave48       Wave48Stretcher;
+    [ f64 ]   [ Wave64 ]   [ Wave64Stretcher ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Stretcher ];
+)]
+impl<T: Float> AudioNode for Wave48Stretcher<T> {
+    const ID: u64 = 113;
+    type Sample = T;
+    type Inputs = U0;
+    type Outputs = U1;
+    type Setting = (f64, f64);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (rate, pitch) = setting;
+        self.rate = rate;
+        self.pitch = pitch;
+    }
+
+    fn reset(&mut self) {
+        self.position = self.start_point;
+        self.start_grains();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.speed = self.wave.sample_rate() / sample_rate;
+        self.hop = max(1, round(GRAIN_DURATION * 0.5 * sample_rate) as usize);
+        self.reset();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if self.age == self.hop {
+            let start = self.next_grain();
+            self.grain = [start, self.grain[0]];
+            self.age = 0;
+        }
+        let step = self.pitch * self.speed;
+        let length = (self.hop * 2) as f64;
+        let mut output = 0.0;
+        for (i, start) in self.grain.iter().enumerate() {
+            let age = (self.age + i * self.hop) as f64;
+            // Periodic Hann windows at 50% overlap sum to one.
+            let window = 0.5 - 0.5 * cos(age * TAU / length);
+            output += window * self.read(start + age * step);
+        }
+        self.age += 1;
+        self.position += self.rate * self.speed;
+        [convert(output)].into()
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        vec![
+            ParameterInfo::new("rate", ParameterUnit::Unitless, 0.1, 10.0, 1.0),
+            ParameterInfo::new("pitch", ParameterUnit::Unitless, 0.25, 4.0, 1.0),
+        ]
+    }
+
+    fn parameter_count(&self) -> usize {
+        2
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.rate,
+            1 => self.pitch,
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_rate(value),
+            1 => self.set_pitch(value),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
@@ -8,7 +8,7 @@ use super::signal::*;
 use super::*;
 use num_complex::Complex64;
 use numeric_array::typenum::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// State variable filter coefficients, generic formulation.
 #[derive(Clone, Default)]
@@ -227,6 +227,8 @@ pub trait SvfMode<F: Real>: Clone + Default + Sync + Send {
     type Inputs: Size<F>;
     /// Format of settings for this mode.
     type Setting: Sync + Send + Clone + Default;
+    /// Whether the mode supports gain. Only equalizing modes (bell and shelf) do.
+    const GAIN: bool = false;
 
     /// Update coefficients and parameters from settings.
     fn set(&mut self, setting: Self::Setting, params: &mut SvfParams<F>, coeffs: &mut SvfCoeffs<F>);
@@ -651,6 +653,7 @@ impl<F: Real> BellMode<F> {
 impl<F: Real> SvfMode<F> for BellMode<F> {
     type Inputs = U4;
     type Setting = (F, F, F);
+    const GAIN: bool = true;
 
     fn set(
         &mut self,
@@ -716,6 +719,7 @@ impl<F: Real> LowshelfMode<F> {
 impl<F: Real> SvfMode<F> for LowshelfMode<F> {
     type Inputs = U4;
     type Setting = (F, F, F);
+    const GAIN: bool = true;
 
     fn set(
         &mut self,
@@ -784,6 +788,7 @@ impl<F: Real> HighshelfMode<F> {
 impl<F: Real> SvfMode<F> for HighshelfMode<F> {
     type Inputs = U4;
     type Setting = (F, F, F);
+    const GAIN: bool = true;
 
     fn set(
         &mut self,
@@ -1129,6 +1134,49 @@ where
         output[0] = input[0].filter(0.0, |r| r * self.mode.response(&self.params, frequency));
         output
     }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        let mut parameters = vec![
+            ParameterInfo::new("cutoff", ParameterUnit::Hz, 20.0, 20_000.0, 1000.0),
+            ParameterInfo::new("q", ParameterUnit::Unitless, 0.1, 100.0, SQRT_2 * 0.5),
+        ];
+        if M::GAIN {
+            parameters.push(ParameterInfo::new(
+                "gain",
+                ParameterUnit::Amplitude,
+                0.0,
+                16.0,
+                1.0,
+            ));
+        }
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        if M::GAIN {
+            3
+        } else {
+            2
+        }
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.params.cutoff.to_f64(),
+            1 => self.params.q.to_f64(),
+            2 if M::GAIN => self.params.gain.to_f64(),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_cutoff(F::from_f64(value)),
+            1 => self.set_q(F::from_f64(value)),
+            2 if M::GAIN => self.set_gain(F::from_f64(value)),
+            _ => panic!("Parameter index out of range."),
+        }
+    }
 }
 
 /// Morphing filter that morphs between lowpass, peak and highpass modes.
@@ -1213,3 +1261,319 @@ impl<T: Float, F: Real> AudioNode for Morph<T, F> {
         self.filter.ping(probe, hash).hash(Self::ID)
     }
 }
+
+/// Simper SVF with simultaneous lowpass, highpass, bandpass and notch outputs.
+/// All four responses are taken from a single filter core.
+/// - Input 0: audio
+/// - Input 1: cutoff frequency (Hz)
+/// - Input 2: Q
+/// - Output 0: lowpass filtered audio
+/// - Output 1: highpass filtered audio
+/// - Output 2: bandpass filtered audio
+/// - Output 3: notch filtered audio
+#[derive(Clone)]
+pub struct SvfMulti<T: Float, F: Real> {
+    params: SvfParams<F>,
+    coeffs: SvfCoeffs<F>,
+    k: F,
+    ic1eq: F,
+    ic2eq: F,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Float, F: Real> SvfMulti<T, F> {
+    pub fn new(sample_rate: f64, cutoff: F, q: F) -> Self {
+        let mut node = Self {
+            params: SvfParams {
+                sample_rate: convert(sample_rate),
+                cutoff,
+                q,
+                gain: F::one(),
+            },
+            coeffs: SvfCoeffs::default(),
+            k: F::one(),
+            ic1eq: F::zero(),
+            ic2eq: F::zero(),
+            _marker: PhantomData,
+        };
+        node.update();
+        node
+    }
+
+    /// Filter cutoff in Hz.
+    #[inline]
+    pub fn cutoff(&self) -> F {
+        self.params.cutoff
+    }
+    /// Filter Q.
+    #[inline]
+    pub fn q(&self) -> F {
+        self.params.q
+    }
+
+    fn update(&mut self) {
+        // The core coefficients a1, a2 and a3 are shared by all modes.
+        self.coeffs = SvfCoeffs::lowpass(self.params.sample_rate, self.params.cutoff, self.params.q);
+        self.k = F::one() / self.params.q;
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for SvfMulti<T, F> {
+    const ID: u64 = 94;
+    type Sample = T;
+    type Inputs = U3;
+    type Outputs = U4;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        self.ic1eq = F::zero();
+        self.ic2eq = F::zero();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.params.sample_rate = convert(sample_rate);
+        self.update();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let cutoff: F = convert(input[1]);
+        let q: F = convert(input[2]);
+        if cutoff != self.params.cutoff || q != self.params.q {
+            self.params.cutoff = cutoff;
+            self.params.q = q;
+            self.update();
+        }
+        let v0: F = convert(input[0]);
+        let v3 = v0 - self.ic2eq;
+        let v1 = self.coeffs.a1 * self.ic1eq + self.coeffs.a2 * v3;
+        let v2 = self.ic2eq + self.coeffs.a2 * self.ic1eq + self.coeffs.a3 * v3;
+        self.ic1eq = F::new(2) * v1 - self.ic1eq;
+        self.ic2eq = F::new(2) * v2 - self.ic2eq;
+        let notch = v0 - self.k * v1;
+        [
+            convert(v2),
+            convert(notch - v2),
+            convert(v1),
+            convert(notch),
+        ]
+        .into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let lowpass = LowpassMode::new().response(&self.params, frequency);
+        let highpass = HighpassMode::new().response(&self.params, frequency);
+        let bandpass = BandpassMode::new().response(&self.params, frequency);
+        let notch = NotchMode::new().response(&self.params, frequency);
+        output[0] = input[0].filter(0.0, |r| r * lowpass);
+        output[1] = input[0].filter(0.0, |r| r * highpass);
+        output[2] = input[0].filter(0.0, |r| r * bandpass);
+        output[3] = input[0].filter(0.0, |r| r * notch);
+        output
+    }
+}
+
+/// Shape of a parametric equalizer band.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EqShape {
+    Lowshelf,
+    Bell,
+    Highshelf,
+}
+
+/// Parametric equalizer band. Gain is given as amplitude (gain > 0).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EqBand<F: Real> {
+    pub shape: EqShape,
+    /// Center frequency in Hz.
+    pub frequency: F,
+    pub q: F,
+    pub gain: F,
+}
+
+impl<F: Real> Default for EqBand<F> {
+    /// Flat bell band.
+    fn default() -> Self {
+        Self::bell(F::new(1000), F::one(), F::one())
+    }
+}
+
+impl<F: Real> EqBand<F> {
+    /// Low shelf band with center frequency `frequency` Hz, Q value `q` and amplitude gain `gain`.
+    pub fn lowshelf(frequency: F, q: F, gain: F) -> Self {
+        Self {
+            shape: EqShape::Lowshelf,
+            frequency,
+            q,
+            gain,
+        }
+    }
+    /// Bell band with center frequency `frequency` Hz, Q value `q` and amplitude gain `gain`.
+    pub fn bell(frequency: F, q: F, gain: F) -> Self {
+        Self {
+            shape: EqShape::Bell,
+            frequency,
+            q,
+            gain,
+        }
+    }
+    /// High shelf band with center frequency `frequency` Hz, Q value `q` and amplitude gain `gain`.
+    pub fn highshelf(frequency: F, q: F, gain: F) -> Self {
+        Self {
+            shape: EqShape::Highshelf,
+            frequency,
+            q,
+            gain,
+        }
+    }
+
+    fn params(&self, sample_rate: F) -> SvfParams<F> {
+        SvfParams {
+            sample_rate,
+            cutoff: self.frequency,
+            q: self.q,
+            gain: self.gain,
+        }
+    }
+
+    fn coeffs(&self, sample_rate: F) -> SvfCoeffs<F> {
+        match self.shape {
+            EqShape::Lowshelf => {
+                SvfCoeffs::lowshelf(sample_rate, self.frequency, self.q, self.gain)
+            }
+            EqShape::Bell => SvfCoeffs::bell(sample_rate, self.frequency, self.q, self.gain),
+            EqShape::Highshelf => {
+                SvfCoeffs::highshelf(sample_rate, self.frequency, self.q, self.gain)
+            }
+        }
+    }
+
+    fn response(&self, sample_rate: F, frequency: f64) -> Complex64 {
+        let params = self.params(sample_rate);
+        match self.shape {
+            EqShape::Lowshelf => LowshelfMode::new().response(&params, frequency),
+            EqShape::Bell => BellMode::new().response(&params, frequency),
+            EqShape::Highshelf => HighshelfMode::new().response(&params, frequency),
+        }
+    }
+}
+
+/// Parametric equalizer made of a series of low shelf, bell and high shelf bands.
+/// Bands can be changed, added and removed at runtime.
+/// The combined frequency response is available via `AudioNode::response`.
+/// Setting: (band index, band).
+/// - Input 0: audio
+/// - Output 0: equalized audio
+#[derive(Clone)]
+pub struct ParametricEq<T: Float, F: Real> {
+    bands: Vec<EqBand<F>>,
+    coeffs: Vec<SvfCoeffs<F>>,
+    /// Filter states (ic1eq, ic2eq) of the bands.
+    state: Vec<(F, F)>,
+    sample_rate: F,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Float, F: Real> ParametricEq<T, F> {
+    pub fn new(sample_rate: f64, bands: &[EqBand<F>]) -> Self {
+        let sample_rate = convert(sample_rate);
+        Self {
+            bands: bands.to_vec(),
+            coeffs: bands.iter().map(|band| band.coeffs(sample_rate)).collect(),
+            state: vec![(F::zero(), F::zero()); bands.len()],
+            sample_rate,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of bands.
+    #[inline]
+    pub fn bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Band `i`.
+    #[inline]
+    pub fn band(&self, i: usize) -> &EqBand<F> {
+        &self.bands[i]
+    }
+
+    /// Set band `i`. The state of the band is retained.
+    pub fn set_band(&mut self, i: usize, band: EqBand<F>) {
+        self.bands[i] = band;
+        self.coeffs[i] = band.coeffs(self.sample_rate);
+    }
+
+    /// Add a band after the existing bands.
+    /// - Allocates: band storage.
+    pub fn push_band(&mut self, band: EqBand<F>) {
+        self.bands.push(band);
+        self.coeffs.push(band.coeffs(self.sample_rate));
+        self.state.push((F::zero(), F::zero()));
+    }
+
+    /// Remove band `i`.
+    pub fn remove_band(&mut self, i: usize) -> EqBand<F> {
+        self.coeffs.remove(i);
+        self.state.remove(i);
+        self.bands.remove(i)
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for ParametricEq<T, F> {
+    const ID: u64 = 98;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (usize, EqBand<F>);
+
+    fn set(&mut self, (i, band): Self::Setting) {
+        self.set_band(i, band);
+    }
+
+    fn reset(&mut self) {
+        for state in self.state.iter_mut() {
+            *state = (F::zero(), F::zero());
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = convert(sample_rate);
+        for i in 0..self.bands() {
+            self.coeffs[i] = self.bands[i].coeffs(self.sample_rate);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut x: F = convert(input[0]);
+        for (coeffs, (ic1eq, ic2eq)) in self.coeffs.iter().zip(self.state.iter_mut()) {
+            let v3 = x - *ic2eq;
+            let v1 = coeffs.a1 * *ic1eq + coeffs.a2 * v3;
+            let v2 = *ic2eq + coeffs.a2 * *ic1eq + coeffs.a3 * v3;
+            *ic1eq = F::new(2) * v1 - *ic1eq;
+            *ic2eq = F::new(2) * v2 - *ic2eq;
+            x = coeffs.m0 * x + coeffs.m1 * v1 + coeffs.m2 * v2;
+        }
+        [convert(x)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        let response = self
+            .bands
+            .iter()
+            .fold(Complex64::new(1.0, 0.0), |r, band| {
+                r * band.response(self.sample_rate, frequency)
+            });
+        output[0] = input[0].filter(0.0, |r| r * response);
+        output
+    }
+}
@@ -5,7 +5,7 @@ use super::combinator::*;
 use super::math::*;
 use super::signal::*;
 use super::*;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// A dynamical system is a node that has an attached update function
 /// `f(t, dt, x)` where `t` is current time, `dt` is time elapsed since
@@ -0,0 +1,231 @@
+//! Test harness for rendering units offline and checking their output.
+
+use super::audit::*;
+use super::audiounit::*;
+use super::buffer::*;
+use super::math::*;
+use super::wave::*;
+use super::*;
+use duplicate::duplicate_item;
+use realfft::RealFftPlanner;
+
+/// Length of the analysis window of spectral measurements in samples.
+const ANALYSIS_LENGTH: usize = 32768;
+
+/// Number of samples rendered and discarded before spectral measurements.
+const ANALYSIS_WARMUP: usize = 4096;
+
+/// Half width of the spectral peak of a windowed sinusoid in bins.
+const PEAK_WIDTH: usize = 6;
+
+/// Power spectrum of `samples` with a Blackman-Harris window.
+fn power_spectrum(samples: &[f64]) -> Vec<f64> {
+    let length = samples.len();
+    let mut planner = RealFftPlanner::<f64>::new();
+    let fft = planner.plan_fft_forward(length);
+    let mut input: Vec<f64> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let phase = i as f64 * TAU / length as f64;
+            let window = 0.35875 - 0.48829 * cos(phase) + 0.14128 * cos(2.0 * phase)
+                - 0.01168 * cos(3.0 * phase);
+            x * window
+        })
+        .collect();
+    let mut output = fft.make_output_vec();
+    fft.process(&mut input, &mut output).unwrap();
+    output.iter().map(|z| z.norm_sqr()).collect()
+}
+
+/// Sum of power within the spectral peak around `frequency` Hz.
+fn peak_power(spectrum: &[f64], sample_rate: f64, frequency: f64) -> f64 {
+    let bin = round(frequency * ANALYSIS_LENGTH as f64 / sample_rate) as usize;
+    let start = bin.saturating_sub(PEAK_WIDTH);
+    let end = min(spectrum.len(), bin + PEAK_WIDTH + 1);
+    spectrum[start..end].iter().sum()
+}
+
+#[duplicate_item(
+    f48       Wave48       AudioUnit48       Harness48;
+    [ f64 ]   [ Wave64 ]   [ AudioUnit64 ]   [ Harness64 ];
+    [ f32 ]   [ Wave32 ]   [ AudioUnit32 ]   [ Harness32 ];
+)]
+/// Test harness. Renders units offline deterministically,
+/// compares output against golden waves, measures distortion of oscillators
+/// and checks that processing does not allocate.
+///
+/// ### Example: Check That Rendering Is Deterministic
+/// ```
+/// use fundsp::hacker::*;
+/// let harness = Harness64::new(44100.0).with_seed(1);
+/// let a = harness.render(&mut noise(), 0.1);
+/// let b = harness.render(&mut noise(), 0.1);
+/// harness.assert_close(&a, &b, 0.0);
+/// ```
+#[derive(Clone)]
+pub struct Harness48 {
+    sample_rate: f64,
+    seed: u64,
+}
+
+#[duplicate_item(
+    f48       Wave48       AudioUnit48       Harness48;
+    [ f64 ]   [ Wave64 ]   [ AudioUnit64 ]   [ Harness64 ];
+    [ f32 ]   [ Wave32 ]   [ AudioUnit32 ]   [ Harness32 ];
+)]
+impl Harness48 {
+    /// Create harness that renders at `sample_rate` Hz.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            seed: 0,
+        }
+    }
+
+    /// Seed units with `seed` before rendering. The default seed is zero.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sample rate in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Prepare `unit` for a deterministic render: reseed it, set the sample rate and reset it.
+    pub fn prepare(&self, unit: &mut dyn AudioUnit48) {
+        unit.ping(false, AttoHash::new(self.seed));
+        unit.set_sample_rate(self.sample_rate);
+        unit.reset();
+    }
+
+    /// Render `duration` seconds from generator `unit` after preparing it.
+    pub fn render(&self, unit: &mut dyn AudioUnit48, duration: f64) -> Wave48 {
+        self.prepare(unit);
+        Wave48::render(self.sample_rate, duration, unit)
+    }
+
+    /// Filter `input` with `unit` for `duration` seconds after preparing it.
+    /// The input samples are processed as they are at the harness sample rate.
+    pub fn filter(&self, unit: &mut dyn AudioUnit48, input: &Wave48, duration: f64) -> Wave48 {
+        self.prepare(unit);
+        let mut input = input.clone();
+        input.set_sample_rate(self.sample_rate);
+        input.filter(duration, unit)
+    }
+
+    /// Maximum absolute difference between two waves.
+    /// Missing channels and samples count as zeros.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn max_difference(&self, expected: &Wave48, actual: &Wave48) -> f64 {
+        let channels = max(expected.channels(), actual.channels());
+        let length = max(expected.len(), actual.len());
+        let sample = |wave: &Wave48, channel: usize, i: usize| -> f64 {
+            if channel < wave.channels() && i < wave.len() {
+                wave.at(channel, i) as f64
+            } else {
+                0.0
+            }
+        };
+        let mut difference = 0.0;
+        for channel in 0..channels {
+            for i in 0..length {
+                let d = abs(sample(expected, channel, i) - sample(actual, channel, i));
+                if d.is_nan() {
+                    return f64::INFINITY;
+                }
+                difference = max(difference, d);
+            }
+        }
+        difference
+    }
+
+    /// Panic unless `actual` matches the golden wave `expected`
+    /// to within `tolerance` at every sample.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn assert_close(&self, expected: &Wave48, actual: &Wave48, tolerance: f64) {
+        assert_eq!(expected.channels(), actual.channels(), "Channel counts differ.");
+        assert_eq!(expected.len(), actual.len(), "Lengths differ.");
+        for channel in 0..expected.channels() {
+            for i in 0..expected.len() {
+                let x = expected.at(channel, i) as f64;
+                let y = actual.at(channel, i) as f64;
+                assert!(
+                    abs(x - y) <= tolerance,
+                    "Mismatch at channel {} sample {}: expected {}, got {}.",
+                    channel,
+                    i,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    /// Render output 0 of generator `unit` for spectral analysis.
+    #[allow(clippy::unnecessary_cast)]
+    fn analysis(&self, unit: &mut dyn AudioUnit48) -> Vec<f64> {
+        let duration = (ANALYSIS_WARMUP + ANALYSIS_LENGTH) as f64 / self.sample_rate;
+        let wave = self.render(unit, duration);
+        (ANALYSIS_WARMUP..ANALYSIS_WARMUP + ANALYSIS_LENGTH)
+            .map(|i| wave.at(0, i) as f64)
+            .collect()
+    }
+
+    /// Total harmonic distortion of generator `unit` playing fundamental `frequency` Hz,
+    /// as an amplitude ratio of harmonics below Nyquist to the fundamental.
+    /// For example, a sine wave has a THD close to zero.
+    pub fn thd(&self, unit: &mut dyn AudioUnit48, frequency: f64) -> f64 {
+        assert!(frequency > 0.0 && frequency < 0.5 * self.sample_rate);
+        let spectrum = power_spectrum(&self.analysis(unit));
+        let fundamental = peak_power(&spectrum, self.sample_rate, frequency);
+        let mut harmonics = 0.0;
+        let mut harmonic = 2.0 * frequency;
+        while harmonic < 0.5 * self.sample_rate {
+            harmonics += peak_power(&spectrum, self.sample_rate, harmonic);
+            harmonic += frequency;
+        }
+        sqrt(harmonics / fundamental)
+    }
+
+    /// Aliasing of generator `unit` playing fundamental `frequency` Hz,
+    /// as an amplitude ratio of energy away from the harmonics to the total energy.
+    /// Zero means that the output is perfectly band-limited.
+    pub fn aliasing(&self, unit: &mut dyn AudioUnit48, frequency: f64) -> f64 {
+        assert!(frequency > 0.0 && frequency < 0.5 * self.sample_rate);
+        let spectrum = power_spectrum(&self.analysis(unit));
+        let total: f64 = spectrum.iter().skip(PEAK_WIDTH + 1).sum();
+        let mut harmonics = 0.0;
+        let mut harmonic = frequency;
+        while harmonic < 0.5 * self.sample_rate {
+            harmonics += peak_power(&spectrum, self.sample_rate, harmonic);
+            harmonic += frequency;
+        }
+        if total > 0.0 {
+            sqrt(max(0.0, total - harmonics) / total)
+        } else {
+            0.0
+        }
+    }
+
+    /// Process `blocks` blocks of silence with `unit`, panicking if processing allocates.
+    /// Block sizes vary from 1 to `MAX_BUFFER_SIZE`. Allocations are seen only when
+    /// `AuditAllocator` is the global allocator of the test binary.
+    pub fn assert_no_alloc(&self, unit: &mut dyn AudioUnit48, blocks: usize) {
+        self.prepare(unit);
+        unit.allocate();
+        let mut input = Buffer::<f48>::with_channels(unit.inputs());
+        let mut output = Buffer::<f48>::with_channels(unit.outputs());
+        let input = input.self_ref();
+        let output = output.self_mut();
+        let mut rnd = AttoHash::new(self.seed);
+        for _ in 0..blocks {
+            rnd = rnd.hash(1);
+            let size = 1 + (rnd.state() as usize) % MAX_BUFFER_SIZE;
+            let _guard = AllocGuard::new();
+            unit.process(size, input, output);
+        }
+    }
+}
@@ -0,0 +1,583 @@
+//! Master tempo and tempo synced nodes.
+
+use super::audionode::*;
+use super::delay::*;
+use super::math::*;
+use super::pan::*;
+use super::shared::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+
+/// Slowest supported tempo in beats per minute.
+pub const MIN_TEMPO: f64 = 20.0;
+
+/// Time constant of delay time glides in seconds.
+const GLIDE_TIME: f64 = 0.05;
+
+/// Transport. Holds the master tempo, which can be changed
+/// from any thread while tempo synced nodes are playing.
+/// Clones of a transport share the same tempo.
+#[derive(Clone)]
+pub struct Transport {
+    tempo: Shared<f32>,
+}
+
+impl Transport {
+    /// Create transport with tempo `bpm` in beats per minute.
+    pub fn new(bpm: f64) -> Self {
+        Self {
+            tempo: Shared::new(max(MIN_TEMPO, bpm) as f32),
+        }
+    }
+
+    /// Tempo in beats per minute.
+    #[inline]
+    pub fn tempo(&self) -> f64 {
+        self.tempo.value() as f64
+    }
+
+    /// Set tempo in beats per minute. Tempos below `MIN_TEMPO` are clamped.
+    #[inline]
+    pub fn set_tempo(&self, bpm: f64) {
+        self.tempo.set_value(max(MIN_TEMPO, bpm) as f32);
+    }
+
+    /// Duration of `beats` beats in seconds at the current tempo.
+    #[inline]
+    pub fn beats_to_seconds(&self, beats: f64) -> f64 {
+        beats * 60.0 / self.tempo()
+    }
+}
+
+/// Tempo synced delay. Delay time is a number of beats at the tempo of the transport.
+/// When the tempo changes, delay time glides to the new value.
+/// Setting: delay in beats.
+/// - Allocates: the delay line, with room for `beats` beats at `MIN_TEMPO`.
+/// - Input 0: input
+/// - Output 0: delayed input
+#[derive(Clone)]
+pub struct DelaySync<T: Float> {
+    delay: FracDelay<T>,
+    transport: Transport,
+    beats: f64,
+    /// Current delay time in seconds.
+    time: f64,
+    /// Glide coefficient per sample.
+    glide: f64,
+}
+
+impl<T: Float> DelaySync<T> {
+    /// Create tempo synced delay of `beats` (`beats` > 0) beats.
+    pub fn new(transport: &Transport, beats: f64) -> Self {
+        assert!(beats > 0.0);
+        let max_delay = beats * 60.0 / MIN_TEMPO;
+        let mut node = Self {
+            delay: FracDelay::new(Interpolation::Cubic, 0.0, max_delay, 0.0),
+            transport: transport.clone(),
+            beats,
+            time: 0.0,
+            glide: 0.0,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Delay in beats.
+    #[inline]
+    pub fn beats(&self) -> f64 {
+        self.beats
+    }
+
+    /// Set delay in beats. The delay time glides to the new value.
+    /// Delays longer than the delay line can hold are clamped.
+    #[inline]
+    pub fn set_beats(&mut self, beats: f64) {
+        self.beats = beats;
+    }
+}
+
+impl<T: Float> AudioNode for DelaySync<T> {
+    const ID: u64 = 117;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = f64;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_beats(setting);
+    }
+
+    fn reset(&mut self) {
+        self.delay.reset();
+        self.time = self.transport.beats_to_seconds(self.beats);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.delay.set_sample_rate(sample_rate);
+        self.glide = 1.0 - exp(-1.0 / (GLIDE_TIME * sample_rate));
+        self.reset();
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let target = self.transport.beats_to_seconds(self.beats);
+        self.time += (target - self.time) * self.glide;
+        self.delay.tick(&[input[0], T::from_f64(self.time)].into())
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Waveform of a tempo synced LFO.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LfoShape {
+    /// Sine wave.
+    #[default]
+    Sine,
+    /// Triangle wave.
+    Triangle,
+    /// Rising sawtooth wave.
+    Saw,
+    /// Square wave.
+    Square,
+    /// Random value held for each cycle.
+    SampleHold,
+    /// Random values interpolated smoothly from cycle to cycle.
+    SmoothRandom,
+}
+
+impl LfoShape {
+    /// Bipolar value of the waveform at `phase` in 0...1.
+    /// Random shapes are zero here; see `at_cycle`.
+    #[inline]
+    pub fn at(&self, phase: f64) -> f64 {
+        self.at_cycle(phase, 0.0, 0.0)
+    }
+
+    /// Bipolar value of the waveform at `phase` in 0...1,
+    /// where random shapes use the random values `current` and `next`
+    /// in -1...1 drawn for the current and next cycle.
+    #[inline]
+    pub fn at_cycle(&self, phase: f64, current: f64, next: f64) -> f64 {
+        match self {
+            LfoShape::Sine => sin(phase * TAU),
+            LfoShape::Triangle => 1.0 - 4.0 * abs(phase - 0.5),
+            LfoShape::Saw => 2.0 * phase - 1.0,
+            LfoShape::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            LfoShape::SampleHold => current,
+            LfoShape::SmoothRandom => lerp(current, next, smooth3(phase)),
+        }
+    }
+}
+
+/// Random values of LFO cycles.
+#[derive(Clone)]
+pub(crate) struct LfoRandom {
+    /// Random value of the current cycle.
+    pub current: f64,
+    /// Random value of the next cycle.
+    pub next: f64,
+    hash: u64,
+    state: u64,
+}
+
+impl LfoRandom {
+    /// Create random sequence seeded with `hash`.
+    pub fn new(hash: u64) -> Self {
+        let mut random = Self {
+            current: 0.0,
+            next: 0.0,
+            hash,
+            state: hash,
+        };
+        random.reset();
+        random
+    }
+
+    /// Seed with `hash` and draw values for the first two cycles.
+    pub fn set_hash(&mut self, hash: u64) {
+        self.hash = hash;
+        self.reset();
+    }
+
+    /// Restart the random sequence.
+    pub fn reset(&mut self) {
+        self.state = self.hash;
+        self.current = self.draw();
+        self.next = self.draw();
+    }
+
+    /// Move to the next cycle.
+    #[inline]
+    pub fn advance(&mut self) {
+        self.current = self.next;
+        self.next = self.draw();
+    }
+
+    #[inline]
+    fn draw(&mut self) -> f64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        rnd(self.state as i64) * 2.0 - 1.0
+    }
+}
+
+/// Tempo synced LFO. One cycle lasts a number of beats at the tempo of the transport.
+/// Tempo changes affect the rate immediately without phase jumps.
+/// Setting: waveform.
+/// - Output 0: LFO in -1...1
+#[derive(Clone)]
+pub struct LfoSync<T: Float> {
+    transport: Transport,
+    beats: f64,
+    shape: LfoShape,
+    phase: f64,
+    random: LfoRandom,
+    sample_duration: f64,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Float> LfoSync<T> {
+    /// Create tempo synced LFO with a cycle of `beats` (`beats` > 0) beats.
+    pub fn new(transport: &Transport, beats: f64, shape: LfoShape) -> Self {
+        assert!(beats > 0.0);
+        Self {
+            transport: transport.clone(),
+            beats,
+            shape,
+            phase: 0.0,
+            random: LfoRandom::new(0),
+            sample_duration: 1.0 / DEFAULT_SR,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Cycle length in beats.
+    #[inline]
+    pub fn beats(&self) -> f64 {
+        self.beats
+    }
+
+    /// Set cycle length in beats.
+    #[inline]
+    pub fn set_beats(&mut self, beats: f64) {
+        self.beats = beats;
+    }
+
+    /// Waveform.
+    #[inline]
+    pub fn shape(&self) -> LfoShape {
+        self.shape
+    }
+
+    /// Set waveform.
+    #[inline]
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+}
+
+impl<T: Float> AudioNode for LfoSync<T> {
+    const ID: u64 = 118;
+    type Sample = T;
+    type Inputs = U0;
+    type Outputs = U1;
+    type Setting = LfoShape;
+
+    fn set(&mut self, setting: Self::Setting) {
+        self.set_shape(setting);
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.random.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        _input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let value = self
+            .shape
+            .at_cycle(self.phase, self.random.current, self.random.next);
+        self.phase += self.sample_duration / self.transport.beats_to_seconds(self.beats);
+        if self.phase >= 1.0 {
+            self.phase -= floor(self.phase);
+            self.random.advance();
+        }
+        [convert(value)].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.random.set_hash(hash);
+    }
+
+    fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = Signal::Latency(0.0);
+        output
+    }
+}
+
+/// Modulation oscillator of `AutoPan` and `Tremolo`. The rate is either a frequency in Hz
+/// or, with a transport, a cycle length in beats.
+#[derive(Clone)]
+struct ModLfo {
+    transport: Option<Transport>,
+    rate: f64,
+    shape: LfoShape,
+    phase: f64,
+    random: LfoRandom,
+    sample_duration: f64,
+}
+
+impl ModLfo {
+    fn new(transport: Option<&Transport>, rate: f64, shape: LfoShape) -> Self {
+        Self {
+            transport: transport.cloned(),
+            rate,
+            shape,
+            phase: 0.0,
+            random: LfoRandom::new(0),
+            sample_duration: 1.0 / DEFAULT_SR,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.random.reset();
+    }
+
+    /// Return the current bipolar value and advance.
+    #[inline]
+    fn tick(&mut self) -> f64 {
+        let value = self
+            .shape
+            .at_cycle(self.phase, self.random.current, self.random.next);
+        self.phase += match &self.transport {
+            Some(transport) => self.sample_duration / transport.beats_to_seconds(self.rate),
+            None => self.rate * self.sample_duration,
+        };
+        if self.phase >= 1.0 {
+            self.phase -= floor(self.phase);
+            self.random.advance();
+        }
+        value
+    }
+}
+
+/// Auto-panner. Pans a mono signal back and forth with an LFO, using equal power panning.
+/// The rate is a frequency in Hz or, when synced to a transport, a cycle length in beats.
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: left output
+/// - Output 1: right output
+#[derive(Clone)]
+pub struct AutoPan<T: Real> {
+    lfo: ModLfo,
+    depth: f64,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Real> AutoPan<T> {
+    /// Create auto-panner with a rate of `rate` Hz, pan `depth` in 0...1 and waveform `shape`.
+    pub fn new(rate: f64, depth: f64, shape: LfoShape) -> Self {
+        Self {
+            lfo: ModLfo::new(None, rate, shape),
+            depth,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Create auto-panner with a cycle of `beats` (`beats` > 0) beats at the tempo of `transport`.
+    pub fn new_sync(transport: &Transport, beats: f64, depth: f64, shape: LfoShape) -> Self {
+        assert!(beats > 0.0);
+        Self {
+            lfo: ModLfo::new(Some(transport), beats, shape),
+            depth,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Rate in Hz, or cycle length in beats if synced.
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        self.lfo.rate
+    }
+
+    /// Set rate in Hz, or cycle length in beats if synced.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f64) {
+        self.lfo.rate = rate;
+    }
+
+    /// Pan depth in 0...1.
+    #[inline]
+    pub fn depth(&self) -> f64 {
+        self.depth
+    }
+
+    /// Set pan depth in 0...1.
+    #[inline]
+    pub fn set_depth(&mut self, depth: f64) {
+        self.depth = depth;
+    }
+}
+
+impl<T: Real> AudioNode for AutoPan<T> {
+    const ID: u64 = 143;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U2;
+    type Setting = (f64, f64);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (rate, depth) = setting;
+        self.set_rate(rate);
+        self.set_depth(depth);
+    }
+
+    fn reset(&mut self) {
+        self.lfo.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.lfo.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let (left, right) = pan_weights(T::from_f64(self.depth * self.lfo.tick()));
+        [input[0] * left, input[0] * right].into()
+    }
+
+    fn set_hash(&mut self, hash: u64) {
+        self.lfo.random.set_hash(hash);
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output[1] = input[0].distort(0.0);
+        output
+    }
+}
+
+/// Tremolo. Modulates the amplitude of a signal with a sine LFO.
+/// The gain swings between 1 and 1 - depth.
+/// The rate is a frequency in Hz or, when synced to a transport, a cycle length in beats.
+/// Setting: (rate, depth).
+/// - Input 0: signal
+/// - Output 0: modulated signal
+#[derive(Clone)]
+pub struct Tremolo<T: Real> {
+    lfo: ModLfo,
+    depth: f64,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T: Real> Tremolo<T> {
+    /// Create tremolo with a rate of `rate` Hz and `depth` in 0...1.
+    pub fn new(rate: f64, depth: f64) -> Self {
+        Self {
+            lfo: ModLfo::new(None, rate, LfoShape::Sine),
+            depth,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Create tremolo with a cycle of `beats` (`beats` > 0) beats at the tempo of `transport`.
+    pub fn new_sync(transport: &Transport, beats: f64, depth: f64) -> Self {
+        assert!(beats > 0.0);
+        Self {
+            lfo: ModLfo::new(Some(transport), beats, LfoShape::Sine),
+            depth,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Rate in Hz, or cycle length in beats if synced.
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        self.lfo.rate
+    }
+
+    /// Set rate in Hz, or cycle length in beats if synced.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f64) {
+        self.lfo.rate = rate;
+    }
+
+    /// Modulation depth in 0...1.
+    #[inline]
+    pub fn depth(&self) -> f64 {
+        self.depth
+    }
+
+    /// Set modulation depth in 0...1.
+    #[inline]
+    pub fn set_depth(&mut self, depth: f64) {
+        self.depth = depth;
+    }
+}
+
+impl<T: Real> AudioNode for Tremolo<T> {
+    const ID: u64 = 144;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U1;
+    type Setting = (f64, f64);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (rate, depth) = setting;
+        self.set_rate(rate);
+        self.set_depth(depth);
+    }
+
+    fn reset(&mut self) {
+        self.lfo.reset();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.lfo.sample_duration = 1.0 / sample_rate;
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let gain = 1.0 - self.depth * 0.5 * (1.0 - self.lfo.tick());
+        [input[0] * T::from_f64(gain)].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
@@ -0,0 +1,270 @@
+//! Unison voice stacking.
+
+use super::audionode::*;
+use super::buffer::*;
+use super::math::*;
+use super::pan::pan_weights;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+
+/// Unison stack. Contains `N` copies of an oscillator that are detuned
+/// and spread in the stereo field. Voices are placed evenly from left to right
+/// in order of increasing pitch. The output is scaled by `1 / sqrt(N)`.
+/// Setting: (detune in cents, stereo spread in 0...1).
+/// - Input 0: frequency in Hz
+/// - Output 0: left output
+/// - Output 1: right output
+///
+/// The oscillator has one input (frequency in Hz) and one output.
+#[derive(Clone)]
+pub struct Unison<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Real,
+    X: AudioNode<Sample = T, Inputs = U1, Outputs = U1>,
+{
+    x: Frame<X, N>,
+    /// Total detune from the lowest to the highest voice in cents.
+    detune: T,
+    /// Stereo spread in 0...1.
+    spread: T,
+    /// Frequency ratios of voices.
+    ratio: Frame<T, N>,
+    /// Left channel weights of voices.
+    left: Frame<T, N>,
+    /// Right channel weights of voices.
+    right: Frame<T, N>,
+    input: Buffer<T>,
+    output: Buffer<T>,
+}
+
+impl<N, T, X> Unison<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Real,
+    X: AudioNode<Sample = T, Inputs = U1, Outputs = U1>,
+{
+    /// Create unison stack from voices. Total detune from the lowest
+    /// to the highest voice is `detune` cents and stereo spread is `spread` in 0...1.
+    pub fn new(detune: T, spread: T, x: Frame<X, N>) -> Self {
+        let mut node = Self {
+            x,
+            detune,
+            spread,
+            ratio: Frame::splat(T::one()),
+            left: Frame::splat(T::zero()),
+            right: Frame::splat(T::zero()),
+            input: Buffer::with_channels(1),
+            output: Buffer::with_channels(1),
+        };
+        node.update();
+        let hash = node.ping(true, AttoHash::new(Self::ID));
+        node.ping(false, hash);
+        node
+    }
+
+    /// Total detune from the lowest to the highest voice in cents.
+    #[inline]
+    pub fn detune(&self) -> T {
+        self.detune
+    }
+
+    /// Set total detune from the lowest to the highest voice in cents.
+    #[inline]
+    pub fn set_detune(&mut self, detune: T) {
+        self.detune = detune;
+        self.update();
+    }
+
+    /// Stereo spread in 0...1.
+    #[inline]
+    pub fn spread(&self) -> T {
+        self.spread
+    }
+
+    /// Set stereo spread in 0...1.
+    #[inline]
+    pub fn set_spread(&mut self, spread: T) {
+        self.spread = spread;
+        self.update();
+    }
+
+    /// Access a contained voice.
+    #[inline]
+    pub fn node_mut(&mut self, index: usize) -> &mut X {
+        &mut self.x[index]
+    }
+
+    /// Access a contained voice.
+    #[inline]
+    pub fn node(&self, index: usize) -> &X {
+        &self.x[index]
+    }
+
+    /// Recalculate voice ratios and weights.
+    fn update(&mut self) {
+        let gain = T::one() / sqrt(T::new(N::USIZE as i64));
+        for i in 0..N::USIZE {
+            // Position of the voice in -1...1.
+            let position = if N::USIZE > 1 {
+                T::from_f64(2.0 * i as f64 / (N::USIZE - 1) as f64 - 1.0)
+            } else {
+                T::zero()
+            };
+            let half = T::from_f64(0.5);
+            self.ratio[i] = exp2(position * half * self.detune / T::new(1200));
+            let (left, right) = pan_weights(position * clamp01(self.spread));
+            self.left[i] = left * gain;
+            self.right[i] = right * gain;
+        }
+    }
+}
+
+impl<N, T, X> AudioNode for Unison<N, T, X>
+where
+    N: Size<T>,
+    N: Size<X>,
+    T: Real,
+    X: AudioNode<Sample = T, Inputs = U1, Outputs = U1>,
+{
+    const ID: u64 = 108;
+    type Sample = T;
+    type Inputs = U1;
+    type Outputs = U2;
+    type Setting = (T, T);
+
+    fn set(&mut self, setting: Self::Setting) {
+        let (detune, spread) = setting;
+        self.detune = detune;
+        self.spread = spread;
+        self.update();
+    }
+
+    fn reset(&mut self) {
+        self.x.iter_mut().for_each(|node| node.reset());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.x
+            .iter_mut()
+            .for_each(|node| node.set_sample_rate(sample_rate));
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let mut left = T::zero();
+        let mut right = T::zero();
+        for (i, x) in self.x.iter_mut().enumerate() {
+            let y = x.tick(&[input[0] * self.ratio[i]].into())[0];
+            left += y * self.left[i];
+            right += y * self.right[i];
+        }
+        [left, right].into()
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        output[0][..size].fill(T::zero());
+        output[1][..size].fill(T::zero());
+        for (i, x) in self.x.iter_mut().enumerate() {
+            let ratio = self.ratio[i];
+            for (y, f) in self.input.get_mut(1)[0][..size]
+                .iter_mut()
+                .zip(input[0][..size].iter())
+            {
+                *y = *f * ratio;
+            }
+            x.process(size, self.input.self_ref(), self.output.get_mut(1));
+            let (left, right) = (self.left[i], self.right[i]);
+            for (j, y) in self.output.at(0)[..size].iter().enumerate() {
+                output[0][j] += *y * left;
+                output[1][j] += *y * right;
+            }
+        }
+    }
+
+    #[inline]
+    fn ping(&mut self, probe: bool, hash: AttoHash) -> AttoHash {
+        // Each voice receives a different hash, which randomizes oscillator phases.
+        let mut hash = hash.hash(Self::ID);
+        for x in &mut self.x {
+            hash = x.ping(probe, hash);
+        }
+        hash
+    }
+
+    fn route(&mut self, _input: &SignalFrame, frequency: f64) -> SignalFrame {
+        let input = new_signal_frame(1);
+        let mut output = new_signal_frame(2);
+        for i in 0..N::USIZE {
+            let output_i = self.x[i].route(&input, frequency);
+            let left = output_i[0].scale(self.left[i].to_f64());
+            let right = output_i[0].scale(self.right[i].to_f64());
+            if i == 0 {
+                output[0] = left;
+                output[1] = right;
+            } else {
+                output[0] = output[0].combine_linear(left, 0.0, |x, y| x + y, |x, y| x + y);
+                output[1] = output[1].combine_linear(right, 0.0, |x, y| x + y, |x, y| x + y);
+            }
+        }
+        output
+    }
+
+    fn allocate(&mut self) {
+        self.input.allocate(1);
+        self.output.allocate(1);
+        for x in &mut self.x {
+            x.allocate();
+        }
+    }
+
+    fn describe(&mut self) -> NodeInfo {
+        let children = self.x.iter_mut().map(|x| x.describe()).collect();
+        NodeInfo::from_node(self).with_children(children)
+    }
+
+    fn parameters(&self) -> Vec<ParameterInfo> {
+        // Voice parameters follow detune and spread. All voices share them.
+        let mut parameters = vec![
+            ParameterInfo::new("detune", ParameterUnit::Cents, 0.0, 100.0, 0.0),
+            ParameterInfo::new("spread", ParameterUnit::Unitless, 0.0, 1.0, 0.0),
+        ];
+        parameters.extend(self.x[0].parameters());
+        parameters
+    }
+
+    fn parameter_count(&self) -> usize {
+        2 + self.x[0].parameter_count()
+    }
+
+    fn parameter(&self, index: usize) -> f64 {
+        match index {
+            0 => self.detune.to_f64(),
+            1 => self.spread.to_f64(),
+            _ => self.x[0].parameter(index - 2),
+        }
+    }
+
+    fn set_parameter(&mut self, index: usize, value: f64) {
+        match index {
+            0 => self.set_detune(T::from_f64(value)),
+            1 => self.set_spread(T::from_f64(value)),
+            _ => {
+                for x in self.x.iter_mut() {
+                    x.set_parameter(index - 2, value);
+                }
+            }
+        }
+    }
+}
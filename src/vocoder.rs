@@ -0,0 +1,128 @@
+//! Channel vocoder.
+
+use super::audionode::*;
+use super::filter::*;
+use super::math::*;
+use super::signal::*;
+use super::*;
+use numeric_array::typenum::*;
+
+/// Lowest vocoder band center frequency in Hz.
+const LOWEST_BAND: f64 = 100.0;
+/// Highest vocoder band center frequency in Hz.
+const HIGHEST_BAND: f64 = 8000.0;
+
+/// Channel vocoder. Imposes the spectral envelope of a modulator on a carrier.
+/// Both inputs are split into logarithmically spaced bands with bandpass filters.
+/// The level of each modulator band, tracked with an envelope follower,
+/// sets the gain of the corresponding carrier band.
+/// - Input 0: carrier
+/// - Input 1: modulator
+/// - Output 0: vocoded signal
+#[derive(Clone)]
+pub struct Vocoder<T: Float, F: Real> {
+    carrier: Vec<Biquad<T, F>>,
+    modulator: Vec<Biquad<T, F>>,
+    envelope: Vec<F>,
+    /// Per-sample envelope smoothing factor.
+    smoothing: F,
+    sample_rate: f64,
+}
+
+impl<T: Float, F: Real> Vocoder<T, F> {
+    /// Create a vocoder with `bands` bands (`bands` > 0).
+    pub fn new(bands: usize) -> Self {
+        assert!(bands > 0);
+        let mut node = Self {
+            carrier: vec![Biquad::new(); bands],
+            modulator: vec![Biquad::new(); bands],
+            envelope: vec![F::zero(); bands],
+            smoothing: F::zero(),
+            sample_rate: DEFAULT_SR,
+        };
+        node.set_sample_rate(DEFAULT_SR);
+        node
+    }
+
+    /// Number of bands.
+    #[inline]
+    pub fn bands(&self) -> usize {
+        self.carrier.len()
+    }
+
+    /// Center frequency of band `i` in Hz.
+    pub fn center(&self, i: usize) -> f64 {
+        let bands = self.bands();
+        if bands == 1 {
+            sqrt(LOWEST_BAND * HIGHEST_BAND)
+        } else {
+            LOWEST_BAND * pow(HIGHEST_BAND / LOWEST_BAND, i as f64 / (bands - 1) as f64)
+        }
+    }
+}
+
+impl<T: Float, F: Real> AudioNode for Vocoder<T, F> {
+    const ID: u64 = 93;
+    type Sample = T;
+    type Inputs = U2;
+    type Outputs = U1;
+    type Setting = ();
+
+    fn reset(&mut self) {
+        for i in 0..self.bands() {
+            self.carrier[i].reset();
+            self.modulator[i].reset();
+            self.envelope[i] = F::zero();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        // Envelope followers move halfway to a new level in 5 ms.
+        self.smoothing = F::from_f64(pow(0.5, 1.0 / (0.005 * sample_rate)));
+        let bands = self.bands();
+        // Bands meet halfway between adjacent centers on a logarithmic scale.
+        let ratio = if bands == 1 {
+            HIGHEST_BAND / LOWEST_BAND
+        } else {
+            pow(HIGHEST_BAND / LOWEST_BAND, 1.0 / (bands - 1) as f64)
+        };
+        for i in 0..bands {
+            let center = min(self.center(i), sample_rate * 0.45);
+            let q = 1.0 / (sqrt(ratio) - 1.0 / sqrt(ratio));
+            let coefs = BiquadCoefs::bandpass(
+                F::from_f64(sample_rate),
+                F::from_f64(center),
+                F::from_f64(q),
+            );
+            self.carrier[i].set_coefs(coefs);
+            self.modulator[i].set_coefs(coefs);
+            self.carrier[i].set_sample_rate(sample_rate);
+            self.modulator[i].set_sample_rate(sample_rate);
+        }
+    }
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        let carrier = Frame::from([input[0]]);
+        let modulator = Frame::from([input[1]]);
+        let mut output = F::zero();
+        for i in 0..self.bands() {
+            let c: F = convert(self.carrier[i].tick(&carrier)[0]);
+            let m: F = convert(self.modulator[i].tick(&modulator)[0]);
+            self.envelope[i] = self.smoothing * self.envelope[i] + (F::one() - self.smoothing) * abs(m);
+            output += c * self.envelope[i];
+        }
+        // The envelope of a sine wave averages to 2 / pi of its amplitude.
+        [convert(output * F::from_f64(PI * 0.5))].into()
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        let mut output = new_signal_frame(self.outputs());
+        output[0] = input[0].distort(0.0);
+        output
+    }
+}
@@ -4,20 +4,29 @@ use super::audionode::*;
 use super::audiounit::*;
 use super::combinator::*;
 use super::math::*;
+#[cfg(feature = "std")]
+use super::signal::*;
 use super::*;
+use alloc::sync::Arc;
+use core::marker::PhantomData;
 use duplicate::duplicate_item;
 use numeric_array::typenum::Unsigned;
 use numeric_array::*;
 use rsor::Slice;
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
 use std::io::prelude::*;
+#[cfg(feature = "std")]
 use std::io::BufWriter;
-use std::marker::PhantomData;
+#[cfg(feature = "std")]
 use std::path::Path;
-use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
 
 /// Write a 32-bit value to a WAV file.
 #[inline]
+#[cfg(feature = "std")]
 fn write32<W: Write>(writer: &mut W, x: u32) -> std::io::Result<()> {
     // WAV files are little endian.
     writer.write_all(&[x as u8, (x >> 8) as u8, (x >> 16) as u8, (x >> 24) as u8])?;
@@ -26,12 +35,14 @@ fn write32<W: Write>(writer: &mut W, x: u32) -> std::io::Result<()> {
 
 /// Write a 16-bit value to a WAV file.
 #[inline]
+#[cfg(feature = "std")]
 fn write16<W: Write>(writer: &mut W, x: u16) -> std::io::Result<()> {
     writer.write_all(&[x as u8, (x >> 8) as u8])?;
     std::io::Result::Ok(())
 }
 
 // Write WAV header, including the header of the data block.
+#[cfg(feature = "std")]
 fn write_wav_header<W: Write>(
     writer: &mut W,
     data_length: usize,
@@ -400,32 +411,77 @@ impl Wave48 {
     /// wave.fade_in(1.0);
     /// ```
     pub fn fade_in(&mut self, time: f64) {
+        self.fade_in_curve(time, smooth5);
+    }
+
+    /// Applies a fade-out envelope to the wave with a duration of `time` seconds.
+    /// The duration may not exceed the duration of the wave.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::render(44100.0, 10.0, &mut(brown() | brown()));
+    /// wave.fade_out(5.0);
+    /// ```
+    pub fn fade_out(&mut self, time: f64) {
+        self.fade_out_curve(time, smooth5);
+    }
+
+    /// Applies both fade-in and fade-out to the wave with a duration of `time` seconds.
+    /// The duration may not exceed the duration of the wave.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::render(44100.0, 10.0, &mut(pink() | pink()));
+    /// wave.fade(1.0);
+    /// ```
+    pub fn fade(&mut self, time: f64) {
+        self.fade_in(time);
+        self.fade_out(time);
+    }
+
+    /// Applies a fade-in envelope shaped by easing function `curve` to the wave
+    /// with a duration of `time` seconds. The curve maps 0...1 to 0...1,
+    /// for example, `smooth5` (the default of `fade_in`), `uparc` or `|x| x`.
+    /// The duration may not exceed the duration of the wave.
+    ///
+    /// ### Example: Linear Fade-In
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::render(44100.0, 1.0, &mut (white()));
+    /// wave.fade_in_curve(0.1, |x| x);
+    /// ```
+    pub fn fade_in_curve(&mut self, time: f64, curve: impl Fn(f64) -> f64) {
         assert!(time <= self.duration());
         let fade_n = round(time * self.sample_rate());
         for i in 0..fade_n as usize {
-            let a = smooth5((i + 1) as f64 / (fade_n + 1.0)) as f48;
+            let a = curve((i + 1) as f64 / (fade_n + 1.0)) as f48;
             for channel in 0..self.channels() {
                 self.set(channel, i, self.at(channel, i) * a);
             }
         }
     }
 
-    /// Applies a fade-out envelope to the wave with a duration of `time` seconds.
+    /// Applies a fade-out envelope shaped by easing function `curve` to the wave
+    /// with a duration of `time` seconds. The curve maps 0...1 to 0...1 and is applied
+    /// in reverse, for example, `smooth5` (the default of `fade_out`), `uparc` or `|x| x`.
     /// The duration may not exceed the duration of the wave.
     ///
-    /// ### Example
-    ///
+    /// ### Example: Linear Fade-Out
     /// ```
     /// use fundsp::hacker::*;
-    /// let mut wave = Wave64::render(44100.0, 10.0, &mut(brown() | brown()));
-    /// wave.fade_out(5.0);
+    /// let mut wave = Wave64::render(44100.0, 1.0, &mut (white()));
+    /// wave.fade_out_curve(0.1, |x| x);
     /// ```
-    pub fn fade_out(&mut self, time: f64) {
+    pub fn fade_out_curve(&mut self, time: f64, curve: impl Fn(f64) -> f64) {
         assert!(time <= self.duration());
         let fade_n = round(time * self.sample_rate());
         let fade_i = fade_n as usize;
         for i in 0..fade_i {
-            let a = smooth5((fade_n - i as f64) / (fade_n + 1.0)) as f48;
+            let a = curve((fade_n - i as f64) / (fade_n + 1.0)) as f48;
             let sample = self.len() - fade_i + i;
             for channel in 0..self.channels() {
                 self.set(channel, sample, self.at(channel, sample) * a);
@@ -433,19 +489,239 @@ impl Wave48 {
         }
     }
 
-    /// Applies both fade-in and fade-out to the wave with a duration of `time` seconds.
-    /// The duration may not exceed the duration of the wave.
+    /// Reverses the wave in-place, for reverse playback.
     ///
     /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::from_samples(44100.0, &[1.0, 2.0, 3.0]);
+    /// wave.reverse();
+    /// assert!(wave.at(0, 0) == 3.0 && wave.at(0, 2) == 1.0);
+    /// ```
+    pub fn reverse(&mut self) {
+        for channel in self.vec.iter_mut() {
+            channel.reverse();
+        }
+    }
+
+    /// Returns a new wave containing the samples in `range` of this wave.
     ///
+    /// ### Example
     /// ```
     /// use fundsp::hacker::*;
-    /// let mut wave = Wave64::render(44100.0, 10.0, &mut(pink() | pink()));
-    /// wave.fade(1.0);
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(110.0) | sine_hz(220.0)));
+    /// let region = wave.slice(4410..8820);
+    /// assert!(region.channels() == 2 && region.duration() == 0.1);
+    /// assert!(region.at(1, 0) == wave.at(1, 4410));
     /// ```
-    pub fn fade(&mut self, time: f64) {
-        self.fade_in(time);
-        self.fade_out(time);
+    pub fn slice(&self, range: impl core::ops::RangeBounds<usize>) -> Self {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        let mut wave = Self::new(0, self.sample_rate());
+        for channel in self.vec.iter() {
+            wave.push_channel(&channel[..self.len()][range]);
+        }
+        wave
+    }
+
+    /// Appends `other` to the end of this wave. The number of channels must match.
+    /// The sample rate of `other` is assumed to be the same.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::zero(2, 44100.0, 1.0);
+    /// wave.append(&Wave64::zero(2, 44100.0, 0.5));
+    /// assert!(wave.duration() == 1.5);
+    /// ```
+    pub fn append(&mut self, other: &Self) {
+        assert!(self.channels() == other.channels());
+        for (channel, samples) in self.vec.iter_mut().zip(other.vec.iter()) {
+            channel.extend_from_slice(&samples[..other.len()]);
+        }
+        self.len += other.len();
+    }
+
+    /// Mixes (adds) `other` into this wave starting at `time` seconds
+    /// (rounded to the nearest sample), extending this wave if necessary.
+    /// A mono `other` is mixed into every channel; otherwise, the number of channels must match.
+    /// The sample rate of `other` is assumed to be the same.
+    ///
+    /// ### Example: Layer A Sound Half A Second In
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::zero(2, 44100.0, 1.0);
+    /// wave.mix_at(0.5, &Wave64::render(44100.0, 1.0, &mut (sine_hz(440.0))));
+    /// assert!(wave.duration() == 1.5);
+    /// ```
+    pub fn mix_at(&mut self, time: f64, other: &Self) {
+        assert!(other.channels() == 1 || other.channels() == self.channels());
+        let start = round(max(0.0, time) * self.sample_rate()) as usize;
+        if start + other.len() > self.len() {
+            self.resize(start + other.len());
+        }
+        for channel in 0..self.channels() {
+            let source = if other.channels() == 1 { 0 } else { channel };
+            for (x, y) in self.vec[channel][start..]
+                .iter_mut()
+                .zip(other.vec[source][..other.len()].iter())
+            {
+                *x += *y;
+            }
+        }
+    }
+
+    /// Multiplies the wave by `db` decibels of gain.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let mut wave = Wave64::from_samples(44100.0, &[1.0, -1.0]);
+    /// wave.gain_db(-6.0);
+    /// assert!((wave.amplitude() - db_amp(-6.0)).abs() < 1.0e-6);
+    /// ```
+    pub fn gain_db(&mut self, db: f64) {
+        let gain = db_amp(db) as f48;
+        for channel in self.vec.iter_mut() {
+            channel.iter_mut().for_each(|x| *x *= gain);
+        }
+    }
+
+    /// Splits the wave into mono waves, one for each channel.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(110.0) | sine_hz(220.0)));
+    /// let channels = wave.split_channels();
+    /// assert!(channels.len() == 2 && channels[1].at(0, 100) == wave.at(1, 100));
+    /// ```
+    pub fn split_channels(&self) -> Vec<Self> {
+        self.vec
+            .iter()
+            .map(|channel| Self::from_samples(self.sample_rate(), &channel[..self.len()]))
+            .collect()
+    }
+
+    /// Merges the channels of `waves` into a single multichannel wave.
+    /// All waves must have the same length. The sample rate is taken from the first wave.
+    ///
+    /// ### Example: Make A Stereo Wave From Two Mono Waves
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let left = Wave64::render(44100.0, 1.0, &mut (sine_hz(110.0)));
+    /// let right = Wave64::render(44100.0, 1.0, &mut (sine_hz(220.0)));
+    /// let stereo = Wave64::merge_channels(&[left, right]);
+    /// assert!(stereo.channels() == 2 && stereo.duration() == 1.0);
+    /// ```
+    pub fn merge_channels(waves: &[Self]) -> Self {
+        let sample_rate = waves.first().map_or(DEFAULT_SR, |wave| wave.sample_rate());
+        let mut merged = Self::new(0, sample_rate);
+        for wave in waves {
+            for channel in wave.vec.iter() {
+                merged.push_channel(&channel[..wave.len()]);
+            }
+        }
+        merged
+    }
+
+    /// Sample `i` averaged over channels.
+    #[inline]
+    fn mono_at(&self, i: usize) -> f64 {
+        let mut sum = 0.0;
+        for channel in self.vec.iter() {
+            sum += channel[i] as f64;
+        }
+        sum / self.channels() as f64
+    }
+
+    /// Finds the rising zero crossing nearest to sample index `near`.
+    /// A rising zero crossing is an index `i` where sample `i - 1` is negative and sample `i`
+    /// is non-negative. Channels are averaged. Returns `None` if there are no zero crossings.
+    ///
+    /// ### Example
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::from_samples(44100.0, &[0.5, -0.5, -0.2, 0.3, 0.7, -0.1, 0.2]);
+    /// assert_eq!(wave.find_zero_crossing(4), Some(3));
+    /// assert_eq!(wave.find_zero_crossing(6), Some(6));
+    /// ```
+    pub fn find_zero_crossing(&self, near: usize) -> Option<usize> {
+        if self.channels() == 0 {
+            return None;
+        }
+        let is_crossing = |i: usize| self.mono_at(i - 1) < 0.0 && self.mono_at(i) >= 0.0;
+        let near = clamp(1, max(1, self.len()) - 1, near);
+        for distance in 0..self.len() {
+            if near > distance && is_crossing(near - distance) {
+                return Some(near - distance);
+            }
+            if near + distance < self.len() && is_crossing(near + distance) {
+                return Some(near + distance);
+            }
+            if near < distance + 1 && near + distance >= self.len() {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Finds loop points for seamless looping near `start` and `end` (`start` < `end`),
+    /// searching rising zero crossings within `radius` samples of each.
+    /// The loop plays samples from `start` up to but not including `end`.
+    /// The pair of loop points is chosen to minimize the mismatch between the waveform
+    /// around the loop end and around the loop start, which avoids both clicks
+    /// and sudden changes in spectral content at the loop boundary.
+    /// Channels are averaged. Returns `None` if no suitable zero crossings are found.
+    ///
+    /// ### Example: Find A Loop In A Sustained Tone
+    /// ```
+    /// use fundsp::hacker::*;
+    /// let wave = Wave64::render(44100.0, 1.0, &mut (sine_hz(441.0)));
+    /// let (start, end) = wave.find_loop(10000, 30050, 200).unwrap();
+    /// assert!((end - start + 1) % 100 <= 2);
+    /// ```
+    pub fn find_loop(&self, start: usize, end: usize, radius: usize) -> Option<(usize, usize)> {
+        assert!(start < end);
+        if self.channels() == 0 || self.len() < 2 {
+            return None;
+        }
+        // Half width of the comparison window in samples.
+        const WINDOW: usize = 128;
+        let crossings = |center: usize| -> Vec<usize> {
+            let first = max(1, center.saturating_sub(radius));
+            let last = min(self.len() - 1, center + radius);
+            (first..=last)
+                .filter(|&i| self.mono_at(i - 1) < 0.0 && self.mono_at(i) >= 0.0)
+                .collect()
+        };
+        let starts = crossings(start);
+        let ends = crossings(end);
+        let mut best: Option<(usize, usize)> = None;
+        let mut best_error = f64::INFINITY;
+        for &s in starts.iter() {
+            for &e in ends.iter() {
+                if e <= s {
+                    continue;
+                }
+                // Compare the waveform continuing past the loop end with the loop start.
+                let from = min(min(s, e), WINDOW);
+                let to = min(self.len() - e, WINDOW);
+                let mut error = 0.0;
+                let mut weight = 0.0;
+                for k in 0..from + to {
+                    let w = 1.0 - abs(k as f64 - from as f64) / (WINDOW + 1) as f64;
+                    let x = self.mono_at(e + k - from) - self.mono_at(s + k - from);
+                    error += w * x * x;
+                    weight += w;
+                }
+                let error = error / max(weight, 1.0e-9);
+                if error < best_error {
+                    best_error = error;
+                    best = Some((s, e));
+                }
+            }
+        }
+        best
     }
 
     /// Render wave with length `duration` seconds from generator `node`.
@@ -623,6 +899,7 @@ impl Wave48 {
 
     /// Write the wave as a 16-bit WAV to a buffer.
     /// Individual samples are clipped to the range -1...1.
+    #[cfg(feature = "std")]
     pub fn write_wav16<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
         assert!(self.channels() > 0);
         let mut writer = BufWriter::new(writer);
@@ -645,6 +922,7 @@ impl Wave48 {
     /// Write the wave as a 32-bit float WAV to a buffer.
     /// Samples are not clipped to any range but some
     /// applications may expect the range to be -1...1.
+    #[cfg(feature = "std")]
     pub fn write_wav32<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
         assert!(self.channels() > 0);
         let mut writer = BufWriter::new(writer);
@@ -666,6 +944,7 @@ impl Wave48 {
 
     /// Save the wave as a 16-bit WAV file.
     /// Individual samples are clipped to the range -1...1.
+    #[cfg(feature = "std")]
     pub fn save_wav16<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
         assert!(self.channels() > 0);
         let mut file = File::create(path.as_ref())?;
@@ -675,6 +954,7 @@ impl Wave48 {
     /// Save the wave as a 32-bit float WAV file.
     /// Samples are not clipped to any range but some
     /// applications may expect the range to be -1...1.
+    #[cfg(feature = "std")]
     pub fn save_wav32<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
         assert!(self.channels() > 0);
         let mut file = File::create(path.as_ref())?;
@@ -761,4 +1041,93 @@ impl<T: Float> AudioNode for Wave48Player<T> {
             [T::zero()].into()
         }
     }
+
+    fn skip(&mut self, samples: usize, _input: &Frame<Self::Sample, Self::Inputs>) {
+        if self.index < self.end_point {
+            self.index += samples;
+            if self.index >= self.end_point {
+                self.index = match self.loop_point {
+                    Some(point) if point < self.end_point => {
+                        point + (self.index - self.end_point) % (self.end_point - point)
+                    }
+                    _ => self.end_point,
+                };
+            }
+        }
+    }
+}
+
+/// Record input into a growing wave. The input is passed through.
+/// The sample is appended to every channel of the wave.
+/// Recording allocates memory and locks the wave, so it is not real-time safe.
+/// - Input 0: signal to record
+/// - Output 0: signal
+#[cfg(feature = "std")]
+#[duplicate_item(
+    f48       Wave48       Wave48Recorder;
+    [ f64 ]   [ Wave64 ]   [ Wave64Recorder ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Recorder ];
+)]
+#[derive(Clone)]
+pub struct Wave48Recorder<T: Float> {
+    wave: Arc<Mutex<Wave48>>,
+    _marker: PhantomData<T>,
+}
+
+#[cfg(feature = "std")]
+#[duplicate_item(
+    f48       Wave48       Wave48Recorder;
+    [ f64 ]   [ Wave64 ]   [ Wave64Recorder ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Recorder ];
+)]
+impl<T: Float> Wave48Recorder<T> {
+    pub fn new(wave: &Arc<Mutex<Wave48>>) -> Self {
+        Self {
+            wave: wave.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[duplicate_item(
+    f48       Wave48       Wave48Recorder;
+    [ f64 ]   [ Wave64 ]   [ Wave64Recorder ];
+    [ f32 ]   [ Wave32 ]   [ Wave32Recorder ];
+)]
+impl<T: Float> AudioNode for Wave48Recorder<T> {
+    const ID: u64 = 101;
+    type Sample = T;
+    type Inputs = typenum::U1;
+    type Outputs = typenum::U1;
+    type Setting = ();
+
+    #[inline]
+    fn tick(
+        &mut self,
+        input: &Frame<Self::Sample, Self::Inputs>,
+    ) -> Frame<Self::Sample, Self::Outputs> {
+        if let Ok(mut wave) = self.wave.lock() {
+            wave.push(convert::<T, f48>(input[0]));
+        }
+        *input
+    }
+
+    fn process(
+        &mut self,
+        size: usize,
+        input: &[&[Self::Sample]],
+        output: &mut [&mut [Self::Sample]],
+    ) {
+        if let Ok(mut wave) = self.wave.lock() {
+            for x in input[0][..size].iter() {
+                wave.push(convert::<T, f48>(*x));
+            }
+        }
+        output[0][..size].copy_from_slice(&input[0][..size]);
+    }
+
+    fn route(&mut self, input: &SignalFrame, _frequency: f64) -> SignalFrame {
+        input.clone()
+    }
 }
@@ -281,6 +281,15 @@ where
         })
     }
 
+    fn skip(&mut self, samples: usize, input: &Frame<Self::Sample, Self::Inputs>) {
+        // Accumulate phase the same way `tick` does so rounding errors match.
+        let delta = input[0].to_f32() / self.sample_rate;
+        for _ in 0..samples {
+            self.phase += delta;
+            self.phase -= floor(self.phase);
+        }
+    }
+
     fn route(&mut self, _input: &SignalFrame, _frequency: f64) -> SignalFrame {
         let mut output = new_signal_frame(self.outputs());
         for i in 0..N::USIZE {
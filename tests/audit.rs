@@ -0,0 +1,120 @@
+//! Allocation audit tests. These install the auditing allocator for the whole test binary.
+
+#![allow(dead_code)]
+
+use fundsp::hacker::*;
+
+#[global_allocator]
+static ALLOCATOR: AuditAllocator = AuditAllocator;
+
+/// Process `blocks` blocks of output from `unit`, panicking if processing allocates.
+fn process_audited(unit: &mut dyn AudioUnit64, blocks: usize) {
+    let mut output = Buffer::<f64>::with_channels(unit.outputs());
+    for _ in 0..blocks {
+        let _guard = AllocGuard::new();
+        unit.process(MAX_BUFFER_SIZE, &[], output.self_mut());
+    }
+}
+
+#[test]
+fn test_alloc_guard() {
+    // Allocations inside a guarded scope are reported.
+    let result = std::panic::catch_unwind(|| {
+        let _guard = AllocGuard::new();
+        let v = vec![0u8; 256];
+        std::hint::black_box(v);
+    });
+    assert!(result.is_err());
+
+    // Allocations made after a nested guard ends are still seen by the outer guard.
+    let before = audited_allocations();
+    let result = std::panic::catch_unwind(|| {
+        let _outer = AllocGuard::new();
+        {
+            let _inner = AllocGuard::new();
+        }
+        let v = vec![0u8; 256];
+        std::hint::black_box(v);
+    });
+    assert!(result.is_err());
+    assert!(audited_allocations() > before);
+
+    // Allocations outside guarded scopes are not counted.
+    let before = audited_allocations();
+    let v = vec![0u8; 256];
+    std::hint::black_box(v);
+    assert_eq!(audited_allocations(), before);
+
+    // Preallocated buffers cannot grow.
+    let mut buffer = Buffer::<f32>::new();
+    buffer.allocate(2);
+    assert!(buffer.is_allocated());
+    buffer.resize(1);
+    let result = std::panic::catch_unwind(move || buffer.resize(2));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_backend_no_alloc() {
+    // Network backends do not allocate while processing.
+    let mut net = Net64::wrap(Box::new(
+        saw_hz(110.0)
+            >> lowpass_hz(1000.0, 1.0)
+            >> split::<U2>()
+            >> reverb_stereo(10.0, 1.0, 0.5)
+            >> join::<U2>(),
+    ));
+    let mut backend = net.backend();
+    process_audited(&mut backend, 10);
+
+    // Sequencer backends do not allocate while events are pushed, started and retired.
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut backend = sequencer.backend();
+    for i in 0..20 {
+        sequencer.push_relative(
+            0.0,
+            0.002 * (i + 1) as f64,
+            Fade::Smooth,
+            0.001,
+            0.001,
+            Box::new(sine_hz(110.0 * (i + 1) as f64) >> lowpass_hz(2000.0, 1.0)),
+        );
+        process_audited(&mut backend, 2);
+    }
+    process_audited(&mut backend, 20);
+}
+
+#[test]
+fn test_harness_no_alloc() {
+    // The test harness processes varying block sizes under an allocation guard.
+    let harness = Harness64::new(DEFAULT_SR);
+    harness.assert_no_alloc(&mut (saw_hz(110.0) >> lowpass_hz(1000.0, 1.0)), 100);
+    harness.assert_no_alloc(&mut reverb_stereo(10.0, 1.0, 0.5), 100);
+}
+
+#[test]
+#[should_panic]
+fn test_sequencer_capacity() {
+    let mut sequencer = Sequencer64::with_capacity(true, 1, 2);
+    let _backend = sequencer.backend();
+    assert_eq!(sequencer.capacity(), 2);
+    for _ in 0..3 {
+        sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    }
+}
+
+#[test]
+fn test_sequencer_return() {
+    // Events returned from the backend, including those it started with, free up room.
+    let mut sequencer = Sequencer64::with_capacity(false, 1, 2);
+    sequencer.push(0.0, 0.001, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    let mut backend = sequencer.backend();
+    let mut output = Buffer::<f64>::with_channels(1);
+    for _ in 0..4 {
+        for _ in 0..2 {
+            backend.process(MAX_BUFFER_SIZE, &[], output.self_mut());
+        }
+        sequencer.push_relative(0.0, 0.001, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+        sequencer.push_relative(0.0, 0.001, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    }
+}
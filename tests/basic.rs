@@ -14,6 +14,7 @@
 use fundsp::audiounit::*;
 use fundsp::hacker::*;
 use funutd::*;
+use std::sync::{Arc, Mutex};
 
 /// Check that the stereo generator given is rendered identically
 /// via `process` (block processing) and `tick` (single sample processing).
@@ -74,6 +75,27 @@ fn check_wave_filter(input: &Wave64, mut node: impl AudioUnit64) {
     }
 }
 
+/// Check that the stereo filter given is rendered identically
+/// via `process` (block processing) and `process_inplace` (in-place block processing).
+fn check_wave_inplace(input: &Wave64, mut node: impl AudioUnit64) {
+    node.allocate();
+    let wave = input.filter(1.0, &mut node);
+    node.reset();
+    let mut buffer = Buffer::<f64>::with_channels(2);
+    let mut i = 0;
+    while i < input.length() {
+        let size = min(MAX_BUFFER_SIZE, input.length() - i);
+        for channel in 0..2 {
+            buffer.mut_at(channel)[..size].copy_from_slice(&input.channel(channel)[i..i + size]);
+        }
+        node.process_inplace(size, buffer.self_mut());
+        for channel in 0..2 {
+            assert!(buffer.at(channel)[..size] == wave.channel(channel)[i..i + size]);
+        }
+        i += size;
+    }
+}
+
 /// Attempt to test two nodes for equality.
 fn is_equal<X, Y>(rnd: &mut Rnd, x: &mut An<X>, y: &mut An<Y>) -> bool
 where
@@ -153,6 +175,10 @@ fn test_basic() {
     check_wave(noise() >> declick() | noise() + noise());
     check_wave(noise() * noise() | bus::<U4, _, _>(|i| mls_bits(10 + i)));
     check_wave(noise() & noise() | sine_hz(440.0) & -noise());
+    check_wave(velvet(2000.0) + blue() | violet() - white_seed(3));
+    check_wave(
+        lorenz_hz(2.0) * random_walk(1.0, 1.0) | noise() >> sample_hold(100.0) + rossler_hz(3.0),
+    );
     check_wave(
         lfo(|t| xerp(110.0, 220.0, clamp01(t))) >> sine()
             | (envelope(|t| xerp(220.0, 440.0, clamp01(t))) >> pass() >> sine()) & mls(),
@@ -273,6 +299,36 @@ fn test_basic() {
         &input,
         resonator_hz(440.0, 110.0) | resonator_hz(880.0, 110.0),
     );
+    check_wave_filter(
+        &input,
+        compensate(pass(), tick() >> lowpole_hz(1000.0)) | compensate(tick() >> tick(), pass()),
+    );
+
+    check_wave_filter(
+        &input,
+        antialias(shape(Shape::Tube(2.0))) | shape_table(&[0.0, 1.0, -1.0, 0.0]),
+    );
+
+    check_wave_filter(&input, vocoder(8) ^ vocoder(1));
+    check_wave_filter(&input, bitcrush(6.5) | decimate(3000.0));
+    check_wave_filter(
+        &input,
+        (pass() | lfo(|t| 2.0 + t)) >> bitcrusher() | (pass() | lfo(|t| 1000.0 + t)) >> decimator(),
+    );
+
+    check_wave_filter(
+        &input,
+        (svf_multi_hz(1000.0, 2.0) >> join::<U4>())
+            | (pass() | lfo(|t| (xerp11(100.0, 5000.0, sin_hz(0.5, t)), 1.0)))
+                >> svf_multi()
+                >> join::<U4>(),
+    );
+
+    check_wave_filter(
+        &input,
+        moog_zdf_hz(1500.0, 0.8)
+            | (pass() | lfo(|t| (xerp11(100.0, 5000.0, sin_hz(0.5, t)), 0.5))) >> moog_zdf(),
+    );
 
     // Constants.
     let mut d = constant(1.0);
@@ -549,6 +605,1565 @@ fn test_basic() {
     assert_eq!(inouts(!-!!!--!!!-!!--!zero()), (0, 0)); // Hot-rodded null unit with a custom hash. Uses more electricity.
 }
 
+#[test]
+fn test_zdf_ladder() {
+    // The zero-delay feedback ladder stays stable under audio-rate cutoff modulation.
+    let input = Wave64::render(44100.0, 1.0, &mut (noise() | noise()));
+    let mut filter = moog_zdf();
+    filter.set_drive(4.0);
+    let mut node = (noise() | sine_hz(3000.0) * 5000.0 + 5100.0 | dc(1.0)) >> filter;
+    for _ in 0..44100 {
+        assert!(node.get_mono().abs() <= 1.0);
+    }
+
+    check_wave_filter(
+        &input,
+        formant(0.3) | (pass() | lfo(|t| sin_hz(0.5, t) * 0.5 + 0.5)) >> formant_morph(),
+    );
+    check_wave_filter(
+        &input,
+        formant_bank(&[500.0, 1500.0], &[5.0, 10.0], &[1.0, 0.5]) | formant(1.0),
+    );
+}
+
+#[test]
+fn test_parametric_eq() {
+    // Parametric equalizer matches a chain of equalizing filters.
+    let mut rnd = Rnd::new();
+    let mut eq = parametric_eq(&[
+        EqBand::lowshelf(200.0, 0.7, 2.0),
+        EqBand::bell(1000.0, 1.0, 0.5),
+        EqBand::highshelf(5000.0, 0.7, 1.5),
+    ]);
+    let mut chain =
+        lowshelf_hz(200.0, 0.7, 2.0) >> bell_hz(1000.0, 1.0, 0.5) >> highshelf_hz(5000.0, 0.7, 1.5);
+    assert!(is_equal(&mut rnd, &mut eq, &mut chain));
+    for f in [50.0, 1000.0, 3000.0, 10000.0] {
+        let (x, y) = (eq.response(0, f).unwrap(), chain.response(0, f).unwrap());
+        assert!((x - y).norm() < 1.0e-9);
+    }
+    eq.set((1, EqBand::bell(2000.0, 2.0, 0.25)));
+    eq.remove_band(0);
+    let mut chain = bell_hz(2000.0, 2.0, 0.25) >> highshelf_hz(5000.0, 0.7, 1.5);
+    eq.reset();
+    assert!(is_equal(&mut rnd, &mut eq, &mut chain));
+}
+
+#[test]
+fn test_svf_multi() {
+    // Multi-mode SVF outputs match the single mode filters.
+    let mut rnd = Rnd::new();
+    assert!(is_equal(
+        &mut rnd,
+        &mut (svf_multi_hz(1000.0, 2.0) >> (pass() | sink() | sink() | sink())),
+        &mut lowpass_hz(1000.0, 2.0)
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut (svf_multi_hz(1000.0, 2.0) >> (sink() | pass() | sink() | sink())),
+        &mut highpass_hz(1000.0, 2.0)
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut (svf_multi_hz(1000.0, 2.0) >> (sink() | sink() | pass() | sink())),
+        &mut bandpass_hz(1000.0, 2.0)
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut (svf_multi_hz(1000.0, 2.0) >> (sink() | sink() | sink() | pass())),
+        &mut notch_hz(1000.0, 2.0)
+    ));
+}
+
+#[test]
+fn test_formant() {
+    // Formant filters have the given gain at their center frequencies.
+    let mut bank = formant_bank(&[1000.0], &[10.0], &[0.5]);
+    assert!((bank.response(0, 1000.0).unwrap().norm() - 0.5).abs() < 1.0e-6);
+    let mut vowel_a = formant(0.0);
+    assert!((vowel_a.response(0, 600.0).unwrap().norm() - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn test_seeded_noise() {
+    // Explicitly seeded noise is independent of graph structure.
+    let mut seeded1 = white_seed(7) | pink_seed(8);
+    let mut seeded2 = (white_seed(7) >> pass()) | (pink_seed(8) >> pass() * 1.0);
+    let mut seeded3 = white_seed(9) | pink_seed(8);
+    let mut differ = false;
+    for _ in 0..1000 {
+        let (x1, y1) = seeded1.get_stereo();
+        let (x2, y2) = seeded2.get_stereo();
+        let (x3, _) = seeded3.get_stereo();
+        assert_eq!((x1, y1), (x2, y2));
+        differ |= x1 != x3;
+    }
+    assert!(differ);
+
+    // Velvet noise has one impulse of -1 or 1 per grid period.
+    let mut node = velvet_seed(1000.0, 1);
+    let mut impulses = 0;
+    for _ in 0..44100 {
+        let x = node.get_mono();
+        assert!(x == 0.0 || x == 1.0 || x == -1.0);
+        impulses += (x != 0.0) as usize;
+    }
+    assert!((999..=1001).contains(&impulses));
+}
+
+#[test]
+fn test_seeded_sequencer() {
+    // Seeded sequencers render reproducibly, also after a reset.
+    let render = |seed: u64| {
+        let mut sequencer = Sequencer64::new(true, 1);
+        sequencer.set_rng_seed(seed);
+        sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(noise()));
+        sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(noise()));
+        let first: Vec<f64> = (0..100).map(|_| sequencer.get_mono()).collect();
+        sequencer.reset();
+        let second: Vec<f64> = (0..100).map(|_| sequencer.get_mono()).collect();
+        assert_eq!(first, second);
+        first
+    };
+    assert_eq!(render(5), render(5));
+    assert_ne!(render(5), render(6));
+    assert_eq!(noise_seed(3).get_mono(), white_seed(3).get_mono());
+}
+
+#[test]
+fn test_random_walk() {
+    // Random walk stays within its range.
+    let mut walk = random_walk(10.0, 0.5);
+    let mut moved = false;
+    let start = walk.get_mono();
+    for _ in 0..44100 {
+        let x = walk.get_mono();
+        assert!((-0.5..=0.5).contains(&x));
+        moved |= x != start;
+    }
+    assert!(moved);
+
+    // Sample-and-hold holds values for the sampling period.
+    let mut held = lfo(|t| t) >> sample_hold(10.0);
+    let mut changes = 0;
+    let mut previous = held.get_mono();
+    for _ in 0..44100 {
+        let x = held.get_mono();
+        changes += (x != previous) as usize;
+        previous = x;
+    }
+    assert!((9..=11).contains(&changes));
+}
+
+#[test]
+fn test_patterns() {
+    // Pattern generators.
+    let tresillo = Pattern::euclid(3, 8, 0);
+    assert_eq!(tresillo.generate(1, 0), vec![0, 3, 6]);
+    assert_eq!(Pattern::euclid(3, 8, 3).generate(1, 0), vec![0, 3, 5]);
+    let coin = Pattern::new(&[0.5; 16]);
+    assert_eq!(coin.generate(8, 1), coin.generate(8, 1));
+    assert_ne!(coin.generate(8, 1), coin.generate(8, 2));
+    assert!((32..=96).contains(&coin.generate(8, 1).len()));
+    let chain = Markov::new(vec![vec![0.0, 1.0], vec![1.0, 1.0]]);
+    assert_eq!(chain.probability(1, 0), 0.5);
+    let states = chain.generate(0, 100, 3);
+    assert!(states.windows(2).all(|pair| pair[0] == 1 || pair[1] == 1));
+    let mut sequencer = Sequencer64::new(true, 2);
+    let ids = tresillo.schedule(&mut sequencer, 0.0, 0.05, 2, 0, |step| {
+        Box::new(sine_hz(110.0 * (step + 1) as f64) | noise())
+    });
+    assert_eq!(ids.len(), 6);
+    chain.schedule(&mut sequencer, 0.1, 0.1, 0, 4, 0, |state| {
+        Box::new(noise() | sine_hz(220.0 * (state + 1) as f64))
+    });
+    check_wave(sequencer);
+}
+
+#[test]
+fn test_record() {
+    // Recording and capturing pass input through.
+    let wave = Arc::new(Mutex::new(Wave64::new(1, 44100.0)));
+    let buffer = Arc::new(CaptureBuffer::new(1000));
+    let mut node = white_seed(1) >> record64(&wave) >> capture(&buffer);
+    let mut source = white_seed(1);
+    let recorded: Vec<f64> = (0..1500).map(|_| node.get_mono()).collect();
+    let source_output: Vec<f64> = (0..1500).map(|_| source.get_mono()).collect();
+    assert_eq!(recorded, source_output);
+    assert_eq!(wave.lock().unwrap().channel(0), &source_output);
+    assert!(buffer.len() == 1000 && buffer.overflow());
+    assert_eq!(buffer.at(999), source_output[999] as f32);
+    buffer.clear();
+    assert!(buffer.is_empty() && !buffer.overflow());
+}
+
+#[test]
+fn test_looper() {
+    // Looper plays back the recorded loop, overdubs, and varies speed.
+    let mut node = looper(1.0);
+    node.set(LoopState::Record);
+    for i in 0..1000 {
+        node.filter_mono(i as f64);
+    }
+    node.set(LoopState::Play);
+    let fade = (0.01 * DEFAULT_SR).round() as usize;
+    for i in 0..1000 {
+        let x = node.filter_mono(1000.0 + i as f64);
+        // The start of the loop is crossfaded with material recorded past the loop end.
+        if i >= fade {
+            assert_eq!(x, i as f64);
+        } else {
+            assert!(x >= i as f64 && x <= 1000.0 + i as f64);
+        }
+    }
+    node.set(LoopState::Overdub);
+    for _ in 0..1000 {
+        node.filter_mono(1.0);
+    }
+    node.set(LoopState::Play);
+    for i in 0..1000 {
+        let x = node.filter_mono(0.0);
+        if i >= fade {
+            assert_eq!(x, i as f64 + 1.0);
+        }
+    }
+    node.set(LoopState::Clear);
+    assert_eq!(node.filter_mono(1.0), 0.0);
+    let gate = lfo(|t: f64| if t % 0.25 < 0.1 { 1.0 } else { 0.0 });
+    check_wave(
+        (noise() | gate.clone() | dc(1.0)) >> looper_gate(0.5)
+            | (noise() | gate | dc(-0.5)) >> looper_gate(0.5),
+    );
+}
+
+#[test]
+fn test_scope() {
+    // Scope snapshots of a periodic signal are stabilized by the trigger.
+    let (mut display, backend) = scope(50);
+    let mut node = sine_hz(441.0) >> backend;
+    let mut snapshots = Vec::new();
+    for _ in 0..10 {
+        for _ in 0..1000 {
+            node.get_mono();
+        }
+        if display.update() {
+            snapshots.push(display.snapshot());
+        }
+    }
+    assert_eq!(snapshots.len(), 10);
+    assert!(snapshots[0][0] >= 0.0 && snapshots[0][0] < 0.1 && snapshots[0][10] > 0.5);
+    for pair in snapshots.windows(2) {
+        assert!(pair[0]
+            .iter()
+            .zip(&pair[1])
+            .all(|(x, y)| (x - y).abs() < 1.0e-3));
+    }
+    assert!(!display.update());
+    // Without trigger, the scope runs free.
+    let (mut display, backend) = scope(50);
+    let mut node = dc(-1.0) >> backend;
+    for _ in 0..200 {
+        node.get_mono();
+    }
+    assert!(display.update());
+    assert_eq!(display.snapshot(), vec![-1.0; 50]);
+    // Clones of the backend take turns with the same buffers.
+    let (mut display, backend) = scope(50);
+    let mut nodes = [dc(1.0) >> backend.clone(), dc(2.0) >> backend];
+    for i in 0..10 {
+        for _ in 0..100 {
+            nodes[i % 2].get_mono();
+        }
+        assert!(display.update());
+        assert_eq!(display.snapshot(), vec![(i % 2 + 1) as f32; 50]);
+    }
+
+    // Triggers report rising edges with sample accuracy.
+    let (mut triggers, backend) = trigger(0.5);
+    let ramp = lfo(|t: f64| (t * 10.0) % 1.0);
+    let mut node = ramp >> backend;
+    let wave = Wave64::render(44100.0, 1.0, &mut node);
+    let events = triggers.events();
+    assert_eq!(events.len(), 10);
+    assert_eq!(triggers.total(), 10);
+    for (i, event) in events.iter().enumerate() {
+        assert_eq!(wave.at(0, event.sample as usize), 1.0);
+        assert!((event.time - (i as f64 + 0.5) / 10.0).abs() < 1.0e-4);
+    }
+    assert_eq!(wave.channel(0).iter().sum::<f64>(), 10.0);
+    let count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = count.clone();
+    let mut node = square_hz(5.0)
+        >> trigger_fn(0.0, move |_event| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+    Wave64::render(44100.0, 1.0, &mut node);
+    assert_eq!(count.load(std::sync::atomic::Ordering::Relaxed), 5);
+}
+
+#[test]
+fn test_parallel() {
+    // Parallel processing matches serial processing.
+    let input = Wave64::render(44100.0, 1.0, &mut (noise() | noise()));
+    let voices = || -> Vec<Box<dyn AudioUnit64>> {
+        (1..=6)
+            .map(|i| {
+                Box::new(saw_hz(55.0 * i as f64) >> lowpass_hz(800.0 * i as f64, 1.0) >> pan(0.0))
+                    as Box<dyn AudioUnit64>
+            })
+            .collect()
+    };
+    let parallel_wave = Wave64::render(
+        44100.0,
+        1.0,
+        &mut Parallel64::new(ParallelMode::Bus, voices(), 3),
+    );
+    let serial_waves: Vec<Wave64> = voices()
+        .iter_mut()
+        .map(|voice| Wave64::render(44100.0, 1.0, voice.as_mut()))
+        .collect();
+    for i in 0..44100 {
+        for channel in 0..2 {
+            let serial: f64 = serial_waves.iter().map(|wave| wave.at(channel, i)).sum();
+            assert!((parallel_wave.at(channel, i) - serial).abs() < 1.0e-9);
+        }
+    }
+    check_wave(Parallel64::new(ParallelMode::Bus, voices(), 2));
+    check_wave(Parallel64::new(
+        ParallelMode::Branch,
+        vec![Box::new(saw_hz(110.0)), Box::new(square_hz(220.0))],
+        1,
+    ));
+    check_wave_filter(
+        &input,
+        Parallel64::new(
+            ParallelMode::Stack,
+            vec![
+                Box::new(lowpass_hz(1000.0, 1.0)),
+                Box::new(highpass_hz(500.0, 2.0)),
+            ],
+            1,
+        ),
+    );
+}
+
+#[test]
+fn test_poly() {
+    // Polyphonic voice allocation. The envelope keeps its state in the voice:
+    // voices sharing state through shared variables, as `adsr_live` does,
+    // would depend on the order in which they are processed.
+    let mut env = envelope_bp(&[
+        (0.0, 0.0, 0.0),
+        (0.01, 1.0, 0.0),
+        (0.11, 0.5, 0.0),
+        (0.21, 0.0, 0.0),
+    ]);
+    env.set_loop(2, 2);
+    let voice = (saw() | env | pass()) >> map(|x: &Frame<f64, U3>| x[0] * x[1] * x[2]);
+    let mut synth = poly::<U4, _>(voice.clone());
+    assert_eq!(synth.note_on(60, midi_hz(60.0), 1.0), 0);
+    assert_eq!(synth.note_on(62, midi_hz(62.0), 1.0), 1);
+    // A held key retriggers its voice.
+    assert_eq!(synth.note_on(60, midi_hz(60.0), 0.5), 0);
+    synth.set_steal(VoiceSteal::Oldest);
+    assert_eq!(synth.note_on(64, midi_hz(64.0), 1.0), 2);
+    assert_eq!(synth.note_on(65, midi_hz(65.0), 1.0), 3);
+    // All voices are held, so the oldest note is stolen.
+    assert_eq!(synth.note_on(67, midi_hz(67.0), 1.0), 1);
+    assert_eq!(synth.held(), 4);
+    assert_eq!(synth.note_off(64), Some(2));
+    assert_eq!(synth.note_off(64), None);
+    assert_eq!(synth.note_on(69, midi_hz(69.0), 1.0), 2);
+    // Block processing matches single sample processing.
+    let mut ticked = synth.clone();
+    let mut processed = synth.clone();
+    let mut buffer = Buffer::<f64>::with_channels(1);
+    for block in 0..50 {
+        if block == 20 {
+            ticked.set(PolyEvent::AllOff);
+            processed.set(PolyEvent::AllOff);
+        }
+        processed.process(MAX_BUFFER_SIZE, &[], buffer.self_mut());
+        for i in 0..MAX_BUFFER_SIZE {
+            assert!((ticked.get_mono() - buffer.at(0)[i]).abs() < 1.0e-9);
+        }
+    }
+    assert_eq!(processed.held(), 0);
+    // The quietest voice is stolen.
+    let mut synth = poly::<U4, _>(voice);
+    synth.set_steal(VoiceSteal::Quietest);
+    for (key, velocity) in [1.0, 0.1, 0.5, 0.8].into_iter().enumerate() {
+        synth.set(PolyEvent::On {
+            key: key as i64,
+            pitch: 110.0,
+            velocity,
+        });
+    }
+    for _ in 0..2000 {
+        synth.get_mono();
+    }
+    assert_eq!(synth.note_on(4, 220.0, 1.0), 1);
+}
+
+#[test]
+fn test_unison() {
+    // Unison detune and spread.
+    let mut voices = unison::<U3, _>(1200.0, 1.0, pass());
+    let output = voices.tick(&[100.0].into());
+    let (left, right) = (output[0], output[1]);
+    let third = sqrt(1.0 / 3.0);
+    assert!((left - third * (100.0 * sqrt(0.5) + 100.0 * sqrt(0.5))).abs() < 1.0e-9);
+    assert!((right - third * (100.0 * sqrt(0.5) + 100.0 * sqrt(2.0))).abs() < 1.0e-9);
+    voices.set((0.0, 0.0));
+    let output = voices.tick(&[100.0].into());
+    let (left, right) = (output[0], output[1]);
+    assert!((left - 300.0 * third * sqrt(0.5)).abs() < 1.0e-9);
+    assert!((left - right).abs() < 1.0e-9);
+    // Voices start at different phases.
+    let mut supersaw = unison::<U7, _>(25.0, 0.8, saw());
+    assert!(supersaw.node_mut(0).filter_mono(110.0) != supersaw.node_mut(1).filter_mono(110.0));
+    check_wave(dc(110.0) >> unison::<U7, _>(25.0, 0.8, saw()));
+    check_wave(lfo(|t| 110.0 + 10.0 * sin(t)) >> unison::<U2, _>(10.0, 0.5, sine()));
+}
+
+#[test]
+fn test_sync_oscillators() {
+    // Oscillators with sync and phase inputs.
+    for waveform in [
+        Waveform::Sine,
+        Waveform::Saw,
+        Waveform::Square,
+        Waveform::Triangle,
+    ] {
+        let mut synced = An(SyncSynth::new(DEFAULT_SR, waveform, SyncInput::Sync));
+        let mut phased = An(SyncSynth::new(DEFAULT_SR, waveform, SyncInput::Phase));
+        for _ in 0..1000 {
+            let x = synced.tick(&[1000.0, -1.0].into())[0];
+            let y = phased.tick(&[1000.0, 0.0].into())[0];
+            assert!((x - y).abs() < 1.0e-9);
+            assert!(x.abs() < 1.2);
+        }
+    }
+    // A synced oscillator repeats at the period of the sync signal.
+    let mut lead = (sine_hz(441.0) >> split::<U2>() >> (add(1102.5) | pass())) >> saw_sync();
+    let output: Vec<f64> = (0..1000).map(|_| lead.get_mono()).collect();
+    assert!((500..900).all(|i| (output[i] - output[i + 100]).abs() < 1.0e-6));
+    assert!((500..900).any(|i| (output[i] - output[i + 40]).abs() > 0.1));
+    check_wave(
+        ((dc(330.0) | sine_hz(110.0) + 0.1) >> saw_sync())
+            | ((dc(220.0) | sine_hz(3.0) * 0.3) >> triangle_phase()),
+    );
+
+    // PolyBLEP pulse width.
+    let mut pwm = (dc((441.0, 0.25))) >> pulse_blep();
+    let mean = (0..44100).map(|_| pwm.get_mono()).sum::<f64>() / 44100.0;
+    assert!((mean + 0.5).abs() < 1.0e-3);
+    let mut pwm = (dc((441.0, 0.0))) >> pulse_blep();
+    assert!((0..1000).all(|_| pwm.get_mono() == -1.0));
+    check_wave(
+        (lfo(|t| (110.0, lerp11(0.1, 0.9, sin_hz(0.2, t)))) >> pulse_blep())
+            | (dc((2000.0, 0.5)) >> pulse_blep()),
+    );
+}
+
+#[test]
+fn test_additive() {
+    // Additive synthesis.
+    let mut synth = additive(&[Partial::new(1.0, 1.0), Partial::new(2.0, 0.5)]);
+    synth.set_hash(0);
+    assert_eq!(synth.partials(), 2);
+    synth.set(AdditiveSetting::Amplitude(1, 0.0));
+    synth.set(AdditiveSetting::Partial(0, Partial::new(1.0, 0.0)));
+    assert!((0..100).all(|_| synth.filter_mono(440.0) == 0.0));
+    // Partials above Nyquist are silent.
+    synth.set(AdditiveSetting::Partial(0, Partial::new(30.0, 1.0)));
+    assert!((0..100).all(|_| synth.filter_mono(1000.0) == 0.0));
+    // Inharmonicity stretches partials.
+    let mut synth = additive(&[Partial::new(1.0, 1.0)]);
+    synth.set(AdditiveSetting::Inharmonicity(3.0));
+    let mut reference = sine();
+    reference.set_hash(0);
+    synth.set_hash(0);
+    for _ in 0..100 {
+        let x = synth.filter_mono(441.0);
+        let y = reference.filter_mono(882.0);
+        assert!((x - y).abs() < 1.0e-9);
+    }
+    synth.push_partial(Partial::new(3.0, 0.5));
+    assert_eq!(synth.remove_partial(0), Partial::new(1.0, 1.0));
+    check_wave(
+        dc((110.0, 220.0))
+            >> (additive(&[Partial::new(1.0, 1.0), Partial::new(2.5, 0.3)])
+                | additive(&[Partial::new(1.5, 0.5)])),
+    );
+}
+
+#[test]
+fn test_parameters() {
+    // Named parameters.
+    let mut node = dc(110.0) >> unison::<U3, _>(10.0, 0.5, sine())
+        | (saw_hz(110.0) >> bell_hz(1000.0, 1.0, 2.0) >> lowpass_hz(500.0, 1.0));
+    let names: Vec<String> = node.parameters().iter().map(|x| x.name.clone()).collect();
+    assert_eq!(
+        names,
+        ["value", "detune", "spread", "value", "cutoff", "q", "gain", "cutoff", "q"]
+    );
+    assert_eq!(node.parameter_count(), 9);
+    assert_eq!(node.parameter(1), 10.0);
+    assert_eq!(node.parameter(6), 2.0);
+    node.set_parameter(2, 0.25);
+    assert_eq!(node.parameter(2), 0.25);
+    node.set_parameter(7, 2000.0);
+    assert_eq!(node.parameter(7), 2000.0);
+    assert_eq!(node.parameter_index("gain"), Some(6));
+    assert_eq!(node.parameter_index("pan"), None);
+    let mut net = Net64::wrap(Box::new(node.clone()));
+    let id = net.push(Box::new(pan(0.0)));
+    net.connect_input(0, id, 0);
+    assert_eq!(net.parameter_count(), 10);
+    net.set_parameter(9, 0.5);
+    assert_eq!(net.node(id).parameter(0), 0.5);
+    let mut left = pan(-1.0);
+    let mut right = pan(-1.0);
+    right.set_parameter(0, 1.0);
+    left.set(1.0);
+    assert_eq!(left.tick(&[1.0].into()), right.tick(&[1.0].into()));
+}
+
+#[test]
+fn test_preset_morph() {
+    // Preset morphing.
+    let mut node = preset_morph(noise() >> bell_hz(100.0, 1.0, 1.0));
+    node.set_sample_rate(1000.0);
+    let a = node.store();
+    let b = node.push_preset(&[10000.0, 2.0, 3.0]);
+    node.set_curve(2, MorphCurve::Step);
+    assert_eq!(node.curve(0), MorphCurve::Logarithmic);
+    node.set(MorphSetting::Morph(b, 1.0));
+    for _ in 0..500 {
+        node.tick(&Frame::default());
+    }
+    assert!(node.is_morphing());
+    assert!((node.parameter(0) - 1000.0).abs() < 1.0e-6);
+    assert!((node.parameter(1) - 1.5).abs() < 1.0e-9);
+    assert_eq!(node.parameter(2), 3.0);
+    let mut output = [0.0; 64];
+    for _ in 0..8 {
+        node.process(64, &[], &mut [&mut output]);
+    }
+    assert!(!node.is_morphing());
+    assert_eq!(node.snapshot(), node.preset(b));
+    node.set(MorphSetting::Blend(a, b, 0.25));
+    assert_eq!(node.parameter(2), 1.0);
+    node.set_preset(a, &[100.0, 1.0, 1.0]);
+    node.morph(a, 0.0);
+    assert_eq!(node.snapshot(), [100.0, 1.0, 1.0]);
+}
+
+#[test]
+fn test_stretch() {
+    // Time stretching.
+    let wave = Arc::new(Wave64::render(44100.0, 0.5, &mut (noise())));
+    let mut player = stretch64(&wave, 0, 1.0);
+    for i in 0..wave.len() {
+        assert!((player.get_mono() - wave.at(0, i)).abs() < 1.0e-9);
+    }
+    // Count zero crossings to estimate the frequency of a stretched sine.
+    let crossings = |node: &mut dyn AudioUnit64, samples: usize| {
+        let mut previous = node.get_mono();
+        let mut count = 0;
+        for _ in 0..samples {
+            let value = node.get_mono();
+            if (previous < 0.0) != (value < 0.0) {
+                count += 1;
+            }
+            previous = value;
+        }
+        count
+    };
+    let wave = Arc::new(Wave64::render(44100.0, 2.0, &mut (sine_hz(441.0))));
+    let mut player = stretch64(&wave, 0, 0.5);
+    let count = crossings(&mut player, 44100);
+    assert!((count as i64 - 882).abs() <= 4);
+    assert!(player.position() > 0.49 && player.position() < 0.51);
+    player.set((1.0, 2.0));
+    let count = crossings(&mut player, 22050);
+    assert!((count as i64 - 882).abs() <= 4);
+    player.reset();
+    assert_eq!(player.position(), 0.0);
+}
+
+#[test]
+fn test_pitch_shift() {
+    // Pitch shifting.
+    let mut shifter = sine_hz(440.0) >> pitch_shift(12.0);
+    for _ in 0..4410 {
+        shifter.get_mono();
+    }
+    let count = crossings(&mut shifter, 44100);
+    assert!((count as i64 - 1760).abs() < 20);
+    let mut shifter = pitch_shift(0.0);
+    shifter.set(-12.0);
+    assert_eq!(shifter.parameter(0), -12.0);
+    let mut shifter = sine_hz(440.0) >> shifter;
+    for _ in 0..4410 {
+        shifter.get_mono();
+    }
+    let count = crossings(&mut shifter, 44100);
+    assert!((count as i64 - 440).abs() < 20);
+    for formants in [false, true] {
+        let mut shifter = sine_hz(440.0) >> pitch_shift_spectral(7.0, formants);
+        for _ in 0..8192 {
+            shifter.get_mono();
+        }
+        let count = crossings(&mut shifter, 44100);
+        assert!((count as f64 / 2.0 / 440.0 / semitone_ratio(7.0) - 1.0).abs() < 0.02);
+    }
+}
+
+#[test]
+fn test_denoise() {
+    // Spectral noise reduction attenuates stationary noise and keeps a loud tone.
+    let rms = |wave: &Wave64| {
+        let n = wave.len() - 22050;
+        sqrt(
+            (22050..wave.len())
+                .map(|i| squared(wave.at(0, i)))
+                .sum::<f64>()
+                / n as f64,
+        )
+    };
+    // White noise is uniformly distributed in -1...1.
+    let level = 0.01 / sqrt(3.0);
+    let room = Wave64::render(44100.0, 1.0, &mut (noise() * 0.01));
+    let profile = NoiseProfile::new(2048, room.channel(0));
+    assert_eq!(profile.bins(), 1025);
+    let mut x = (noise_seed(1) * 0.01) >> denoise_profile(1.0, &profile);
+    assert!(rms(&Wave64::render(44100.0, 2.0, &mut x)) < 0.5 * level);
+    let mut x = (noise_seed(1) * 0.01) >> denoise(1.0);
+    assert!(rms(&Wave64::render(44100.0, 2.0, &mut x)) < 0.5 * level);
+    let mut x = (noise_seed(1) * 0.01) >> denoise(0.0);
+    assert!((rms(&Wave64::render(44100.0, 2.0, &mut x)) / level - 1.0).abs() < 0.1);
+    let mut x = (sine_hz(440.0) * 0.5 + noise_seed(1) * 0.01) >> denoise_profile(1.0, &profile);
+    let tone = rms(&Wave64::render(44100.0, 2.0, &mut x));
+    assert!((tone / (0.5 / SQRT_2) - 1.0).abs() < 0.05);
+}
+
+#[test]
+fn test_frac_delay() {
+    // Fractional delays.
+    let sr = DEFAULT_SR;
+    let mut wave = Wave64::new(1, sr);
+    for i in 0..1000 {
+        wave.push(sin_hz(100.0, i as f64 / sr));
+    }
+    let wave = Arc::new(wave);
+    for interpolation in [
+        Interpolation::Nearest,
+        Interpolation::Linear,
+        Interpolation::Cubic,
+        Interpolation::Allpass,
+        Interpolation::Sinc,
+    ] {
+        // Whole sample delays are exact.
+        let mut node = (white_seed(1) | dc(10.0 / sr)) >> frac_delay(interpolation, 0.0, 0.01);
+        let mut reference = white_seed(1) >> delay(10.0 / sr);
+        for _ in 0..1000 {
+            assert!((node.get_mono() - reference.get_mono()).abs() < 1.0e-9);
+        }
+        // Fractional delays of a low frequency sine are accurate.
+        let mut fractional = frac_delay(Interpolation::Nearest, 0.0, 0.01);
+        fractional.set(interpolation);
+        assert_eq!(fractional.interpolation(), interpolation);
+        let mut node = (wave64(&wave, 0, None) | dc(10.25 / sr)) >> fractional;
+        let mut error: f64 = 0.0;
+        for i in 0..1000 {
+            let value = node.get_mono();
+            if i >= 100 {
+                let t = (i as f64 - 10.25) / sr;
+                error = error.max((value - sin_hz(100.0, t)).abs());
+            }
+        }
+        let tolerance = if interpolation == Interpolation::Nearest {
+            0.02
+        } else {
+            0.001
+        };
+        assert!(error < tolerance);
+    }
+    // Abrupt delay changes are crossfaded.
+    let mut input = Wave64::new(2, sr);
+    for i in 0..44100 {
+        let time = if i < 22075 { 0.1 } else { 0.1 + 0.5 / 440.0 };
+        input.push((sin_hz(440.0, i as f64 / sr), time));
+    }
+    let input = Arc::new(input);
+    let jump = |smooth: bool| {
+        let source = wave64(&input, 0, None) | wave64(&input, 1, None);
+        let mut node: Box<dyn AudioUnit64> = if smooth {
+            Box::new(source >> frac_delay_smooth(Interpolation::Linear, 0.1, 0.2))
+        } else {
+            Box::new(source >> frac_delay(Interpolation::Linear, 0.1, 0.2))
+        };
+        let mut previous = 0.0;
+        let mut largest: f64 = 0.0;
+        for _ in 0..44100 {
+            let value = node.get_mono();
+            largest = largest.max((value - previous).abs());
+            previous = value;
+        }
+        largest
+    };
+    assert!(jump(false) > 1.0);
+    assert!(jump(true) < 0.1);
+}
+
+#[test]
+fn test_diffuser() {
+    // Diffusers are allpass with mutually prime delay lengths.
+    let mut node = diffuser(6, 0.03, 7);
+    let lengths = node.lengths();
+    assert_eq!(lengths.len(), 6);
+    for (i, a) in lengths.iter().enumerate() {
+        assert!(*a <= (0.03 * DEFAULT_SR) as usize + 10);
+        for b in lengths[i + 1..].iter() {
+            assert!((2..=*a.min(b)).all(|d| a % d != 0 || b % d != 0));
+        }
+    }
+    assert_ne!(lengths, diffuser(6, 0.03, 8).lengths());
+    let mut energy = 0.0;
+    for i in 0..441000 {
+        let value = node.filter_mono(if i == 0 { 1.0 } else { 0.0 });
+        energy += value * value;
+    }
+    assert!((energy - 1.0).abs() < 1.0e-6);
+}
+
+#[test]
+fn test_transport() {
+    // Tempo synced nodes follow the transport.
+    let transport = Transport::new(120.0);
+    let mut node = delay_sync(&transport, 0.5);
+    let peak = (0..22050)
+        .map(|i| node.filter_mono(if i == 0 { 1.0 } else { 0.0 }))
+        .enumerate()
+        .fold(
+            (0, 0.0),
+            |best, (i, x)| if x > best.1 { (i, x) } else { best },
+        );
+    assert_eq!(peak.0, 11025);
+    assert!((peak.1 - 1.0).abs() < 1.0e-9);
+    let mut node = sine_hz(440.0) >> delay_sync(&transport, 0.5);
+    let mut previous = node.get_mono();
+    for i in 0..44100 {
+        if i == 22050 {
+            transport.set_tempo(90.0);
+        }
+        let value = node.get_mono();
+        // Skip the start of the delayed signal.
+        if i > 12000 {
+            assert!((value - previous).abs() < 0.2);
+        }
+        previous = value;
+    }
+    transport.set_tempo(120.0);
+    let mut node = lfo_sync(&transport, 1.0, LfoShape::Square);
+    let cycles = |node: &mut An<LfoSync<f64>>| {
+        let mut previous = node.get_mono();
+        let mut count = 0;
+        for _ in 0..44100 {
+            let value = node.get_mono();
+            if previous < 0.0 && value > 0.0 {
+                count += 1;
+            }
+            previous = value;
+        }
+        count
+    };
+    assert_eq!(cycles(&mut node), 2);
+    transport.set_tempo(240.0);
+    assert_eq!(cycles(&mut node), 4);
+}
+
+#[test]
+fn test_tremolo() {
+    // Tremolo and auto-panner, free running and tempo synced.
+    let mut node = dc(1.0) >> tremolo(5.0, 0.5);
+    let wave = Wave64::render(44100.0, 1.0, &mut node);
+    let (low, high) = (0..44100).fold((1.0, 0.0), |(low, high): (f64, f64), i| {
+        (low.min(wave.at(0, i)), high.max(wave.at(0, i)))
+    });
+    assert!((low - 0.5).abs() < 1.0e-6 && (high - 1.0).abs() < 1.0e-6);
+    // At 240 BPM, a cycle of 1 beat lasts 11025 samples.
+    let transport = Transport::new(240.0);
+    let mut node = dc(1.0) >> tremolo_sync(&transport, 1.0, 1.0);
+    let wave = Wave64::render(44100.0, 1.0, &mut node);
+    assert!((wave.at(0, 11025 / 4) - 1.0).abs() < 1.0e-3);
+    assert!(wave.at(0, 11025 * 3 / 4) < 1.0e-3);
+    let mut node = dc(1.0) >> autopan(1.0, 1.0, LfoShape::Square);
+    let (left, right) = node.get_stereo();
+    assert!(left.abs() < 1.0e-12 && (right - 1.0).abs() < 1.0e-12);
+    let mut node = dc(1.0) >> autopan_sync(&transport, 1.0, 0.0, LfoShape::Saw);
+    let (left, right) = node.get_stereo();
+    assert!((left - right).abs() < 1.0e-12);
+}
+
+#[test]
+fn test_breakpoints() {
+    // Breakpoint envelopes hold at sustain breakpoints, release and retrigger.
+    let mut env = envelope_bp(&[
+        (0.0, 0.0, 0.0),
+        (0.01, 1.0, 0.0),
+        (0.02, 0.5, 0.0),
+        (0.03, 0.0, 0.0),
+    ]);
+    env.set_loop(2, 2);
+    assert_eq!(env.tick(&Frame::from([0.0]))[0], 0.0);
+    let gate = |env: &mut An<Breakpoints<f64>>, gate: f64, samples: usize| {
+        let mut value = 0.0;
+        for _ in 0..samples {
+            value = env.tick(&Frame::from([gate]))[0];
+        }
+        value
+    };
+    assert!((gate(&mut env, 1.0, 221) - 220.0 / 441.0).abs() < 1.0e-6);
+    assert!((gate(&mut env, 1.0, 221) - 1.0).abs() < 1.0e-6);
+    assert_eq!(gate(&mut env, 1.0, 10000), 0.5);
+    assert!((gate(&mut env, 0.0, 221) - 0.25).abs() < 1.0e-2);
+    assert_eq!(gate(&mut env, 0.0, 1000), 0.0);
+    assert!(gate(&mut env, 1.0, 100) > 0.0);
+    // Loops repeat while the gate is open.
+    env.set(BreakpointSetting::Loop(1, 2));
+    env.set(BreakpointSetting::Retrigger);
+    let mut env2 = env.clone();
+    gate(&mut env, 1.0, 441 + 441 * 10 + 220);
+    gate(&mut env2, 1.0, 441 + 441 * 12 + 220);
+    let value = gate(&mut env, 1.0, 1);
+    assert!(value > 0.5 && value < 1.0);
+    assert!((value - gate(&mut env2, 1.0, 1)).abs() < 1.0e-2);
+}
+
+#[test]
+fn test_lfo_wave() {
+    // LFOs have phase offsets, polarity and retrigger.
+    let mut lfo_node = lfo_wave(LfoShape::Saw, 10.0);
+    lfo_node.set_phase_offset(0.5);
+    assert_eq!(lfo_node.tick(&Frame::from([0.0]))[0], 0.0);
+    lfo_node.set_unipolar(true);
+    for _ in 0..1000 {
+        lfo_node.tick(&Frame::from([0.0]));
+    }
+    assert_eq!(lfo_node.tick(&Frame::from([1.0]))[0], 0.5);
+    let mut lfo_node = lfo_wave(LfoShape::SampleHold, 10.0);
+    let first = lfo_node.tick(&Frame::from([0.0]))[0];
+    for _ in 1..4400 {
+        assert_eq!(lfo_node.tick(&Frame::from([0.0]))[0], first);
+    }
+    for _ in 4400..4420 {
+        lfo_node.tick(&Frame::from([0.0]));
+    }
+    assert_ne!(lfo_node.tick(&Frame::from([0.0]))[0], first);
+}
+
+#[test]
+fn test_crossfade() {
+    // Equal-power crossfades and matrix mixers.
+    let mut node = (dc(1.0) | lfo(|t| t)) >> xfade(pass() * 2.0, pass() * 3.0);
+    for i in 0..100 {
+        let mix = i as f64 / DEFAULT_SR;
+        let (a, b) = equal_power(mix);
+        assert!((a * a + b * b - 1.0).abs() < 1.0e-12);
+        assert!((node.get_mono() - (2.0 * a + 3.0 * b)).abs() < 1.0e-3);
+    }
+    let mut node =
+        dc((1.0, 2.0, 0.0)) >> xfade(multipass::<U2>(), multisink::<U2>() >> dc((5.0, 6.0)));
+    assert_eq!(node.get_stereo(), (1.0, 2.0));
+    check_wave(
+        (noise() | noise() | lfo(|t| sin_hz(1.0, t) * 0.5 + 0.5))
+            >> xfade(
+                lowpass_hz(1000.0, 1.0) | pass(),
+                pass() | highpass_hz(1000.0, 1.0),
+            ),
+    );
+    let mut node = dc((1.0, 2.0, 3.0)) >> matrix_mixer::<U3, U2>(0.0);
+    assert_eq!(node.get_stereo(), (1.0, 2.0));
+    node.set(Side::Right((0, 2, 1.0)));
+    node.set(Side::Right((1, 1, 0.5)));
+    assert_eq!(node.get_stereo(), (4.0, 1.0));
+    let mut node = matrix_mixer::<U1, U1>(0.01);
+    node.set_gain(0, 0, 0.0);
+    let first = node.filter_mono(1.0);
+    assert!(first > 0.9 && first < 1.0);
+    for _ in 0..4410 {
+        node.filter_mono(1.0);
+    }
+    assert!(node.filter_mono(1.0) < 1.0e-3);
+    check_wave(noise() >> split::<U3>() >> matrix_mixer::<U3, U2>(0.01));
+}
+
+#[test]
+fn test_feedback() {
+    // Feedback loops with gain input, stability guard and latency compensation.
+    let impulse_response = |node: &mut dyn AudioUnit64| -> Vec<f64> {
+        (0..32)
+            .map(|i| node.filter_mono(if i == 0 { 1.0 } else { 0.0 }))
+            .collect()
+    };
+    let delay_time = 10.0 / DEFAULT_SR;
+    let mut node = (pass() | dc(0.5)) >> feedback_loop(delay_time, LoopGuard::None, pass());
+    let response = impulse_response(&mut node);
+    assert_eq!(response[0], 1.0);
+    assert_eq!(response[10], 0.5);
+    assert_eq!(response[20], 0.25);
+    assert_eq!(response[15], 0.0);
+    // The latency of the enclosed node is part of the loop delay.
+    let mut node = (pass() | dc(0.5)) >> feedback_loop(delay_time, LoopGuard::None, tick());
+    let response = impulse_response(&mut node);
+    assert_eq!(response[1], 1.0);
+    assert_eq!(response[11], 0.5);
+    assert_eq!(response[21], 0.25);
+    // Delays shorter than the latency are lengthened to the minimum.
+    let mut node = feedback_loop(0.0, LoopGuard::None, tick() >> tick());
+    assert_eq!(node.loop_samples(), 3);
+    // Guards keep loops with a gain above one bounded.
+    for guard in [LoopGuard::Clip(1.0), LoopGuard::Energy(1.0)] {
+        let mut node = (pass() | dc(2.0)) >> feedback_loop(0.0, guard, pass());
+        for _ in 0..44100 {
+            assert!(node.filter_mono(1.0).abs() <= 3.0);
+        }
+    }
+    let mut node = (pass() | dc(2.0)) >> feedback_loop(0.0, LoopGuard::None, pass());
+    for _ in 0..100 {
+        node.filter_mono(1.0);
+    }
+    assert!(node.filter_mono(1.0) > 1.0e10);
+    check_wave(
+        (noise() | dc(0.9) | noise() | dc(1.5))
+            >> (feedback_loop(0.01, LoopGuard::None, lowpass_hz(1000.0, 1.0))
+                | feedback_loop(0.02, LoopGuard::Energy(0.5), highpass_hz(500.0, 1.0))),
+    );
+}
+
+#[test]
+fn test_control_rate() {
+    // Block-rate conversions between audio-rate controls, shared variables and settings.
+    let value = shared(0.0);
+    let mut node = from_shared(&value);
+    assert_eq!(node.get_mono(), 0.0);
+    value.set_value(1.0);
+    for _ in 1..CONTROL_BLOCK {
+        assert_eq!(node.get_mono(), 0.0);
+    }
+    assert_eq!(node.get_mono(), 1.0 / CONTROL_BLOCK as f64);
+    for _ in 2..CONTROL_BLOCK {
+        node.get_mono();
+    }
+    assert_eq!(node.get_mono(), 1.0);
+    assert_eq!(node.get_mono(), 1.0);
+    let level = shared(0.0);
+    let mut node = to_shared(&level);
+    for _ in 1..CONTROL_BLOCK {
+        assert_eq!(node.filter_mono(2.0), 2.0);
+    }
+    assert_eq!(level.value(), 0.0);
+    node.filter_mono(2.0);
+    assert_eq!(level.value(), 2.0);
+    // Probes publish the latest value once per block.
+    let mut node = probe(&level);
+    for i in 1..CONTROL_BLOCK {
+        assert_eq!(node.filter_mono(i as f64), i as f64);
+    }
+    assert_eq!(level.value(), 2.0);
+    node.filter_mono(5.0);
+    assert_eq!(level.value(), 5.0);
+    let probe_input = [1.0, 2.0, 3.0];
+    let mut output = [0.0; 3];
+    node.process(3, &[&probe_input], &mut [&mut output]);
+    assert_eq!(output, probe_input);
+    assert_eq!(level.value(), 3.0);
+    let mut node = to_setting(dc(0.0), |x| [x].into());
+    assert_eq!(node.filter_mono(3.0), 3.0);
+    for _ in 1..CONTROL_BLOCK {
+        assert_eq!(node.filter_mono(1.0), 3.0);
+    }
+    let average = (3.0 + (CONTROL_BLOCK - 1) as f64) / CONTROL_BLOCK as f64;
+    assert!((node.filter_mono(1.0) - average).abs() < 1.0e-12);
+    check_wave(
+        (noise() | lfo(|t| xerp(500.0, 5000.0, t)))
+            >> to_setting(lowpole_hz(1000.0), |f| f)
+            >> to_shared(&level)
+            >> split::<U2>(),
+    );
+}
+
+#[test]
+fn test_sequencer_events() {
+    // Per-event and per-group stems sum to the mix.
+    let mut sequencer = Sequencer64::new(true, 2);
+    let a = sequencer.push(
+        0.0,
+        0.5,
+        Fade::Smooth,
+        0.1,
+        0.1,
+        Box::new(noise() | noise()),
+    );
+    let b = sequencer.push(
+        0.25,
+        1.0,
+        Fade::Power,
+        0.0,
+        0.2,
+        Box::new(saw_hz(110.0) | sine_hz(220.0)),
+    );
+    let c = sequencer.push(0.75, 2.0, Fade::Smooth, 0.0, 0.0, Box::new(dc((0.5, -0.5))));
+    let (mix, stems) = sequencer.render_stems(DEFAULT_SR, 1.5);
+    assert_eq!(
+        stems.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        vec![a, b, c]
+    );
+    assert_eq!(mix.duration(), 1.5);
+    for channel in 0..2 {
+        for i in 0..mix.len() {
+            let sum: f64 = stems.iter().map(|(_, stem)| stem.at(channel, i)).sum();
+            assert!((sum - mix.at(channel, i)).abs() < 1.0e-9);
+        }
+    }
+    // The first event has ended and the last one has started by 40000 samples.
+    assert!(stems[0].1.at(0, 40000) == 0.0 && stems[2].1.at(0, 40000) == 0.5);
+    let (_, groups) = sequencer.render_groups(DEFAULT_SR, 1.5, 2, |id| if id == b { 1 } else { 0 });
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[1].at(1, 20000), stems[1].1.at(1, 20000));
+    assert_eq!(groups[0].at(0, 40000), 0.5);
+
+    // Finished events are reported in order of finishing, also from a backend.
+    let mut sequencer = Sequencer64::new(false, 1);
+    let a = sequencer.push(0.0, 0.2, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    let b = sequencer.push(0.0, 0.1, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    let _c = sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    for _ in 0..4410 {
+        sequencer.get_mono();
+    }
+    assert_eq!(sequencer.finished_events().count(), 0);
+    for _ in 0..10000 {
+        sequencer.get_mono();
+    }
+    assert_eq!(sequencer.finished_events().collect::<Vec<_>>(), vec![b, a]);
+    assert_eq!(sequencer.finished_events().count(), 0);
+    let mut sequencer = Sequencer64::new(true, 1);
+    let mut backend = sequencer.backend();
+    let a = sequencer.push(0.0, 0.01, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    let mut output = Buffer::<f64>::with_channels(1);
+    for _ in 0..10 {
+        backend.process(MAX_BUFFER_SIZE, &[], output.self_mut());
+    }
+    assert_eq!(sequencer.finished_events().collect::<Vec<_>>(), vec![a]);
+}
+
+#[test]
+fn test_sequencer_quantize() {
+    // Quantized events start on the next grid boundary of sequencer time.
+    let mut sequencer = Sequencer64::new(false, 1);
+    for _ in 0..13230 {
+        sequencer.get_mono();
+    }
+    sequencer.push_quantized(0.25, 0.1, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    for _ in 13230..22050 {
+        assert_eq!(sequencer.get_mono(), 0.0);
+    }
+    assert_eq!(sequencer.get_mono(), 1.0);
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut backend = sequencer.backend();
+    let mut output = Buffer::<f64>::with_channels(1);
+    // Backend time is 0.25 seconds when the event arrives, which is on the grid.
+    for _ in 0..172 {
+        backend.process(64, &[], output.self_mut());
+    }
+    backend.process(17, &[], output.self_mut());
+    sequencer.push_quantized(0.125, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    assert_eq!(backend.get_mono(), 1.0);
+    // The frontend sees backend time.
+    let time = (172 * 64 + 17 + 1) as f64 / DEFAULT_SR;
+    assert!((sequencer.backend_time() - time).abs() < 1.0e-9);
+    assert_eq!(sequencer.backend_samples(), 172 * 64 + 17 + 1);
+
+    // Batches of events start together.
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut backend = sequencer.backend();
+    let chord = (1..=3)
+        .map(|i| Event64::new(Box::new(dc(i as f64)), 0.0, 1.0, Fade::Smooth, 0.0, 0.0))
+        .collect();
+    sequencer.push_batch(chord);
+    assert_eq!(backend.get_mono(), 6.0);
+
+    // The grid offset shifts quantized events.
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut backend = sequencer.backend();
+    sequencer.set_grid_offset(0.1);
+    sequencer.push_quantized(0.25, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    for _ in 0..4400 {
+        assert_eq!(backend.get_mono(), 0.0);
+    }
+    for _ in 4400..4420 {
+        backend.get_mono();
+    }
+    assert_eq!(backend.get_mono(), 1.0);
+}
+
+#[test]
+fn test_voice_budget() {
+    // Over budget, the lowest priority and quietest events are culled.
+    let mut sequencer = Sequencer64::new(false, 1);
+    sequencer.set_voice_budget(2, 0.0);
+    sequencer.set_priority(1);
+    sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    sequencer.set_priority(0);
+    sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(2.0)));
+    sequencer.push(0.0101, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(4.0)));
+    // The third event starts at sample 445.
+    for _ in 0..445 {
+        assert_eq!(sequencer.get_mono(), 3.0);
+    }
+    assert_eq!(sequencer.get_mono(), 7.0);
+    assert_eq!(sequencer.get_mono(), 5.0);
+
+    // Virtual voices keep time silently and resume when the budget frees up.
+    let mut sequencer = Sequencer64::new(false, 1);
+    sequencer.set_voice_budget(1, 0.0);
+    sequencer.set_virtual_voices(true);
+    sequencer.push(0.0, 1.0, Fade::Smooth, 0.0, 0.0, Box::new(lfo(|t| 1.0 + t)));
+    sequencer.push(0.0101, 0.0201, Fade::Smooth, 0.0, 0.0, Box::new(dc(4.0)));
+    let output: Vec<f64> = (0..44200).map(|_| sequencer.get_mono()).collect();
+    assert!((output[100] - 1.0 - 100.0 / DEFAULT_SR).abs() < 1.0e-3);
+    assert_eq!(output[500], 4.0);
+    assert!((output[1000] - 1.0 - 1000.0 / DEFAULT_SR).abs() < 1.0e-3);
+    assert_eq!(output[44150], 0.0);
+}
+
+#[test]
+fn test_sequencer_offset() {
+    // Events can start from an offset into the unit.
+    let mut sequencer = Sequencer64::new(true, 1);
+    sequencer.push_from_offset(0.0, 1.0, 0.5, Fade::Smooth, 0.0, 0.0, Box::new(lfo(|t| t)));
+    assert!((sequencer.get_mono() - 0.5).abs() < 1.0e-3);
+    // The offset is applied again after a reset or a change of sample rate.
+    sequencer.reset();
+    assert!((sequencer.get_mono() - 0.5).abs() < 1.0e-3);
+    sequencer.set_sample_rate(48000.0);
+    sequencer.reset();
+    assert!((sequencer.get_mono() - 0.5).abs() < 1.0e-3);
+}
+
+#[test]
+fn test_stream() {
+    // Streaming playback from disk matches the file and restarts on reset.
+    let path = std::env::temp_dir().join("fundsp_stream_test.wav");
+    let wave = Wave64::render(DEFAULT_SR, 0.5, &mut (white_seed(1) | white_seed(2)));
+    wave.save_wav32(&path).unwrap();
+    let mut stream = stream_wav(&path, 0.1).unwrap();
+    let status = stream.status();
+    assert_eq!(stream.outputs(), 2);
+    assert_eq!(stream.sample_rate(), DEFAULT_SR);
+    let mut output = Buffer::<f64>::with_channels(2);
+    let mut i = 0;
+    while i < wave.len() {
+        let n = min(MAX_BUFFER_SIZE, wave.len() - i);
+        stream.process(n, &[], output.self_mut());
+        for j in 0..n {
+            assert_eq!(output.at(0)[j], wave.at(0, i + j) as f32 as f64);
+            assert_eq!(output.at(1)[j], wave.at(1, i + j) as f32 as f64);
+        }
+        i += n;
+        // Give the decoder time to keep up.
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert_eq!(status.underruns(), 0);
+    assert!(stream.is_finished());
+    assert_eq!(stream.get_stereo(), (0.0, 0.0));
+    stream.reset();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert_eq!(stream.get_stereo().0, wave.at(0, 0) as f32 as f64);
+    drop(stream);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_backend_sample_rate() {
+    // Live sample rate changes fade backends out and back in.
+    let mut net = Net64::new(0, 1);
+    net.chain(Box::new(dc(1.0)));
+    let mut backend = net.backend();
+    assert_eq!(backend.get_mono(), 1.0);
+    net.set_sample_rate(48000.0);
+    net.commit();
+    let output: Vec<f64> = (0..1000).map(|_| backend.get_mono()).collect();
+    assert!(output[0] < 1.0);
+    assert!(output.contains(&0.0));
+    assert_eq!(output[999], 1.0);
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut backend = sequencer.backend();
+    sequencer.push(0.0, 10.0, Fade::Smooth, 0.0, 0.0, Box::new(dc(1.0)));
+    assert_eq!(backend.get_mono(), 1.0);
+    sequencer.set_sample_rate(48000.0);
+    let output: Vec<f64> = (0..1000).map(|_| backend.get_mono()).collect();
+    assert!(output[0] < 1.0);
+    assert!(output.contains(&0.0));
+    assert_eq!(output[999], 1.0);
+}
+
+#[test]
+fn test_block_adapter() {
+    // A fixed block size adapter delays output by one block for any call sizes.
+    let mut reference = lowpole_hz(1000.0);
+    let mut adapter = BigBlockAdapter64::with_block_size(Box::new(lowpole_hz(1000.0)), 48);
+    assert_eq!(adapter.latency(), Some(48.0));
+    let mut rnd = Rnd::from_u64(1);
+    let signal: Vec<f64> = (0..2000).map(|_| rnd.f64() - 0.5).collect();
+    let mut output = vec![0.0; 2000];
+    let mut i = 0;
+    for n in [1, 7, 100, 333, 1, 64, 65].iter().cycle() {
+        let n = min(*n, 2000 - i);
+        adapter.process(n, &[&signal[i..i + n]], &mut [&mut output[i..i + n]]);
+        i += n;
+        if i == 2000 {
+            break;
+        }
+    }
+    let expected: Vec<f64> = signal.iter().map(|x| reference.filter_mono(*x)).collect();
+    for i in 0..2000 {
+        let y = if i < 48 { 0.0 } else { expected[i - 48] };
+        assert!((output[i] - y).abs() <= 1.0e-9);
+    }
+}
+
+#[test]
+fn test_playlist() {
+    // Playlists chain segments without gaps and crossfade on request.
+    let first = Arc::new(Wave64::render(DEFAULT_SR, 0.01, &mut (noise() | noise())));
+    let second = Arc::new(Wave64::render(DEFAULT_SR, 0.02, &mut noise()));
+    let (mut playlist, mut backend) = Playlist64::new(2);
+    playlist.enqueue(&first, 1.0, Fade::Smooth, 0.0);
+    playlist.enqueue(&second, 0.5, Fade::Smooth, 0.0);
+    let mut output = Buffer::<f64>::with_channels(2);
+    let mut i = 0;
+    while i < first.len() + second.len() + 100 {
+        backend.process(MAX_BUFFER_SIZE, &[], output.self_mut());
+        for j in 0..MAX_BUFFER_SIZE {
+            for channel in 0..2 {
+                let x = if i + j < first.len() {
+                    first.at(channel, i + j)
+                } else if i + j < first.len() + second.len() {
+                    0.5 * second.at(0, i + j - first.len())
+                } else {
+                    0.0
+                };
+                assert_eq!(output.at(channel)[j], x);
+            }
+        }
+        i += MAX_BUFFER_SIZE;
+    }
+    let ones = Arc::new(Wave64::render(DEFAULT_SR, 0.01, &mut dc(1.0)));
+    let (mut playlist, mut backend) = Playlist64::new(1);
+    playlist.enqueue(&ones, 1.0, Fade::Power, 0.0);
+    playlist.enqueue(&ones, 1.0, Fade::Power, 0.001);
+    let crossfade = (0.001 * DEFAULT_SR).round() as usize;
+    let output: Vec<f64> = (0..2 * ones.len()).map(|_| backend.get_mono()).collect();
+    for (i, x) in output.iter().enumerate() {
+        if i < 2 * ones.len() - crossfade {
+            assert!(*x > 0.0 && *x <= 1.5);
+        } else {
+            assert_eq!(*x, 0.0);
+        }
+    }
+    let (mut playlist, mut backend) = Playlist64::new(1);
+    playlist.enqueue(&second, 1.0, Fade::Smooth, 0.0);
+    playlist.enqueue(&ones, 1.0, Fade::Smooth, 0.0);
+    for _ in 0..100 {
+        backend.get_mono();
+    }
+    playlist.skip(0.001);
+    for _ in 0..crossfade {
+        backend.get_mono();
+    }
+    assert_eq!(backend.get_mono(), 1.0);
+    playlist.clear(0.0);
+    backend.get_mono();
+    assert_eq!(backend.get_mono(), 0.0);
+}
+
+#[test]
+fn test_layer_mixer() {
+    // Layer mixers crossfade stems on the beat grid.
+    let intensity = shared(0.0);
+    let transport = Transport::new(120.0);
+    let mut mixer = LayerMixer64::new(1, &intensity).with_transport(&transport, 1.0);
+    let ones = Arc::new(Wave64::render(DEFAULT_SR, 0.01, &mut dc(1.0)));
+    let twos = Arc::new(Wave64::render(DEFAULT_SR, 0.01, &mut dc(2.0)));
+    mixer.push(&ones, 0.0, 0.5, 1.0);
+    mixer.push(&twos, 0.5, 1.0, 1.0);
+    mixer.set_fade(Fade::Smooth, 0.01);
+    assert_eq!(mixer.get_mono(), 1.0);
+    intensity.set_value(1.0);
+    // One beat at 120 bpm is half a second.
+    let output: Vec<f64> = (0..DEFAULT_SR as usize).map(|_| mixer.get_mono()).collect();
+    let beat = (0.5 * DEFAULT_SR) as usize;
+    assert!(output[..beat - 1].iter().all(|x| *x == 1.0));
+    assert!(output[beat] > 1.0 && output[beat] < 1.5);
+    assert_eq!(output[beat + 500], 2.0);
+    assert!(!mixer.is_active(0) && mixer.is_active(1));
+}
+
+#[test]
+fn test_music_graph() {
+    // Music graphs change sections on bar lines.
+    let ones = Arc::new(Wave64::render(DEFAULT_SR, 0.01, &mut dc(1.0)));
+    let twos = Arc::new(Wave64::render(DEFAULT_SR, 0.01, &mut dc(2.0)));
+    let mut graph = MusicGraph64::new(1);
+    graph.add_section("a", &ones, 100.0 / DEFAULT_SR);
+    graph.add_section("b", &twos, 0.0);
+    graph.set_next("b", None);
+    graph.add_rule("a", "b", Boundary::Bar, None);
+    graph.set_fade(Fade::Smooth, 0.0);
+    let mut backend = graph.backend();
+    graph.start("a");
+    let output: Vec<f64> = (0..150).map(|_| backend.get_mono()).collect();
+    assert!(output.iter().all(|x| *x == 1.0));
+    assert_eq!(graph.section(), Some("a"));
+    graph.goto("b");
+    let output: Vec<f64> = (0..600).map(|_| backend.get_mono()).collect();
+    assert!(output[..50].iter().all(|x| *x == 1.0));
+    assert!(output[50..50 + twos.len()].iter().all(|x| *x == 2.0));
+    assert_eq!(output[50 + twos.len()], 0.0);
+    assert_eq!(graph.section(), None);
+}
+
+#[test]
+fn test_harness() {
+    // The test harness renders deterministically and measures distortion.
+    let harness = Harness64::new(DEFAULT_SR).with_seed(1);
+    let a = harness.render(&mut (noise() | pink()), 0.1);
+    let b = harness.render(&mut (noise() | pink()), 0.1);
+    harness.assert_close(&a, &b, 0.0);
+    let c = Harness64::new(DEFAULT_SR)
+        .with_seed(2)
+        .render(&mut (noise() | pink()), 0.1);
+    assert!(harness.max_difference(&a, &c) > 0.0);
+    assert!(harness.thd(&mut sine_hz(1000.0), 1000.0) < 1.0e-3);
+    assert!(harness.thd(&mut saw_hz(1000.0), 1000.0) > 0.5);
+    assert!(harness.aliasing(&mut saw_hz(1000.0), 1000.0) < 0.05);
+}
+
+#[test]
+fn test_shape_curves() {
+    // Waveshaping curves.
+    assert_eq!(bitcrush(2.0).filter_mono(0.3), 0.5);
+    assert_eq!(bitcrush(1.0).filter_mono(0.3), 0.0);
+    assert_eq!(shape(Shape::Fold(1.0)).filter_mono(1.5), 0.5);
+    assert_eq!(shape(Shape::Fold(2.0)).filter_mono(-1.0), 0.0);
+    assert_eq!(shape(Shape::SoftClip(1.0)).filter_mono(2.0), 1.0);
+    assert_eq!(shape(Shape::Tube(1.0)).filter_mono(0.0), 0.0);
+    assert_eq!(shape_table(&[-1.0, 1.0]).filter_mono(0.5), 0.5);
+}
+
+#[test]
+fn test_stereo() {
+    // Stereo field tools.
+    let mut codec = ms_encode() >> ms_decode();
+    assert_eq!(codec.filter_stereo(0.5, -0.25), (0.5, -0.25));
+    assert_eq!(ms_encode().filter_stereo(1.0, 0.5), (0.75, 0.25));
+    assert_eq!(stereo_width(0.0).filter_stereo(1.0, 0.5), (0.75, 0.75));
+    assert_eq!(stereo_width(1.0).filter_stereo(1.0, 0.5), (1.0, 0.5));
+    assert_eq!(stereo_width(2.0).filter_stereo(1.0, 0.5), (1.25, 0.25));
+    let (l, r) = stereo_rotate(PI / 2.0).filter_stereo(1.0, 0.0);
+    assert!(l.abs() < 1.0e-12 && (r - 1.0).abs() < 1.0e-12);
+    let mut meter = (noise() >> split::<U2>()) >> correlation();
+    assert!((meter.get_mono() - 1.0).abs() < 1.0e-9);
+    let mut meter = (noise() >> (pass() ^ -pass())) >> correlation();
+    assert!((meter.get_mono() + 1.0).abs() < 1.0e-9);
+    // White noise is uncorrelated with itself delayed by a sample.
+    let mut meter = (noise() >> (pass() ^ tick())) >> correlation();
+    let wave = Wave64::render(44100.0, 2.0, &mut meter);
+    assert!(wave.at(0, 88199).abs() < 0.1);
+    let mut meter = (dc(0.0) | dc(0.0)) >> correlation();
+    assert_eq!(meter.get_mono(), 0.0);
+    assert_eq!(center_cancel().filter_stereo(0.5, 0.5), (0.0, 0.0));
+    assert_eq!(center_cancel().filter_stereo(1.0, 0.0), (0.5, -0.5));
+    assert_eq!(center_extract().filter_stereo(1.0, 0.5), (0.75, 0.75));
+    // Spectral center processing removes or keeps a centered tone and leaves a panned tone alone.
+    let peak = |wave: &Wave64, channel: usize| {
+        (8192..wave.len()).fold(0.0, |peak: f64, i| peak.max(wave.at(channel, i).abs()))
+    };
+    let mut x = sine_hz(440.0) >> split::<U2>() >> center_cancel_spectral();
+    let wave = Wave64::render(44100.0, 1.0, &mut x);
+    assert!(peak(&wave, 0) < 1.0e-3 && peak(&wave, 1) < 1.0e-3);
+    let mut x = sine_hz(440.0) >> split::<U2>() >> center_extract_spectral();
+    let wave = Wave64::render(44100.0, 1.0, &mut x);
+    assert!((peak(&wave, 0) - 1.0).abs() < 1.0e-2 && (peak(&wave, 1) - 1.0).abs() < 1.0e-2);
+    let mut x = (sine_hz(440.0) | dc(0.0)) >> center_cancel_spectral();
+    let wave = Wave64::render(44100.0, 1.0, &mut x);
+    assert!((peak(&wave, 0) - 1.0).abs() < 1.0e-2 && peak(&wave, 1) < 1.0e-3);
+}
+
+#[test]
+fn test_saturation() {
+    // Saturation is gain compensated: the reference amplitude keeps its level at any drive.
+    for model in [
+        SaturationModel::Tape,
+        SaturationModel::Tube,
+        SaturationModel::Diode,
+    ] {
+        for drive in [1.0, 4.0, 16.0] {
+            let mut x = sine_hz(100.0) * 0.25 >> saturate(model, drive);
+            let wave = Wave64::render(44100.0, 1.0, &mut x);
+            let peak = (22050..44100).fold(0.0, |peak: f64, i| peak.max(wave.at(0, i).abs()));
+            assert!((peak - 0.25).abs() < 0.06);
+        }
+    }
+    let mut node = Saturator::new(SaturationModel::Diode, 1.0);
+    node.set(8.0);
+    assert_eq!(node.drive(), 8.0);
+    assert!((node.filter_mono(0.25) - 0.25).abs() < 1.0e-12);
+}
+
+#[test]
+fn test_inplace() {
+    // In-place processing vs. process rendering.
+    let input = Wave64::render(44100.0, 1.0, &mut (noise() | noise()));
+    check_wave_inplace(
+        &input,
+        (pass() >> lowpole_hz(1000.0) * 0.5) | (tick() >> highpole_hz(500.0)),
+    );
+    check_wave_inplace(
+        &input,
+        ((pass() ^ tick()) >> (pass() + pass())) | butterpass_hz(1000.0),
+    );
+    check_wave_inplace(
+        &input,
+        pipe::<U3, _, _>(|i| lowpole_hz(100.0 * (i + 1) as f64) | mul(0.5)),
+    );
+    check_wave_inplace(
+        &input,
+        stack::<U2, _, _>(|i| pluck(110.0 * (i + 1) as f64, 0.5, 0.1)),
+    );
+}
+
+#[test]
+fn test_latency_compensation() {
+    // Latency compensation.
+    let mut rnd = Rnd::new();
+    let x = 3.0;
+    assert!(is_equal(
+        &mut rnd,
+        &mut compensate(pass(), tick() >> tick() >> mul(x)),
+        &mut (tick() >> tick() >> mul(x + 1.0))
+    ));
+    assert_eq!(compensate(tick(), pass()).latency(), Some(1.0));
+    assert!(is_equal(
+        &mut rnd,
+        &mut wet(0.5, tick() >> tick() >> mul(x)),
+        &mut (tick() >> tick() >> mul((x + 1.0) * 0.5))
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut bypass(tick() >> mul(x)),
+        &mut (tick() >> mul(x))
+    ));
+}
+
+#[test]
+fn test_remap() {
+    // Channel remapping.
+    let mut rnd = Rnd::new();
+    let x = 3.0;
+    let y = -4.0;
+    let z = 5.0;
+    assert!(is_equal(
+        &mut rnd,
+        &mut route::<U2>(&[1, 0]),
+        &mut reverse()
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut swap_channels::<U3>(0, 2),
+        &mut reverse()
+    ));
+    assert!(is_equal(
+        &mut rnd,
+        &mut extract_channels::<U3, U2>(&[2, 0]),
+        &mut (reverse() >> (pass() | sink() | pass()))
+    ));
+    let mut remap = route::<U3>(&[2, 2, 0]);
+    remap.set((1, None));
+    assert_eq!(
+        remap.tick(&Frame::from([x, y, z])),
+        Frame::from([z, 0.0, x])
+    );
+}
+
+#[test]
+fn test_denormals() {
+    // Denormal protection.
+    let mut rnd = Rnd::new();
+    let x = 3.0;
+    let y = -4.0;
+    assert!(is_equal(
+        &mut rnd,
+        &mut flush_denormals(mul(x) >> add(y)),
+        &mut (mul(x) >> add(y))
+    ));
+    if DENORMAL_FLUSH {
+        // Ticking flushes denormals, too.
+        let mut node = flush_denormals(mul(0.5));
+        let tiny = std::hint::black_box(f64::MIN_POSITIVE);
+        assert_eq!(node.tick(&[tiny].into())[0], 0.0);
+        let guard = DenormalGuard::new();
+        assert!(is_flushing());
+        drop(guard);
+    }
+}
+
+#[test]
+fn test_describe() {
+    // Structural descriptions.
+    let info = ((pass() ^ tick()) >> (pass() + pass())).describe();
+    assert_eq!(info.name, "Pipe");
+    assert_eq!((info.inputs, info.outputs), (1, 1));
+    assert_eq!(info.size(), 7);
+    assert_eq!(info.children[0].children[1].latency, Some(1.0));
+    let mut net = Net64::wrap(Box::new(sine_hz(110.0)));
+    net.chain(Box::new(lowpass_hz(1000.0, 1.0)));
+    assert_eq!(net.describe().children.len(), 2);
+    assert!(net.display().contains("Sine"));
+}
+
+#[test]
+#[should_panic]
+/// Test that a guard catches a blown up signal.
+fn test_guard() {
+    let mut node = dc(1.0e9) >> guard();
+    node.get_mono();
+}
+
+#[test]
+#[should_panic]
+/// Test that a panic in a parallel branch reaches the calling thread instead of hanging it.
+fn test_parallel_panic() {
+    let voices: Vec<Box<dyn AudioUnit64>> = (0..4)
+        .map(|i| {
+            Box::new(
+                dc(i as f64)
+                    >> map(|x: &Frame<f64, U1>| {
+                        assert!(x[0] < 3.0);
+                        x[0]
+                    }),
+            ) as Box<dyn AudioUnit64>
+        })
+        .collect();
+    let mut synth = Parallel64::new(ParallelMode::Bus, voices, 2);
+    let mut output = Buffer::<f64>::with_channels(1);
+    synth.process(MAX_BUFFER_SIZE, &[], output.self_mut());
+}
+
 #[test]
 /// Test a pass-through resynthesizer.
 fn test_resynth() {
@@ -573,4 +2188,363 @@ fn test_resynth() {
                 && input.at(0, i) + tolerance >= output.at(0, i)
         );
     }
+
+    // Spectral morphs reconstruct input 0 or input 1 at either end.
+    let input = Wave64::render(DEFAULT_SR, duration, &mut (noise() | sine_hz(1000.0)));
+    for (amount, channel) in [(0.0, 0), (1.0, 1)] {
+        let mut morph = SpectralMorph::new(amount, true);
+        let mut synth: An<Resynth<U2, U1, _, _>> = resynth(window, move |fft| morph.process(fft));
+        let output = input.filter_latency(duration, &mut synth);
+        for i in window..input.length() {
+            assert!((input.at(channel, i) - output.at(0, i)).abs() <= 1.0e-5);
+        }
+    }
+}
+
+#[test]
+fn test_wave_editing() {
+    let wave = Wave64::render(44100.0, 1.0, &mut (noise() | sine_hz(110.0)));
+
+    // Reversing twice restores the wave.
+    let mut reversed = wave.clone();
+    reversed.reverse();
+    assert!(reversed.at(0, 0) == wave.at(0, 44099) && reversed.at(1, 100) == wave.at(1, 43999));
+    reversed.reverse();
+    assert!(reversed.at(1, 1234) == wave.at(1, 1234));
+
+    // Slicing and appending.
+    let mut head = wave.slice(..22050);
+    let tail = wave.slice(22050..);
+    assert!(head.len() == 22050 && tail.len() == 22050 && tail.at(0, 0) == wave.at(0, 22050));
+    head.append(&tail);
+    assert!(head.len() == 44100 && head.at(1, 30000) == wave.at(1, 30000));
+
+    // Fades with custom curves.
+    let mut faded = Wave64::from_samples(44100.0, &[1.0; 100]);
+    faded.fade_in_curve(9.0 / 44100.0, |x| x);
+    assert!(faded.at(0, 0) == 0.1 && faded.at(0, 4) == 0.5 && faded.at(0, 9) == 1.0);
+    faded.fade_out_curve(9.0 / 44100.0, |x| x);
+    assert!(faded.at(0, 99) == 0.1 && faded.at(0, 90) == 1.0);
+
+    // Mixing extends the wave and broadcasts mono waves.
+    let mut mix = Wave64::zero(2, 44100.0, 1.0);
+    mix.mix_at(0.5, &Wave64::from_samples(44100.0, &[1.0; 44100]));
+    assert!(mix.duration() == 1.5);
+    assert!(mix.at(0, 22049) == 0.0 && mix.at(1, 22050) == 1.0 && mix.at(1, 66149) == 1.0);
+    mix.mix_at(0.0, &Wave64::zero(2, 44100.0, 2.0));
+    assert!(mix.duration() == 2.0 && mix.at(0, 44100) == 1.0);
+
+    // Gain.
+    let mut gained = wave.clone();
+    gained.gain_db(6.0);
+    assert!((gained.at(1, 1000) - wave.at(1, 1000) * db_amp(6.0)).abs() < 1.0e-12);
+
+    // Channel split and merge.
+    let channels = wave.split_channels();
+    assert!(channels.len() == 2 && channels[0].channels() == 1);
+    let merged = Wave64::merge_channels(&[channels[1].clone(), channels[0].clone()]);
+    assert!(merged.channels() == 2 && merged.len() == 44100);
+    assert!(merged.at(0, 500) == wave.at(1, 500) && merged.at(1, 500) == wave.at(0, 500));
+}
+
+#[test]
+fn test_wave_loop() {
+    // A 441 Hz sine wave has a period of 100 samples. Use a fixed initial phase.
+    let sine = An(Sine::with_phase(44100.0, Some(0.0)));
+    let wave = Wave64::render(44100.0, 1.0, &mut (dc(441.0) >> sine >> split::<U2>()));
+    let crossing = wave.find_zero_crossing(1234).unwrap();
+    assert!((1199..=1201).contains(&crossing));
+    assert!(wave.at(0, crossing - 1) < 0.0 && wave.at(0, crossing) >= 0.0);
+    assert!(Wave64::zero(1, 44100.0, 1.0)
+        .find_zero_crossing(100)
+        .is_none());
+
+    let (start, end) = wave.find_loop(5000, 40050, 300).unwrap();
+    assert!((4700..=5300).contains(&start) && (39750..=40350).contains(&end));
+    assert!((end - start + 1) % 100 <= 2);
+    assert!((wave.at(0, end) - wave.at(0, start)).abs() < 0.01);
+}
+
+#[test]
+fn test_interleaved() {
+    // Compare interleaved processing against sample by sample processing.
+    let mut rnd = Rnd::from_u64(1);
+    for channels in [1, 2, 9] {
+        let mut x = Net64::wrap(Box::new(multipass::<U1>()));
+        for _ in 1..channels {
+            x = x | Net64::wrap(Box::new(lowpass_hz(1000.0, 1.0)));
+        }
+        let mut y = x.clone();
+        let size = 200;
+        let input: Vec<f32> = (0..size * channels).map(|_| rnd.f32() - 0.5).collect();
+        let mut output = vec![0.0f32; size * channels];
+        x.process_interleaved(size, &input, &mut output);
+        let mut frame = vec![0.0; channels];
+        for i in 0..size {
+            let input_frame: Vec<f64> = input[i * channels..(i + 1) * channels]
+                .iter()
+                .map(|x| *x as f64)
+                .collect();
+            y.tick(&input_frame, &mut frame);
+            for j in 0..channels {
+                assert!((output[i * channels + j] as f64 - frame[j]).abs() < 1.0e-6);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hacker32_coverage() {
+    // The 32-bit prelude mirrors every function of the 64-bit prelude.
+    let names = |source: &str| -> Vec<String> {
+        let mut names: Vec<String> = source
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub fn "))
+            .map(|line| {
+                let name: String = line
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                name.replace("node64", "node32")
+            })
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(
+        names(include_str!("../src/hacker.rs")),
+        names(include_str!("../src/hacker32.rs"))
+    );
+}
+
+#[test]
+fn test_approx() {
+    // Check documented error bounds of fast approximations.
+    for i in -10000..10000 {
+        let x = i as f64 * 0.001;
+        assert!((sin_approx(x) - sin(x)).abs() < 1.0e-7);
+        assert!((cos_approx(x) - cos(x)).abs() < 1.0e-7);
+        assert!((tanh_approx(x) - tanh(x)).abs() < 1.0e-4);
+        assert!((exp2_approx(x) / exp2(x) - 1.0).abs() < 1.0e-6);
+        assert!((exp_approx(x) / exp(x) - 1.0).abs() < 1.0e-6);
+        let y = exp(x);
+        assert!((log2_approx(y) - log2(y)).abs() < 2.0e-6);
+    }
+    assert!(tanh_approx(1.0e30) == 1.0 && tanh_approx(-1.0e30) == -1.0);
+}
+
+#[test]
+fn test_snapshot() {
+    let (mut snapshot, writer) = Snapshot::new(&["dc", "peak", "parameter"]);
+    assert_eq!(snapshot.len(), 3);
+    assert_eq!(snapshot.index("peak"), Some(1));
+    assert_eq!(snapshot.index("missing"), None);
+    assert!(!snapshot.update());
+
+    let mut node = (dc(0.5) >> snapshot_meter(&writer, 0, Meter::Sample))
+        * (sine_hz(1000.0) >> snapshot_meter(&writer, 1, Meter::Peak(0.1)))
+        >> snapshot_publish(&writer);
+    writer.set(2, 3.0);
+
+    // Nothing is published before a block has been processed.
+    node.get_mono();
+    assert!(!snapshot.update());
+
+    let mut output = [0.0; MAX_BUFFER_SIZE];
+    for _ in 0..100 {
+        node.process(MAX_BUFFER_SIZE, &[], &mut [&mut output]);
+    }
+    assert!(snapshot.update());
+    assert_eq!(snapshot.total(), 1);
+    assert_eq!(snapshot.value(0), 0.5);
+    assert!(snapshot.value(1) > 0.9 && snapshot.value(1) <= 1.0);
+    assert_eq!(snapshot.value(2), 3.0);
+    // Only the latest snapshot is kept.
+    assert!(!snapshot.update());
+
+    writer.set(2, 4.0);
+    node.process(MAX_BUFFER_SIZE, &[], &mut [&mut output]);
+    assert!(snapshot.update());
+    assert_eq!(snapshot.value(2), 4.0);
+
+    // Clones of the publisher take turns with the same buffers.
+    let mut publishers = [snapshot_publish(&writer), snapshot_publish(&writer)];
+    for i in 0..10 {
+        writer.set(2, i as f32);
+        publishers[i % 2].process(
+            MAX_BUFFER_SIZE,
+            &[&output],
+            &mut [&mut [0.0; MAX_BUFFER_SIZE]],
+        );
+        assert!(snapshot.update());
+        assert_eq!(snapshot.value(2), i as f32);
+    }
+}
+
+#[test]
+fn test_wet() {
+    let mut node = bypass(tick() >> mul(2.0));
+    assert_eq!(node.compensation(), 1);
+    node.set(WetSetting::Bypass(true));
+    assert!(node.is_bypassed());
+    let mut block = node.clone();
+
+    // Bypass crossfades over 10 ms, after which the delayed dry signal passes through.
+    let length = 16 * MAX_BUFFER_SIZE;
+    let input: Vec<f64> = (0..length)
+        .map(|i| 1.0 + i as f64 / length as f64)
+        .collect();
+    let mut output = vec![0.0; length];
+    for (i, x) in input.iter().enumerate() {
+        output[i] = node.filter_mono(*x);
+    }
+    assert!(output[1] > 1.9);
+    for (i, y) in output.iter().enumerate().skip(442) {
+        assert_eq!(*y, input[i - 1]);
+    }
+    let mut processed = vec![0.0; length];
+    for i in (0..length).step_by(MAX_BUFFER_SIZE) {
+        let end = i + MAX_BUFFER_SIZE;
+        block.process(
+            MAX_BUFFER_SIZE,
+            &[&input[i..end]],
+            &mut [&mut processed[i..end]],
+        );
+    }
+    for (x, y) in output.iter().zip(processed.iter()) {
+        assert!((x - y).abs() <= 1.0e-9);
+    }
+
+    node.set(WetSetting::Bypass(false));
+    node.set(WetSetting::Amount(0.25));
+    assert_eq!(node.amount(), 0.25);
+    for _ in 0..441 {
+        node.filter_mono(1.0);
+    }
+    assert!((node.filter_mono(1.0) - 1.25).abs() <= 1.0e-9);
+}
+
+#[test]
+fn test_gain() {
+    let mut node = gain_db(-6.0);
+    assert!((node.filter_mono(1.0) - db_amp(-6.0)).abs() <= 1.0e-9);
+    assert_eq!(node.parameter(0), -6.0);
+
+    // Muting fades out smoothly and ends in exact silence.
+    node.set(f64::NEG_INFINITY);
+    let first = node.filter_mono(1.0);
+    assert!(first > 0.4 && first < db_amp(-6.0));
+    for _ in 0..44100 {
+        node.filter_mono(1.0);
+    }
+    assert_eq!(node.filter_mono(1.0), 0.0);
+
+    let mut trim = trim_db(48.0);
+    assert_eq!(trim.parameter(0), 24.0);
+    assert!((trim.filter_mono(1.0) - db_amp(24.0)).abs() <= 1.0e-9);
+
+    let level = shared(0.0);
+    let mut channel = fader(&level, 0.01);
+    assert_eq!(channel.filter_mono(1.0), 1.0);
+    level.set(MUTE_DB);
+    let mut output = [0.0; MAX_BUFFER_SIZE];
+    for _ in 0..1000 {
+        channel.process(
+            MAX_BUFFER_SIZE,
+            &[&[1.0; MAX_BUFFER_SIZE]],
+            &mut [&mut output],
+        );
+    }
+    assert_eq!(output[MAX_BUFFER_SIZE - 1], 0.0);
+    channel.set(-12.0);
+    assert_eq!(level.value(), -12.0);
+}
+
+#[test]
+fn test_channels() {
+    // One gain serves any channel count.
+    let mut gain = MultiGain64::new(6, -6.0);
+    assert_eq!(gain.inputs(), 6);
+    let mut output = [0.0; 6];
+    gain.tick(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &mut output);
+    assert!((output[5] - 6.0 * db_amp(-6.0)).abs() <= 1.0e-9);
+
+    // Downmix 5.1 to stereo.
+    let mut mixer = MultiMixer64::new(6, 2, 0.0);
+    mixer.set_gains(&[
+        1.0, 0.0, 0.5, 0.0, 0.5, 0.0, //
+        0.0, 1.0, 0.5, 0.0, 0.0, 0.5,
+    ]);
+    let mut stereo = [0.0; 2];
+    mixer.tick(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &mut stereo);
+    assert_eq!(stereo, [5.0, 6.5]);
+
+    // Stereo panning matches `pan`.
+    let mut panner = MultiPan64::new(2, 0.3);
+    let mut pair = [0.0; 2];
+    panner.tick(&[1.0], &mut pair);
+    let reference = pan(0.3).tick(&Frame::from([1.0]));
+    assert!((pair[0] - reference[0]).abs() <= 1.0e-9);
+    assert!((pair[1] - reference[1]).abs() <= 1.0e-9);
+    let mut panner = MultiPan64::new(3, 0.0);
+    let mut triple = [0.0; 3];
+    panner.tick(&[1.0], &mut triple);
+    assert!(triple[0].abs() <= 1.0e-9 && (triple[1] - 1.0).abs() <= 1.0e-9);
+
+    let mut meter = MultiMeter64::new(3, Meter::Peak(0.1));
+    let mut frame = [0.0; 3];
+    meter.tick(&[1.0, 2.0, -3.0], &mut frame);
+    assert_eq!(frame, [1.0, 2.0, -3.0]);
+    assert_eq!(meter.level(2), 3.0);
+    assert_eq!(meter.levels().len(), 3);
+}
+
+#[test]
+fn test_skip() {
+    // Skipping matches ticking with discarded output.
+    fn check_skip<X: AudioNode<Sample = f64, Inputs = U0>>(mut x: An<X>, samples: usize)
+    where
+        X::Outputs: Size<f64>,
+    {
+        let mut y = x.clone();
+        x.skip(samples, &Frame::default());
+        for _ in 0..samples {
+            y.tick(&Frame::default());
+        }
+        for _ in 0..100 {
+            let (x, y) = (x.tick(&Frame::default()), y.tick(&Frame::default()));
+            assert!(x.iter().zip(y.iter()).all(|(x, y)| (x - y).abs() < 1.0e-3));
+        }
+    }
+    check_skip(sine_hz(440.0), 1000);
+    check_skip(saw_hz(110.0), 12345);
+    check_skip(lfo(|t| sin(t * 3.0)), 50000);
+    check_skip(noise() >> delay(0.01), 300);
+    check_skip(dc(1.0) >> delay(0.01) | sine_hz(1.0), 3000);
+    // Constants through delays reach the next node only after the delay.
+    check_skip(dc(1.0) >> delay(0.01) >> lowpass_hz(1000.0, 1.0), 200);
+    check_skip(dc(1.0) >> delay(0.01) >> lowpass_hz(1000.0, 1.0), 2000);
+    check_skip(dc(1.0) >> tick() >> follow(0.01), 1);
+    check_skip(dc(220.0) * 2.0 >> saw(), 5000);
+    let wave = Arc::new(Wave64::render(DEFAULT_SR, 0.1, &mut noise()));
+    check_skip(wave64(&wave, 0, Some(1000)), 10000);
+    check_skip(wave64(&wave, 0, None), 10000);
+
+    // Sequencer events can be fast-forwarded through the object safe interface.
+    let mut unit: Box<dyn AudioUnit64> = Box::new(sine_hz(441.0) * 0.5);
+    let mut reference = unit.clone();
+    unit.skip(50);
+    for _ in 0..50 {
+        reference.get_mono();
+    }
+    assert!((unit.get_mono() - reference.get_mono()).abs() < 1.0e-9);
+
+    // Networks skip in blocks.
+    let mut net = Net64::wrap(Box::new(noise() >> lowpass_hz(1000.0, 1.0)));
+    let mut reference = net.clone();
+    net.skip(1000);
+    for _ in 0..1000 {
+        reference.get_mono();
+    }
+    assert!((net.get_mono() - reference.get_mono()).abs() < 1.0e-6);
 }
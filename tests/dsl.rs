@@ -0,0 +1,69 @@
+//! Patch language tests.
+
+#![cfg(feature = "dsl")]
+
+use fundsp::hacker::*;
+
+#[test]
+fn test_dsl() {
+    let patch = Patch64::new();
+
+    // Parsed patches render like the same expressions written in Rust.
+    // Oscillator phases are derived from the graph structure, so use a fixed-phase source.
+    let mut net = patch
+        .parse("dc(1.0) >> lowpass_hz(800.0, 1.0) * 0.3")
+        .unwrap();
+    let mut graph = dc(1.0) >> lowpass_hz(800.0, 1.0) * 0.3;
+    for _ in 0..1000 {
+        assert!((net.get_mono() - graph.get_mono()).abs() < 1.0e-9);
+    }
+
+    // Operator precedence follows Rust.
+    let mut net = patch.parse("dc(1.0) + 2.0 * 3.0 | -dc(4.0 / 2.0)").unwrap();
+    assert_eq!(net.get_stereo(), (7.0, -2.0));
+    assert!(patch
+        .parse("(dc(1.0) | dc(2.0)) >> join(2) & split(2) >> add(1.0)")
+        .is_err());
+    let mut net = patch.parse("dc(0.5) >> (pass() ^ mul(2.0))").unwrap();
+    assert_eq!(net.get_stereo(), (0.5, 1.0));
+    net = patch
+        .parse("!lowpass() >> (pass() | sink() | sink())")
+        .unwrap();
+    assert_eq!((net.inputs(), net.outputs()), (3, 1));
+
+    // Custom opcodes.
+    let mut patch = Patch64::new();
+    patch.add_opcode("twice", 1, |a| Net64::wrap(Box::new(dc(2.0 * a[0]))));
+    assert!(patch.has_opcode("twice"));
+    assert_eq!(patch.parse("twice(1.5 + 1.5)").unwrap().get_mono(), 6.0);
+
+    // Errors are reported with positions.
+    assert_eq!(
+        patch.parse("sine_hz(1.0) $").err(),
+        Some(PatchError::Character(13))
+    );
+    assert_eq!(
+        patch.parse("sine_hz(1.0) >>").err(),
+        Some(PatchError::Token(15))
+    );
+    assert_eq!(
+        patch.parse("bogus()").err(),
+        Some(PatchError::Opcode("bogus".to_string(), 0))
+    );
+    assert_eq!(
+        patch.parse("sine_hz(1.0, 2.0)").err(),
+        Some(PatchError::Arguments("sine_hz".to_string(), 1, 2))
+    );
+    assert_eq!(
+        patch.parse("delay(sine())").err(),
+        Some(PatchError::Number(6))
+    );
+    assert_eq!(
+        patch.parse("sine() / 2.0").err(),
+        Some(PatchError::Operator(7))
+    );
+    assert_eq!(
+        patch.parse("pan(0.0) >> sine()").err(),
+        Some(PatchError::Channels(9))
+    );
+}
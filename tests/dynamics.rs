@@ -6,6 +6,7 @@
 use fundsp::dynamics::*;
 use fundsp::hacker::*;
 use funutd::*;
+use std::sync::Arc;
 
 #[test]
 fn test_dynamics() {
@@ -69,4 +70,199 @@ fn test_dynamics() {
         assert!(x > 0.0 && x == x1 && x2 >= 0.0);
         assert_eq!(x2, s1.value());
     }
+
+    // Test level meter. A stereo 1 kHz sine at -20 dBFS reads -20 LUFS.
+    let levels = Arc::new(Levels::new());
+    let amplitude = db_amp(-20.0);
+    let mut x =
+        ((sine_hz(1000.0) | sine_hz(1000.0)) * amplitude) >> level_meter_stereo(&levels, 0.3);
+    for _ in 0..5 * 44100 {
+        x.get_stereo();
+    }
+    assert!((levels.peak() - amplitude).abs() < 0.001);
+    assert!((levels.rms() - amplitude * sqrt(0.5)).abs() < 0.001);
+    assert!((levels.momentary() + 20.0).abs() < 0.1);
+    assert!((levels.short_term() + 20.0).abs() < 0.1);
+    assert!((levels.integrated() + 20.0).abs() < 0.1);
+    // Silence is gated out of integrated loudness. Render a block at a time.
+    let gate = lfo(|t: f64| if t < 3.0 { 1.0 } else { 0.0 });
+    let mut x = ((sine_hz(1000.0) | sine_hz(1000.0)) * amplitude * (gate.clone() | gate))
+        >> level_meter_stereo(&levels, 0.3);
+    Wave64::render(44100.0, 6.0, &mut x);
+    assert_eq!(levels.momentary(), f64::NEG_INFINITY);
+    // Gating blocks that overlap the end of the tone lower the reading slightly.
+    assert!((levels.integrated() + 20.2).abs() < 0.1);
+
+    // Test filterbank analyzer. A sine shows up in its own band only.
+    let centers = third_octave_bands();
+    assert_eq!(centers.len(), 31);
+    assert!((centers[17] - 1000.0).abs() < 1.0e-9);
+    let mut x = sine_hz(1000.0) >> filterbank_analyzer::<U31>(&centers, 0.1);
+    let mut output = Frame::default();
+    for _ in 0..44100 {
+        output = x.tick(&Frame::default());
+    }
+    assert!((output[17] - sqrt(0.5)).abs() < 0.01);
+    assert!(output[11] < 0.01 && output[23] < 0.01);
+    // Bands above the Nyquist frequency read zero.
+    assert_eq!(output[30], 0.0);
+    let bands = Arc::new(BandLevels::new(&constant_q_bands(100.0, 10000.0, 1.0)));
+    let mut x = sine_hz(1000.0) >> filterbank_meter(&bands, 0.1);
+    for _ in 0..44100 {
+        x.get_mono();
+    }
+    assert_eq!(bands.bands(), 7);
+    assert!((bands.center(3) - 1000.0).abs() < 1.0e-9);
+    assert!((bands.level(3) - sqrt(0.5)).abs() < 0.01);
+    assert!(bands.level(0) < 0.02);
+
+    // Test ducker. The signal is ducked fully under a loud key and recovers when the key stops.
+    let key = lfo(|t: f64| if t >= 0.5 && t < 1.0 { 0.5 } else { 0.0 });
+    let mut x = (dc(1.0) | key) >> ducker(0.1, 0.75, 0.01, 0.1);
+    let wave = Wave64::render(44100.0, 2.0, &mut x);
+    assert_eq!(wave.at(0, 0), 1.0);
+    assert!((wave.at(0, 40000) - 0.25).abs() < 1.0e-6);
+    assert!((wave.at(0, 88000) - 1.0).abs() < 1.0e-6);
+    // A key below threshold leaves the signal alone.
+    let mut x = (dc(1.0) | dc(0.05)) >> ducker(0.1, 0.75, 0.01, 0.1);
+    for _ in 0..44100 {
+        assert_eq!(x.get_mono(), 1.0);
+    }
+
+    // Test transient shaper. Attacks are boosted, steady signals pass and decays are cut.
+    let level = lfo(|t: f64| {
+        if t < 0.5 {
+            0.0
+        } else if t < 1.5 {
+            0.5
+        } else {
+            0.1
+        }
+    });
+    let mut x = level >> transient(2.0, 0.5);
+    let wave = Wave64::render(44100.0, 2.0, &mut x);
+    assert_eq!(wave.at(0, 0), 0.0);
+    // The boosted attack peaks at double the level shortly after the step.
+    let peak = (22050..22650).map(|i| wave.at(0, i)).fold(0.0, f64::max);
+    assert!((peak - 1.0).abs() < 1.0e-2);
+    assert!((wave.at(0, 61740) - 0.5).abs() < 1.0e-3);
+    assert!(wave.at(0, 79380) < 0.075);
+    assert!(wave.at(0, 79380) > 0.04);
+    // Unit gains leave the signal alone.
+    let mut x = pink() >> ((pass() ^ transient(1.0, 1.0)) >> (pass() - pass()));
+    for _ in 0..44100 {
+        assert!(x.get_mono().abs() < 1.0e-9);
+    }
+
+    // Test de-esser. Low frequencies pass unchanged while loud high frequencies are compressed.
+    let mut x = (sine_hz(200.0) * 0.5) >> deesser(5000.0, 0.05, 4.0);
+    let mut y = sine_hz(200.0) * 0.5;
+    for _ in 0..44100 {
+        assert!((x.get_mono() - y.get_mono()).abs() < 1.0e-6);
+    }
+    let mut x = (sine_hz(8000.0) * 0.5) >> deesser(5000.0, 0.05, 4.0);
+    let wave = Wave64::render(44100.0, 1.0, &mut x);
+    let peak = (22050..44100).fold(0.0, |peak: f64, i| peak.max(wave.at(0, i).abs()));
+    assert!(peak < 0.25);
+
+    // Test onset detector. Each tone burst produces exactly one onset impulse
+    // while a steady tone only produces one at its start.
+    let gate = lfo(|t: f64| if (t * 4.0) % 1.0 < 0.1 { 0.5 } else { 0.0 });
+    let mut x = (sine_hz(440.0) * gate) >> onset(0.5);
+    let wave = Wave64::render(44100.0, 1.0, &mut x);
+    let onsets: Vec<usize> = (0..wave.len()).filter(|&i| wave.at(0, i) > 0.0).collect();
+    assert_eq!(onsets.len(), 4);
+    for (i, onset) in onsets.iter().enumerate() {
+        assert_eq!(wave.at(0, *onset), 1.0);
+        let expected = i * 11025;
+        assert!(*onset >= expected && *onset < expected + 441);
+    }
+    let mut x = (sine_hz(440.0) * 0.5) >> onset(0.5);
+    let wave = Wave64::render(44100.0, 1.0, &mut x);
+    assert_eq!(wave.channel(0).iter().sum::<f64>(), 1.0);
+
+    // Test tempo detection on tone bursts at 120 and 90 BPM.
+    for bpm in [120.0, 90.0] {
+        let beat = 60.0 / bpm;
+        let gate = lfo(move |t: f64| if (t / beat) % 1.0 < 0.1 { 0.5 } else { 0.0 });
+        let mut x = sine_hz(440.0) * gate;
+        let mut wave = Wave64::render(44100.0, 8.0, &mut x);
+        assert!((wave.detect_bpm().unwrap() - bpm).abs() < 1.0);
+        let tempo = wave.filter(8.0, &mut tempo_tracker(60.0, 180.0));
+        assert_eq!(tempo.at(0, 0), 0.0);
+        assert!((tempo.at(0, tempo.len() - 1) - bpm).abs() < 1.0);
+        // One second is too short to cover two beats at the minimum tempo.
+        wave.resize(44100);
+        assert!(wave.detect_bpm().is_none());
+    }
+
+    // Test click repair. Impulsive clicks are interpolated over and clean signals pass.
+    let clean = Wave64::render(44100.0, 1.0, &mut (sine_hz(440.0) * 0.5));
+    let mut wave = clean.clone();
+    for i in [5000, 15000, 25000] {
+        wave.set(0, i, wave.at(0, i) + 0.3);
+    }
+    wave.set(0, 35000, -0.8);
+    wave.set(0, 35001, 0.8);
+    assert_eq!(wave.repair_clicks(0.5), 4);
+    for i in 0..wave.len() {
+        assert!((wave.at(0, i) - clean.at(0, i)).abs() < 0.01);
+    }
+    let mut wave = clean.clone();
+    assert_eq!(wave.repair_clicks(1.0), 0);
+    assert_eq!(wave.channel(0), clean.channel(0));
+    // The start and the end of the wave are not mistaken for clicks.
+    for phase in [0.0, 0.25, 0.5, 0.75] {
+        let sine = An(Sine::with_phase(44100.0, Some(phase)));
+        let mut wave = Wave64::render(44100.0, 1.0, &mut (dc(440.0) >> sine * 0.5));
+        wave.set(0, 10000, 1.0);
+        assert_eq!(wave.repair_clicks(0.5), 1);
+    }
+    let mut x = click_repair(0.5);
+    assert_eq!(x.latency(), Some(64.0));
+
+    // Test true peak limiter. A sine at a quarter of the sample rate, sampled at 45 degrees,
+    // has sample peaks 3 dB below its true peaks.
+    let mut wave = Wave64::new(1, 44100.0);
+    for i in 0..44100 {
+        wave.push(sin(PI * 0.5 * i as f64 + PI * 0.25));
+    }
+    let wave = Arc::new(wave);
+    let reduction = shared(0.0);
+    let ceiling = db_amp(-1.0);
+    let mut x = wave64(&wave, 0, None) >> limiter_true_peak(ceiling, 0.005, 0.1, &reduction);
+    let mut peak: f64 = 0.0;
+    for i in 0..44100 {
+        let y = x.get_mono();
+        if i > 1000 {
+            peak = peak.max(y.abs());
+        }
+    }
+    assert!(peak * SQRT_2 <= ceiling * 1.01 && peak * SQRT_2 >= ceiling * 0.98);
+    assert!((reduction.value() - 1.0).abs() < 0.2);
+    // Quiet signals pass through unchanged after the latency.
+    let mut x =
+        (wave64(&wave, 0, None) * 0.1) >> limiter_true_peak(ceiling, 0.005, 0.1, &reduction);
+    let latency = round(0.005 * 44100.0) as usize - 1 + 6;
+    for i in 0..10000 {
+        let y = x.get_mono();
+        if i >= latency {
+            assert!((y - wave.at(0, i - latency) * 0.1).abs() < 1.0e-12);
+        }
+    }
+    assert_eq!(reduction.value(), 0.0);
+
+    // Test offline loudness measurement. Gating ignores silence.
+    let mut x = (sine_hz(1000.0) | sine_hz(1000.0)) * amplitude;
+    let mut wave = Wave64::render(48000.0, 5.0, &mut x);
+    assert!((wave.measure_lufs() + 20.0).abs() < 0.05);
+    wave.resize(wave.len() * 2);
+    // Gating blocks that overlap the end of the tone lower the reading slightly.
+    assert!((wave.measure_lufs() + 20.15).abs() < 0.05);
+    wave.normalize_lufs(-14.0);
+    assert!((wave.measure_lufs() + 14.0).abs() < 1.0e-6);
+    assert_eq!(
+        Wave64::zero(1, 48000.0, 1.0).measure_lufs(),
+        f64::NEG_INFINITY
+    );
 }
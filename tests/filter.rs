@@ -75,16 +75,19 @@ fn is_equal_response(x: Complex64, y: Complex64) -> bool {
     let abs_tolerance = 1.0e-9;
     let amp_tolerance = db_amp(0.05);
     let phase_tolerance = 1.0e-4 * TAU;
+    // Below this magnitude (-100 dB), phase is dominated by numerical noise.
+    let phase_floor = 1.0e-5;
     let x_norm = x.norm();
     let y_norm = y.norm();
     let x_phase = x.arg();
     let y_phase = y.arg();
     x_norm / amp_tolerance - abs_tolerance <= y_norm
         && x_norm * amp_tolerance + abs_tolerance >= y_norm
-        && min(
-            abs(x_phase - y_phase),
-            min(abs(x_phase - y_phase + TAU), abs(x_phase - y_phase - TAU)),
-        ) <= phase_tolerance
+        && (max(x_norm, y_norm) < phase_floor
+            || min(
+                abs(x_phase - y_phase),
+                min(abs(x_phase - y_phase + TAU), abs(x_phase - y_phase - TAU)),
+            ) <= phase_tolerance)
 }
 
 fn test_response<X>(mut filter: X)
@@ -163,6 +166,192 @@ fn test_misc() {
             .norm()
             > 0.1
     );
+    let mut node = feedback(0.5 * pass());
+    assert!((node.response(0, 0.0).unwrap().norm() - 1.0).abs() < epsilon);
+    assert!((node.response(0, 22050.0).unwrap().norm() - 1.0 / 3.0).abs() < epsilon);
+
+    // Runtime coefficient filters match their fixed size and FIR counterparts.
+    let mut x = fir((0.1, 0.2, 0.3, 0.4));
+    let mut y = fir_coefs(&[0.4, 0.3, 0.2, 0.1]);
+    let mut z = iir(&[0.8, 0.6, 0.4, 0.2], &[2.0]);
+    let mut rnd = Rnd::new();
+    for _ in 0..100 {
+        let input = rnd.f64();
+        let output = x.filter_mono(input);
+        assert!((y.filter_mono(input) - output).abs() < epsilon);
+        assert!((z.filter_mono(input) - output).abs() < epsilon);
+    }
+    let mut node = FirFilter::new(&[1.0]);
+    node.set(vec![0.0, 1.0]);
+    assert_eq!(node.filter_mono(1.0), 0.0);
+    assert_eq!(node.filter_mono(0.0), 1.0);
+    // A one-pole lowpass filter.
+    let mut node = IirFilter::new(&[0.5], &[1.0, -0.5]);
+    assert_eq!(node.filter_mono(1.0), 0.5);
+    assert_eq!(node.filter_mono(1.0), 0.75);
+    node.set((vec![1.0], vec![1.0]));
+    assert_eq!(node.filter_mono(1.0), 1.0);
+
+    // Windowed sinc designs.
+    let gain = |b: &[f64], f: f64| fir_coefs(b).response(0, f).unwrap().norm();
+    for window in [
+        FirWindow::Rectangular,
+        FirWindow::Hann,
+        FirWindow::Hamming,
+        FirWindow::Blackman,
+        FirWindow::Kaiser(8.0),
+    ] {
+        let b = sinc_lowpass(101, 44100.0, 2000.0, window);
+        assert!((gain(&b, 0.0) - 1.0).abs() < epsilon);
+        assert!((gain(&b, 2000.0) - 0.5).abs() < 0.05);
+        let b = sinc_highpass(101, 44100.0, 2000.0, window);
+        assert!((gain(&b, 22050.0) - 1.0).abs() < epsilon);
+        assert!(gain(&b, 0.0) < epsilon);
+        let b = sinc_bandpass(101, 44100.0, 2000.0, 6000.0, window);
+        assert!((gain(&b, 4000.0) - 1.0).abs() < epsilon);
+        assert!(gain(&b, 0.0) < epsilon);
+        let b = sinc_bandstop(101, 44100.0, 2000.0, 6000.0, window);
+        assert!((gain(&b, 0.0) - 1.0).abs() < epsilon);
+    }
+    let b = sinc_lowpass(101, 44100.0, 2000.0, FirWindow::Blackman);
+    assert!(gain(&b, 6000.0) < 1.0e-3);
+
+    // Designed IIR filters meet their passband and stopband specifications.
+    let db = |node: &mut An<IirCascade<f64>>, f: f64| amp_db(node.response(0, f).unwrap().norm());
+    let mut node = iir_lowpass(FilterPrototype::Butterworth, 7, 1000.0);
+    assert!(node.design().sections() == 4);
+    assert!(db(&mut node, 0.0).abs() < 1.0e-6);
+    assert!((db(&mut node, 1000.0) + 3.0103).abs() < 1.0e-3);
+    assert!(db(&mut node, 4000.0) < -80.0);
+    let mut node = iir_highpass(FilterPrototype::Butterworth, 2, 1000.0);
+    assert!(db(&mut node, 22050.0).abs() < 1.0e-6);
+    assert!((db(&mut node, 1000.0) + 3.0103).abs() < 1.0e-3);
+    for order in 1..=8 {
+        let mut node = iir_lowpass(FilterPrototype::Chebyshev1(1.0), order, 1000.0);
+        let mut f = 0.0;
+        while f <= 1000.0 {
+            let gain = db(&mut node, f);
+            assert!(gain < 1.0e-6 && gain > -1.0 - 1.0e-6);
+            f += 10.0;
+        }
+        let mut node = iir_lowpass(FilterPrototype::Chebyshev2(40.0), order, 1000.0);
+        assert!(db(&mut node, 0.0).abs() < 1.0e-6);
+        let mut f = 1000.0;
+        while f <= 22050.0 {
+            assert!(db(&mut node, f) < -40.0 + 1.0e-6);
+            f += 50.0;
+        }
+        let mut node = iir_highpass(FilterPrototype::Elliptic(0.5, 60.0), order, 1000.0);
+        let mut f = 1000.0;
+        while f <= 22050.0 {
+            let gain = db(&mut node, f);
+            assert!(gain < 1.0e-6 && gain > -0.5 - 1.0e-6);
+            f += 50.0;
+        }
+    }
+    // An 8th order elliptic lowpass reaches its stopband attenuation quickly.
+    let mut node = iir_lowpass(FilterPrototype::Elliptic(0.5, 60.0), 8, 1000.0);
+    assert!(db(&mut node, 1200.0) < -60.0);
+    // Retuning moves the cutoff.
+    node.set(2000.0);
+    assert!(db(&mut node, 2000.0) > -0.5 - 1.0e-6);
+    assert!(db(&mut node, 2400.0) < -60.0);
+    let coefs = FilterDesign::new(FilterPrototype::Butterworth, FilterPass::Lowpass, 3)
+        .biquads::<f64>(44100.0, 1000.0);
+    assert!(coefs.len() == 2);
+
+    // Linear phase FFT convolution matches direct FIR filtering after the block latency.
+    let b = sinc_lowpass(701, 44100.0, 1000.0, FirWindow::Blackman);
+    let mut x = fir_coefs(&b);
+    let mut y = linear_phase(&b);
+    assert!(y.block_length() == 512);
+    assert!(y.latency() == Some(512.0 + 350.0));
+    let mut expected = Vec::new();
+    for _ in 0..2000 {
+        let input = rnd.f64() - 0.5;
+        expected.push(x.filter_mono(input));
+        let output = y.filter_mono(input);
+        if expected.len() > 512 {
+            assert!((output - expected[expected.len() - 513]).abs() < epsilon);
+        } else {
+            assert!(output == 0.0);
+        }
+    }
+    // Latency is compensated in parallel paths.
+    assert!(compensate(pass(), linear_phase(&[0.25, 0.5, 0.25])).compensation() == 17);
+    // Frequency sampling design interpolates the desired magnitude response.
+    let mut bell = bell_hz(1000.0, 1.0, 2.0);
+    let b = fir_design(
+        1023,
+        44100.0,
+        |f| bell.response(0, f).unwrap().norm(),
+        FirWindow::Hann,
+    );
+    let mut node = linear_phase(&b);
+    for f in [100.0, 1000.0, 4000.0, 12000.0] {
+        let gain = node.response(0, f).unwrap().norm();
+        assert!((gain - bell.response(0, f).unwrap().norm()).abs() < 0.02);
+    }
+
+    // Phase rotators rotate the phase by 90 degrees per stage at the tuning frequency.
+    let mut node = phase_rotator(1, 1000.0);
+    assert!((node.response(0, 1000.0).unwrap().arg() + PI * 0.5).abs() < epsilon);
+    let mut node = phase_rotator(3, 1000.0);
+    assert!(node.stages() == 3);
+    assert!((node.response(0, 1000.0).unwrap().arg() - PI * 0.5).abs() < epsilon);
+    for f in [10.0, 1000.0, 5000.0, 20000.0] {
+        assert!((node.response(0, f).unwrap().norm() - 1.0).abs() < epsilon);
+    }
+    node.set(2000.0);
+    assert!((node.response(0, 2000.0).unwrap().arg() - PI * 0.5).abs() < epsilon);
+}
+
+/// Count positive zero crossings of `node` in one second after a warmup.
+fn crossings(node: &mut dyn AudioUnit64) -> usize {
+    for _ in 0..4410 {
+        node.get_mono();
+    }
+    let mut previous = node.get_mono();
+    let mut count = 0;
+    for _ in 0..44100 {
+        let value = node.get_mono();
+        if previous < 0.0 && value >= 0.0 {
+            count += 1;
+        }
+        previous = value;
+    }
+    count
+}
+
+#[test]
+fn test_shift() {
+    // Frequency shifters move all frequencies by the same amount.
+    assert!(crossings(&mut (sine_hz(1000.0) >> freq_shift(100.0))).abs_diff(1100) <= 1);
+    assert!(crossings(&mut (sine_hz(1000.0) >> freq_shift(-300.0))).abs_diff(700) <= 1);
+    let mut node = sine_hz(1000.0) >> freq_shift(100.0);
+    for _ in 0..4410 {
+        node.get_mono();
+    }
+    for _ in 0..4410 {
+        assert!(node.get_mono().abs() < 1.01);
+    }
+
+    // The analytic signal of a sinusoid has a constant magnitude.
+    let mut node = sine_hz(1000.0) >> hilbert();
+    for _ in 0..4410 {
+        node.get_stereo();
+    }
+    for _ in 0..4410 {
+        let (i, q) = node.get_stereo();
+        assert!((i.hypot(q) - 1.0).abs() < 0.01);
+    }
+
+    // Ring modulators block DC from both inputs.
+    let mut node = (dc(1.0) | dc(1.0)) >> ring_mod();
+    for _ in 0..44100 {
+        node.get_mono();
+    }
+    assert!(node.get_mono().abs() < 1.0e-3);
 }
 
 /// Test frequency response system.
@@ -184,6 +373,8 @@ fn test_responses() {
     test_response(delay(0.0001));
     test_response(delay(0.0001) >> delay(0.0002));
     test_response(dcblock());
+    test_response(comb_ff(0.01, -0.5));
+    test_response(comb_fb(0.01, 0.5));
     test_response(dcblock_hz(100.0) & follow(0.001));
     test_response(lowpole_hz(1000.0));
     test_response(split() >> (lowpole_hz(100.0) + lowpole_hz(190.0)));
@@ -242,6 +433,29 @@ fn test_responses() {
     test_response(fir((0.5, 0.5)) | timer(&tmp));
     test_response(fir((0.25, 0.5, 0.25)) >> monitor(&tmp, Meter::Sample));
     test_response(fir((0.4, 0.3, 0.2, 0.1)));
+    test_response(fir_coefs(&[0.4, 0.3, 0.2, 0.1]));
+    test_response(fir_coefs(&sinc_bandpass(
+        31,
+        44100.0,
+        1000.0,
+        3000.0,
+        FirWindow::Blackman,
+    )));
+    test_response(iir(&[0.1, 0.05], &[1.0, -0.9, 0.2]));
+    test_response(iir(&[0.2, 0.1, 0.1], &[2.0, -1.0]));
+    test_response(iir_lowpass(FilterPrototype::Butterworth, 5, 2000.0));
+    test_response(iir_highpass(FilterPrototype::Chebyshev1(1.0), 4, 1000.0));
+    test_response(iir_lowpass(FilterPrototype::Chebyshev2(40.0), 5, 3000.0));
+    test_response(iir_highpass(FilterPrototype::Elliptic(0.5, 50.0), 6, 500.0));
+    test_response(linear_phase(&sinc_lowpass(
+        63,
+        44100.0,
+        2000.0,
+        FirWindow::Hamming,
+    )));
+    test_response(linear_phase(&[0.25, 0.5, 0.25]));
+    test_response(phase_rotator(4, 200.0));
+    test_response(phase_rotator(1, 5000.0));
     test_response(morph_hz(1000.0, 1.0, 0.5));
     test_response(morph_hz(2000.0, 2.0, -0.5));
     test_response((pass() | dc((1000.0, 0.5, 0.5))) >> morph());
@@ -257,6 +471,10 @@ fn test_responses() {
     test_response(pass() + 1.0 >> lowpass_hz(1000.0, 1.0));
     test_response((pass() | dc(1.0)) >> rotate(0.5, 1.0) >> (pass() | sink()));
     test_response((dc(2.0) | pass()) >> rotate(-0.1, 0.5) >> (pass() | sink()));
+    test_response((pass() | dc(1.0)) >> stereo_rotate(0.5) >> (sink() | pass()));
+    test_response((pass() | dc(1.0)) >> stereo_width(1.5) >> (pass() | sink()));
+    test_response((dc(1.0) | pass()) >> ms_encode() >> (sink() | pass()));
+    test_response((pass() | dc(1.0)) >> ms_decode() >> (pass() | sink()));
 
     let mut net1 = Net64::new(1, 1);
     net1.chain(Box::new(lowpole_hz(1500.0)));
@@ -327,6 +545,27 @@ fn test_responses() {
             >> (Net64::wrap(Box::new(pass())) | pass() | pinkpass())
             >> (Net64::wrap(Box::new(pinkpass())) + pass() + pass()),
     );
+
+    // Feedback loops.
+    test_response(feedback(lowpass_hz(1000.0, 1.0) * 0.5));
+    test_response(pass() & feedback(delay(0.001) >> lowpole_hz(2000.0) * 0.7));
+    test_response(feedback2(pass() * 0.5, lowpole_hz(3000.0)));
+    test_response(
+        split()
+            >> feedback((lowpole_hz(500.0) * 0.4 | highpass_hz(2000.0, 1.0) * 0.4) >> reverse())
+            >> join(),
+    );
+    test_response(
+        split::<U4>()
+            >> fdn(stack::<U4, _, _>(|i| {
+                delay(0.0001 * (i + 1) as f64) >> lowpole_hz(1000.0 * (i + 1) as f64) * 0.5
+            }))
+            >> join(),
+    );
+    test_response(Feedback64::new(
+        0.0005,
+        Box::new(bell_hz(2000.0, 2.0, 4.0) * 0.2),
+    ));
 }
 
 // Test various allpass filters for the allpass property.
@@ -365,3 +604,46 @@ fn test_allpass() {
         }
     }
 }
+
+#[test]
+fn test_ir_switch() {
+    let mut rnd = Rnd::new();
+    let mut library = ImpulseLibrary::new(64);
+    assert!(library.add(&[1.0]) == 0);
+    assert!(library.add(&[0.0, 0.0, 0.5]) == 1);
+    let long: Vec<f64> = (0..300).map(|_| rnd.f64() - 0.5).collect();
+    assert!(library.add(&long) == 2);
+    let library = std::sync::Arc::new(library);
+
+    // Convolution with a multi-partition impulse response matches direct FIR filtering.
+    let mut x = fir_coefs(&long);
+    let mut y = IrSwitch::<f64>::new(&library, 2, 0.01);
+    assert!(y.latency() == Some(64.0));
+    let mut expected = Vec::new();
+    for _ in 0..1000 {
+        let input = rnd.f64() - 0.5;
+        expected.push(x.filter_mono(input));
+        let output = y.filter_mono(input);
+        if expected.len() > 64 {
+            assert!((output - expected[expected.len() - 65]).abs() < 1.0e-9);
+        }
+    }
+
+    // Switching crossfades smoothly between impulse responses.
+    let mut node = ir_switch(&library, 0.01);
+    for _ in 0..200 {
+        node.filter_mono(1.0);
+    }
+    assert!((node.filter_mono(1.0) - 1.0).abs() < 1.0e-9);
+    node.set(1);
+    let mut previous = 1.0;
+    for _ in 0..64 + 441 {
+        let output = node.filter_mono(1.0);
+        assert!(output <= previous + 1.0e-9 && output >= 0.5 - 1.0e-9);
+        previous = output;
+    }
+    assert!(node.current() == 1 && !node.is_fading());
+    assert!((node.filter_mono(1.0) - 0.5).abs() < 1.0e-9);
+
+    test_response(ir_switch(&library, 0.01));
+}
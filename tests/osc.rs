@@ -0,0 +1,102 @@
+//! Open Sound Control tests.
+
+#![cfg(feature = "osc")]
+
+use fundsp::hacker::*;
+
+#[test]
+fn test_osc_codec() {
+    let message = OscMessage::new(
+        "/synth/1/note",
+        vec![
+            OscArg::Int(60),
+            OscArg::Float(0.5),
+            OscArg::Double(-1.25),
+            OscArg::Long(1 << 40),
+            OscArg::String(String::from("pad")),
+            OscArg::Blob(vec![1, 2, 3, 4, 5]),
+            OscArg::Bool(true),
+            OscArg::Nil,
+        ],
+    );
+    let packet = message.encode();
+    assert_eq!(packet.len() % 4, 0);
+    assert_eq!(OscMessage::decode(&packet).unwrap(), vec![message.clone()]);
+    assert_eq!(message.number(0), Some(60.0));
+    assert_eq!(message.number(4), None);
+
+    // Bundles are flattened in order.
+    let second = OscMessage::new("/b", vec![]);
+    let mut bundle = Vec::new();
+    bundle.extend_from_slice(b"#bundle\0");
+    bundle.extend_from_slice(&1u64.to_be_bytes());
+    for element in [message.encode(), second.encode()] {
+        bundle.extend_from_slice(&(element.len() as i32).to_be_bytes());
+        bundle.extend_from_slice(&element);
+    }
+    assert_eq!(OscMessage::decode(&bundle).unwrap(), vec![message, second]);
+
+    // Malformed packets are rejected.
+    assert_eq!(OscMessage::decode(&packet[..packet.len() - 2]), Err(OscError::Truncated));
+    assert_eq!(OscMessage::decode(b"abc\0"), Err(OscError::Address));
+    assert!(OscMessage::decode(b"/abc").is_err());
+}
+
+#[test]
+fn test_osc_match() {
+    assert!(osc_match("/a/b", "/a/b"));
+    assert!(!osc_match("/a/b", "/a/b/c"));
+    assert!(osc_match("/a/*", "/a/gain"));
+    assert!(osc_match("/a/g*n", "/a/gain"));
+    assert!(!osc_match("/a/*", "/b/gain"));
+    assert!(osc_match("/ch?/pan", "/ch3/pan"));
+    assert!(!osc_match("/ch?/pan", "/ch10/pan"));
+    assert!(osc_match("/fader[1-4]", "/fader3"));
+    assert!(!osc_match("/fader[!1-4]", "/fader3"));
+    assert!(osc_match("/fader[!1-4]", "/fader7"));
+    assert!(osc_match("/{left,right}/pan", "/left/pan"));
+    assert!(!osc_match("/{left,right}/pan", "/center/pan"));
+}
+
+#[test]
+fn test_osc_router() {
+    let gain = shared(0.0);
+    let cutoff = shared(0.0);
+    let mut router = OscRouter::<()>::new();
+    router.add_shared("/mix/gain", &gain);
+    router.add_shared_range("/mix/cutoff", &cutoff, 100.0, 1100.0);
+    assert_eq!(router.methods(), 2);
+
+    let message = OscMessage::new("/mix/gain", vec![OscArg::Int(2)]);
+    assert_eq!(router.dispatch(&message, &mut ()), 1);
+    assert_eq!(gain.value(), 2.0);
+
+    let packet = OscMessage::new("/mix/*", vec![OscArg::Float(0.25)]).encode();
+    assert_eq!(router.handle(&packet, &mut ()), Ok(2));
+    assert_eq!(gain.value(), 0.25);
+    assert_eq!(cutoff.value(), 350.0);
+
+    // Range mapping clamps the argument.
+    let message = OscMessage::new("/mix/cutoff", vec![OscArg::Double(2.0)]);
+    router.dispatch(&message, &mut ());
+    assert_eq!(cutoff.value(), 1100.0);
+
+    // Messages without numeric arguments leave parameters unchanged.
+    let message = OscMessage::new("/mix/gain", vec![OscArg::Nil]);
+    router.dispatch(&message, &mut ());
+    assert_eq!(gain.value(), 0.25);
+
+    // Messages push events into a sequencer context.
+    let mut sequencer = Sequencer64::new(false, 1);
+    let mut router = OscRouter::<Sequencer64>::new();
+    router.add_event("/note", 1.0, Fade::Smooth, 0.0, 0.0, |message| {
+        message
+            .number(0)
+            .map(|x| Box::new(dc(x)) as Box<dyn AudioUnit64>)
+    });
+    let message = OscMessage::new("/note", vec![OscArg::Float(0.5)]);
+    assert_eq!(router.dispatch(&message, &mut sequencer), 1);
+    let message = OscMessage::new("/note", vec![OscArg::String(String::from("x"))]);
+    router.dispatch(&message, &mut sequencer);
+    assert_eq!(sequencer.get_mono(), 0.5);
+}
@@ -0,0 +1,65 @@
+//! Plugin parameter bridge tests.
+
+#![cfg(feature = "plugin")]
+
+use fundsp::hacker::*;
+
+#[test]
+fn test_plugin() {
+    let graph = saw() >> lowpass_hz(1000.0, 1.0) >> lowpass_hz(2000.0, 1.0);
+    let (sender, mut unit) = PluginUnit64::new(Box::new(graph.clone()));
+
+    // Parameter identifiers are unique.
+    let parameters = unit.plugin_parameters();
+    let ids: Vec<&str> = parameters.iter().map(|x| x.id.as_str()).collect();
+    assert_eq!(ids, ["cutoff", "q", "cutoff 2", "q 2"]);
+    assert_eq!(unit.plugin_parameter_index("cutoff 2"), Some(2));
+    assert_eq!(unit.plugin_parameter_index("gain"), None);
+
+    // Normalized values map back to plain values.
+    let cutoff = &parameters[0];
+    assert!(cutoff.is_logarithmic());
+    assert!((cutoff.denormalize(0.5) - sqrt(20.0 * 20000.0)).abs() < 1.0e-9);
+    for value in [20.0, 440.0, 1000.0, 20000.0] {
+        assert!((cutoff.denormalize(cutoff.normalize(value)) - value).abs() < 1.0e-9);
+    }
+    let q = &parameters[1];
+    assert!(!q.is_logarithmic());
+    assert_eq!(q.normalize(100.0), 1.0);
+    assert_eq!(q.denormalize(0.0), 0.1);
+
+    // Automation is applied at the given sample offset, in blocks of any size.
+    let mut reference = BigBlockAdapter64::new(Box::new(graph));
+    let input = Wave64::render(44100.0, 1000.0 / 44100.0, &mut noise());
+    let mut output = vec![0.0; 1000];
+    unit.automate(Automation::new(100, 0, 500.0));
+    sender
+        .try_send(Automation::new(300, 2, 300.0))
+        .expect("Cannot send automation.");
+    unit.automate(Automation::new(1050, 1, 2.0));
+    unit.process(1000, &[input.channel(0)], &mut [&mut output]);
+    assert_eq!(unit.parameter(0), 500.0);
+    assert_eq!(unit.parameter(2), 300.0);
+    assert_eq!(unit.parameter(1), 1.0);
+    let mut expected = vec![0.0; 1000];
+    for (start, end) in [(0, 100), (100, 300), (300, 1000)] {
+        match start {
+            100 => reference.set_parameter(0, 500.0),
+            300 => reference.set_parameter(2, 300.0),
+            _ => (),
+        }
+        reference.process(
+            end - start,
+            &[&input.channel(0)[start..end]],
+            &mut [&mut expected[start..end]],
+        );
+    }
+    assert_eq!(output, expected);
+
+    // Pending automation carries over to the next block.
+    let mut out = [0.0; 50];
+    unit.process(50, &[&[0.0; 50]], &mut [&mut out]);
+    assert_eq!(unit.parameter(1), 1.0);
+    unit.process(1, &[&[0.0]], &mut [&mut out[..1]]);
+    assert_eq!(unit.parameter(1), 2.0);
+}